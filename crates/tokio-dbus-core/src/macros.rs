@@ -35,6 +35,31 @@ macro_rules! raw_enum {
                 $(#[$($variant_meta)*])*
                 $vis const $variant: Self = Self($value);
             )*
+
+            /// Get the underlying protocol value.
+            #[inline]
+            pub const fn get(self) -> $repr {
+                self.0
+            }
+
+            /// Convert from the underlying protocol value, returning `None` if
+            /// it does not correspond to a known value.
+            pub const fn from_repr(value: $repr) -> Option<Self> {
+                $(
+                    if value == $value {
+                        return Some(Self($value));
+                    }
+                )*
+
+                None
+            }
+        }
+
+        impl ::core::convert::From<$name> for $repr {
+            #[inline]
+            fn from(value: $name) -> Self {
+                value.0
+            }
         }
 
         impl ::core::fmt::Debug for $name {
@@ -85,6 +110,31 @@ macro_rules! raw_set {
                 $(#[$($variant_meta)*])*
                 $vis const $variant: Self = Self($value);
             )*
+
+            /// Get the underlying protocol value.
+            #[inline]
+            pub const fn get(self) -> $repr {
+                self.0
+            }
+
+            /// Convert from the underlying protocol value, returning `None` if
+            /// it contains bits which do not correspond to a known flag.
+            pub const fn from_repr(value: $repr) -> Option<Self> {
+                let known = 0 $(| $value)*;
+
+                if value & !known != 0 {
+                    return None;
+                }
+
+                Some(Self(value))
+            }
+        }
+
+        impl ::core::convert::From<$name> for $repr {
+            #[inline]
+            fn from(value: $name) -> Self {
+                value.0
+            }
         }
 
         impl ::core::fmt::Debug for $name {
@@ -55,7 +55,9 @@ raw_enum! {
         /// implementations to represent the general concept of a struct, and must
         /// not appear in signatures used on D-Bus..
         STRUCT = b'r',
+        /// Opening of a struct in a signature.
         OPEN_PAREN = b'(',
+        /// Closing of a struct in a signature.
         CLOSE_PAREN = b')',
         /// Variant type (the type of the value is part of the value itself).
         VARIANT = b'v',
@@ -64,7 +66,9 @@ raw_enum! {
         /// general concept of a dict or dict-entry, and must not appear in
         /// signatures used on D-Bus..
         DICT_ENTRY = b'e',
+        /// Opening of a dict entry in a signature.
         OPEN_BRACE = b'{',
+        /// Closing of a dict entry in a signature.
         CLOSE_BRACE = b'}',
         /// Unix file descriptor.
         UNIX_FD = b'h',
@@ -81,7 +85,11 @@ raw_enum! {
         /// appear in signatures used on D-Bus. GVariant uses these type-codes to
         /// encode calling conventions.
         RESERVED3 = b'@',
+        /// Reserved for internal use by bindings/implementations, see
+        /// [`Type::RESERVED3`].
         RESERVED4 = b'&',
+        /// Reserved for internal use by bindings/implementations, see
+        /// [`Type::RESERVED3`].
         RESERVED5 = b'^',
     }
 }
@@ -170,6 +178,7 @@ raw_set! {
 }
 
 raw_enum! {
+    /// The code of a header field in a message.
     #[repr(u8)]
     pub enum Variant {
         /// The object to send a call to, or the object a signal is emitted from.
@@ -221,5 +230,14 @@ raw_enum! {
         /// of the message itself is transferred or after the last byte of the
         /// message itself. This header field is controlled by the message sender.
         UNIX_FDS = 9,
+        /// An identifier used to correlate messages which belong to the same
+        /// operation across services, such as for tracing. This is not part of
+        /// the specification, and implementations which don't know about it
+        /// ignore it. Note that the reference implementation of the bus daemon
+        /// removes unknown header fields from the messages it routes, so this
+        /// is only preserved over peer-to-peer connections or by buses which
+        /// forward unknown fields. This header field is controlled by the
+        /// message sender.
+        CORRELATION_ID = 128,
     }
 }
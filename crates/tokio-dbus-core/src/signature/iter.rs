@@ -7,9 +7,14 @@ use super::Signature;
 /// The item yielded by the [`Iter`] iterator.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Type<'a> {
+    /// A single basic type, like `u` or `v`.
     Signature(&'a Signature),
+    /// An array, containing the signature of its element type.
     Array(&'a Signature),
+    /// A struct, containing the signature of its fields without the
+    /// surrounding parenthesis.
     Struct(&'a Signature),
+    /// A dict entry, containing the signatures of its key and value.
     Dict(&'a Signature, &'a Signature),
 }
 
@@ -26,7 +31,7 @@ impl<'a> Iter<'a> {
         }
     }
 
-    fn next_signature(&mut self) -> Option<&'a Signature> {
+    pub(super) fn next_signature(&mut self) -> Option<&'a Signature> {
         let slice = self.iter.as_slice();
         let mut depth = 0usize;
         let mut n = 0;
@@ -14,7 +14,7 @@ use super::{validate, Iter, SignatureBuf, SignatureError};
 ///
 /// assert!(Signature::new(b"aai").is_ok());
 /// ```
-#[derive(Hash, PartialEq, Eq)]
+#[derive(Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(transparent)]
 pub struct Signature([u8]);
 
@@ -105,6 +105,24 @@ impl Signature {
     /// ```
     pub const BYTE: &'static Signature = Signature::new_const(b"y");
 
+    /// Boolean value: 0 is false, 1 is true, any other value allowed by the
+    /// marshalling format is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, Signature, Variant};
+    ///
+    /// let mut body = BodyBuf::new();
+    ///
+    /// body.store(Variant::Bool(true));
+    ///
+    /// let mut body = body.as_body();
+    /// assert_eq!(body.read::<Signature>()?, Signature::BOOLEAN);
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub const BOOLEAN: &'static Signature = Signature::new_const(b"b");
+
     /// Signed (two's complement) 16-bit integer.
     ///
     /// # Examples
@@ -289,6 +307,7 @@ impl Signature {
         self.0.len()
     }
 
+    /// Iterate over the complete types in the signature.
     #[inline]
     pub fn iter(&self) -> Iter<'_> {
         Iter::new(self)
@@ -304,6 +323,64 @@ impl Signature {
     pub fn as_bytes(&self) -> &[u8] {
         &self.0
     }
+
+    /// Test if the signature starts with the complete types in `prefix`.
+    ///
+    /// Since no complete type is a prefix of another complete type, this only
+    /// matches on boundaries between complete types.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::Signature;
+    ///
+    /// let signature = Signature::new("a{sv}as")?;
+    ///
+    /// assert!(signature.starts_with(Signature::new("a{sv}")?));
+    /// assert!(signature.starts_with(signature));
+    /// assert!(signature.starts_with(Signature::EMPTY));
+    /// assert!(!signature.starts_with(Signature::new("as")?));
+    /// assert!(!signature.starts_with(Signature::new("a{sv}ass")?));
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn starts_with(&self, prefix: &Signature) -> bool {
+        self.0.starts_with(&prefix.0)
+    }
+
+    /// Split the signature into its first complete type and the remaining
+    /// signature.
+    ///
+    /// Returns `None` if the signature is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::Signature;
+    ///
+    /// let signature = Signature::new("a{sv}(ii)u")?;
+    ///
+    /// let (first, rest) = signature.split_first_complete_type().unwrap();
+    /// assert_eq!(first, "a{sv}");
+    /// assert_eq!(rest, "(ii)u");
+    ///
+    /// let (first, rest) = rest.split_first_complete_type().unwrap();
+    /// assert_eq!(first, "(ii)");
+    /// assert_eq!(rest, "u");
+    ///
+    /// let (first, rest) = rest.split_first_complete_type().unwrap();
+    /// assert_eq!(first, Signature::UINT32);
+    /// assert!(rest.is_empty());
+    ///
+    /// assert!(rest.split_first_complete_type().is_none());
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn split_first_complete_type(&self) -> Option<(&Signature, &Signature)> {
+        let first = self.iter().next_signature()?;
+        // SAFETY: A valid signature is a sequence of complete types, so what
+        // remains after the first one is also valid.
+        let rest = unsafe { Self::new_unchecked(&self.0[first.len()..]) };
+        Some((first, rest))
+    }
 }
 
 impl fmt::Debug for Signature {
@@ -491,3 +568,10 @@ impl From<&Signature> for Box<Signature> {
         unsafe { Box::from_raw(Box::into_raw(Box::<[u8]>::from(&signature.0)) as *mut Signature) }
     }
 }
+
+impl Clone for Box<Signature> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Box::<Signature>::from(&**self)
+    }
+}
@@ -1,5 +1,7 @@
 use std::borrow::Borrow;
+use std::cmp::Ordering;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::mem::transmute;
 use std::mem::MaybeUninit;
 use std::ops::Deref;
@@ -36,7 +38,7 @@ impl SignatureBuf {
 
     /// Convert an owned signature into its raw parts.
     pub const fn into_raw_parts(self) -> ([MaybeUninit<u8>; MAX_SIGNATURE], usize) {
-        (self.data, 0)
+        (self.data, self.init)
     }
 
     /// Construct a new signature with validation inside of a constant context.
@@ -172,6 +174,63 @@ impl PartialEq<SignatureBuf> for SignatureBuf {
 
 impl Eq for SignatureBuf {}
 
+/// Hashing of [`SignatureBuf`], which is consistent with [`Signature`].
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashSet;
+///
+/// use tokio_dbus::{Signature, SignatureBuf};
+///
+/// let mut set = HashSet::new();
+/// set.insert(SignatureBuf::new(b"s")?);
+///
+/// assert!(set.contains(Signature::STRING));
+/// assert!(!set.contains(Signature::UINT32));
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+impl Hash for SignatureBuf {
+    #[inline]
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        (**self).hash(state);
+    }
+}
+
+/// Ordering of [`SignatureBuf`], which is consistent with [`Signature`].
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeSet;
+///
+/// use tokio_dbus::{Signature, SignatureBuf};
+///
+/// let mut set = BTreeSet::new();
+/// set.insert(SignatureBuf::new(b"u")?);
+/// set.insert(SignatureBuf::new(b"s")?);
+///
+/// assert!(set.contains(Signature::STRING));
+/// assert_eq!(set.first().map(|s| s.as_str()), Some("s"));
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+impl PartialOrd for SignatureBuf {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SignatureBuf {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
 /// Equality check between [`Signature`] and [`SignatureBuf`].
 ///
 /// # Examples
@@ -100,6 +100,34 @@ impl SignatureBuilder {
         Ok(())
     }
 
+    /// Open a dict entry in the signature.
+    ///
+    /// Dict entries count towards the same recursion limit as structs.
+    pub fn open_dict(&mut self) -> Result<(), SignatureError> {
+        if self.structs == MAX_CONTAINER_DEPTH || self.structs + self.arrays == MAX_DEPTH {
+            return Err(SignatureError::new(
+                SignatureErrorKind::ExceededMaximumStructRecursion,
+            ));
+        }
+
+        if !self.push(b'{') {
+            return Err(SignatureError::new(SignatureErrorKind::SignatureTooLong));
+        }
+
+        self.structs += 1;
+        Ok(())
+    }
+
+    /// Close a dict entry in the signature.
+    pub fn close_dict(&mut self) -> Result<(), SignatureError> {
+        if !self.push(b'}') {
+            return Err(SignatureError::new(SignatureErrorKind::SignatureTooLong));
+        }
+
+        self.structs -= 1;
+        Ok(())
+    }
+
     /// Push a single byte onto the signature.
     fn push(&mut self, byte: u8) -> bool {
         if self.init == MAX_SIGNATURE {
@@ -23,6 +23,13 @@ impl SignatureError {
     pub const fn too_long() -> Self {
         Self::new(SignatureErrorKind::SignatureTooLong)
     }
+
+    /// Indicate that a signature was expected to contain a single complete
+    /// type, such as the element of an array.
+    #[inline]
+    pub const fn not_single_complete_type() -> Self {
+        Self::new(SignatureErrorKind::NotSingleCompleteType)
+    }
 }
 
 #[allow(missing_docs)]
@@ -44,6 +51,7 @@ pub(crate) enum SignatureErrorKind {
     ExceededMaximumStructRecursion,
     ExceededMaximumDictRecursion,
     DictEntryHasTooManyFields,
+    NotSingleCompleteType,
 }
 
 impl fmt::Display for SignatureError {
@@ -97,6 +105,9 @@ impl fmt::Display for SignatureError {
             SignatureErrorKind::DictEntryHasTooManyFields => {
                 write!(f, "Dict entry has too many fields")
             }
+            SignatureErrorKind::NotSingleCompleteType => {
+                write!(f, "Expected a single complete type")
+            }
         }
     }
 }
@@ -115,3 +115,15 @@ fn test_iter() -> Result<(), SignatureError> {
     assert_eq!(value, Signature::BYTE);
     Ok(())
 }
+
+#[test]
+fn unterminated_containers() {
+    test!(b"a{", Err(DictStartedButNotEnded));
+    test!(b"a{s", Err(DictStartedButNotEnded));
+    test!(b"a{sv", Err(DictStartedButNotEnded));
+    test!(b"(a{sv}", Err(StructStartedButNotEnded));
+    test!(b"a(i", Err(StructStartedButNotEnded));
+    test!(b"a{s(i", Err(StructStartedButNotEnded));
+    test!(b"(ia", Err(MissingArrayElementType));
+    test!(b"a{sa", Err(MissingArrayElementType));
+}
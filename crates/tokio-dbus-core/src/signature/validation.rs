@@ -143,7 +143,7 @@ pub(super) const fn validate(bytes: &[u8]) -> Result<(), SignatureError> {
         }
     }
 
-    match stack_pop!(stack, (Kind, u8)) {
+    match stack_peek!(stack) {
         Some((Kind::Array, _)) => {
             return Err(SignatureError::new(MissingArrayElementType));
         }
@@ -0,0 +1,70 @@
+/* C interface to tokio-dbus, built from the tokio-dbus-ffi crate. */
+
+#ifndef TOKIO_DBUS_H
+#define TOKIO_DBUS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct TdbusConnection TdbusConnection;
+typedef struct TdbusMessage TdbusMessage;
+
+/* Message types as returned by tdbus_message_type. */
+#define TDBUS_METHOD_CALL 1
+#define TDBUS_METHOD_RETURN 2
+#define TDBUS_ERROR 3
+#define TDBUS_SIGNAL 4
+
+/* Description of the last error raised on the current thread, or NULL. */
+const char *tdbus_last_error(void);
+
+/* Connect to a bus, returning NULL on error. */
+TdbusConnection *tdbus_session_bus(void);
+TdbusConnection *tdbus_system_bus(void);
+void tdbus_connection_free(TdbusConnection *c);
+
+/*
+ * Send a method call whose body is built from `signature` and one string
+ * argument per complete type in it. Returns the serial of the call, or 0 on
+ * error. The call is sent by the next call to tdbus_poll().
+ */
+uint32_t tdbus_method_call(TdbusConnection *c,
+                           const char *destination,
+                           const char *path,
+                           const char *interface,
+                           const char *member,
+                           const char *signature,
+                           const char *const *arguments,
+                           size_t len);
+
+/*
+ * Wait for the next message for at most `timeout_ms` milliseconds, or
+ * indefinitely if negative. Returns 1 if a message was stored in `out`, 0 on
+ * timeout and -1 on error.
+ */
+int tdbus_poll(TdbusConnection *c, int64_t timeout_ms, TdbusMessage **out);
+
+void tdbus_message_free(TdbusMessage *m);
+uint8_t tdbus_message_type(const TdbusMessage *m);
+uint32_t tdbus_message_serial(const TdbusMessage *m);
+uint32_t tdbus_message_reply_serial(const TdbusMessage *m);
+
+/* Strings are owned by the message, and are NULL if the field is missing. */
+const char *tdbus_message_path(const TdbusMessage *m);
+const char *tdbus_message_interface(const TdbusMessage *m);
+const char *tdbus_message_member(const TdbusMessage *m);
+const char *tdbus_message_error_name(const TdbusMessage *m);
+const char *tdbus_message_destination(const TdbusMessage *m);
+const char *tdbus_message_sender(const TdbusMessage *m);
+const char *tdbus_message_signature(const TdbusMessage *m);
+const char *tdbus_message_body(const TdbusMessage *m);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* TOKIO_DBUS_H */
@@ -0,0 +1,222 @@
+use std::ffi::{c_char, c_int, CStr};
+use std::ptr;
+use std::time::Duration;
+
+use tokio::runtime::{Builder, Runtime};
+use tokio_dbus::{Connection, Error, ObjectPath, Result, Signature};
+
+use crate::error::{catch_panic, clear_last_error, set_last_error};
+use crate::TdbusMessage;
+
+/// A connection to a message bus, together with the runtime driving it.
+pub struct TdbusConnection {
+    runtime: Runtime,
+    connection: Connection,
+}
+
+impl TdbusConnection {
+    fn connect<F>(f: fn() -> F) -> Result<Box<Self>>
+    where
+        F: std::future::Future<Output = Result<Connection>>,
+    {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        let connection = runtime.block_on(f())?;
+
+        Ok(Box::new(Self {
+            runtime,
+            connection,
+        }))
+    }
+}
+
+/// Connect to the session bus.
+///
+/// Returns `NULL` on error. The connection must be freed with
+/// [`tdbus_connection_free`].
+#[no_mangle]
+pub extern "C" fn tdbus_session_bus() -> *mut TdbusConnection {
+    catch_panic(ptr::null_mut(), || {
+        into_raw(TdbusConnection::connect(Connection::session_bus))
+    })
+}
+
+/// Connect to the system bus.
+///
+/// Returns `NULL` on error. The connection must be freed with
+/// [`tdbus_connection_free`].
+#[no_mangle]
+pub extern "C" fn tdbus_system_bus() -> *mut TdbusConnection {
+    catch_panic(ptr::null_mut(), || {
+        into_raw(TdbusConnection::connect(Connection::system_bus))
+    })
+}
+
+/// Free a connection.
+///
+/// # Safety
+///
+/// The connection must have been returned by [`tdbus_session_bus`] or
+/// [`tdbus_system_bus`] and not already been freed, or be `NULL`.
+#[no_mangle]
+pub unsafe extern "C" fn tdbus_connection_free(c: *mut TdbusConnection) {
+    catch_panic((), || {
+        if !c.is_null() {
+            drop(Box::from_raw(c));
+        }
+    })
+}
+
+/// Send a method call and return its serial.
+///
+/// The body of the call is built from `signature`, with one string argument
+/// for each complete type in it. `destination` and `interface` may be `NULL`.
+///
+/// The call is buffered, and is sent by the next call to [`tdbus_poll`], which
+/// is also where its reply is received.
+///
+/// Returns `0` on error.
+///
+/// # Safety
+///
+/// `c` must be a valid connection. `path`, `member` and `signature` must be
+/// valid NUL-terminated strings, as must `destination` and `interface` unless
+/// they are `NULL`. `arguments` must point to `len` valid NUL-terminated
+/// strings, or be `NULL` if `len` is `0`.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn tdbus_method_call(
+    c: *mut TdbusConnection,
+    destination: *const c_char,
+    path: *const c_char,
+    interface: *const c_char,
+    member: *const c_char,
+    signature: *const c_char,
+    arguments: *const *const c_char,
+    len: usize,
+) -> u32 {
+    catch_panic(0, || {
+        let c = &mut *c;
+
+        let result = (|| {
+            let path = ObjectPath::new(to_str(path)?)?;
+            let member = to_str(member)?;
+            let signature = Signature::new(to_str(signature)?)?;
+
+            let mut strings = Vec::with_capacity(len);
+
+            for n in 0..len {
+                strings.push(to_str(*arguments.add(n))?);
+            }
+
+            let (_, send, body) = c.connection.buffers();
+            body.clear();
+            body.store_parsed(signature, &strings)?;
+
+            let mut m = send.method_call(path, member).with_body(body);
+
+            if !destination.is_null() {
+                m = m.with_destination(to_str(destination)?);
+            }
+
+            if !interface.is_null() {
+                m = m.with_interface(to_str(interface)?);
+            }
+
+            let serial = m.serial();
+            send.write_message(m)?;
+            Ok::<_, Error>(serial.get())
+        })();
+
+        match result {
+            Ok(serial) => {
+                clear_last_error();
+                serial
+            }
+            Err(error) => {
+                set_last_error(error);
+                0
+            }
+        }
+    })
+}
+
+/// Wait for the next message on the connection.
+///
+/// Waits for at most `timeout_ms` milliseconds, or indefinitely if it is
+/// negative. Returns `1` and stores the message in `out` if one was received,
+/// `0` on timeout and `-1` on error. Received messages must be freed with
+/// [`tdbus_message_free`].
+///
+/// [`tdbus_message_free`]: crate::tdbus_message_free
+///
+/// # Safety
+///
+/// `c` must be a valid connection and `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn tdbus_poll(
+    c: *mut TdbusConnection,
+    timeout_ms: i64,
+    out: *mut *mut TdbusMessage,
+) -> c_int {
+    catch_panic(-1, || {
+        let c = &mut *c;
+        *out = ptr::null_mut();
+
+        let result = c.runtime.block_on(async {
+            let message = if let Ok(timeout) = u64::try_from(timeout_ms) {
+                c.connection
+                    .wait_timeout(Duration::from_millis(timeout))
+                    .await?
+            } else {
+                c.connection.wait().await?;
+                Some(c.connection.last_message()?)
+            };
+
+            match message {
+                Some(message) => Ok::<_, Error>(Some(TdbusMessage::new(message.to_owned()))),
+                None => Ok(None),
+            }
+        });
+
+        match result {
+            Ok(Some(message)) => {
+                clear_last_error();
+                *out = Box::into_raw(Box::new(message));
+                1
+            }
+            Ok(None) => {
+                clear_last_error();
+                0
+            }
+            Err(error) => {
+                set_last_error(error);
+                -1
+            }
+        }
+    })
+}
+
+fn into_raw(result: Result<Box<TdbusConnection>>) -> *mut TdbusConnection {
+    match result {
+        Ok(c) => {
+            clear_last_error();
+            Box::into_raw(c)
+        }
+        Err(error) => {
+            set_last_error(error);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Convert a C string into a string slice.
+unsafe fn to_str<'a>(string: *const c_char) -> Result<&'a str> {
+    if string.is_null() {
+        return Err(Error::from(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "unexpected NULL string",
+        )));
+    }
+
+    CStr::from_ptr(string).to_str().map_err(Error::from)
+}
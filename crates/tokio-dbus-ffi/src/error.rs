@@ -0,0 +1,58 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Get a description of the last error raised on the current thread.
+///
+/// Returns `NULL` if no error has been raised. The returned string is valid
+/// until the next call into this library on the same thread.
+#[no_mangle]
+pub extern "C" fn tdbus_last_error() -> *const c_char {
+    catch_panic(ptr::null(), || {
+        LAST_ERROR.with(|last| match &*last.borrow() {
+            Some(error) => error.as_ptr(),
+            None => ptr::null(),
+        })
+    })
+}
+
+/// Store the error to be returned by [`tdbus_last_error`].
+pub(crate) fn set_last_error(error: impl fmt::Display) {
+    let error = error.to_string().replace('\0', "\\0");
+    let error = CString::new(error).ok();
+    LAST_ERROR.with(|last| *last.borrow_mut() = error);
+}
+
+/// Call `f`, returning `on_panic` and storing the panic as the last error if it
+/// panics, since unwinding across the FFI boundary is undefined behavior.
+pub(crate) fn catch_panic<T>(on_panic: T, f: impl FnOnce() -> T) -> T {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(value) => value,
+        Err(payload) => {
+            set_last_error(format_args!("Panicked: {}", panic_message(&*payload)));
+            on_panic
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
+    }
+}
+
+/// Clear the last error.
+pub(crate) fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
@@ -0,0 +1,40 @@
+//! A C ABI for [tokio-dbus], which allows it to be embedded in applications
+//! which are not written in Rust.
+//!
+//! The library is built as a `cdylib`, and the functions it exposes are
+//! declared in `include/tokio_dbus.h`. Every connection is driven by its own
+//! single-threaded Tokio runtime, so all calls block the calling thread until
+//! they complete.
+//!
+//! Method calls are built from a signature and one string argument for each
+//! complete type in it, using the same syntax as [`BodyBuf::store_parsed`].
+//! Received messages expose their header fields, and a textual rendering of
+//! their body as produced by [`Body::display`].
+//!
+//! Functions which can fail indicate so through their return value, after
+//! which a description of the error is available through
+//! [`tdbus_last_error`].
+//!
+//! [tokio-dbus]: https://docs.rs/tokio-dbus
+//! [`BodyBuf::store_parsed`]: tokio_dbus::BodyBuf::store_parsed
+//! [`Body::display`]: tokio_dbus::Body::display
+
+#[cfg(test)]
+mod tests;
+
+pub use self::error::tdbus_last_error;
+mod error;
+
+pub use self::connection::{
+    tdbus_connection_free, tdbus_method_call, tdbus_poll, tdbus_session_bus, tdbus_system_bus,
+    TdbusConnection,
+};
+mod connection;
+
+pub use self::message::{
+    tdbus_message_body, tdbus_message_destination, tdbus_message_error_name, tdbus_message_free,
+    tdbus_message_interface, tdbus_message_member, tdbus_message_path, tdbus_message_reply_serial,
+    tdbus_message_sender, tdbus_message_serial, tdbus_message_signature, tdbus_message_type,
+    TdbusMessage,
+};
+mod message;
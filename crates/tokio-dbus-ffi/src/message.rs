@@ -0,0 +1,157 @@
+use std::ffi::{c_char, CString};
+use std::ptr;
+
+use tokio_dbus::MessageBuf;
+
+use crate::error::catch_panic;
+
+/// A message received over a connection.
+///
+/// Header fields and the rendered body are converted into C strings up front,
+/// so that pointers to them remain valid for as long as the message is alive.
+pub struct TdbusMessage {
+    message: MessageBuf,
+    path: Option<CString>,
+    interface: Option<CString>,
+    member: Option<CString>,
+    error_name: Option<CString>,
+    destination: Option<CString>,
+    sender: Option<CString>,
+    signature: CString,
+    body: CString,
+}
+
+impl TdbusMessage {
+    pub(crate) fn new(message: MessageBuf) -> Self {
+        let body = message.body();
+        let path = message.path().map(|path| path.to_string());
+
+        Self {
+            path: c_string(path.as_deref()),
+            interface: c_string(message.interface()),
+            member: c_string(message.member()),
+            error_name: c_string(message.error_name()),
+            destination: c_string(message.destination()),
+            sender: c_string(message.sender()),
+            signature: c_string(Some(body.signature().as_str())).unwrap_or_default(),
+            body: c_string(Some(&body.display().to_string())).unwrap_or_default(),
+            message,
+        }
+    }
+}
+
+/// Free a message.
+///
+/// # Safety
+///
+/// The message must have been returned by [`tdbus_poll`] and not already been
+/// freed, or be `NULL`.
+///
+/// [`tdbus_poll`]: crate::tdbus_poll
+#[no_mangle]
+pub unsafe extern "C" fn tdbus_message_free(m: *mut TdbusMessage) {
+    catch_panic((), || {
+        if !m.is_null() {
+            drop(Box::from_raw(m));
+        }
+    })
+}
+
+/// Get the type of the message, as encoded in the D-Bus protocol.
+///
+/// # Safety
+///
+/// `m` must be a valid message.
+#[no_mangle]
+pub unsafe extern "C" fn tdbus_message_type(m: *const TdbusMessage) -> u8 {
+    catch_panic(0, || u8::from((*m).message.message_type()))
+}
+
+/// Get the serial of the message.
+///
+/// # Safety
+///
+/// `m` must be a valid message.
+#[no_mangle]
+pub unsafe extern "C" fn tdbus_message_serial(m: *const TdbusMessage) -> u32 {
+    catch_panic(0, || (*m).message.serial().get())
+}
+
+/// Get the serial of the message this message is a reply to, or `0` if it
+/// isn't a reply.
+///
+/// # Safety
+///
+/// `m` must be a valid message.
+#[no_mangle]
+pub unsafe extern "C" fn tdbus_message_reply_serial(m: *const TdbusMessage) -> u32 {
+    catch_panic(0, || {
+        (*m).message.reply_serial().map_or(0, |serial| serial.get())
+    })
+}
+
+macro_rules! string_accessors {
+    ($($(#[doc = $doc:literal])* $name:ident => $field:ident;)*) => {
+        $(
+            $(#[doc = $doc])*
+            ///
+            /// Returns `NULL` if the message doesn't have the field. The string
+            /// is valid for as long as the message is.
+            ///
+            /// # Safety
+            ///
+            /// `m` must be a valid message.
+            #[no_mangle]
+            pub unsafe extern "C" fn $name(m: *const TdbusMessage) -> *const c_char {
+                catch_panic(ptr::null(), || as_ptr((*m).$field.as_ref()))
+            }
+        )*
+    };
+}
+
+string_accessors! {
+    /// Get the object path of the message.
+    tdbus_message_path => path;
+    /// Get the interface of the message.
+    tdbus_message_interface => interface;
+    /// Get the member of the message.
+    tdbus_message_member => member;
+    /// Get the error name of the message.
+    tdbus_message_error_name => error_name;
+    /// Get the destination of the message.
+    tdbus_message_destination => destination;
+    /// Get the sender of the message.
+    tdbus_message_sender => sender;
+}
+
+/// Get the signature of the body of the message.
+///
+/// The string is valid for as long as the message is.
+///
+/// # Safety
+///
+/// `m` must be a valid message.
+#[no_mangle]
+pub unsafe extern "C" fn tdbus_message_signature(m: *const TdbusMessage) -> *const c_char {
+    catch_panic(ptr::null(), || (*m).signature.as_ptr())
+}
+
+/// Get a textual rendering of the body of the message.
+///
+/// The string is valid for as long as the message is.
+///
+/// # Safety
+///
+/// `m` must be a valid message.
+#[no_mangle]
+pub unsafe extern "C" fn tdbus_message_body(m: *const TdbusMessage) -> *const c_char {
+    catch_panic(ptr::null(), || (*m).body.as_ptr())
+}
+
+fn c_string(string: Option<&str>) -> Option<CString> {
+    CString::new(string?.replace('\0', "\\0")).ok()
+}
+
+fn as_ptr(string: Option<&CString>) -> *const c_char {
+    string.map_or(ptr::null(), |string| string.as_ptr())
+}
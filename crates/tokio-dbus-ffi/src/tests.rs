@@ -0,0 +1,49 @@
+use std::borrow::Cow;
+use std::ffi::CStr;
+use std::num::NonZeroU32;
+
+use tokio_dbus::{BodyBuf, MessageBuf, ObjectPath};
+
+use crate::error::catch_panic;
+use crate::*;
+
+const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Example");
+
+#[test]
+fn message_accessors() -> tokio_dbus::Result<()> {
+    let mut body = BodyBuf::new();
+    body.store(42u32)?;
+
+    let message = MessageBuf::signal(Cow::Borrowed("Changed"), NonZeroU32::MIN)
+        .with_path(Cow::Borrowed(PATH))
+        .with_interface(Cow::Borrowed("se.tedro.Example"))
+        .with_body(body);
+
+    let m = Box::into_raw(Box::new(TdbusMessage::new(message)));
+
+    unsafe {
+        let string = |ptr| CStr::from_ptr(ptr).to_str().unwrap();
+
+        assert_eq!(tdbus_message_type(m), 4);
+        assert_eq!(tdbus_message_serial(m), 1);
+        assert_eq!(tdbus_message_reply_serial(m), 0);
+        assert_eq!(string(tdbus_message_path(m)), "/se/tedro/Example");
+        assert_eq!(string(tdbus_message_interface(m)), "se.tedro.Example");
+        assert_eq!(string(tdbus_message_member(m)), "Changed");
+        assert!(tdbus_message_sender(m).is_null());
+        assert_eq!(string(tdbus_message_signature(m)), "u");
+        assert_eq!(string(tdbus_message_body(m)), "uint32 42");
+        tdbus_message_free(m);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn panics_set_last_error() {
+    let value = catch_panic(-1, || panic!("boom"));
+    assert_eq!(value, -1);
+
+    let error = unsafe { CStr::from_ptr(tdbus_last_error()) };
+    assert_eq!(error.to_str().unwrap(), "Panicked: boom");
+}
@@ -0,0 +1,115 @@
+//! [<img alt="github" src="https://img.shields.io/badge/github-udoprog/tokio--dbus-8da0cb?style=for-the-badge&logo=github" height="20">](https://github.com/udoprog/tokio-dbus)
+//! [<img alt="crates.io" src="https://img.shields.io/crates/v/tokio-dbus-macros.svg?style=for-the-badge&color=fc8d62&logo=rust" height="20">](https://crates.io/crates/tokio-dbus-macros)
+//! [<img alt="docs.rs" src="https://img.shields.io/badge/docs.rs-tokio--dbus--macros-66c2a5?style=for-the-badge&logoColor=white&logo=data:image/svg+xml;base64,PHN2ZyByb2xlPSJpbWciIHhtbG5zPSJodHRwOi8vd3d3LnczLm9yZy8yMDAwL3N2ZyIgdmlld0JveD0iMCAwIDUxMiA1MTIiPjxwYXRoIGZpbGw9IiNmNWY1ZjUiIGQ9Ik00ODguNiAyNTAuMkwzOTIgMjE0VjEwNS41YzAtMTUtOS4zLTI4LjQtMjMuNC0zMy43bC0xMDAtMzcuNWMtOC4xLTMuMS0xNy4xLTMuMS0yNS4zIDBsLTEwMCAzNy41Yy0xNC4xIDUuMy0yMy40IDE4LjctMjMuNCAzMy43VjIxNGwtOTYuNiAzNi4yQzkuMyAyNTUuNSAwIDI2OC45IDAgMjgzLjlWMzk0YzAgMTMuNiA3LjcgMjYuMSAxOS45IDMyLjJsMTAwIDUwYzEwLjEgNS4xIDIyLjEgNS4xIDMyLjIgMGwxMDMuOS01MiAxMDMuOSA1MmMxMC4xIDUuMSAyMi4xIDUuMSAzMi4yIDBsMTAwLTUwYzEyLjItNi4xIDE5LjktMTguNiAxOS45LTMyLjJWMjgzLjljMC0xNS05LjMtMjguNC0yMy40LTMzLjd6TTM1OCAyMTQuOGwtODUgMzEuOXYtNjguMmw4NS0zN3Y3My4zek0xNTQgMTA0LjFsMTAyLTM4LjIgMTAyIDM4LjJ2LjZsLTEwMiA0MS40LTEwMi00MS40di0uNnptODQgMjkxLjFsLTg1IDQyLjV2LTc5LjFsODUtMzguOHY3NS40em0wLTExMmwtMTAyIDQxLjQtMTAyLTQxLjR2LS42bDEwMi0zOC4yIDEwMiAzOC4ydi42em0yNDAgMTEybC04NSA0Mi41di03OS4xbDg1LTM4Ljh2NzUuNHptMC0xMTJsLTEwMiA0MS40LTEwMi00MS40di0uNmwxMDItMzguMiAxMDIgMzguMnYuNnoiPjwvcGF0aD48L3N2Zz4K" height="20">](https://docs.rs/tokio-dbus-macros)
+//!
+//! Procedural macros for [`tokio-dbus`].
+//!
+//! Prefer using the re-exports in [`tokio-dbus`] over depending on this crate
+//! directly.
+//!
+//! [`tokio-dbus`]: https://docs.rs/tokio-dbus
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Fields, GenericParam, Lifetime, LifetimeParam};
+
+/// Derive `FromBody` for a struct, decoding each field in order.
+///
+/// This also implements `TryFrom<Body<'de>>` for the struct.
+#[proc_macro_derive(FromBody)]
+pub fn from_body(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+
+    match expand_from_body(input) {
+        Ok(output) => output.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand_from_body(input: DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        Data::Enum(data) => {
+            return Err(syn::Error::new(
+                data.enum_token.span(),
+                "FromBody cannot be derived for enums",
+            ));
+        }
+        Data::Union(data) => {
+            return Err(syn::Error::new(
+                data.union_token.span(),
+                "FromBody cannot be derived for unions",
+            ));
+        }
+    };
+
+    let ident = &input.ident;
+
+    let types = fields.iter().map(|field| &field.ty).collect::<Vec<_>>();
+
+    // Use the first lifetime of the struct as the lifetime of the body,
+    // otherwise introduce a new one.
+    let mut generics = input.generics.clone();
+
+    let lt = match input.generics.lifetimes().next() {
+        Some(param) => param.lifetime.clone(),
+        None => {
+            let lt = Lifetime::new("'__de", Span::call_site());
+            generics
+                .params
+                .insert(0, GenericParam::Lifetime(LifetimeParam::new(lt.clone())));
+            lt
+        }
+    };
+
+    let construct = match fields {
+        Fields::Named(..) => {
+            let names = fields.iter().map(|field| &field.ident);
+            quote!(Self { #(#names: <#types as ::tokio_dbus::FromBody<#lt>>::from_body(body)?,)* })
+        }
+        Fields::Unnamed(..) => {
+            quote!(Self(#(<#types as ::tokio_dbus::FromBody<#lt>>::from_body(body)?,)*))
+        }
+        Fields::Unit => quote!(Self),
+    };
+
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let (_, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let where_clause = {
+        let predicates = where_clause.map(|w| &w.predicates).into_iter();
+        quote!(where #(#predicates,)* #(#types: ::tokio_dbus::FromBody<#lt>,)*)
+    };
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::tokio_dbus::FromBody<#lt> for #ident #type_generics #where_clause {
+            #[inline]
+            fn write_signature(
+                signature: &mut ::tokio_dbus::__private::SignatureBuilder,
+            ) -> ::core::result::Result<(), ::tokio_dbus::SignatureError> {
+                #(<#types as ::tokio_dbus::FromBody<#lt>>::write_signature(signature)?;)*
+                ::core::result::Result::Ok(())
+            }
+
+            #[inline]
+            fn from_body(
+                body: &mut ::tokio_dbus::Body<#lt>,
+            ) -> ::tokio_dbus::Result<Self> {
+                let _ = &body;
+                ::core::result::Result::Ok(#construct)
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::core::convert::TryFrom<::tokio_dbus::Body<#lt>> for #ident #type_generics #where_clause {
+            type Error = ::tokio_dbus::Error;
+
+            #[inline]
+            fn try_from(body: ::tokio_dbus::Body<#lt>) -> ::tokio_dbus::Result<Self> {
+                body.decode()
+            }
+        }
+    })
+}
@@ -3,6 +3,9 @@ use tokio_dbus_core::signature::Signature;
 /// A D-Bus node.
 #[derive(Debug, Clone)]
 pub struct Node<'a> {
+    /// The name of the node, which for child nodes is their path relative to
+    /// the parent node.
+    pub name: Option<&'a str>,
     /// Interfaces in the node.
     pub interfaces: Box<[Interface<'a>]>,
     /// Sub-nodes in the node.
@@ -16,6 +19,10 @@ pub struct Interface<'a> {
     pub name: &'a str,
     /// Methods associated with the interface.
     pub methods: Box<[Method<'a>]>,
+    /// Signals associated with the interface.
+    pub signals: Box<[Signal<'a>]>,
+    /// Properties associated with the interface.
+    pub properties: Box<[Property<'a>]>,
 }
 
 /// The direction of an argument.
@@ -38,6 +45,37 @@ pub struct Argument<'a> {
     pub direction: Direction,
 }
 
+impl Argument<'_> {
+    /// Get a suggested Rust type expression for the type of the argument.
+    ///
+    /// See [`rust_type`] for details.
+    ///
+    /// [`rust_type`]: crate::rust_type
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus_xml::parse_interface;
+    ///
+    /// let node = parse_interface(r#"
+    /// <node>
+    ///   <interface name="se.tedro.Example">
+    ///     <method name="Notify">
+    ///       <arg name="hints" direction="in" type="a{sv}"/>
+    ///     </method>
+    ///   </interface>
+    /// </node>
+    /// "#)?;
+    ///
+    /// let argument = &node.interfaces[0].methods[0].arguments[0];
+    /// assert_eq!(argument.rust_type(), "ty::Dict<ty::Str, ty::Variant>");
+    /// # Ok::<_, tokio_dbus_xml::Error>(())
+    /// ```
+    pub fn rust_type(&self) -> String {
+        crate::rust_type(self.ty)
+    }
+}
+
 /// A single interface.
 #[derive(Debug, Clone)]
 pub struct Method<'a> {
@@ -47,6 +85,69 @@ pub struct Method<'a> {
     pub arguments: Box<[Argument<'a>]>,
 }
 
+/// A signal.
+#[derive(Debug, Clone)]
+pub struct Signal<'a> {
+    /// The name of the signal.
+    pub name: &'a str,
+    /// Arguments of the signal.
+    pub arguments: Box<[Argument<'a>]>,
+}
+
+/// How a property can be accessed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyAccess {
+    /// The property can only be read.
+    Read,
+    /// The property can only be written.
+    Write,
+    /// The property can be both read and written.
+    ReadWrite,
+}
+
+impl PropertyAccess {
+    /// Test if the property can be read.
+    pub fn is_readable(self) -> bool {
+        matches!(self, PropertyAccess::Read | PropertyAccess::ReadWrite)
+    }
+
+    /// Test if the property can be written.
+    pub fn is_writable(self) -> bool {
+        matches!(self, PropertyAccess::Write | PropertyAccess::ReadWrite)
+    }
+}
+
+/// How changes to a property are emitted through the
+/// `org.freedesktop.DBus.Properties.PropertiesChanged` signal.
+///
+/// This is determined by the `org.freedesktop.DBus.Property.EmitsChangedSignal`
+/// annotation on either the property or its interface.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EmitsChangedSignal {
+    /// The signal is emitted with the new value of the property.
+    #[default]
+    True,
+    /// The signal is emitted, but the value of the property is not included.
+    Invalidates,
+    /// The property never changes, so the signal is never emitted.
+    Const,
+    /// The signal is not emitted when the property changes.
+    False,
+}
+
+/// A property of an interface.
+#[derive(Debug, Clone, Copy)]
+pub struct Property<'a> {
+    /// The name of the property.
+    pub name: &'a str,
+    /// The type of the property.
+    pub ty: &'a Signature,
+    /// How the property can be accessed.
+    pub access: PropertyAccess,
+    /// How changes to the property are emitted.
+    pub emits_changed_signal: EmitsChangedSignal,
+}
+
 /// Documentation associated with an element.
 #[derive(Debug, Default)]
 pub struct Doc<'a> {
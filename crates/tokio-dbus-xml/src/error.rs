@@ -56,10 +56,17 @@ pub(crate) enum ErrorKind {
         actual: Box<str>,
     },
     MissingMethodName,
+    MissingSignalName,
     MissingInterfaceName,
     MissingArgumentType,
     UnsupportedArgumentDirection(Box<str>),
-    MissingArgumentDirection,
+    MissingPropertyName,
+    MissingPropertyType,
+    MissingPropertyAccess,
+    UnsupportedPropertyAccess(Box<str>),
+    MissingAnnotationName,
+    MissingAnnotationValue,
+    UnsupportedEmitsChangedSignal(Box<str>),
 }
 
 impl From<xmlparser::Error> for ErrorKind {
@@ -99,6 +106,9 @@ impl fmt::Display for ErrorKind {
             ErrorKind::MissingMethodName => {
                 write!(f, "Missing method name")
             }
+            ErrorKind::MissingSignalName => {
+                write!(f, "Missing signal name")
+            }
             ErrorKind::MissingInterfaceName => {
                 write!(f, "Missing interface name")
             }
@@ -108,8 +118,29 @@ impl fmt::Display for ErrorKind {
             ErrorKind::UnsupportedArgumentDirection(value) => {
                 write!(f, "Unsupported argument direction `{value}`")
             }
-            ErrorKind::MissingArgumentDirection => {
-                write!(f, "Missing argument direction")
+            ErrorKind::MissingPropertyName => {
+                write!(f, "Missing property name")
+            }
+            ErrorKind::MissingPropertyType => {
+                write!(f, "Missing property type")
+            }
+            ErrorKind::MissingPropertyAccess => {
+                write!(f, "Missing property access")
+            }
+            ErrorKind::UnsupportedPropertyAccess(value) => {
+                write!(f, "Unsupported property access `{value}`")
+            }
+            ErrorKind::MissingAnnotationName => {
+                write!(f, "Missing annotation name")
+            }
+            ErrorKind::MissingAnnotationValue => {
+                write!(f, "Missing annotation value")
+            }
+            ErrorKind::UnsupportedEmitsChangedSignal(value) => {
+                write!(
+                    f,
+                    "Unsupported value `{value}` for EmitsChangedSignal annotation"
+                )
             }
         }
     }
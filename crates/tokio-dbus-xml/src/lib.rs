@@ -9,8 +9,14 @@ mod tests;
 pub use self::error::{Error, Result};
 mod error;
 
-pub use self::elements::{Argument, Description, Direction, Doc, Interface, Method, Node};
+pub use self::elements::{
+    Argument, Description, Direction, Doc, EmitsChangedSignal, Interface, Method, Node, Property,
+    PropertyAccess, Signal,
+};
 mod elements;
 
 pub use self::parser::parse_interface;
 mod parser;
+
+pub use self::rust_type::rust_type;
+mod rust_type;
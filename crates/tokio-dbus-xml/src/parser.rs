@@ -4,7 +4,13 @@ use tokio_dbus_core::signature::Signature;
 use xmlparser::{ElementEnd, Token};
 
 use crate::error::ErrorKind;
-use crate::{Argument, Description, Direction, Doc, Error, Interface, Method, Node, Result};
+use crate::{
+    Argument, Description, Direction, Doc, EmitsChangedSignal, Error, Interface, Method, Node,
+    Property, PropertyAccess, Result, Signal,
+};
+
+/// The annotation which determines how property changes are emitted.
+const EMITS_CHANGED_SIGNAL: &str = "org.freedesktop.DBus.Property.EmitsChangedSignal";
 
 /// Parse the contents of an interface file.
 pub fn parse_interface(interface: &str) -> Result<Node<'_>> {
@@ -39,7 +45,7 @@ pub fn parse_interface(interface: &str) -> Result<Node<'_>> {
         match token {
             Token::ElementStart { local, .. } => {
                 match (stack.last(), local.as_str()) {
-                    (None, "node") => {
+                    (None | Some(State::Node(..)), "node") => {
                         stack.push(State::Node(NodeBuilder::default()));
                     }
                     (Some(State::Node(..)), "interface") => {
@@ -49,7 +55,30 @@ pub fn parse_interface(interface: &str) -> Result<Node<'_>> {
                         stack.push(State::Method(MethodBuilder::default()));
                     }
                     (Some(State::Method(..)), "arg") => {
-                        stack.push(State::Argument(ArgumentBuilder::default()));
+                        // NB: The direction of method arguments defaults to
+                        // `in`.
+                        stack.push(State::Argument(ArgumentBuilder::new(Direction::In)));
+                    }
+                    (Some(State::Interface(..)), "signal") => {
+                        stack.push(State::Signal(SignalBuilder::default()));
+                    }
+                    (Some(State::Signal(..)), "arg") => {
+                        stack.push(State::Argument(ArgumentBuilder::new(Direction::Out)));
+                    }
+                    (Some(State::Interface(..)), "property") => {
+                        stack.push(State::Property(PropertyBuilder::default()));
+                    }
+                    (
+                        Some(
+                            State::Interface(..)
+                            | State::Method(..)
+                            | State::Signal(..)
+                            | State::Argument(..)
+                            | State::Property(..),
+                        ),
+                        "annotation",
+                    ) => {
+                        stack.push(State::Annotation(AnnotationBuilder::default()));
                     }
                     (Some(State::Argument(..) | State::Method(..)), "doc") => {
                         stack.push(State::Doc(Doc::default()));
@@ -87,9 +116,18 @@ pub fn parse_interface(interface: &str) -> Result<Node<'_>> {
                     [.., State::Interface(interface), State::Method(..)] => {
                         let _ = write!(path, "[{}]", interface.methods.len());
                     }
+                    [.., State::Interface(interface), State::Signal(..)] => {
+                        let _ = write!(path, "[{}]", interface.signals.len());
+                    }
                     [.., State::Method(method), State::Argument(..)] => {
                         let _ = write!(path, "[{}]", method.arguments.len());
                     }
+                    [.., State::Signal(signal), State::Argument(..)] => {
+                        let _ = write!(path, "[{}]", signal.arguments.len());
+                    }
+                    [.., State::Interface(interface), State::Property(..)] => {
+                        let _ = write!(path, "[{}]", interface.properties.len());
+                    }
                     _ => {}
                 }
             }
@@ -109,6 +147,7 @@ pub fn parse_interface(interface: &str) -> Result<Node<'_>> {
                 match (&mut stack[..], top) {
                     ([], State::Node(node)) => {
                         expect_end!(name, "node");
+                        root.name = node.name;
                         root.interfaces.extend(node.interfaces);
                         root.nodes.extend(node.nodes);
                     }
@@ -121,7 +160,7 @@ pub fn parse_interface(interface: &str) -> Result<Node<'_>> {
                         );
                     }
                     ([.., State::Node(node)], State::Node(builder)) => {
-                        expect_end!(name, "interface");
+                        expect_end!(name, "node");
                         node.nodes.push(builder.build());
                     }
                     ([.., State::Interface(interface)], State::Method(builder)) => {
@@ -132,6 +171,22 @@ pub fn parse_interface(interface: &str) -> Result<Node<'_>> {
                                 .map_err(|kind| Error::new(path.as_str(), kind))?,
                         );
                     }
+                    ([.., State::Interface(interface)], State::Signal(builder)) => {
+                        expect_end!(name, "signal");
+                        interface.signals.push(
+                            builder
+                                .build()
+                                .map_err(|kind| Error::new(path.as_str(), kind))?,
+                        );
+                    }
+                    ([.., State::Signal(signal)], State::Argument(builder)) => {
+                        expect_end!(name, "arg");
+                        signal.arguments.push(
+                            builder
+                                .build()
+                                .map_err(|kind| Error::new(path.as_str(), kind))?,
+                        );
+                    }
                     ([.., State::Method(method)], State::Argument(builder)) => {
                         expect_end!(name, "arg");
                         method.arguments.push(
@@ -140,6 +195,36 @@ pub fn parse_interface(interface: &str) -> Result<Node<'_>> {
                                 .map_err(|kind| Error::new(path.as_str(), kind))?,
                         );
                     }
+                    ([.., State::Interface(interface)], State::Property(builder)) => {
+                        expect_end!(name, "property");
+                        interface.properties.push(
+                            builder
+                                .build()
+                                .map_err(|kind| Error::new(path.as_str(), kind))?,
+                        );
+                    }
+                    ([.., parent], State::Annotation(builder)) => {
+                        expect_end!(name, "annotation");
+
+                        let (key, value) = builder
+                            .build()
+                            .map_err(|kind| Error::new(path.as_str(), kind))?;
+
+                        if key == EMITS_CHANGED_SIGNAL {
+                            let emits = parse_emits_changed_signal(value)
+                                .map_err(|kind| Error::new(path.as_str(), kind))?;
+
+                            match parent {
+                                State::Interface(interface) => {
+                                    interface.emits_changed_signal = Some(emits);
+                                }
+                                State::Property(property) => {
+                                    property.emits_changed_signal = Some(emits);
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
                     ([.., State::Argument(argument)], State::Doc(doc)) => {
                         expect_end!(name, "doc");
                         argument.doc = doc;
@@ -185,17 +270,23 @@ pub fn parse_interface(interface: &str) -> Result<Node<'_>> {
                         // validate, in practice they don't make much of a
                         // difference and are rarely used.
                     }
+                    ([.., State::Node(builder)], _, "name") => {
+                        builder.name = Some(value.as_str());
+                    }
                     ([.., State::Interface(builder)], _, "name") => {
                         builder.name = Some(value.as_str());
                     }
                     ([.., State::Method(builder)], _, "name") => {
                         builder.name = Some(value.as_str());
                     }
+                    ([.., State::Signal(builder)], _, "name") => {
+                        builder.name = Some(value.as_str());
+                    }
                     ([.., State::Argument(builder)], _, "name") => {
                         builder.name = Some(value.as_str());
                     }
                     ([.., State::Argument(builder)], _, "direction") => {
-                        builder.direction = Some(match value.as_str() {
+                        builder.direction = match value.as_str() {
                             "in" => Direction::In,
                             "out" => Direction::Out,
                             other => {
@@ -204,8 +295,36 @@ pub fn parse_interface(interface: &str) -> Result<Node<'_>> {
                                     ErrorKind::UnsupportedArgumentDirection(other.into()),
                                 ))
                             }
+                        };
+                    }
+                    ([.., State::Property(builder)], _, "name") => {
+                        builder.name = Some(value.as_str());
+                    }
+                    ([.., State::Property(builder)], _, "type") => {
+                        builder.ty = Some(
+                            Signature::new(value.as_str())
+                                .map_err(|kind| Error::new(path.as_str(), kind))?,
+                        );
+                    }
+                    ([.., State::Property(builder)], _, "access") => {
+                        builder.access = Some(match value.as_str() {
+                            "read" => PropertyAccess::Read,
+                            "write" => PropertyAccess::Write,
+                            "readwrite" => PropertyAccess::ReadWrite,
+                            other => {
+                                return Err(Error::new(
+                                    path,
+                                    ErrorKind::UnsupportedPropertyAccess(other.into()),
+                                ))
+                            }
                         });
                     }
+                    ([.., State::Annotation(builder)], _, "name") => {
+                        builder.name = Some(value.as_str());
+                    }
+                    ([.., State::Annotation(builder)], _, "value") => {
+                        builder.value = Some(value.as_str());
+                    }
                     ([.., State::Argument(builder)], _, "type") => {
                         builder.ty = Some(
                             Signature::new(value.as_str())
@@ -241,6 +360,7 @@ pub fn parse_interface(interface: &str) -> Result<Node<'_>> {
 
 #[derive(Debug, Default)]
 struct NodeBuilder<'a> {
+    name: Option<&'a str>,
     interfaces: Vec<Interface<'a>>,
     nodes: Vec<Node<'a>>,
 }
@@ -248,6 +368,7 @@ struct NodeBuilder<'a> {
 impl<'a> NodeBuilder<'a> {
     fn build(self) -> Node<'a> {
         Node {
+            name: self.name,
             interfaces: self.interfaces.into(),
             nodes: self.nodes.into(),
         }
@@ -258,18 +379,92 @@ impl<'a> NodeBuilder<'a> {
 struct InterfaceBuilder<'a> {
     name: Option<&'a str>,
     methods: Vec<Method<'a>>,
+    signals: Vec<Signal<'a>>,
+    properties: Vec<PropertyBuilder<'a>>,
+    emits_changed_signal: Option<EmitsChangedSignal>,
 }
 
 impl<'a> InterfaceBuilder<'a> {
     fn build(self) -> Result<Interface<'a>, ErrorKind> {
         let name = self.name.ok_or(ErrorKind::MissingInterfaceName)?;
+
+        let properties = self
+            .properties
+            .into_iter()
+            .map(|builder| builder.build_with_default(self.emits_changed_signal))
+            .collect::<Result<Vec<_>, _>>()?;
+
         Ok(Interface {
             name,
             methods: self.methods.into(),
+            signals: self.signals.into(),
+            properties: properties.into(),
         })
     }
 }
 
+#[derive(Debug, Default)]
+struct PropertyBuilder<'a> {
+    name: Option<&'a str>,
+    ty: Option<&'a Signature>,
+    access: Option<PropertyAccess>,
+    emits_changed_signal: Option<EmitsChangedSignal>,
+}
+
+impl<'a> PropertyBuilder<'a> {
+    /// Validate the property.
+    ///
+    /// Properties are only fully built with the interface they belong to,
+    /// since the interface might carry a default for how changes are emitted.
+    fn build(self) -> Result<Self, ErrorKind> {
+        self.build_with_default(None)?;
+        Ok(self)
+    }
+
+    fn build_with_default(
+        &self,
+        emits_changed_signal: Option<EmitsChangedSignal>,
+    ) -> Result<Property<'a>, ErrorKind> {
+        let name = self.name.ok_or(ErrorKind::MissingPropertyName)?;
+        let ty = self.ty.ok_or(ErrorKind::MissingPropertyType)?;
+        let access = self.access.ok_or(ErrorKind::MissingPropertyAccess)?;
+
+        Ok(Property {
+            name,
+            ty,
+            access,
+            emits_changed_signal: self
+                .emits_changed_signal
+                .or(emits_changed_signal)
+                .unwrap_or_default(),
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct AnnotationBuilder<'a> {
+    name: Option<&'a str>,
+    value: Option<&'a str>,
+}
+
+impl<'a> AnnotationBuilder<'a> {
+    fn build(self) -> Result<(&'a str, &'a str), ErrorKind> {
+        let name = self.name.ok_or(ErrorKind::MissingAnnotationName)?;
+        let value = self.value.ok_or(ErrorKind::MissingAnnotationValue)?;
+        Ok((name, value))
+    }
+}
+
+fn parse_emits_changed_signal(value: &str) -> Result<EmitsChangedSignal, ErrorKind> {
+    Ok(match value {
+        "true" => EmitsChangedSignal::True,
+        "invalidates" => EmitsChangedSignal::Invalidates,
+        "const" => EmitsChangedSignal::Const,
+        "false" => EmitsChangedSignal::False,
+        other => return Err(ErrorKind::UnsupportedEmitsChangedSignal(other.into())),
+    })
+}
+
 #[derive(Debug, Default)]
 struct MethodBuilder<'a> {
     name: Option<&'a str>,
@@ -288,22 +483,46 @@ impl<'a> MethodBuilder<'a> {
 }
 
 #[derive(Debug, Default)]
+struct SignalBuilder<'a> {
+    name: Option<&'a str>,
+    arguments: Vec<Argument<'a>>,
+}
+
+impl<'a> SignalBuilder<'a> {
+    fn build(self) -> Result<Signal<'a>, ErrorKind> {
+        let name = self.name.ok_or(ErrorKind::MissingSignalName)?;
+        Ok(Signal {
+            name,
+            arguments: self.arguments.into(),
+        })
+    }
+}
+
+#[derive(Debug)]
 struct ArgumentBuilder<'a> {
     name: Option<&'a str>,
     ty: Option<&'a Signature>,
-    direction: Option<Direction>,
+    direction: Direction,
     doc: Doc<'a>,
 }
 
 impl<'a> ArgumentBuilder<'a> {
+    fn new(direction: Direction) -> Self {
+        Self {
+            name: None,
+            ty: None,
+            direction,
+            doc: Doc::default(),
+        }
+    }
+
     fn build(self) -> Result<Argument<'a>, ErrorKind> {
         let ty = self.ty.ok_or(ErrorKind::MissingArgumentType)?;
-        let direction = self.direction.ok_or(ErrorKind::MissingArgumentDirection)?;
 
         Ok(Argument {
             name: self.name,
             ty,
-            direction,
+            direction: self.direction,
         })
     }
 }
@@ -318,7 +537,10 @@ enum State<'a> {
     Node(NodeBuilder<'a>),
     Interface(InterfaceBuilder<'a>),
     Method(MethodBuilder<'a>),
+    Signal(SignalBuilder<'a>),
     Argument(ArgumentBuilder<'a>),
+    Property(PropertyBuilder<'a>),
+    Annotation(AnnotationBuilder<'a>),
     Doc(Doc<'a>),
     Description(Description<'a>),
     String(&'static str, StringBuilder<'a>),
@@ -0,0 +1,104 @@
+use tokio_dbus_core::signature::{Signature, Type};
+
+/// Convert a signature into a suggested Rust type expression, using the
+/// markers in the `tokio_dbus::ty` module where necessary. Unix file
+/// descriptors are written as `UnixFd`, which is its own marker.
+///
+/// A signature with multiple complete types is converted into a tuple, and an
+/// empty signature is converted into `()`.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus_core::signature::Signature;
+/// use tokio_dbus_xml::rust_type;
+///
+/// assert_eq!(rust_type(Signature::new("u")?), "u32");
+/// assert_eq!(rust_type(Signature::new("as")?), "ty::Array<ty::Str>");
+/// assert_eq!(rust_type(Signature::new("a{sv}")?), "ty::Dict<ty::Str, ty::Variant>");
+/// assert_eq!(rust_type(Signature::new("(yo)")?), "(u8, ty::ObjectPath)");
+/// assert_eq!(rust_type(Signature::new("us")?), "(u32, ty::Str)");
+/// assert_eq!(rust_type(Signature::new("")?), "()");
+/// # Ok::<_, tokio_dbus_core::signature::SignatureError>(())
+/// ```
+pub fn rust_type(signature: &Signature) -> String {
+    let mut out = String::new();
+    write_all(&mut out, signature, false);
+    out
+}
+
+/// Write all types in `signature`, as a tuple unless it consists of a single
+/// complete type. Structs set `is_struct` so that single-field structs are
+/// written as a one-element tuple.
+fn write_all(out: &mut String, signature: &Signature, is_struct: bool) {
+    let count = signature.iter().count();
+
+    if count == 1 && !is_struct {
+        if let Some(ty) = signature.iter().next() {
+            write_type(out, ty);
+        }
+
+        return;
+    }
+
+    out.push('(');
+
+    for (index, ty) in signature.iter().enumerate() {
+        if index > 0 {
+            out.push_str(", ");
+        }
+
+        write_type(out, ty);
+    }
+
+    if count == 1 {
+        out.push(',');
+    }
+
+    out.push(')');
+}
+
+fn write_type(out: &mut String, ty: Type<'_>) {
+    match ty {
+        Type::Signature(signature) => {
+            out.push_str(match signature.as_bytes() {
+                b"y" => "u8",
+                b"b" => "ty::Bool",
+                b"n" => "i16",
+                b"q" => "u16",
+                b"i" => "i32",
+                b"u" => "u32",
+                b"x" => "i64",
+                b"t" => "u64",
+                b"d" => "f64",
+                b"h" => "UnixFd",
+                b"s" => "ty::Str",
+                b"o" => "ty::ObjectPath",
+                b"g" => "ty::Signature",
+                b"v" => "ty::Variant",
+                _ => signature.as_str(),
+            });
+        }
+        Type::Array(element) => {
+            // NB: Arrays of dict entries are written as dictionaries.
+            if let Some(Type::Dict(key, value)) = element.iter().next() {
+                write_type(out, Type::Dict(key, value));
+                return;
+            }
+
+            out.push_str("ty::Array<");
+            write_all(out, element, false);
+            out.push('>');
+        }
+        Type::Struct(fields) => {
+            write_all(out, fields, true);
+        }
+        Type::Dict(key, value) => {
+            out.push_str("ty::Dict<");
+            write_all(out, key, false);
+            out.push_str(", ");
+            write_all(out, value, false);
+            out.push('>');
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use crate::{parse_interface, Result};
+use tokio_dbus_core::signature::Signature;
+
+use crate::{parse_interface, rust_type, Direction, EmitsChangedSignal, PropertyAccess, Result};
 
 const SIMPLE: &str = r#"
 <!DOCTYPE node PUBLIC
@@ -39,3 +41,131 @@ fn test_simple() -> Result<()> {
     assert_eq!(node.interfaces[0].methods[0].name, "AddContact");
     Ok(())
 }
+
+const PROPERTIES: &str = r#"
+<node>
+  <interface name="se.tedro.Example">
+    <annotation name="org.freedesktop.DBus.Property.EmitsChangedSignal" value="invalidates"/>
+    <property name="Name" type="s" access="read"/>
+    <property name="Volume" type="d" access="readwrite">
+      <annotation name="org.freedesktop.DBus.Property.EmitsChangedSignal" value="true"/>
+    </property>
+    <property name="Id" type="t" access="read">
+      <annotation name="org.freedesktop.DBus.Property.EmitsChangedSignal" value="const"/>
+    </property>
+    <method name="Reset">
+      <annotation name="org.freedesktop.DBus.Deprecated" value="true"/>
+    </method>
+  </interface>
+</node>
+"#;
+
+#[test]
+fn test_properties() -> Result<()> {
+    let node = parse_interface(PROPERTIES)?;
+    let properties = &node.interfaces[0].properties;
+
+    assert_eq!(properties.len(), 3);
+
+    assert_eq!(properties[0].name, "Name");
+    assert_eq!(properties[0].ty, "s");
+    assert_eq!(properties[0].access, PropertyAccess::Read);
+    assert_eq!(
+        properties[0].emits_changed_signal,
+        EmitsChangedSignal::Invalidates
+    );
+
+    assert_eq!(properties[1].name, "Volume");
+    assert!(properties[1].access.is_writable());
+    assert_eq!(properties[1].emits_changed_signal, EmitsChangedSignal::True);
+
+    assert_eq!(
+        properties[2].emits_changed_signal,
+        EmitsChangedSignal::Const
+    );
+
+    let error = parse_interface(
+        r#"<node><interface name="a"><property name="A" type="s" access="sometimes"/></interface></node>"#,
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "node/interface[0]/property[0]:access: Unsupported property access `sometimes`"
+    );
+
+    Ok(())
+}
+
+const INTROSPECTED: &str = r#"
+<!DOCTYPE node PUBLIC "-//freedesktop//DTD D-BUS Object Introspection 1.0//EN"
+"http://www.freedesktop.org/standards/dbus/1.0/introspect.dtd">
+<node name="/org/freedesktop/DBus">
+  <interface name="org.freedesktop.DBus">
+    <method name="GetNameOwner">
+      <arg type="s"/>
+      <arg direction="out" type="s"/>
+    </method>
+    <signal name="NameOwnerChanged">
+      <arg type="s"/>
+      <arg type="s"/>
+      <arg type="s"/>
+    </signal>
+  </interface>
+  <node name="Local"/>
+  <node name="Example">
+    <interface name="se.tedro.Example"/>
+  </node>
+</node>
+"#;
+
+#[test]
+fn test_introspected() -> Result<()> {
+    let node = parse_interface(INTROSPECTED)?;
+    assert_eq!(node.name, Some("/org/freedesktop/DBus"));
+
+    let interface = &node.interfaces[0];
+
+    let arguments = &interface.methods[0].arguments;
+    assert!(matches!(arguments[0].direction, Direction::In));
+    assert!(matches!(arguments[1].direction, Direction::Out));
+
+    let signal = &interface.signals[0];
+    assert_eq!(signal.name, "NameOwnerChanged");
+    assert_eq!(signal.arguments.len(), 3);
+    assert!(matches!(signal.arguments[0].direction, Direction::Out));
+
+    let names = node.nodes.iter().map(|n| n.name).collect::<Vec<_>>();
+    assert_eq!(names, [Some("Local"), Some("Example")]);
+    assert_eq!(node.nodes[1].interfaces[0].name, "se.tedro.Example");
+    Ok(())
+}
+
+#[test]
+fn test_rust_type() -> Result<(), tokio_dbus_core::signature::SignatureError> {
+    let cases = [
+        ("y", "u8"),
+        ("b", "ty::Bool"),
+        ("h", "UnixFd"),
+        ("a{sb}", "ty::Dict<ty::Str, ty::Bool>"),
+        ("ao", "ty::Array<ty::ObjectPath>"),
+        ("aay", "ty::Array<ty::Array<u8>>"),
+        ("a(ii)", "ty::Array<(i32, i32)>"),
+        ("(s)", "(ty::Str,)"),
+        (
+            "a{oa{sa{sv}}}",
+            "ty::Dict<ty::ObjectPath, ty::Dict<ty::Str, ty::Dict<ty::Str, ty::Variant>>>",
+        ),
+        ("sav", "(ty::Str, ty::Array<ty::Variant>)"),
+    ];
+
+    for (signature, expected) in cases {
+        assert_eq!(
+            rust_type(Signature::new(signature)?),
+            expected,
+            "{signature}"
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+//! Benchmarks for constructing and cloning owned object paths and
+//! signatures, which are stored inline when they are short enough.
+//!
+//! Run with `cargo bench -p tokio-dbus --bench inline_storage`.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use tokio_dbus::{ObjectPath, Signature};
+
+const ITERATIONS: u32 = 1_000_000;
+
+/// Run `f` repeatedly and report the average time of each iteration.
+fn bench<T>(name: &str, mut f: impl FnMut() -> T) {
+    for _ in 0..ITERATIONS / 10 {
+        black_box(f());
+    }
+
+    let start = Instant::now();
+
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+
+    let elapsed = start.elapsed();
+    let per_iter = elapsed.as_nanos() as f64 / f64::from(ITERATIONS);
+    println!("{name:<32} {per_iter:>8.2} ns/iter ({elapsed:?} total)");
+}
+
+fn main() -> tokio_dbus::Result<()> {
+    // Fits in the inline representation.
+    let short = ObjectPath::new("/org/freedesktop/DBus")?;
+    // Too long to be stored inline.
+    let long = ObjectPath::new("/org/freedesktop/NetworkManager/Devices/0")?;
+    let signature = Signature::new("a{sv}")?;
+
+    bench("object_path_to_owned_inline", || {
+        black_box(short).to_owned()
+    });
+    bench("object_path_to_owned_heap", || black_box(long).to_owned());
+
+    let short = short.to_owned();
+    let long = long.to_owned();
+
+    bench("object_path_clone_inline", || black_box(&short).clone());
+    bench("object_path_clone_heap", || black_box(&long).clone());
+
+    bench("signature_to_owned", || black_box(signature).to_owned());
+
+    let signature = signature.to_owned();
+    bench("signature_clone", || black_box(&signature).clone());
+    Ok(())
+}
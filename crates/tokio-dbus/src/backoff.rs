@@ -0,0 +1,181 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// An exponential backoff policy.
+///
+/// This is used by retrying helpers such as
+/// [`Connection::request_name_with_retry`], but can also be used directly to
+/// drive custom retry loops through [`Backoff::delays`].
+///
+/// [`Connection::request_name_with_retry`]: crate::Connection::request_name_with_retry
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use tokio_dbus::Backoff;
+///
+/// let backoff = Backoff::new(Duration::from_millis(100))
+///     .with_multiplier(2.0)
+///     .with_max_delay(Duration::from_millis(500))
+///     .with_max_attempts(5);
+///
+/// let delays = backoff.delays().collect::<Vec<_>>();
+///
+/// assert_eq!(delays, [
+///     Duration::from_millis(100),
+///     Duration::from_millis(200),
+///     Duration::from_millis(400),
+///     Duration::from_millis(500),
+/// ]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Backoff {
+    initial_delay: Duration,
+    multiplier: f64,
+    max_delay: Duration,
+    jitter: f64,
+    max_attempts: Option<u32>,
+}
+
+impl Backoff {
+    /// Construct a new backoff policy starting at `initial_delay`.
+    ///
+    /// By default the delay doubles for every attempt up to a maximum of 30
+    /// seconds, no jitter is applied and the number of attempts is unbounded.
+    pub const fn new(initial_delay: Duration) -> Self {
+        Self {
+            initial_delay,
+            multiplier: 2.0,
+            max_delay: Duration::from_secs(30),
+            jitter: 0.0,
+            max_attempts: None,
+        }
+    }
+
+    /// Set the factor by which the delay is multiplied for each attempt.
+    ///
+    /// Values smaller than `1.0` are treated as `1.0`.
+    #[must_use]
+    pub fn with_multiplier(self, multiplier: f64) -> Self {
+        Self {
+            multiplier: multiplier.max(1.0),
+            ..self
+        }
+    }
+
+    /// Set the maximum delay between two attempts.
+    #[must_use]
+    pub fn with_max_delay(self, max_delay: Duration) -> Self {
+        Self { max_delay, ..self }
+    }
+
+    /// Set the jitter to apply to each delay as a fraction between `0.0` and
+    /// `1.0`.
+    ///
+    /// A jitter of `0.25` means that each delay is randomly reduced by up to
+    /// 25%, which avoids many clients retrying in lockstep.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use tokio_dbus::Backoff;
+    ///
+    /// let backoff = Backoff::new(Duration::from_secs(1))
+    ///     .with_multiplier(1.0)
+    ///     .with_jitter(0.25)
+    ///     .with_max_attempts(100);
+    ///
+    /// for delay in backoff.delays() {
+    ///     assert!(delay > Duration::from_millis(750) && delay <= Duration::from_secs(1));
+    /// }
+    /// ```
+    #[must_use]
+    pub fn with_jitter(self, jitter: f64) -> Self {
+        Self {
+            jitter: jitter.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    /// Set the maximum number of attempts, including the first one.
+    #[must_use]
+    pub fn with_max_attempts(self, max_attempts: u32) -> Self {
+        Self {
+            max_attempts: Some(max_attempts),
+            ..self
+        }
+    }
+
+    /// Get the delays to wait before each retry.
+    ///
+    /// Since the first attempt is performed without delay, this yields one
+    /// delay less than the maximum number of attempts.
+    pub fn delays(&self) -> Delays {
+        Delays {
+            backoff: *self,
+            next: self.initial_delay,
+            attempt: 1,
+            state: RandomState::new().build_hasher().finish() | 1,
+        }
+    }
+}
+
+impl Default for Backoff {
+    /// A backoff starting at 100 milliseconds.
+    #[inline]
+    fn default() -> Self {
+        Self::new(Duration::from_millis(100))
+    }
+}
+
+/// Iterator over the delays of a [`Backoff`] policy.
+///
+/// See [`Backoff::delays`].
+#[derive(Debug, Clone)]
+pub struct Delays {
+    backoff: Backoff,
+    next: Duration,
+    attempt: u32,
+    state: u64,
+}
+
+impl Delays {
+    /// Get a random number in the range `0.0..1.0`.
+    fn random(&mut self) -> f64 {
+        // NB: xorshift64, which is plenty for jitter.
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl Iterator for Delays {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(max_attempts) = self.backoff.max_attempts {
+            if self.attempt >= max_attempts {
+                return None;
+            }
+        }
+
+        self.attempt = self.attempt.saturating_add(1);
+
+        let delay = self.next.min(self.backoff.max_delay);
+        self.next = Duration::try_from_secs_f64(delay.as_secs_f64() * self.backoff.multiplier)
+            .unwrap_or(self.backoff.max_delay);
+
+        if self.backoff.jitter > 0.0 {
+            let factor = 1.0 - self.backoff.jitter * self.random();
+            return Some(delay.mul_f64(factor));
+        }
+
+        Some(delay)
+    }
+}
@@ -12,9 +12,11 @@ mod sealed {
 /// Trait for types which can be cheaply coerced into a [`Body`].
 ///
 /// This is used in combination with [`Message::with_body`] to allow for
-/// convenient construction of a borrowed body.
+/// convenient construction of a borrowed body. See [`Message::with_arguments`]
+/// to use a tuple of arguments as the body.
 ///
 /// [`Message::with_body`]: crate::Message::with_body
+/// [`Message::with_arguments`]: crate::Message::with_arguments
 ///
 /// # Examples
 ///
@@ -0,0 +1,148 @@
+use std::fmt;
+
+use tokio_dbus_core::signature::Type as SignatureType;
+
+use crate::error::Result;
+use crate::{Body, ObjectPath, Signature, Visitor};
+
+/// Decode the values of a body if its signature only consists of basic types.
+///
+/// Returns `None` if the signature contains containers or variants, or if the
+/// body can't be decoded.
+pub(super) fn basic_values<'de>(body: &Body<'de>) -> Option<Vec<BasicValue<'de>>> {
+    let signature = body.remaining_signature();
+
+    let is_basic = signature
+        .iter()
+        .all(|ty| matches!(ty, SignatureType::Signature(s) if s != Signature::VARIANT));
+
+    if !is_basic {
+        return None;
+    }
+
+    let mut values = Values(Vec::new());
+    body.clone().walk(signature, &mut values).ok()?;
+    Some(values.0)
+}
+
+/// A decoded basic value.
+pub(super) enum BasicValue<'de> {
+    U8(u8),
+    Bool(bool),
+    I16(i16),
+    U16(u16),
+    I32(i32),
+    U32(u32),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Str(&'de str),
+    ObjectPath(&'de ObjectPath),
+    Signature(&'de Signature),
+    UnixFd(u32),
+}
+
+impl fmt::Debug for BasicValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BasicValue::U8(value) => value.fmt(f),
+            BasicValue::Bool(value) => value.fmt(f),
+            BasicValue::I16(value) => value.fmt(f),
+            BasicValue::U16(value) => value.fmt(f),
+            BasicValue::I32(value) => value.fmt(f),
+            BasicValue::U32(value) => value.fmt(f),
+            BasicValue::I64(value) => value.fmt(f),
+            BasicValue::U64(value) => value.fmt(f),
+            BasicValue::F64(value) => value.fmt(f),
+            BasicValue::Str(value) => value.fmt(f),
+            BasicValue::ObjectPath(value) => write!(f, "ObjectPath({:?})", value.as_str()),
+            BasicValue::Signature(value) => write!(f, "Signature({:?})", value.as_str()),
+            BasicValue::UnixFd(index) => write!(f, "UnixFd({index})"),
+        }
+    }
+}
+
+/// Raw data formatted as a hex string.
+pub(super) struct Hex<'a>(pub(super) &'a [u8]);
+
+impl fmt::Debug for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\"")?;
+
+        for b in self.0 {
+            write!(f, "{b:02x}")?;
+        }
+
+        f.write_str("\"")
+    }
+}
+
+/// Visitor which collects basic values.
+struct Values<'de>(Vec<BasicValue<'de>>);
+
+impl<'de> Visitor<'de> for Values<'de> {
+    fn visit_u8(&mut self, value: u8) -> Result<()> {
+        self.0.push(BasicValue::U8(value));
+        Ok(())
+    }
+
+    fn visit_bool(&mut self, value: bool) -> Result<()> {
+        self.0.push(BasicValue::Bool(value));
+        Ok(())
+    }
+
+    fn visit_i16(&mut self, value: i16) -> Result<()> {
+        self.0.push(BasicValue::I16(value));
+        Ok(())
+    }
+
+    fn visit_u16(&mut self, value: u16) -> Result<()> {
+        self.0.push(BasicValue::U16(value));
+        Ok(())
+    }
+
+    fn visit_i32(&mut self, value: i32) -> Result<()> {
+        self.0.push(BasicValue::I32(value));
+        Ok(())
+    }
+
+    fn visit_u32(&mut self, value: u32) -> Result<()> {
+        self.0.push(BasicValue::U32(value));
+        Ok(())
+    }
+
+    fn visit_i64(&mut self, value: i64) -> Result<()> {
+        self.0.push(BasicValue::I64(value));
+        Ok(())
+    }
+
+    fn visit_u64(&mut self, value: u64) -> Result<()> {
+        self.0.push(BasicValue::U64(value));
+        Ok(())
+    }
+
+    fn visit_f64(&mut self, value: f64) -> Result<()> {
+        self.0.push(BasicValue::F64(value));
+        Ok(())
+    }
+
+    fn visit_str(&mut self, value: &'de str) -> Result<()> {
+        self.0.push(BasicValue::Str(value));
+        Ok(())
+    }
+
+    fn visit_object_path(&mut self, value: &'de ObjectPath) -> Result<()> {
+        self.0.push(BasicValue::ObjectPath(value));
+        Ok(())
+    }
+
+    fn visit_signature(&mut self, value: &'de Signature) -> Result<()> {
+        self.0.push(BasicValue::Signature(value));
+        Ok(())
+    }
+
+    fn visit_unix_fd(&mut self, index: u32) -> Result<()> {
+        self.0.push(BasicValue::UnixFd(index));
+        Ok(())
+    }
+}
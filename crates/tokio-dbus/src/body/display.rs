@@ -0,0 +1,181 @@
+use std::fmt::{self, Write};
+
+use crate::error::Result;
+use crate::{Body, ObjectPath, Signature, Visitor};
+
+/// Helper for printing a [`Body`] as a human-readable tree.
+///
+/// See [`Body::display`].
+pub struct DisplayBody<'a> {
+    body: Body<'a>,
+}
+
+impl<'a> DisplayBody<'a> {
+    #[inline]
+    pub(crate) fn new(body: Body<'a>) -> Self {
+        Self { body }
+    }
+}
+
+impl fmt::Display for DisplayBody<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut body = self.body.clone();
+        let mut printer = Printer::default();
+
+        if let Err(error) = body.walk(self.body.remaining_signature(), &mut printer) {
+            printer.line(format_args!("<invalid body: {error}>"));
+        }
+
+        f.write_str(&printer.out)
+    }
+}
+
+impl fmt::Debug for DisplayBody<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Visitor which prints values similarly to `dbus-monitor`.
+#[derive(Default)]
+struct Printer {
+    out: String,
+    depth: usize,
+    /// Set if the next value should continue on the current line, such as the
+    /// value of a variant.
+    inline: bool,
+}
+
+impl Printer {
+    fn line(&mut self, args: fmt::Arguments<'_>) {
+        if self.inline {
+            self.inline = false;
+            self.out.push(' ');
+        } else {
+            if !self.out.is_empty() {
+                self.out.push('\n');
+            }
+
+            for _ in 0..self.depth {
+                self.out.push_str("  ");
+            }
+        }
+
+        // NB: Writing to a string is infallible.
+        _ = self.out.write_fmt(args);
+    }
+
+    fn enter(&mut self, args: fmt::Arguments<'_>) {
+        self.line(args);
+        self.depth += 1;
+    }
+
+    fn leave(&mut self, close: char) {
+        self.depth -= 1;
+        self.line(format_args!("{close}"));
+    }
+}
+
+impl<'de> Visitor<'de> for Printer {
+    fn visit_u8(&mut self, value: u8) -> Result<()> {
+        self.line(format_args!("byte {value}"));
+        Ok(())
+    }
+
+    fn visit_bool(&mut self, value: bool) -> Result<()> {
+        self.line(format_args!("boolean {value}"));
+        Ok(())
+    }
+
+    fn visit_i16(&mut self, value: i16) -> Result<()> {
+        self.line(format_args!("int16 {value}"));
+        Ok(())
+    }
+
+    fn visit_u16(&mut self, value: u16) -> Result<()> {
+        self.line(format_args!("uint16 {value}"));
+        Ok(())
+    }
+
+    fn visit_i32(&mut self, value: i32) -> Result<()> {
+        self.line(format_args!("int32 {value}"));
+        Ok(())
+    }
+
+    fn visit_u32(&mut self, value: u32) -> Result<()> {
+        self.line(format_args!("uint32 {value}"));
+        Ok(())
+    }
+
+    fn visit_i64(&mut self, value: i64) -> Result<()> {
+        self.line(format_args!("int64 {value}"));
+        Ok(())
+    }
+
+    fn visit_u64(&mut self, value: u64) -> Result<()> {
+        self.line(format_args!("uint64 {value}"));
+        Ok(())
+    }
+
+    fn visit_f64(&mut self, value: f64) -> Result<()> {
+        self.line(format_args!("double {value}"));
+        Ok(())
+    }
+
+    fn visit_str(&mut self, value: &'de str) -> Result<()> {
+        self.line(format_args!("string {value:?}"));
+        Ok(())
+    }
+
+    fn visit_object_path(&mut self, value: &'de ObjectPath) -> Result<()> {
+        self.line(format_args!("object path \"{value}\""));
+        Ok(())
+    }
+
+    fn visit_signature(&mut self, value: &'de Signature) -> Result<()> {
+        self.line(format_args!("signature \"{}\"", value.as_str()));
+        Ok(())
+    }
+
+    fn visit_unix_fd(&mut self, index: u32) -> Result<()> {
+        self.line(format_args!("file descriptor {index}"));
+        Ok(())
+    }
+
+    fn enter_array(&mut self, _: &Signature) -> Result<()> {
+        self.enter(format_args!("array ["));
+        Ok(())
+    }
+
+    fn leave_array(&mut self) -> Result<()> {
+        self.leave(']');
+        Ok(())
+    }
+
+    fn enter_struct(&mut self, _: &Signature) -> Result<()> {
+        self.enter(format_args!("struct {{"));
+        Ok(())
+    }
+
+    fn leave_struct(&mut self) -> Result<()> {
+        self.leave('}');
+        Ok(())
+    }
+
+    fn enter_dict_entry(&mut self, _: &Signature, _: &Signature) -> Result<()> {
+        self.enter(format_args!("dict entry("));
+        Ok(())
+    }
+
+    fn leave_dict_entry(&mut self) -> Result<()> {
+        self.leave(')');
+        Ok(())
+    }
+
+    fn enter_variant(&mut self, _: &'de Signature) -> Result<()> {
+        self.line(format_args!("variant"));
+        self.inline = true;
+        Ok(())
+    }
+}
@@ -0,0 +1,313 @@
+use std::fmt::{self, Write};
+use std::ops::Range;
+
+use tokio_dbus_core::signature::Type as SignatureType;
+
+use crate::buf::MAX_ARRAY_LENGTH;
+use crate::error::{ErrorKind, Result};
+use crate::proto::Type;
+use crate::{Body, Error, Frame, ObjectPath, Signature};
+
+use crate::limits::enter;
+
+/// The number of bytes displayed on each line of a dump.
+const BYTES_PER_LINE: usize = 8;
+
+/// Helper for printing a [`Body`] as an annotated hex dump.
+///
+/// See [`Body::dump`].
+pub struct DumpBody<'a> {
+    body: Body<'a>,
+}
+
+impl<'a> DumpBody<'a> {
+    #[inline]
+    pub(crate) fn new(body: Body<'a>) -> Self {
+        Self { body }
+    }
+}
+
+impl fmt::Display for DumpBody<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut body = self.body.clone();
+
+        let mut dumper = Dumper {
+            data: self.body.get(),
+            out: String::new(),
+            depth: 0,
+        };
+
+        match dumper.dump(&mut body, self.body.remaining_signature(), 0) {
+            Ok(()) => {
+                if !body.is_empty() {
+                    let at = dumper.pos(&body);
+                    dumper.row(at..at + body.len(), format_args!("<trailing data>"));
+                }
+            }
+            Err(error) => {
+                let at = dumper.pos(&body);
+                dumper.row(at..at, format_args!("<invalid body: {error}>"));
+            }
+        }
+
+        f.write_str(&dumper.out)
+    }
+}
+
+impl fmt::Debug for DumpBody<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+struct Dumper<'a> {
+    /// The data of the dumped body, which offsets are relative to.
+    data: &'a [u8],
+    out: String,
+    depth: usize,
+}
+
+impl<'a> Dumper<'a> {
+    /// Get the offset of the next byte to be read from `body`.
+    fn pos(&self, body: &Body<'_>) -> usize {
+        body.get().as_ptr() as usize - self.data.as_ptr() as usize
+    }
+
+    /// Write a row covering the given range of bytes.
+    fn row(&mut self, range: Range<usize>, label: fmt::Arguments<'_>) {
+        let data = self.data;
+        let bytes = data.get(range.clone()).unwrap_or_default();
+        let mut chunks = bytes.chunks(BYTES_PER_LINE);
+        let mut offset = range.start;
+
+        let first = chunks.next().unwrap_or_default();
+        self.line(offset, first);
+
+        for _ in 0..self.depth {
+            self.out.push_str("  ");
+        }
+
+        // NB: Writing to a string is infallible.
+        _ = self.out.write_fmt(label);
+
+        for chunk in chunks {
+            offset += BYTES_PER_LINE;
+            self.line(offset, chunk);
+        }
+    }
+
+    fn line(&mut self, offset: usize, bytes: &[u8]) {
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+
+        _ = write!(self.out, "{offset:04x} ");
+
+        for n in 0..BYTES_PER_LINE {
+            match bytes.get(n) {
+                Some(b) => _ = write!(self.out, " {b:02x}"),
+                None => self.out.push_str("   "),
+            }
+        }
+
+        self.out.push_str("  ");
+    }
+
+    fn align<T>(&mut self, body: &mut Body<'_>) -> Result<()> {
+        let start = self.pos(body);
+        body.align::<T>()?;
+        let end = self.pos(body);
+
+        if start != end {
+            self.row(start..end, format_args!("<padding>"));
+        }
+
+        Ok(())
+    }
+
+    fn load<T>(&mut self, body: &mut Body<'_>) -> Result<(Range<usize>, T)>
+    where
+        T: Frame,
+    {
+        self.align::<T>(body)?;
+        let start = self.pos(body);
+        let value = body.load::<T>()?;
+        Ok((start..self.pos(body), value))
+    }
+
+    fn dump(&mut self, body: &mut Body<'_>, signature: &Signature, depth: usize) -> Result<()> {
+        for ty in signature.iter() {
+            self.dump_type(body, ty, depth)?;
+        }
+
+        Ok(())
+    }
+
+    fn dump_type(
+        &mut self,
+        body: &mut Body<'_>,
+        ty: SignatureType<'_>,
+        depth: usize,
+    ) -> Result<()> {
+        match ty {
+            SignatureType::Signature(signature) => {
+                let Some(&b) = signature.as_bytes().first() else {
+                    return Ok(());
+                };
+
+                self.dump_basic(body, Type::new(b), depth)?;
+            }
+            SignatureType::Array(element) => {
+                let depth = enter(depth)?;
+                let (range, bytes) = self.load::<u32>(body)?;
+
+                if bytes > MAX_ARRAY_LENGTH {
+                    return Err(Error::new(ErrorKind::ArrayTooLong(bytes)));
+                }
+
+                self.row(
+                    range,
+                    format_args!("array a{} ({bytes} bytes) [", element.as_str()),
+                );
+
+                // NB: Padding up until the first element is not included in
+                // the length of the array.
+                let start = self.pos(body);
+                body.align_for(element)?;
+                let end = self.pos(body);
+
+                if start != end {
+                    self.row(start..end, format_args!("<padding>"));
+                }
+
+                if bytes as usize > body.len() {
+                    return Err(Error::new(ErrorKind::BufferUnderflow));
+                }
+
+                let mut array = body.read_until(bytes as usize);
+
+                self.depth += 1;
+
+                while !array.is_empty() {
+                    self.dump(&mut array, element, depth)?;
+                }
+
+                self.depth -= 1;
+                let at = self.pos(body);
+                self.row(at..at, format_args!("]"));
+            }
+            SignatureType::Struct(fields) => {
+                let depth = enter(depth)?;
+                self.align::<u64>(body)?;
+                let at = self.pos(body);
+                self.row(at..at, format_args!("struct ({}) {{", fields.as_str()));
+                self.depth += 1;
+                self.dump(body, fields, depth)?;
+                self.depth -= 1;
+                let at = self.pos(body);
+                self.row(at..at, format_args!("}}"));
+            }
+            SignatureType::Dict(key, value) => {
+                let depth = enter(depth)?;
+                self.align::<u64>(body)?;
+                let at = self.pos(body);
+                self.row(
+                    at..at,
+                    format_args!("dict entry {{{}{}}} (", key.as_str(), value.as_str()),
+                );
+                self.depth += 1;
+                self.dump(body, key, depth)?;
+                self.dump(body, value, depth)?;
+                self.depth -= 1;
+                let at = self.pos(body);
+                self.row(at..at, format_args!(")"));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn dump_basic(&mut self, body: &mut Body<'_>, ty: Type, depth: usize) -> Result<()> {
+        match ty {
+            Type::BYTE => {
+                let (range, value) = self.load::<u8>(body)?;
+                self.row(range, format_args!("byte {value}"));
+            }
+            Type::BOOLEAN => {
+                let (range, value) = self.load::<u32>(body)?;
+                self.row(range, format_args!("boolean {value}"));
+            }
+            Type::INT16 => {
+                let (range, value) = self.load::<i16>(body)?;
+                self.row(range, format_args!("int16 {value}"));
+            }
+            Type::UINT16 => {
+                let (range, value) = self.load::<u16>(body)?;
+                self.row(range, format_args!("uint16 {value}"));
+            }
+            Type::INT32 => {
+                let (range, value) = self.load::<i32>(body)?;
+                self.row(range, format_args!("int32 {value}"));
+            }
+            Type::UINT32 => {
+                let (range, value) = self.load::<u32>(body)?;
+                self.row(range, format_args!("uint32 {value}"));
+            }
+            Type::INT64 => {
+                let (range, value) = self.load::<i64>(body)?;
+                self.row(range, format_args!("int64 {value}"));
+            }
+            Type::UINT64 => {
+                let (range, value) = self.load::<u64>(body)?;
+                self.row(range, format_args!("uint64 {value}"));
+            }
+            Type::DOUBLE => {
+                let (range, value) = self.load::<f64>(body)?;
+                self.row(range, format_args!("double {value}"));
+            }
+            Type::STRING => {
+                self.align::<u32>(body)?;
+                let start = self.pos(body);
+                let value = body.read::<str>()?;
+                self.row(start..self.pos(body), format_args!("string {value:?}"));
+            }
+            Type::OBJECT_PATH => {
+                self.align::<u32>(body)?;
+                let start = self.pos(body);
+                let value = body.read::<ObjectPath>()?;
+                self.row(
+                    start..self.pos(body),
+                    format_args!("object path \"{value}\""),
+                );
+            }
+            Type::SIGNATURE => {
+                let start = self.pos(body);
+                let value = body.read::<Signature>()?;
+                self.row(
+                    start..self.pos(body),
+                    format_args!("signature \"{}\"", value.as_str()),
+                );
+            }
+            Type::UNIX_FD => {
+                let (range, value) = self.load::<u32>(body)?;
+                self.row(range, format_args!("unix fd {value}"));
+            }
+            Type::VARIANT => {
+                let depth = enter(depth)?;
+                let start = self.pos(body);
+                let signature = body.read::<Signature>()?;
+                self.row(
+                    start..self.pos(body),
+                    format_args!("variant \"{}\"", signature.as_str()),
+                );
+                self.depth += 1;
+                self.dump(body, signature, depth)?;
+                self.depth -= 1;
+            }
+            _ => return Err(Error::new(ErrorKind::UnsupportedType(ty))),
+        }
+
+        Ok(())
+    }
+}
@@ -15,7 +15,10 @@ pub struct LoadArray<'de, T> {
     _marker: PhantomData<T>,
 }
 
-impl<'de, T> LoadArray<'de, T> {
+impl<'de, T> LoadArray<'de, T>
+where
+    T: ty::Aligned,
+{
     #[inline]
     pub(crate) fn from_mut(buf: &mut Body<'de>) -> Result<LoadArray<'de, T>> {
         let bytes = buf.load::<u32>()?;
@@ -24,10 +27,20 @@ impl<'de, T> LoadArray<'de, T> {
             return Err(Error::new(ErrorKind::ArrayTooLong(bytes)));
         }
 
+        // NB: Padding up until the first element is not included in the
+        // length of the array.
+        buf.align::<T::Alignment>()?;
+
+        if bytes as usize > buf.len() {
+            return Err(Error::new(ErrorKind::BufferUnderflow));
+        }
+
         let buf = buf.read_until(bytes as usize);
         Ok(LoadArray::new(buf))
     }
+}
 
+impl<'de, T> LoadArray<'de, T> {
     /// Construct a new array reader around a buffer.
     pub(crate) fn new(buf: Body<'de>) -> Self {
         LoadArray {
@@ -105,3 +118,72 @@ where
         Ok(Some(T::load_struct(&mut self.buf)?))
     }
 }
+
+/// Iterate over the elements of an array.
+///
+/// Iteration stops after the first error has been returned.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{ty, BodyBuf};
+///
+/// let mut buf = BodyBuf::new();
+/// let mut array = buf.store_array::<u32>()?;
+/// array.store(10u32);
+/// array.store(20u32);
+/// array.store(30u32);
+/// array.finish()?;
+///
+/// let mut array = buf.store_array::<(u8, ty::Str)>()?;
+/// array.store_struct().store(1u8).store("foo").finish()?;
+/// array.store_struct().store(2u8).store("bar").finish()?;
+/// array.finish()?;
+///
+/// let mut buf = buf.as_body();
+///
+/// let numbers = buf.load_array::<u32>()?;
+/// assert_eq!(numbers.size_hint(), (3, Some(3)));
+/// assert_eq!(numbers.collect::<Result<Vec<_>, _>>()?, [10, 20, 30]);
+///
+/// let mut total = 0;
+///
+/// for entry in buf.load_array::<(u8, ty::Str)>()? {
+///     let (n, _) = entry?;
+///     total += n;
+/// }
+///
+/// assert_eq!(total, 3);
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+impl<'de, T> Iterator for LoadArray<'de, T>
+where
+    T: ty::Marker,
+{
+    type Item = Result<T::Return<'de>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buf.is_empty() {
+            return None;
+        }
+
+        match T::load_struct(&mut self.buf) {
+            Ok(value) => Some(Ok(value)),
+            Err(error) => {
+                self.buf = Body::empty();
+                Some(Err(error))
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.buf.len();
+
+        match T::FIXED_SIZE {
+            Some(size) => (len / size, Some(len / size)),
+            None => (usize::from(len > 0), Some(len)),
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::marker::PhantomData;
+
+use crate::buf::MAX_ARRAY_LENGTH;
+use crate::error::ErrorKind;
+use crate::{ty, Body};
+use crate::{Error, Result};
+
+/// Read a dictionary from a buffer.
+///
+/// See [`Body::load_dict`].
+///
+/// [`Body::load_dict`]: crate::Body::load_dict
+pub struct LoadDict<'de, K, V> {
+    buf: Body<'de>,
+    _marker: PhantomData<(K, V)>,
+}
+
+impl<'de, K, V> LoadDict<'de, K, V> {
+    #[inline]
+    pub(crate) fn from_mut(buf: &mut Body<'de>) -> Result<Self> {
+        Ok(Self {
+            buf: read_dict(buf)?,
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// Read the header of a dictionary, returning a body over its entries.
+pub(crate) fn read_dict<'de>(buf: &mut Body<'de>) -> Result<Body<'de>> {
+    let bytes = buf.load::<u32>()?;
+
+    if bytes > MAX_ARRAY_LENGTH {
+        return Err(Error::new(ErrorKind::ArrayTooLong(bytes)));
+    }
+
+    // NB: Dict entries are aligned like structs, and padding up until the
+    // first entry is not included in the length of the array.
+    buf.align::<u64>()?;
+
+    if bytes as usize > buf.len() {
+        return Err(Error::new(ErrorKind::BufferUnderflow));
+    }
+
+    Ok(buf.read_until(bytes as usize))
+}
+
+impl<'de, K, V> LoadDict<'de, K, V>
+where
+    K: ty::Marker,
+    V: ty::Marker,
+{
+    /// Load the next entry from the dictionary.
+    ///
+    /// See [`Body::load_dict`].
+    ///
+    /// [`Body::load_dict`]: crate::Body::load_dict
+    pub fn load_entry(&mut self) -> Result<Option<(K::Return<'de>, V::Return<'de>)>> {
+        if self.buf.is_empty() {
+            return Ok(None);
+        }
+
+        self.buf.align::<u64>()?;
+        let key = K::load_struct(&mut self.buf)?;
+        let value = V::load_struct(&mut self.buf)?;
+        Ok(Some((key, value)))
+    }
+}
+
+impl<'de, K, V> Iterator for LoadDict<'de, K, V>
+where
+    K: ty::Marker,
+    V: ty::Marker,
+{
+    type Item = Result<(K::Return<'de>, V::Return<'de>)>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.load_entry() {
+            Ok(entry) => Ok(entry).transpose(),
+            Err(error) => {
+                // NB: Make sure that the iterator is fused after an error.
+                self.buf = Body::empty();
+                Some(Err(error))
+            }
+        }
+    }
+}
@@ -1,15 +1,37 @@
 pub use self::load_array::LoadArray;
 mod load_array;
 
+pub use self::load_dict::LoadDict;
+mod load_dict;
+
 pub use self::as_body::AsBody;
 mod as_body;
 
+pub use self::walk::Visitor;
+mod walk;
+
+pub use self::display::DisplayBody;
+mod display;
+
+mod debug;
+
+#[cfg(feature = "dump")]
+pub use self::dump::DumpBody;
+#[cfg(feature = "dump")]
+mod dump;
+
 use std::fmt;
 
-use crate::buf::Aligned;
-use crate::error::Result;
+use tokio_dbus_core::signature::Type as SignatureType;
+
+use crate::buf::{Aligned, MAX_ARRAY_LENGTH};
+use crate::error::{ErrorKind, Result};
 use crate::ty;
-use crate::{BodyBuf, Endianness, Frame, Read, Signature};
+use crate::FromBody;
+use crate::{BodyBuf, Endianness, Error, Frame, Read, Signature};
+
+pub(crate) use self::load_dict::read_dict;
+use self::walk::{walk, Skip};
 
 /// A read-only view into a buffer suitable for use as a body in a [`Message`].
 ///
@@ -33,6 +55,8 @@ pub struct Body<'a> {
     data: Aligned<'a>,
     endianness: Endianness,
     signature: &'a Signature,
+    unix_fds: u32,
+    start: usize,
 }
 
 impl<'a> Body<'a> {
@@ -48,10 +72,14 @@ impl<'a> Body<'a> {
         endianness: Endianness,
         signature: &'a Signature,
     ) -> Self {
+        let start = data.position();
+
         Self {
             data,
             endianness,
             signature,
+            unix_fds: 0,
+            start,
         }
     }
 
@@ -101,6 +129,58 @@ impl<'a> Body<'a> {
         Self { endianness, ..self }
     }
 
+    /// Re-encode the remaining data of the body into a new buffer with the
+    /// given endianness.
+    ///
+    /// Unlike [`Body::with_endianness`], which only changes how the data is
+    /// interpreted, this decodes every value according to the [remaining
+    /// signature] of the body and stores it again. Values stored in the returned buffer
+    /// afterwards use the same endianness, so it never mixes byte orders.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the body doesn't match its signature.
+    ///
+    /// [remaining signature]: Body::remaining_signature
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, Endianness};
+    ///
+    /// let mut buf = BodyBuf::with_endianness(Endianness::BIG);
+    /// buf.arguments((0x1234u16, "foo"))?;
+    ///
+    /// let mut array = buf.store_array::<u32>()?;
+    /// array.store(1);
+    /// array.store(2);
+    /// array.finish()?;
+    ///
+    /// let mut little = buf.as_body().to_endianness(Endianness::LITTLE)?;
+    /// assert_eq!(little.endianness(), Endianness::LITTLE);
+    /// assert_eq!(little.signature(), "qsau");
+    /// assert_eq!(&little.get()[..2], &[0x34, 0x12]);
+    ///
+    /// little.store(0x5678u16)?;
+    ///
+    /// let mut body = little.as_body();
+    /// assert_eq!(body.load::<u16>()?, 0x1234);
+    /// assert_eq!(body.read::<str>()?, "foo");
+    ///
+    /// let mut array = body.load_array::<u32>()?;
+    /// assert_eq!(array.load()?, Some(1));
+    /// assert_eq!(array.load()?, Some(2));
+    /// assert_eq!(array.load()?, None);
+    ///
+    /// assert_eq!(body.load::<u16>()?, 0x5678);
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn to_endianness(&self, endianness: Endianness) -> Result<BodyBuf> {
+        let mut buf = BodyBuf::with_endianness(endianness);
+        buf.append(self)?;
+        Ok(buf)
+    }
+
     /// Get the signature of the buffer.
     ///
     /// # Examples
@@ -122,9 +202,204 @@ impl<'a> Body<'a> {
         self.signature
     }
 
-    /// Adjust the signature of buffer.
+    /// Get the signature of the values which have not yet been read from the
+    /// buffer.
+    ///
+    /// Values are only considered read once all of their data has been
+    /// consumed, so the signature of a value which has only been partially read
+    /// is still included. This is computed by skipping over the values which
+    /// precede the read position according to [`Body::signature`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, Signature};
+    ///
+    /// let mut buf = BodyBuf::new();
+    /// buf.store_parsed(Signature::new("ua{sv}s")?, &["42", "{a: u:1}", "foo"])?;
+    ///
+    /// let mut body = buf.as_body();
+    /// assert_eq!(body.remaining_signature(), "ua{sv}s");
+    ///
+    /// assert_eq!(body.load::<u32>()?, 42);
+    /// assert_eq!(body.remaining_signature(), "a{sv}s");
+    ///
+    /// // Only reading the length of the array leaves it unread.
+    /// assert_eq!(body.load::<u32>()?, 16);
+    /// assert_eq!(body.remaining_signature(), "a{sv}s");
+    ///
+    /// let mut body = buf.as_body();
+    /// body.load::<u32>()?;
+    /// body.load_dict::<tokio_dbus::ty::Str, tokio_dbus::ty::Variant>()?;
+    /// assert_eq!(body.remaining_signature(), "s");
+    ///
+    /// assert_eq!(body.read::<str>()?, "foo");
+    /// assert!(body.remaining_signature().is_empty());
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn remaining_signature(&self) -> &'a Signature {
+        let position = self.data.position();
+
+        if position == self.start {
+            return self.signature;
+        }
+
+        let mut body = Body {
+            data: self.data.rewind(self.start),
+            ..self.clone()
+        };
+
+        let bytes = self.signature.as_bytes();
+        let mut offset = 0;
+
+        for ty in self.signature.iter() {
+            let len = match ty {
+                SignatureType::Signature(signature) => signature.len(),
+                SignatureType::Array(element) => element.len() + 1,
+                SignatureType::Struct(fields) => fields.len() + 2,
+                SignatureType::Dict(key, value) => key.len() + value.len() + 2,
+            };
+
+            // SAFETY: Each type yielded by the iterator is a complete type.
+            let signature = unsafe { Signature::new_unchecked(&bytes[offset..offset + len]) };
+
+            if walk(&mut body, signature, &mut Skip, 0).is_err() || body.data.position() > position
+            {
+                break;
+            }
+
+            offset += len;
+
+            if body.data.position() == position {
+                break;
+            }
+        }
+
+        // SAFETY: The remainder of a signature following a complete type is
+        // also a valid signature.
+        unsafe { Signature::new_unchecked(&bytes[offset..]) }
+    }
+
+    /// Test if any data has been read from the body.
+    pub(crate) fn is_partially_read(&self) -> bool {
+        self.data.position() != self.start
+    }
+
+    /// Ensure that all data in the body has been read.
+    ///
+    /// This should be called after decoding the expected arguments of a
+    /// message, to catch messages carrying more arguments than expected or
+    /// decoding which silently stopped short. Errors returned by this are
+    /// converted into `org.freedesktop.DBus.Error.InvalidArgs` replies through
+    /// [`ToDbusError`].
+    ///
+    /// [`ToDbusError`]: crate::ToDbusError
+    ///
+    /// # Errors
+    ///
+    /// Errors if there is data left in the body, with an error mentioning the
+    /// [remaining signature].
+    ///
+    /// [remaining signature]: Body::remaining_signature
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, ToDbusError};
+    ///
+    /// let mut buf = BodyBuf::new();
+    /// buf.arguments((42u32, "foo"))?;
+    ///
+    /// let mut body = buf.as_body();
+    /// assert_eq!(body.load::<u32>()?, 42);
+    ///
+    /// let error = body.ensure_consumed().unwrap_err();
+    /// assert_eq!(error.to_string(), "Body has 8 trailing bytes with remaining signature \"s\"");
+    /// assert_eq!(error.error_name(), "org.freedesktop.DBus.Error.InvalidArgs");
+    ///
+    /// assert_eq!(body.read::<str>()?, "foo");
+    /// body.ensure_consumed()?;
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn ensure_consumed(&self) -> Result<()> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        Err(Error::new(ErrorKind::TrailingBody(
+            self.remaining_signature().into(),
+            self.len(),
+        )))
+    }
+
+    /// Finish reading the body, ensuring that all of its data has been read.
+    ///
+    /// This is the same as [`Body::ensure_consumed`], but consumes the body.
+    ///
+    /// # Errors
+    ///
+    /// Errors if there is data left in the body.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::BodyBuf;
+    ///
+    /// let mut buf = BodyBuf::new();
+    /// buf.arguments((42u32, "foo"))?;
+    ///
+    /// let mut body = buf.as_body();
+    /// assert_eq!(body.load::<u32>()?, 42);
+    /// assert!(body.clone().finish().is_err());
+    ///
+    /// assert_eq!(body.read::<str>()?, "foo");
+    /// body.finish()?;
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn finish(self) -> Result<()> {
+        self.ensure_consumed()
+    }
+
+    /// Adjust the signature of buffer, which describes the data following the
+    /// current read position.
     pub(crate) fn with_signature(self, signature: &'a Signature) -> Self {
-        Self { signature, ..self }
+        let start = self.data.position();
+
+        Self {
+            signature,
+            start,
+            ..self
+        }
+    }
+
+    /// Set the number of unix file descriptors referenced by the body.
+    #[inline]
+    pub(crate) const fn with_unix_fds(self, unix_fds: u32) -> Self {
+        Self { unix_fds, ..self }
+    }
+
+    /// Get the number of unix file descriptors referenced by the body.
+    ///
+    /// For a body constructed through a [`BodyBuf`] this is the value of
+    /// [`BodyBuf::unix_fds`], and for a received message it's the value of its
+    /// [`UNIX_FDS`] header field.
+    ///
+    /// [`UNIX_FDS`]: crate::proto::Variant::UNIX_FDS
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, UnixFd};
+    ///
+    /// let mut body = BodyBuf::new();
+    /// body.store(UnixFd::new(0))?;
+    ///
+    /// assert_eq!(body.as_body().unix_fds(), 1);
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    #[inline]
+    pub fn unix_fds(&self) -> u32 {
+        self.unix_fds
     }
 
     /// Get a slice out of the buffer that has ben written to.
@@ -219,6 +494,44 @@ impl<'a> Body<'a> {
         T::read_from(self)
     }
 
+    /// Read a byte array `ay` without copying it.
+    ///
+    /// This is the same as reading `[u8]` through [`read()`], except that the
+    /// length prefix is validated against [`MAX_ARRAY_LENGTH`] before the
+    /// array is read.
+    ///
+    /// [`read()`]: Self::read
+    /// [`MAX_ARRAY_LENGTH`]: crate::limits::MAX_ARRAY_LENGTH
+    ///
+    /// # Errors
+    ///
+    /// Errors if the length prefix exceeds the maximum array length, or if the
+    /// body is too short to hold the array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::BodyBuf;
+    ///
+    /// let mut buf = BodyBuf::new();
+    /// buf.store_bytes(b"\x89PNG")?;
+    /// buf.store(42u32)?;
+    ///
+    /// let mut body = buf.as_body();
+    /// assert_eq!(body.read_byte_array()?, b"\x89PNG");
+    /// assert_eq!(body.load::<u32>()?, 42);
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn read_byte_array(&mut self) -> Result<&'a [u8]> {
+        let len = self.load::<u32>()?;
+
+        if len > MAX_ARRAY_LENGTH {
+            return Err(Error::new(ErrorKind::ArrayTooLong(len)));
+        }
+
+        self.load_slice(len as usize)
+    }
+
     /// Read `len` bytes from the buffer and make accessible through another
     /// [`Body`] instance constituting that sub-slice.
     ///
@@ -250,6 +563,7 @@ impl<'a> Body<'a> {
     /// ```
     pub fn read_until(&mut self, len: usize) -> Body<'a> {
         Body::from_raw_parts(self.data.read_until(len), self.endianness, self.signature)
+            .with_unix_fds(self.unix_fds)
     }
 
     /// Read an array from the buffer.
@@ -264,15 +578,15 @@ impl<'a> Body<'a> {
     /// array.store(10u32);
     /// array.store(20u32);
     /// array.store(30u32);
-    /// array.finish();
+    /// array.finish()?;
     ///
     /// let mut array = buf.store_array::<ty::Array<ty::Str>>()?;
     /// let mut inner = array.store_array();
     /// inner.store("foo");
     /// inner.store("bar");
     /// inner.store("baz");
-    /// inner.finish();
-    /// array.finish();
+    /// inner.finish()?;
+    /// array.finish()?;
     ///
     /// assert_eq!(buf.signature(), b"auaas");
     ///
@@ -302,6 +616,36 @@ impl<'a> Body<'a> {
         LoadArray::from_mut(self)
     }
 
+    /// Read a dictionary from the buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{ty, BodyBuf, Endianness};
+    ///
+    /// let mut buf = BodyBuf::with_endianness(Endianness::LITTLE);
+    /// let mut dict = buf.store_dict::<u32, ty::Str>()?;
+    /// dict.store(1, "one");
+    /// dict.store(2, "two");
+    /// dict.finish()?;
+    ///
+    /// assert_eq!(buf.signature(), b"a{us}");
+    ///
+    /// let mut buf = buf.as_body();
+    /// let mut dict = buf.load_dict::<u32, ty::Str>()?;
+    /// assert_eq!(dict.load_entry()?, Some((1, "one")));
+    /// assert_eq!(dict.load_entry()?, Some((2, "two")));
+    /// assert_eq!(dict.load_entry()?, None);
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn load_dict<K, V>(&mut self) -> Result<LoadDict<'a, K, V>>
+    where
+        K: ty::Marker,
+        V: ty::Marker,
+    {
+        LoadDict::from_mut(self)
+    }
+
     /// Read a struct from the buffer.
     ///
     /// # Examples
@@ -319,9 +663,10 @@ impl<'a> Body<'a> {
     ///         w.store(1u8);
     ///         w.store(2u8);
     ///         w.store(3u8);
-    ///     })
+    ///         Ok(())
+    ///     })?
     ///     .store("Hello World")
-    ///     .finish();
+    ///     .finish()?;
     ///
     /// assert_eq!(buf.signature(), "y(quays)");
     ///
@@ -348,6 +693,159 @@ impl<'a> Body<'a> {
         E::load_struct(self)
     }
 
+    /// Decode the body as `T`, where the signature of `T` must match the
+    /// signature of the body exactly.
+    ///
+    /// This does not advance the body.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the signature of the body does not match that of `T`, with
+    /// an error describing both the expected and the actual signature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::BodyBuf;
+    ///
+    /// let mut buf = BodyBuf::new();
+    /// buf.store(10u32)?;
+    /// buf.store("Hello World")?;
+    ///
+    /// let body = buf.as_body();
+    /// let (number, string) = body.decode::<(u32, &str)>()?;
+    /// assert_eq!(number, 10);
+    /// assert_eq!(string, "Hello World");
+    ///
+    /// let error = body.decode::<(u32, u32)>().unwrap_err();
+    /// assert_eq!(error.to_string(), "Expected body with signature \"uu\" but found \"us\"");
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn decode<T>(&self) -> Result<T>
+    where
+        T: FromBody<'a>,
+    {
+        crate::from_body::decode(self)
+    }
+
+    /// Walk the values described by `signature`, calling the corresponding
+    /// method on `visitor` for each value.
+    ///
+    /// This can be used to process bodies whose layout is only known at
+    /// runtime, see [`Visitor`] for more details.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the body does not contain the values described by
+    /// `signature`, or if any of the callbacks of the visitor errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{ty, BodyBuf, Result, Signature, Visitor};
+    ///
+    /// #[derive(Default)]
+    /// struct Sum(u64);
+    ///
+    /// impl Visitor<'_> for Sum {
+    ///     fn visit_u8(&mut self, value: u8) -> Result<()> {
+    ///         self.0 += u64::from(value);
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn visit_u32(&mut self, value: u32) -> Result<()> {
+    ///         self.0 += u64::from(value);
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let mut buf = BodyBuf::new();
+    ///
+    /// let mut array = buf.store_array::<(u8, u32)>()?;
+    /// array.store_struct().store(1u8).store(2u32).finish()?;
+    /// array.store_struct().store(3u8).store(4u32).finish()?;
+    /// array.finish()?;
+    ///
+    /// let mut sum = Sum::default();
+    /// let mut body = buf.as_body();
+    /// body.walk(buf.signature(), &mut sum)?;
+    ///
+    /// assert_eq!(sum.0, 10);
+    /// assert!(body.is_empty());
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn walk<V>(&mut self, signature: &Signature, visitor: &mut V) -> Result<()>
+    where
+        V: ?Sized + Visitor<'a>,
+    {
+        walk(self, signature, visitor, 0)
+    }
+
+    /// Get a helper which displays the values in the body according to its
+    /// signature as a human-readable tree, similarly to how `dbus-monitor`
+    /// displays message bodies.
+    ///
+    /// This does not advance the body. Only the values which have not yet
+    /// been read are displayed, according to the
+    /// [remaining signature][Body::remaining_signature].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, Signature};
+    ///
+    /// let mut buf = BodyBuf::new();
+    /// buf.store_parsed(Signature::new("ua{sv}")?, &["42", "{a: s:b}"])?;
+    ///
+    /// let expected = r#"uint32 42
+    /// array [
+    ///   dict entry(
+    ///     string "a"
+    ///     variant string "b"
+    ///   )
+    /// ]"#;
+    ///
+    /// assert_eq!(buf.as_body().display().to_string(), expected);
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn display(&self) -> DisplayBody<'a> {
+        DisplayBody::new(self.clone())
+    }
+
+    /// Get a helper which displays the body as an annotated hex dump, where
+    /// each value is listed with its offset, its encoded bytes and the
+    /// padding which precedes it.
+    ///
+    /// This is intended for diagnosing encoding issues. Decoding stops at the
+    /// first value which doesn't match the signature of the body, which is
+    /// marked in the dump together with the error. Like with
+    /// [`Body::display`], only data which has not yet been read is dumped.
+    ///
+    /// This requires the `dump` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, Endianness};
+    ///
+    /// let mut buf = BodyBuf::with_endianness(Endianness::LITTLE);
+    /// buf.arguments((1u8, "foo", 2u64))?;
+    ///
+    /// let expected = "\
+    /// 0000  01                       byte 1
+    /// 0001  00 00 00                 <padding>
+    /// 0004  03 00 00 00 66 6f 6f 00  string \"foo\"
+    /// 000c  00 00 00 00              <padding>
+    /// 0010  02 00 00 00 00 00 00 00  uint64 2";
+    ///
+    /// assert_eq!(buf.as_body().dump().to_string(), expected);
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    #[cfg(feature = "dump")]
+    pub fn dump(&self) -> DumpBody<'a> {
+        DumpBody::new(self.clone())
+    }
+
     /// Load a frame of the given type.
     ///
     /// This advances the read cursor of the buffer by the alignment and size of
@@ -384,10 +882,15 @@ impl<'a> Body<'a> {
         Ok(frame)
     }
 
-    /// Advance the read cursor by `n`.
-    #[inline]
-    pub(crate) fn advance(&mut self, n: usize) -> Result<()> {
-        self.data.advance(n)
+    /// Align the read side of the buffer to the alignment of the first type in
+    /// `signature`.
+    pub(crate) fn align_for(&mut self, signature: &Signature) -> Result<()> {
+        match crate::signature::alignment_of(signature) {
+            2 => self.align::<u16>(),
+            4 => self.align::<u32>(),
+            8 => self.align::<u64>(),
+            _ => Ok(()),
+        }
     }
 
     /// Align the read side of the buffer.
@@ -421,6 +924,8 @@ impl Clone for Body<'_> {
             data: self.data.clone(),
             endianness: self.endianness,
             signature: self.signature,
+            unix_fds: self.unix_fds,
+            start: self.start,
         }
     }
 }
@@ -428,10 +933,15 @@ impl Clone for Body<'_> {
 impl fmt::Debug for Body<'_> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Body")
-            .field("data", &self.data)
-            .field("endianness", &self.endianness)
-            .finish()
+        let mut f = f.debug_struct("Body");
+        f.field("signature", &self.signature.as_str());
+
+        match debug::basic_values(self) {
+            Some(values) => f.field("values", &values),
+            None => f.field("data", &debug::Hex(self.get())),
+        };
+
+        f.field("endianness", &self.endianness).finish()
     }
 }
 
@@ -0,0 +1,402 @@
+use tokio_dbus_core::signature::Type as SignatureType;
+
+use crate::buf::MAX_ARRAY_LENGTH;
+use crate::error::{ErrorKind, Result};
+use crate::limits::enter;
+use crate::proto::Type;
+use crate::{Body, Error, ObjectPath, Signature};
+
+/// A visitor which receives typed callbacks for every value walked in a
+/// [`Body`].
+///
+/// This is used in combination with [`Body::walk`] to process bodies whose
+/// layout is only known at runtime. All callbacks default to doing nothing, so
+/// implementors only need to provide the callbacks they are interested in.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{BodyBuf, Result, Signature, Visitor};
+///
+/// #[derive(Default)]
+/// struct Strings(Vec<String>);
+///
+/// impl Visitor<'_> for Strings {
+///     fn visit_str(&mut self, value: &str) -> Result<()> {
+///         self.0.push(value.to_owned());
+///         Ok(())
+///     }
+/// }
+///
+/// let mut buf = BodyBuf::new();
+/// buf.store(42u32)?;
+/// buf.store("Hello")?;
+///
+/// let mut array = buf.store_array::<tokio_dbus::ty::Str>()?;
+/// array.store("World");
+/// array.finish()?;
+///
+/// let mut strings = Strings::default();
+/// let mut body = buf.as_body();
+/// body.walk(Signature::new("usas")?, &mut strings)?;
+///
+/// assert_eq!(strings.0, ["Hello", "World"]);
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+#[allow(unused_variables)]
+pub trait Visitor<'de> {
+    /// Visit a byte, as indicated by the `y` type code.
+    #[inline]
+    fn visit_u8(&mut self, value: u8) -> Result<()> {
+        Ok(())
+    }
+
+    /// Visit a boolean, as indicated by the `b` type code.
+    #[inline]
+    fn visit_bool(&mut self, value: bool) -> Result<()> {
+        Ok(())
+    }
+
+    /// Visit a signed 16-bit integer, as indicated by the `n` type code.
+    #[inline]
+    fn visit_i16(&mut self, value: i16) -> Result<()> {
+        Ok(())
+    }
+
+    /// Visit an unsigned 16-bit integer, as indicated by the `q` type code.
+    #[inline]
+    fn visit_u16(&mut self, value: u16) -> Result<()> {
+        Ok(())
+    }
+
+    /// Visit a signed 32-bit integer, as indicated by the `i` type code.
+    #[inline]
+    fn visit_i32(&mut self, value: i32) -> Result<()> {
+        Ok(())
+    }
+
+    /// Visit an unsigned 32-bit integer, as indicated by the `u` type code.
+    #[inline]
+    fn visit_u32(&mut self, value: u32) -> Result<()> {
+        Ok(())
+    }
+
+    /// Visit a signed 64-bit integer, as indicated by the `x` type code.
+    #[inline]
+    fn visit_i64(&mut self, value: i64) -> Result<()> {
+        Ok(())
+    }
+
+    /// Visit an unsigned 64-bit integer, as indicated by the `t` type code.
+    #[inline]
+    fn visit_u64(&mut self, value: u64) -> Result<()> {
+        Ok(())
+    }
+
+    /// Visit a double, as indicated by the `d` type code.
+    #[inline]
+    fn visit_f64(&mut self, value: f64) -> Result<()> {
+        Ok(())
+    }
+
+    /// Visit a string, as indicated by the `s` type code.
+    #[inline]
+    fn visit_str(&mut self, value: &'de str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Visit an object path, as indicated by the `o` type code.
+    #[inline]
+    fn visit_object_path(&mut self, value: &'de ObjectPath) -> Result<()> {
+        Ok(())
+    }
+
+    /// Visit a signature, as indicated by the `g` type code.
+    #[inline]
+    fn visit_signature(&mut self, value: &'de Signature) -> Result<()> {
+        Ok(())
+    }
+
+    /// Visit the index of a unix file descriptor, as indicated by the `h` type
+    /// code.
+    #[inline]
+    fn visit_unix_fd(&mut self, index: u32) -> Result<()> {
+        Ok(())
+    }
+
+    /// Enter an array with elements of the given signature.
+    #[inline]
+    fn enter_array(&mut self, element: &Signature) -> Result<()> {
+        Ok(())
+    }
+
+    /// Leave the array most recently entered.
+    #[inline]
+    fn leave_array(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Enter a struct with the given field signature.
+    #[inline]
+    fn enter_struct(&mut self, fields: &Signature) -> Result<()> {
+        Ok(())
+    }
+
+    /// Leave the struct most recently entered.
+    #[inline]
+    fn leave_struct(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Enter a dict entry with the given key and value signatures.
+    #[inline]
+    fn enter_dict_entry(&mut self, key: &Signature, value: &Signature) -> Result<()> {
+        Ok(())
+    }
+
+    /// Leave the dict entry most recently entered.
+    #[inline]
+    fn leave_dict_entry(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Enter a variant containing a value of the given signature.
+    #[inline]
+    fn enter_variant(&mut self, signature: &'de Signature) -> Result<()> {
+        Ok(())
+    }
+
+    /// Leave the variant most recently entered.
+    #[inline]
+    fn leave_variant(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<'de, V> Visitor<'de> for &mut V
+where
+    V: ?Sized + Visitor<'de>,
+{
+    #[inline]
+    fn visit_u8(&mut self, value: u8) -> Result<()> {
+        (**self).visit_u8(value)
+    }
+
+    #[inline]
+    fn visit_bool(&mut self, value: bool) -> Result<()> {
+        (**self).visit_bool(value)
+    }
+
+    #[inline]
+    fn visit_i16(&mut self, value: i16) -> Result<()> {
+        (**self).visit_i16(value)
+    }
+
+    #[inline]
+    fn visit_u16(&mut self, value: u16) -> Result<()> {
+        (**self).visit_u16(value)
+    }
+
+    #[inline]
+    fn visit_i32(&mut self, value: i32) -> Result<()> {
+        (**self).visit_i32(value)
+    }
+
+    #[inline]
+    fn visit_u32(&mut self, value: u32) -> Result<()> {
+        (**self).visit_u32(value)
+    }
+
+    #[inline]
+    fn visit_i64(&mut self, value: i64) -> Result<()> {
+        (**self).visit_i64(value)
+    }
+
+    #[inline]
+    fn visit_u64(&mut self, value: u64) -> Result<()> {
+        (**self).visit_u64(value)
+    }
+
+    #[inline]
+    fn visit_f64(&mut self, value: f64) -> Result<()> {
+        (**self).visit_f64(value)
+    }
+
+    #[inline]
+    fn visit_str(&mut self, value: &'de str) -> Result<()> {
+        (**self).visit_str(value)
+    }
+
+    #[inline]
+    fn visit_object_path(&mut self, value: &'de ObjectPath) -> Result<()> {
+        (**self).visit_object_path(value)
+    }
+
+    #[inline]
+    fn visit_signature(&mut self, value: &'de Signature) -> Result<()> {
+        (**self).visit_signature(value)
+    }
+
+    #[inline]
+    fn visit_unix_fd(&mut self, index: u32) -> Result<()> {
+        (**self).visit_unix_fd(index)
+    }
+
+    #[inline]
+    fn enter_array(&mut self, element: &Signature) -> Result<()> {
+        (**self).enter_array(element)
+    }
+
+    #[inline]
+    fn leave_array(&mut self) -> Result<()> {
+        (**self).leave_array()
+    }
+
+    #[inline]
+    fn enter_struct(&mut self, fields: &Signature) -> Result<()> {
+        (**self).enter_struct(fields)
+    }
+
+    #[inline]
+    fn leave_struct(&mut self) -> Result<()> {
+        (**self).leave_struct()
+    }
+
+    #[inline]
+    fn enter_dict_entry(&mut self, key: &Signature, value: &Signature) -> Result<()> {
+        (**self).enter_dict_entry(key, value)
+    }
+
+    #[inline]
+    fn leave_dict_entry(&mut self) -> Result<()> {
+        (**self).leave_dict_entry()
+    }
+
+    #[inline]
+    fn enter_variant(&mut self, signature: &'de Signature) -> Result<()> {
+        (**self).enter_variant(signature)
+    }
+
+    #[inline]
+    fn leave_variant(&mut self) -> Result<()> {
+        (**self).leave_variant()
+    }
+}
+
+/// A visitor which skips over all values.
+pub(super) struct Skip;
+
+impl Visitor<'_> for Skip {}
+
+/// Walk the values described by `signature` in `buf`.
+pub(crate) fn walk<'de, V>(
+    buf: &mut Body<'de>,
+    signature: &Signature,
+    visitor: &mut V,
+    depth: usize,
+) -> Result<()>
+where
+    V: ?Sized + Visitor<'de>,
+{
+    for ty in signature.iter() {
+        walk_type(buf, ty, visitor, depth)?;
+    }
+
+    Ok(())
+}
+
+fn walk_type<'de, V>(
+    buf: &mut Body<'de>,
+    ty: SignatureType<'_>,
+    visitor: &mut V,
+    depth: usize,
+) -> Result<()>
+where
+    V: ?Sized + Visitor<'de>,
+{
+    match ty {
+        SignatureType::Signature(signature) => {
+            let Some(&b) = signature.as_bytes().first() else {
+                return Ok(());
+            };
+
+            walk_basic(buf, Type::new(b), visitor, depth)?;
+        }
+        SignatureType::Array(element) => {
+            let depth = enter(depth)?;
+            let bytes = buf.load::<u32>()?;
+
+            if bytes > MAX_ARRAY_LENGTH {
+                return Err(Error::new(ErrorKind::ArrayTooLong(bytes)));
+            }
+
+            // NB: Padding up until the first element is not included in the
+            // length of the array.
+            buf.align_for(element)?;
+
+            if bytes as usize > buf.len() {
+                return Err(Error::new(ErrorKind::BufferUnderflow));
+            }
+
+            let mut array = buf.read_until(bytes as usize);
+
+            visitor.enter_array(element)?;
+
+            while !array.is_empty() {
+                walk(&mut array, element, visitor, depth)?;
+            }
+
+            visitor.leave_array()?;
+        }
+        SignatureType::Struct(fields) => {
+            let depth = enter(depth)?;
+            buf.align::<u64>()?;
+            visitor.enter_struct(fields)?;
+            walk(buf, fields, visitor, depth)?;
+            visitor.leave_struct()?;
+        }
+        SignatureType::Dict(key, value) => {
+            let depth = enter(depth)?;
+            buf.align::<u64>()?;
+            visitor.enter_dict_entry(key, value)?;
+            walk(buf, key, visitor, depth)?;
+            walk(buf, value, visitor, depth)?;
+            visitor.leave_dict_entry()?;
+        }
+    }
+
+    Ok(())
+}
+
+fn walk_basic<'de, V>(buf: &mut Body<'de>, ty: Type, visitor: &mut V, depth: usize) -> Result<()>
+where
+    V: ?Sized + Visitor<'de>,
+{
+    match ty {
+        Type::BYTE => visitor.visit_u8(buf.load()?),
+        Type::BOOLEAN => match buf.load::<u32>()? {
+            0 => visitor.visit_bool(false),
+            1 => visitor.visit_bool(true),
+            n => Err(Error::new(ErrorKind::InvalidBoolean(n))),
+        },
+        Type::INT16 => visitor.visit_i16(buf.load()?),
+        Type::UINT16 => visitor.visit_u16(buf.load()?),
+        Type::INT32 => visitor.visit_i32(buf.load()?),
+        Type::UINT32 => visitor.visit_u32(buf.load()?),
+        Type::INT64 => visitor.visit_i64(buf.load()?),
+        Type::UINT64 => visitor.visit_u64(buf.load()?),
+        Type::DOUBLE => visitor.visit_f64(buf.load()?),
+        Type::STRING => visitor.visit_str(buf.read()?),
+        Type::OBJECT_PATH => visitor.visit_object_path(buf.read()?),
+        Type::SIGNATURE => visitor.visit_signature(buf.read()?),
+        Type::UNIX_FD => visitor.visit_unix_fd(buf.load()?),
+        Type::VARIANT => {
+            let depth = enter(depth)?;
+            let signature = buf.read::<Signature>()?;
+            visitor.enter_variant(signature)?;
+            walk(buf, signature, visitor, depth)?;
+            visitor.leave_variant()
+        }
+        _ => Err(Error::new(ErrorKind::UnsupportedType(ty))),
+    }
+}
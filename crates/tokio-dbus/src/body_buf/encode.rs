@@ -0,0 +1,140 @@
+use crate::buf::Alloc;
+use crate::error::Result;
+use crate::{BodyBuf, ObjectPath, Signature, UnixFd, Visitor};
+
+/// A visitor which re-encodes every value it visits into a body buffer,
+/// using the endianness of that buffer.
+///
+/// Note that this does not affect the signature of the buffer, which is left
+/// for the caller to maintain.
+pub(crate) struct Encode<'a> {
+    buf: &'a mut BodyBuf,
+    arrays: Vec<(Alloc<u32>, usize)>,
+}
+
+impl<'a> Encode<'a> {
+    pub(crate) fn new(buf: &'a mut BodyBuf) -> Self {
+        Self {
+            buf,
+            arrays: Vec::new(),
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for Encode<'_> {
+    #[inline]
+    fn visit_u8(&mut self, value: u8) -> Result<()> {
+        self.buf.store_frame(value);
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_bool(&mut self, value: bool) -> Result<()> {
+        self.buf.store_frame(u32::from(value));
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_i16(&mut self, value: i16) -> Result<()> {
+        self.buf.store_frame(value);
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_u16(&mut self, value: u16) -> Result<()> {
+        self.buf.store_frame(value);
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_i32(&mut self, value: i32) -> Result<()> {
+        self.buf.store_frame(value);
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_u32(&mut self, value: u32) -> Result<()> {
+        self.buf.store_frame(value);
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_i64(&mut self, value: i64) -> Result<()> {
+        self.buf.store_frame(value);
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_u64(&mut self, value: u64) -> Result<()> {
+        self.buf.store_frame(value);
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_f64(&mut self, value: f64) -> Result<()> {
+        self.buf.store_frame(value);
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_str(&mut self, value: &'de str) -> Result<()> {
+        self.buf.write_only(value);
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_object_path(&mut self, value: &'de ObjectPath) -> Result<()> {
+        self.buf.write_only(value);
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_signature(&mut self, value: &'de Signature) -> Result<()> {
+        self.buf.write_only(value);
+        Ok(())
+    }
+
+    #[inline]
+    fn visit_unix_fd(&mut self, index: u32) -> Result<()> {
+        self.buf.store_unix_fd(UnixFd::new(index));
+        Ok(())
+    }
+
+    #[inline]
+    fn enter_array(&mut self, element: &Signature) -> Result<()> {
+        let len = self.buf.alloc::<u32>();
+        // NB: Padding up until the first element is not included in the
+        // length of the array.
+        self.buf.align_mut_for(element);
+        self.arrays.push((len, self.buf.len()));
+        Ok(())
+    }
+
+    #[inline]
+    fn leave_array(&mut self) -> Result<()> {
+        if let Some((len, start)) = self.arrays.pop() {
+            let len_bytes = self.buf.len() - start;
+            self.buf.store_at(len, len_bytes as u32);
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn enter_struct(&mut self, _: &Signature) -> Result<()> {
+        self.buf.align_mut::<u64>();
+        Ok(())
+    }
+
+    #[inline]
+    fn enter_dict_entry(&mut self, _: &Signature, _: &Signature) -> Result<()> {
+        self.buf.align_mut::<u64>();
+        Ok(())
+    }
+
+    #[inline]
+    fn enter_variant(&mut self, signature: &'de Signature) -> Result<()> {
+        self.buf.write_only(signature);
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use crate::buf::Alloc;
+
+/// A reserved `u32` length prefix in a [`BodyBuf`].
+///
+/// This is constructed through [`BodyBuf::reserve_length`] and is patched
+/// with the length of everything written after it through
+/// [`BodyBuf::patch_length`].
+///
+/// [`BodyBuf`]: crate::BodyBuf
+/// [`BodyBuf::reserve_length`]: crate::BodyBuf::reserve_length
+/// [`BodyBuf::patch_length`]: crate::BodyBuf::patch_length
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[must_use = "A reserved length must be patched with BodyBuf::patch_length"]
+pub struct LengthPrefix {
+    at: usize,
+    start: usize,
+}
+
+impl LengthPrefix {
+    #[inline]
+    pub(crate) fn new(len: Alloc<u32>, start: usize) -> Self {
+        Self {
+            at: len.into_usize(),
+            start,
+        }
+    }
+
+    /// Get the location of the reserved length.
+    #[inline]
+    pub(crate) fn alloc(&self) -> Alloc<u32> {
+        Alloc::new(self.at)
+    }
+
+    /// Get the offset in the buffer at which the measured content starts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::BodyBuf;
+    ///
+    /// let mut buf = BodyBuf::new();
+    /// buf.store(1u8)?;
+    ///
+    /// let prefix = buf.reserve_length::<u64>();
+    /// assert_eq!(prefix.start(), 8);
+    /// buf.patch_length(prefix)?;
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    #[inline]
+    pub fn start(&self) -> usize {
+        self.start
+    }
+}
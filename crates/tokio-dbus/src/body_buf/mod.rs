@@ -4,14 +4,30 @@ mod store_array;
 pub use self::store_struct::StoreStruct;
 mod store_struct;
 
+pub use self::store_dict::StoreDict;
+mod store_dict;
+
+pub use self::length_prefix::LengthPrefix;
+mod length_prefix;
+
+mod parse;
+
+mod values;
+
+use self::encode::Encode;
+mod encode;
+
 use std::fmt;
 
 use crate::arguments::Arguments;
-use crate::buf::{AlignedBuf, Alloc};
-use crate::error::Result;
+use crate::buf::{AlignedBuf, Alloc, MAX_ARRAY_LENGTH};
+use crate::error::{ErrorKind, Result};
 use crate::signature::{SignatureBuilder, SignatureError};
 use crate::ty;
-use crate::{Body, Endianness, Frame, Signature, SignatureBuf, Storable, Write};
+use crate::{
+    limits, Body, Endianness, Error, Frame, Signature, SignatureBuf, Storable, StorableDyn, UnixFd,
+    Write,
+};
 
 /// A buffer that can be used to write a body.
 ///
@@ -33,6 +49,14 @@ pub struct BodyBuf {
     buf: AlignedBuf,
     endianness: Endianness,
     signature: SignatureBuilder,
+    unix_fds: u32,
+}
+
+/// A point in a [`BodyBuf`] which it can be restored to.
+#[derive(Clone, Copy)]
+pub(crate) struct Checkpoint {
+    len: usize,
+    unix_fds: u32,
 }
 
 impl BodyBuf {
@@ -65,6 +89,7 @@ impl BodyBuf {
             buf,
             endianness,
             signature: SignatureBuilder::from_owned_signature(signature),
+            unix_fds: 0,
         }
     }
 
@@ -82,6 +107,7 @@ impl BodyBuf {
             signature: SignatureBuilder::new(),
             endianness,
             buf: AlignedBuf::new(),
+            unix_fds: 0,
         }
     }
 
@@ -105,6 +131,7 @@ impl BodyBuf {
     pub fn clear(&mut self) {
         self.signature.clear();
         self.buf.clear();
+        self.unix_fds = 0;
     }
 
     /// Get the signature of the buffer.
@@ -192,6 +219,82 @@ impl BodyBuf {
         self.buf.align_mut::<T>();
     }
 
+    /// Align the buffer to the alignment of the first type in `signature`.
+    pub(crate) fn align_mut_for(&mut self, signature: &Signature) {
+        match crate::signature::alignment_of(signature) {
+            2 => self.align_mut::<u16>(),
+            4 => self.align_mut::<u32>(),
+            8 => self.align_mut::<u64>(),
+            _ => {}
+        }
+    }
+
+    /// Get the number of unix file descriptors referenced by the buffer.
+    ///
+    /// This is one more than the largest [`UnixFd`] index stored in the
+    /// buffer, and is the number of file descriptors which must be attached
+    /// to a message using this body. It is written as the [`UNIX_FDS`] header
+    /// field of such a message.
+    ///
+    /// [`UnixFd`]: crate::UnixFd
+    /// [`UNIX_FDS`]: crate::proto::Variant::UNIX_FDS
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, UnixFd};
+    ///
+    /// let mut body = BodyBuf::new();
+    /// assert_eq!(body.unix_fds(), 0);
+    ///
+    /// body.store(UnixFd::new(1))?;
+    /// body.store(UnixFd::new(0))?;
+    /// assert_eq!(body.unix_fds(), 2);
+    ///
+    /// body.clear();
+    /// assert_eq!(body.unix_fds(), 0);
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    #[inline]
+    pub fn unix_fds(&self) -> u32 {
+        self.unix_fds
+    }
+
+    /// Store a unix file descriptor without appending its signature, keeping
+    /// track of the number of file descriptors referenced.
+    pub(crate) fn store_unix_fd(&mut self, fd: UnixFd) {
+        self.store_frame(fd);
+        self.unix_fds = self.unix_fds.max(fd.index().saturating_add(1));
+    }
+
+    /// Take a checkpoint of the data in the buffer, which can be restored
+    /// with [`BodyBuf::restore`].
+    #[inline]
+    pub(crate) fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            len: self.buf.len(),
+            unix_fds: self.unix_fds,
+        }
+    }
+
+    /// Restore the data of the buffer to a checkpoint.
+    ///
+    /// Note that this does not affect the signature of the buffer.
+    #[inline]
+    pub(crate) fn restore(&mut self, checkpoint: Checkpoint) {
+        self.buf.truncate(checkpoint.len);
+        self.unix_fds = checkpoint.unix_fds;
+    }
+
+    /// Extend the signature of the buffer without writing any data.
+    pub(crate) fn extend_signature(&mut self, signature: &Signature) -> Result<()> {
+        if !self.signature.extend_from_signature(signature) {
+            return Err(SignatureError::too_long().into());
+        }
+
+        Ok(())
+    }
+
     /// Get a slice out of the buffer that has ben written to.
     ///
     /// # Examples
@@ -233,7 +336,7 @@ impl BodyBuf {
     /// buf.store_struct::<(u16, u32)>()?
     ///     .store(20u16)
     ///     .store(30u32)
-    ///     .finish();
+    ///     .finish()?;
     ///
     /// assert_eq!(buf.signature(), "(qu)");
     ///
@@ -249,7 +352,7 @@ impl BodyBuf {
     #[inline]
     pub fn as_body(&self) -> Body<'_> {
         let data = self.buf.as_aligned();
-        Body::from_raw_parts(data, self.endianness, &self.signature)
+        Body::from_raw_parts(data, self.endianness, &self.signature).with_unix_fds(self.unix_fds)
     }
 
     /// Allocate, zero space for and align data for `T`.
@@ -271,6 +374,81 @@ impl BodyBuf {
         self.buf.store_at(at, frame);
     }
 
+    /// Reserve a `u32` length prefix in the buffer, to be patched with
+    /// [`BodyBuf::patch_length`] once the content it measures has been
+    /// written.
+    ///
+    /// After the prefix the buffer is padded to the alignment of `E`. Like
+    /// with arrays, this padding is not included in the measured length. Use
+    /// `u8` if no additional alignment is needed.
+    ///
+    /// Note that this does not affect the signature of the buffer, which is
+    /// left for the caller to maintain. This is intended for implementing
+    /// container encodings which are not directly supported by [`BodyBuf`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, Endianness};
+    ///
+    /// let mut buf = BodyBuf::with_endianness(Endianness::LITTLE);
+    ///
+    /// let prefix = buf.reserve_length::<u64>();
+    /// buf.store(1u64)?;
+    /// buf.store(2u8)?;
+    /// assert_eq!(buf.patch_length(prefix)?, 9);
+    ///
+    /// assert_eq!(buf.get(), &[9, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 2]);
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn reserve_length<E>(&mut self) -> LengthPrefix
+    where
+        E: ty::Aligned,
+    {
+        let len = self.alloc::<u32>();
+        self.align_mut::<E::Alignment>();
+        LengthPrefix::new(len, self.len())
+    }
+
+    /// Patch a length prefix previously reserved with
+    /// [`BodyBuf::reserve_length`] with the number of bytes written since, and
+    /// return the patched length.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the measured length exceeds the maximum length of an array,
+    /// or if the buffer has been truncated or cleared since the prefix was
+    /// reserved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::BodyBuf;
+    ///
+    /// let mut buf = BodyBuf::new();
+    ///
+    /// let prefix = buf.reserve_length::<u8>();
+    /// buf.store(1u32)?;
+    /// buf.clear();
+    ///
+    /// assert!(buf.patch_length(prefix).is_err());
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn patch_length(&mut self, prefix: LengthPrefix) -> Result<u32> {
+        let Some(len) = self.len().checked_sub(prefix.start()) else {
+            return Err(Error::new(ErrorKind::InvalidLengthPrefix));
+        };
+
+        let len = u32::try_from(len).unwrap_or(u32::MAX);
+
+        if len > MAX_ARRAY_LENGTH {
+            return Err(Error::new(ErrorKind::ArrayTooLong(len)));
+        }
+
+        self.store_at(prefix.alloc(), len);
+        Ok(len)
+    }
+
     /// Store a [`Frame`] of type `T` in the buffer and add its signature.
     ///
     /// This both allocates enough space for the frame and ensures that the
@@ -330,6 +508,92 @@ impl BodyBuf {
         Ok(())
     }
 
+    /// Store a dynamically dispatched value into the buffer.
+    ///
+    /// This is the same as [`store()`], except that the type of the value
+    /// doesn't have to be known at compile time. See [`StorableDyn`].
+    ///
+    /// [`store()`]: Self::store
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, StorableDyn};
+    ///
+    /// let value: &dyn StorableDyn = &42u32;
+    ///
+    /// let mut body = BodyBuf::new();
+    /// body.store_dyn(value)?;
+    /// body.store_dyn(&String::from("Hello"))?;
+    ///
+    /// assert_eq!(body.signature(), "us");
+    /// assert_eq!(body.as_body().decode::<(u32, &str)>()?, (42, "Hello"));
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn store_dyn(&mut self, value: &dyn StorableDyn) -> Result<()> {
+        if !value.write_signature_dyn(&mut self.signature) {
+            return Err(SignatureError::too_long().into());
+        }
+
+        value.store_to_dyn(self);
+        Ok(())
+    }
+
+    /// Append the remaining contents of another body to this buffer, extending
+    /// the signature of this buffer with the remaining signature of `body`.
+    ///
+    /// Values are re-encoded as they are appended, so that they are correctly
+    /// aligned and use the endianness of this buffer. This can be used to add
+    /// trailing arguments to an existing body, or to combine bodies.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the combined signature would be too long, or if `body`
+    /// doesn't match its signature. The buffer is left unmodified on errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::BodyBuf;
+    ///
+    /// let mut payload = BodyBuf::new();
+    /// payload.arguments(("Hello", 7u64))?;
+    ///
+    /// let mut body = BodyBuf::new();
+    /// body.store(1u8)?;
+    /// body.append(&payload.as_body())?;
+    /// body.store(42u32)?;
+    ///
+    /// assert_eq!(body.signature(), "ystu");
+    /// assert_eq!(body.as_body().decode::<(u8, &str, u64, u32)>()?, (1, "Hello", 7, 42));
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn append(&mut self, body: &Body<'_>) -> Result<()> {
+        let signature = body.remaining_signature();
+
+        if self.signature.len() + signature.len() > limits::MAX_SIGNATURE_LENGTH {
+            return Err(SignatureError::too_long().into());
+        }
+
+        let checkpoint = self.checkpoint();
+        let mut body = body.clone();
+
+        let result = match body.walk(signature, &mut Encode::new(self)) {
+            Ok(()) if !body.is_empty() => Err(Error::new(ErrorKind::BodySignatureMismatch(
+                signature.into(),
+                body.len(),
+            ))),
+            result => result,
+        };
+
+        if let Err(error) = result {
+            self.restore(checkpoint);
+            return Err(error);
+        }
+
+        self.extend_signature(signature)
+    }
+
     /// Only store the specified value without appending its signature.
     pub(crate) fn store_frame<T>(&mut self, mut frame: T)
     where
@@ -399,7 +663,7 @@ impl BodyBuf {
     /// let mut buf = BodyBuf::with_endianness(Endianness::LITTLE);
     /// let mut array = buf.store_array::<u32>()?;
     /// array.store(1u32);
-    /// array.finish();
+    /// array.finish()?;
     ///
     /// assert_eq!(buf.signature(), b"au");
     /// assert_eq!(buf.get(), &[4, 0, 0, 0, 1, 0, 0, 0]);
@@ -413,7 +677,7 @@ impl BodyBuf {
     ///
     /// let mut buf = BodyBuf::with_endianness(Endianness::LITTLE);
     /// let mut array = buf.store_array::<u64>()?;
-    /// array.finish();
+    /// array.finish()?;
     ///
     /// assert_eq!(buf.signature(), b"at");
     /// assert_eq!(buf.get(), &[0, 0, 0, 0, 0, 0, 0, 0]);
@@ -429,6 +693,39 @@ impl BodyBuf {
         Ok(StoreArray::new(self))
     }
 
+    /// Write a dictionary into the buffer.
+    ///
+    /// The key `K` must be a basic type, such as a number or a string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{ty, BodyBuf, Variant};
+    ///
+    /// let mut buf = BodyBuf::new();
+    /// let mut dict = buf.store_dict::<ty::Str, ty::Variant>()?;
+    /// dict.store("resident", Variant::Bool(true));
+    /// dict.store("x", Variant::I32(10));
+    /// dict.finish()?;
+    ///
+    /// assert_eq!(buf.signature(), b"a{sv}");
+    ///
+    /// let mut body = buf.as_body();
+    /// let entries = body.load_dict::<ty::Str, ty::Variant>()?.collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(entries, [("resident", Variant::Bool(true)), ("x", Variant::I32(10))]);
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn store_dict<K, V>(&mut self) -> Result<StoreDict<'_, K, V>>
+    where
+        K: ty::Marker,
+        V: ty::Marker,
+    {
+        <ty::Dict<K, V> as ty::Marker>::write_signature(&mut self.signature)?;
+        // NB: We write directly onto the underlying buffer, because we've
+        // already applied the correct signature.
+        Ok(StoreDict::new(self))
+    }
+
     /// Write a slice as an byte array.
     ///
     /// # Examples
@@ -444,7 +741,40 @@ impl BodyBuf {
     /// # Ok::<_, tokio_dbus::Error>(())
     /// ```
     pub fn write_slice(&mut self, data: &[u8]) -> Result<()> {
-        self.store_array::<u8>()?.write_slice(data);
+        self.store_array::<u8>()?.write_slice(data)
+    }
+
+    /// Store a byte array `ay` by copying `bytes` directly into the buffer.
+    ///
+    /// Unlike storing the bytes through [`store_array()`], this doesn't go
+    /// through the per-element array machinery.
+    ///
+    /// [`store_array()`]: Self::store_array
+    ///
+    /// # Errors
+    ///
+    /// Errors if `bytes` is longer than [`MAX_ARRAY_LENGTH`], in which case
+    /// nothing is stored.
+    ///
+    /// [`MAX_ARRAY_LENGTH`]: crate::limits::MAX_ARRAY_LENGTH
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, Endianness};
+    ///
+    /// let mut buf = BodyBuf::with_endianness(Endianness::LITTLE);
+    /// buf.store_bytes(&[1, 2, 3, 4])?;
+    ///
+    /// assert_eq!(buf.signature(), "ay");
+    /// assert_eq!(buf.get(), &[4, 0, 0, 0, 1, 2, 3, 4]);
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn store_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        limits::check_array_length(bytes.len())?;
+        self.extend_signature(<[u8] as Write>::SIGNATURE)?;
+        self.store_frame(bytes.len() as u32);
+        self.extend_from_slice(bytes);
         Ok(())
     }
 
@@ -466,9 +796,10 @@ impl BodyBuf {
     ///         w.store(1u8);
     ///         w.store(2u8);
     ///         w.store(3u8);
-    ///     })
+    ///         Ok(())
+    ///     })?
     ///     .store("Hello World")
-    ///     .finish();
+    ///     .finish()?;
     ///
     /// assert_eq!(buf.signature(), b"y(quays)");
     /// assert_eq!(buf.get(), &[10, 0, 0, 0, 0, 0, 0, 0, 10, 0, 0, 0, 10, 0, 0, 0, 3, 0, 0, 0, 1, 2, 3, 0, 11, 0, 0, 0, 72, 101, 108, 108, 111, 32, 87, 111, 114, 108, 100, 0]);
@@ -503,12 +834,22 @@ impl Default for BodyBuf {
 }
 
 /// Construct an aligned buffer from a read buffer.
+///
+/// The bytes of the body are copied verbatim, so the constructed buffer keeps
+/// the endianness of the body and any subsequently stored values use the same
+/// endianness. To convert a body to a different endianness, use
+/// [`Body::to_endianness`].
 impl From<Body<'_>> for BodyBuf {
     #[inline]
     fn from(buf: Body<'_>) -> Self {
+        let unix_fds = buf.unix_fds();
         let (buf, endianness, signature) = buf.into_raw_parts();
         let buf = AlignedBuf::from(buf);
         let signature = signature.to_owned();
-        Self::from_raw_parts(buf, endianness, signature)
+
+        Self {
+            unix_fds,
+            ..Self::from_raw_parts(buf, endianness, signature)
+        }
     }
 }
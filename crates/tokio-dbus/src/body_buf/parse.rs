@@ -0,0 +1,445 @@
+use std::str::FromStr;
+
+use tokio_dbus_core::signature::Type as SignatureType;
+
+use crate::buf::MAX_ARRAY_LENGTH;
+use crate::error::{ErrorKind, Result};
+use crate::limits;
+use crate::proto::Type;
+use crate::{BodyBuf, Error, ObjectPath, Signature, UnixFd};
+
+impl BodyBuf {
+    /// Store string arguments in the buffer, parsed according to the types in
+    /// `signature`.
+    ///
+    /// Each complete type in the signature consumes one argument. Basic types
+    /// use the whole argument, so the argument `"10"` is stored as a `u32` if
+    /// its type is `u` and a string argument is stored as-is. Booleans are
+    /// written as `true` or `false`, with `yes`, `no`, `1` and `0` also being
+    /// accepted.
+    ///
+    /// Containers use a simple syntax:
+    /// * Arrays are written as `[1, 2, 3]`.
+    /// * Dictionaries, that is arrays of dict entries, are written as `{key:
+    ///   value, ...}`.
+    /// * Structs are written as `(1, "hello")`.
+    /// * Variants are written as a signature followed by a colon and the
+    ///   value, like `u:42` or `as:[a, b]`.
+    ///
+    /// Strings inside of containers can be quoted, in which case `\"`, `\\`,
+    /// `\n` and `\t` escapes are supported. Quoting is only necessary if the
+    /// string is empty or contains whitespace or any of the characters
+    /// `,:[](){}"`.
+    ///
+    /// If parsing fails the buffer is left unmodified.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the number of arguments doesn't match the signature, or if an
+    /// argument can't be parsed as its corresponding type. The error indicates
+    /// which argument is invalid, and for containers the position in the
+    /// argument at which parsing failed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, Signature};
+    ///
+    /// let mut buf = BodyBuf::new();
+    /// buf.store_parsed(Signature::new("usb")?, &["10", "Hello World", "true"])?;
+    ///
+    /// assert_eq!(buf.signature(), "usb");
+    ///
+    /// let mut body = buf.as_body();
+    /// assert_eq!(body.load::<u32>()?, 10);
+    /// assert_eq!(body.read::<str>()?, "Hello World");
+    /// assert_eq!(body.load::<u32>()?, 1);
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    ///
+    /// Storing containers:
+    ///
+    /// ```
+    /// use tokio_dbus::{ty, BodyBuf, Signature};
+    ///
+    /// let mut buf = BodyBuf::new();
+    ///
+    /// buf.store_parsed(
+    ///     Signature::new("au(sy)a{sv}")?,
+    ///     &["[1, 2]", "(\"a b\", 7)", "{answer: u:42}"],
+    /// )?;
+    ///
+    /// assert_eq!(buf.signature(), "au(sy)a{sv}");
+    ///
+    /// let mut body = buf.as_body();
+    ///
+    /// let values = body.load_array::<u32>()?.collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(values, [1, 2]);
+    ///
+    /// assert_eq!(body.load_struct::<(ty::Str, u8)>()?, ("a b", 7));
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    ///
+    /// Errors point out the offending argument:
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, Signature};
+    ///
+    /// let mut buf = BodyBuf::new();
+    /// let error = buf.store_parsed(Signature::new("uau")?, &["1", "[1, x]"]).unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "Invalid argument #1: at position 4: expected 32-bit unsigned integer, but found `x`"
+    /// );
+    ///
+    /// assert!(buf.is_empty());
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn store_parsed(&mut self, signature: &Signature, arguments: &[&str]) -> Result<()> {
+        let checkpoint = self.checkpoint();
+
+        if let Err(error) = store_all(self, signature, arguments) {
+            self.restore(checkpoint);
+            return Err(error);
+        }
+
+        if let Err(error) = self.extend_signature(signature) {
+            self.restore(checkpoint);
+            return Err(error);
+        }
+
+        Ok(())
+    }
+}
+
+fn store_all(buf: &mut BodyBuf, signature: &Signature, arguments: &[&str]) -> Result<()> {
+    let count = arguments.len();
+    let mut arguments = arguments.iter().enumerate();
+
+    for ty in signature.iter() {
+        let Some((index, argument)) = arguments.next() else {
+            return Err(invalid(
+                count,
+                format!("missing argument for `{}`", signature.as_str()),
+            ));
+        };
+
+        store_argument(buf, ty, argument).map_err(|message| invalid(index, message))?;
+    }
+
+    if let Some((index, _)) = arguments.next() {
+        return Err(invalid(
+            index,
+            format!("too many arguments for `{}`", signature.as_str()),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Store a single top-level argument.
+fn store_argument(buf: &mut BodyBuf, ty: SignatureType<'_>, argument: &str) -> Result<(), String> {
+    if let SignatureType::Signature(basic) = ty {
+        let Some(&b) = basic.as_bytes().first() else {
+            return Ok(());
+        };
+
+        let ty = Type::new(b);
+
+        // NB: Basic types use the entire argument, so that strings do not
+        // need to be quoted.
+        if ty != Type::VARIANT {
+            return store_basic(buf, ty, argument);
+        }
+    }
+
+    let mut parser = Parser::new(argument);
+
+    let result = parser.store(buf, ty, 0).and_then(|()| parser.expect_end());
+
+    result.map_err(|message| format!("at position {}: {message}", parser.pos))
+}
+
+fn store_basic(buf: &mut BodyBuf, ty: Type, argument: &str) -> Result<(), String> {
+    match ty {
+        Type::BYTE => buf.store_frame(number::<u8>(argument, "byte")?),
+        Type::INT16 => buf.store_frame(number::<i16>(argument, "16-bit signed integer")?),
+        Type::UINT16 => buf.store_frame(number::<u16>(argument, "16-bit unsigned integer")?),
+        Type::INT32 => buf.store_frame(number::<i32>(argument, "32-bit signed integer")?),
+        Type::UINT32 => buf.store_frame(number::<u32>(argument, "32-bit unsigned integer")?),
+        Type::INT64 => buf.store_frame(number::<i64>(argument, "64-bit signed integer")?),
+        Type::UINT64 => buf.store_frame(number::<u64>(argument, "64-bit unsigned integer")?),
+        Type::DOUBLE => buf.store_frame(number::<f64>(argument, "double")?),
+        Type::UNIX_FD => buf.store_unix_fd(UnixFd::new(number::<u32>(
+            argument,
+            "unix file descriptor index",
+        )?)),
+        Type::BOOLEAN => {
+            let value = match argument {
+                "true" | "yes" | "1" => true,
+                "false" | "no" | "0" => false,
+                _ => return Err(expected("boolean", argument)),
+            };
+
+            buf.store_frame(u32::from(value));
+        }
+        Type::STRING => buf.write_only(argument),
+        Type::OBJECT_PATH => {
+            let Ok(path) = ObjectPath::new(argument) else {
+                return Err(expected("object path", argument));
+            };
+
+            buf.write_only(path);
+        }
+        Type::SIGNATURE => {
+            let Ok(signature) = Signature::new(argument) else {
+                return Err(expected("signature", argument));
+            };
+
+            buf.write_only(signature);
+        }
+        _ => return Err(format!("unsupported type {ty:?}")),
+    }
+
+    Ok(())
+}
+
+/// Parser for arguments using the container syntax.
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    /// Store a value of type `ty`.
+    fn store(
+        &mut self,
+        buf: &mut BodyBuf,
+        ty: SignatureType<'_>,
+        depth: usize,
+    ) -> Result<(), String> {
+        match ty {
+            SignatureType::Signature(signature) => {
+                let Some(&b) = signature.as_bytes().first() else {
+                    return Ok(());
+                };
+
+                match Type::new(b) {
+                    Type::VARIANT => {
+                        let depth = limits::enter(depth).map_err(|error| error.to_string())?;
+                        self.store_variant(buf, depth)?;
+                    }
+                    ty => {
+                        self.skip_whitespace();
+                        let at = self.pos;
+                        let token = self.token()?;
+
+                        if let Err(message) = store_basic(buf, ty, &token) {
+                            self.pos = at;
+                            return Err(message);
+                        }
+                    }
+                }
+            }
+            SignatureType::Array(element) => {
+                let depth = limits::enter(depth).map_err(|error| error.to_string())?;
+
+                let (open, close) = match element.iter().next() {
+                    Some(SignatureType::Dict(..)) => (b'{', b'}'),
+                    _ => (b'[', b']'),
+                };
+
+                self.expect(open)?;
+
+                let len = buf.alloc::<u32>();
+                buf.align_mut_for(element);
+                let start = buf.len();
+
+                if !self.eat(close) {
+                    loop {
+                        for ty in element.iter() {
+                            self.store(buf, ty, depth)?;
+                        }
+
+                        if self.eat(close) {
+                            break;
+                        }
+
+                        self.expect(b',')?;
+                    }
+                }
+
+                let bytes = (buf.len() - start) as u32;
+
+                if bytes > MAX_ARRAY_LENGTH {
+                    return Err(format!("array of length {bytes} is too long"));
+                }
+
+                buf.store_at(len, bytes);
+            }
+            SignatureType::Struct(fields) => {
+                let depth = limits::enter(depth).map_err(|error| error.to_string())?;
+                self.expect(b'(')?;
+                buf.align_mut::<u64>();
+
+                for (index, ty) in fields.iter().enumerate() {
+                    if index > 0 {
+                        self.expect(b',')?;
+                    }
+
+                    self.store(buf, ty, depth)?;
+                }
+
+                self.expect(b')')?;
+            }
+            SignatureType::Dict(key, value) => {
+                let depth = limits::enter(depth).map_err(|error| error.to_string())?;
+                buf.align_mut::<u64>();
+
+                for ty in key.iter() {
+                    self.store(buf, ty, depth)?;
+                }
+
+                self.expect(b':')?;
+
+                for ty in value.iter() {
+                    self.store(buf, ty, depth)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Store a variant written as `<signature>:<value>`.
+    fn store_variant(&mut self, buf: &mut BodyBuf, depth: usize) -> Result<(), String> {
+        self.skip_whitespace();
+
+        let rest = &self.input[self.pos..];
+        let end = rest.find(':').unwrap_or(rest.len());
+
+        let signature = match Signature::new(&rest[..end]) {
+            Ok(signature) if signature.iter().count() == 1 => signature,
+            _ => return Err(expected("single complete type", &rest[..end])),
+        };
+
+        self.pos += end;
+        self.expect(b':')?;
+
+        buf.write_only(signature);
+
+        for ty in signature.iter() {
+            self.store(buf, ty, depth)?;
+        }
+
+        Ok(())
+    }
+
+    /// Read a single token, which is either a quoted string or a sequence of
+    /// characters up until the next delimiter.
+    fn token(&mut self) -> Result<String, String> {
+        let rest = &self.input[self.pos..];
+
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let mut out = String::new();
+            let mut chars = quoted.char_indices();
+
+            while let Some((n, c)) = chars.next() {
+                match c {
+                    '"' => {
+                        self.pos += n + 2;
+                        return Ok(out);
+                    }
+                    '\\' => match chars.next() {
+                        Some((_, '"')) => out.push('"'),
+                        Some((_, '\\')) => out.push('\\'),
+                        Some((_, 'n')) => out.push('\n'),
+                        Some((_, 't')) => out.push('\t'),
+                        Some((m, c)) => {
+                            self.pos += m + 1;
+                            return Err(format!("unsupported escape `\\{c}`"));
+                        }
+                        None => break,
+                    },
+                    c => out.push(c),
+                }
+            }
+
+            self.pos = self.input.len();
+            return Err("unterminated string".into());
+        }
+
+        let end = rest
+            .find(|c: char| c.is_whitespace() || ",:[](){}\"".contains(c))
+            .unwrap_or(rest.len());
+
+        if end == 0 {
+            return Err(match rest.chars().next() {
+                Some(c) => format!("expected value, but found `{c}`"),
+                None => "expected value, but found end of input".into(),
+            });
+        }
+
+        self.pos += end;
+        Ok(rest[..end].to_owned())
+    }
+
+    /// Consume the given byte if it is next, ignoring leading whitespace.
+    fn eat(&mut self, b: u8) -> bool {
+        self.skip_whitespace();
+
+        if self.input.as_bytes().get(self.pos) == Some(&b) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Expect the given byte to be next, ignoring leading whitespace.
+    fn expect(&mut self, b: u8) -> Result<(), String> {
+        if self.eat(b) {
+            return Ok(());
+        }
+
+        Err(match self.input[self.pos..].chars().next() {
+            Some(c) => format!("expected `{}`, but found `{c}`", b as char),
+            None => format!("expected `{}`, but found end of input", b as char),
+        })
+    }
+
+    /// Expect there to be no more input, except for whitespace.
+    fn expect_end(&mut self) -> Result<(), String> {
+        self.skip_whitespace();
+
+        match self.input[self.pos..].chars().next() {
+            Some(c) => Err(format!("expected end of input, but found `{c}`")),
+            None => Ok(()),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+}
+
+fn number<T>(argument: &str, what: &str) -> Result<T, String>
+where
+    T: FromStr,
+{
+    argument.parse().map_err(|_| expected(what, argument))
+}
+
+fn expected(what: &str, argument: &str) -> String {
+    format!("expected {what}, but found `{argument}`")
+}
+
+fn invalid(index: usize, message: String) -> Error {
+    Error::new(ErrorKind::InvalidArgument(index, message.into()))
+}
@@ -2,8 +2,9 @@ use std::marker::PhantomData;
 use std::mem::ManuallyDrop;
 
 use crate::buf::Alloc;
+use crate::error::Result;
 use crate::ty;
-use crate::{BodyBuf, Storable};
+use crate::{limits, BodyBuf, Storable};
 
 use super::StoreStruct;
 
@@ -12,6 +13,7 @@ use super::StoreStruct;
 /// See [`BodyBuf::store_array`].
 ///
 /// [`BodyBuf::store_array`]: crate::BodyBuf::store_array
+#[must_use = "Must call `finish` after writing all elements"]
 pub struct StoreArray<'a, T>
 where
     T: ty::Aligned,
@@ -28,6 +30,9 @@ where
 {
     pub(crate) fn new(buf: &'a mut BodyBuf) -> Self {
         let len = buf.alloc();
+        // NB: Padding up until the first element is not included in the
+        // length of the array, but it is always present.
+        buf.align_mut::<T::Alignment>();
         let start = buf.len();
 
         Self {
@@ -40,22 +45,28 @@ where
 
     /// Finish writing the array.
     ///
-    /// This will also be done implicitly once this is dropped.
+    /// The length of the array is only written once this is called.
     ///
     /// See [`BodyBuf::store_array`].
     ///
     /// [`BodyBuf::store_array`]: crate::BodyBuf::store_array
+    ///
+    /// # Errors
+    ///
+    /// Errors if the written array exceeds [`MAX_ARRAY_LENGTH`].
+    ///
+    /// [`MAX_ARRAY_LENGTH`]: crate::limits::MAX_ARRAY_LENGTH
     #[inline]
-    pub fn finish(self) {
-        ManuallyDrop::new(self).finalize();
+    pub fn finish(self) -> Result<()> {
+        ManuallyDrop::new(self).finalize()
     }
 
     #[inline(always)]
-    fn finalize(&mut self) {
-        let end = self.buf.len();
-        let len = (end - self.start) as u32;
-        self.buf.store_at(self.len, len);
-        self.buf.align_mut::<T::Alignment>();
+    fn finalize(&mut self) -> Result<()> {
+        let len = self.buf.len() - self.start;
+        limits::check_array_length(len)?;
+        self.buf.store_at(self.len, len as u32);
+        Ok(())
     }
 }
 
@@ -111,10 +122,16 @@ impl StoreArray<'_, u8> {
     /// See [`BodyBuf::store_array`].
     ///
     /// [`BodyBuf::store_array`]: crate::BodyBuf::store_array
+    ///
+    /// # Errors
+    ///
+    /// Errors if the written array exceeds [`MAX_ARRAY_LENGTH`].
+    ///
+    /// [`MAX_ARRAY_LENGTH`]: crate::limits::MAX_ARRAY_LENGTH
     #[inline]
-    pub fn write_slice(self, bytes: &[u8]) {
+    pub fn write_slice(self, bytes: &[u8]) -> Result<()> {
         let mut this = ManuallyDrop::new(self);
         this.buf.extend_from_slice(bytes);
-        this.finalize();
+        this.finalize()
     }
 }
@@ -0,0 +1,82 @@
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+
+use crate::buf::Alloc;
+use crate::error::Result;
+use crate::ty;
+use crate::{limits, BodyBuf, Storable};
+
+/// Write a typed dictionary.
+///
+/// See [`BodyBuf::store_dict`].
+///
+/// [`BodyBuf::store_dict`]: crate::BodyBuf::store_dict
+#[must_use = "Must call `finish` after writing all entries"]
+pub struct StoreDict<'a, K, V> {
+    buf: &'a mut BodyBuf,
+    len: Alloc<u32>,
+    start: usize,
+    _marker: PhantomData<(K, V)>,
+}
+
+impl<'a, K, V> StoreDict<'a, K, V> {
+    pub(crate) fn new(buf: &'a mut BodyBuf) -> Self {
+        let len = buf.alloc();
+        // NB: Dict entries are aligned like structs, and padding up until the
+        // first entry is not included in the length of the array.
+        buf.align_mut::<u64>();
+        let start = buf.len();
+
+        Self {
+            buf,
+            start,
+            len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Finish writing the dictionary.
+    ///
+    /// See [`BodyBuf::store_dict`].
+    ///
+    /// [`BodyBuf::store_dict`]: crate::BodyBuf::store_dict
+    ///
+    /// # Errors
+    ///
+    /// Errors if the written dictionary exceeds [`MAX_ARRAY_LENGTH`].
+    ///
+    /// [`MAX_ARRAY_LENGTH`]: crate::limits::MAX_ARRAY_LENGTH
+    #[inline]
+    pub fn finish(self) -> Result<()> {
+        ManuallyDrop::new(self).finalize()
+    }
+
+    #[inline(always)]
+    fn finalize(&mut self) -> Result<()> {
+        let len = self.buf.len() - self.start;
+        limits::check_array_length(len)?;
+        self.buf.store_at(self.len, len as u32);
+        Ok(())
+    }
+}
+
+impl<K, V> StoreDict<'_, K, V>
+where
+    K: ty::Marker,
+    V: ty::Marker,
+{
+    /// Store an entry in the dictionary.
+    ///
+    /// See [`BodyBuf::store_dict`].
+    ///
+    /// [`BodyBuf::store_dict`]: crate::BodyBuf::store_dict
+    pub fn store(&mut self, key: K::Return<'_>, value: V::Return<'_>)
+    where
+        for<'b> K::Return<'b>: Storable,
+        for<'b> V::Return<'b>: Storable,
+    {
+        self.buf.align_mut::<u64>();
+        key.store_to(self.buf);
+        value.store_to(self.buf);
+    }
+}
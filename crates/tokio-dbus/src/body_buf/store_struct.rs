@@ -1,30 +1,90 @@
 use std::marker::PhantomData;
 
+use crate::error::{Error, ErrorKind, Result};
 use crate::ty;
 use crate::{Arguments, BodyBuf, Storable};
 
-use super::StoreArray;
+use super::{StoreArray, StoreDict};
 
 /// Write a struct.
 ///
 /// See [`BodyBuf::store_struct`].
 ///
 /// [`BodyBuf::store_struct`]: crate::BodyBuf::store_struct
+///
+/// The number of fields stored is checked at compile time. Each call to
+/// [`store`] advances to the next field, and [`finish`] is only available
+/// once every field has been stored.
+///
+/// [`store`]: Self::store
+/// [`finish`]: Self::finish
+///
+/// # Examples
+///
+/// Finishing a struct before all fields have been stored doesn't compile:
+///
+/// ```compile_fail
+/// use tokio_dbus::BodyBuf;
+///
+/// let mut buf = BodyBuf::new();
+/// buf.store_struct::<(u16, u32)>()?.store(10u16).finish()?;
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+///
+/// Neither does storing too many fields:
+///
+/// ```compile_fail
+/// use tokio_dbus::BodyBuf;
+///
+/// let mut buf = BodyBuf::new();
+/// buf.store_struct::<(u16,)>()?.store(10u16).store(20u32).finish()?;
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+///
+/// Or storing a struct without fields, which is not permitted by the
+/// specification:
+///
+/// ```compile_fail
+/// use tokio_dbus::BodyBuf;
+///
+/// let mut buf = BodyBuf::new();
+/// buf.store_struct::<()>()?.finish()?;
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
 #[must_use = "Must call `finish` after writing all related fields"]
 pub struct StoreStruct<'a, T> {
     buf: &'a mut BodyBuf,
+    // Set once the struct has been finished, used to verify that nested
+    // structs are completely written.
+    finished: Option<&'a mut bool>,
     _marker: PhantomData<T>,
 }
 
 impl<'a, T> StoreStruct<'a, T> {
-    pub(crate) fn new(buf: &'a mut BodyBuf) -> Self {
+    pub(crate) fn new(buf: &'a mut BodyBuf) -> Self
+    where
+        T: ty::Fields,
+    {
+        #[allow(clippy::let_unit_value)]
+        let () = NonEmpty::<T>::ASSERT;
+        buf.align_mut::<u64>();
+        Self::inner(buf, None)
+    }
+
+    fn nested(buf: &'a mut BodyBuf, finished: &'a mut bool) -> Self
+    where
+        T: ty::Fields,
+    {
+        #[allow(clippy::let_unit_value)]
+        let () = NonEmpty::<T>::ASSERT;
         buf.align_mut::<u64>();
-        Self::inner(buf)
+        Self::inner(buf, Some(finished))
     }
 
-    pub(crate) fn inner(buf: &'a mut BodyBuf) -> Self {
+    fn inner(buf: &'a mut BodyBuf, finished: Option<&'a mut bool>) -> Self {
         Self {
             buf,
+            finished,
             _marker: PhantomData,
         }
     }
@@ -42,7 +102,7 @@ impl<'a, T> StoreStruct<'a, T> {
     /// buf.store_struct::<(u16, u32)>()?
     ///     .store(10u16)
     ///     .store(10u32)
-    ///     .finish();
+    ///     .finish()?;
     ///
     /// assert_eq!(buf.signature(), b"(qu)");
     /// assert_eq!(buf.get(), &[10, 0, 0, 0, 10, 0, 0, 0]);
@@ -59,7 +119,7 @@ impl<'a, T> StoreStruct<'a, T> {
     ///
     /// buf.store_struct::<(ty::Str,)>()?
     ///     .store("Hello World")
-    ///     .finish();
+    ///     .finish()?;
     ///
     /// assert_eq!(buf.signature(), b"(s)");
     /// assert_eq!(buf.get(), &[11, 0, 0, 0, 72, 101, 108, 108, 111, 32, 87, 111, 114, 108, 100, 0]);
@@ -73,10 +133,10 @@ impl<'a, T> StoreStruct<'a, T> {
         for<'b> <T::First as ty::Marker>::Return<'b>: Storable,
     {
         value.store_to(self.buf);
-        StoreStruct::inner(self.buf)
+        StoreStruct::inner(self.buf, self.finished)
     }
 
-    /// Store a value and return the builder for the next value to store.
+    /// Store all fields of the struct at once, finishing it.
     ///
     /// # Examples
     ///
@@ -86,21 +146,29 @@ impl<'a, T> StoreStruct<'a, T> {
     ///
     /// let mut buf = BodyBuf::with_endianness(Endianness::LITTLE);
     ///
-    /// buf.store_struct::<(u8, u32)>()?.fields((42u8, 42u32));
+    /// buf.store_struct::<(u8, u32)>()?.fields((42u8, 42u32))?;
     ///
     /// assert_eq!(buf.signature(), b"(yu)");
     /// assert_eq!(buf.get(), &[42, 0, 0, 0, 42, 0, 0, 0]);
     /// # Ok::<_, tokio_dbus::Error>(())
     /// ```
     #[inline]
-    pub fn fields(self, arguments: T)
+    pub fn fields(self, arguments: T) -> Result<()>
     where
         T: Arguments,
     {
         arguments.buf_to(self.buf);
+        StoreStruct::<()>::inner(self.buf, self.finished).finish()
     }
 
-    /// Store a value and return the builder for the next value to store.
+    /// Store an array and return the builder for the next value to store.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the `writer` errors, or if the written array exceeds
+    /// [`MAX_ARRAY_LENGTH`].
+    ///
+    /// [`MAX_ARRAY_LENGTH`]: crate::limits::MAX_ARRAY_LENGTH
     ///
     /// # Examples
     ///
@@ -116,49 +184,129 @@ impl<'a, T> StoreStruct<'a, T> {
     ///         w.store(2);
     ///         w.store(3);
     ///         w.store(4);
-    ///     })
-    ///     .finish();
+    ///         Ok(())
+    ///     })?
+    ///     .finish()?;
     ///
     /// assert_eq!(buf.signature(), b"(au)");
     /// assert_eq!(buf.get(), &[16, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0]);
     /// # Ok::<_, tokio_dbus::Error>(())
     /// ```
     #[inline]
-    pub fn store_array<W, U>(self, writer: W) -> StoreStruct<'a, T::Remaining>
+    pub fn store_array<W, U>(self, writer: W) -> Result<StoreStruct<'a, T::Remaining>>
     where
-        W: FnOnce(&mut StoreArray<'_, U>),
+        W: FnOnce(&mut StoreArray<'_, U>) -> Result<()>,
         T: ty::Fields<First = ty::Array<U>>,
         U: ty::Aligned,
     {
         let mut w = StoreArray::new(self.buf);
-        writer(&mut w);
-        w.finish();
-        StoreStruct::inner(self.buf)
+        writer(&mut w)?;
+        w.finish()?;
+        Ok(StoreStruct::inner(self.buf, self.finished))
     }
 
-    /// Store a value and return the builder for the next value to store.
+    /// Store a dictionary and return the builder for the next value to store.
     ///
-    /// See [`BodyBuf::store_struct`].
+    /// See [`BodyBuf::store_dict`].
     ///
-    /// [`BodyBuf::store_struct`]: crate::BodyBuf::store_struct
+    /// [`BodyBuf::store_dict`]: crate::BodyBuf::store_dict
+    ///
+    /// # Errors
+    ///
+    /// Errors if the `writer` errors, or if the written dictionary exceeds
+    /// [`MAX_ARRAY_LENGTH`].
+    ///
+    /// [`MAX_ARRAY_LENGTH`]: crate::limits::MAX_ARRAY_LENGTH
+    #[inline]
+    pub fn store_dict<W, K, V>(self, writer: W) -> Result<StoreStruct<'a, T::Remaining>>
+    where
+        W: FnOnce(&mut StoreDict<'_, K, V>) -> Result<()>,
+        T: ty::Fields<First = ty::Dict<K, V>>,
+        K: ty::Marker,
+        V: ty::Marker,
+    {
+        let mut w = StoreDict::new(self.buf);
+        writer(&mut w)?;
+        w.finish()?;
+        Ok(StoreStruct::inner(self.buf, self.finished))
+    }
+
+    /// Store a nested struct and return the builder for the next value to
+    /// store.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the `writer` errors, or if it returns without finishing the
+    /// nested struct.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, Endianness};
+    ///
+    /// let mut buf = BodyBuf::with_endianness(Endianness::LITTLE);
+    ///
+    /// buf.store_struct::<(u8, (u16, u32))>()?
+    ///     .store(1u8)
+    ///     .store_struct(|w| w.store(2u16).store(3u32).finish())?
+    ///     .finish()?;
+    ///
+    /// assert_eq!(buf.signature(), b"(y(qu))");
+    /// assert_eq!(buf.get(), &[1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0]);
+    ///
+    /// let error = buf
+    ///     .store_struct::<(u8, (u16, u32))>()?
+    ///     .store(1u8)
+    ///     .store_struct(|w| {
+    ///         _ = w.store(2u16);
+    ///         Ok(())
+    ///     })
+    ///     .err();
+    ///
+    /// assert!(error.is_some());
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
     #[inline]
-    pub fn store_struct<W>(self, writer: W) -> StoreStruct<'a, T::Remaining>
+    pub fn store_struct<W>(self, writer: W) -> Result<StoreStruct<'a, T::Remaining>>
     where
-        W: FnOnce(&mut StoreStruct<'_, T::First>),
+        W: FnOnce(StoreStruct<'_, T::First>) -> Result<()>,
         T: ty::Fields,
         T::First: ty::Fields,
     {
-        let mut w = StoreStruct::new(self.buf);
-        writer(&mut w);
-        StoreStruct::inner(self.buf)
+        let mut finished = false;
+        writer(StoreStruct::nested(self.buf, &mut finished))?;
+
+        if !finished {
+            return Err(Error::new(ErrorKind::StructIncomplete));
+        }
+
+        Ok(StoreStruct::inner(self.buf, self.finished))
     }
 }
 
 impl StoreStruct<'_, ()> {
     /// Finish writing the struct.
     ///
+    /// This is only available once every field of the struct has been stored.
+    ///
     /// See [`BodyBuf::store_struct`].
     ///
     /// [`BodyBuf::store_struct`]: crate::BodyBuf::store_struct
-    pub fn finish(self) {}
+    pub fn finish(self) -> Result<()> {
+        if let Some(finished) = self.finished {
+            *finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Compile time assertion that a struct has at least one field.
+struct NonEmpty<T>(PhantomData<T>);
+
+impl<T> NonEmpty<T>
+where
+    T: ty::Fields,
+{
+    const ASSERT: () = assert!(T::LEN > 0, "Structs must have at least one field");
 }
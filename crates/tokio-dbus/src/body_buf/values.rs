@@ -0,0 +1,376 @@
+use tokio_dbus_core::signature::Type as SignatureType;
+
+use crate::buf::MAX_ARRAY_LENGTH;
+use crate::error::{ErrorKind, Result};
+use crate::limits;
+use crate::proto::Type;
+use crate::signature::SignatureBuilder;
+use crate::{BodyBuf, Error, Signature, UnixFd, Value};
+
+impl BodyBuf {
+    /// Store values in the buffer, encoded according to the types in
+    /// `signature`.
+    ///
+    /// Each complete type in the signature consumes one value, which must be
+    /// of a matching type. Arrays of dict entries are stored from
+    /// [`Value::Dict`], and variants can hold any value. Since [`Value`] is
+    /// flattened, the signature of a variant is derived from the value it
+    /// holds, where empty arrays are typed as `av` and empty dictionaries as
+    /// `a{sv}`.
+    ///
+    /// If storing fails the buffer is left unmodified.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the number of values doesn't match the signature, or if a
+    /// value doesn't match its corresponding type. The error indicates which
+    /// value is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, Signature, Value};
+    ///
+    /// let mut buf = BodyBuf::new();
+    ///
+    /// buf.store_values(
+    ///     Signature::new("sa{sv}")?,
+    ///     &[
+    ///         Value::String(String::from("Home")),
+    ///         Value::Dict(vec![(Value::String(String::from("id")), Value::U32(1))]),
+    ///     ],
+    /// )?;
+    ///
+    /// assert_eq!(buf.signature(), "sa{sv}");
+    ///
+    /// let error = buf
+    ///     .store_values(Signature::new("u")?, &[Value::String(String::from("Home"))])
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "Invalid argument #0: expected 32-bit unsigned integer, but found string"
+    /// );
+    ///
+    /// assert_eq!(buf.signature(), "sa{sv}");
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn store_values(&mut self, signature: &Signature, values: &[Value]) -> Result<()> {
+        let checkpoint = self.checkpoint();
+
+        if let Err(error) = store_all(self, signature, values) {
+            self.restore(checkpoint);
+            return Err(error);
+        }
+
+        if let Err(error) = self.extend_signature(signature) {
+            self.restore(checkpoint);
+            return Err(error);
+        }
+
+        Ok(())
+    }
+}
+
+fn store_all(buf: &mut BodyBuf, signature: &Signature, values: &[Value]) -> Result<()> {
+    let count = values.len();
+    let mut values = values.iter().enumerate();
+
+    for ty in signature.iter() {
+        let Some((index, value)) = values.next() else {
+            return Err(invalid(
+                count,
+                format!("missing value for `{}`", signature.as_str()),
+            ));
+        };
+
+        store(buf, ty, value, 0).map_err(|message| invalid(index, message))?;
+    }
+
+    if let Some((index, _)) = values.next() {
+        return Err(invalid(
+            index,
+            format!("too many values for `{}`", signature.as_str()),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Store a value of type `ty`.
+fn store(
+    buf: &mut BodyBuf,
+    ty: SignatureType<'_>,
+    value: &Value,
+    depth: usize,
+) -> Result<(), String> {
+    match (ty, value) {
+        (SignatureType::Signature(signature), value) => {
+            let Some(&b) = signature.as_bytes().first() else {
+                return Ok(());
+            };
+
+            store_basic(buf, Type::new(b), value, depth)?;
+        }
+        (SignatureType::Array(element), Value::Array(values)) => {
+            let depth = limits::enter(depth).map_err(|error| error.to_string())?;
+
+            if let Some(SignatureType::Dict(..)) = element.iter().next() {
+                return Err(expected(element, value));
+            }
+
+            store_array(buf, element, values, |buf, ty, value| {
+                store(buf, ty, value, depth)
+            })?;
+        }
+        (SignatureType::Array(element), Value::Dict(entries)) => {
+            let depth = limits::enter(depth).map_err(|error| error.to_string())?;
+
+            let Some(SignatureType::Dict(key, value)) = element.iter().next() else {
+                return Err(expected(element, &Value::Dict(Vec::new())));
+            };
+
+            store_array(buf, element, entries, |buf, _, (k, v)| {
+                buf.align_mut::<u64>();
+                store_single(buf, key, k, depth)?;
+                store_single(buf, value, v, depth)
+            })?;
+        }
+        (SignatureType::Struct(fields), Value::Struct(values)) => {
+            let depth = limits::enter(depth).map_err(|error| error.to_string())?;
+
+            if fields.iter().count() != values.len() {
+                return Err(format!(
+                    "expected struct with {} fields, but found {}",
+                    fields.iter().count(),
+                    values.len()
+                ));
+            }
+
+            buf.align_mut::<u64>();
+
+            for (ty, value) in fields.iter().zip(values) {
+                store(buf, ty, value, depth)?;
+            }
+        }
+        (SignatureType::Array(element), value) => {
+            return Err(format!(
+                "expected array of `{}`, but found {}",
+                element.as_str(),
+                kind(value)
+            ));
+        }
+        (SignatureType::Struct(..), value) => {
+            return Err(format!("expected struct, but found {}", kind(value)));
+        }
+        (SignatureType::Dict(..), _) => {
+            return Err("dict entries are only supported in arrays".into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Store an array, where the element type is stored through `f`.
+fn store_array<T>(
+    buf: &mut BodyBuf,
+    element: &Signature,
+    values: &[T],
+    mut f: impl FnMut(&mut BodyBuf, SignatureType<'_>, &T) -> Result<(), String>,
+) -> Result<(), String> {
+    let Some(ty) = element.iter().next() else {
+        return Err(format!("invalid array element `{}`", element.as_str()));
+    };
+
+    let len = buf.alloc::<u32>();
+    buf.align_mut_for(element);
+    let start = buf.len();
+
+    for value in values {
+        f(buf, ty, value)?;
+    }
+
+    let bytes = (buf.len() - start) as u32;
+
+    if bytes > MAX_ARRAY_LENGTH {
+        return Err(format!("array of length {bytes} is too long"));
+    }
+
+    buf.store_at(len, bytes);
+    Ok(())
+}
+
+/// Store a value whose signature is a single complete type.
+fn store_single(
+    buf: &mut BodyBuf,
+    signature: &Signature,
+    value: &Value,
+    depth: usize,
+) -> Result<(), String> {
+    for ty in signature.iter() {
+        store(buf, ty, value, depth)?;
+    }
+
+    Ok(())
+}
+
+fn store_basic(buf: &mut BodyBuf, ty: Type, value: &Value, depth: usize) -> Result<(), String> {
+    match (ty, value) {
+        (Type::BYTE, &Value::U8(value)) => buf.store_frame(value),
+        (Type::BOOLEAN, &Value::Bool(value)) => buf.store_frame(u32::from(value)),
+        (Type::INT16, &Value::I16(value)) => buf.store_frame(value),
+        (Type::UINT16, &Value::U16(value)) => buf.store_frame(value),
+        (Type::INT32, &Value::I32(value)) => buf.store_frame(value),
+        (Type::UINT32, &Value::U32(value)) => buf.store_frame(value),
+        (Type::INT64, &Value::I64(value)) => buf.store_frame(value),
+        (Type::UINT64, &Value::U64(value)) => buf.store_frame(value),
+        (Type::DOUBLE, &Value::F64(value)) => buf.store_frame(value),
+        (Type::UNIX_FD, &Value::UnixFd(value)) => buf.store_unix_fd(UnixFd::new(value)),
+        (Type::STRING, Value::String(value)) => buf.write_only(value.as_str()),
+        (Type::OBJECT_PATH, Value::ObjectPath(value)) => buf.write_only(&**value),
+        (Type::SIGNATURE, Value::Signature(value)) => buf.write_only(&**value),
+        (Type::VARIANT, value) => {
+            let depth = limits::enter(depth).map_err(|error| error.to_string())?;
+
+            let mut signature = SignatureBuilder::new();
+            write_signature(&mut signature, value, depth)?;
+            let signature = signature.to_signature();
+
+            buf.write_only(signature);
+            store_single(buf, signature, value, depth)?;
+        }
+        (ty, value) => return Err(format!("expected {}, but found {}", name(ty), kind(value))),
+    }
+
+    Ok(())
+}
+
+/// Write the signature of a value held in a variant.
+fn write_signature(
+    signature: &mut SignatureBuilder,
+    value: &Value,
+    depth: usize,
+) -> Result<(), String> {
+    let basic = match value {
+        Value::U8(..) => Signature::BYTE,
+        Value::Bool(..) => Signature::BOOLEAN,
+        Value::I16(..) => Signature::INT16,
+        Value::U16(..) => Signature::UINT16,
+        Value::I32(..) => Signature::INT32,
+        Value::U32(..) => Signature::UINT32,
+        Value::I64(..) => Signature::INT64,
+        Value::U64(..) => Signature::UINT64,
+        Value::F64(..) => Signature::DOUBLE,
+        Value::String(..) => Signature::STRING,
+        Value::ObjectPath(..) => Signature::OBJECT_PATH,
+        Value::Signature(..) => Signature::SIGNATURE,
+        Value::UnixFd(..) => Signature::UNIX_FD,
+        Value::Array(values) => {
+            let depth = limits::enter(depth).map_err(|error| error.to_string())?;
+            signature.open_array().map_err(|e| e.to_string())?;
+
+            match values.first() {
+                Some(value) => write_signature(signature, value, depth)?,
+                None => extend(signature, Signature::VARIANT)?,
+            }
+
+            signature.close_array();
+            return Ok(());
+        }
+        Value::Dict(entries) => {
+            let depth = limits::enter(depth).map_err(|error| error.to_string())?;
+            signature.open_array().map_err(|e| e.to_string())?;
+            signature.open_dict().map_err(|e| e.to_string())?;
+
+            match entries.first() {
+                Some((key, value)) => {
+                    write_signature(signature, key, depth)?;
+                    write_signature(signature, value, depth)?;
+                }
+                None => {
+                    extend(signature, Signature::STRING)?;
+                    extend(signature, Signature::VARIANT)?;
+                }
+            }
+
+            signature.close_dict().map_err(|e| e.to_string())?;
+            signature.close_array();
+            return Ok(());
+        }
+        Value::Struct(values) => {
+            let depth = limits::enter(depth).map_err(|error| error.to_string())?;
+            signature.open_struct().map_err(|e| e.to_string())?;
+
+            for value in values {
+                write_signature(signature, value, depth)?;
+            }
+
+            signature.close_struct().map_err(|e| e.to_string())?;
+            return Ok(());
+        }
+    };
+
+    extend(signature, basic)
+}
+
+fn extend(signature: &mut SignatureBuilder, other: &Signature) -> Result<(), String> {
+    if !signature.extend_from_signature(other) {
+        return Err("signature is too long".into());
+    }
+
+    Ok(())
+}
+
+/// The name of a basic type.
+fn name(ty: Type) -> &'static str {
+    match ty {
+        Type::BYTE => "byte",
+        Type::BOOLEAN => "boolean",
+        Type::INT16 => "16-bit signed integer",
+        Type::UINT16 => "16-bit unsigned integer",
+        Type::INT32 => "32-bit signed integer",
+        Type::UINT32 => "32-bit unsigned integer",
+        Type::INT64 => "64-bit signed integer",
+        Type::UINT64 => "64-bit unsigned integer",
+        Type::DOUBLE => "double",
+        Type::STRING => "string",
+        Type::OBJECT_PATH => "object path",
+        Type::SIGNATURE => "signature",
+        Type::UNIX_FD => "unix file descriptor index",
+        _ => "unsupported type",
+    }
+}
+
+/// The kind of a value.
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::U8(..) => "byte",
+        Value::Bool(..) => "boolean",
+        Value::I16(..) => "16-bit signed integer",
+        Value::U16(..) => "16-bit unsigned integer",
+        Value::I32(..) => "32-bit signed integer",
+        Value::U32(..) => "32-bit unsigned integer",
+        Value::I64(..) => "64-bit signed integer",
+        Value::U64(..) => "64-bit unsigned integer",
+        Value::F64(..) => "double",
+        Value::String(..) => "string",
+        Value::ObjectPath(..) => "object path",
+        Value::Signature(..) => "signature",
+        Value::UnixFd(..) => "unix file descriptor index",
+        Value::Array(..) => "array",
+        Value::Struct(..) => "struct",
+        Value::Dict(..) => "dictionary",
+    }
+}
+
+fn expected(element: &Signature, value: &Value) -> String {
+    format!(
+        "expected array of `{}`, but found {}",
+        element.as_str(),
+        kind(value)
+    )
+}
+
+fn invalid(index: usize, message: String) -> Error {
+    Error::new(ErrorKind::InvalidArgument(index, message.into()))
+}
@@ -0,0 +1,113 @@
+use std::num::NonZeroU32;
+
+use crate::error::{Error, ErrorKind, Result};
+use crate::{Message, SendBuf};
+
+/// The offset of the serial in an encoded message header.
+const SERIAL_OFFSET: usize = 8;
+
+/// A message which has been encoded once so that it can be queued to multiple
+/// destinations.
+///
+/// This is useful when the same signal should be emitted both on the bus and
+/// to a number of peers over direct connections. The message is serialized
+/// once when the broadcast is constructed, and writing it to a connection is a
+/// plain copy where only the serial is replaced with the next serial of that
+/// connection.
+///
+/// The serial of the message used to construct the broadcast is ignored.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_dbus::{BodyBuf, Broadcast, Connection, ObjectPath, SendBuf};
+///
+/// const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/DBusExample");
+///
+/// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+/// let mut bus = Connection::session_bus().await?;
+/// let mut peers: Vec<Connection> = Vec::new();
+///
+/// let mut send = SendBuf::new();
+/// let mut body = BodyBuf::new();
+/// body.store(42u32)?;
+///
+/// let broadcast = Broadcast::new(
+///     send.signal("Changed")
+///         .with_path(PATH)
+///         .with_interface("se.tedro.DBusExample")
+///         .with_body(&body),
+/// )?;
+///
+/// for c in std::iter::once(&mut bus).chain(peers.iter_mut()) {
+///     c.write_broadcast(&broadcast)?;
+///     c.flush().await?;
+/// }
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Broadcast {
+    encoded: Box<[u8]>,
+}
+
+impl Broadcast {
+    /// Encode a message for broadcasting.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the message cannot be encoded, such as if its body is too
+    /// long.
+    pub fn new(message: Message<'_>) -> Result<Self> {
+        let mut send = SendBuf::new();
+        send.write_message(message)?;
+
+        Ok(Self {
+            encoded: send.buf().get().into(),
+        })
+    }
+
+    /// Queue the broadcast message in the given send buffer, returning the
+    /// serial it was assigned.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the buffer is in the middle of writing a byte array started
+    /// with [`SendBuf::begin_byte_array`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{Broadcast, SendBuf};
+    ///
+    /// let mut send = SendBuf::new();
+    /// let broadcast = Broadcast::new(send.signal("Tick"))?;
+    ///
+    /// let mut a = SendBuf::new();
+    /// let mut b = SendBuf::new();
+    /// b.next_serial();
+    ///
+    /// assert_eq!(broadcast.write_to(&mut a)?.get(), 1);
+    /// assert_eq!(broadcast.write_to(&mut b)?.get(), 2);
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn write_to(&self, send: &mut SendBuf) -> Result<NonZeroU32> {
+        let remaining = send.remaining_byte_array();
+
+        if remaining > 0 {
+            return Err(Error::new(ErrorKind::ByteArrayIncomplete(remaining)));
+        }
+
+        let serial = send.next_serial();
+        let (head, tail) = self.encoded.split_at(SERIAL_OFFSET);
+        let tail = &tail[4..];
+
+        let buf = send.buf_mut();
+        buf.update_base_align();
+        let start = buf.len();
+        buf.extend_from_slice(head);
+        buf.extend_from_slice(&serial.get().to_ne_bytes());
+        buf.extend_from_slice(tail);
+        send.end_message(start);
+        Ok(serial)
+    }
+}
@@ -33,6 +33,30 @@ impl<'a> Aligned<'a> {
         }
     }
 
+    /// Get the position of the read cursor relative to the base of the
+    /// buffer.
+    #[inline]
+    pub(crate) const fn position(&self) -> usize {
+        self.read
+    }
+
+    /// Construct a copy of this buffer with the read cursor moved back to
+    /// `position`.
+    pub(crate) fn rewind(&self, position: usize) -> Aligned<'a> {
+        assert!(
+            position <= self.read,
+            "requested: {position} > read: {}",
+            self.read
+        );
+
+        Self {
+            data: self.data,
+            read: position,
+            written: self.written,
+            _marker: PhantomData,
+        }
+    }
+
     /// Get a slice out of the buffer that has ben written to.
     pub(crate) fn get(&self) -> &'a [u8] {
         unsafe {
@@ -55,11 +79,20 @@ impl<'a> Aligned<'a> {
 
     /// Read `len` bytes from the buffer and make accessible through another
     /// [`Aligned`] instance constituting that sub-slice.
+    ///
+    /// The sub-slice shares the base of this buffer, so that alignment is
+    /// preserved in the sub-slice.
     pub(crate) fn read_until(&mut self, n: usize) -> Aligned<'a> {
         assert!(n <= self.len(), "requested: {n} > length: {}", self.len());
-        let data = unsafe { ptr::NonNull::new_unchecked(self.data.as_ptr().add(self.read)) };
+        let read = self.read;
         self.read += n;
-        Aligned::new(data, n)
+
+        Self {
+            data: self.data,
+            read,
+            written: self.read,
+            _marker: PhantomData,
+        }
     }
 
     /// Load a frame of the given type.
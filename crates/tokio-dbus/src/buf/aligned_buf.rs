@@ -164,6 +164,13 @@ impl AlignedBuf {
         self.len = 0;
     }
 
+    /// Truncate the buffer to `len` bytes.
+    ///
+    /// This has no effect if the buffer is already shorter than `len`.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
     /// Ensure that the buffer has at least `capacity` bytes.
     fn ensure_capacity(&mut self, capacity: usize) {
         if capacity <= self.capacity {
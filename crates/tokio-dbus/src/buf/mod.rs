@@ -15,10 +15,7 @@ mod unaligned_buf;
 pub(crate) use self::alloc::Alloc;
 mod alloc;
 
-/// The maximum length of an array in bytes.
-pub(crate) const MAX_ARRAY_LENGTH: u32 = 1u32 << 26;
-/// The maximum length of a body in bytes.
-pub(crate) const MAX_BODY_LENGTH: u32 = 1u32 << 27;
+pub(crate) use crate::limits::{MAX_ARRAY_LENGTH, MAX_BODY_LENGTH};
 
 use core::mem::align_of;
 
@@ -2,7 +2,7 @@ use crate::error::Result;
 use crate::proto::{self, Header};
 use crate::proto::{Endianness, Flags, MessageType};
 use crate::ty;
-use crate::{BodyBuf, Signature, Variant};
+use crate::{BodyBuf, ObjectPath, RecvBuf, SendBuf, Signature, Variant, Visitor};
 
 #[rustfmt::skip]
 const LE_BLOB: [u8; 36] = [
@@ -126,15 +126,15 @@ fn write_blob(buf: &mut BodyBuf) -> Result<()> {
         .store_struct()
         .store(proto::Variant::REPLY_SERIAL)
         .store(Variant::U32(0xabcdef12u32))
-        .finish();
+        .finish()?;
 
     array
         .store_struct()
         .store(proto::Variant::SIGNATURE)
         .store(Variant::Signature(Signature::UINT32))
-        .finish();
+        .finish()?;
 
-    array.finish();
+    array.finish()?;
 
     buf.store(0xdeadbeefu32)?;
     Ok(())
@@ -217,3 +217,652 @@ fn test_nested_read_buf() -> Result<()> {
     assert_eq!(buf.get(), &[3, 4, 0]);
     Ok(())
 }
+
+#[test]
+fn test_array_padding() -> Result<()> {
+    let mut buf = BodyBuf::with_endianness(Endianness::LITTLE);
+    buf.store(1u8)?;
+
+    let mut array = buf.store_array::<u64>()?;
+    array.store(2u64);
+    array.finish()?;
+
+    let mut array = buf.store_array::<(u8, ty::Str)>()?;
+    array.store_struct().store(3u8).store("foo").finish()?;
+    array.finish()?;
+
+    assert_eq!(buf.signature(), b"yata(ys)");
+    assert_eq!(
+        buf.get(),
+        &[
+            1, 0, 0, 0, 8, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 12, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 3,
+            0, 0, 0, 102, 111, 111, 0
+        ]
+    );
+
+    let mut body = buf.as_body();
+    assert_eq!(body.load::<u8>()?, 1);
+
+    let values = body.load_array::<u64>()?.collect::<Result<Vec<_>>>()?;
+    assert_eq!(values, [2]);
+
+    let values = body
+        .load_array::<(u8, ty::Str)>()?
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(values, [(3, "foo")]);
+    assert!(body.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_nested_array_padding() -> Result<()> {
+    let mut buf = BodyBuf::with_endianness(Endianness::LITTLE);
+
+    let mut array = buf.store_array::<ty::Array<u64>>()?;
+    let mut inner = array.store_array();
+    inner.store(1u64);
+    inner.finish()?;
+    array.finish()?;
+
+    // NB: Arrays are aligned to their length prefix, so the inner array
+    // starts right after the length of the outer array, while its elements
+    // are padded to 8 bytes.
+    assert_eq!(buf.signature(), b"aat");
+    assert_eq!(
+        buf.get(),
+        &[12, 0, 0, 0, 8, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0]
+    );
+
+    let mut body = buf.as_body();
+    let mut array = body.load_array::<ty::Array<u64>>()?;
+    let inner = array.load_array()?.expect("missing inner array");
+    let values = inner.collect::<Result<Vec<_>>>()?;
+    assert_eq!(values, [1]);
+    assert!(array.load_array()?.is_none());
+    assert!(body.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_marker_alignment() {
+    fn alignment<T>() -> usize
+    where
+        T: ty::Aligned,
+    {
+        std::mem::size_of::<T::Alignment>()
+    }
+
+    assert_eq!(alignment::<ty::Str>(), 4);
+    assert_eq!(alignment::<ty::ObjectPath>(), 4);
+    assert_eq!(alignment::<ty::Signature>(), 1);
+    assert_eq!(alignment::<ty::Variant>(), 1);
+    assert_eq!(alignment::<ty::Array<u64>>(), 4);
+    assert_eq!(alignment::<ty::Dict<u8, u8>>(), 4);
+    assert_eq!(alignment::<(u8, u8)>(), 8);
+}
+
+#[test]
+fn test_try_from_body() -> Result<()> {
+    let mut buf = BodyBuf::new();
+    buf.store(42u32)?;
+    buf.store("hello")?;
+
+    let (a, b): (u32, &str) = buf.as_body().try_into()?;
+    assert_eq!(a, 42);
+    assert_eq!(b, "hello");
+
+    let error = <(u32, u32)>::try_from(buf.as_body()).unwrap_err();
+    assert!(error.is_signature_mismatch());
+    assert!(<(u32,)>::try_from(buf.as_body()).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_walk() -> Result<()> {
+    #[derive(Default)]
+    struct Events(Vec<String>);
+
+    impl<'de> Visitor<'de> for Events {
+        fn visit_u8(&mut self, value: u8) -> Result<()> {
+            self.0.push(format!("y {value}"));
+            Ok(())
+        }
+
+        fn visit_u32(&mut self, value: u32) -> Result<()> {
+            self.0.push(format!("u {value}"));
+            Ok(())
+        }
+
+        fn visit_str(&mut self, value: &'de str) -> Result<()> {
+            self.0.push(format!("s {value}"));
+            Ok(())
+        }
+
+        fn visit_object_path(&mut self, value: &'de ObjectPath) -> Result<()> {
+            self.0.push(format!("o {value}"));
+            Ok(())
+        }
+
+        fn enter_array(&mut self, element: &Signature) -> Result<()> {
+            self.0.push(format!("a {}", element.as_str()));
+            Ok(())
+        }
+
+        fn leave_array(&mut self) -> Result<()> {
+            self.0.push("end a".to_owned());
+            Ok(())
+        }
+
+        fn enter_dict_entry(&mut self, key: &Signature, value: &Signature) -> Result<()> {
+            self.0.push(format!("{{{}{}", key.as_str(), value.as_str()));
+            Ok(())
+        }
+
+        fn leave_dict_entry(&mut self) -> Result<()> {
+            self.0.push("}".to_owned());
+            Ok(())
+        }
+
+        fn enter_variant(&mut self, signature: &'de Signature) -> Result<()> {
+            self.0.push(format!("v {}", signature.as_str()));
+            Ok(())
+        }
+
+        fn leave_variant(&mut self) -> Result<()> {
+            self.0.push("end v".to_owned());
+            Ok(())
+        }
+    }
+
+    let mut buf = BodyBuf::with_endianness(Endianness::BIG);
+    buf.store(7u8)?;
+
+    let mut array = buf.store_array::<(ty::Str, ty::Variant)>()?;
+    array
+        .store_struct()
+        .store("a")
+        .store(Variant::String("b"))
+        .finish()?;
+    array
+        .store_struct()
+        .store("c")
+        .store(Variant::U32(42))
+        .finish()?;
+    array.finish()?;
+
+    buf.store(ObjectPath::ROOT)?;
+
+    let mut events = Events::default();
+    let mut body = buf.as_body();
+    body.walk(Signature::new("ya{sv}o")?, &mut events)?;
+    assert!(body.is_empty());
+
+    assert_eq!(
+        events.0,
+        [
+            "y 7", "a {sv}", "{sv", "s a", "v s", "s b", "end v", "}", "{sv", "s c", "v u", "u 42",
+            "end v", "}", "end a", "o /"
+        ]
+    );
+
+    let mut body = buf.as_body();
+    assert!(body.walk(Signature::new("ya{sv}ot")?, &mut events).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_store_parsed() -> Result<()> {
+    let mut buf = BodyBuf::with_endianness(Endianness::LITTLE);
+    buf.store_parsed(Signature::new("ubs")?, &["10", "true", "hello"])?;
+
+    assert_eq!(buf.signature(), "ubs");
+
+    let mut body = buf.as_body();
+    assert_eq!(body.load::<u32>()?, 10);
+    assert_eq!(body.load::<u32>()?, 1);
+    assert_eq!(body.read::<str>()?, "hello");
+
+    assert!(buf.store_parsed(Signature::new("y")?, &["256"]).is_err());
+    assert!(buf.store_parsed(Signature::new("uu")?, &["1"]).is_err());
+    assert!(buf.store_parsed(Signature::new("u")?, &["1", "2"]).is_err());
+    assert_eq!(buf.signature(), "ubs");
+    assert_eq!(buf.len(), 18);
+    Ok(())
+}
+
+#[test]
+fn test_store_parsed_containers() -> Result<()> {
+    let mut buf = BodyBuf::with_endianness(Endianness::LITTLE);
+
+    buf.store_parsed(
+        Signature::new("ya(yt)a{sv}v")?,
+        &["1", "[(2, 3)]", r#"{"a b": s:"\"c\"", d: u:5}"#, "s:e"],
+    )?;
+
+    assert_eq!(buf.signature(), "ya(yt)a{sv}v");
+
+    // NB: Dict entries have the same layout as structs.
+    let mut expected = BodyBuf::with_endianness(Endianness::LITTLE);
+    expected.store(1u8)?;
+
+    let mut array = expected.store_array::<(u8, u64)>()?;
+    array.store_struct().store(2u8).store(3u64).finish()?;
+    array.finish()?;
+
+    let mut array = expected.store_array::<(ty::Str, ty::Variant)>()?;
+    array
+        .store_struct()
+        .store("a b")
+        .store(Variant::String("\"c\""))
+        .finish()?;
+    array
+        .store_struct()
+        .store("d")
+        .store(Variant::U32(5))
+        .finish()?;
+    array.finish()?;
+
+    expected.store(Variant::String("e"))?;
+
+    assert_eq!(buf.get(), expected.get());
+
+    let error = buf
+        .store_parsed(Signature::new("a(ys)")?, &["[(1, a), (2 b)]"])
+        .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "Invalid argument #0: at position 12: expected `,`, but found `b`"
+    );
+
+    let error = buf
+        .store_parsed(Signature::new("v")?, &["uu:1"])
+        .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "Invalid argument #0: at position 0: expected single complete type, but found `uu`"
+    );
+
+    let error = buf
+        .store_parsed(Signature::new("a(ys)")?, &[r#"[(1, "a\qb")]"#])
+        .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "Invalid argument #0: at position 8: unsupported escape `\\q`"
+    );
+
+    assert_eq!(buf.signature(), "ya(yt)a{sv}v");
+    assert_eq!(buf.get(), expected.get());
+    Ok(())
+}
+
+#[test]
+fn test_stale_message_ref() -> Result<()> {
+    let mut send = SendBuf::new();
+
+    for member in ["First", "Second"] {
+        let m = send.signal(member);
+        send.write_message(m)?;
+    }
+
+    let mut bytes = Vec::new();
+    send.write_to(&mut bytes)?;
+    let mut reader = &bytes[..];
+
+    let mut recv = RecvBuf::new();
+    let message_ref = recv.read_from(&mut reader)?;
+    assert_eq!(recv.last_message_ref()?.serial(), message_ref.serial());
+
+    recv.clear();
+    assert!(recv
+        .read_message(&message_ref)
+        .unwrap_err()
+        .is_stale_message());
+
+    recv.read_from(&mut reader)?;
+    assert!(recv
+        .read_message(&message_ref)
+        .unwrap_err()
+        .is_stale_message());
+
+    let other = RecvBuf::new();
+    assert!(other
+        .read_message(&message_ref)
+        .unwrap_err()
+        .is_stale_message());
+    Ok(())
+}
+
+#[test]
+fn test_notification_body() -> Result<()> {
+    use crate::org_freedesktop_notifications::Notification;
+
+    let hints = [
+        ("urgency", Variant::U8(2)),
+        ("resident", Variant::Bool(true)),
+        ("desktop-entry", Variant::String("example")),
+    ];
+
+    let n = Notification::new("Summary", "Body")
+        .with_app_name("app")
+        .with_replaces_id(7)
+        .with_actions(&[("default", "Open"), ("dismiss", "Dismiss")])
+        .with_hints(&hints)
+        .with_expire_timeout(1000);
+
+    let mut buf = BodyBuf::new();
+    n.store_to(&mut buf)?;
+
+    assert_eq!(buf.signature(), "susssasa{sv}i");
+
+    let mut body = buf.as_body();
+    let (app_name, replaces_id, app_icon, summary, text) =
+        body.load_struct::<(ty::Str, u32, ty::Str, ty::Str, ty::Str)>()?;
+    assert_eq!(app_name, "app");
+    assert_eq!(replaces_id, 7);
+    assert_eq!(app_icon, "");
+    assert_eq!(summary, "Summary");
+    assert_eq!(text, "Body");
+
+    let actions = body.load_array::<ty::Str>()?.collect::<Result<Vec<_>>>()?;
+    assert_eq!(actions, ["default", "Open", "dismiss", "Dismiss"]);
+
+    let entries = body
+        .load_dict::<ty::Str, ty::Variant>()?
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(entries, hints);
+
+    assert_eq!(body.load::<i32>()?, 1000);
+    assert!(body.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_append() -> Result<()> {
+    let mut payload = BodyBuf::with_endianness(Endianness::BIG);
+    payload.store_parsed(Signature::new("a(yt)v")?, &["[(1, 2), (3, 4)]", "s:foo"])?;
+
+    let mut buf = BodyBuf::new();
+    buf.store(1u8)?;
+    buf.append(&payload.as_body())?;
+    buf.store(5u16)?;
+
+    assert_eq!(buf.signature(), "ya(yt)vq");
+    assert_eq!(
+        buf.as_body().display().to_string(),
+        "byte 1\narray [\n  struct {\n    byte 1\n    uint64 2\n  }\n  struct {\n    byte 3\n    uint64 4\n  }\n]\nvariant string \"foo\"\nuint16 5"
+    );
+
+    // A body which doesn't match its signature leaves the buffer untouched.
+    let mut broken = BodyBuf::new();
+    broken.store(1u8)?;
+    let body = broken.as_body().with_signature(Signature::UINT32);
+
+    let mut truncated = buf.clone();
+    let len = truncated.len();
+    assert!(truncated.append(&body).is_err());
+    assert_eq!(truncated.len(), len);
+    assert_eq!(truncated.signature(), "ya(yt)vq");
+    Ok(())
+}
+
+#[test]
+fn test_append_partially_read() -> Result<()> {
+    let mut payload = BodyBuf::new();
+    payload.arguments((1u8, "foo", 2u32))?;
+
+    let mut body = payload.as_body();
+    assert_eq!(body.load::<u8>()?, 1);
+
+    let mut buf = BodyBuf::new();
+    buf.store(3u16)?;
+    buf.append(&body)?;
+
+    assert_eq!(buf.signature(), "qsu");
+    assert_eq!(buf.as_body().decode::<(u16, &str, u32)>()?, (3, "foo", 2));
+
+    assert_eq!(body.read::<str>()?, "foo");
+    buf.append(&body)?;
+
+    assert_eq!(buf.signature(), "qsuu");
+    assert_eq!(
+        buf.as_body().decode::<(u16, &str, u32, u32)>()?,
+        (3, "foo", 2, 2)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_display_partially_read() -> Result<()> {
+    let mut buf = BodyBuf::new();
+    buf.store_parsed(Signature::new("ysu")?, &["1", "foo", "2"])?;
+
+    let mut body = buf.as_body();
+    assert_eq!(body.load::<u8>()?, 1);
+    assert_eq!(body.display().to_string(), "string \"foo\"\nuint32 2");
+
+    let values = format!("{body:?}");
+    assert!(values.contains("values: [\"foo\", 2]"), "{values}");
+
+    assert_eq!(body.read::<str>()?, "foo");
+    assert_eq!(body.display().to_string(), "uint32 2");
+    Ok(())
+}
+
+#[cfg(feature = "dump")]
+#[test]
+fn test_dump() -> Result<()> {
+    let mut buf = BodyBuf::with_endianness(Endianness::LITTLE);
+    buf.store_parsed(Signature::new("a(yt)v")?, &["[(1, 2)]", "u:3"])?;
+
+    let expected = "\
+0000  10 00 00 00              array a(yt) (16 bytes) [
+0004  00 00 00 00              <padding>
+0008                             struct (yt) {
+0008  01                           byte 1
+0009  00 00 00 00 00 00 00         <padding>
+0010  02 00 00 00 00 00 00 00      uint64 2
+0018                             }
+0018                           ]
+0018  01 75 00                 variant \"u\"
+001b  00                         <padding>
+001c  03 00 00 00                uint32 3";
+
+    assert_eq!(buf.as_body().dump().to_string(), expected);
+
+    let mut buf = BodyBuf::with_endianness(Endianness::LITTLE);
+    buf.store(1u8)?;
+    let body = buf.as_body().with_signature(Signature::new("yu")?);
+
+    let expected = "\
+0000  01                       byte 1
+0001                           <invalid body: Buffer underflow>";
+
+    assert_eq!(body.dump().to_string(), expected);
+    Ok(())
+}
+
+#[test]
+fn test_nested_dicts() -> Result<()> {
+    use std::collections::HashMap;
+
+    use crate::Value;
+
+    for endianness in [Endianness::LITTLE, Endianness::BIG] {
+        let mut buf = BodyBuf::with_endianness(endianness);
+
+        buf.store_parsed(
+            Signature::new("a{sa{sv}}aa{sv}")?,
+            &[
+                "{connection: {id: s:Home, timestamp: t:1700000000}, \
+                  802-11-wireless: {ssid: ay:[72, 111], seen-bssids: as:[]}, \
+                  ipv4: {address-data: aa{sv}:[{address: s:10.0.0.2, prefix: u:24}], \
+                         route: (ua{sv}):(1, {metric: v:i:-1})}}",
+                "[{}, {UUIDs: as:[a, b]}]",
+            ],
+        )?;
+
+        let (settings, devices) = buf.as_body().decode::<(
+            HashMap<String, HashMap<String, Value>>,
+            Vec<HashMap<String, Value>>,
+        )>()?;
+
+        assert_eq!(settings.len(), 3);
+        assert_eq!(settings["connection"]["id"].as_str(), Some("Home"));
+        assert_eq!(settings["connection"]["timestamp"], Value::U64(1700000000));
+
+        let wireless = &settings["802-11-wireless"];
+        assert_eq!(
+            wireless["ssid"],
+            Value::Array(vec![Value::U8(72), Value::U8(111)])
+        );
+        assert_eq!(wireless["seen-bssids"], Value::Array(vec![]));
+
+        let ipv4 = &settings["ipv4"];
+        let addresses = ipv4["address-data"].as_array().unwrap_or_default();
+        assert_eq!(addresses.len(), 1);
+        assert_eq!(
+            addresses[0].get("address").and_then(Value::as_str),
+            Some("10.0.0.2")
+        );
+        assert_eq!(addresses[0].get("prefix"), Some(&Value::U32(24)));
+
+        let Value::Struct(route) = &ipv4["route"] else {
+            panic!("expected struct");
+        };
+
+        assert_eq!(route[0], Value::U32(1));
+        assert_eq!(route[1].get("metric"), Some(&Value::I32(-1)));
+
+        assert_eq!(devices.len(), 2);
+        assert!(devices[0].is_empty());
+
+        assert_eq!(
+            devices[1]["UUIDs"],
+            Value::Array(vec![Value::String("a".into()), Value::String("b".into())])
+        );
+    }
+
+    let mut buf = BodyBuf::new();
+    buf.store_parsed(Signature::new("a{sv}")?, &["{a: u:1}"])?;
+    assert!(buf.as_body().decode::<HashMap<String, u32>>().is_err());
+    assert!(buf.as_body().decode::<Vec<(String, Value)>>().is_err());
+    Ok(())
+}
+
+#[test]
+fn test_body_debug() -> Result<()> {
+    let mut buf = BodyBuf::new();
+    buf.store(42u32)?;
+    buf.store("hello")?;
+    buf.store(7u8)?;
+
+    assert_eq!(
+        format!("{:?}", buf.as_body()),
+        r#"Body { signature: "usy", values: [42, "hello", 7], endianness: NATIVE }"#
+            .replace("NATIVE", &format!("{:?}", Endianness::NATIVE))
+    );
+
+    let mut buf = BodyBuf::with_endianness(Endianness::LITTLE);
+    buf.store_parsed(Signature::new("ayay")?, &["[]", "[1, 2]"])?;
+
+    assert_eq!(
+        format!("{:?}", buf.as_body()),
+        r#"Body { signature: "ayay", data: "00000000020000000102", endianness: LITTLE }"#
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_remaining_signature() -> Result<()> {
+    let mut buf = BodyBuf::with_endianness(Endianness::BIG);
+    buf.store_parsed(Signature::new("y(us)ay")?, &["1", "(2, foo)", "[3]"])?;
+
+    let mut body = buf.as_body();
+    assert_eq!(body.load::<u8>()?, 1);
+    assert_eq!(body.remaining_signature(), "(us)ay");
+
+    body.align::<u64>()?;
+    assert_eq!(body.load::<u32>()?, 2);
+    assert_eq!(body.remaining_signature(), "(us)ay");
+
+    assert_eq!(body.read::<str>()?, "foo");
+    assert_eq!(body.remaining_signature(), "ay");
+
+    let error = body.clone().finish().unwrap_err();
+    assert!(error.is_trailing_body());
+
+    assert_eq!(body.read_byte_array()?, &[3]);
+    assert_eq!(body.remaining_signature(), "");
+    body.finish()?;
+    Ok(())
+}
+
+#[test]
+fn test_duplicate_header_field() -> Result<()> {
+    #[rustfmt::skip]
+    const BLOB: [u8; 44] = [
+        b'l', b'\x02', b'\x02', b'\x01',
+        // bytes in body = 4
+        b'\x04', b'\x00', b'\x00', b'\x00',
+        // serial number = 0x12345678
+        b'\x78', b'\x56', b'\x34', b'\x12',
+        // bytes in array of variable headers = 23
+        b'\x17', b'\0', b'\0', b'\0',
+        // byte 16: in reply to 0xabcdef12
+        b'\x05', b'\x01', b'u', b'\0',
+        b'\x12', b'\xef', b'\xcd', b'\xab',
+        // byte 24: in reply to 0xabcdef13
+        b'\x05', b'\x01', b'u', b'\0',
+        b'\x13', b'\xef', b'\xcd', b'\xab',
+        // byte 32: signature = u
+        b'\x08', b'\x01', b'g', b'\0',
+        b'\x01', b'u', b'\0',
+        // pad to 8-byte boundary for body
+        b'\0',
+        // body; byte 40
+        b'\xef', b'\xbe', b'\xad', b'\xde',
+    ];
+
+    let mut recv = RecvBuf::new();
+    let message_ref = recv.read_from(&mut &BLOB[..])?;
+
+    let error = recv.read_message(&message_ref).unwrap_err();
+    let violation = error.protocol_violation().expect("protocol violation");
+    assert_eq!(violation.offset(), 24);
+    assert_eq!(
+        violation.error().to_string(),
+        "Duplicate REPLY_SERIAL header"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_malformed_signal_header() -> Result<()> {
+    fn violation(m: crate::Message<'_>) -> String {
+        let mut send = SendBuf::new();
+        send.write_message(m).unwrap();
+
+        let mut bytes = Vec::new();
+        send.write_to(&mut bytes).unwrap();
+
+        let mut recv = RecvBuf::new();
+        let message_ref = recv.read_from(&mut &bytes[..]).unwrap();
+        let error = recv.read_message(&message_ref).unwrap_err();
+        let violation = error.protocol_violation().expect("protocol violation");
+        assert!(violation.is_recoverable());
+        violation.error().to_string()
+    }
+
+    let mut send = SendBuf::new();
+
+    let m = send.signal("Tick").with_interface("se.tedro.Test");
+    assert_eq!(violation(m), "Missing required PATH header");
+
+    let m = send.signal("Tick").with_path(ObjectPath::ROOT);
+    assert_eq!(violation(m), "Missing required INTERFACE header");
+    Ok(())
+}
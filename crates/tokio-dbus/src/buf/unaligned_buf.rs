@@ -1,7 +1,9 @@
 use std::alloc::{alloc, dealloc, handle_alloc_error, realloc, Layout};
 use std::mem::size_of;
 use std::ptr;
-use std::slice::{from_raw_parts, from_raw_parts_mut};
+use std::slice::from_raw_parts;
+#[cfg(feature = "tokio")]
+use std::slice::from_raw_parts_mut;
 
 use crate::buf::{max_size_for_align, padding_to};
 use crate::{Frame, Write};
@@ -140,6 +142,7 @@ impl UnalignedBuf {
     }
 
     /// Reserve space for `bytes` additional bytes in the buffer.
+    #[cfg(feature = "tokio")]
     pub(crate) fn reserve_bytes(&mut self, bytes: usize) {
         let requested = self.written + bytes;
         self.ensure_capacity(requested);
@@ -166,6 +169,7 @@ impl UnalignedBuf {
     }
 
     /// Get remaining slice of the buffer that can be written.
+    #[cfg(feature = "tokio")]
     pub(crate) fn get_mut(&mut self) -> &mut [u8] {
         unsafe {
             let len = self.capacity - self.written;
@@ -175,11 +179,13 @@ impl UnalignedBuf {
     }
 
     /// Indicate that we've written `n` bytes to the buffer.
+    #[cfg(feature = "tokio")]
     pub(crate) fn advance_mut(&mut self, n: usize) {
         self.written += n;
     }
 
     /// Read until len bytes.
+    #[cfg(feature = "tokio")]
     pub(crate) fn read_until(&mut self, len: usize) -> &[u8] {
         assert!(len <= self.len());
 
@@ -207,6 +213,17 @@ impl UnalignedBuf {
         self.base = 0;
     }
 
+    /// Truncate the remaining data in the buffer to `len` bytes.
+    ///
+    /// This has no effect if the buffer is already shorter than `len`.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.written = self.written.min(self.read + len);
+
+        if self.read == self.written {
+            self.clear();
+        }
+    }
+
     /// Ensure that the buffer has at least `capacity` bytes.
     fn ensure_capacity(&mut self, capacity: usize) {
         if capacity <= self.capacity {
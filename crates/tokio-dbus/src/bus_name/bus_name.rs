@@ -0,0 +1,137 @@
+use std::fmt;
+
+use crate::limits::MAX_NAME_LENGTH;
+
+use super::BusNameError;
+
+/// A validated bus name, which is either the unique name of a connection or
+/// a well-known name.
+///
+/// Unique names are assigned by the bus, and start with a colon like
+/// `:1.42`. Well-known names are requested by applications, like
+/// `org.freedesktop.DBus`. The following rules define a [valid bus name]:
+///
+/// [valid bus name]: https://dbus.freedesktop.org/doc/dbus-specification.html#message-protocol-names-bus
+///
+/// * The name must be at most 255 bytes long, and must not be empty.
+/// * The name must consist of two or more elements separated by a period
+///   `.`, where no element may be the empty string.
+/// * Each element must only contain the ASCII characters "[A-Z][a-z][0-9]_-".
+/// * Only elements of unique names may begin with a digit.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::BusName;
+///
+/// let name = BusName::new(":1.42")?;
+/// assert!(name.is_unique());
+/// assert_eq!(name, BusName::Unique(":1.42"));
+///
+/// let name = BusName::new("org.freedesktop.DBus")?;
+/// assert!(!name.is_unique());
+/// assert_eq!(name.as_str(), "org.freedesktop.DBus");
+///
+/// assert!(BusName::new("org").is_err());
+/// assert!(BusName::new("org.7zip").is_err());
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BusName<'a> {
+    /// A unique connection name, like `:1.42`.
+    Unique(&'a str),
+    /// A well-known name, like `org.freedesktop.DBus`.
+    WellKnown(&'a str),
+}
+
+impl<'a> BusName<'a> {
+    /// Validate and classify a bus name.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the name is not a valid bus name, see [`BusName`].
+    pub fn new(name: &'a str) -> Result<Self, BusNameError> {
+        if name.len() > MAX_NAME_LENGTH {
+            return Err(BusNameError);
+        }
+
+        if let Some(rest) = name.strip_prefix(':') {
+            if !validate(rest, true) {
+                return Err(BusNameError);
+            }
+
+            return Ok(BusName::Unique(name));
+        }
+
+        if !validate(name, false) {
+            return Err(BusNameError);
+        }
+
+        Ok(BusName::WellKnown(name))
+    }
+
+    /// Test if this is the unique name of a connection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::BusName;
+    ///
+    /// assert!(BusName::new(":1.42")?.is_unique());
+    /// assert!(!BusName::new("se.tedro.Example")?.is_unique());
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    #[inline]
+    pub fn is_unique(&self) -> bool {
+        matches!(self, BusName::Unique(..))
+    }
+
+    /// Get the bus name as a string.
+    #[inline]
+    pub fn as_str(&self) -> &'a str {
+        match *self {
+            BusName::Unique(name) | BusName::WellKnown(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for BusName<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+impl AsRef<str> for BusName<'_> {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+/// Validate the elements of a bus name, where `unique` indicates that
+/// elements may start with a digit.
+fn validate(name: &str, unique: bool) -> bool {
+    let mut count = 0;
+
+    for element in name.split('.') {
+        let Some(first) = element.bytes().next() else {
+            return false;
+        };
+
+        if !unique && first.is_ascii_digit() {
+            return false;
+        }
+
+        if !element
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+        {
+            return false;
+        }
+
+        count += 1;
+    }
+
+    count >= 2
+}
@@ -0,0 +1,16 @@
+use std::error;
+use std::fmt;
+
+/// An error constructing a bus name.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct BusNameError;
+
+impl fmt::Display for BusNameError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid D-Bus bus name")
+    }
+}
+
+impl error::Error for BusNameError {}
@@ -0,0 +1,8 @@
+pub use self::bus_name_error::BusNameError;
+mod bus_name_error;
+
+pub use self::bus_name::BusName;
+mod bus_name;
+
+#[cfg(test)]
+mod tests;
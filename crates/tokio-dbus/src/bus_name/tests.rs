@@ -0,0 +1,26 @@
+use super::BusName;
+
+#[test]
+fn legal_names() {
+    assert!(BusName::new("").is_err());
+    assert!(BusName::new(":").is_err());
+    assert!(BusName::new(":1").is_err());
+    assert!(BusName::new(":1.").is_err());
+    assert!(BusName::new("se").is_err());
+    assert!(BusName::new("se..tedro").is_err());
+    assert!(BusName::new(".se.tedro").is_err());
+    assert!(BusName::new("se.tedro/dbus").is_err());
+    assert!(BusName::new("se.1tedro").is_err());
+    assert!(BusName::new(&format!("se.{}", "a".repeat(253))).is_err());
+
+    assert_eq!(BusName::new(":1.42").unwrap(), BusName::Unique(":1.42"));
+    assert_eq!(
+        BusName::new(":a-b.1_2").unwrap(),
+        BusName::Unique(":a-b.1_2")
+    );
+    assert_eq!(
+        BusName::new("se.tedro-dbus.Example_1").unwrap(),
+        BusName::WellKnown("se.tedro-dbus.Example_1")
+    );
+    assert!(BusName::new(&format!("se.{}", "a".repeat(252))).is_ok());
+}
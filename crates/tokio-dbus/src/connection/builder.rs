@@ -1,7 +1,11 @@
-use crate::error::Result;
-use crate::sasl::{Auth, SaslRequest, SaslResponse};
+use std::future::Future;
+use std::time::Duration;
 
-use super::{Connection, Transport};
+use crate::error::{Error, ErrorKind, Result};
+use crate::sasl::{self, Auth, SaslRequest, SaslResponse};
+use crate::SenderQuota;
+
+use super::{Connection, DefaultHandshake, Handshake, Transport};
 
 enum BusKind {
     Session,
@@ -13,6 +17,7 @@ enum AuthKind {
     None,
     #[cfg(feature = "libc")]
     Uid,
+    External(Box<[u8]>),
 }
 
 impl AuthKind {
@@ -22,10 +27,25 @@ impl AuthKind {
     const DEFAULT: Self = Self::Uid;
 }
 
+/// The default delay between parallel connection attempts, as recommended by
+/// RFC 8305.
+const DEFAULT_CONNECT_DELAY: Duration = Duration::from_millis(250);
+
 /// Builder of a [`Connection`].
 pub struct ConnectionBuilder {
     bus: BusKind,
     auth: AuthKind,
+    auth_timeout: Option<Duration>,
+    hello_timeout: Option<Duration>,
+    connect_delay: Duration,
+    skip_invalid_messages: bool,
+    watchdog: Option<Duration>,
+    deliver_bus_signals: bool,
+    handshake: Box<dyn Handshake + Send + Sync>,
+    sender_quota: Option<SenderQuota>,
+    max_in_flight_calls: Option<usize>,
+    #[cfg(feature = "libc")]
+    kernel_timestamps: bool,
 }
 
 impl ConnectionBuilder {
@@ -42,6 +62,17 @@ impl ConnectionBuilder {
         Self {
             bus: BusKind::Session,
             auth: AuthKind::DEFAULT,
+            auth_timeout: None,
+            hello_timeout: None,
+            connect_delay: DEFAULT_CONNECT_DELAY,
+            skip_invalid_messages: false,
+            watchdog: None,
+            deliver_bus_signals: false,
+            handshake: Box::new(DefaultHandshake),
+            sender_quota: None,
+            max_in_flight_calls: None,
+            #[cfg(feature = "libc")]
+            kernel_timestamps: false,
         }
     }
 
@@ -77,41 +108,445 @@ impl ConnectionBuilder {
         self
     }
 
+    /// Set the SASL authentication to use when connecting.
+    ///
+    /// By default, `EXTERNAL` authentication with the real user id of the
+    /// current process is used. See [`ConnectionBuilder::auth_uid`] and
+    /// [`ConnectionBuilder::auth_external_id`] for convenient ways to
+    /// authenticate with a different identity.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::{Auth, ConnectionBuilder};
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let c = ConnectionBuilder::new()
+    ///     .auth(Auth::external_from_u32_ascii_hex(&mut [0; 32], 1000))
+    ///     .connect()
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn auth(&mut self, auth: Auth<'_>) -> &mut Self {
+        self.auth = match auth {
+            Auth::External(payload) => AuthKind::External(payload.into()),
+        };
+
+        self
+    }
+
+    /// Authenticate using `EXTERNAL` authentication with the given numerical
+    /// user id.
+    ///
+    /// This is useful when running in a user namespace, where the user id
+    /// advertised to the bus must differ from the one of the current process.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::ConnectionBuilder;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let c = ConnectionBuilder::new().auth_uid(1000).connect().await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn auth_uid(&mut self, uid: u32) -> &mut Self {
+        self.auth(Auth::external_from_u32_ascii_hex(&mut [0; 32], uid))
+    }
+
+    /// Authenticate using `EXTERNAL` authentication with an arbitrary
+    /// identity, such as the string form of a Windows SID.
+    ///
+    /// The identity is hex-encoded as required by the protocol.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::ConnectionBuilder;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let c = ConnectionBuilder::new()
+    ///     .auth_external_id("S-1-5-21-1004336348-1177238915-682003330-512")
+    ///     .connect()
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn auth_external_id(&mut self, id: &str) -> &mut Self {
+        let mut payload = Vec::with_capacity(id.len() * 2);
+        sasl::encode_hex(id.as_bytes(), &mut payload);
+        self.auth = AuthKind::External(payload.into());
+        self
+    }
+
+    /// Set the maximum amount of time the SASL authentication exchange is
+    /// allowed to take.
+    ///
+    /// If it takes longer, connecting fails with an error for which
+    /// [`Error::is_timeout`] returns `true`. By default there is no timeout.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use tokio_dbus::ConnectionBuilder;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let c = ConnectionBuilder::new()
+    ///     .auth_timeout(Duration::from_secs(5))
+    ///     .connect()
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn auth_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.auth_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the maximum amount of time to wait for the bus to reply to the
+    /// initial `Hello` message.
+    ///
+    /// If it takes longer, connecting fails with an error for which
+    /// [`Error::is_timeout`] returns `true`. By default there is no timeout.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use tokio_dbus::ConnectionBuilder;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let c = ConnectionBuilder::new()
+    ///     .hello_timeout(Duration::from_secs(5))
+    ///     .connect()
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn hello_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.hello_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the delay between starting connection attempts when the bus address
+    /// lists multiple candidates.
+    ///
+    /// Candidates are attempted in order, where the next attempt is started
+    /// once the delay has elapsed or as soon as the previous attempt fails.
+    /// The first connection to succeed is used and any other attempts are
+    /// cancelled. Defaults to 250 milliseconds.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use tokio_dbus::ConnectionBuilder;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let c = ConnectionBuilder::new()
+    ///     .connect_delay(Duration::from_millis(100))
+    ///     .connect()
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn connect_delay(&mut self, delay: Duration) -> &mut Self {
+        self.connect_delay = delay;
+        self
+    }
+
+    /// Skip received messages which violate the protocol instead of failing.
+    ///
+    /// By default, a message which violates the protocol causes
+    /// [`Connection::wait`] to fail with an error for which
+    /// [`Error::protocol_violation`] returns details. With this enabled, such
+    /// messages are silently skipped as long as the connection can stay in
+    /// sync with its peer, which is useful for monitors that should keep
+    /// running. Violations which can't be recovered from, such as a message
+    /// with an out of bounds length, still cause an error.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::ConnectionBuilder;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let c = ConnectionBuilder::new()
+    ///     .skip_invalid_messages(true)
+    ///     .connect()
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn skip_invalid_messages(&mut self, skip: bool) -> &mut Self {
+        self.skip_invalid_messages = skip;
+        self
+    }
+
+    /// Fail writing messages once queued messages haven't been sent for longer
+    /// than `period`.
+    ///
+    /// This catches the mistake of writing messages without driving the
+    /// connection through [`Connection::wait`] or [`Connection::flush`]. See
+    /// [`SendBuf::set_watchdog`] for details. By default there is no watchdog.
+    ///
+    /// [`SendBuf::set_watchdog`]: crate::SendBuf::set_watchdog
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use tokio_dbus::ConnectionBuilder;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let c = ConnectionBuilder::new()
+    ///     .watchdog(Duration::from_secs(10))
+    ///     .connect()
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn watchdog(&mut self, period: Duration) -> &mut Self {
+        self.watchdog = Some(period);
+        self
+    }
+
+    /// Deliver signals emitted by the `org.freedesktop.DBus` interface, such as
+    /// `NameOwnerChanged` and `NameLost`, to the caller.
+    ///
+    /// By default these signals are only handled internally, such as by
+    /// calling the handler set through [`Connection::on_name_lost`], and are
+    /// never returned by [`Connection::wait`]. The reply to `Hello` is always
+    /// handled internally. Delivered signals can be decoded with
+    /// [`BusSignal::from_message`].
+    ///
+    /// [`BusSignal::from_message`]: crate::org_freedesktop_dbus::BusSignal::from_message
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::ConnectionBuilder;
+    /// use tokio_dbus::org_freedesktop_dbus::BusSignal;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = ConnectionBuilder::new()
+    ///     .deliver_bus_signals(true)
+    ///     .connect()
+    ///     .await?;
+    ///
+    /// c.add_match("type='signal',member='NameOwnerChanged'").await?;
+    ///
+    /// loop {
+    ///     c.wait().await?;
+    ///
+    ///     if let Ok(Some(signal)) = BusSignal::from_message(&c.last_message()?) {
+    ///         println!("{signal:?}");
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn deliver_bus_signals(&mut self, deliver: bool) -> &mut Self {
+        self.deliver_bus_signals = deliver;
+        self
+    }
+
+    /// Record the time at which the kernel received each message, which is
+    /// available through [`Message::kernel_timestamp`].
+    ///
+    /// This enables `SO_TIMESTAMP` on the socket. Note that not every platform
+    /// reports timestamps for stream sockets, notably Linux doesn't, in which
+    /// case no timestamp is available. By default only the monotonic time at
+    /// which a message was read is recorded, see [`MessageRef::received`].
+    ///
+    /// [`Message::kernel_timestamp`]: crate::Message::kernel_timestamp
+    /// [`MessageRef::received`]: crate::MessageRef::received
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::ConnectionBuilder;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let c = ConnectionBuilder::new()
+    ///     .kernel_timestamps(true)
+    ///     .connect()
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    #[cfg(feature = "libc")]
+    pub fn kernel_timestamps(&mut self, enabled: bool) -> &mut Self {
+        self.kernel_timestamps = enabled;
+        self
+    }
+
+    /// Set the quota of deferred messages which can be held for each sender.
+    ///
+    /// See [`SenderQuota`] and [`RecvBuf::set_sender_quota`].
+    ///
+    /// [`RecvBuf::set_sender_quota`]: crate::RecvBuf::set_sender_quota
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::{ConnectionBuilder, SenderQuota};
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let c = ConnectionBuilder::new()
+    ///     .sender_quota(SenderQuota::new().with_max_messages(128).with_drop(true))
+    ///     .connect()
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn sender_quota(&mut self, quota: SenderQuota) -> &mut Self {
+        self.sender_quota = Some(quota);
+        self
+    }
+
+    /// Set the maximum number of received method calls which can be handled at
+    /// the same time, answering further calls with a `LimitsExceeded` error.
+    ///
+    /// See [`SendBuf::set_max_in_flight_calls`]. By default there is no limit.
+    ///
+    /// [`SendBuf::set_max_in_flight_calls`]: crate::SendBuf::set_max_in_flight_calls
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::ConnectionBuilder;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let c = ConnectionBuilder::new()
+    ///     .max_in_flight_calls(16)
+    ///     .connect()
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn max_in_flight_calls(&mut self, max: usize) -> &mut Self {
+        self.max_in_flight_calls = Some(max);
+        self
+    }
+
+    /// Replace the handshake performed once the connection has been
+    /// authenticated.
+    ///
+    /// By default the `Hello` message is sent to the message bus. See
+    /// [`Handshake`] for how to customize this, which can be used to connect to
+    /// brokers that don't implement the `org.freedesktop.DBus` interface.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::num::NonZeroU32;
+    ///
+    /// use tokio_dbus::{BodyBuf, ConnectionBuilder, Handshake, ObjectPath, Result, SendBuf};
+    ///
+    /// struct Register;
+    ///
+    /// impl Handshake for Register {
+    ///     fn hello(&self, send: &mut SendBuf, body: &mut BodyBuf) -> Result<Option<NonZeroU32>> {
+    ///         body.store("example")?;
+    ///
+    ///         let m = send
+    ///             .method_call(ObjectPath::ROOT, "Register")
+    ///             .with_destination("se.tedro.Broker")
+    ///             .with_body(body);
+    ///
+    ///         let serial = m.serial();
+    ///         send.write_message(m)?;
+    ///         Ok(Some(serial))
+    ///     }
+    /// }
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let c = ConnectionBuilder::new()
+    ///     .handshake(Register)
+    ///     .connect()
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn handshake<H>(&mut self, handshake: H) -> &mut Self
+    where
+        H: 'static + Handshake + Send + Sync,
+    {
+        self.handshake = Box::new(handshake);
+        self
+    }
+
     /// Construct and connect a [`Connection`] with the current configuration.
+    ///
+    /// This completes once the bus has replied to the initial `Hello`
+    /// message, or the message used in its place by a custom [`Handshake`].
     pub async fn connect(&self) -> Result<Connection> {
         let transport = match self.bus {
-            BusKind::Session => Transport::session_bus()?,
-            BusKind::System => Transport::system_bus()?,
+            BusKind::Session => Transport::session_bus(self.connect_delay).await?,
+            BusKind::System => Transport::system_bus(self.connect_delay).await?,
         };
 
+        #[cfg(feature = "libc")]
+        if self.kernel_timestamps {
+            transport.set_kernel_timestamps(true)?;
+        }
+
         let mut auth_buf;
 
-        let auth = match self.auth {
+        let auth = match &self.auth {
             AuthKind::None => None,
             #[cfg(feature = "libc")]
             AuthKind::Uid => {
                 auth_buf = [0; 32];
                 Some(Auth::external_from_uid(&mut auth_buf))
             }
+            AuthKind::External(payload) => Some(Auth::External(payload)),
         };
 
         let mut c = Connection::new(transport)?;
+        c.skip_invalid_messages = self.skip_invalid_messages;
+        c.send.set_watchdog(self.watchdog);
+        c.deliver_bus_signals = self.deliver_bus_signals;
+        c.recv.set_sender_quota(self.sender_quota);
+        c.send.set_max_in_flight_calls(self.max_in_flight_calls);
 
-        if let Some(auth) = auth {
-            let sasl = c.sasl_request(&SaslRequest::Auth(auth)).await?;
+        let sasl = async {
+            if let Some(auth) = auth {
+                let sasl = c.sasl_request(&SaslRequest::Auth(auth)).await?;
 
-            match sasl {
-                SaslResponse::Ok(..) => {}
+                let SaslResponse::Ok(..) = sasl else {
+                    return Err(Error::new(ErrorKind::InvalidSaslResponse));
+                };
+
+                // NB: The bus refuses to pass file descriptors if this is
+                // rejected, which isn't an error until they're needed.
+                #[cfg(feature = "libc")]
+                c.sasl_request(&SaslRequest::NegotiateUnixFd).await?;
             }
-        }
 
-        // Transition to message mode.
-        c.sasl_begin().await?;
-        c.hello()?;
+            // Transition to message mode.
+            c.sasl_begin().await
+        };
+
+        timeout(self.auth_timeout, ErrorKind::AuthTimeout, sasl).await?;
+
+        c.hello(&*self.handshake)?;
+        timeout(self.hello_timeout, ErrorKind::HelloTimeout, c.hello_reply()).await?;
         Ok(c)
     }
 }
 
+/// Run `future` to completion, failing with `kind` if it takes longer than
+/// `duration`.
+async fn timeout<F>(duration: Option<Duration>, kind: ErrorKind, future: F) -> Result<()>
+where
+    F: Future<Output = Result<()>>,
+{
+    let Some(duration) = duration else {
+        return future.await;
+    };
+
+    match tokio::time::timeout(duration, future).await {
+        Ok(result) => result,
+        Err(..) => Err(Error::new(kind)),
+    }
+}
+
 impl Default for ConnectionBuilder {
     #[inline]
     fn default() -> Self {
@@ -0,0 +1,129 @@
+use std::num::NonZeroU32;
+
+use crate::error::{Error, ErrorKind, Result};
+use crate::{MatchRule, Message, MessageKind};
+
+use super::Connection;
+
+/// An event received for a method call made through
+/// [`Connection::call_with_signals`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CallEvent<'a> {
+    /// A signal which matches the rule of the call.
+    Signal(Message<'a>),
+    /// The reply to the method call.
+    Return(Message<'a>),
+}
+
+/// A method call whose reply is interleaved with the signals it causes.
+///
+/// This is constructed through [`Connection::call_with_signals`]. Events are
+/// received with [`CallWithSignals::next`] in the order in which they arrive,
+/// and other messages received in the meantime are deferred. Since services
+/// commonly keep emitting signals after they've replied, the call doesn't end
+/// with the reply.
+///
+/// The match rule is removed from the bus once the call is dropped, by
+/// queueing a request to remove it which is sent the next time the connection
+/// is flushed or waited on. Call [`CallWithSignals::finish`] to instead wait
+/// for the rule to be removed.
+pub struct CallWithSignals<'a, 'r> {
+    connection: &'a mut Connection,
+    serial: NonZeroU32,
+    rule: MatchRule<'r>,
+    replied: bool,
+    removed: bool,
+}
+
+impl<'a, 'r> CallWithSignals<'a, 'r> {
+    pub(super) fn new(
+        connection: &'a mut Connection,
+        serial: NonZeroU32,
+        rule: MatchRule<'r>,
+    ) -> Self {
+        Self {
+            connection,
+            serial,
+            rule,
+            replied: false,
+            removed: false,
+        }
+    }
+
+    /// Test if the reply to the method call has been received.
+    #[must_use]
+    pub fn is_replied(&self) -> bool {
+        self.replied
+    }
+
+    /// Wait for the next signal matching the rule of the call, or the reply
+    /// to the method call.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the connection fails, or if the method call is replied to
+    /// with an error. Matching signals can still be received after the call
+    /// has been replied to with an error.
+    pub async fn next(&mut self) -> Result<CallEvent<'_>> {
+        let is_return = loop {
+            self.connection.wait_no_deferred().await?;
+            let message = self.connection.recv.last_message_no_deferred()?;
+
+            match message.kind() {
+                MessageKind::MethodReturn { reply_serial } if reply_serial == self.serial => {
+                    self.replied = true;
+                    break true;
+                }
+                MessageKind::Error {
+                    error_name,
+                    reply_serial,
+                } if reply_serial == self.serial => {
+                    self.replied = true;
+
+                    return Err(Error::new(ErrorKind::ResponseError(
+                        error_name.into(),
+                        message.error_message().unwrap_or_default().into(),
+                    )));
+                }
+                _ if self.rule.matches(&message) => {
+                    break false;
+                }
+                _ => {
+                    self.connection.recv.defer_last()?;
+                }
+            }
+        };
+
+        // NB: The message is read again here, since it can't be borrowed across
+        // iterations of the loop above.
+        let message = self.connection.recv.last_message_no_deferred()?;
+
+        if is_return {
+            Ok(CallEvent::Return(message))
+        } else {
+            Ok(CallEvent::Signal(message))
+        }
+    }
+
+    /// Finish the call by removing its match rule from the bus, and wait for
+    /// the bus to confirm that it has been removed.
+    ///
+    /// Messages which were deferred while waiting for events are delivered
+    /// through subsequent calls to [`Connection::wait`].
+    pub async fn finish(mut self) -> Result<()> {
+        self.connection.remove_match(&self.rule.to_string()).await?;
+        self.removed = true;
+        Ok(())
+    }
+}
+
+impl Drop for CallWithSignals<'_, '_> {
+    fn drop(&mut self) {
+        if !self.removed {
+            // NB: Errors can't be reported here, and only mean that the
+            // connection can't be used to send messages any longer.
+            _ = self.connection.queue_remove_match(&self.rule.to_string());
+        }
+    }
+}
@@ -1,15 +1,43 @@
+use std::future::{poll_fn, Future};
 use std::io;
 use std::num::NonZeroU32;
+use std::ops::ControlFlow;
+use std::os::fd::OwnedFd;
+use std::pin::pin;
+use std::task::Poll;
+use std::time::Duration;
 
 use tokio::io::unix::AsyncFd;
-use tokio::io::{Interest, Ready};
+use tokio::io::{AsyncRead, AsyncReadExt, Interest, Ready};
+use tokio::time::Instant;
 
 use crate::error::{ErrorKind, Result};
-use crate::org_freedesktop_dbus::{self, NameFlag, NameReply};
+use crate::object_registry::call_strict;
+use crate::org_freedesktop_dbus::{self, BusSignal, NameFlag, NameReply};
+use crate::org_freedesktop_dbus_object_manager::{self, ManagedObjects};
+use crate::org_freedesktop_dbus_properties::{self, InterfaceVersion, PropertyEntries};
 use crate::sasl::{SaslRequest, SaslResponse};
-use crate::{BodyBuf, Error, Message, MessageKind, ObjectPath, RecvBuf, SendBuf};
+use crate::{
+    ty, Backoff, Body, BodyBuf, Broadcast, Error, Flags, FromBody, HeaderFields, MatchRule,
+    Message, MessageBuf, MessageKind, MessageRef, ObjectPath, RecvBuf, SendBuf, Signature, Value,
+    Variant,
+};
 
-use super::{sasl_recv, ConnectionBuilder, Transport};
+#[cfg(feature = "libc")]
+use super::Credentials;
+use super::{
+    sasl_recv, CallWithSignals, ConnectionBuilder, Handshake, ReplyBuilder, SendPermit, Transport,
+};
+
+/// The interface used to introspect objects.
+const INTROSPECTABLE: &str = "org.freedesktop.DBus.Introspectable";
+
+/// The size of chunks in which byte arrays are sent by
+/// [`Connection::send_byte_array`].
+const BYTE_ARRAY_CHUNK: usize = 1 << 16;
+
+/// Callback invoked when the connection loses ownership of a name.
+type NameLostHandler = Box<dyn FnMut(&str) + Send + Sync>;
 
 /// The high level state of a client.
 pub(crate) enum ConnectionState {
@@ -21,6 +49,23 @@ pub(crate) enum ConnectionState {
     Idle,
 }
 
+/// The lifecycle state of a [`Connection`].
+///
+/// See [`Connection::status`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConnectionStatus {
+    /// The connection is authenticating with the bus.
+    Authenticating,
+    /// The `Hello` message has been sent, and the connection is waiting for
+    /// the bus to assign it a unique name.
+    HelloPending,
+    /// The connection is ready to be used.
+    Ready,
+    /// The connection has been lost, or can no longer send messages.
+    Disconnected,
+}
+
 /// An asynchronous D-Bus client.
 pub struct Connection {
     /// Poller for the underlying file descriptor.
@@ -28,13 +73,27 @@ pub struct Connection {
     /// Hello serial.
     state: ConnectionState,
     /// Receive buffer.
-    recv: RecvBuf,
+    pub(crate) recv: RecvBuf,
     /// Send buffer.
-    send: SendBuf,
+    pub(super) send: SendBuf,
     /// Body buffer.
     body: BodyBuf,
     /// The name of the client.
     name: Option<Box<str>>,
+    /// If the connection has been lost and a local `Disconnected` signal has
+    /// been emitted.
+    disconnected: bool,
+    /// Handler called when a name owned by the connection is lost.
+    name_lost: Option<NameLostHandler>,
+    /// If sending failed in the middle of a message, after which nothing
+    /// more can be sent without desynchronizing the connection.
+    send_failed: bool,
+    /// Skip received messages which violate the protocol, as long as the
+    /// connection can recover from them.
+    pub(super) skip_invalid_messages: bool,
+    /// Deliver signals emitted by the bus interface to the caller instead of
+    /// only handling them internally.
+    pub(super) deliver_bus_signals: bool,
 }
 
 impl Connection {
@@ -49,6 +108,11 @@ impl Connection {
             send: SendBuf::new(),
             body: BodyBuf::new(),
             name: None,
+            disconnected: false,
+            name_lost: None,
+            send_failed: false,
+            skip_invalid_messages: false,
+            deliver_bus_signals: false,
         })
     }
 
@@ -76,15 +140,55 @@ impl Connection {
     ///
     /// [`flush()`]: Self::flush
     ///
+    /// Once the connection is lost, a single `Disconnected` signal on the
+    /// [`LOCAL_INTERFACE`] interface and [`LOCAL_PATH`] path is received
+    /// through this method. Any subsequent calls return the error which
+    /// caused the connection to be lost.
+    ///
+    /// [`LOCAL_INTERFACE`]: org_freedesktop_dbus::LOCAL_INTERFACE
+    /// [`LOCAL_PATH`]: org_freedesktop_dbus::LOCAL_PATH
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe. If it is used as an event in a
+    /// `tokio::select!` statement and some other branch completes first, any
+    /// partially received message is kept in the connection and is completed
+    /// by the next call. Queued outgoing messages are also retained.
+    ///
+    /// To wait with a timeout, see [`wait_timeout()`] and [`wait_until()`].
+    ///
+    /// [`wait_timeout()`]: Self::wait_timeout
+    /// [`wait_until()`]: Self::wait_until
+    ///
+    /// # Tracing
+    ///
+    /// With the `tracing` feature enabled, waiting for the connection to become
+    /// ready is done inside of a `tokio_dbus::ready` span, whose `waiting_for`
+    /// field is one of `read`, `read+write` or `sasl`. This makes it possible to
+    /// see what a task is waiting on in tools like [tokio-console], or to find
+    /// tasks which are stuck because nothing is driving the connection.
+    ///
+    /// [tokio-console]: https://github.com/tokio-rs/console
+    ///
     /// # Examples
     ///
     /// ```no_run
-    /// use tokio_dbus::{Connection, Message};
+    /// use tokio_dbus::{Connection, Message, MessageKind};
+    /// use tokio_dbus::org_freedesktop_dbus::{DISCONNECTED, LOCAL_INTERFACE};
     ///
     /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
     /// let mut c = Connection::session_bus().await?;
-    /// c.wait().await?;
-    /// let message: Message<'_> = c.last_message()?;
+    ///
+    /// loop {
+    ///     c.wait().await?;
+    ///     let message: Message<'_> = c.last_message()?;
+    ///
+    ///     if let (Some(LOCAL_INTERFACE), MessageKind::Signal { member: DISCONNECTED, .. }) =
+    ///         (message.interface(), message.kind())
+    ///     {
+    ///         break;
+    ///     }
+    /// }
     /// # Ok(()) }
     /// ```
     pub async fn wait(&mut self) -> Result<()> {
@@ -94,7 +198,92 @@ impl Connection {
             return Ok(());
         }
 
-        self.wait_no_deferred().await
+        match self.wait_no_deferred().await {
+            Err(error) if error.is_disconnected() && !self.disconnected => {
+                self.disconnected = true;
+
+                // NB: The signal is never sent, so it doesn't take a serial
+                // from the send buffer.
+                let message =
+                    MessageBuf::signal(org_freedesktop_dbus::DISCONNECTED.into(), NonZeroU32::MIN)
+                        .with_path(org_freedesktop_dbus::LOCAL_PATH.into())
+                        .with_interface(org_freedesktop_dbus::LOCAL_INTERFACE.into());
+
+                self.recv.defer(message);
+                self.recv.take_deferred();
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// Wait for the next incoming message on this connection for at most
+    /// `duration`.
+    ///
+    /// Returns `Ok(None)` if no message was received before the timeout
+    /// elapsed. Like [`wait()`], this is cancel safe, so no partially received
+    /// message is lost on timeout.
+    ///
+    /// [`wait()`]: Self::wait
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use tokio_dbus::Connection;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    ///
+    /// loop {
+    ///     let Some(message) = c.wait_timeout(Duration::from_secs(1)).await? else {
+    ///         println!("Performing periodic work");
+    ///         continue;
+    ///     };
+    ///
+    ///     println!("{:?}", message.kind());
+    /// }
+    /// # }
+    /// ```
+    pub async fn wait_timeout(&mut self, duration: Duration) -> Result<Option<Message<'_>>> {
+        self.wait_until(Instant::now() + duration).await
+    }
+
+    /// Wait for the next incoming message on this connection until the given
+    /// `deadline`.
+    ///
+    /// Returns `Ok(None)` if no message was received before the deadline. Like
+    /// [`wait()`], this is cancel safe, so no partially received message is
+    /// lost on timeout.
+    ///
+    /// [`wait()`]: Self::wait
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use tokio::time::Instant;
+    /// use tokio_dbus::Connection;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    /// let deadline = Instant::now() + Duration::from_secs(5);
+    ///
+    /// while let Some(message) = c.wait_until(deadline).await? {
+    ///     println!("{:?}", message.kind());
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub async fn wait_until(&mut self, deadline: Instant) -> Result<Option<Message<'_>>> {
+        match tokio::time::timeout_at(deadline, self.wait()).await {
+            Ok(result) => {
+                result?;
+                Ok(Some(self.recv.last_message()?))
+            }
+            Err(..) => Ok(None),
+        }
     }
 
     /// Wait for the next incoming message on this connection ignoring messages
@@ -113,12 +302,18 @@ impl Connection {
     /// ```
     pub async fn wait_no_deferred(&mut self) -> Result<()> {
         loop {
-            if !self.io(false).await? {
-                continue;
-            };
+            match self.io(false).await {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(error) if self.should_skip(&error) => continue,
+                Err(error) => return Err(error),
+            }
 
-            if self.handle_internal()? {
-                continue;
+            match self.handle_internal() {
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(error) if self.should_skip(&error) => continue,
+                Err(error) => return Err(error),
             }
 
             return Ok(());
@@ -127,6 +322,22 @@ impl Connection {
 
     /// Flush all outgoing messages and return when the send buffer is empty.
     ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe. Outgoing messages are only removed from
+    /// the send buffer once they have been written, so the remainder of a
+    /// partially sent message is sent by the next call to [`flush()`] or
+    /// [`wait()`].
+    ///
+    /// If sending fails in the middle of a message, the connection can no
+    /// longer send anything since the peer would interpret it as a
+    /// continuation of that message. Any subsequent attempt to send then
+    /// fails with an error for which [`Error::is_disconnected`] returns
+    /// `true`.
+    ///
+    /// [`flush()`]: Self::flush
+    /// [`wait()`]: Self::wait
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -138,23 +349,114 @@ impl Connection {
     /// # Ok(()) }
     /// ```
     pub async fn flush(&mut self) -> Result<()> {
-        while self.io(true).await? {
-            self.handle_internal()?;
+        loop {
+            let result = match self.io(true).await {
+                Ok(true) => self.handle_internal().map(|_| ()),
+                Ok(false) => return Ok(()),
+                Err(error) => Err(error),
+            };
+
+            match result {
+                Err(error) if !self.should_skip(&error) => return Err(error),
+                _ => {}
+            }
         }
+    }
+
+    /// Acquire a permit to write one message of up to `size` bytes.
+    ///
+    /// This waits until every message already in the send buffer has been
+    /// sent, like [`flush()`], and then reserves room for `size` bytes in the
+    /// send buffer. Producers emitting messages in a loop can use this to
+    /// avoid growing the send buffer faster than the connection can send it.
+    /// Messages received while waiting are deferred, and returned by
+    /// subsequent calls to [`wait()`].
+    ///
+    /// Writing a message larger than `size` through the permit still works,
+    /// but might grow the send buffer.
+    ///
+    /// [`flush()`]: Self::flush
+    /// [`wait()`]: Self::wait
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::{Connection, ObjectPath};
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    ///
+    /// let mut permit = c.acquire_send_permit(128).await?;
+    /// let m = permit.signal("Ping").with_path(ObjectPath::ROOT);
+    /// permit.write_message(m)?;
+    ///
+    /// c.flush().await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn acquire_send_permit(&mut self, size: usize) -> Result<SendPermit<'_>> {
+        self.reserve_send(size).await?;
+        Ok(SendPermit::borrowed(&mut self.send))
+    }
 
+    /// Flush the send buffer and reserve room for `size` bytes in it.
+    pub(super) async fn reserve_send(&mut self, size: usize) -> Result<()> {
+        self.flush_deferred().await?;
+        self.send.reserve(size);
         Ok(())
     }
 
+    /// Flush the send buffer, deferring every message received in the
+    /// meantime which isn't handled internally.
+    async fn flush_deferred(&mut self) -> Result<()> {
+        loop {
+            let result = match self.io(true).await {
+                Ok(true) => self.handle_internal(),
+                Ok(false) => return Ok(()),
+                Err(error) => Err(error),
+            };
+
+            match result {
+                Ok(false) => self.recv.defer_last()?,
+                Ok(true) => {}
+                Err(error) if self.should_skip(&error) => {}
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Test if the given error is a protocol violation which should be
+    /// skipped.
+    fn should_skip(&self, error: &Error) -> bool {
+        self.skip_invalid_messages
+            && error
+                .protocol_violation()
+                .is_some_and(|violation| violation.is_recoverable())
+    }
+
     /// Handle internal messages, returns `true` if a message was intercepted.
     fn handle_internal(&mut self) -> Result<bool> {
         // Read once for internal processing. Avoid this once borrow checker
         // allows returning a reference here directly.
         let message = self.recv.last_message()?;
 
+        if let MessageKind::MethodReturn { reply_serial }
+        | MessageKind::Error { reply_serial, .. } = message.kind
+        {
+            self.send.complete_reply(reply_serial);
+        }
+
         if let ConnectionState::HelloSent(serial) = self.state {
             match message.kind {
                 MessageKind::MethodReturn { reply_serial } if reply_serial == serial => {
-                    self.name = Some(message.body().read::<str>()?.into());
+                    let mut body = message.body();
+
+                    // NB: Custom handshakes might not assign a unique name.
+                    self.name = if body.signature() == "s" {
+                        Some(body.read::<str>()?.into())
+                    } else {
+                        None
+                    };
+
                     self.state = ConnectionState::Idle;
                     return Ok(true);
                 }
@@ -162,17 +464,103 @@ impl Connection {
             }
         }
 
-        // TODO: Ignore freedesktop signals for now, but eventually we might
-        // want to handle internally.
-        if let (Some(org_freedesktop_dbus::INTERFACE), MessageKind::Signal { .. }) =
-            (message.interface, message.kind)
+        // Signals from the bus are handled internally, and only delivered to
+        // the caller if requested. Signals which fail to decode are ignored,
+        // since they shouldn't prevent the connection from being used.
+        if let (
+            Some(org_freedesktop_dbus::DESTINATION),
+            Some(org_freedesktop_dbus::INTERFACE),
+            MessageKind::Signal { .. },
+        ) = (message.sender, message.interface, message.kind)
         {
-            return Ok(true);
+            if let Ok(Some(BusSignal::NameLost { name })) = BusSignal::from_message(&message) {
+                if let Some(handler) = &mut self.name_lost {
+                    handler(name);
+                }
+            }
+
+            return Ok(!self.deliver_bus_signals);
         }
 
         Ok(false)
     }
 
+    /// Get the unique name assigned to the connection by the bus.
+    ///
+    /// This is `None` until the bus has replied to the initial `Hello`
+    /// message, which has always happened for connections returned by
+    /// [`ConnectionBuilder::connect`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::Connection;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let c = Connection::session_bus().await?;
+    /// println!("Connected as {:?}", c.name());
+    /// # Ok(()) }
+    /// ```
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Get the lifecycle state of the connection.
+    ///
+    /// This can be used by supervisory code to health-check a connection.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::{Connection, ConnectionStatus};
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let c = Connection::session_bus().await?;
+    /// assert_eq!(c.status(), ConnectionStatus::Ready);
+    /// # Ok(()) }
+    /// ```
+    pub fn status(&self) -> ConnectionStatus {
+        if self.disconnected || self.send_failed {
+            return ConnectionStatus::Disconnected;
+        }
+
+        if self.transport.get_ref().is_authenticating() {
+            return ConnectionStatus::Authenticating;
+        }
+
+        match self.state {
+            ConnectionState::HelloSent(..) => ConnectionStatus::HelloPending,
+            ConnectionState::Init | ConnectionState::Idle => ConnectionStatus::Ready,
+        }
+    }
+
+    /// Get the credentials of the process at the other end of the connection,
+    /// as reported by the operating system.
+    ///
+    /// This uses `SO_PEERCRED` on Linux and `getpeereid` on BSDs and macOS,
+    /// where the process identifier is not available.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the credentials could not be retrieved, or if retrieving them
+    /// is not supported on the current platform.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::Connection;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let c = Connection::session_bus().await?;
+    /// let credentials = c.peer_credentials()?;
+    /// println!("Bus is running as uid {}", credentials.uid());
+    /// # Ok(()) }
+    /// ```
+    #[cfg(feature = "libc")]
+    pub fn peer_credentials(&self) -> Result<Credentials> {
+        Ok(self.transport.get_ref().peer_credentials()?)
+    }
+
     /// Construct a new [`Message`] corresponding to a method call.
     pub fn method_call<'a>(&mut self, path: &'a ObjectPath, member: &'a str) -> Message<'a> {
         self.send.method_call(path, member)
@@ -190,6 +578,26 @@ impl Connection {
         self.send.write_message(message)
     }
 
+    /// Queue a [`Broadcast`] message in the send buffer, returning the serial
+    /// it was assigned.
+    ///
+    /// Like with [`write_message()`], the message is sent during the next
+    /// call to [`wait()`] or [`flush()`].
+    ///
+    /// [`write_message()`]: Self::write_message
+    /// [`wait()`]: Self::wait
+    /// [`flush()`]: Self::flush
+    ///
+    /// # Errors
+    ///
+    /// Errors if the connection is in the middle of sending a byte array
+    /// through [`send_byte_array()`], such as if sending it failed.
+    ///
+    /// [`send_byte_array()`]: Self::send_byte_array
+    pub fn write_broadcast(&mut self, broadcast: &Broadcast) -> Result<NonZeroU32> {
+        broadcast.write_to(&mut self.send)
+    }
+
     /// Read the last message buffered.
     ///
     /// # Errors
@@ -199,6 +607,86 @@ impl Connection {
         self.recv.last_message()
     }
 
+    /// Get a reference to the last message received, not including deferred
+    /// messages.
+    ///
+    /// See [`RecvBuf::last_message_ref`].
+    ///
+    /// # Errors
+    ///
+    /// In case there is no message buffered.
+    pub fn last_message_ref(&self) -> Result<MessageRef> {
+        self.recv.last_message_ref()
+    }
+
+    /// Read the message referenced by `message_ref`.
+    ///
+    /// See [`RecvBuf::read_message`].
+    ///
+    /// # Errors
+    ///
+    /// If another message has been received since the reference was taken,
+    /// this errors with an error for which [`Error::is_stale_message`] returns
+    /// `true`.
+    pub fn read_message(&self, message_ref: &MessageRef) -> Result<Message<'_>> {
+        self.recv.read_message(message_ref)
+    }
+
+    /// Iterate over the raw header fields of the message referenced by
+    /// `message_ref`.
+    ///
+    /// See [`RecvBuf::header_fields`].
+    ///
+    /// # Errors
+    ///
+    /// If another message has been received since the reference was taken,
+    /// this errors with an error for which [`Error::is_stale_message`] returns
+    /// `true`.
+    pub fn header_fields(&self, message_ref: &MessageRef) -> Result<HeaderFields<'_>> {
+        self.recv.header_fields(message_ref)
+    }
+
+    /// Take the file descriptors which were received alongside the message
+    /// referenced by `message_ref`.
+    ///
+    /// See [`RecvBuf::take_unix_fds`].
+    ///
+    /// # Errors
+    ///
+    /// If another message has been received since the reference was taken,
+    /// this errors with an error for which [`Error::is_stale_message`] returns
+    /// `true`.
+    pub fn take_unix_fds(&mut self, message_ref: &MessageRef) -> Result<Vec<OwnedFd>> {
+        self.recv.take_unix_fds(message_ref)
+    }
+
+    /// Start building a reply to the message referenced by `message_ref`.
+    ///
+    /// The returned [`ReplyBuilder`] copies the header fields it needs from
+    /// the referenced message, so it only borrows the send half of the
+    /// connection. The reply is addressed to the sender of the message.
+    ///
+    /// See [`ReplyBuilder`].
+    ///
+    /// # Errors
+    ///
+    /// If another message has been received since the reference was taken,
+    /// this errors with an error for which [`Error::is_stale_message`] returns
+    /// `true`.
+    pub fn reply_to(&mut self, message_ref: &MessageRef) -> Result<ReplyBuilder<'_>> {
+        let message = self.recv.read_message(message_ref)?;
+        let destination = message.sender().map(Box::from);
+        let correlation_id = message.correlation_id().map(Box::from);
+
+        Ok(ReplyBuilder::new(
+            &mut self.send,
+            &mut self.body,
+            message.serial(),
+            destination,
+            correlation_id,
+        ))
+    }
+
     /// Access the underlying buffers of the connection.
     ///
     /// The [`RecvBuf`] instance is used to access messages received after a
@@ -255,105 +743,1183 @@ impl Connection {
         (&self.recv, &mut self.send, &mut self.body)
     }
 
-    /// Send a SASL message and receive a response.
-    pub(crate) async fn sasl_request(
-        &mut self,
-        sasl: &SaslRequest<'_>,
-    ) -> Result<SaslResponse<'_>> {
+    /// Serve incoming messages through `handler` until `scope` completes,
+    /// and return its output.
+    ///
+    /// The handler is called with the buffers of the connection once for
+    /// every received message, like after calling [`wait()`] and
+    /// [`buffers()`]. Handlers run to completion before the next message is
+    /// waited for, so a handler is never interrupted when the scope
+    /// completes. Waiting for messages is cancel safe, so no partially
+    /// received message is lost either.
+    ///
+    /// Method calls are accepted through [`SendBuf::accept_call`] before being
+    /// passed to `handler`, so if a maximum number of calls in flight has been
+    /// configured through [`ConnectionBuilder::max_in_flight_calls`], calls
+    /// beyond it are answered with a `LimitsExceeded` error without calling
+    /// `handler`.
+    ///
+    /// Once the scope completes, any replies written by handlers which have
+    /// not yet been sent are flushed before this returns. This makes it
+    /// straightforward to tear a service down cleanly, for example when the
+    /// process receives a signal.
+    ///
+    /// See [`serve_strict_until()`] to reject method calls with unexpected
+    /// arguments automatically.
+    ///
+    /// [`wait()`]: Self::wait
+    /// [`buffers()`]: Self::buffers
+    /// [`serve_strict_until()`]: Self::serve_strict_until
+    /// [`ConnectionBuilder::max_in_flight_calls`]: crate::ConnectionBuilder::max_in_flight_calls
+    ///
+    /// # Errors
+    ///
+    /// Errors if the connection fails or if the handler returns an error, in
+    /// which case buffered replies are not flushed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::{Connection, MessageKind};
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    ///
+    /// c.serve_until(tokio::signal::ctrl_c(), |recv, send, body| {
+    ///     let message = recv.last_message()?;
+    ///
+    ///     if let MessageKind::MethodCall { member: "Ping", .. } = message.kind() {
+    ///         body.store("Pong")?;
+    ///         let reply = message.method_return(send.next_serial()).with_body(body);
+    ///         send.write_message(reply)?;
+    ///     }
+    ///
+    ///     Ok(())
+    /// })
+    /// .await??;
+    /// # Ok(()) }
+    /// ```
+    pub async fn serve_until<S, H>(&mut self, scope: S, mut handler: H) -> Result<S::Output>
+    where
+        S: Future,
+        H: FnMut(&RecvBuf, &mut SendBuf, &mut BodyBuf) -> Result<()>,
+    {
+        let mut scope = pin!(scope);
+
         loop {
-            let mut guard = self.transport.writable_mut().await?;
+            let result = {
+                let mut wait = pin!(self.wait());
 
-            match guard.get_inner_mut().sasl_send(self.send.buf_mut(), sasl) {
-                Err(e) if e.would_block() => {
-                    guard.clear_ready();
-                    continue;
-                }
-                Err(e) => return Err(e),
-                Ok(()) => break,
-            }
-        }
+                poll_fn(|cx| {
+                    if let Poll::Ready(output) = scope.as_mut().poll(cx) {
+                        return Poll::Ready(ControlFlow::Break(output));
+                    }
 
-        loop {
-            let mut guard = self.transport.readable_mut().await?;
+                    wait.as_mut().poll(cx).map(ControlFlow::Continue)
+                })
+                .await
+            };
 
-            match guard.get_inner_mut().sasl_recv(self.send.buf_mut()) {
-                Err(e) if e.would_block() => {
-                    guard.clear_ready();
-                    continue;
+            match result {
+                ControlFlow::Continue(result) => {
+                    result?;
+                    let (recv, send, body) = self.buffers();
+
+                    if send.accept_call(&recv.last_message()?, body)? {
+                        handler(recv, send, body)?;
+                    }
                 }
-                Err(e) => return Err(e),
-                Ok(len) => {
-                    return sasl_recv(self.send.buf_mut().read_until(len));
+                ControlFlow::Break(output) => {
+                    self.flush().await?;
+                    return Ok(output);
                 }
             }
         }
     }
 
-    /// Send the SASL `BEGIN` message.
+    /// Serve incoming messages like [`serve_until()`], but pass the handler
+    /// the received message and its arguments, and reply to method calls
+    /// whose handler leaves arguments unread with an
+    /// `org.freedesktop.DBus.Error.InvalidArgs` error.
     ///
-    /// This does not expect a response from the server, instead it is expected
-    /// to transition into the binary D-Bus protocol.
-    pub(crate) async fn sasl_begin(&mut self) -> Result<()> {
-        loop {
-            let mut guard = self.transport.writable_mut().await?;
-
-            match guard.get_inner_mut().sasl_begin(self.send.buf_mut()) {
-                Err(e) if e.would_block() => {
-                    guard.clear_ready();
-                    continue;
-                }
+    /// Anything the handler wrote in response to such a method call is
+    /// discarded in favor of the error, so handlers don't have to call
+    /// [`Body::ensure_consumed`] themselves. Messages which aren't method
+    /// calls are passed to the handler without checking their arguments.
+    ///
+    /// [`serve_until()`]: Self::serve_until
+    /// [`Body::ensure_consumed`]: crate::Body::ensure_consumed
+    ///
+    /// # Errors
+    ///
+    /// Errors if the connection fails or if the handler returns an error, in
+    /// which case buffered replies are not flushed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::{Connection, MessageKind};
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    ///
+    /// c.serve_strict_until(tokio::signal::ctrl_c(), |message, args, send, body| {
+    ///     if let MessageKind::MethodCall { member: "Echo", .. } = message.kind() {
+    ///         body.store(args.read::<str>()?)?;
+    ///         let reply = message.method_return(send.next_serial()).with_body(body);
+    ///         send.write_message(reply)?;
+    ///     }
+    ///
+    ///     Ok(())
+    /// })
+    /// .await??;
+    /// # Ok(()) }
+    /// ```
+    pub async fn serve_strict_until<S, H>(&mut self, scope: S, mut handler: H) -> Result<S::Output>
+    where
+        S: Future,
+        H: FnMut(&Message<'_>, &mut Body<'_>, &mut SendBuf, &mut BodyBuf) -> Result<()>,
+    {
+        self.serve_until(scope, |recv, send, body| {
+            let message = recv.last_message()?;
+
+            if let MessageKind::MethodCall { .. } = message.kind() {
+                return call_strict(&message, send, body, |args, send, body| {
+                    handler(&message, args, send, body)
+                });
+            }
+
+            handler(&message, &mut message.body(), send, body)
+        })
+        .await
+    }
+
+    /// Send a SASL message and receive a response.
+    pub(crate) async fn sasl_request(
+        &mut self,
+        sasl: &SaslRequest<'_>,
+    ) -> Result<SaslResponse<'_>> {
+        loop {
+            let mut guard = ready("sasl", self.transport.writable_mut()).await?;
+
+            match guard.get_inner_mut().sasl_send(self.send.buf_mut(), sasl) {
+                Err(e) if e.is_would_block() => {
+                    guard.clear_ready();
+                    continue;
+                }
+                Err(e) => return Err(e),
+                Ok(()) => break,
+            }
+        }
+
+        loop {
+            let mut guard = ready("sasl", self.transport.readable_mut()).await?;
+
+            match guard.get_inner_mut().sasl_recv(self.send.buf_mut()) {
+                Err(e) if e.is_would_block() => {
+                    guard.clear_ready();
+                    continue;
+                }
+                Err(e) => return Err(e),
+                Ok(len) => {
+                    return sasl_recv(self.send.buf_mut().read_until(len));
+                }
+            }
+        }
+    }
+
+    /// Send the SASL `BEGIN` message.
+    ///
+    /// This does not expect a response from the server, instead it is expected
+    /// to transition into the binary D-Bus protocol.
+    pub(crate) async fn sasl_begin(&mut self) -> Result<()> {
+        loop {
+            let mut guard = ready("sasl", self.transport.writable_mut()).await?;
+
+            match guard.get_inner_mut().sasl_begin(self.send.buf_mut()) {
+                Err(e) if e.is_would_block() => {
+                    guard.clear_ready();
+                    continue;
+                }
                 Err(e) => return Err(e),
                 Ok(()) => return Ok(()),
             }
         }
     }
 
-    /// Send "Hello" message.
-    pub(crate) fn hello(&mut self) -> Result<()> {
+    /// Send "Hello" message, or whatever `handshake` uses in its place.
+    pub(crate) fn hello(&mut self, handshake: &dyn Handshake) -> Result<()> {
+        self.body.clear();
+        handshake.pre_hello(&mut self.send, &mut self.body)?;
+        self.body.clear();
+
+        self.state = match handshake.hello(&mut self.send, &mut self.body)? {
+            Some(serial) => ConnectionState::HelloSent(serial),
+            None => ConnectionState::Idle,
+        };
+
+        self.body.clear();
+        Ok(())
+    }
+
+    /// Wait for the reply to the "Hello" message sent through [`hello()`].
+    ///
+    /// Any other messages received in the meantime are deferred.
+    ///
+    /// [`hello()`]: Self::hello
+    pub(crate) async fn hello_reply(&mut self) -> Result<()> {
+        loop {
+            let waiting = matches!(self.state, ConnectionState::HelloSent(..));
+
+            // NB: Once the reply has been received or if no hello message was
+            // sent, any messages written by the handshake are flushed.
+            if !self.io(!waiting).await? {
+                if waiting {
+                    continue;
+                }
+
+                return Ok(());
+            }
+
+            if !self.handle_internal()? {
+                self.recv.defer_last()?;
+            }
+        }
+    }
+
+    /// Request the given well-known name.
+    pub async fn request_name(&mut self, name: &str, flags: NameFlag) -> Result<NameReply> {
+        self.body.clear();
+        self.body.store(name)?;
+        self.body.store(flags)?;
+
+        let m = self
+            .send
+            .method_call(org_freedesktop_dbus::PATH, "RequestName")
+            .with_destination(org_freedesktop_dbus::DESTINATION)
+            .with_body(&self.body);
+
+        let serial = m.serial();
+        self.send.write_message(m)?;
+        self.wait_for_reply(serial).await?;
+
+        let message = self.recv.last_message_no_deferred()?;
+        message.body().load::<NameReply>()
+    }
+
+    /// Request the given well-known name, retrying according to `backoff` for
+    /// as long as the name is owned by someone else.
+    ///
+    /// This is useful for daemons which are started while a previous instance
+    /// is still shutting down. The `observe` callback is called with the
+    /// attempt number and the delay before each retry.
+    ///
+    /// Returns the reply of the last attempt, which is
+    /// [`NameReply::EXISTS`] if the name could not be acquired before the
+    /// attempts ran out.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use tokio_dbus::{Backoff, Connection};
+    /// use tokio_dbus::org_freedesktop_dbus::{NameFlag, NameReply};
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    ///
+    /// let backoff = Backoff::new(Duration::from_millis(100))
+    ///     .with_jitter(0.25)
+    ///     .with_max_attempts(10);
+    ///
+    /// let reply = c
+    ///     .request_name_with_retry("se.tedro.Example", NameFlag::DO_NOT_QUEUE, &backoff, |attempt, delay| {
+    ///         println!("Name is taken, attempt #{attempt} in {delay:?}");
+    ///     })
+    ///     .await?;
+    ///
+    /// assert_eq!(reply, NameReply::PRIMARY_OWNER);
+    /// # Ok(()) }
+    /// ```
+    pub async fn request_name_with_retry<F>(
+        &mut self,
+        name: &str,
+        flags: NameFlag,
+        backoff: &Backoff,
+        mut observe: F,
+    ) -> Result<NameReply>
+    where
+        F: FnMut(u32, Duration),
+    {
+        let mut delays = backoff.delays();
+        let mut attempt = 1;
+
+        loop {
+            let reply = self.request_name(name, flags).await?;
+
+            if reply != NameReply::EXISTS {
+                return Ok(reply);
+            }
+
+            let Some(delay) = delays.next() else {
+                return Ok(reply);
+            };
+
+            attempt += 1;
+            observe(attempt, delay);
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Request the given well-known name, and if the request is queued wait
+    /// until the name has been acquired.
+    ///
+    /// This is used to take over a name from a running instance of a service.
+    /// If `flags` includes [`NameFlag::REPLACE_EXISTING`] and the current
+    /// owner allows replacement the name is acquired immediately. Otherwise the
+    /// request is queued and this waits for the current owner to release the
+    /// name or to exit.
+    ///
+    /// Other messages received while waiting are deferred.
+    ///
+    /// Returns [`NameReply::PRIMARY_OWNER`] once the name has been acquired,
+    /// [`NameReply::ALREADY_OWNER`] if this connection already owned it, or
+    /// [`NameReply::EXISTS`] if the name is taken and `flags` includes
+    /// [`NameFlag::DO_NOT_QUEUE`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::Connection;
+    /// use tokio_dbus::org_freedesktop_dbus::NameFlag;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    ///
+    /// c.acquire_name("se.tedro.Example", NameFlag::REPLACE_EXISTING | NameFlag::ALLOW_REPLACEMENT)
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn acquire_name(&mut self, name: &str, flags: NameFlag) -> Result<NameReply> {
+        let reply = self.request_name(name, flags).await?;
+
+        if reply != NameReply::IN_QUEUE {
+            return Ok(reply);
+        }
+
+        loop {
+            if !self.io(false).await? {
+                continue;
+            }
+
+            let message = self.recv.last_message_no_deferred()?;
+
+            if let Ok(Some(BusSignal::NameAcquired { name: acquired })) =
+                BusSignal::from_message(&message)
+            {
+                if acquired == name {
+                    return Ok(NameReply::PRIMARY_OWNER);
+                }
+            }
+
+            if !self.handle_internal()? {
+                self.recv.defer_last()?;
+            }
+        }
+    }
+
+    /// Set a handler which is called with the name whenever this connection
+    /// loses ownership of a well-known name.
+    ///
+    /// This happens when a name requested with
+    /// [`NameFlag::ALLOW_REPLACEMENT`] is taken over by another connection
+    /// using [`NameFlag::REPLACE_EXISTING`], which is how single-instance
+    /// services hand over to a newly started instance. The handler is called
+    /// while waiting for messages, so it should only signal the rest of the
+    /// application to shut down.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio::sync::oneshot;
+    /// use tokio_dbus::Connection;
+    /// use tokio_dbus::org_freedesktop_dbus::NameFlag;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    /// let (tx, mut replaced) = oneshot::channel();
+    /// let mut tx = Some(tx);
+    ///
+    /// c.on_name_lost(move |name| {
+    ///     println!("Replaced as the owner of {name}, shutting down");
+    ///
+    ///     if let Some(tx) = tx.take() {
+    ///         _ = tx.send(());
+    ///     }
+    /// });
+    ///
+    /// c.acquire_name("se.tedro.Example", NameFlag::ALLOW_REPLACEMENT).await?;
+    ///
+    /// loop {
+    ///     tokio::select! {
+    ///         _ = &mut replaced => break,
+    ///         result = c.wait() => {
+    ///             result?;
+    ///             // Handle messages.
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn on_name_lost<F>(&mut self, handler: F)
+    where
+        F: 'static + Send + Sync + FnMut(&str),
+    {
+        self.name_lost = Some(Box::new(handler));
+    }
+
+    /// List the names which are currently owned on the bus.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::Connection;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    ///
+    /// for name in c.list_names().await? {
+    ///     println!("{name}");
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub async fn list_names(&mut self) -> Result<Vec<String>> {
+        let m = self
+            .send
+            .method_call(org_freedesktop_dbus::PATH, "ListNames")
+            .with_destination(org_freedesktop_dbus::DESTINATION)
+            .with_interface(org_freedesktop_dbus::INTERFACE);
+
+        let serial = m.serial();
+        self.send.write_message(m)?;
+        self.wait_for_reply(serial).await?;
+
+        let message = self.recv.last_message_no_deferred()?;
+        let mut names = Vec::new();
+
+        for name in message.body().load_array::<ty::Str>()? {
+            names.push(name?.to_owned());
+        }
+
+        Ok(names)
+    }
+
+    /// Read the version of `interface` implemented by the object at `path`
+    /// owned by `destination`.
+    ///
+    /// The version is read from the conventional [`VERSION`] property. Returns
+    /// `None` if the interface doesn't have such a property.
+    ///
+    /// [`VERSION`]: org_freedesktop_dbus_properties::VERSION
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::{Connection, ObjectPath};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/DBusExample");
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    ///
+    /// let version = c
+    ///     .interface_version("se.tedro.DBusExample", PATH, "se.tedro.DBusExample.Pingable")
+    ///     .await?;
+    ///
+    /// if version.is_some_and(|v| v.supports(2)) {
+    ///     println!("Batched pings are supported");
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub async fn interface_version(
+        &mut self,
+        destination: &str,
+        path: &ObjectPath,
+        interface: &str,
+    ) -> Result<Option<InterfaceVersion>> {
+        self.body.clear();
+        self.body
+            .arguments((interface, org_freedesktop_dbus_properties::VERSION))?;
+
+        let m = self
+            .send
+            .method_call(path, "Get")
+            .with_destination(destination)
+            .with_interface(org_freedesktop_dbus_properties::INTERFACE)
+            .with_body(&self.body);
+
+        let serial = m.serial();
+        self.send.write_message(m)?;
+
+        if let Err(error) = self.wait_for_reply(serial).await {
+            return match error.response_error_name() {
+                Some(
+                    org_freedesktop_dbus_properties::UNKNOWN_PROPERTY
+                    | org_freedesktop_dbus::INVALID_ARGS,
+                ) => Ok(None),
+                _ => Err(error),
+            };
+        }
+
+        let message = self.recv.last_message_no_deferred()?;
+        let value = message.body().decode::<Variant<'_>>()?;
+        Ok(Some(InterfaceVersion::from_variant(&value)?))
+    }
+
+    /// Get all properties of `interface` on the object at `path` owned by
+    /// `destination`.
+    ///
+    /// The properties are decoded one at a time as the returned
+    /// [`PropertyEntries`] is advanced, which borrows the reply from the
+    /// connection. This avoids materializing every value at once for services
+    /// which expose very large property maps.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::{Connection, ObjectPath};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/NetworkManager");
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::system_bus().await?;
+    ///
+    /// let properties = c
+    ///     .get_all_properties("org.freedesktop.NetworkManager", PATH, "org.freedesktop.NetworkManager")
+    ///     .await?;
+    ///
+    /// for entry in properties {
+    ///     let (name, value) = entry?;
+    ///     println!("{name}: {value:?}");
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub async fn get_all_properties(
+        &mut self,
+        destination: &str,
+        path: &ObjectPath,
+        interface: &str,
+    ) -> Result<PropertyEntries<'_>> {
+        self.body.clear();
+        self.body.store(interface)?;
+
+        let m = self
+            .send
+            .method_call(path, org_freedesktop_dbus_properties::GET_ALL)
+            .with_destination(destination)
+            .with_interface(org_freedesktop_dbus_properties::INTERFACE)
+            .with_body(&self.body);
+
+        let serial = m.serial();
+        self.send.write_message(m)?;
+        self.wait_for_reply(serial).await?;
+
+        let message = self.recv.last_message_no_deferred()?;
+        PropertyEntries::from_message(&message)
+    }
+
+    /// Get all objects managed by the object at `path` owned by `destination`,
+    /// including the properties of all of their interfaces.
+    ///
+    /// Like [`Connection::get_all_properties`], the objects are decoded one at
+    /// a time as the returned [`ManagedObjects`] is advanced.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::{Connection, ObjectPath};
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::system_bus().await?;
+    ///
+    /// let objects = c
+    ///     .get_managed_objects("org.bluez", ObjectPath::ROOT)
+    ///     .await?;
+    ///
+    /// for entry in objects {
+    ///     let (path, interfaces) = entry?;
+    ///
+    ///     for entry in interfaces {
+    ///         let (interface, _) = entry?;
+    ///         println!("{path}: {interface}");
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub async fn get_managed_objects(
+        &mut self,
+        destination: &str,
+        path: &ObjectPath,
+    ) -> Result<ManagedObjects<'_>> {
+        let m = self
+            .send
+            .method_call(
+                path,
+                org_freedesktop_dbus_object_manager::GET_MANAGED_OBJECTS,
+            )
+            .with_destination(destination)
+            .with_interface(org_freedesktop_dbus_object_manager::INTERFACE);
+
+        let serial = m.serial();
+        self.send.write_message(m)?;
+        self.wait_for_reply(serial).await?;
+
+        let message = self.recv.last_message_no_deferred()?;
+        ManagedObjects::from_message(&message)
+    }
+
+    /// Get the introspection XML of the object at `path` owned by
+    /// `destination`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::{Connection, ObjectPath};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    /// let xml = c.introspect("org.freedesktop.DBus", PATH).await?;
+    /// println!("{xml}");
+    /// # Ok(()) }
+    /// ```
+    pub async fn introspect(&mut self, destination: &str, path: &ObjectPath) -> Result<String> {
+        let message = self
+            .call_method(destination, path, INTROSPECTABLE, "Introspect", |_| Ok(()))
+            .await?;
+
+        let xml = message.body().read::<str>()?;
+        Ok(xml.to_owned())
+    }
+
+    /// Call a method with string arguments which are parsed according to
+    /// `signature`, and wait for its reply.
+    ///
+    /// This is the programmatic equivalent of `busctl call`, where for
+    /// example the argument `"10"` is sent as a `u32` if the signature is
+    /// `u`. See [`BodyBuf::store_parsed`] for the syntax of arguments.
+    ///
+    /// # Errors
+    ///
+    /// Errors if an argument can't be parsed as its corresponding type, or if
+    /// the remote end responds with an error.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::{Connection, ObjectPath, Signature};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    ///
+    /// let reply = c.call_parsed(
+    ///     "org.freedesktop.DBus",
+    ///     PATH,
+    ///     "org.freedesktop.DBus",
+    ///     "GetNameOwner",
+    ///     Signature::STRING,
+    ///     &["org.freedesktop.DBus"],
+    /// ).await?;
+    ///
+    /// println!("{}", reply.body().read::<str>()?);
+    /// # Ok(()) }
+    /// ```
+    pub async fn call_parsed(
+        &mut self,
+        destination: &str,
+        path: &ObjectPath,
+        interface: &str,
+        member: &str,
+        signature: &Signature,
+        arguments: &[&str],
+    ) -> Result<MessageBuf> {
+        self.body.clear();
+        self.body.store_parsed(signature, arguments)?;
+
+        let m = self
+            .send
+            .method_call(path, member)
+            .with_destination(destination)
+            .with_interface(interface)
+            .with_body(&self.body);
+
+        let serial = m.serial();
+        self.send.write_message(m)?;
+        self.wait_for_reply(serial).await?;
+
+        let message = self.recv.last_message_no_deferred()?;
+        Ok(message.to_owned())
+    }
+
+    /// Call a method with dynamically typed arguments which are encoded
+    /// according to `signature`, and wait for its reply.
+    ///
+    /// See [`BodyBuf::store_values`] for how values are encoded.
+    ///
+    /// # Errors
+    ///
+    /// Errors if an argument doesn't match its corresponding type, or if the
+    /// remote end responds with an error.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::{Connection, ObjectPath, Signature, Value};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    ///
+    /// let reply = c.call_values(
+    ///     "org.freedesktop.DBus",
+    ///     PATH,
+    ///     "org.freedesktop.DBus",
+    ///     "GetNameOwner",
+    ///     Signature::STRING,
+    ///     &[Value::String(String::from("org.freedesktop.DBus"))],
+    /// ).await?;
+    ///
+    /// println!("{}", reply.body().read::<str>()?);
+    /// # Ok(()) }
+    /// ```
+    pub async fn call_values(
+        &mut self,
+        destination: &str,
+        path: &ObjectPath,
+        interface: &str,
+        member: &str,
+        signature: &Signature,
+        arguments: &[Value],
+    ) -> Result<MessageBuf> {
+        self.body.clear();
+        self.body.store_values(signature, arguments)?;
+
+        let m = self
+            .send
+            .method_call(path, member)
+            .with_destination(destination)
+            .with_interface(interface)
+            .with_body(&self.body);
+
+        let serial = m.serial();
+        self.send.write_message(m)?;
+        self.wait_for_reply(serial).await?;
+
+        let message = self.recv.last_message_no_deferred()?;
+        Ok(message.to_owned())
+    }
+
+    /// Send a method call and decode the body of its reply as `T`.
+    ///
+    /// The signature of the reply must match the signature of `T` exactly,
+    /// which for multiple return values means that `T` is a tuple, like
+    /// `(u32, String)`. Since `T` can't borrow from the reply, it has to
+    /// consist of owned values.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the remote end responds with an error, or if the reply has a
+    /// different signature than `T`. In the latter case the error describes
+    /// both the expected and the actual signature, and
+    /// [`Error::is_signature_mismatch`] returns `true` for it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::{BodyBuf, Connection, ObjectPath};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    ///
+    /// let mut body = BodyBuf::new();
+    /// body.store("org.freedesktop.DBus")?;
+    ///
+    /// let m = c.method_call(PATH, "GetConnectionUnixUser")
+    ///     .with_destination("org.freedesktop.DBus")
+    ///     .with_interface("org.freedesktop.DBus")
+    ///     .with_body(&body);
+    ///
+    /// let uid = c.call_typed::<u32>(m).await?;
+    /// println!("The bus is running as uid {uid}");
+    /// # Ok(()) }
+    /// ```
+    pub async fn call_typed<T>(&mut self, message: Message<'_>) -> Result<T>
+    where
+        T: for<'de> FromBody<'de>,
+    {
+        let serial = message.serial();
+        self.send.write_message(message)?;
+        self.wait_for_reply(serial).await?;
+
+        let message = self.recv.last_message_no_deferred()?;
+        message.body().decode::<T>()
+    }
+
+    /// Send a method call and receive its reply interleaved with the signals
+    /// matching `rule`.
+    ///
+    /// Some services report progress through signals tied to a request, for
+    /// example by emitting them on an object path derived from a token passed
+    /// in the call, before and after replying. The rule is added to the bus
+    /// before the method call is sent so that no signals are missed, and is
+    /// removed once the returned [`CallWithSignals`] is dropped or through
+    /// [`CallWithSignals::finish`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if the match rule can't be added, or if the method call can't
+    /// be written.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::{BodyBuf, CallEvent, Connection, MatchRule, ObjectPath};
+    /// use tokio_dbus::proto::MessageType;
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Jobs");
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    ///
+    /// let mut body = BodyBuf::new();
+    /// body.store("backup-1")?;
+    ///
+    /// let m = c.method_call(PATH, "Start")
+    ///     .with_destination("se.tedro.Jobs")
+    ///     .with_interface("se.tedro.Jobs")
+    ///     .with_body(&body);
+    ///
+    /// let rule = MatchRule::new()
+    ///     .with_message_type(MessageType::SIGNAL)
+    ///     .with_path(PATH)
+    ///     .with_interface("se.tedro.Jobs")
+    ///     .with_arg(0, "backup-1");
+    ///
+    /// let mut call = c.call_with_signals(m, rule).await?;
+    ///
+    /// // NB: If the call fails, the rule is removed once `call` is dropped.
+    /// loop {
+    ///     match call.next().await? {
+    ///         CallEvent::Return(..) => println!("Job started"),
+    ///         CallEvent::Signal(m) if m.member() == Some("Progress") => {
+    ///             let (_, percent) = m.body().decode::<(String, u32)>()?;
+    ///             println!("Progress: {percent}%");
+    ///         }
+    ///         CallEvent::Signal(m) if m.member() == Some("Finished") => break,
+    ///         _ => {}
+    ///     }
+    /// }
+    ///
+    /// call.finish().await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn call_with_signals<'r>(
+        &mut self,
+        message: Message<'_>,
+        rule: MatchRule<'r>,
+    ) -> Result<CallWithSignals<'_, 'r>> {
+        let rule_string = rule.to_string();
+        self.add_match(&rule_string).await?;
+
+        let serial = message.serial();
+
+        if let Err(error) = self.send.write_message(message) {
+            // NB: The original error is more relevant than a failure to remove
+            // the rule, which most likely fails for the same reason.
+            _ = self.remove_match(&rule_string).await;
+            return Err(error);
+        }
+
+        Ok(CallWithSignals::new(self, serial, rule))
+    }
+
+    /// Send a message whose body ends with a byte array of `len` bytes read
+    /// from `reader`.
+    ///
+    /// The body of `message` holds the arguments which precede the array. The
+    /// contents of the array are read and sent in chunks, so the whole payload
+    /// never has to be resident in memory. This is useful for file transfer
+    /// style interfaces. Messages received while sending are deferred, and
+    /// returned by subsequent calls to [`wait()`].
+    ///
+    /// [`wait()`]: Self::wait
+    ///
+    /// # Errors
+    ///
+    /// Errors if the message can't be written, see
+    /// [`SendBuf::begin_byte_array`], or if `reader` fails or ends before
+    /// `len` bytes have been read. In the latter cases, and if the future is
+    /// cancelled before completing, the connection can no longer send
+    /// messages since its peer expects the remainder of the array.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio::fs::File;
+    /// use tokio_dbus::{BodyBuf, Connection, ObjectPath};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Files");
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    ///
+    /// let file = File::open("image.png").await?;
+    /// let len = file.metadata().await?.len() as usize;
+    ///
+    /// let mut body = BodyBuf::new();
+    /// body.store("image.png")?;
+    ///
+    /// let m = c.method_call(PATH, "Upload")
+    ///     .with_destination("se.tedro.Files")
+    ///     .with_body(&body);
+    ///
+    /// c.send_byte_array(m, len, file).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn send_byte_array<R>(
+        &mut self,
+        message: Message<'_>,
+        len: usize,
+        mut reader: R,
+    ) -> Result<()>
+    where
+        R: AsyncRead + Unpin,
+    {
+        self.send.begin_byte_array(message, len)?;
+
+        let mut chunk = vec![0; len.min(BYTE_ARRAY_CHUNK)];
+
+        loop {
+            self.flush_deferred().await?;
+
+            let remaining = self.send.remaining_byte_array();
+
+            if remaining == 0 {
+                return Ok(());
+            }
+
+            let n = remaining.min(chunk.len());
+
+            let n = match reader.read(&mut chunk[..n]).await {
+                Ok(0) => {
+                    return Err(Error::from(io::Error::from(io::ErrorKind::UnexpectedEof)));
+                }
+                Ok(n) => n,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error.into()),
+            };
+
+            self.send.extend_byte_array(&chunk[..n])?;
+        }
+    }
+
+    /// Add a match rule to the bus, which subscribes this connection to the
+    /// matching signals.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::Connection;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    /// c.add_match("type='signal',interface='org.freedesktop.DBus'").await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn add_match(&mut self, rule: &str) -> Result<()> {
+        self.body.clear();
+        self.body.store(rule)?;
+
         let m = self
             .send
-            .method_call(org_freedesktop_dbus::PATH, "Hello")
-            .with_destination(org_freedesktop_dbus::DESTINATION);
+            .method_call(org_freedesktop_dbus::PATH, "AddMatch")
+            .with_destination(org_freedesktop_dbus::DESTINATION)
+            .with_interface(org_freedesktop_dbus::INTERFACE)
+            .with_body(&self.body);
 
         let serial = m.serial();
         self.send.write_message(m)?;
-        self.state = ConnectionState::HelloSent(serial);
+        self.wait_for_reply(serial).await?;
         Ok(())
     }
 
-    /// Request the given well-known name.
-    pub async fn request_name(&mut self, name: &str, flags: NameFlag) -> Result<NameReply> {
+    /// Remove a match rule previously added through [`add_match()`].
+    ///
+    /// The rule must be identical to the one which was added.
+    ///
+    /// [`add_match()`]: Self::add_match
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::Connection;
+    ///
+    /// const RULE: &str = "type='signal',interface='org.freedesktop.DBus'";
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    /// c.add_match(RULE).await?;
+    /// c.remove_match(RULE).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn remove_match(&mut self, rule: &str) -> Result<()> {
         self.body.clear();
-        self.body.store(name)?;
-        self.body.store(flags)?;
+        self.body.store(rule)?;
 
         let m = self
             .send
-            .method_call(org_freedesktop_dbus::PATH, "RequestName")
+            .method_call(org_freedesktop_dbus::PATH, "RemoveMatch")
             .with_destination(org_freedesktop_dbus::DESTINATION)
+            .with_interface(org_freedesktop_dbus::INTERFACE)
             .with_body(&self.body);
 
         let serial = m.serial();
         self.send.write_message(m)?;
+        self.wait_for_reply(serial).await?;
+        Ok(())
+    }
+
+    /// Queue a request to remove a match rule without waiting for the bus to
+    /// reply to it.
+    pub(super) fn queue_remove_match(&mut self, rule: &str) -> Result<()> {
+        self.body.clear();
+        self.body.store(rule)?;
+
+        let m = self
+            .send
+            .method_call(org_freedesktop_dbus::PATH, "RemoveMatch")
+            .with_destination(org_freedesktop_dbus::DESTINATION)
+            .with_interface(org_freedesktop_dbus::INTERFACE)
+            .with_flags(Flags::NO_REPLY_EXPECTED)
+            .with_body(&self.body);
+
+        self.send.write_message(m)?;
+        Ok(())
+    }
+
+    /// Turn this connection into a monitor, which receives a copy of every
+    /// message on the bus matching any of the given `rules`, or every message
+    /// if `rules` is empty.
+    ///
+    /// Once this has succeeded, the connection can no longer be used to send
+    /// messages and the bus closes it if it tries to. This usually requires
+    /// elevated privileges on the system bus.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::{Connection, MatchRule};
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    /// c.become_monitor(&[MatchRule::new().with_sender("org.freedesktop.Notifications")]).await?;
+    ///
+    /// loop {
+    ///     c.wait().await?;
+    ///     let message = c.last_message()?;
+    ///     println!("{message}");
+    /// }
+    /// # }
+    /// ```
+    pub async fn become_monitor(&mut self, rules: &[MatchRule<'_>]) -> Result<()> {
+        self.body.clear();
+
+        let mut array = self.body.store_array::<ty::Str>()?;
+
+        for rule in rules {
+            array.store(rule.to_string().as_str());
+        }
+
+        array.finish()?;
+        // NB: Flags are reserved and must be zero.
+        self.body.store(0u32)?;
+
+        let m = self
+            .send
+            .method_call(org_freedesktop_dbus::PATH, "BecomeMonitor")
+            .with_destination(org_freedesktop_dbus::DESTINATION)
+            .with_interface(org_freedesktop_dbus::MONITORING_INTERFACE)
+            .with_body(&self.body);
+
+        let serial = m.serial();
+        self.send.write_message(m)?;
+        self.wait_for_reply(serial).await?;
+        Ok(())
+    }
+
+    /// Call `member` of `interface` on the object at `path` owned by
+    /// `destination` with the arguments written by `arguments`, and wait for
+    /// its reply.
+    ///
+    /// This is used by the typed clients of well-known services. Once this
+    /// returns, the reply is the last message which isn't deferred, so its
+    /// reference can be taken through [`Connection::last_message_ref`].
+    pub(crate) async fn call_method<F>(
+        &mut self,
+        destination: &str,
+        path: &ObjectPath,
+        interface: &str,
+        member: &str,
+        arguments: F,
+    ) -> Result<Message<'_>>
+    where
+        F: FnOnce(&mut BodyBuf) -> Result<()>,
+    {
+        self.body.clear();
+        arguments(&mut self.body)?;
+
+        let m = self
+            .send
+            .method_call(path, member)
+            .with_destination(destination)
+            .with_interface(interface)
+            .with_body(&self.body);
+
+        let serial = m.serial();
+        self.send.write_message(m)?;
+        self.wait_for_reply(serial).await?;
+        self.recv.last_message_no_deferred()
+    }
 
+    /// Wait for the reply to the method call with the given `serial`.
+    ///
+    /// Any other messages received in the meantime are deferred. Once this
+    /// returns successfully the reply is available through
+    /// [`RecvBuf::last_message_no_deferred`], and an error reply is converted
+    /// into an error.
+    async fn wait_for_reply(&mut self, serial: NonZeroU32) -> Result<()> {
         loop {
             self.wait_no_deferred().await?;
             let message = self.recv.last_message_no_deferred()?;
 
             match message.kind {
                 MessageKind::MethodReturn { reply_serial } if reply_serial == serial => {
-                    let reply = message.body().load::<NameReply>()?;
-                    return Ok(reply);
+                    return Ok(());
                 }
                 MessageKind::Error {
                     error_name,
                     reply_serial,
                 } if reply_serial == serial => {
-                    let message = message.body().read::<str>()?;
-
                     return Err(Error::new(ErrorKind::ResponseError(
                         error_name.into(),
-                        message.into(),
+                        message.error_message().unwrap_or_default().into(),
                     )));
                 }
                 _ => {
@@ -368,19 +1934,29 @@ impl Connection {
             let mut interest = Interest::READABLE;
 
             if !self.send.buf().is_empty() {
+                if self.send_failed {
+                    return Err(Error::new(ErrorKind::PartiallySent));
+                }
+
                 interest |= Interest::WRITABLE;
             } else if flush {
                 return Ok(false);
             }
 
-            let mut guard = self.transport.ready_mut(interest).await?;
+            let waiting_for = if interest.is_writable() {
+                "read+write"
+            } else {
+                "read"
+            };
+
+            let mut guard = ready(waiting_for, self.transport.ready_mut(interest)).await?;
 
             if guard.ready().is_readable() {
                 match guard.get_inner_mut().recv_message(&mut self.recv) {
                     Ok(()) => {
                         return Ok(true);
                     }
-                    Err(e) if e.would_block() => {
+                    Err(e) if e.is_would_block() => {
                         guard.clear_ready_matching(Ready::READABLE);
                         continue;
                     }
@@ -389,15 +1965,43 @@ impl Connection {
             }
 
             if guard.ready().is_writable() {
-                match guard.get_inner().send_buf(self.send.buf_mut()) {
+                match guard.get_inner().send_messages(&mut self.send) {
                     Ok(()) => {}
-                    Err(e) if e.would_block() => {
+                    Err(e) if e.is_would_block() => {
                         guard.clear_ready_matching(Ready::WRITABLE);
                         continue;
                     }
-                    Err(e) => return Err(e),
+                    Err(e) => {
+                        // NB: The peer has received the start of a message,
+                        // so sending anything else would desynchronize it.
+                        self.send_failed = self.send.is_partially_sent();
+                        return Err(e);
+                    }
                 }
             }
         }
     }
 }
+
+/// Wait for the connection to become ready.
+///
+/// With the `tracing` feature enabled, the wait is instrumented with a span
+/// describing what the connection is waiting for.
+#[inline]
+async fn ready<F>(waiting_for: &'static str, future: F) -> F::Output
+where
+    F: Future,
+{
+    #[cfg(feature = "tracing")]
+    {
+        use tracing::Instrument;
+        let span = tracing::trace_span!("tokio_dbus::ready", waiting_for);
+        future.instrument(span).await
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = waiting_for;
+        future.await
+    }
+}
@@ -0,0 +1,158 @@
+use std::io;
+use std::os::fd::RawFd;
+
+use crate::error::{Error, ErrorKind, Result};
+use crate::sasl::{self, Auth};
+
+/// The credentials of the process at the other end of a connection.
+///
+/// See [`Connection::peer_credentials`].
+///
+/// [`Connection::peer_credentials`]: crate::Connection::peer_credentials
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Credentials {
+    pid: Option<u32>,
+    uid: u32,
+    gid: u32,
+}
+
+impl Credentials {
+    /// The process identifier of the peer, if it is available on the current
+    /// platform.
+    #[inline]
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    /// The effective user identifier of the peer.
+    #[inline]
+    pub fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    /// The effective group identifier of the peer.
+    #[inline]
+    pub fn gid(&self) -> u32 {
+        self.gid
+    }
+
+    /// Check the identity claimed by a peer through `EXTERNAL` authentication
+    /// against these credentials.
+    ///
+    /// A server accepting a connection should only accept the authentication
+    /// if this succeeds. The payload is a user id encoded as by
+    /// [`sasl::encode_uid`], and an empty payload asks the server to use the
+    /// credentials of the socket as they are.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the payload is not an encoded user id, or if the user id
+    /// doesn't match [`Credentials::uid`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::{Auth, Connection};
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let c = Connection::session_bus().await?;
+    /// let credentials = c.peer_credentials()?;
+    ///
+    /// let uid = credentials.uid();
+    /// credentials.verify_external(Auth::external_from_u32_ascii_hex(&mut [0; 32], uid))?;
+    ///
+    /// let mut buf = [0; 32];
+    /// let other = Auth::external_from_u32_ascii_hex(&mut buf, uid.wrapping_add(1));
+    /// assert!(credentials.verify_external(other).is_err());
+    /// # Ok(()) }
+    /// ```
+    pub fn verify_external(&self, auth: Auth<'_>) -> Result<()> {
+        let Auth::External(payload) = auth;
+
+        if payload.is_empty() {
+            return Ok(());
+        }
+
+        match sasl::decode_uid(payload) {
+            Some(uid) if uid == self.uid => Ok(()),
+            claimed => Err(Error::new(ErrorKind::ExternalIdentityMismatch(
+                claimed, self.uid,
+            ))),
+        }
+    }
+}
+
+/// Get the credentials of the peer connected to the given socket.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn peer_credentials(fd: RawFd) -> io::Result<Credentials> {
+    use std::mem::{size_of, MaybeUninit};
+
+    let mut ucred = MaybeUninit::<libc::ucred>::zeroed();
+    let mut len = size_of::<libc::ucred>() as libc::socklen_t;
+
+    // SAFETY: The buffer and length passed in are valid for a `ucred`.
+    let result = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            ucred.as_mut_ptr().cast(),
+            &mut len,
+        )
+    };
+
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // SAFETY: The structure has been zero-initialized and populated above.
+    let ucred = unsafe { ucred.assume_init() };
+
+    Ok(Credentials {
+        pid: u32::try_from(ucred.pid).ok(),
+        uid: ucred.uid,
+        gid: ucred.gid,
+    })
+}
+
+/// Get the credentials of the peer connected to the given socket.
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+))]
+pub(crate) fn peer_credentials(fd: RawFd) -> io::Result<Credentials> {
+    let mut uid = 0;
+    let mut gid = 0;
+
+    // SAFETY: The out pointers are valid for the duration of the call.
+    let result = unsafe { libc::getpeereid(fd, &mut uid, &mut gid) };
+
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(Credentials {
+        pid: None,
+        uid,
+        gid,
+    })
+}
+
+/// Get the credentials of the peer connected to the given socket.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+)))]
+pub(crate) fn peer_credentials(_: RawFd) -> io::Result<Credentials> {
+    Err(io::Error::from(io::ErrorKind::Unsupported))
+}
@@ -0,0 +1,77 @@
+use std::num::NonZeroU32;
+
+use crate::error::Result;
+use crate::org_freedesktop_dbus;
+use crate::{BodyBuf, SendBuf};
+
+/// A hook which replaces the handshake performed once a connection has been
+/// authenticated.
+///
+/// By default a connection sends the `Hello` message to the message bus and
+/// waits for its reply, which carries the unique name of the connection. Some
+/// brokers speak the D-Bus wire protocol but expect a different handshake, or
+/// none at all. Such handshakes can be implemented through this trait and set
+/// with [`ConnectionBuilder::handshake`].
+///
+/// [`ConnectionBuilder::handshake`]: crate::ConnectionBuilder::handshake
+///
+/// # Examples
+///
+/// A handshake which doesn't register with the broker at all:
+///
+/// ```no_run
+/// use std::num::NonZeroU32;
+///
+/// use tokio_dbus::{BodyBuf, ConnectionBuilder, Handshake, Result, SendBuf};
+///
+/// struct NoHello;
+///
+/// impl Handshake for NoHello {
+///     fn hello(&self, _: &mut SendBuf, _: &mut BodyBuf) -> Result<Option<NonZeroU32>> {
+///         Ok(None)
+///     }
+/// }
+///
+/// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+/// let c = ConnectionBuilder::new()
+///     .handshake(NoHello)
+///     .connect()
+///     .await?;
+///
+/// assert_eq!(c.name(), None);
+/// # Ok(()) }
+/// ```
+pub trait Handshake {
+    /// Write messages which are sent before the hello message.
+    ///
+    /// By default this doesn't write anything.
+    #[allow(unused_variables)]
+    fn pre_hello(&self, send: &mut SendBuf, body: &mut BodyBuf) -> Result<()> {
+        Ok(())
+    }
+
+    /// Write the hello message, returning its serial.
+    ///
+    /// The connection is ready once the reply to the message with the returned
+    /// serial has been received. If the reply carries a single string, it's
+    /// used as the unique name of the connection. If `None` is returned, the
+    /// connection is ready immediately and doesn't have a unique name.
+    ///
+    /// By default this writes the `Hello` message defined by the
+    /// specification.
+    #[allow(unused_variables)]
+    fn hello(&self, send: &mut SendBuf, body: &mut BodyBuf) -> Result<Option<NonZeroU32>> {
+        let m = send
+            .method_call(org_freedesktop_dbus::PATH, "Hello")
+            .with_destination(org_freedesktop_dbus::DESTINATION);
+
+        let serial = m.serial();
+        send.write_message(m)?;
+        Ok(Some(serial))
+    }
+}
+
+/// The handshake defined by the specification.
+pub(crate) struct DefaultHandshake;
+
+impl Handshake for DefaultHandshake {}
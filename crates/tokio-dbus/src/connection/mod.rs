@@ -6,5 +6,29 @@ mod transport;
 pub use self::builder::ConnectionBuilder;
 mod builder;
 
-pub use self::connection::Connection;
+pub(crate) use self::handshake::DefaultHandshake;
+pub use self::handshake::Handshake;
+mod handshake;
+
+pub use self::connection::{Connection, ConnectionStatus};
 mod connection;
+
+pub use self::shared::{SharedConnection, WeakConnection};
+mod shared;
+
+pub use self::reply_builder::ReplyBuilder;
+mod reply_builder;
+
+pub use self::send_permit::SendPermit;
+mod send_permit;
+
+pub use self::call_with_signals::{CallEvent, CallWithSignals};
+mod call_with_signals;
+
+#[cfg(feature = "libc")]
+pub use self::credentials::Credentials;
+#[cfg(feature = "libc")]
+mod credentials;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,201 @@
+use std::num::NonZeroU32;
+
+use crate::error::Result;
+use crate::{Arguments, BodyBuf, Flags, Message, MessageKind, SendBuf, ToDbusError};
+
+/// Builder for a reply to a received message.
+///
+/// This is constructed through [`Connection::reply_to`]. It keeps its own copy
+/// of the header fields needed to reply, so it only borrows the send half of
+/// the connection and the received message doesn't need to be kept around.
+///
+/// [`Connection::reply_to`]: crate::Connection::reply_to
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_dbus::Connection;
+///
+/// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+/// let mut c = Connection::session_bus().await?;
+/// c.wait_no_deferred().await?;
+///
+/// let message_ref = c.last_message_ref()?;
+///
+/// c.reply_to(&message_ref)?
+///     .arguments(("Hello World!", 42u32))?
+///     .write()?;
+///
+/// c.flush().await?;
+/// # Ok(()) }
+/// ```
+pub struct ReplyBuilder<'a> {
+    send: &'a mut SendBuf,
+    body: &'a mut BodyBuf,
+    reply_serial: NonZeroU32,
+    destination: Option<Box<str>>,
+    correlation_id: Option<Box<str>>,
+    error_name: Option<Box<str>>,
+}
+
+impl<'a> ReplyBuilder<'a> {
+    pub(super) fn new(
+        send: &'a mut SendBuf,
+        body: &'a mut BodyBuf,
+        reply_serial: NonZeroU32,
+        destination: Option<Box<str>>,
+        correlation_id: Option<Box<str>>,
+    ) -> Self {
+        body.clear();
+
+        Self {
+            send,
+            body,
+            reply_serial,
+            destination,
+            correlation_id,
+            error_name: None,
+        }
+    }
+
+    /// Get the serial of the message being replied to.
+    pub fn reply_serial(&self) -> NonZeroU32 {
+        self.reply_serial
+    }
+
+    /// Get the destination of the reply, which is the sender of the message
+    /// being replied to.
+    pub fn destination(&self) -> Option<&str> {
+        self.destination.as_deref()
+    }
+
+    /// Get the correlation id of the reply, which is the correlation id of the
+    /// message being replied to.
+    ///
+    /// See [`Message::correlation_id`].
+    pub fn correlation_id(&self) -> Option<&str> {
+        self.correlation_id.as_deref()
+    }
+
+    /// Reply with an error with the given name instead of a method return.
+    ///
+    /// By convention, the first argument of an error reply is a string
+    /// describing the error.
+    #[must_use]
+    pub fn with_error(self, error_name: &str) -> Self {
+        Self {
+            error_name: Some(error_name.into()),
+            ..self
+        }
+    }
+
+    /// Use a copy of `body` as the body of the reply.
+    ///
+    /// This replaces anything that has previously been stored in the body.
+    #[must_use]
+    pub fn with_body(self, body: &BodyBuf) -> Self {
+        self.body.clone_from(body);
+        self
+    }
+
+    /// Extend the body of the reply with the given arguments.
+    pub fn arguments<T>(self, arguments: T) -> Result<Self>
+    where
+        T: Arguments,
+    {
+        self.body.arguments(arguments)?;
+        Ok(self)
+    }
+
+    /// Get mutable access to the body of the reply.
+    pub fn body_mut(&mut self) -> &mut BodyBuf {
+        self.body
+    }
+
+    /// Write a reply corresponding to the result of handling the message,
+    /// returning the serial it was assigned.
+    ///
+    /// If `result` is `Ok`, its value is used as the arguments of a method
+    /// return. Otherwise the error is converted into an error reply through
+    /// [`ToDbusError`]. Anything previously stored in the body is discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::Connection;
+    ///
+    /// fn handle(name: &str) -> Result<u32, std::io::Error> {
+    ///     Ok(std::fs::read_to_string(name)?.len() as u32)
+    /// }
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    /// c.wait_no_deferred().await?;
+    ///
+    /// let message_ref = c.last_message_ref()?;
+    /// let name = c.read_message(&message_ref)?.body().read::<str>()?.to_owned();
+    ///
+    /// c.reply_to(&message_ref)?.write_result(handle(&name))?;
+    /// c.flush().await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn write_result<T, E>(mut self, result: Result<T, E>) -> Result<NonZeroU32>
+    where
+        T: Arguments,
+        E: ToDbusError,
+    {
+        self.body.clear();
+
+        match result {
+            Ok(value) => {
+                self.body.arguments(value)?;
+                self.error_name = None;
+            }
+            Err(error) => {
+                error.write_body(self.body)?;
+                self.error_name = Some(error.error_name().into());
+            }
+        }
+
+        self.write()
+    }
+
+    /// Write the reply to the send buffer, returning the serial it was
+    /// assigned.
+    ///
+    /// Like with [`Connection::write_message`], the reply is sent during the
+    /// next call to [`Connection::wait`] or [`Connection::flush`].
+    ///
+    /// [`Connection::write_message`]: crate::Connection::write_message
+    /// [`Connection::wait`]: crate::Connection::wait
+    /// [`Connection::flush`]: crate::Connection::flush
+    pub fn write(self) -> Result<NonZeroU32> {
+        let kind = match &self.error_name {
+            Some(error_name) => MessageKind::Error {
+                error_name,
+                reply_serial: self.reply_serial,
+            },
+            None => MessageKind::MethodReturn {
+                reply_serial: self.reply_serial,
+            },
+        };
+
+        let serial = self.send.next_serial();
+
+        let message = Message {
+            kind,
+            path: None,
+            serial,
+            flags: Flags::EMPTY,
+            interface: None,
+            destination: self.destination.as_deref(),
+            sender: None,
+            correlation_id: self.correlation_id.as_deref(),
+            body: self.body.as_body(),
+            kernel_timestamp: None,
+        };
+
+        self.send.write_message(message)?;
+        Ok(serial)
+    }
+}
@@ -0,0 +1,108 @@
+use tokio::sync::MutexGuard;
+
+use crate::error::Result;
+use crate::{Message, ObjectPath, SendBuf};
+
+use super::Connection;
+
+/// A permit to write one message to a connection.
+///
+/// This is constructed through [`Connection::acquire_send_permit`] or
+/// [`SharedConnection::acquire_send_permit`]. Acquiring a permit waits until
+/// previously written messages have been sent, and reserves room in the send
+/// buffer for a message of the requested size. Producers which emit messages
+/// in a loop, like signal emitters, can use this to apply backpressure instead
+/// of growing the send buffer indefinitely.
+///
+/// Writing a message through the permit consumes it, so that a new permit has
+/// to be acquired for every message.
+///
+/// [`Connection::acquire_send_permit`]: crate::Connection::acquire_send_permit
+/// [`SharedConnection::acquire_send_permit`]: crate::SharedConnection::acquire_send_permit
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_dbus::{BodyBuf, Connection, ObjectPath};
+///
+/// const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/DBusExample");
+///
+/// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+/// let mut c = Connection::session_bus().await?;
+/// let mut body = BodyBuf::new();
+///
+/// for n in 0..1024u32 {
+///     body.clear();
+///     body.store(n)?;
+///
+///     let mut permit = c.acquire_send_permit(256).await?;
+///
+///     let m = permit
+///         .signal("Tick")
+///         .with_path(PATH)
+///         .with_interface("se.tedro.DBusExample")
+///         .with_body(&body);
+///
+///     permit.write_message(m)?;
+/// }
+///
+/// c.flush().await?;
+/// # Ok(()) }
+/// ```
+pub struct SendPermit<'a> {
+    inner: Inner<'a>,
+}
+
+enum Inner<'a> {
+    Borrowed(&'a mut SendBuf),
+    Locked(MutexGuard<'a, Connection>),
+}
+
+impl<'a> SendPermit<'a> {
+    pub(super) fn borrowed(send: &'a mut SendBuf) -> Self {
+        Self {
+            inner: Inner::Borrowed(send),
+        }
+    }
+
+    pub(super) fn locked(connection: MutexGuard<'a, Connection>) -> Self {
+        Self {
+            inner: Inner::Locked(connection),
+        }
+    }
+
+    /// Access the send buffer the permit writes to, such as to allocate
+    /// serials for messages.
+    pub fn send_buf(&mut self) -> &mut SendBuf {
+        match &mut self.inner {
+            Inner::Borrowed(send) => send,
+            Inner::Locked(connection) => &mut connection.send,
+        }
+    }
+
+    /// Construct a method call.
+    ///
+    /// See [`SendBuf::method_call`].
+    pub fn method_call<'m>(&mut self, path: &'m ObjectPath, member: &'m str) -> Message<'m> {
+        self.send_buf().method_call(path, member)
+    }
+
+    /// Construct a signal.
+    ///
+    /// See [`SendBuf::signal`].
+    pub fn signal<'m>(&mut self, member: &'m str) -> Message<'m> {
+        self.send_buf().signal(member)
+    }
+
+    /// Write a message to the send buffer, consuming the permit.
+    ///
+    /// The message is sent once the connection is driven, such as through
+    /// [`Connection::flush`] or by acquiring the next permit.
+    ///
+    /// [`Connection::flush`]: crate::Connection::flush
+    ///
+    /// See [`SendBuf::write_message`].
+    pub fn write_message(mut self, message: Message<'_>) -> Result<()> {
+        self.send_buf().write_message(message)
+    }
+}
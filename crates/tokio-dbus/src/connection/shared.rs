@@ -0,0 +1,227 @@
+use std::fmt;
+use std::sync::{Arc, Weak};
+
+use tokio::sync::{Mutex, MutexGuard, OnceCell};
+
+use crate::error::Result;
+use crate::Error;
+
+use super::{Connection, ConnectionBuilder, SendPermit};
+
+static SESSION: OnceCell<SharedConnection> = OnceCell::const_new();
+static SYSTEM: OnceCell<SharedConnection> = OnceCell::const_new();
+
+/// A cheaply cloneable handle to a connection shared by multiple users.
+///
+/// This is constructed through [`Connection::session_shared`] or
+/// [`Connection::system_shared`], which lazily set up a single connection per
+/// process that libraries and applications can use instead of each opening
+/// their own connection to the bus.
+///
+/// Access to the connection is serialized through [`SharedConnection::lock`].
+/// Since messages are received through the connection that holds the lock,
+/// users should avoid holding it for longer than necessary, such as while
+/// waiting indefinitely for messages.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_dbus::Connection;
+///
+/// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+/// let shared = Connection::session_shared().await?;
+///
+/// let names = shared.lock().await.list_names().await?;
+/// assert!(names.iter().any(|name| name == "org.freedesktop.DBus"));
+/// # Ok(()) }
+/// ```
+#[derive(Clone)]
+pub struct SharedConnection {
+    inner: Arc<Mutex<Connection>>,
+}
+
+impl SharedConnection {
+    /// Wrap an existing connection so that it can be shared.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::{Connection, SharedConnection};
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let c = Connection::session_bus().await?;
+    /// let shared = SharedConnection::new(c);
+    ///
+    /// let other = shared.clone();
+    ///
+    /// tokio::spawn(async move {
+    ///     other.lock().await.add_match("type='signal'").await
+    /// });
+    /// # Ok(()) }
+    /// ```
+    pub fn new(connection: Connection) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(connection)),
+        }
+    }
+
+    /// Lock the shared connection for exclusive use.
+    pub async fn lock(&self) -> MutexGuard<'_, Connection> {
+        self.inner.lock().await
+    }
+
+    /// Try to lock the shared connection without waiting, returning `None` if
+    /// it is currently in use.
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, Connection>> {
+        self.inner.try_lock().ok()
+    }
+
+    /// Lock the shared connection and acquire a permit to write one message of
+    /// up to `size` bytes.
+    ///
+    /// The connection stays locked until the permit is used or dropped, so
+    /// other users of the connection can't fill the reserved room in the
+    /// meantime.
+    ///
+    /// See [`Connection::acquire_send_permit`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::{Connection, ObjectPath};
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let shared = Connection::session_shared().await?;
+    ///
+    /// let mut permit = shared.acquire_send_permit(128).await?;
+    /// let m = permit.signal("Ping").with_path(ObjectPath::ROOT);
+    /// permit.write_message(m)?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn acquire_send_permit(&self, size: usize) -> Result<SendPermit<'_>> {
+        let mut connection = self.inner.lock().await;
+        connection.reserve_send(size).await?;
+        Ok(SendPermit::locked(connection))
+    }
+
+    /// Construct a weak handle to the shared connection.
+    ///
+    /// See [`WeakConnection`].
+    pub fn downgrade(&self) -> WeakConnection {
+        WeakConnection {
+            inner: Arc::downgrade(&self.inner),
+        }
+    }
+}
+
+impl fmt::Debug for SharedConnection {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedConnection").finish_non_exhaustive()
+    }
+}
+
+/// A weak handle to a [`SharedConnection`], which doesn't keep the connection
+/// alive.
+///
+/// This is constructed through [`SharedConnection::downgrade`], and is intended
+/// for background tasks which should stop once every other user of the
+/// connection has dropped their handle, rather than keeping it open
+/// indefinitely.
+///
+/// Note that the process-wide connections returned by
+/// [`Connection::session_shared`] and [`Connection::system_shared`] are never
+/// dropped, so weak handles to them can always be upgraded.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use tokio_dbus::{Connection, SharedConnection};
+///
+/// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+/// let c = Connection::session_bus().await?;
+/// let shared = SharedConnection::new(c);
+/// let weak = shared.downgrade();
+///
+/// let task = tokio::spawn(async move {
+///     while let Some(shared) = weak.upgrade() {
+///         shared.lock().await.flush().await?;
+///         drop(shared);
+///         tokio::time::sleep(Duration::from_secs(1)).await;
+///     }
+///
+///     Ok::<_, tokio_dbus::Error>(())
+/// });
+///
+/// // Dropping the last strong handle closes the connection and stops the task.
+/// drop(shared);
+/// task.await.unwrap()?;
+/// # Ok(()) }
+/// ```
+#[derive(Clone)]
+pub struct WeakConnection {
+    inner: Weak<Mutex<Connection>>,
+}
+
+impl WeakConnection {
+    /// Try to upgrade to a [`SharedConnection`], returning `None` if the
+    /// connection has been dropped.
+    pub fn upgrade(&self) -> Option<SharedConnection> {
+        Some(SharedConnection {
+            inner: self.inner.upgrade()?,
+        })
+    }
+}
+
+impl fmt::Debug for WeakConnection {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeakConnection").finish_non_exhaustive()
+    }
+}
+
+impl Connection {
+    /// Get a handle to the shared session bus connection of the process,
+    /// connecting it using the default configuration on first use.
+    ///
+    /// If connecting fails, the error is returned and a subsequent call tries
+    /// again.
+    ///
+    /// Note that the connection is registered with the runtime in which it was
+    /// first created, so it stops working if that runtime is shut down.
+    ///
+    /// See [`SharedConnection`].
+    pub async fn session_shared() -> Result<SharedConnection> {
+        shared(&SESSION, ConnectionBuilder::new().session_bus()).await
+    }
+
+    /// Get a handle to the shared system bus connection of the process,
+    /// connecting it using the default configuration on first use.
+    ///
+    /// If connecting fails, the error is returned and a subsequent call tries
+    /// again.
+    ///
+    /// Note that the connection is registered with the runtime in which it was
+    /// first created, so it stops working if that runtime is shut down.
+    ///
+    /// See [`SharedConnection`].
+    pub async fn system_shared() -> Result<SharedConnection> {
+        shared(&SYSTEM, ConnectionBuilder::new().system_bus()).await
+    }
+}
+
+async fn shared(
+    cell: &'static OnceCell<SharedConnection>,
+    builder: &mut ConnectionBuilder,
+) -> Result<SharedConnection> {
+    let shared = cell
+        .get_or_try_init(|| async {
+            let connection = builder.connect().await?;
+            Ok::<_, Error>(SharedConnection::new(connection))
+        })
+        .await?;
+
+    Ok(shared.clone())
+}
@@ -0,0 +1,2031 @@
+use std::io::{Read, Write};
+use std::net::Shutdown;
+use std::num::NonZeroU32;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::buf::UnalignedBuf;
+use crate::error::Result;
+use crate::org_freedesktop_dbus::{self, DISCONNECTED, LOCAL_INTERFACE, LOCAL_PATH};
+use crate::sasl::SaslResponse;
+use crate::{proto, BodyBuf, Message, MessageKind, ObjectPath, SendBuf, Signature, Variant};
+
+use super::transport::{connect_any, parse_address_bytes};
+use super::{sasl_recv, Connection, DefaultHandshake, Handshake, SharedConnection, Transport};
+
+#[tokio::test]
+async fn disconnected_signal() -> Result<()> {
+    let (a, b) = UnixStream::pair()?;
+
+    let mut transport = Transport::from_std(a);
+    transport.sasl_begin(&mut UnalignedBuf::new())?;
+    drop(b);
+
+    let mut c = Connection::new(transport)?;
+    c.wait().await?;
+
+    let message = c.last_message()?;
+    assert_eq!(message.interface(), Some(LOCAL_INTERFACE));
+    assert_eq!(
+        message.kind(),
+        MessageKind::Signal {
+            member: DISCONNECTED
+        }
+    );
+    assert_eq!(message.path(), Some(LOCAL_PATH));
+
+    // The local signal doesn't consume a serial of the connection.
+    assert_eq!(c.send.next_serial().get(), 1);
+
+    let error = c.wait().await.unwrap_err();
+    assert!(error.is_disconnected());
+    Ok(())
+}
+
+#[test]
+fn parse_addresses() -> Result<()> {
+    let candidates =
+        parse_address_bytes(b"unix:path=/tmp/a,guid=1234;tcp:host=localhost;unix:path=/tmp/b")?;
+    assert_eq!(
+        candidates,
+        [PathBuf::from("/tmp/a"), PathBuf::from("/tmp/b")]
+    );
+
+    let error = parse_address_bytes(b"tcp:host=localhost;nonce-tcp:host=localhost").unwrap_err();
+    assert_eq!(error.to_string(), "Unsupported d-bus transport `tcp`");
+    assert!(parse_address_bytes(b"").is_err());
+    Ok(())
+}
+
+#[test]
+fn sasl_responses() -> Result<()> {
+    assert!(matches!(sasl_recv(b"OK 1234\r\n")?, SaslResponse::Ok(..)));
+    assert!(matches!(
+        sasl_recv(b"ERROR \"Unknown command\"\r\n")?,
+        SaslResponse::Error
+    ));
+    #[cfg(feature = "libc")]
+    assert!(matches!(
+        sasl_recv(b"AGREE_UNIX_FD\r\n")?,
+        SaslResponse::AgreeUnixFd
+    ));
+    assert!(sasl_recv(b"REJECTED EXTERNAL\r\n").is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn connect_first_available() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("tokio-dbus-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+
+    let missing = dir.join("missing");
+    let path = dir.join("bus");
+    _ = std::fs::remove_file(&path);
+    let _listener = UnixListener::bind(&path)?;
+
+    let stream = connect_any(vec![missing, path.clone()], Duration::from_secs(10)).await?;
+    assert_eq!(stream.peer_addr()?.as_pathname(), Some(path.as_path()));
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn wait_timeout_partial_message() -> Result<()> {
+    let (a, mut b) = UnixStream::pair()?;
+
+    let mut transport = Transport::from_std(a);
+    transport.sasl_begin(&mut UnalignedBuf::new())?;
+    let mut c = Connection::new(transport)?;
+
+    let mut send = SendBuf::new();
+    let m = send
+        .signal("Tick")
+        .with_path(ObjectPath::ROOT)
+        .with_interface("se.tedro.Test");
+    send.write_message(m)?;
+    let bytes = send.buf().get();
+
+    assert!(c.wait_timeout(Duration::from_millis(10)).await?.is_none());
+
+    // Split the message in the middle of the header to ensure that what has
+    // been read so far is retained across timeouts.
+    for chunk in [&bytes[..7], &bytes[7..20]] {
+        b.write_all(chunk)?;
+        assert!(c.wait_timeout(Duration::from_millis(10)).await?.is_none());
+    }
+
+    let before = Instant::now();
+    b.write_all(&bytes[20..])?;
+
+    let message = c
+        .wait_timeout(Duration::from_secs(10))
+        .await?
+        .expect("expected message");
+
+    assert_eq!(message.interface(), Some("se.tedro.Test"));
+    assert_eq!(message.kind(), MessageKind::Signal { member: "Tick" });
+    assert_eq!(message.path(), Some(ObjectPath::ROOT));
+
+    assert!(c.last_message_ref()?.received() >= before);
+    Ok(())
+}
+
+#[cfg(all(target_os = "linux", feature = "libc"))]
+#[tokio::test]
+async fn kernel_timestamps() -> Result<()> {
+    use std::os::fd::FromRawFd;
+    use std::time::SystemTime;
+
+    // NB: Linux doesn't report timestamps for stream sockets, so a sequenced
+    // packet socket is used where each packet is sized to match a read.
+    let mut fds = [0; 2];
+    let result =
+        unsafe { libc::socketpair(libc::AF_UNIX, libc::SOCK_SEQPACKET, 0, fds.as_mut_ptr()) };
+    assert_eq!(result, 0);
+    let (a, mut b) = unsafe {
+        (
+            UnixStream::from_raw_fd(fds[0]),
+            UnixStream::from_raw_fd(fds[1]),
+        )
+    };
+
+    let mut transport = Transport::from_std(a);
+    transport.set_kernel_timestamps(true)?;
+    transport.sasl_begin(&mut UnalignedBuf::new())?;
+    let mut c = Connection::new(transport)?;
+
+    let mut send = SendBuf::new();
+    let m = send
+        .signal("Tick")
+        .with_path(ObjectPath::ROOT)
+        .with_interface("se.tedro.Test");
+    send.write_message(m)?;
+    let bytes = send.buf().get();
+
+    let before = SystemTime::now();
+    b.write_all(&bytes[..16])?;
+    b.write_all(&bytes[16..])?;
+    c.wait().await?;
+    let after = SystemTime::now();
+
+    let message = c.last_message()?;
+    assert_eq!(message.member(), Some("Tick"));
+
+    let timestamp = message
+        .kernel_timestamp()
+        .expect("expected kernel timestamp");
+    assert!(timestamp >= before - Duration::from_secs(1) && timestamp <= after);
+    assert_eq!(c.last_message_ref()?.kernel_timestamp(), Some(timestamp));
+    Ok(())
+}
+
+#[cfg(all(target_os = "linux", feature = "libc"))]
+#[tokio::test]
+async fn peer_credentials() -> Result<()> {
+    let (a, _b) = UnixStream::pair()?;
+    let c = Connection::new(Transport::from_std(a))?;
+
+    let credentials = c.peer_credentials()?;
+    assert_eq!(credentials.uid(), unsafe { libc::getuid() });
+    assert_eq!(credentials.gid(), unsafe { libc::getgid() });
+    assert_eq!(credentials.pid(), Some(std::process::id()));
+    Ok(())
+}
+
+#[cfg(all(target_os = "linux", feature = "libc"))]
+#[tokio::test]
+async fn verify_external() -> Result<()> {
+    use crate::Auth;
+
+    let (a, _b) = UnixStream::pair()?;
+    let c = Connection::new(Transport::from_std(a))?;
+
+    let credentials = c.peer_credentials()?;
+    let uid = credentials.uid();
+
+    credentials.verify_external(Auth::external_from_u32_ascii_hex(&mut [0; 32], uid))?;
+    credentials.verify_external(Auth::External(b""))?;
+
+    let mut buf = [0; 32];
+    let other = Auth::external_from_u32_ascii_hex(&mut buf, uid.wrapping_add(1));
+    assert!(credentials.verify_external(other).is_err());
+    assert!(credentials.verify_external(Auth::External(b"zz")).is_err());
+    Ok(())
+}
+
+/// Construct a connection where `bytes` have been sent by the peer.
+fn connection_with_input(bytes: &[u8]) -> Result<(Connection, UnixStream)> {
+    let (a, mut b) = UnixStream::pair()?;
+
+    let mut transport = Transport::from_std(a);
+    transport.sasl_begin(&mut UnalignedBuf::new())?;
+    b.write_all(bytes)?;
+    Ok((Connection::new(transport)?, b))
+}
+
+/// Construct a pair of connections which are connected to each other.
+fn connected_pair() -> Result<(Connection, Connection)> {
+    let (a, b) = UnixStream::pair()?;
+    let (mut a2, mut b2) = (a.try_clone()?, b.try_clone()?);
+
+    let mut ours = Transport::from_std(a);
+    ours.sasl_begin(&mut UnalignedBuf::new())?;
+    let mut theirs = Transport::from_std(b);
+    theirs.sasl_begin(&mut UnalignedBuf::new())?;
+
+    // Discard the SASL data sent by both ends.
+    for stream in [&mut a2, &mut b2] {
+        stream.read_exact(&mut [0; 8])?;
+    }
+
+    Ok((Connection::new(ours)?, Connection::new(theirs)?))
+}
+
+/// Encode two signals where the first has a zero serial.
+fn zero_serial_then_valid() -> Result<Vec<u8>> {
+    let mut send = SendBuf::new();
+
+    let m = send
+        .signal("Invalid")
+        .with_path(ObjectPath::ROOT)
+        .with_interface("se.tedro.Test");
+    send.write_message(m)?;
+    let mut bytes = send.buf().get().to_vec();
+    bytes[8..12].copy_from_slice(&0u32.to_ne_bytes());
+
+    let mut send = SendBuf::new();
+    let m = send
+        .signal("Valid")
+        .with_path(ObjectPath::ROOT)
+        .with_interface("se.tedro.Test");
+    send.write_message(m)?;
+    bytes.extend_from_slice(send.buf().get());
+    Ok(bytes)
+}
+
+#[tokio::test]
+async fn protocol_violation() -> Result<()> {
+    let (mut c, _b) = connection_with_input(&zero_serial_then_valid()?)?;
+
+    let error = c.wait().await.unwrap_err();
+    let violation = error
+        .protocol_violation()
+        .expect("expected protocol violation");
+    assert_eq!(violation.serial(), 0);
+    assert_eq!(violation.offset(), 8);
+    assert!(violation.is_recoverable());
+
+    // The connection is still in sync.
+    c.wait().await?;
+    let message = c.last_message()?;
+    assert_eq!(message.kind(), MessageKind::Signal { member: "Valid" });
+    assert_eq!(message.path(), Some(ObjectPath::ROOT));
+    Ok(())
+}
+
+#[tokio::test]
+async fn skip_invalid_messages() -> Result<()> {
+    let (mut c, _b) = connection_with_input(&zero_serial_then_valid()?)?;
+    c.skip_invalid_messages = true;
+
+    c.wait().await?;
+    let message = c.last_message()?;
+    assert_eq!(message.kind(), MessageKind::Signal { member: "Valid" });
+    assert_eq!(message.path(), Some(ObjectPath::ROOT));
+    Ok(())
+}
+
+#[tokio::test]
+async fn unrecoverable_protocol_violation() -> Result<()> {
+    let mut send = SendBuf::new();
+    let m = send
+        .signal("Invalid")
+        .with_path(ObjectPath::ROOT)
+        .with_interface("se.tedro.Test");
+    send.write_message(m)?;
+    let mut bytes = send.buf().get().to_vec();
+    bytes[4..8].copy_from_slice(&u32::MAX.to_ne_bytes());
+
+    let (mut c, _b) = connection_with_input(&bytes)?;
+    c.skip_invalid_messages = true;
+
+    let error = c.wait().await.unwrap_err();
+    let violation = error
+        .protocol_violation()
+        .expect("expected protocol violation");
+    assert_eq!(violation.body_length(), u32::MAX);
+    assert_eq!(violation.offset(), 4);
+    assert!(!violation.is_recoverable());
+    Ok(())
+}
+
+#[tokio::test]
+async fn reply_to() -> Result<()> {
+    let (mut c, mut peer) = connected_pair()?;
+
+    let m = peer
+        .method_call(ObjectPath::ROOT, "Ping")
+        .with_sender(":1.42");
+    let serial = m.serial();
+    peer.write_message(m)?;
+    peer.flush().await?;
+
+    c.wait().await?;
+    let message_ref = c.last_message_ref()?;
+
+    let reply = c.reply_to(&message_ref)?;
+    assert_eq!(reply.reply_serial(), serial);
+    assert_eq!(reply.destination(), Some(":1.42"));
+    reply.arguments(("Pong", 42u32))?.write()?;
+
+    c.reply_to(&message_ref)?
+        .with_error("se.tedro.Error")
+        .arguments(("Failed",))?
+        .write()?;
+
+    c.flush().await?;
+
+    peer.wait().await?;
+    let message = peer.last_message()?;
+    assert_eq!(
+        message.kind(),
+        MessageKind::MethodReturn {
+            reply_serial: serial
+        }
+    );
+    assert_eq!(message.destination(), Some(":1.42"));
+    assert_eq!(message.body().decode::<(&str, u32)>()?, ("Pong", 42));
+
+    peer.wait().await?;
+    let message = peer.last_message()?;
+    assert_eq!(
+        message.kind(),
+        MessageKind::Error {
+            error_name: "se.tedro.Error",
+            reply_serial: serial
+        }
+    );
+    assert_eq!(message.body().decode::<(&str,)>()?, ("Failed",));
+    Ok(())
+}
+
+#[tokio::test]
+async fn empty_body_with_signature() -> Result<()> {
+    let mut send = SendBuf::new();
+    let mut body = BodyBuf::new();
+    body.store("Hello")?;
+
+    let m = send
+        .signal("Empty")
+        .with_path(ObjectPath::ROOT)
+        .with_interface("se.tedro.Test")
+        .with_body(&body);
+    send.write_message(m)?;
+
+    // Strip the body while keeping the signature header.
+    let mut bytes = send.buf().get().to_vec();
+    bytes.truncate(bytes.len() - body.len());
+    bytes[4..8].copy_from_slice(&0u32.to_ne_bytes());
+
+    let (mut c, _b) = connection_with_input(&bytes)?;
+    let error = c.wait().await.unwrap_err();
+    assert!(error.is_body_signature_mismatch());
+
+    let violation = error
+        .protocol_violation()
+        .expect("expected protocol violation");
+    assert!(violation.is_recoverable());
+    Ok(())
+}
+
+#[tokio::test]
+async fn header_fields() -> Result<()> {
+    let mut send = SendBuf::new();
+    let mut body = BodyBuf::new();
+    body.store("Hello")?;
+
+    let m = send
+        .signal("Fields")
+        .with_path(ObjectPath::ROOT)
+        .with_interface("se.tedro.Test")
+        .with_body(&body);
+    send.write_message(m)?;
+
+    let (mut c, _b) = connection_with_input(send.buf().get())?;
+    c.wait_no_deferred().await?;
+    let message_ref = c.last_message_ref()?;
+
+    let mut fields = Vec::new();
+
+    for field in c.header_fields(&message_ref)? {
+        let field = field?;
+        assert_eq!(field.signature(), field.value().signature());
+        fields.push((field.code(), field.variant()?));
+    }
+
+    assert_eq!(
+        fields,
+        [
+            (proto::Variant::PATH, Variant::ObjectPath(ObjectPath::ROOT)),
+            (proto::Variant::MEMBER, Variant::String("Fields")),
+            (proto::Variant::INTERFACE, Variant::String("se.tedro.Test")),
+            (
+                proto::Variant::SIGNATURE,
+                Variant::Signature(Signature::STRING)
+            ),
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn correlation_id() -> Result<()> {
+    const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Test");
+
+    let mut send = SendBuf::new();
+
+    let m = send
+        .method_call(PATH, "Trace")
+        .with_correlation_id("4bf92f3577b34da6");
+    send.write_message(m)?;
+
+    let (mut c, _b) = connection_with_input(send.buf().get())?;
+    c.wait_no_deferred().await?;
+    let message_ref = c.last_message_ref()?;
+
+    let message = c.read_message(&message_ref)?;
+    assert_eq!(message.correlation_id(), Some("4bf92f3577b34da6"));
+
+    let field = c
+        .header_fields(&message_ref)?
+        .find_map(|field| {
+            field
+                .ok()
+                .filter(|f| f.code() == proto::Variant::CORRELATION_ID)
+        })
+        .expect("correlation id field");
+
+    assert_eq!(field.variant()?, Variant::String("4bf92f3577b34da6"));
+
+    let reply = c.reply_to(&message_ref)?;
+    assert_eq!(reply.correlation_id(), Some("4bf92f3577b34da6"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn weak_connection() -> Result<()> {
+    let (c, _peer) = connection_with_input(&[])?;
+    let shared = SharedConnection::new(c);
+    let weak = shared.downgrade();
+
+    let upgraded = weak.upgrade().expect("connection is alive");
+    drop(shared);
+    assert!(weak.upgrade().is_some());
+
+    drop(upgraded);
+    assert!(weak.upgrade().is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn portal_request() -> Result<()> {
+    use std::collections::HashMap;
+
+    use crate::org_freedesktop_portal::{self, ResponseCode, HANDLE_TOKEN, REQUEST_INTERFACE};
+    use crate::{ty, Value};
+
+    let (mut c, mut bus) = connected_pair()?;
+
+    // Act as both the bus and the portal.
+    let bus = tokio::spawn(async move {
+        let mut members = Vec::new();
+
+        loop {
+            bus.wait().await?;
+            let message = bus.last_message()?;
+
+            let MessageKind::MethodCall { member, .. } = message.kind() else {
+                continue;
+            };
+
+            members.push(member.to_owned());
+            let message_ref = bus.last_message_ref()?;
+
+            match member {
+                "Hello" => {
+                    bus.reply_to(&message_ref)?.arguments((":1.7",))?.write()?;
+                }
+                "Screenshot" => {
+                    let (_, options) = message.body().decode::<(&str, HashMap<String, Value>)>()?;
+                    let token = options[HANDLE_TOKEN].as_str().unwrap_or_default();
+                    let path = org_freedesktop_portal::request_path(":1.7", token)?;
+
+                    // Deliver the response before the reply, which means that
+                    // the caller has to pick it up from its deferred messages.
+                    let (_, send, body) = bus.buffers();
+                    body.clear();
+                    body.store_parsed(Signature::new("ua{sv}")?, &["1", "{}"])?;
+                    let m = send
+                        .signal("Response")
+                        .with_path(&path)
+                        .with_interface(REQUEST_INTERFACE)
+                        .with_body(body);
+                    send.write_message(m)?;
+
+                    bus.reply_to(&message_ref)?.arguments((&*path,))?.write()?;
+                }
+                "RemoveMatch" => {
+                    bus.reply_to(&message_ref)?.write()?;
+                    bus.flush().await?;
+                    return Ok::<_, crate::Error>(members);
+                }
+                _ => {
+                    bus.reply_to(&message_ref)?.write()?;
+                }
+            }
+
+            bus.flush().await?;
+        }
+    });
+
+    c.hello(&DefaultHandshake)?;
+    c.hello_reply().await?;
+
+    let response = org_freedesktop_portal::request(
+        &mut c,
+        "org.freedesktop.portal.Screenshot",
+        "Screenshot",
+        |body, token| {
+            body.store("")?;
+            let mut options = body.store_dict::<ty::Str, ty::Variant>()?;
+            options.store(HANDLE_TOKEN, Variant::String(token));
+            options.finish()?;
+            Ok(())
+        },
+    )
+    .await?;
+
+    assert_eq!(response.code, ResponseCode::CANCELLED);
+    assert!(response.results.is_empty());
+
+    let members = bus.await.expect("bus task panicked")?;
+    assert_eq!(members, ["Hello", "AddMatch", "Screenshot", "RemoveMatch"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn portal_request_error() -> Result<()> {
+    use crate::org_freedesktop_portal;
+
+    let (mut c, mut bus) = connected_pair()?;
+
+    let bus = tokio::spawn(async move {
+        let mut members = Vec::new();
+
+        loop {
+            bus.wait().await?;
+            let message = bus.last_message()?;
+
+            let MessageKind::MethodCall { member, .. } = message.kind() else {
+                continue;
+            };
+
+            members.push(member.to_owned());
+            let message_ref = bus.last_message_ref()?;
+
+            match member {
+                "Hello" => {
+                    bus.reply_to(&message_ref)?.arguments((":1.7",))?.write()?;
+                }
+                // Reply with something which isn't a request handle.
+                "Screenshot" => {
+                    bus.reply_to(&message_ref)?.arguments((42u32,))?.write()?;
+                }
+                _ => {
+                    bus.reply_to(&message_ref)?.write()?;
+                }
+            }
+
+            bus.flush().await?;
+
+            if members.iter().filter(|m| *m == "RemoveMatch").count() == 2 {
+                return Ok::<_, crate::Error>(members);
+            }
+        }
+    });
+
+    c.hello(&DefaultHandshake)?;
+    c.hello_reply().await?;
+
+    // Both failing to store the arguments and a malformed reply remove the
+    // subscription to the response.
+    let result = org_freedesktop_portal::request(
+        &mut c,
+        "org.freedesktop.portal.Screenshot",
+        "Screenshot",
+        |body, _| body.store_parsed(Signature::new("y")?, &["256"]),
+    )
+    .await;
+
+    assert!(result.is_err());
+
+    let result = org_freedesktop_portal::request(
+        &mut c,
+        "org.freedesktop.portal.Screenshot",
+        "Screenshot",
+        |body, _| body.store(""),
+    )
+    .await;
+
+    assert!(result.is_err());
+
+    let members = bus.await.expect("bus task panicked")?;
+    assert_eq!(
+        members,
+        [
+            "Hello",
+            "AddMatch",
+            "RemoveMatch",
+            "AddMatch",
+            "Screenshot",
+            "RemoveMatch"
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn name_takeover() -> Result<()> {
+    use std::sync::{Arc, Mutex};
+
+    use crate::org_freedesktop_dbus::{
+        NameFlag, NameReply, DESTINATION, INTERFACE, NAME_ACQUIRED, NAME_LOST,
+    };
+
+    const NAME: &str = "se.tedro.Example";
+
+    let (mut c, mut bus) = connected_pair()?;
+
+    let bus = tokio::spawn(async move {
+        loop {
+            bus.wait().await?;
+            let message_ref = bus.last_message_ref()?;
+            let message = bus.read_message(&message_ref)?;
+
+            match message.kind() {
+                MessageKind::MethodCall {
+                    member: "Hello", ..
+                } => {
+                    bus.reply_to(&message_ref)?.arguments((":1.7",))?.write()?;
+                }
+                MessageKind::MethodCall {
+                    member: "RequestName",
+                    ..
+                } => {
+                    bus.reply_to(&message_ref)?
+                        .arguments((NameReply::IN_QUEUE,))?
+                        .write()?;
+
+                    let (_, send, body) = bus.buffers();
+
+                    let m = send
+                        .signal("Unrelated")
+                        .with_path(ObjectPath::ROOT)
+                        .with_interface("se.tedro.Test");
+                    send.write_message(m)?;
+
+                    // A malformed signal from the bus is ignored.
+                    body.clear();
+                    body.store(42u32)?;
+
+                    let m = send
+                        .signal(NAME_LOST)
+                        .with_path(org_freedesktop_dbus::PATH)
+                        .with_interface(INTERFACE)
+                        .with_sender(DESTINATION)
+                        .with_body(&*body);
+                    send.write_message(m)?;
+
+                    body.clear();
+                    body.store(NAME)?;
+
+                    // Signals which are spoofed by other peers are delivered
+                    // like any other message.
+                    for (sender, member) in [
+                        (":1.99", NAME_ACQUIRED),
+                        (DESTINATION, NAME_ACQUIRED),
+                        (":1.99", NAME_LOST),
+                        (DESTINATION, NAME_LOST),
+                    ] {
+                        let m = send
+                            .signal(member)
+                            .with_path(org_freedesktop_dbus::PATH)
+                            .with_interface(INTERFACE)
+                            .with_sender(sender)
+                            .with_body(&*body);
+                        send.write_message(m)?;
+                    }
+
+                    let m = send
+                        .signal("After")
+                        .with_path(ObjectPath::ROOT)
+                        .with_interface("se.tedro.Test");
+                    send.write_message(m)?;
+                    bus.flush().await?;
+                    return Ok::<_, crate::Error>(());
+                }
+                _ => {}
+            }
+
+            bus.flush().await?;
+        }
+    });
+
+    c.hello(&DefaultHandshake)?;
+    c.hello_reply().await?;
+
+    let lost = Arc::new(Mutex::new(Vec::new()));
+
+    c.on_name_lost({
+        let lost = lost.clone();
+        move |name| lost.lock().unwrap().push(name.to_owned())
+    });
+
+    let reply = c.acquire_name(NAME, NameFlag::REPLACE_EXISTING).await?;
+    assert_eq!(reply, NameReply::PRIMARY_OWNER);
+    assert!(lost.lock().unwrap().is_empty());
+
+    for member in ["Unrelated", NAME_ACQUIRED, NAME_LOST] {
+        c.wait().await?;
+        assert_eq!(c.last_message()?.member(), Some(member));
+    }
+
+    assert!(lost.lock().unwrap().is_empty());
+
+    c.wait().await?;
+    assert_eq!(c.last_message()?.member(), Some("After"));
+    assert_eq!(*lost.lock().unwrap(), [NAME]);
+
+    bus.await.expect("bus task panicked")?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn call_typed() -> Result<()> {
+    const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Test");
+
+    let mut send = SendBuf::new();
+    let mut body = BodyBuf::new();
+
+    // NB: Replies to the first two calls made by the connection.
+    for n in [1, 2] {
+        let serial = NonZeroU32::new(n).expect("non-zero serial");
+        let call = Message::method_call(PATH, "Get", serial);
+        body.clear();
+        body.arguments((n, "Hello"))?;
+        let m = call.method_return(send.next_serial()).with_body(&body);
+        send.write_message(m)?;
+    }
+
+    let (mut c, _b) = connection_with_input(send.buf().get())?;
+
+    let m = c.method_call(PATH, "Get");
+    assert_eq!(
+        c.call_typed::<(u32, String)>(m).await?,
+        (1, String::from("Hello"))
+    );
+
+    let m = c.method_call(PATH, "Get");
+    let error = c.call_typed::<(u32, u32)>(m).await.unwrap_err();
+    assert!(error.is_signature_mismatch());
+    assert_eq!(
+        error.to_string(),
+        "Expected body with signature \"uu\" but found \"us\""
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn write_result() -> Result<()> {
+    use std::io;
+
+    const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Test");
+
+    let (mut c, mut peer) = connected_pair()?;
+
+    let peer = tokio::spawn(async move {
+        for n in [0u32, 7] {
+            peer.wait().await?;
+            let message_ref = peer.last_message_ref()?;
+
+            let result = match n {
+                0 => Err(io::Error::new(io::ErrorKind::NotFound, "No such thing")),
+                n => Ok((n,)),
+            };
+
+            peer.reply_to(&message_ref)?.write_result(result)?;
+            peer.flush().await?;
+        }
+
+        Ok::<_, crate::Error>(())
+    });
+
+    let m = c.method_call(PATH, "Get");
+    let error = c.call_typed::<u32>(m).await.unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Response error: org.freedesktop.DBus.Error.Failed: No such thing"
+    );
+
+    let m = c.method_call(PATH, "Get");
+    assert_eq!(c.call_typed::<u32>(m).await?, 7);
+
+    peer.await.expect("peer panicked")?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn trailing_arguments() -> Result<()> {
+    const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Test");
+
+    let (mut c, mut peer) = connected_pair()?;
+
+    let peer = tokio::spawn(async move {
+        for _ in 0..2 {
+            peer.wait().await?;
+            let message_ref = peer.last_message_ref()?;
+
+            let mut body = peer.read_message(&message_ref)?.body();
+            let n = body.load::<u32>()?;
+            let result = body.ensure_consumed().map(|()| (n,));
+
+            peer.reply_to(&message_ref)?.write_result(result)?;
+            peer.flush().await?;
+        }
+
+        Ok::<_, crate::Error>(())
+    });
+
+    let mut body = BodyBuf::new();
+    body.arguments((7u32, 8u32))?;
+
+    let m = c.method_call(PATH, "Get").with_body(&body);
+    let error = c.call_typed::<u32>(m).await.unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Response error: org.freedesktop.DBus.Error.InvalidArgs: Body has 4 trailing bytes with remaining signature \"u\""
+    );
+
+    body.clear();
+    body.store(7u32)?;
+
+    let m = c.method_call(PATH, "Get").with_body(&body);
+    assert_eq!(c.call_typed::<u32>(m).await?, 7);
+
+    peer.await.expect("peer panicked")?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn partially_sent() -> Result<()> {
+    let (mut c, mut b) = connection_with_input(&[])?;
+
+    // NB: Large enough to not fit in the socket buffer.
+    let (_, send, body) = c.buffers();
+    body.store(&vec![0u8; 1 << 22][..])?;
+
+    let m = send
+        .signal("Large")
+        .with_path(ObjectPath::ROOT)
+        .with_interface("se.tedro.Test")
+        .with_body(&*body);
+    send.write_message(m)?;
+
+    // Cancelled flushes retain the remainder of the message.
+    let flush = tokio::time::timeout(Duration::from_millis(50), c.flush()).await;
+    assert!(flush.is_err());
+    assert!(c.buffers().1.is_partially_sent());
+
+    // NB: Draining the socket makes the connection writable again, and
+    // shutting it down makes the next write fail without the connection
+    // seeing the end of its input.
+    b.set_nonblocking(true)?;
+    while b.read(&mut [0; 4096]).is_ok() {}
+    b.shutdown(Shutdown::Read)?;
+
+    let error = c.flush().await.unwrap_err();
+    assert!(error.is_disconnected());
+
+    let (_, send, _) = c.buffers();
+    let m = send
+        .signal("After")
+        .with_path(ObjectPath::ROOT)
+        .with_interface("se.tedro.Test");
+    send.write_message(m)?;
+
+    let error = c.flush().await.unwrap_err();
+    assert!(error.is_disconnected());
+    assert_eq!(
+        error.to_string(),
+        "Sending failed in the middle of a message, so the connection can't send anything else"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn cancel_partial_receive() -> Result<()> {
+    let mut send = SendBuf::new();
+    let mut body = BodyBuf::new();
+
+    for member in ["First", "Second"] {
+        body.clear();
+        body.store(member)?;
+
+        let m = send
+            .signal(member)
+            .with_path(ObjectPath::ROOT)
+            .with_interface("se.tedro.Test")
+            .with_body(&body);
+        send.write_message(m)?;
+    }
+
+    let (mut c, mut b) = connection_with_input(&[])?;
+    let bytes = send.buf().get();
+    let mut members = Vec::new();
+
+    // Cancel the wait after every single byte, which covers every point at
+    // which receiving the header or the body can be interrupted.
+    for byte in bytes {
+        b.write_all(&[*byte])?;
+
+        match tokio::time::timeout(Duration::from_millis(1), c.wait()).await {
+            Ok(result) => {
+                result?;
+                let message = c.last_message()?;
+                members.push(message.member().map(str::to_owned));
+                assert_eq!(message.body().read::<str>()?, message.member().unwrap());
+            }
+            Err(..) => {
+                assert!(c.last_message().is_err());
+            }
+        }
+    }
+
+    // NB: The last wait might have been cancelled before it got to run.
+    while members.len() < 2 {
+        c.wait().await?;
+        members.push(c.last_message()?.member().map(str::to_owned));
+    }
+
+    assert_eq!(
+        members,
+        [Some(String::from("First")), Some(String::from("Second"))]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn serve_until() -> Result<()> {
+    let (mut c, mut peer) = connected_pair()?;
+
+    let m = peer.method_call(ObjectPath::ROOT, "Ping");
+    let serial = m.serial();
+    peer.write_message(m)?;
+    peer.flush().await?;
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let mut tx = Some(tx);
+
+    // The scope completes as soon as the reply has been buffered, so it's
+    // only sent by the final flush.
+    let output = c
+        .serve_until(rx, |recv, send, body| {
+            let message = recv.last_message()?;
+            body.store("Pong")?;
+            let reply = message.method_return(send.next_serial()).with_body(body);
+            send.write_message(reply)?;
+
+            if let Some(tx) = tx.take() {
+                let _ = tx.send(42u32);
+            }
+
+            Ok(())
+        })
+        .await?;
+
+    assert_eq!(output.ok(), Some(42));
+
+    peer.wait().await?;
+    let message = peer.last_message()?;
+    assert_eq!(
+        message.kind(),
+        MessageKind::MethodReturn {
+            reply_serial: serial
+        }
+    );
+    assert_eq!(message.body().read::<str>()?, "Pong");
+    Ok(())
+}
+
+#[tokio::test]
+async fn serve_strict_until() -> Result<()> {
+    use crate::org_freedesktop_dbus::INVALID_ARGS;
+
+    let (mut c, mut peer) = connected_pair()?;
+
+    let mut body = BodyBuf::new();
+    let mut serials = Vec::new();
+
+    for trailing in [false, true] {
+        body.clear();
+        body.store("Ping")?;
+
+        if trailing {
+            body.store(42u32)?;
+        }
+
+        let m = peer.method_call(ObjectPath::ROOT, "Echo").with_body(&body);
+        serials.push(m.serial());
+        peer.write_message(m)?;
+    }
+
+    peer.flush().await?;
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let mut tx = Some(tx);
+    let mut calls = 0;
+
+    let output = c
+        .serve_strict_until(rx, |message, args, send, body| {
+            body.store(args.read::<str>()?)?;
+            let reply = message.method_return(send.next_serial()).with_body(body);
+            send.write_message(reply)?;
+
+            calls += 1;
+
+            if calls == 2 {
+                if let Some(tx) = tx.take() {
+                    let _ = tx.send(());
+                }
+            }
+
+            Ok(())
+        })
+        .await?;
+
+    assert!(output.is_ok());
+
+    peer.wait().await?;
+    let message = peer.last_message()?;
+    assert_eq!(
+        message.kind(),
+        MessageKind::MethodReturn {
+            reply_serial: serials[0]
+        }
+    );
+    assert_eq!(message.body().read::<str>()?, "Ping");
+
+    // The reply written by the handler is replaced with an error.
+    peer.wait().await?;
+    let message = peer.last_message()?;
+    assert_eq!(
+        message.kind(),
+        MessageKind::Error {
+            error_name: INVALID_ARGS,
+            reply_serial: serials[1]
+        }
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn deliver_bus_signals() -> Result<()> {
+    use crate::org_freedesktop_dbus::{BusSignal, DESTINATION, INTERFACE, NAME_OWNER_CHANGED};
+
+    let (mut c, mut peer) = connected_pair()?;
+
+    for deliver in [false, true] {
+        c.deliver_bus_signals = deliver;
+
+        let mut body = BodyBuf::new();
+        body.arguments(("se.tedro.Example", "", ":1.42"))?;
+
+        let (_, send, _) = peer.buffers();
+        let m = send
+            .signal(NAME_OWNER_CHANGED)
+            .with_path(org_freedesktop_dbus::PATH)
+            .with_interface(INTERFACE)
+            .with_sender(DESTINATION)
+            .with_body(&body);
+        send.write_message(m)?;
+
+        let m = send
+            .signal("Marker")
+            .with_path(ObjectPath::ROOT)
+            .with_interface("se.tedro.Test");
+        send.write_message(m)?;
+        peer.flush().await?;
+
+        c.wait().await?;
+
+        if deliver {
+            assert_eq!(
+                BusSignal::from_message(&c.last_message()?)?,
+                Some(BusSignal::NameOwnerChanged {
+                    name: "se.tedro.Example",
+                    old_owner: "",
+                    new_owner: ":1.42",
+                })
+            );
+
+            c.wait().await?;
+        }
+
+        let message = c.last_message()?;
+        assert_eq!(message.kind(), MessageKind::Signal { member: "Marker" });
+        assert_eq!(message.path(), Some(ObjectPath::ROOT));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn status() -> Result<()> {
+    use super::ConnectionStatus;
+
+    let (a, b) = UnixStream::pair()?;
+    let c = Connection::new(Transport::from_std(a))?;
+    assert_eq!(c.status(), ConnectionStatus::Authenticating);
+    assert_eq!(c.name(), None);
+
+    let mut transport = Transport::from_std(b);
+    transport.sasl_begin(&mut UnalignedBuf::new())?;
+    drop(c);
+
+    let mut c = Connection::new(transport)?;
+    assert_eq!(c.status(), ConnectionStatus::Ready);
+
+    c.wait().await?;
+    assert_eq!(c.status(), ConnectionStatus::Disconnected);
+    Ok(())
+}
+
+#[tokio::test]
+async fn send_byte_array() -> Result<()> {
+    let (mut c, mut peer) = connected_pair()?;
+
+    // Larger than both a single chunk and the socket buffer.
+    let data = (0..1 << 20).map(|n| n as u8).collect::<Vec<u8>>();
+
+    let mut body = BodyBuf::new();
+    body.store(7u8)?;
+
+    let m = c.method_call(ObjectPath::ROOT, "Upload").with_body(&body);
+    let (sent, received) = tokio::join!(c.send_byte_array(m, data.len(), &data[..]), peer.wait());
+    sent?;
+    received?;
+
+    let message = peer.last_message()?;
+    let mut body = message.body();
+    assert_eq!(body.signature(), "yay");
+    assert_eq!(body.load::<u8>()?, 7);
+    assert!(body.read_byte_array()? == &data[..]);
+
+    let m = c.method_call(ObjectPath::ROOT, "Upload");
+    assert!(c.send_byte_array(m, 4, &[1, 2][..]).await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn custom_handshake() -> Result<()> {
+    struct Register;
+
+    impl Handshake for Register {
+        fn pre_hello(&self, send: &mut SendBuf, _: &mut BodyBuf) -> Result<()> {
+            let m = send
+                .method_call(ObjectPath::ROOT, "Prepare")
+                .with_flags(crate::Flags::NO_REPLY_EXPECTED);
+            send.write_message(m)
+        }
+
+        fn hello(&self, send: &mut SendBuf, body: &mut BodyBuf) -> Result<Option<NonZeroU32>> {
+            body.store("example")?;
+            let m = send
+                .method_call(ObjectPath::ROOT, "Register")
+                .with_body(body);
+            let serial = m.serial();
+            send.write_message(m)?;
+            Ok(Some(serial))
+        }
+    }
+
+    struct NoHello;
+
+    impl Handshake for NoHello {
+        fn pre_hello(&self, send: &mut SendBuf, _: &mut BodyBuf) -> Result<()> {
+            let m = send
+                .method_call(ObjectPath::ROOT, "Prepare")
+                .with_flags(crate::Flags::NO_REPLY_EXPECTED);
+            send.write_message(m)
+        }
+
+        fn hello(&self, _: &mut SendBuf, _: &mut BodyBuf) -> Result<Option<NonZeroU32>> {
+            Ok(None)
+        }
+    }
+
+    let (mut c, mut bus) = connected_pair()?;
+
+    let bus = tokio::spawn(async move {
+        let mut members = Vec::new();
+
+        while members.len() < 3 {
+            bus.wait().await?;
+            let message_ref = bus.last_message_ref()?;
+            let message = bus.read_message(&message_ref)?;
+            members.push(message.member().unwrap_or_default().to_owned());
+
+            if message.member() == Some("Register") {
+                assert_eq!(message.body().decode::<&str>()?, "example");
+                bus.reply_to(&message_ref)?.write()?;
+                bus.flush().await?;
+            }
+        }
+
+        Ok::<_, crate::Error>(members)
+    });
+
+    c.hello(&Register)?;
+    c.hello_reply().await?;
+    assert_eq!(c.name(), None);
+    assert_eq!(c.status(), super::ConnectionStatus::Ready);
+
+    c.hello(&NoHello)?;
+    c.hello_reply().await?;
+    assert_eq!(c.status(), super::ConnectionStatus::Ready);
+
+    let members = bus.await.expect("bus task panicked")?;
+    assert_eq!(members, ["Prepare", "Register", "Prepare"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn sender_quota() -> Result<()> {
+    use crate::SenderQuota;
+
+    const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Test");
+
+    let mut send = SendBuf::new();
+
+    for (sender, member) in [
+        (":1.9", "A1"),
+        (":1.9", "A2"),
+        (":1.9", "A3"),
+        (":1.10", "B1"),
+    ] {
+        let m = send
+            .signal(member)
+            .with_path(ObjectPath::ROOT)
+            .with_interface("se.tedro.Test")
+            .with_sender(sender);
+        send.write_message(m)?;
+    }
+
+    let serial = NonZeroU32::new(1).expect("non-zero serial");
+    let m = Message::method_call(PATH, "Get", serial).method_return(send.next_serial());
+    send.write_message(m)?;
+
+    let quota = SenderQuota::new().with_max_messages(2);
+
+    let (mut c, _b) = connection_with_input(send.buf().get())?;
+    c.recv.set_sender_quota(Some(quota.with_drop(true)));
+
+    let m = c.method_call(PATH, "Get");
+    c.call_typed::<()>(m).await?;
+
+    let mut members = Vec::new();
+
+    for _ in 0..3 {
+        c.wait().await?;
+        members.push(c.last_message()?.member().unwrap_or_default().to_owned());
+    }
+
+    assert_eq!(members, ["A1", "A2", "B1"]);
+
+    let (mut c, _b) = connection_with_input(send.buf().get())?;
+    c.recv.set_sender_quota(Some(quota));
+
+    let m = c.method_call(PATH, "Get");
+    let error = c.call_typed::<()>(m).await.unwrap_err();
+    assert!(error.is_sender_quota_exceeded());
+    Ok(())
+}
+
+#[tokio::test]
+async fn send_permit() -> Result<()> {
+    let (c, mut peer) = connection_with_input(&[])?;
+    let shared = SharedConnection::new(c);
+
+    for member in ["First", "Second"] {
+        let mut permit = shared.acquire_send_permit(128).await?;
+        let m = permit
+            .signal(member)
+            .with_interface("se.tedro.Test")
+            .with_path(ObjectPath::ROOT);
+        permit.write_message(m)?;
+    }
+
+    // Acquiring the second permit sent the first message, but the second
+    // message is only buffered.
+    let mut buf = [0; 1024];
+    let n = peer.read(&mut buf)?;
+    assert!(buf[..n].windows(5).any(|w| w == b"First"));
+    assert!(!buf[..n].windows(6).any(|w| w == b"Second"));
+
+    shared.lock().await.flush().await?;
+    let n = peer.read(&mut buf)?;
+    assert!(buf[..n].windows(6).any(|w| w == b"Second"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn send_permit_defers_received() -> Result<()> {
+    let mut send = SendBuf::new();
+    let m = send.method_call(ObjectPath::ROOT, "Incoming");
+    send.write_message(m)?;
+
+    let (mut c, mut peer) = connection_with_input(send.buf().get())?;
+
+    // Drain everything sent by the connection, so that flushing the large
+    // message below has to wait for the peer.
+    std::thread::spawn(move || std::io::copy(&mut peer, &mut std::io::sink()));
+
+    let mut body = BodyBuf::new();
+    body.store_bytes(&vec![0u8; 1 << 22])?;
+
+    let m = c.method_call(ObjectPath::ROOT, "Outgoing").with_body(&body);
+    c.write_message(m)?;
+
+    // The call is received while the permit waits for the send buffer to be
+    // flushed, and has to be kept around for the next call to wait.
+    let mut permit = c.acquire_send_permit(128).await?;
+    let m = permit
+        .signal("Ping")
+        .with_interface("se.tedro.Test")
+        .with_path(ObjectPath::ROOT);
+    permit.write_message(m)?;
+
+    tokio::time::timeout(Duration::from_secs(5), c.wait())
+        .await
+        .expect("incoming message was lost")?;
+
+    let message = c.last_message()?;
+    assert_eq!(message.member(), Some("Incoming"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn interface_version() -> Result<()> {
+    use crate::org_freedesktop_dbus_properties::{InterfaceVersion, InterfaceVersions};
+
+    const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Test");
+
+    let (mut c, mut peer) = connected_pair()?;
+
+    let peer = tokio::spawn(async move {
+        let mut versions = InterfaceVersions::new();
+        versions.declare("se.tedro.Test", InterfaceVersion::new(3));
+
+        for _ in 0..2 {
+            peer.wait().await?;
+
+            let (recv, send, body) = peer.buffers();
+            let message = recv.last_message()?;
+
+            let m = match versions.reply(&message, send, body)? {
+                Some(m) => m,
+                None => message.error(
+                    crate::org_freedesktop_dbus_properties::UNKNOWN_PROPERTY,
+                    send.next_serial(),
+                ),
+            };
+
+            send.write_message(m)?;
+            peer.flush().await?;
+        }
+
+        Ok::<_, crate::Error>(())
+    });
+
+    let version = c
+        .interface_version("se.tedro.Test", PATH, "se.tedro.Test")
+        .await?;
+    assert_eq!(version, Some(InterfaceVersion::new(3)));
+    assert!(version.is_some_and(|v| v.supports(2) && !v.supports(4)));
+
+    let version = c
+        .interface_version("se.tedro.Test", PATH, "se.tedro.Other")
+        .await?;
+    assert_eq!(version, None);
+
+    peer.await.expect("peer panicked")?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_all_properties() -> Result<()> {
+    use crate::Value;
+
+    const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Test");
+
+    let (mut c, mut peer) = connected_pair()?;
+
+    let peer = tokio::spawn(async move {
+        for _ in 0..2 {
+            peer.wait().await?;
+
+            let (recv, send, body) = peer.buffers();
+            let message = recv.last_message()?;
+
+            body.clear();
+
+            match message.member() {
+                Some("GetAll") => {
+                    assert_eq!(message.body().decode::<&str>()?, "se.tedro.Test");
+                    body.store_parsed(
+                        Signature::new("a{sv}")?,
+                        &["{Name: s:Test, Routes: au:[1, 2]}"],
+                    )?;
+                }
+                Some("GetManagedObjects") => {
+                    body.store_parsed(
+                        Signature::new("a{oa{sa{sv}}}")?,
+                        &["{/se/tedro/Test: {se.tedro.Test: {Name: s:Test}}}"],
+                    )?;
+                }
+                member => panic!("unexpected member {member:?}"),
+            }
+
+            let m = message.method_return(send.next_serial()).with_body(&*body);
+            send.write_message(m)?;
+            peer.flush().await?;
+        }
+
+        Ok::<_, crate::Error>(())
+    });
+
+    let properties = c
+        .get_all_properties("se.tedro.Test", PATH, "se.tedro.Test")
+        .await?
+        .collect::<Result<Vec<_>>>()?;
+
+    assert_eq!(
+        properties,
+        [
+            ("Name", Value::String(String::from("Test"))),
+            ("Routes", Value::Array(vec![Value::U32(1), Value::U32(2)])),
+        ]
+    );
+
+    let mut objects = c
+        .get_managed_objects("se.tedro.Test", ObjectPath::ROOT)
+        .await?;
+
+    let (path, mut interfaces) = objects.next().expect("object")?;
+    assert_eq!(path, PATH);
+
+    let (interface, mut properties) = interfaces.next().expect("interface")?;
+    assert_eq!(interface, "se.tedro.Test");
+    assert_eq!(
+        properties.next().transpose()?,
+        Some(("Name", Value::String(String::from("Test"))))
+    );
+    assert!(properties.next().is_none());
+    assert!(interfaces.next().is_none());
+    assert!(objects.next().is_none());
+
+    peer.await.expect("peer panicked")?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn call_with_signals() -> Result<()> {
+    use crate::{CallEvent, MatchRule};
+
+    const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Jobs");
+    const OTHER: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Other");
+
+    let (mut c, mut peer) = connected_pair()?;
+
+    let peer = tokio::spawn(async move {
+        let mut body = BodyBuf::new();
+
+        for _ in 0..3 {
+            peer.wait().await?;
+
+            let (recv, send, _) = peer.buffers();
+            let message = recv.last_message()?;
+
+            match message.member() {
+                Some("AddMatch" | "RemoveMatch") => {
+                    let serial = send.next_serial();
+                    send.write_message(message.method_return(serial))?;
+                }
+                Some("Start") => {
+                    let token = message.body().decode::<&str>()?;
+
+                    for (path, percent) in [(PATH, 50u32), (OTHER, 75)] {
+                        body.clear();
+                        body.arguments((token, percent))?;
+                        let m = send
+                            .signal("Progress")
+                            .with_path(path)
+                            .with_interface("se.tedro.Jobs")
+                            .with_body(&body);
+                        send.write_message(m)?;
+                    }
+
+                    let serial = send.next_serial();
+                    send.write_message(message.method_return(serial))?;
+
+                    body.clear();
+                    body.store(token)?;
+                    let m = send
+                        .signal("Finished")
+                        .with_path(PATH)
+                        .with_interface("se.tedro.Jobs")
+                        .with_body(&body);
+                    send.write_message(m)?;
+                }
+                member => panic!("unexpected member {member:?}"),
+            }
+
+            peer.flush().await?;
+        }
+
+        Ok::<_, crate::Error>(())
+    });
+
+    let mut body = BodyBuf::new();
+    body.store("backup")?;
+
+    let m = c
+        .method_call(PATH, "Start")
+        .with_interface("se.tedro.Jobs")
+        .with_body(&body);
+
+    let rule = MatchRule::new()
+        .with_message_type(proto::MessageType::SIGNAL)
+        .with_path(PATH)
+        .with_interface("se.tedro.Jobs")
+        .with_arg(0, "backup");
+
+    let mut call = c.call_with_signals(m, rule).await?;
+    let mut events = Vec::new();
+
+    while events.last().map(String::as_str) != Some("Finished") {
+        let event = match call.next().await? {
+            CallEvent::Return(..) => String::from("Return"),
+            CallEvent::Signal(m) => m.member().unwrap_or_default().to_owned(),
+        };
+
+        events.push(event);
+    }
+
+    assert!(call.is_replied());
+    call.finish().await?;
+
+    assert_eq!(events, ["Progress", "Return", "Finished"]);
+
+    // The progress signal for another path was deferred.
+    c.wait().await?;
+    let message = c.last_message()?;
+    assert_eq!(message.path(), Some(OTHER));
+
+    peer.await.expect("peer panicked")?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn call_with_signals_dropped() -> Result<()> {
+    use crate::{Flags, MatchRule};
+
+    const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Jobs");
+
+    let (mut c, mut peer) = connected_pair()?;
+
+    let rule = MatchRule::new()
+        .with_message_type(proto::MessageType::SIGNAL)
+        .with_path(PATH);
+
+    let expected = rule.to_string();
+
+    let peer = tokio::spawn(async move {
+        loop {
+            peer.wait().await?;
+
+            let (recv, send, _) = peer.buffers();
+            let message = recv.last_message()?;
+
+            match message.member() {
+                Some("AddMatch") => {
+                    let serial = send.next_serial();
+                    send.write_message(message.method_return(serial))?;
+                }
+                Some("Start") => {
+                    let serial = send.next_serial();
+                    let m = message.error("se.tedro.Jobs.Error.Failed", serial);
+                    send.write_message(m)?;
+                }
+                Some("RemoveMatch") => {
+                    assert!(message.flags() & Flags::NO_REPLY_EXPECTED);
+                    assert_eq!(message.body().read::<str>()?, expected);
+                    return Ok::<_, crate::Error>(());
+                }
+                member => panic!("unexpected member {member:?}"),
+            }
+
+            peer.flush().await?;
+        }
+    });
+
+    let m = c.method_call(PATH, "Start");
+    let mut call = c.call_with_signals(m, rule).await?;
+    assert!(call.next().await.is_err());
+    drop(call);
+
+    c.flush().await?;
+    peer.await.expect("peer panicked")?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn buffers_echo() -> Result<()> {
+    let (mut c, mut peer) = connected_pair()?;
+
+    let mut body = BodyBuf::new();
+    body.store("Hello")?;
+
+    let m = peer.method_call(ObjectPath::ROOT, "Echo").with_body(&body);
+    let serial = m.serial();
+    peer.write_message(m)?;
+    peer.flush().await?;
+
+    c.wait().await?;
+
+    let (recv, send, _) = c.buffers();
+    let message = recv.last_message()?;
+    let reply = message
+        .method_return(send.next_serial())
+        .with_body(message.body());
+    send.write_message(reply)?;
+
+    c.flush().await?;
+
+    peer.wait().await?;
+    let message = peer.last_message()?;
+    assert_eq!(
+        message.kind(),
+        MessageKind::MethodReturn {
+            reply_serial: serial
+        }
+    );
+    assert_eq!(message.body().read::<str>()?, "Hello");
+    Ok(())
+}
+
+#[test]
+fn send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Connection>();
+}
+
+/// Send `bytes` over `stream` with `fds` passed alongside them.
+#[cfg(feature = "libc")]
+fn send_with_fds(stream: &UnixStream, bytes: &[u8], fds: &[std::os::fd::RawFd]) -> Result<()> {
+    use std::mem::size_of_val;
+    use std::os::fd::AsRawFd;
+
+    let mut control = [0u64; 8];
+    let data_len = size_of_val(fds) as u32;
+
+    let mut iov = libc::iovec {
+        iov_base: bytes.as_ptr() as *mut _,
+        iov_len: bytes.len(),
+    };
+
+    // SAFETY: The message header points to buffers which are valid for the
+    // duration of the call, and the control buffer is large enough to hold the
+    // descriptors.
+    let n = unsafe {
+        let mut msg = std::mem::zeroed::<libc::msghdr>();
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr().cast();
+        msg.msg_controllen = libc::CMSG_SPACE(data_len) as _;
+
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        (*cmsg).cmsg_level = libc::SOL_SOCKET;
+        (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(data_len) as _;
+        std::ptr::copy_nonoverlapping(fds.as_ptr(), libc::CMSG_DATA(cmsg).cast(), fds.len());
+
+        libc::sendmsg(stream.as_raw_fd(), &msg, 0)
+    };
+
+    assert_eq!(usize::try_from(n).ok(), Some(bytes.len()));
+    Ok(())
+}
+
+#[cfg(feature = "libc")]
+#[tokio::test]
+async fn receive_unix_fds() -> Result<()> {
+    use std::os::fd::AsRawFd;
+
+    use crate::UnixFd;
+
+    let (a, b) = UnixStream::pair()?;
+
+    let mut transport = Transport::from_std(a);
+    transport.sasl_begin(&mut UnalignedBuf::new())?;
+    let mut c = Connection::new(transport)?;
+
+    let (mut ours, theirs) = UnixStream::pair()?;
+
+    let mut send = SendBuf::new();
+    let mut body = BodyBuf::new();
+    body.store(UnixFd::new(0))?;
+
+    for member in ["First", "Second"] {
+        let m = send
+            .signal(member)
+            .with_path(ObjectPath::ROOT)
+            .with_interface("se.tedro.Test")
+            .with_body(&body);
+        send.write_message(m)?;
+
+        let mut bytes = Vec::new();
+        send.write_to(&mut bytes)?;
+        send_with_fds(&b, &bytes, &[theirs.as_raw_fd()])?;
+    }
+
+    drop(theirs);
+
+    c.wait().await?;
+    let message_ref = c.last_message_ref()?;
+    assert_eq!(c.read_message(&message_ref)?.member(), Some("First"));
+
+    let fds = c.take_unix_fds(&message_ref)?;
+    assert_eq!(fds.len(), 1);
+    assert!(c.take_unix_fds(&message_ref)?.is_empty());
+
+    // Descriptors which aren't taken are closed once the next message is
+    // received, and only the taken one keeps the other end open.
+    c.wait().await?;
+    assert!(c
+        .take_unix_fds(&message_ref)
+        .unwrap_err()
+        .is_stale_message());
+    assert_eq!(c.last_message()?.member(), Some("Second"));
+    c.wait_timeout(Duration::from_millis(10)).await?;
+
+    let mut theirs = UnixStream::from(fds.into_iter().next().expect("missing fd"));
+    theirs.write_all(b"x")?;
+    ours.read_exact(&mut [0; 1])?;
+    drop(theirs);
+    assert_eq!(ours.read(&mut [0; 1])?, 0);
+    Ok(())
+}
+
+#[cfg(feature = "libc")]
+#[tokio::test]
+async fn truncated_unix_fds() -> Result<()> {
+    let mut send = SendBuf::new();
+
+    for member in ["First", "Second"] {
+        let m = send
+            .signal(member)
+            .with_path(ObjectPath::ROOT)
+            .with_interface("se.tedro.Test");
+        send.write_message(m)?;
+    }
+
+    let mut bytes = Vec::new();
+    send.write_to(&mut bytes)?;
+
+    let (mut c, _b) = connection_with_input(&bytes)?;
+
+    c.wait().await?;
+    let message_ref = c.last_message_ref()?;
+
+    // NB: Truncation can't be provoked through a socket pair, since the
+    // control buffer fits the most descriptors the kernel passes at once.
+    c.recv.set_unix_fds(Vec::new(), true);
+
+    let error = c.take_unix_fds(&message_ref).unwrap_err();
+    let violation = error
+        .protocol_violation()
+        .expect("expected protocol violation");
+    assert!(violation.is_recoverable());
+
+    // The message itself is still available and the stream is in sync.
+    assert_eq!(c.read_message(&message_ref)?.member(), Some("First"));
+    c.wait().await?;
+    assert_eq!(c.last_message()?.member(), Some("Second"));
+    assert!(c.take_unix_fds(&c.last_message_ref()?)?.is_empty());
+    Ok(())
+}
+
+#[cfg(all(feature = "libc", feature = "systemd"))]
+#[tokio::test]
+async fn login1_inhibit() -> Result<()> {
+    use std::os::fd::AsRawFd;
+
+    use crate::org_freedesktop_login1::{self as login1, InhibitMode};
+    use crate::{RecvBuf, UnixFd};
+
+    let (a, mut b) = UnixStream::pair()?;
+
+    let mut transport = Transport::from_std(a);
+    transport.sasl_begin(&mut UnalignedBuf::new())?;
+    let mut c = Connection::new(transport)?;
+
+    // The lock is held for as long as the other end of this pair is open.
+    let (mut lock, held) = UnixStream::pair()?;
+
+    let peer = std::thread::spawn(move || -> Result<_> {
+        b.read_exact(&mut [0; 8])?;
+
+        let mut recv = RecvBuf::new();
+        let message_ref = recv.read_from(&mut b)?;
+        let call = recv.read_message(&message_ref)?;
+        let arguments = call.body().decode::<(String, String, String, String)>()?;
+
+        let mut send = SendBuf::new();
+        let mut body = BodyBuf::new();
+        body.store(UnixFd::new(0))?;
+        let m = call.method_return(send.next_serial()).with_body(&body);
+        send.write_message(m)?;
+        send_with_fds(&b, send.buf().get(), &[held.as_raw_fd()])?;
+
+        Ok((call.member().map(str::to_owned), arguments))
+    });
+
+    let fd = login1::inhibit(&mut c, "sleep", "tokio-dbus", "Testing", InhibitMode::Delay).await?;
+
+    let (member, arguments) = peer.join().expect("peer panicked")?;
+    assert_eq!(member.as_deref(), Some("Inhibit"));
+    assert_eq!(
+        arguments,
+        (
+            "sleep".to_owned(),
+            "tokio-dbus".to_owned(),
+            "Testing".to_owned(),
+            "delay".to_owned()
+        )
+    );
+
+    // The received descriptor keeps the lock open until it's dropped.
+    lock.set_nonblocking(true)?;
+    let error = lock.read(&mut [0; 1]).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::WouldBlock);
+
+    drop(fd);
+    lock.set_nonblocking(false)?;
+    assert_eq!(lock.read(&mut [0; 1])?, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn notifications() -> Result<()> {
+    use crate::org_freedesktop_notifications::{self as notifications, Notification};
+    use crate::{ty, RecvBuf};
+
+    let (a, mut b) = UnixStream::pair()?;
+
+    let mut transport = Transport::from_std(a);
+    transport.sasl_begin(&mut UnalignedBuf::new())?;
+    let mut c = Connection::new(transport)?;
+
+    let peer = std::thread::spawn(move || -> Result<_> {
+        b.read_exact(&mut [0; 8])?;
+
+        let mut recv = RecvBuf::new();
+        let mut send = SendBuf::new();
+        let mut body = BodyBuf::new();
+        let mut calls = Vec::new();
+
+        for _ in 0..2 {
+            let message_ref = recv.read_from(&mut b)?;
+            let call = recv.read_message(&message_ref)?;
+
+            body.clear();
+
+            match call.member() {
+                Some("Notify") => {
+                    let (_, replaces_id, _, summary) =
+                        call.body()
+                            .load_struct::<(ty::Str, u32, ty::Str, ty::Str)>()?;
+                    calls.push((summary.to_owned(), replaces_id));
+                    body.store(42u32)?;
+                }
+                Some("CloseNotification") => {
+                    let id = call.body().load::<u32>()?;
+                    calls.push(("close".to_owned(), id));
+                }
+                member => panic!("unexpected call to {member:?}"),
+            }
+
+            let m = call.method_return(send.next_serial()).with_body(&body);
+            send.write_message(m)?;
+            send.write_to(&mut b)?;
+        }
+
+        Ok(calls)
+    });
+
+    let n = Notification::new("Hello", "World").with_replaces_id(7);
+    let id = notifications::notify(&mut c, &n).await?;
+    assert_eq!(id, 42);
+    notifications::close_notification(&mut c, id).await?;
+
+    let calls = peer.join().expect("peer panicked")?;
+    assert_eq!(calls, [("Hello".to_owned(), 7), ("close".to_owned(), 42)]);
+    Ok(())
+}
+
+#[cfg(feature = "systemd")]
+#[tokio::test]
+async fn systemd_units() -> Result<()> {
+    use crate::org_freedesktop_systemd1::{self as systemd1, JobMode};
+    use crate::{ty, RecvBuf};
+
+    const JOB: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/systemd1/job/1");
+
+    let (a, mut b) = UnixStream::pair()?;
+
+    let mut transport = Transport::from_std(a);
+    transport.sasl_begin(&mut UnalignedBuf::new())?;
+    let mut c = Connection::new(transport)?;
+
+    let peer = std::thread::spawn(move || -> Result<_> {
+        b.read_exact(&mut [0; 8])?;
+
+        let mut recv = RecvBuf::new();
+        let mut send = SendBuf::new();
+        let mut body = BodyBuf::new();
+        let mut calls = Vec::new();
+
+        for _ in 0..5 {
+            let message_ref = recv.read_from(&mut b)?;
+            let call = recv.read_message(&message_ref)?;
+            let member = call.member().unwrap_or_default().to_owned();
+
+            body.clear();
+
+            match member.as_str() {
+                "StartUnit" | "StopUnit" | "RestartUnit" => {
+                    let (name, mode) = call.body().load_struct::<(ty::Str, ty::Str)>()?;
+                    calls.push(format!("{member} {name} {mode}"));
+                    body.store(JOB)?;
+                }
+                "GetUnit" => {
+                    let name = call.body().read::<str>()?;
+                    calls.push(format!("{member} {name}"));
+                    body.store(JOB)?;
+                }
+                _ => {
+                    calls.push(member);
+                }
+            }
+
+            let m = call.method_return(send.next_serial()).with_body(&body);
+            send.write_message(m)?;
+            send.write_to(&mut b)?;
+        }
+
+        Ok(calls)
+    });
+
+    systemd1::subscribe(&mut c).await?;
+    let job = systemd1::start_unit(&mut c, "a.service", JobMode::Replace).await?;
+    assert_eq!(&*job, JOB);
+    systemd1::stop_unit(&mut c, "b.service", JobMode::Fail).await?;
+    systemd1::restart_unit(&mut c, "c.service", JobMode::IgnoreDependencies).await?;
+    assert_eq!(&*systemd1::get_unit(&mut c, "d.service").await?, JOB);
+
+    let calls = peer.join().expect("peer panicked")?;
+    assert_eq!(
+        calls,
+        [
+            "Subscribe",
+            "StartUnit a.service replace",
+            "StopUnit b.service fail",
+            "RestartUnit c.service ignore-dependencies",
+            "GetUnit d.service",
+        ]
+    );
+    Ok(())
+}
@@ -3,20 +3,25 @@ use std::ffi::OsStr;
 use std::fmt;
 use std::io;
 use std::io::{Read, Write};
-use std::mem::size_of;
-use std::num::NonZeroU32;
 use std::os::fd::AsRawFd;
+#[cfg(feature = "libc")]
+use std::os::fd::OwnedFd;
 use std::os::fd::RawFd;
 use std::os::unix::ffi::OsStrExt;
 use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::Duration;
+#[cfg(feature = "libc")]
+use std::time::SystemTime;
 
-use crate::buf::{padding_to, AlignedBuf, UnalignedBuf, MAX_ARRAY_LENGTH, MAX_BODY_LENGTH};
+use tokio::task::JoinSet;
+
+use crate::buf::UnalignedBuf;
 use crate::error::{Error, ErrorKind, Result};
-use crate::proto;
-use crate::recv_buf::MessageRef;
 use crate::sasl::Auth;
 use crate::sasl::{Guid, SaslRequest, SaslResponse};
-use crate::{Frame, RecvBuf};
+use crate::utils::{read_some, write_some};
+use crate::{RecvBuf, SendBuf};
 
 const ENV_STARTER_ADDRESS: &str = "DBUS_STARTER_ADDRESS";
 const ENV_SESSION_BUS: &str = "DBUS_SESSION_BUS_ADDRESS";
@@ -50,8 +55,6 @@ pub(crate) enum TransportState {
     Sasl(SaslState),
     // Connection is open and idle.
     Idle,
-    /// Body is being received.
-    RecvBody(usize),
 }
 
 impl fmt::Display for TransportState {
@@ -59,7 +62,6 @@ impl fmt::Display for TransportState {
         match self {
             TransportState::Sasl(state) => write!(f, "sasl ({state})"),
             TransportState::Idle => write!(f, "idle"),
-            TransportState::RecvBody(..) => write!(f, "recv-body"),
         }
     }
 }
@@ -70,6 +72,17 @@ pub struct Transport {
     stream: UnixStream,
     // The state of the connection.
     state: TransportState,
+    // File descriptors received as part of the message being received.
+    #[cfg(feature = "libc")]
+    unix_fds: Vec<OwnedFd>,
+    // Whether file descriptors received as part of the message being received
+    // were truncated.
+    #[cfg(feature = "libc")]
+    unix_fds_truncated: bool,
+    // The time at which the kernel received the data most recently read, if
+    // kernel timestamps are enabled.
+    #[cfg(feature = "libc")]
+    timestamp: Option<SystemTime>,
 }
 
 impl Transport {
@@ -77,8 +90,8 @@ impl Transport {
     ///
     /// This uses the `DBUS_SESSION_BUS_ADDRESS` environment variable to
     /// determine its address.
-    pub fn session_bus() -> Result<Self> {
-        Self::from_env([ENV_STARTER_ADDRESS, ENV_SESSION_BUS], None)
+    pub async fn session_bus(delay: Duration) -> Result<Self> {
+        Self::from_env([ENV_STARTER_ADDRESS, ENV_SESSION_BUS], None, delay).await
     }
 
     /// Construct a new connection to the session bus.
@@ -86,18 +99,24 @@ impl Transport {
     /// This uses the `DBUS_SYSTEM_BUS_ADDRESS` environment variable to
     /// determine its address or fallback to the well-known address
     /// `unix:path=/var/run/dbus/system_bus_socket`.
-    pub fn system_bus() -> Result<Self> {
+    pub async fn system_bus(delay: Duration) -> Result<Self> {
         Self::from_env(
             [ENV_STARTER_ADDRESS, ENV_SYSTEM_BUS],
             Some(DEFAULT_SYSTEM_BUS),
+            delay,
         )
+        .await
     }
 
     /// Construct a new connection to the session bus.
     ///
     /// This uses the `DBUS_SESSION_BUS_ADDRESS` environment variable to
     /// determine its address.
-    fn from_env<I>(envs: I, default: Option<&str>) -> Result<Self>
+    ///
+    /// If the address lists multiple candidates, they are attempted in
+    /// parallel where each attempt is started `delay` after the previous one
+    /// or as soon as it fails.
+    async fn from_env<I>(envs: I, default: Option<&str>, delay: Duration) -> Result<Self>
     where
         I: IntoIterator,
         I::Item: AsRef<OsStr>,
@@ -121,11 +140,41 @@ impl Transport {
             return Err(Error::new(ErrorKind::MissingBus));
         };
 
-        let stream = match parse_address(address)? {
-            Address::Unix(address) => UnixStream::connect(OsStr::from_bytes(address))?,
+        let candidates = parse_address(address)?;
+        let stream = connect_any(candidates, delay).await?;
+        Ok(Self::from_std(stream))
+    }
+
+    /// Get the credentials of the connected peer.
+    #[cfg(feature = "libc")]
+    pub(crate) fn peer_credentials(&self) -> io::Result<super::Credentials> {
+        super::credentials::peer_credentials(self.stream.as_raw_fd())
+    }
+
+    /// Enable or disable `SO_TIMESTAMP`, which makes the kernel report the
+    /// time at which data was received alongside it.
+    #[cfg(feature = "libc")]
+    pub(crate) fn set_kernel_timestamps(&self, enabled: bool) -> io::Result<()> {
+        use std::mem::size_of_val;
+
+        let value = libc::c_int::from(enabled);
+
+        // SAFETY: The value passed in is a valid `c_int` option value.
+        let result = unsafe {
+            libc::setsockopt(
+                self.stream.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_TIMESTAMP,
+                (&value as *const libc::c_int).cast(),
+                size_of_val(&value) as libc::socklen_t,
+            )
         };
 
-        Ok(Self::from_std(stream))
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
     }
 
     /// Set the connection as non-blocking.
@@ -134,11 +183,22 @@ impl Transport {
         Ok(())
     }
 
+    /// Test if the transport is still performing SASL authentication.
+    pub(crate) fn is_authenticating(&self) -> bool {
+        matches!(self.state, TransportState::Sasl(..))
+    }
+
     /// Constru.ct a connection directly from a unix stream.
     pub(crate) fn from_std(stream: UnixStream) -> Self {
         Self {
             stream,
             state: TransportState::Sasl(SaslState::Init),
+            #[cfg(feature = "libc")]
+            unix_fds: Vec::new(),
+            #[cfg(feature = "libc")]
+            unix_fds_truncated: false,
+            #[cfg(feature = "libc")]
+            timestamp: None,
         }
     }
 
@@ -163,6 +223,10 @@ impl Transport {
                                     buf.extend_from_slice(external);
                                 }
                             },
+                            #[cfg(feature = "libc")]
+                            SaslRequest::NegotiateUnixFd => {
+                                buf.extend_from_slice(b"NEGOTIATE_UNIX_FD");
+                            }
                         }
 
                         buf.extend_from_slice(b"\r\n");
@@ -217,92 +281,56 @@ impl Transport {
         }
     }
 
-    /// Write and sned a single message over the connection.
-    pub(crate) fn send_buf(&self, buf: &mut UnalignedBuf) -> Result<()> {
-        send_buf(&mut &self.stream, buf)?;
-        Ok(())
+    /// Send the messages which are queued in the send buffer.
+    pub(crate) fn send_messages(&self, send: &mut SendBuf) -> Result<()> {
+        send.write_to(&mut &self.stream)
     }
 
     /// Receive a message.
+    ///
+    /// With the `libc` feature enabled, file descriptors passed alongside the
+    /// message are attached to it in the receive buffer.
     pub(crate) fn recv_message(&mut self, recv: &mut RecvBuf) -> Result<()> {
-        loop {
-            match self.state {
-                TransportState::Idle => {
-                    recv.clear();
-
-                    self.recv_buf(
-                        recv.buf_mut(),
-                        size_of::<proto::Header>() + size_of::<u32>(),
-                    )?;
-
-                    let mut read_buf = recv.buf().as_aligned();
-
-                    let mut header = read_buf.load::<proto::Header>()?;
-                    let mut headers = read_buf.load::<u32>()?;
-
-                    header.adjust(header.endianness);
-                    headers.adjust(header.endianness);
-
-                    if header.body_length > MAX_BODY_LENGTH {
-                        return Err(Error::new(ErrorKind::BodyTooLong(header.body_length)));
-                    }
-
-                    if headers > MAX_ARRAY_LENGTH {
-                        return Err(Error::new(ErrorKind::ArrayTooLong(headers)));
-                    }
-
-                    let Some(body_length) = usize::try_from(header.body_length).ok() else {
-                        return Err(Error::new(ErrorKind::BodyTooLong(header.body_length)));
-                    };
-
-                    let Some(headers) = usize::try_from(headers).ok() else {
-                        return Err(Error::new(ErrorKind::ArrayTooLong(headers)));
-                    };
-
-                    let serial = NonZeroU32::new(header.serial).ok_or(ErrorKind::ZeroSerial)?;
-
-                    // Padding used in the header.
-                    let total = headers + padding_to::<u64>(headers) + body_length;
-
-                    let message_ref = MessageRef {
-                        serial,
-                        message_type: header.message_type,
-                        flags: header.flags,
-                        headers,
+        match self.state {
+            TransportState::Idle => {
+                #[cfg(feature = "libc")]
+                {
+                    let mut reader = UnixFdReader {
+                        stream: &self.stream,
+                        unix_fds: &mut self.unix_fds,
+                        truncated: &mut self.unix_fds_truncated,
+                        timestamp: &mut self.timestamp,
                     };
 
-                    recv.set_endianness(header.endianness);
-                    recv.set_last_message(message_ref);
-                    self.state = TransportState::RecvBody(total);
-                }
-                TransportState::RecvBody(total) => {
-                    self.recv_buf(recv.buf_mut(), total)?;
-                    self.state = TransportState::Idle;
-                    return Ok(());
+                    match recv.read_from(&mut reader) {
+                        Ok(..) => {
+                            recv.set_unix_fds(
+                                std::mem::take(&mut self.unix_fds),
+                                std::mem::take(&mut self.unix_fds_truncated),
+                            );
+                            recv.set_kernel_timestamp(self.timestamp.take());
+                        }
+                        Err(error) => {
+                            // NB: Unless we're waiting for more data, the
+                            // message the descriptors belong to is done.
+                            if !error.is_would_block() {
+                                self.unix_fds.clear();
+                                self.unix_fds_truncated = false;
+                                self.timestamp = None;
+                            }
+
+                            return Err(error);
+                        }
+                    }
                 }
-                state => return Err(Error::new(ErrorKind::InvalidState(state))),
-            }
-        }
-    }
-
-    /// Receive exactly `n` bytes into the receive buffer.
-    pub(crate) fn recv_buf(&mut self, buf: &mut AlignedBuf, n: usize) -> io::Result<()> {
-        buf.reserve_bytes(n);
-
-        let mut remaining = n;
 
-        while remaining > 0 {
-            let n = self.stream.read(&mut buf.get_mut()[..remaining])?;
+                #[cfg(not(feature = "libc"))]
+                recv.read_from(&mut &self.stream)?;
 
-            if n == 0 {
-                return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+                Ok(())
             }
-
-            buf.advance(n);
-            remaining -= n;
+            state => Err(Error::new(ErrorKind::InvalidState(state))),
         }
-
-        Ok(())
     }
 }
 
@@ -329,12 +357,18 @@ impl Write for Transport {
 pub(crate) fn sasl_recv(bytes: &[u8]) -> Result<SaslResponse<'_>> {
     let line = crate::utils::trim_end(bytes);
 
+    #[cfg(feature = "libc")]
+    if line == b"AGREE_UNIX_FD" {
+        return Ok(SaslResponse::AgreeUnixFd);
+    }
+
     let Some((command, rest)) = crate::utils::split_once(line, b' ') else {
         return Err(Error::new(ErrorKind::InvalidSasl));
     };
 
     match command {
         b"OK" => Ok(SaslResponse::Ok(Guid::new(rest))),
+        b"ERROR" => Ok(SaslResponse::Error),
         _ => Err(Error::new(ErrorKind::InvalidSaslResponse)),
     }
 }
@@ -342,7 +376,7 @@ pub(crate) fn sasl_recv(bytes: &[u8]) -> Result<SaslResponse<'_>> {
 /// Send the given buffer over the connection.
 fn send_buf(stream: &mut &UnixStream, buf: &mut UnalignedBuf) -> io::Result<()> {
     while !buf.is_empty() {
-        let n = stream.write(buf.get())?;
+        let n = write_some(stream, buf.get())?;
         buf.advance(n);
     }
 
@@ -363,35 +397,237 @@ fn recv_line(stream: &mut &UnixStream, buf: &mut UnalignedBuf) -> io::Result<usi
 /// Receive data into the specified buffer.
 fn recv_some(stream: &mut &UnixStream, buf: &mut UnalignedBuf) -> io::Result<()> {
     buf.reserve_bytes(4096);
-    let n = stream.read(buf.get_mut())?;
-
-    if n == 0 {
-        return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
-    }
-
+    let n = read_some(stream, buf.get_mut())?;
     buf.advance_mut(n);
     Ok(())
 }
 
-enum Address<'a> {
-    Unix(&'a [u8]),
+/// The maximum number of file descriptors received with a single read, which
+/// is the most Linux allows to be passed at once.
+#[cfg(feature = "libc")]
+const MAX_UNIX_FDS: usize = 253;
+
+/// Reader which collects file descriptors passed over the socket.
+#[cfg(feature = "libc")]
+struct UnixFdReader<'a> {
+    stream: &'a UnixStream,
+    unix_fds: &'a mut Vec<OwnedFd>,
+    truncated: &'a mut bool,
+    timestamp: &'a mut Option<SystemTime>,
+}
+
+#[cfg(feature = "libc")]
+impl Read for UnixFdReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        use std::mem::{size_of, size_of_val};
+        use std::os::fd::FromRawFd;
+
+        // NB: Aligned for `cmsghdr`, and large enough to hold `MAX_UNIX_FDS`
+        // descriptors and a timestamp with their headers.
+        let mut control = [0u64; (MAX_UNIX_FDS * size_of::<libc::c_int>()) / 8 + 8];
+
+        let mut iov = libc::iovec {
+            iov_base: buf.as_mut_ptr().cast(),
+            iov_len: buf.len(),
+        };
+
+        // SAFETY: An all-zero `msghdr` is valid.
+        let mut msg = unsafe { std::mem::zeroed::<libc::msghdr>() };
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr().cast();
+        msg.msg_controllen = size_of_val(&control) as _;
+
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+        let flags = libc::MSG_CMSG_CLOEXEC;
+        #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+        let flags = 0;
+
+        // SAFETY: The message header points to buffers which are valid for the
+        // duration of the call.
+        let n = unsafe { libc::recvmsg(self.stream.as_raw_fd(), &mut msg, flags) };
+
+        if n < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // SAFETY: The control buffer has been populated by `recvmsg`.
+        let mut cmsg = unsafe { libc::CMSG_FIRSTHDR(&msg) };
+
+        while !cmsg.is_null() {
+            // SAFETY: `CMSG_FIRSTHDR` and `CMSG_NXTHDR` return valid headers.
+            let header = unsafe { &*cmsg };
+
+            if header.cmsg_level == libc::SOL_SOCKET && header.cmsg_type == libc::SCM_RIGHTS {
+                // SAFETY: The header is valid, so its data is as well.
+                let data = unsafe { libc::CMSG_DATA(cmsg) };
+                let offset = data as usize - cmsg as usize;
+                let count = (header.cmsg_len as usize - offset) / size_of::<libc::c_int>();
+
+                for index in 0..count {
+                    // SAFETY: The data holds `count` descriptors which are now
+                    // owned by this process.
+                    let fd = unsafe {
+                        let fd = data.cast::<libc::c_int>().add(index).read_unaligned();
+                        OwnedFd::from_raw_fd(fd)
+                    };
+
+                    self.unix_fds.push(fd);
+                }
+            }
+
+            if header.cmsg_level == libc::SOL_SOCKET && header.cmsg_type == libc::SCM_TIMESTAMP {
+                // SAFETY: The header is valid, and a `SCM_TIMESTAMP` message
+                // holds a `timeval`.
+                let tv = unsafe {
+                    libc::CMSG_DATA(cmsg)
+                        .cast::<libc::timeval>()
+                        .read_unaligned()
+                };
+
+                if let (Ok(secs), Ok(micros)) =
+                    (u64::try_from(tv.tv_sec), u32::try_from(tv.tv_usec))
+                {
+                    let since_epoch = Duration::new(secs, micros.saturating_mul(1000));
+                    *self.timestamp = SystemTime::UNIX_EPOCH.checked_add(since_epoch);
+                }
+            }
+
+            // SAFETY: `cmsg` is a valid header of `msg`.
+            cmsg = unsafe { libc::CMSG_NXTHDR(&msg, cmsg) };
+        }
+
+        // NB: The bytes have already been consumed from the socket, so they
+        // are returned to keep the stream in sync and the truncation is
+        // reported for the message they belong to instead.
+        if msg.msg_flags & libc::MSG_CTRUNC != 0 {
+            *self.truncated = true;
+        }
+
+        Ok(n as usize)
+    }
 }
 
 #[cfg(unix)]
-fn parse_address(string: &OsStr) -> Result<Address<'_>> {
+fn parse_address(string: &OsStr) -> Result<Vec<PathBuf>> {
     parse_address_bytes(string.as_bytes())
 }
 
-fn parse_address_bytes(bytes: &[u8]) -> Result<Address<'_>> {
-    let Some(index) = bytes.iter().position(|&b| b == b'=') else {
+/// Parse a list of `;`-separated D-Bus addresses into the paths of the unix
+/// sockets to attempt to connect to, in order of preference.
+///
+/// Addresses using transports other than `unix:path` are skipped, but if no
+/// address uses a supported transport an error naming the first unsupported
+/// transport is returned.
+pub(super) fn parse_address_bytes(bytes: &[u8]) -> Result<Vec<PathBuf>> {
+    let mut candidates = Vec::new();
+    let mut unsupported = None;
+
+    for address in bytes.split(|&b| b == b';') {
+        if address.is_empty() {
+            continue;
+        }
+
+        let Some(index) = address.iter().position(|&b| b == b':') else {
+            return Err(Error::new(ErrorKind::InvalidAddress));
+        };
+
+        let (transport, options) = address.split_at(index);
+
+        if transport != b"unix" {
+            unsupported.get_or_insert(transport);
+            continue;
+        }
+
+        for option in options[1..].split(|&b| b == b',') {
+            if let Some(path) = option.strip_prefix(b"path=") {
+                candidates.push(PathBuf::from(OsStr::from_bytes(path)));
+            }
+        }
+    }
+
+    if candidates.is_empty() {
+        if let Some(transport) = unsupported {
+            let transport = String::from_utf8_lossy(transport);
+            return Err(Error::new(ErrorKind::UnsupportedTransport(
+                transport.into(),
+            )));
+        }
+
         return Err(Error::new(ErrorKind::InvalidAddress));
-    };
+    }
+
+    Ok(candidates)
+}
+
+/// Spawn an attempt to connect to the given path.
+///
+/// With the `tracing` feature enabled, the task is instrumented with a span
+/// naming the path it connects to.
+fn spawn_connect(attempts: &mut JoinSet<io::Result<tokio::net::UnixStream>>, path: PathBuf) {
+    #[cfg(feature = "tracing")]
+    let span = tracing::debug_span!("tokio_dbus::connect", path = %path.display());
+
+    let future = tokio::net::UnixStream::connect(path);
+
+    #[cfg(feature = "tracing")]
+    let future = tracing::Instrument::instrument(future, span);
+
+    attempts.spawn(future);
+}
+
+/// Connect to the first candidate which accepts a connection.
+///
+/// Attempts are started in order, where each attempt is started `delay` after
+/// the previous one or as soon as the previous one fails. Once a connection is
+/// established, any other attempts in flight are cancelled.
+pub(super) async fn connect_any(candidates: Vec<PathBuf>, delay: Duration) -> Result<UnixStream> {
+    let mut candidates = candidates.into_iter();
+    let mut attempts = JoinSet::new();
+    let mut last_error = None;
+
+    loop {
+        if attempts.is_empty() {
+            let Some(path) = candidates.next() else {
+                break;
+            };
+
+            spawn_connect(&mut attempts, path);
+        }
+
+        let result = match tokio::time::timeout(delay, attempts.join_next()).await {
+            Ok(Some(result)) => result,
+            Ok(None) => continue,
+            Err(..) => {
+                // The current attempts are taking too long, so start another
+                // one in parallel.
+                if let Some(path) = candidates.next() {
+                    spawn_connect(&mut attempts, path);
+                }
+
+                continue;
+            }
+        };
 
-    let (head, tail) = bytes.split_at(index);
+        match result {
+            Ok(Ok(stream)) => return Ok(stream.into_std()?),
+            Ok(Err(error)) => {
+                last_error = Some(error);
+            }
+            Err(error) => {
+                last_error = Some(io::Error::new(io::ErrorKind::Other, error));
+            }
+        }
+
+        // The attempt failed, so immediately start the next one.
+        if let Some(path) = candidates.next() {
+            spawn_connect(&mut attempts, path);
+        }
+    }
 
-    match head {
-        b"unix:path" => Ok(Address::Unix(&tail[1..])),
-        _ => Err(Error::new(ErrorKind::InvalidAddress)),
+    match last_error {
+        Some(error) => Err(Error::from(error)),
+        None => Err(Error::new(ErrorKind::InvalidAddress)),
     }
 }
 
@@ -0,0 +1,254 @@
+use tokio_dbus_xml::Direction;
+
+use crate::error::{ErrorKind, Result};
+use crate::signature::SignatureBuilder;
+use crate::{Connection, Error, ObjectPath, ObjectPathBuf, Signature, SignatureBuf, Value};
+
+/// A proxy for an object whose interfaces are discovered at runtime through
+/// introspection.
+///
+/// This allows calling methods on objects which aren't known ahead of time,
+/// with arguments and return values represented as [`Value`]. Arguments are
+/// validated against the signature of the method before the call is sent, and
+/// return values against the signature of its output arguments.
+///
+/// This requires the `introspect` feature.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_dbus::{Connection, DynamicProxy, ObjectPath, Value};
+///
+/// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+///
+/// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+/// let mut c = Connection::session_bus().await?;
+/// let proxy = DynamicProxy::new(&mut c, "org.freedesktop.DBus", PATH).await?;
+///
+/// let values = proxy
+///     .call_method(
+///         &mut c,
+///         "org.freedesktop.DBus",
+///         "GetNameOwner",
+///         &[Value::String(String::from("org.freedesktop.DBus"))],
+///     )
+///     .await?;
+///
+/// assert_eq!(values.len(), 1);
+/// println!("{:?}", values[0].as_str());
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DynamicProxy {
+    destination: Box<str>,
+    path: ObjectPathBuf,
+    interfaces: Vec<DynamicInterface>,
+}
+
+impl DynamicProxy {
+    /// Construct a proxy by introspecting the object at `path` owned by
+    /// `destination`.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the object can't be introspected, or if the introspection
+    /// data is invalid.
+    pub async fn new(c: &mut Connection, destination: &str, path: &ObjectPath) -> Result<Self> {
+        let xml = c.introspect(destination, path).await?;
+        Self::from_xml(destination, path, &xml)
+    }
+
+    /// Construct a proxy from already available introspection data of the
+    /// object at `path` owned by `destination`.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the introspection data is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{DynamicProxy, ObjectPath};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Example");
+    ///
+    /// let proxy = DynamicProxy::from_xml("se.tedro.Example", PATH, r#"
+    /// <node>
+    ///   <interface name="se.tedro.Example">
+    ///     <method name="Add">
+    ///       <arg name="a" type="u" direction="in"/>
+    ///       <arg name="b" type="u" direction="in"/>
+    ///       <arg name="sum" type="u" direction="out"/>
+    ///     </method>
+    ///   </interface>
+    /// </node>
+    /// "#)?;
+    ///
+    /// let method = proxy.method("se.tedro.Example", "Add").expect("method");
+    /// assert_eq!(method.inputs(), "uu");
+    /// assert_eq!(method.outputs(), "u");
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn from_xml(destination: &str, path: &ObjectPath, xml: &str) -> Result<Self> {
+        let node = tokio_dbus_xml::parse_interface(xml)
+            .map_err(|error| Error::new(ErrorKind::Introspect(error.to_string().into())))?;
+
+        let mut interfaces = Vec::with_capacity(node.interfaces.len());
+
+        for interface in node.interfaces.iter() {
+            let mut methods = Vec::with_capacity(interface.methods.len());
+
+            for method in interface.methods.iter() {
+                let mut inputs = SignatureBuilder::new();
+                let mut outputs = SignatureBuilder::new();
+
+                for argument in method.arguments.iter() {
+                    let signature = match argument.direction {
+                        Direction::In => &mut inputs,
+                        Direction::Out => &mut outputs,
+                    };
+
+                    if !signature.extend_from_signature(argument.ty) {
+                        return Err(Error::new(ErrorKind::Introspect(
+                            format!("arguments of method `{}` are too long", method.name).into(),
+                        )));
+                    }
+                }
+
+                methods.push(DynamicMethod {
+                    name: method.name.into(),
+                    inputs: inputs.to_signature().to_owned(),
+                    outputs: outputs.to_signature().to_owned(),
+                });
+            }
+
+            interfaces.push(DynamicInterface {
+                name: interface.name.into(),
+                methods,
+            });
+        }
+
+        Ok(Self {
+            destination: destination.into(),
+            path: path.to_owned(),
+            interfaces,
+        })
+    }
+
+    /// Get the destination of the proxy.
+    pub fn destination(&self) -> &str {
+        &self.destination
+    }
+
+    /// Get the path of the object the proxy refers to.
+    pub fn path(&self) -> &ObjectPath {
+        &self.path
+    }
+
+    /// Get the interfaces implemented by the object.
+    pub fn interfaces(&self) -> &[DynamicInterface] {
+        &self.interfaces
+    }
+
+    /// Look up a method by interface and member name.
+    pub fn method(&self, interface: &str, member: &str) -> Option<&DynamicMethod> {
+        self.interfaces
+            .iter()
+            .find(|i| &*i.name == interface)?
+            .method(member)
+    }
+
+    /// Call a method on the object, and decode its return values.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the method isn't part of the introspected interface, if the
+    /// arguments don't match the signature of its input arguments, if the
+    /// remote end responds with an error, or if the reply doesn't match the
+    /// signature of its output arguments.
+    pub async fn call_method(
+        &self,
+        c: &mut Connection,
+        interface: &str,
+        member: &str,
+        arguments: &[Value],
+    ) -> Result<Vec<Value>> {
+        let Some(method) = self.method(interface, member) else {
+            return Err(Error::new(ErrorKind::UnknownMethod(
+                interface.into(),
+                member.into(),
+            )));
+        };
+
+        let reply = c
+            .call_values(
+                &self.destination,
+                &self.path,
+                interface,
+                member,
+                &method.inputs,
+                arguments,
+            )
+            .await?;
+
+        let mut body = reply.body();
+
+        if body.signature() != method.outputs {
+            return Err(Error::new(ErrorKind::SignatureMismatch(
+                method.outputs.as_ref().into(),
+                body.signature().into(),
+            )));
+        }
+
+        Value::load_body(&mut body)
+    }
+}
+
+/// An interface of an object accessed through a [`DynamicProxy`].
+#[derive(Debug, Clone)]
+pub struct DynamicInterface {
+    name: Box<str>,
+    methods: Vec<DynamicMethod>,
+}
+
+impl DynamicInterface {
+    /// Get the name of the interface.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the methods of the interface.
+    pub fn methods(&self) -> &[DynamicMethod] {
+        &self.methods
+    }
+
+    /// Look up a method by name.
+    pub fn method(&self, member: &str) -> Option<&DynamicMethod> {
+        self.methods.iter().find(|m| &*m.name == member)
+    }
+}
+
+/// A method of an interface accessed through a [`DynamicProxy`].
+#[derive(Debug, Clone)]
+pub struct DynamicMethod {
+    name: Box<str>,
+    inputs: SignatureBuf,
+    outputs: SignatureBuf,
+}
+
+impl DynamicMethod {
+    /// Get the name of the method.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the combined signature of the input arguments of the method.
+    pub fn inputs(&self) -> &Signature {
+        &self.inputs
+    }
+
+    /// Get the combined signature of the output arguments of the method.
+    pub fn outputs(&self) -> &Signature {
+        &self.outputs
+    }
+}
@@ -2,9 +2,15 @@ use std::error;
 use std::fmt;
 use std::io;
 use std::str::Utf8Error;
+use std::time::Duration;
 
+#[cfg(feature = "tokio")]
 use crate::connection::TransportState;
+use crate::limits::{MAX_ARRAY_LENGTH, MAX_BODY_LENGTH, MAX_NAME_LENGTH};
+use crate::proto::{self, Type};
+use crate::BusNameError;
 use crate::ObjectPathError;
+use crate::ProtocolViolation;
 use crate::Signature;
 use crate::SignatureError;
 
@@ -23,11 +29,267 @@ impl Error {
         Self { kind }
     }
 
+    /// Wrap an error as a protocol violation in the message with the given
+    /// `header`, detected at `offset`.
+    pub(crate) fn new_protocol_violation(
+        header: proto::Header,
+        offset: usize,
+        recoverable: bool,
+        error: Error,
+    ) -> Error {
+        let violation = ProtocolViolation::new(header, offset, recoverable, error);
+        Self::new(ErrorKind::ProtocolViolation(Box::new(violation)))
+    }
+
     /// Test if the error indicates that the operation would block.
+    ///
+    /// This is returned by [`RecvBuf::read_from`] and [`SendBuf::write_to`]
+    /// when the underlying reader or writer would block, in which case the
+    /// operation can be resumed once it is ready.
+    ///
+    /// [`RecvBuf::read_from`]: crate::RecvBuf::read_from
+    /// [`SendBuf::write_to`]: crate::SendBuf::write_to
     #[inline]
-    pub(crate) fn would_block(&self) -> bool {
+    pub fn is_would_block(&self) -> bool {
         matches!(self.kind, ErrorKind::WouldBlock)
     }
+
+    /// Test if the error indicates that a [`MessageRef`] was read after it
+    /// was invalidated by another message being received.
+    ///
+    /// [`MessageRef`]: crate::MessageRef
+    #[inline]
+    pub fn is_stale_message(&self) -> bool {
+        matches!(self.kind, ErrorKind::StaleMessage)
+    }
+
+    /// Test if the error indicates that connecting to the bus timed out.
+    ///
+    /// See [`ConnectionBuilder::auth_timeout`] and
+    /// [`ConnectionBuilder::hello_timeout`].
+    ///
+    /// [`ConnectionBuilder::auth_timeout`]: crate::ConnectionBuilder::auth_timeout
+    /// [`ConnectionBuilder::hello_timeout`]: crate::ConnectionBuilder::hello_timeout
+    #[inline]
+    pub fn is_timeout(&self) -> bool {
+        match self.kind {
+            #[cfg(feature = "tokio")]
+            ErrorKind::AuthTimeout | ErrorKind::HelloTimeout => true,
+            _ => false,
+        }
+    }
+
+    /// Test if the error indicates that one of the [limits] imposed by the
+    /// specification has been exceeded.
+    ///
+    /// [limits]: crate::limits
+    #[inline]
+    pub fn is_limit_exceeded(&self) -> bool {
+        matches!(
+            self.kind,
+            ErrorKind::ArrayTooLong(..) | ErrorKind::BodyTooLong(..) | ErrorKind::NameTooLong(..)
+        )
+    }
+
+    /// Test if the error indicates that a method call could not be sent
+    /// because too many method calls are already awaiting a reply.
+    ///
+    /// See [`SendBuf::set_max_pending_replies`].
+    ///
+    /// [`SendBuf::set_max_pending_replies`]: crate::SendBuf::set_max_pending_replies
+    #[inline]
+    pub fn is_too_many_pending_replies(&self) -> bool {
+        matches!(self.kind, ErrorKind::TooManyPendingReplies(..))
+    }
+
+    /// Test if the error indicates that a message could not be deferred
+    /// because its sender has exceeded its quota.
+    ///
+    /// See [`SenderQuota`].
+    ///
+    /// [`SenderQuota`]: crate::SenderQuota
+    #[inline]
+    pub fn is_sender_quota_exceeded(&self) -> bool {
+        match self.kind {
+            #[cfg(feature = "tokio")]
+            ErrorKind::SenderQuotaExceeded(..) => true,
+            _ => false,
+        }
+    }
+
+    /// Test if the error indicates that a message could not be written because
+    /// previously queued messages haven't been sent for longer than the
+    /// configured watchdog period.
+    ///
+    /// This usually means that nothing is driving the connection, such as
+    /// [`Connection::wait`] or [`Connection::flush`]. See
+    /// [`SendBuf::set_watchdog`].
+    ///
+    /// [`Connection::wait`]: crate::Connection::wait
+    /// [`Connection::flush`]: crate::Connection::flush
+    /// [`SendBuf::set_watchdog`]: crate::SendBuf::set_watchdog
+    #[inline]
+    pub fn is_not_driven(&self) -> bool {
+        matches!(self.kind, ErrorKind::NotDriven(..))
+    }
+
+    /// Test if the error indicates that the body of a message is empty while
+    /// its signature is not, or the other way around.
+    ///
+    /// This also matches if such a message was received, in which case the
+    /// error is reported as a [protocol violation].
+    ///
+    /// [protocol violation]: Self::protocol_violation
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    /// let mut body = BodyBuf::new();
+    ///
+    /// // Reserving a length prefix writes data without extending the
+    /// // signature.
+    /// let prefix = body.reserve_length::<u8>();
+    /// body.patch_length(prefix)?;
+    ///
+    /// let m = send.method_call(PATH, "Hello").with_body(&body);
+    ///
+    /// let error = send.write_message(m).unwrap_err();
+    /// assert!(error.is_body_signature_mismatch());
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    #[inline]
+    pub fn is_body_signature_mismatch(&self) -> bool {
+        match &self.kind {
+            ErrorKind::BodySignatureMismatch(..) => true,
+            ErrorKind::ProtocolViolation(violation) => {
+                violation.error().is_body_signature_mismatch()
+            }
+            _ => false,
+        }
+    }
+
+    /// Test if the error was caused by data remaining in a body which was
+    /// expected to be fully read.
+    ///
+    /// See [`Body::ensure_consumed`].
+    ///
+    /// [`Body::ensure_consumed`]: crate::Body::ensure_consumed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::BodyBuf;
+    ///
+    /// let mut body = BodyBuf::new();
+    /// body.store(42u32)?;
+    ///
+    /// let error = body.as_body().finish().unwrap_err();
+    /// assert!(error.is_trailing_body());
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    #[inline]
+    pub fn is_trailing_body(&self) -> bool {
+        matches!(self.kind, ErrorKind::TrailingBody(..))
+    }
+
+    /// Test if the error was caused by decoding a body whose signature doesn't
+    /// match the signature of the type it was decoded into.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::BodyBuf;
+    ///
+    /// let mut body = BodyBuf::new();
+    /// body.store(42u32)?;
+    ///
+    /// let error = body.as_body().decode::<String>().unwrap_err();
+    /// assert!(error.is_signature_mismatch());
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    #[inline]
+    pub fn is_signature_mismatch(&self) -> bool {
+        matches!(self.kind, ErrorKind::SignatureMismatch(..))
+    }
+
+    /// Test if the error was caused by trying to convert a [`Variant`] into a
+    /// type which can't losslessly represent its value.
+    ///
+    /// [`Variant`]: crate::Variant
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::Variant;
+    ///
+    /// let error = u8::try_from(Variant::U32(42)).unwrap_err();
+    /// assert!(error.is_variant_mismatch());
+    /// ```
+    #[inline]
+    pub fn is_variant_mismatch(&self) -> bool {
+        matches!(self.kind, ErrorKind::VariantMismatch(..))
+    }
+
+    /// Access details about a protocol violation by the peer, if this error
+    /// was caused by one.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::Connection;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    ///
+    /// if let Err(error) = c.wait().await {
+    ///     if let Some(violation) = error.protocol_violation() {
+    ///         println!("Serial {} at offset {}", violation.serial(), violation.offset());
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    #[inline]
+    pub fn protocol_violation(&self) -> Option<&ProtocolViolation> {
+        match &self.kind {
+            ErrorKind::ProtocolViolation(violation) => Some(violation),
+            _ => None,
+        }
+    }
+
+    /// Get the name of the error the peer responded with, if this error is
+    /// caused by an error response.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn response_error_name(&self) -> Option<&str> {
+        match &self.kind {
+            ErrorKind::ResponseError(error_name, _) => Some(error_name),
+            _ => None,
+        }
+    }
+
+    /// Test if the error indicates that the connection to the bus was lost.
+    ///
+    /// This includes a connection which can no longer send anything, because
+    /// sending failed after only part of a message had been sent.
+    #[inline]
+    pub fn is_disconnected(&self) -> bool {
+        match &self.kind {
+            ErrorKind::Io(error) => matches!(
+                error.kind(),
+                io::ErrorKind::UnexpectedEof
+                    | io::ErrorKind::BrokenPipe
+                    | io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+            ),
+            #[cfg(feature = "tokio")]
+            ErrorKind::PartiallySent => true,
+            _ => false,
+        }
+    }
 }
 
 impl From<SignatureError> for Error {
@@ -44,6 +306,13 @@ impl From<ObjectPathError> for Error {
     }
 }
 
+impl From<BusNameError> for Error {
+    #[inline]
+    fn from(error: BusNameError) -> Self {
+        Self::new(ErrorKind::BusName(error))
+    }
+}
+
 impl From<io::Error> for Error {
     #[inline]
     fn from(error: io::Error) -> Self {
@@ -75,39 +344,191 @@ impl fmt::Display for Error {
             ErrorKind::Io(..) => write!(f, "I/O error"),
             ErrorKind::Signature(..) => write!(f, "Signature error"),
             ErrorKind::ObjectPath(..) => write!(f, "ObjectPath error"),
+            ErrorKind::BusName(..) => write!(f, "BusName error"),
             ErrorKind::Utf8Error(..) => write!(f, "UTF-8 error"),
             ErrorKind::WouldBlock => write!(f, "Would block"),
+            ErrorKind::ByteArrayIncomplete(remaining) => write!(
+                f,
+                "Byte array of the previous message is incomplete with {remaining} bytes remaining"
+            ),
+            ErrorKind::ByteArrayOverflow(len, remaining) => write!(
+                f,
+                "Writing {len} bytes overflows byte array with {remaining} bytes remaining"
+            ),
+            #[cfg(feature = "tokio")]
+            ErrorKind::PartiallySent => write!(
+                f,
+                "Sending failed in the middle of a message, so the connection can't send anything else"
+            ),
             ErrorKind::BufferUnderflow => write!(f, "Buffer underflow"),
+            ErrorKind::StructIncomplete => write!(f, "Struct was not finished"),
+            ErrorKind::InvalidInterfaceVersion(version) => {
+                write!(f, "Invalid interface version `{version}`")
+            }
+            #[cfg(feature = "tokio")]
             ErrorKind::MissingBus => write!(f, "Missing bus to connect to"),
+            #[cfg(feature = "tokio")]
             ErrorKind::InvalidAddress => write!(f, "Invalid d-bus address"),
+            #[cfg(feature = "tokio")]
+            ErrorKind::UnsupportedTransport(transport) => {
+                write!(f, "Unsupported d-bus transport `{transport}`")
+            }
+            #[cfg(feature = "tokio")]
             ErrorKind::InvalidSasl => write!(f, "Invalid SASL message"),
+            #[cfg(feature = "tokio")]
             ErrorKind::InvalidSaslResponse => write!(f, "Invalid SASL command"),
+            #[cfg(all(feature = "tokio", feature = "libc"))]
+            ErrorKind::ExternalIdentityMismatch(Some(claimed), uid) => {
+                write!(f, "Claimed SASL identity {claimed} does not match peer uid {uid}")
+            }
+            #[cfg(all(feature = "tokio", feature = "libc"))]
+            ErrorKind::ExternalIdentityMismatch(None, uid) => {
+                write!(f, "Invalid SASL identity claimed by peer with uid {uid}")
+            }
+            #[cfg(feature = "tokio")]
             ErrorKind::InvalidState(state) => write!(f, "Invalid connection state `{state}`"),
             ErrorKind::InvalidProtocol => write!(f, "Invalid protocol"),
             ErrorKind::MissingPath => write!(f, "Missing required PATH header"),
+            ErrorKind::MissingInterface => write!(f, "Missing required INTERFACE header"),
             ErrorKind::MissingMember => write!(f, "Missing required MEMBER header"),
             ErrorKind::MissingReplySerial => write!(f, "Missing required REPLY_SERIAL header"),
             ErrorKind::ZeroSerial => write!(f, "Zero in header serial"),
             ErrorKind::ZeroReplySerial => write!(f, "Zero REPLY_SERIAL header"),
             ErrorKind::MissingErrorName => write!(f, "Missing required ERROR_NAME header"),
+            ErrorKind::DuplicateHeaderField(variant) => {
+                write!(f, "Duplicate {variant:?} header")
+            }
             ErrorKind::NotNullTerminated => {
                 write!(f, "String is not null terminated")
             }
             ErrorKind::ArrayTooLong(length) => {
-                write!(f, "Array of length {length} is too long (max is 67108864)")
+                write!(
+                    f,
+                    "Array of length {length} is too long (max is {MAX_ARRAY_LENGTH})"
+                )
             }
             ErrorKind::BodyTooLong(length) => {
-                write!(f, "Body of length {length} is too long (max is 134217728)")
+                write!(
+                    f,
+                    "Body of length {length} is too long (max is {MAX_BODY_LENGTH})"
+                )
+            }
+            ErrorKind::NameTooLong(length) => {
+                write!(
+                    f,
+                    "Name of length {length} is too long (max is {MAX_NAME_LENGTH})"
+                )
+            }
+            ErrorKind::InvalidLengthPrefix => {
+                write!(f, "Length prefix is out of bounds of the buffer")
+            }
+            ErrorKind::TooManyPendingReplies(count) => {
+                write!(f, "Too many pending replies ({count})")
+            }
+            #[cfg(feature = "tokio")]
+            ErrorKind::SenderQuotaExceeded(sender) => {
+                write!(f, "Sender `{sender}` exceeded its quota of deferred messages")
+            }
+            ErrorKind::NotDriven(period) => write!(
+                f,
+                "Queued messages haven't been sent for {period:?}, is the connection being driven?"
+            ),
+            ErrorKind::ProtocolViolation(violation) => violation.fmt(f),
+            #[cfg(feature = "tokio")]
+            ErrorKind::MissingUniqueName => {
+                write!(f, "Connection has not been assigned a unique name")
             }
             ErrorKind::MissingMessage => {
                 write!(f, "No message")
             }
+            ErrorKind::StaleMessage => {
+                write!(f, "Message reference is stale")
+            }
+            #[cfg(feature = "tokio")]
+            ErrorKind::AuthTimeout => {
+                write!(f, "Timed out during SASL authentication")
+            }
+            #[cfg(feature = "tokio")]
+            ErrorKind::HelloTimeout => {
+                write!(f, "Timed out waiting for reply to Hello")
+            }
+            #[cfg(feature = "inventory")]
+            ErrorKind::DuplicateInterface(path, interface) => {
+                write!(f, "Interface {interface} on {path} is already registered")
+            }
+            #[cfg(all(feature = "tokio", feature = "libc"))]
+            ErrorKind::UnixFdsTruncated => {
+                write!(f, "File descriptors passed with the message were truncated")
+            }
+            #[cfg(all(feature = "tokio", feature = "systemd"))]
+            ErrorKind::MissingUnixFd(index) => {
+                write!(f, "Missing file descriptor #{index} in reply")
+            }
+            #[cfg(feature = "tokio")]
             ErrorKind::ResponseError(error_name, message) => {
                 write!(f, "Response error: {error_name}: {message}")
             }
+            ErrorKind::SignatureMismatch(expected, actual) => {
+                write!(
+                    f,
+                    "Expected body with signature {:?} but found {:?}",
+                    expected.as_str(),
+                    actual.as_str()
+                )
+            }
+            ErrorKind::BodySignatureMismatch(signature, length) => {
+                if signature.is_empty() {
+                    write!(f, "Body of {length} bytes has an empty signature")
+                } else {
+                    write!(f, "Empty body for signature {:?}", signature.as_str())
+                }
+            }
+            ErrorKind::TrailingBody(signature, length) => {
+                if signature.is_empty() {
+                    write!(f, "Body has {length} trailing bytes")
+                } else {
+                    write!(
+                        f,
+                        "Body has {length} trailing bytes with remaining signature {:?}",
+                        signature.as_str()
+                    )
+                }
+            }
+            ErrorKind::VariantMismatch(expected, actual) => {
+                write!(
+                    f,
+                    "Expected variant convertible to {:?} but found {:?}",
+                    expected.as_str(),
+                    actual.as_str()
+                )
+            }
             ErrorKind::UnsupportedVariant(signature) => {
                 write!(f, "Unsupported variant {signature:?}")
             }
+            ErrorKind::UnsupportedType(ty) => {
+                write!(f, "Unsupported type {ty:?}")
+            }
+            ErrorKind::InvalidBoolean(value) => {
+                write!(f, "Invalid boolean value {value}")
+            }
+            ErrorKind::DepthExceeded => {
+                write!(f, "Maximum depth of nested containers exceeded")
+            }
+            ErrorKind::InvalidArgument(index, message) => {
+                write!(f, "Invalid argument #{index}: {message}")
+            }
+            #[cfg(feature = "serde_json")]
+            ErrorKind::Json(message) => {
+                write!(f, "JSON conversion error: {message}")
+            }
+            #[cfg(all(feature = "tokio", feature = "introspect"))]
+            ErrorKind::Introspect(message) => {
+                write!(f, "Invalid introspection data: {message}")
+            }
+            #[cfg(all(feature = "tokio", feature = "introspect"))]
+            ErrorKind::UnknownMethod(interface, member) => {
+                write!(f, "Unknown method {member:?} in interface {interface:?}")
+            }
         }
     }
 }
@@ -118,7 +539,9 @@ impl error::Error for Error {
             ErrorKind::Io(error) => Some(error),
             ErrorKind::Signature(error) => Some(error),
             ErrorKind::ObjectPath(error) => Some(error),
+            ErrorKind::BusName(error) => Some(error),
             ErrorKind::Utf8Error(error) => Some(error),
+            ErrorKind::ProtocolViolation(violation) => Some(&**violation),
             _ => None,
         }
     }
@@ -129,25 +552,78 @@ pub(crate) enum ErrorKind {
     Io(io::Error),
     Signature(SignatureError),
     ObjectPath(ObjectPathError),
+    BusName(BusNameError),
     Utf8Error(Utf8Error),
     WouldBlock,
+    #[cfg(feature = "tokio")]
+    PartiallySent,
+    ByteArrayIncomplete(usize),
+    ByteArrayOverflow(usize, usize),
     BufferUnderflow,
+    StructIncomplete,
+    InvalidInterfaceVersion(Box<str>),
+    #[cfg(feature = "tokio")]
     MissingBus,
+    #[cfg(feature = "tokio")]
     InvalidAddress,
+    #[cfg(feature = "tokio")]
+    UnsupportedTransport(Box<str>),
+    #[cfg(feature = "tokio")]
     InvalidSasl,
+    #[cfg(feature = "tokio")]
     InvalidSaslResponse,
+    #[cfg(all(feature = "tokio", feature = "libc"))]
+    ExternalIdentityMismatch(Option<u32>, u32),
+    #[cfg(feature = "tokio")]
     InvalidState(TransportState),
     InvalidProtocol,
     MissingPath,
+    MissingInterface,
     MissingMember,
     MissingReplySerial,
     ZeroSerial,
     ZeroReplySerial,
     MissingErrorName,
+    DuplicateHeaderField(proto::Variant),
     NotNullTerminated,
     BodyTooLong(u32),
     ArrayTooLong(u32),
+    NameTooLong(usize),
+    InvalidLengthPrefix,
+    TooManyPendingReplies(usize),
+    #[cfg(feature = "tokio")]
+    SenderQuotaExceeded(Box<str>),
+    NotDriven(Duration),
+    ProtocolViolation(Box<ProtocolViolation>),
+    #[cfg(feature = "tokio")]
+    MissingUniqueName,
     MissingMessage,
+    StaleMessage,
+    #[cfg(feature = "tokio")]
+    AuthTimeout,
+    #[cfg(feature = "tokio")]
+    HelloTimeout,
+    SignatureMismatch(Box<Signature>, Box<Signature>),
+    BodySignatureMismatch(Box<Signature>, usize),
+    TrailingBody(Box<Signature>, usize),
+    VariantMismatch(&'static Signature, &'static Signature),
     UnsupportedVariant(Box<Signature>),
+    UnsupportedType(Type),
+    InvalidBoolean(u32),
+    DepthExceeded,
+    InvalidArgument(usize, Box<str>),
+    #[cfg(feature = "serde_json")]
+    Json(Box<str>),
+    #[cfg(all(feature = "tokio", feature = "introspect"))]
+    Introspect(Box<str>),
+    #[cfg(all(feature = "tokio", feature = "introspect"))]
+    UnknownMethod(Box<str>, Box<str>),
+    #[cfg(feature = "inventory")]
+    DuplicateInterface(Box<str>, Box<str>),
+    #[cfg(all(feature = "tokio", feature = "libc"))]
+    UnixFdsTruncated,
+    #[cfg(all(feature = "tokio", feature = "systemd"))]
+    MissingUnixFd(u32),
+    #[cfg(feature = "tokio")]
     ResponseError(Box<str>, Box<str>),
 }
@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+
+use crate::buf::MAX_ARRAY_LENGTH;
+use crate::error::{ErrorKind, Result};
+use crate::signature::SignatureBuilder;
+use crate::{Body, Error, Frame, ObjectPath, Signature, SignatureError, UnixFd, Variant};
+
+/// Trait for types which can be decoded from a sequence of values in a
+/// [`Body`].
+///
+/// This is implemented for basic types and tuples of them, where each element
+/// of a tuple corresponds to one value in the body. It can be implemented for
+/// structs using `#[derive(FromBody)]`, which decodes each field in order and
+/// also implements `TryFrom<Body<'_>>` like it is for tuples.
+///
+/// Use [`Body::decode`] to decode a body as a type implementing this trait.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{BodyBuf, FromBody};
+///
+/// #[derive(FromBody)]
+/// struct Request<'de> {
+///     id: u32,
+///     name: &'de str,
+/// }
+///
+/// let mut buf = BodyBuf::new();
+/// buf.store(42u32)?;
+/// buf.store("example")?;
+///
+/// let request: Request<'_> = buf.as_body().try_into()?;
+/// assert_eq!(request.id, 42);
+/// assert_eq!(request.name, "example");
+///
+/// let (id, name): (u32, &str) = buf.as_body().try_into()?;
+/// assert_eq!(id, 42);
+/// assert_eq!(name, "example");
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+pub trait FromBody<'de>: Sized {
+    /// Write the signature of the type.
+    #[doc(hidden)]
+    fn write_signature(signature: &mut SignatureBuilder) -> Result<(), SignatureError>;
+
+    /// Decode the type from the given body.
+    #[doc(hidden)]
+    fn from_body(body: &mut Body<'de>) -> Result<Self>;
+}
+
+/// Decode a body by checking its signature first.
+pub(crate) fn decode<'de, T>(body: &Body<'de>) -> Result<T>
+where
+    T: FromBody<'de>,
+{
+    let mut signature = SignatureBuilder::new();
+    T::write_signature(&mut signature)?;
+
+    if signature.to_signature() != body.signature() {
+        return Err(Error::new(ErrorKind::SignatureMismatch(
+            signature.to_signature().into(),
+            body.signature().into(),
+        )));
+    }
+
+    let mut body = body.clone();
+    T::from_body(&mut body)
+}
+
+macro_rules! impl_frame {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl<'de> FromBody<'de> for $ty {
+                #[inline]
+                fn write_signature(signature: &mut SignatureBuilder) -> Result<(), SignatureError> {
+                    if !signature.extend_from_signature(<$ty as Frame>::SIGNATURE) {
+                        return Err(SignatureError::too_long());
+                    }
+
+                    Ok(())
+                }
+
+                #[inline]
+                fn from_body(body: &mut Body<'de>) -> Result<Self> {
+                    body.load()
+                }
+            }
+        )*
+    }
+}
+
+impl_frame!(u8, i16, u16, i32, u32, i64, u64, f64, UnixFd);
+
+macro_rules! impl_unsized {
+    ($($ty:ty, $signature:expr),* $(,)?) => {
+        $(
+            impl<'de> FromBody<'de> for &'de $ty {
+                #[inline]
+                fn write_signature(signature: &mut SignatureBuilder) -> Result<(), SignatureError> {
+                    if !signature.extend_from_signature($signature) {
+                        return Err(SignatureError::too_long());
+                    }
+
+                    Ok(())
+                }
+
+                #[inline]
+                fn from_body(body: &mut Body<'de>) -> Result<Self> {
+                    body.read()
+                }
+            }
+        )*
+    }
+}
+
+impl_unsized! {
+    str, Signature::STRING,
+    ObjectPath, Signature::OBJECT_PATH,
+    Signature, Signature::SIGNATURE,
+}
+
+impl<'de> FromBody<'de> for bool {
+    #[inline]
+    fn write_signature(signature: &mut SignatureBuilder) -> Result<(), SignatureError> {
+        if !signature.extend_from_signature(Signature::BOOLEAN) {
+            return Err(SignatureError::too_long());
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn from_body(body: &mut Body<'de>) -> Result<Self> {
+        match body.load::<u32>()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            n => Err(Error::new(ErrorKind::InvalidBoolean(n))),
+        }
+    }
+}
+
+impl<'de> FromBody<'de> for String {
+    #[inline]
+    fn write_signature(signature: &mut SignatureBuilder) -> Result<(), SignatureError> {
+        <&str>::write_signature(signature)
+    }
+
+    #[inline]
+    fn from_body(body: &mut Body<'de>) -> Result<Self> {
+        Ok(body.read::<str>()?.to_owned())
+    }
+}
+
+impl<'de> FromBody<'de> for Variant<'de> {
+    #[inline]
+    fn write_signature(signature: &mut SignatureBuilder) -> Result<(), SignatureError> {
+        <crate::ty::Variant as crate::ty::Marker>::write_signature(signature)
+    }
+
+    #[inline]
+    fn from_body(body: &mut Body<'de>) -> Result<Self> {
+        <crate::ty::Variant as crate::ty::Marker>::load_struct(body)
+    }
+}
+
+/// Decode an array, where each element is decoded as `T`.
+///
+/// `T` must correspond to a single complete type, so tuples can't be used as
+/// elements. Decoding arrays of structs requires the typed markers in [`ty`].
+///
+/// [`ty`]: crate::ty
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use tokio_dbus::{BodyBuf, Signature, Value};
+///
+/// let mut buf = BodyBuf::new();
+/// buf.store_parsed(Signature::new("aa{sv}")?, &["[{Address: s:\"00:11\"}, {Paired: b:false}]"])?;
+///
+/// let devices = buf.as_body().decode::<Vec<HashMap<String, Value>>>()?;
+/// assert_eq!(devices.len(), 2);
+/// assert_eq!(devices[0]["Address"].as_str(), Some("00:11"));
+/// assert_eq!(devices[1]["Paired"], Value::Bool(false));
+///
+/// assert!(buf.as_body().decode::<Vec<(String, Value)>>().is_err());
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+impl<'de, T> FromBody<'de> for Vec<T>
+where
+    T: FromBody<'de>,
+{
+    #[inline]
+    fn write_signature(signature: &mut SignatureBuilder) -> Result<(), SignatureError> {
+        signature.open_array()?;
+        write_single::<T>(signature)?;
+        signature.close_array();
+        Ok(())
+    }
+
+    fn from_body(body: &mut Body<'de>) -> Result<Self> {
+        let mut element = SignatureBuilder::new();
+        T::write_signature(&mut element)?;
+
+        let mut array = load_array(body, |body| body.align_for(&element))?;
+        let mut values = Vec::new();
+
+        while !array.is_empty() {
+            values.push(T::from_body(&mut array)?);
+        }
+
+        Ok(values)
+    }
+}
+
+/// Decode a dictionary, where keys are decoded as `K` and values as `V`.
+///
+/// `K` must correspond to a basic type, and `V` must correspond to a single
+/// complete type. If a key is repeated, the last value is used.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use tokio_dbus::{ty, BodyBuf};
+///
+/// let mut buf = BodyBuf::new();
+/// let mut dict = buf.store_dict::<u32, ty::Str>()?;
+/// dict.store(1, "one");
+/// dict.store(2, "two");
+/// dict.finish()?;
+///
+/// let dict = buf.as_body().decode::<HashMap<u32, &str>>()?;
+/// assert_eq!(dict[&1], "one");
+/// assert_eq!(dict[&2], "two");
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+impl<'de, K, V, S> FromBody<'de> for HashMap<K, V, S>
+where
+    K: FromBody<'de> + Eq + Hash,
+    V: FromBody<'de>,
+    S: Default + BuildHasher,
+{
+    #[inline]
+    fn write_signature(signature: &mut SignatureBuilder) -> Result<(), SignatureError> {
+        let start = signature.len();
+        signature.open_array()?;
+        signature.open_dict()?;
+        K::write_signature(signature)?;
+        V::write_signature(signature)?;
+        signature.close_dict()?;
+        signature.close_array();
+
+        // NB: Validating the complete dictionary ensures that the key is a
+        // basic type and that the entry has exactly two fields.
+        Signature::new(&signature.as_bytes()[start..])?;
+        Ok(())
+    }
+
+    fn from_body(body: &mut Body<'de>) -> Result<Self> {
+        // NB: Dict entries are aligned like structs.
+        let mut array = load_array(body, Body::align::<u64>)?;
+        let mut map = HashMap::default();
+
+        while !array.is_empty() {
+            array.align::<u64>()?;
+            let key = K::from_body(&mut array)?;
+            let value = V::from_body(&mut array)?;
+            map.insert(key, value);
+        }
+
+        Ok(map)
+    }
+}
+
+/// Write the signature of `T`, ensuring that it's a single complete type.
+fn write_single<'de, T>(signature: &mut SignatureBuilder) -> Result<(), SignatureError>
+where
+    T: FromBody<'de>,
+{
+    let start = signature.len();
+    T::write_signature(signature)?;
+
+    let mut it = Signature::new(&signature.as_bytes()[start..])?.iter();
+
+    let (Some(..), None) = (it.next(), it.next()) else {
+        return Err(SignatureError::not_single_complete_type());
+    };
+
+    Ok(())
+}
+
+/// Load the body of an array, where `align` aligns the body to the first
+/// element.
+fn load_array<'de, A>(body: &mut Body<'de>, align: A) -> Result<Body<'de>>
+where
+    A: FnOnce(&mut Body<'de>) -> Result<()>,
+{
+    let bytes = body.load::<u32>()?;
+
+    if bytes > MAX_ARRAY_LENGTH {
+        return Err(Error::new(ErrorKind::ArrayTooLong(bytes)));
+    }
+
+    // NB: Padding up until the first element is not included in the length of
+    // the array.
+    align(body)?;
+
+    if bytes as usize > body.len() {
+        return Err(Error::new(ErrorKind::BufferUnderflow));
+    }
+
+    Ok(body.read_until(bytes as usize))
+}
+
+macro_rules! impl_tuple {
+    ($($ty:ident),*) => {
+        impl<'de, $($ty),*> FromBody<'de> for ($($ty,)*)
+        where
+            $($ty: FromBody<'de>,)*
+        {
+            #[inline]
+            fn write_signature(signature: &mut SignatureBuilder) -> Result<(), SignatureError> {
+                $(<$ty>::write_signature(signature)?;)*
+                Ok(())
+            }
+
+            #[inline]
+            fn from_body(body: &mut Body<'de>) -> Result<Self> {
+                Ok(($(<$ty>::from_body(body)?,)*))
+            }
+        }
+
+        impl<'de, $($ty),*> TryFrom<Body<'de>> for ($($ty,)*)
+        where
+            $($ty: FromBody<'de>,)*
+        {
+            type Error = Error;
+
+            #[inline]
+            fn try_from(body: Body<'de>) -> Result<Self> {
+                body.decode()
+            }
+        }
+    }
+}
+
+impl<'de> FromBody<'de> for () {
+    #[inline]
+    fn write_signature(_: &mut SignatureBuilder) -> Result<(), SignatureError> {
+        Ok(())
+    }
+
+    #[inline]
+    fn from_body(_: &mut Body<'de>) -> Result<Self> {
+        Ok(())
+    }
+}
+
+repeat!(impl_tuple);
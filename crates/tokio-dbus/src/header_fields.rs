@@ -0,0 +1,106 @@
+use crate::error::Result;
+use crate::proto;
+use crate::ty::Marker;
+use crate::{ty, Body, Signature, Variant};
+
+/// A single field in the header of a received message.
+///
+/// See [`RecvBuf::header_fields`].
+///
+/// [`RecvBuf::header_fields`]: crate::RecvBuf::header_fields
+#[derive(Debug, Clone)]
+pub struct HeaderField<'a> {
+    code: proto::Variant,
+    /// The variant, positioned at its signature.
+    variant: Body<'a>,
+    /// The value of the variant.
+    value: Body<'a>,
+}
+
+impl<'a> HeaderField<'a> {
+    /// Get the code of the header field.
+    ///
+    /// This is one of the constants in [`proto::Variant`] for fields defined by
+    /// the specification, but could be any other value for fields added by
+    /// extensions.
+    pub fn code(&self) -> proto::Variant {
+        self.code
+    }
+
+    /// Get the signature of the value of the header field.
+    pub fn signature(&self) -> &'a Signature {
+        self.value.signature()
+    }
+
+    /// Get a body which can be used to read the value of the header field.
+    ///
+    /// This supports reading values of any type.
+    pub fn value(&self) -> Body<'a> {
+        self.value.clone()
+    }
+
+    /// Decode the value of the header field as a [`Variant`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if the value is not of a basic type.
+    pub fn variant(&self) -> Result<Variant<'a>> {
+        ty::Variant::load_struct(&mut self.variant.clone())
+    }
+}
+
+/// Iterator over the fields in the header of a received message.
+///
+/// See [`RecvBuf::header_fields`].
+///
+/// [`RecvBuf::header_fields`]: crate::RecvBuf::header_fields
+#[derive(Debug, Clone)]
+pub struct HeaderFields<'a> {
+    fields: Body<'a>,
+}
+
+impl<'a> HeaderFields<'a> {
+    #[inline]
+    pub(crate) fn new(fields: Body<'a>) -> Self {
+        Self { fields }
+    }
+
+    fn next_field(&mut self) -> Result<HeaderField<'a>> {
+        // NB: Structs are aligned to 8 bytes.
+        self.fields.align::<u64>()?;
+        let code = self.fields.load::<proto::Variant>()?;
+        let variant = self.fields.clone();
+        let signature = self.fields.read::<Signature>()?;
+
+        let mut value = self.fields.clone();
+        crate::signature::skip(signature, &mut self.fields)?;
+        let value = value
+            .read_until(value.len() - self.fields.len())
+            .with_signature(signature);
+
+        Ok(HeaderField {
+            code,
+            variant,
+            value,
+        })
+    }
+}
+
+impl<'a> Iterator for HeaderFields<'a> {
+    type Item = Result<HeaderField<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.fields.is_empty() {
+            return None;
+        }
+
+        match self.next_field() {
+            Ok(field) => Some(Ok(field)),
+            Err(error) => {
+                let len = self.fields.len();
+                self.fields.read_until(len);
+                Some(Err(error))
+            }
+        }
+    }
+}
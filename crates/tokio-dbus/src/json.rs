@@ -0,0 +1,504 @@
+//! Conversion between D-Bus bodies and [`serde_json::Value`].
+//!
+//! D-Bus values are mapped to JSON like this:
+//! * Numbers are mapped to JSON numbers. Doubles which are not finite are
+//!   mapped to `null`.
+//! * Booleans are mapped to JSON booleans.
+//! * Strings, object paths and signatures are mapped to JSON strings.
+//! * Arrays and structs are mapped to JSON arrays.
+//! * Arrays of dict entries are mapped to JSON objects, where keys which are
+//!   not strings are converted to their string representation.
+//! * Variants are mapped to the value they contain.
+//!
+//! Since bodies contain a sequence of values, a body is always represented as a
+//! JSON array containing one element for each complete type in its signature.
+
+use serde_json::{Map, Number, Value};
+
+use tokio_dbus_core::signature::Type as SignatureType;
+
+use crate::buf::MAX_ARRAY_LENGTH;
+use crate::error::{ErrorKind, Result};
+use crate::limits::enter;
+use crate::proto::Type;
+use crate::{Body, BodyBuf, Error, ObjectPath, Signature, UnixFd, Visitor};
+
+const ARRAY_OF_VARIANTS: &Signature = Signature::new_const(b"av");
+const DICT_OF_VARIANTS: &Signature = Signature::new_const(b"a{sv}");
+
+impl Body<'_> {
+    /// Convert the remaining contents of the body into a JSON value.
+    ///
+    /// The returned value is an array containing one element for each complete
+    /// type in the [remaining signature] of the body. See the [`json` module]
+    /// for how values are mapped.
+    ///
+    /// [remaining signature]: Body::remaining_signature
+    /// [`json` module]: crate::json
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// use tokio_dbus::{ty, BodyBuf};
+    ///
+    /// let mut buf = BodyBuf::new();
+    /// buf.store(42u32)?;
+    ///
+    /// let mut array = buf.store_array::<(ty::Str, u8)>()?;
+    /// array.store_struct().store("a").store(1u8).finish()?;
+    /// array.finish()?;
+    ///
+    /// let value = buf.as_body().to_json()?;
+    /// assert_eq!(value, json!([42, [["a", 1]]]));
+    ///
+    /// let mut body = buf.as_body();
+    /// assert_eq!(body.load::<u32>()?, 42);
+    /// assert_eq!(body.to_json()?, json!([[["a", 1]]]));
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn to_json(&self) -> Result<Value> {
+        let mut body = self.clone();
+        let mut builder = JsonBuilder::new();
+        body.walk(self.remaining_signature(), &mut builder)?;
+        Ok(Value::Array(builder.finish()))
+    }
+}
+
+impl BodyBuf {
+    /// Store a JSON value in the buffer according to the given `signature`.
+    ///
+    /// The value must be an array containing one element for each complete
+    /// type in `signature`. See the [`json` module] for how values are mapped.
+    ///
+    /// Variants are stored by inferring their type from the JSON value, where
+    /// integers are stored as `x` (or `t` if they don't fit), other numbers as
+    /// `d`, arrays as `av`, and objects as `a{sv}`.
+    ///
+    /// [`json` module]: crate::json
+    ///
+    /// # Errors
+    ///
+    /// Errors if the value doesn't match the signature, in which case the
+    /// buffer is left unmodified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// use tokio_dbus::{BodyBuf, Signature};
+    ///
+    /// let mut buf = BodyBuf::new();
+    /// buf.store_json(Signature::new("ua{sv}")?, &json!([42, {"a": "b"}]))?;
+    ///
+    /// assert_eq!(buf.signature(), "ua{sv}");
+    /// assert_eq!(buf.as_body().to_json()?, json!([42, {"a": "b"}]));
+    ///
+    /// assert!(buf.store_json(Signature::new("y")?, &json!([256])).is_err());
+    /// assert_eq!(buf.signature(), "ua{sv}");
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn store_json(&mut self, signature: &Signature, value: &Value) -> Result<()> {
+        let Value::Array(values) = value else {
+            return Err(mismatch("array of arguments", value));
+        };
+
+        let checkpoint = self.checkpoint();
+
+        if let Err(error) = store_all(self, signature, values, 0) {
+            self.restore(checkpoint);
+            return Err(error);
+        }
+
+        if let Err(error) = self.extend_signature(signature) {
+            self.restore(checkpoint);
+            return Err(error);
+        }
+
+        Ok(())
+    }
+}
+
+fn store_all(
+    buf: &mut BodyBuf,
+    signature: &Signature,
+    values: &[Value],
+    depth: usize,
+) -> Result<()> {
+    let mut values = values.iter();
+
+    for ty in signature.iter() {
+        let Some(value) = values.next() else {
+            return Err(json_error(format!(
+                "missing value for `{}`",
+                signature.as_str()
+            )));
+        };
+
+        store_type(buf, ty, value, depth)?;
+    }
+
+    if values.next().is_some() {
+        return Err(json_error(format!(
+            "too many values for `{}`",
+            signature.as_str()
+        )));
+    }
+
+    Ok(())
+}
+
+fn store_type(buf: &mut BodyBuf, ty: SignatureType<'_>, value: &Value, depth: usize) -> Result<()> {
+    match ty {
+        SignatureType::Signature(signature) => {
+            let Some(&b) = signature.as_bytes().first() else {
+                return Ok(());
+            };
+
+            store_basic(buf, Type::new(b), value, depth)?;
+        }
+        SignatureType::Array(element) => {
+            let depth = enter(depth)?;
+            let len = buf.alloc::<u32>();
+            buf.align_mut_for(element);
+            let start = buf.len();
+
+            match (element.iter().next(), value) {
+                (Some(SignatureType::Dict(key, value)), Value::Object(map)) => {
+                    for (k, v) in map {
+                        buf.align_mut::<u64>();
+                        store_key(buf, key, k)?;
+                        store_all(buf, value, std::slice::from_ref(v), depth)?;
+                    }
+                }
+                (_, Value::Array(values)) => {
+                    for value in values {
+                        store_all(buf, element, std::slice::from_ref(value), depth)?;
+                    }
+                }
+                (_, value) => return Err(mismatch("array", value)),
+            }
+
+            let bytes = (buf.len() - start) as u32;
+
+            if bytes > MAX_ARRAY_LENGTH {
+                return Err(Error::new(ErrorKind::ArrayTooLong(bytes)));
+            }
+
+            buf.store_at(len, bytes);
+        }
+        SignatureType::Struct(fields) => {
+            let depth = enter(depth)?;
+
+            let Value::Array(values) = value else {
+                return Err(mismatch("array of struct fields", value));
+            };
+
+            buf.align_mut::<u64>();
+            store_all(buf, fields, values, depth)?;
+        }
+        SignatureType::Dict(..) => {
+            return Err(json_error("dict entry outside of array".into()));
+        }
+    }
+
+    Ok(())
+}
+
+fn store_key(buf: &mut BodyBuf, signature: &Signature, key: &str) -> Result<()> {
+    let Some(&b) = signature.as_bytes().first() else {
+        return Ok(());
+    };
+
+    let value = match Type::new(b) {
+        Type::STRING | Type::OBJECT_PATH | Type::SIGNATURE => Value::String(key.to_owned()),
+        Type::BOOLEAN => match key {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => return Err(json_error(format!("invalid boolean key `{key}`"))),
+        },
+        _ => match serde_json::from_str::<Number>(key) {
+            Ok(number) => Value::Number(number),
+            Err(..) => return Err(json_error(format!("invalid numeric key `{key}`"))),
+        },
+    };
+
+    store_basic(buf, Type::new(b), &value, 0)
+}
+
+fn store_basic(buf: &mut BodyBuf, ty: Type, value: &Value, depth: usize) -> Result<()> {
+    macro_rules! integer {
+        ($ty:ty, $expected:literal) => {{
+            let Some(n) = value.as_i64().and_then(|n| <$ty>::try_from(n).ok()) else {
+                return Err(mismatch($expected, value));
+            };
+
+            buf.store_frame(n);
+        }};
+    }
+
+    match ty {
+        Type::BYTE => integer!(u8, "byte"),
+        Type::INT16 => integer!(i16, "16-bit signed integer"),
+        Type::UINT16 => integer!(u16, "16-bit unsigned integer"),
+        Type::INT32 => integer!(i32, "32-bit signed integer"),
+        Type::UINT32 => integer!(u32, "32-bit unsigned integer"),
+        Type::UNIX_FD => {
+            let Some(n) = value.as_i64().and_then(|n| u32::try_from(n).ok()) else {
+                return Err(mismatch("unix file descriptor index", value));
+            };
+
+            buf.store_unix_fd(UnixFd::new(n));
+        }
+        Type::INT64 => integer!(i64, "64-bit signed integer"),
+        Type::UINT64 => {
+            let Some(n) = value.as_u64() else {
+                return Err(mismatch("64-bit unsigned integer", value));
+            };
+
+            buf.store_frame(n);
+        }
+        Type::DOUBLE => {
+            let Some(n) = value.as_f64() else {
+                return Err(mismatch("double", value));
+            };
+
+            buf.store_frame(n);
+        }
+        Type::BOOLEAN => {
+            let Value::Bool(b) = value else {
+                return Err(mismatch("boolean", value));
+            };
+
+            buf.store_frame(u32::from(*b));
+        }
+        Type::STRING => {
+            let Value::String(string) = value else {
+                return Err(mismatch("string", value));
+            };
+
+            buf.write_only(string.as_str());
+        }
+        Type::OBJECT_PATH => {
+            let Value::String(string) = value else {
+                return Err(mismatch("object path", value));
+            };
+
+            buf.write_only(ObjectPath::new(string.as_str())?);
+        }
+        Type::SIGNATURE => {
+            let Value::String(string) = value else {
+                return Err(mismatch("signature", value));
+            };
+
+            buf.write_only(Signature::new(string.as_str())?);
+        }
+        Type::VARIANT => {
+            let depth = enter(depth)?;
+
+            let signature = match value {
+                Value::Bool(..) => Signature::BOOLEAN,
+                Value::Number(n) if n.is_i64() => Signature::INT64,
+                Value::Number(n) if n.is_u64() => Signature::UINT64,
+                Value::Number(..) => Signature::DOUBLE,
+                Value::String(..) => Signature::STRING,
+                Value::Array(..) => ARRAY_OF_VARIANTS,
+                Value::Object(..) => DICT_OF_VARIANTS,
+                Value::Null => return Err(mismatch("value for variant", value)),
+            };
+
+            buf.write_only(signature);
+            store_all(buf, signature, std::slice::from_ref(value), depth)?;
+        }
+        _ => return Err(Error::new(ErrorKind::UnsupportedType(ty))),
+    }
+
+    Ok(())
+}
+
+fn mismatch(expected: &str, actual: &Value) -> Error {
+    json_error(format!("expected {expected}, but found `{actual}`"))
+}
+
+fn json_error(message: String) -> Error {
+    Error::new(ErrorKind::Json(message.into()))
+}
+
+/// A container being built.
+enum Frame {
+    Array(Vec<Value>),
+    Object(Map<String, Value>),
+    Entry(Vec<Value>),
+    Variant(Option<Value>),
+}
+
+/// Visitor which builds JSON values.
+struct JsonBuilder {
+    root: Vec<Value>,
+    stack: Vec<Frame>,
+}
+
+impl JsonBuilder {
+    fn new() -> Self {
+        Self {
+            root: Vec::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    fn finish(self) -> Vec<Value> {
+        self.root
+    }
+
+    fn push(&mut self, value: Value) -> Result<()> {
+        match self.stack.last_mut() {
+            None => self.root.push(value),
+            Some(Frame::Array(values) | Frame::Entry(values)) => values.push(value),
+            Some(Frame::Variant(inner)) => *inner = Some(value),
+            Some(Frame::Object(..)) => {
+                return Err(json_error("value outside of dict entry".into()));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<Frame> {
+        let Some(frame) = self.stack.pop() else {
+            return Err(json_error("unbalanced containers".into()));
+        };
+
+        Ok(frame)
+    }
+}
+
+impl<'de> Visitor<'de> for JsonBuilder {
+    fn visit_u8(&mut self, value: u8) -> Result<()> {
+        self.push(Value::from(value))
+    }
+
+    fn visit_bool(&mut self, value: bool) -> Result<()> {
+        self.push(Value::from(value))
+    }
+
+    fn visit_i16(&mut self, value: i16) -> Result<()> {
+        self.push(Value::from(value))
+    }
+
+    fn visit_u16(&mut self, value: u16) -> Result<()> {
+        self.push(Value::from(value))
+    }
+
+    fn visit_i32(&mut self, value: i32) -> Result<()> {
+        self.push(Value::from(value))
+    }
+
+    fn visit_u32(&mut self, value: u32) -> Result<()> {
+        self.push(Value::from(value))
+    }
+
+    fn visit_i64(&mut self, value: i64) -> Result<()> {
+        self.push(Value::from(value))
+    }
+
+    fn visit_u64(&mut self, value: u64) -> Result<()> {
+        self.push(Value::from(value))
+    }
+
+    fn visit_f64(&mut self, value: f64) -> Result<()> {
+        self.push(Number::from_f64(value).map_or(Value::Null, Value::Number))
+    }
+
+    fn visit_str(&mut self, value: &'de str) -> Result<()> {
+        self.push(Value::from(value))
+    }
+
+    fn visit_object_path(&mut self, value: &'de ObjectPath) -> Result<()> {
+        self.push(Value::from(value.to_string()))
+    }
+
+    fn visit_signature(&mut self, value: &'de Signature) -> Result<()> {
+        self.push(Value::from(value.as_str()))
+    }
+
+    fn visit_unix_fd(&mut self, index: u32) -> Result<()> {
+        self.push(Value::from(index))
+    }
+
+    fn enter_array(&mut self, element: &Signature) -> Result<()> {
+        if element.as_bytes().first() == Some(&b'{') {
+            self.stack.push(Frame::Object(Map::new()));
+        } else {
+            self.stack.push(Frame::Array(Vec::new()));
+        }
+
+        Ok(())
+    }
+
+    fn leave_array(&mut self) -> Result<()> {
+        let value = match self.pop()? {
+            Frame::Array(values) => Value::Array(values),
+            Frame::Object(map) => Value::Object(map),
+            _ => return Err(json_error("unbalanced containers".into())),
+        };
+
+        self.push(value)
+    }
+
+    fn enter_struct(&mut self, _: &Signature) -> Result<()> {
+        self.stack.push(Frame::Array(Vec::new()));
+        Ok(())
+    }
+
+    fn leave_struct(&mut self) -> Result<()> {
+        let Frame::Array(values) = self.pop()? else {
+            return Err(json_error("unbalanced containers".into()));
+        };
+
+        self.push(Value::Array(values))
+    }
+
+    fn enter_dict_entry(&mut self, _: &Signature, _: &Signature) -> Result<()> {
+        self.stack.push(Frame::Entry(Vec::with_capacity(2)));
+        Ok(())
+    }
+
+    fn leave_dict_entry(&mut self) -> Result<()> {
+        let Frame::Entry(values) = self.pop()? else {
+            return Err(json_error("unbalanced containers".into()));
+        };
+
+        let mut it = values.into_iter();
+
+        let (Some(key), Some(value), None) = (it.next(), it.next(), it.next()) else {
+            return Err(json_error(
+                "dict entry must contain a key and a value".into(),
+            ));
+        };
+
+        let key = match key {
+            Value::String(string) => string,
+            key => key.to_string(),
+        };
+
+        let Some(Frame::Object(map)) = self.stack.last_mut() else {
+            return Err(json_error("dict entry outside of array".into()));
+        };
+
+        map.insert(key, value);
+        Ok(())
+    }
+
+    fn enter_variant(&mut self, _: &'de Signature) -> Result<()> {
+        self.stack.push(Frame::Variant(None));
+        Ok(())
+    }
+
+    fn leave_variant(&mut self) -> Result<()> {
+        let Frame::Variant(Some(value)) = self.pop()? else {
+            return Err(json_error("variant must contain a single value".into()));
+        };
+
+        self.push(value)
+    }
+}
@@ -11,6 +11,16 @@
 //! To currently see how it's used, see:
 //! * [examples/client.rs](https://github.com/udoprog/tokio-dbus/blob/main/examples/examples/client.rs)
 //! * [examples/server.rs](https://github.com/udoprog/tokio-dbus/blob/main/examples/examples/server.rs)
+//!
+//! ## WebAssembly
+//!
+//! The connection is only available with the `tokio` feature, which is
+//! enabled by default. Without default features the message and serialization
+//! layers still build, including for `wasm32-unknown-unknown`. Messages are
+//! then exchanged with a bridge through `SendBuf::write_to` and
+//! `RecvBuf::read_from`, see
+//! [examples/frames.rs](https://github.com/udoprog/tokio-dbus/blob/main/examples/examples/frames.rs)
+//! for an adapter which exchanges them as binary frames over channels.
 
 #![deny(missing_docs)]
 #![allow(clippy::module_inception)]
@@ -18,13 +28,37 @@
 #[macro_use]
 mod macros;
 
+mod literals;
+
 #[doc(inline)]
 pub use self::proto::{Endianness, Flags};
 #[macro_use]
-mod proto;
+pub mod proto;
+
+pub mod limits;
 
 pub mod org_freedesktop_dbus;
 
+pub mod org_freedesktop_dbus_object_manager;
+
+pub mod org_freedesktop_dbus_properties;
+
+pub mod org_freedesktop_notifications;
+
+pub mod org_freedesktop_portal;
+
+#[cfg(feature = "systemd")]
+pub mod org_freedesktop_login1;
+
+#[cfg(feature = "systemd")]
+pub mod org_freedesktop_systemd1;
+
+#[cfg(feature = "serde_json")]
+pub mod json;
+
+#[cfg(feature = "test-support")]
+pub mod test_support;
+
 #[doc(inline)]
 pub use self::write::Write;
 mod write;
@@ -40,44 +74,85 @@ mod error;
 pub(crate) mod buf;
 
 #[doc(inline)]
-pub use self::body_buf::{BodyBuf, StoreArray, StoreStruct};
+pub use self::body_buf::{BodyBuf, LengthPrefix, StoreArray, StoreDict, StoreStruct};
 mod body_buf;
 
+#[cfg(feature = "dump")]
+#[doc(inline)]
+pub use self::body::DumpBody;
 #[doc(inline)]
-pub use self::body::{AsBody, Body, LoadArray};
+pub use self::body::{AsBody, Body, DisplayBody, LoadArray, LoadDict, Visitor};
 mod body;
 
 #[doc(inline)]
-pub use self::send_buf::SendBuf;
+pub use self::to_dbus_error::ToDbusError;
+mod to_dbus_error;
+
+#[doc(inline)]
+pub use self::send_buf::{Middleware, SendBuf, Transaction};
 mod send_buf;
 
 #[doc(inline)]
-pub use self::recv_buf::RecvBuf;
+pub use self::recv_buf::{MessageRef, RecvBuf};
 mod recv_buf;
 
-mod sasl;
+#[doc(inline)]
+pub use self::sender_quota::SenderQuota;
+mod sender_quota;
+
+#[doc(inline)]
+pub use self::header_fields::{HeaderField, HeaderFields};
+mod header_fields;
+
+#[doc(inline)]
+pub use self::match_rule::MatchRule;
+mod match_rule;
+
+#[doc(inline)]
+pub use self::sasl::Auth;
+pub mod sasl;
 
 #[doc(inline)]
 pub use self::signature::{Signature, SignatureBuf, SignatureError};
-mod signature;
+pub mod signature;
 
 #[doc(inline)]
 pub use self::frame::Frame;
 mod frame;
 
 #[doc(inline)]
-pub use self::storable::Storable;
+pub use self::storable::{Storable, StorableDyn};
 mod storable;
 
 #[doc(inline)]
-pub use self::message::{Message, MessageBuf, MessageKind};
+pub use self::message::{DisplayError, Message, MessageBuf, MessageHeader, MessageId, MessageKind};
 mod message;
 
+#[cfg(all(feature = "tokio", feature = "libc"))]
+#[doc(inline)]
+pub use self::connection::Credentials;
 #[cfg(feature = "tokio")]
 #[doc(inline)]
-pub use self::connection::{Connection, ConnectionBuilder};
+pub use self::connection::{
+    CallEvent, CallWithSignals, Connection, ConnectionBuilder, ConnectionStatus, Handshake,
+    ReplyBuilder, SendPermit, SharedConnection, WeakConnection,
+};
+#[cfg(feature = "tokio")]
 mod connection;
 
+#[cfg(all(feature = "tokio", feature = "introspect"))]
+#[doc(inline)]
+pub use self::dynamic_proxy::{DynamicInterface, DynamicMethod, DynamicProxy};
+#[cfg(all(feature = "tokio", feature = "introspect"))]
+mod dynamic_proxy;
+
+#[cfg(feature = "inventory")]
+#[doc(inline)]
+pub use self::object_registry::InterfaceRegistration;
+#[doc(inline)]
+pub use self::object_registry::{InterfaceHandler, ObjectRegistry};
+mod object_registry;
+
 mod lossy_str;
 
 mod utils;
@@ -86,12 +161,60 @@ mod utils;
 pub use self::object_path::{ObjectPath, ObjectPathBuf, ObjectPathError};
 mod object_path;
 
+#[doc(inline)]
+pub use self::bus_name::{BusName, BusNameError};
+mod bus_name;
+
 #[doc(inline)]
 pub use self::variant::Variant;
 mod variant;
 
+#[doc(inline)]
+pub use self::value::Value;
+mod value;
+
+#[doc(inline)]
+pub use self::unix_fd::UnixFd;
+mod unix_fd;
+
 pub mod ty;
 
 #[doc(inline)]
 pub use self::arguments::Arguments;
 mod arguments;
+
+#[doc(inline)]
+pub use self::backoff::{Backoff, Delays};
+mod backoff;
+
+#[cfg(feature = "usec")]
+mod time;
+
+#[doc(inline)]
+pub use self::protocol_violation::ProtocolViolation;
+mod protocol_violation;
+
+#[doc(inline)]
+pub use self::broadcast::Broadcast;
+mod broadcast;
+
+#[doc(inline)]
+pub use self::signal_throttle::SignalThrottle;
+mod signal_throttle;
+
+#[doc(inline)]
+pub use self::from_body::FromBody;
+mod from_body;
+
+/// Derive macro for the [`FromBody`] trait.
+///
+/// See [`FromBody`] for more details.
+#[doc(inline)]
+pub use tokio_dbus_macros::FromBody;
+
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "inventory")]
+    pub use inventory;
+    pub use tokio_dbus_core::signature::SignatureBuilder;
+}
@@ -0,0 +1,113 @@
+//! Limits imposed by the [D-Bus specification].
+//!
+//! Messages which exceed these limits are rejected by the bus, and are
+//! reported as errors when sent or received through this crate. The helper
+//! functions in this module can be used to check sizes up front when building
+//! messages programmatically.
+//!
+//! [D-Bus specification]: https://dbus.freedesktop.org/doc/dbus-specification.html
+
+use crate::error::{Error, ErrorKind, Result};
+
+/// The maximum length of an array in bytes, which is 64 MiB.
+pub const MAX_ARRAY_LENGTH: u32 = 1u32 << 26;
+
+/// The maximum length of a message body in bytes, which is 128 MiB.
+pub const MAX_BODY_LENGTH: u32 = 1u32 << 27;
+
+/// The maximum length in bytes of bus names, interface names, member names
+/// and error names.
+pub const MAX_NAME_LENGTH: usize = 255;
+
+/// The maximum length of a signature in bytes.
+pub const MAX_SIGNATURE_LENGTH: usize = 255;
+
+/// The maximum nesting depth of arrays, and separately of structs.
+///
+/// Object paths are not limited in depth, only by the maximum length of a
+/// message.
+pub const MAX_CONTAINER_DEPTH: usize = tokio_dbus_core::signature::MAX_CONTAINER_DEPTH;
+
+/// The maximum total nesting depth of containers, including variants.
+pub const MAX_DEPTH: usize = tokio_dbus_core::signature::MAX_DEPTH;
+
+/// Check that an array of `len` bytes doesn't exceed [`MAX_ARRAY_LENGTH`].
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::limits;
+///
+/// assert!(limits::check_array_length(1024).is_ok());
+/// assert!(limits::check_array_length(1 << 26).is_ok());
+///
+/// let error = limits::check_array_length((1 << 26) + 1).unwrap_err();
+/// assert!(error.is_limit_exceeded());
+/// ```
+pub fn check_array_length(len: usize) -> Result<()> {
+    match u32::try_from(len) {
+        Ok(len) if len <= MAX_ARRAY_LENGTH => Ok(()),
+        _ => Err(Error::new(ErrorKind::ArrayTooLong(saturate(len)))),
+    }
+}
+
+/// Check that a body of `len` bytes doesn't exceed [`MAX_BODY_LENGTH`].
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{limits, BodyBuf};
+///
+/// let mut body = BodyBuf::new();
+/// body.store("Hello World")?;
+///
+/// limits::check_body_length(body.len())?;
+/// assert!(limits::check_body_length(usize::MAX).is_err());
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+pub fn check_body_length(len: usize) -> Result<()> {
+    match u32::try_from(len) {
+        Ok(len) if len <= MAX_BODY_LENGTH => Ok(()),
+        _ => Err(Error::new(ErrorKind::BodyTooLong(saturate(len)))),
+    }
+}
+
+/// Check that a bus, interface, member or error name doesn't exceed
+/// [`MAX_NAME_LENGTH`].
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::limits;
+///
+/// assert!(limits::check_name_length("org.freedesktop.DBus").is_ok());
+///
+/// let name = "a".repeat(256);
+/// let error = limits::check_name_length(&name).unwrap_err();
+/// assert!(error.is_limit_exceeded());
+/// ```
+pub fn check_name_length(name: &str) -> Result<()> {
+    if name.len() > MAX_NAME_LENGTH {
+        return Err(Error::new(ErrorKind::NameTooLong(name.len())));
+    }
+
+    Ok(())
+}
+
+/// Increment the nesting depth of containers, erroring if it exceeds
+/// [`MAX_DEPTH`].
+#[inline]
+pub(crate) fn enter(depth: usize) -> Result<usize> {
+    let depth = depth + 1;
+
+    if depth > MAX_DEPTH {
+        return Err(Error::new(ErrorKind::DepthExceeded));
+    }
+
+    Ok(depth)
+}
+
+#[inline]
+fn saturate(len: usize) -> u32 {
+    u32::try_from(len).unwrap_or(u32::MAX)
+}
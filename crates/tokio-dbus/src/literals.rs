@@ -0,0 +1,67 @@
+/// Construct a `&'static` [`Signature`] from a string literal, which is
+/// validated at compile time.
+///
+/// This is a more convenient alternative to calling [`Signature::new_const`]
+/// with a byte string in a constant context.
+///
+/// [`Signature`]: crate::Signature
+/// [`Signature::new_const`]: crate::Signature::new_const
+///
+/// An invalid signature fails to compile:
+///
+/// ```compile_fail
+/// let signature = tokio_dbus::sig!("(a)");
+/// ```
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{sig, Signature};
+///
+/// const PROPERTIES: &Signature = sig!("a{sv}");
+///
+/// assert_eq!(PROPERTIES, Signature::new("a{sv}")?);
+/// assert_eq!(sig!("(iu)"), Signature::new_const(b"(iu)"));
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+#[macro_export]
+macro_rules! sig {
+    ($signature:literal) => {{
+        const SIGNATURE: &$crate::Signature = $crate::Signature::new_const($signature.as_bytes());
+        SIGNATURE
+    }};
+}
+
+/// Construct a `&'static` [`ObjectPath`] from a string literal, which is
+/// validated at compile time.
+///
+/// This is a more convenient alternative to calling [`ObjectPath::new_const`]
+/// with a byte string in a constant context.
+///
+/// [`ObjectPath`]: crate::ObjectPath
+/// [`ObjectPath::new_const`]: crate::ObjectPath::new_const
+///
+/// An invalid object path fails to compile:
+///
+/// ```compile_fail
+/// let path = tokio_dbus::path!("/org/example/");
+/// ```
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{path, ObjectPath};
+///
+/// const PATH: &ObjectPath = path!("/org/freedesktop/DBus");
+///
+/// assert_eq!(PATH, ObjectPath::new("/org/freedesktop/DBus")?);
+/// assert_eq!(path!("/"), ObjectPath::ROOT);
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+#[macro_export]
+macro_rules! path {
+    ($path:literal) => {{
+        const PATH: &$crate::ObjectPath = $crate::ObjectPath::new_const($path.as_bytes());
+        PATH
+    }};
+}
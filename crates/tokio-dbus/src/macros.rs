@@ -33,6 +33,8 @@ macro_rules! impl_traits_for_frame {
         impl $crate::ty::Marker for $ty {
             type Return<'de> = $ty;
 
+            const FIXED_SIZE: Option<usize> = Some(::std::mem::size_of::<$ty>());
+
             #[inline]
             fn load_struct<'de>(buf: &mut $crate::Body<'de>) -> $crate::Result<Self::Return<'de>> {
                 buf.load()
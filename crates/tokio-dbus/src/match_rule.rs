@@ -0,0 +1,444 @@
+use std::fmt;
+
+use crate::error::Result;
+use crate::proto::MessageType;
+use crate::{Message, ObjectPath, Signature, Visitor};
+
+/// A match rule, used to select which messages a connection receives.
+///
+/// The rule is converted to the textual format expected by the bus through its
+/// [`Display`] implementation, which takes care of quoting values. See the
+/// [D-Bus specification] for the semantics of each key.
+///
+/// [`Display`]: fmt::Display
+/// [D-Bus specification]: https://dbus.freedesktop.org/doc/dbus-specification.html#message-bus-routing-match-rules
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{MatchRule, ObjectPath};
+/// use tokio_dbus::proto::MessageType;
+///
+/// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+///
+/// let rule = MatchRule::new()
+///     .with_message_type(MessageType::SIGNAL)
+///     .with_interface("org.freedesktop.DBus")
+///     .with_path(PATH)
+///     .with_arg(0, "it's");
+///
+/// assert_eq!(
+///     rule.to_string(),
+///     "type='signal',path='/org/freedesktop/DBus',interface='org.freedesktop.DBus',arg0='it'\\''s'"
+/// );
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MatchRule<'a> {
+    message_type: Option<MessageType>,
+    sender: Option<&'a str>,
+    path: Option<&'a ObjectPath>,
+    path_namespace: Option<&'a ObjectPath>,
+    interface: Option<&'a str>,
+    member: Option<&'a str>,
+    destination: Option<&'a str>,
+    args: Vec<(u8, &'a str)>,
+}
+
+impl<'a> MatchRule<'a> {
+    /// Construct a new match rule which matches every message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::MatchRule;
+    ///
+    /// assert_eq!(MatchRule::new().to_string(), "");
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only match messages of the given type.
+    #[must_use]
+    pub fn with_message_type(self, message_type: MessageType) -> Self {
+        Self {
+            message_type: Some(message_type),
+            ..self
+        }
+    }
+
+    /// Only match messages sent by the given unique or well-known name.
+    #[must_use]
+    pub fn with_sender(self, sender: &'a str) -> Self {
+        Self {
+            sender: Some(sender),
+            ..self
+        }
+    }
+
+    /// Only match messages sent to or from the given object path.
+    #[must_use]
+    pub fn with_path(self, path: &'a ObjectPath) -> Self {
+        Self {
+            path: Some(path),
+            ..self
+        }
+    }
+
+    /// Only match messages sent to or from the given object path or any of
+    /// its descendants.
+    #[must_use]
+    pub fn with_path_namespace(self, path_namespace: &'a ObjectPath) -> Self {
+        Self {
+            path_namespace: Some(path_namespace),
+            ..self
+        }
+    }
+
+    /// Only match messages with the given interface.
+    #[must_use]
+    pub fn with_interface(self, interface: &'a str) -> Self {
+        Self {
+            interface: Some(interface),
+            ..self
+        }
+    }
+
+    /// Only match messages with the given member.
+    #[must_use]
+    pub fn with_member(self, member: &'a str) -> Self {
+        Self {
+            member: Some(member),
+            ..self
+        }
+    }
+
+    /// Only match messages sent to the given unique name.
+    #[must_use]
+    pub fn with_destination(self, destination: &'a str) -> Self {
+        Self {
+            destination: Some(destination),
+            ..self
+        }
+    }
+
+    /// Only match messages where the string argument at `index` is equal to
+    /// `value`.
+    ///
+    /// The bus only supports indexes up to 63. Specifying the same index
+    /// multiple times replaces the previous value.
+    #[must_use]
+    pub fn with_arg(mut self, index: u8, value: &'a str) -> Self {
+        self.args.retain(|&(i, _)| i != index);
+        self.args.push((index, value));
+        self.args.sort_by_key(|&(i, _)| i);
+        self
+    }
+
+    /// Test if the rule matches the given message.
+    ///
+    /// This follows the matching performed by the reference bus
+    /// implementation, which allows rules to be tested without a bus:
+    ///
+    /// * `path_namespace` matches the namespace itself and any path below it,
+    ///   where `/` matches every path.
+    /// * `argN` only matches if argument `N` of the message is a string which
+    ///   is equal to the value.
+    /// * `sender` and `destination` are compared to the corresponding header
+    ///   fields as-is. Unlike the bus, this can't resolve a well-known name to
+    ///   the unique name which owns it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, MatchRule, ObjectPath, SendBuf};
+    /// use tokio_dbus::proto::MessageType;
+    ///
+    /// const NAMESPACE: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop");
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    /// const OTHER: &ObjectPath = ObjectPath::new_const(b"/org/freedesktopper");
+    ///
+    /// let rule = MatchRule::new()
+    ///     .with_message_type(MessageType::SIGNAL)
+    ///     .with_path_namespace(NAMESPACE)
+    ///     .with_member("NameOwnerChanged")
+    ///     .with_arg(0, "se.tedro.Example");
+    ///
+    /// let mut send = SendBuf::new();
+    /// let mut body = BodyBuf::new();
+    /// body.arguments(("se.tedro.Example", "", ":1.42"))?;
+    ///
+    /// let m = send.signal("NameOwnerChanged").with_path(PATH).with_body(&body);
+    /// assert!(rule.matches(&m));
+    /// assert!(!rule.matches(&m.clone().with_path(OTHER)));
+    ///
+    /// let mut other = BodyBuf::new();
+    /// other.arguments(("se.tedro.Other", "", ":1.42"))?;
+    /// assert!(!rule.matches(&m.with_body(&other)));
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn matches(&self, message: &Message<'_>) -> bool {
+        if let Some(message_type) = self.message_type {
+            if message.message_type() != message_type {
+                return false;
+            }
+        }
+
+        if self.sender.is_some() && message.sender() != self.sender {
+            return false;
+        }
+
+        if self.path.is_some() && message.path() != self.path {
+            return false;
+        }
+
+        if let Some(path_namespace) = self.path_namespace {
+            let Some(path) = message.path() else {
+                return false;
+            };
+
+            if !in_namespace(path, path_namespace) {
+                return false;
+            }
+        }
+
+        if self.interface.is_some() && message.interface() != self.interface {
+            return false;
+        }
+
+        if self.member.is_some() && message.member() != self.member {
+            return false;
+        }
+
+        if self.destination.is_some() && message.destination() != self.destination {
+            return false;
+        }
+
+        if self.args.is_empty() {
+            return true;
+        }
+
+        let body = message.body();
+        let mut args = StringArgs::default();
+
+        // NB: A body which can't be decoded can't match any arguments.
+        if body.clone().walk(body.signature(), &mut args).is_err() {
+            return false;
+        }
+
+        self.args
+            .iter()
+            .all(|&(index, value)| args.get(index) == Some(value))
+    }
+}
+
+/// Test if `path` is `namespace` or one of its descendants.
+fn in_namespace(path: &ObjectPath, namespace: &ObjectPath) -> bool {
+    if namespace == ObjectPath::ROOT {
+        return true;
+    }
+
+    match path.as_str().strip_prefix(namespace.as_str()) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+/// Visitor which collects the top-level string arguments of a body.
+#[derive(Default)]
+struct StringArgs<'de> {
+    /// The current nesting depth.
+    depth: usize,
+    /// The index of the argument being visited.
+    index: usize,
+    /// String arguments, by index.
+    strings: Vec<(usize, &'de str)>,
+}
+
+impl<'de> StringArgs<'de> {
+    fn get(&self, index: u8) -> Option<&'de str> {
+        let index = usize::from(index);
+        let &(_, value) = self.strings.iter().find(|&&(i, _)| i == index)?;
+        Some(value)
+    }
+
+    /// Finish visiting a value.
+    #[inline]
+    fn value(&mut self) -> Result<()> {
+        if self.depth == 0 {
+            self.index += 1;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn enter(&mut self) -> Result<()> {
+        self.depth += 1;
+        Ok(())
+    }
+
+    #[inline]
+    fn leave(&mut self) -> Result<()> {
+        self.depth -= 1;
+        self.value()
+    }
+}
+
+impl<'de> Visitor<'de> for StringArgs<'de> {
+    fn visit_u8(&mut self, _: u8) -> Result<()> {
+        self.value()
+    }
+
+    fn visit_bool(&mut self, _: bool) -> Result<()> {
+        self.value()
+    }
+
+    fn visit_i16(&mut self, _: i16) -> Result<()> {
+        self.value()
+    }
+
+    fn visit_u16(&mut self, _: u16) -> Result<()> {
+        self.value()
+    }
+
+    fn visit_i32(&mut self, _: i32) -> Result<()> {
+        self.value()
+    }
+
+    fn visit_u32(&mut self, _: u32) -> Result<()> {
+        self.value()
+    }
+
+    fn visit_i64(&mut self, _: i64) -> Result<()> {
+        self.value()
+    }
+
+    fn visit_u64(&mut self, _: u64) -> Result<()> {
+        self.value()
+    }
+
+    fn visit_f64(&mut self, _: f64) -> Result<()> {
+        self.value()
+    }
+
+    fn visit_str(&mut self, value: &'de str) -> Result<()> {
+        if self.depth == 0 {
+            self.strings.push((self.index, value));
+        }
+
+        self.value()
+    }
+
+    fn visit_object_path(&mut self, _: &'de ObjectPath) -> Result<()> {
+        self.value()
+    }
+
+    fn visit_signature(&mut self, _: &'de Signature) -> Result<()> {
+        self.value()
+    }
+
+    fn visit_unix_fd(&mut self, _: u32) -> Result<()> {
+        self.value()
+    }
+
+    fn enter_array(&mut self, _: &Signature) -> Result<()> {
+        self.enter()
+    }
+
+    fn leave_array(&mut self) -> Result<()> {
+        self.leave()
+    }
+
+    fn enter_struct(&mut self, _: &Signature) -> Result<()> {
+        self.enter()
+    }
+
+    fn leave_struct(&mut self) -> Result<()> {
+        self.leave()
+    }
+
+    fn enter_dict_entry(&mut self, _: &Signature, _: &Signature) -> Result<()> {
+        self.enter()
+    }
+
+    fn leave_dict_entry(&mut self) -> Result<()> {
+        self.leave()
+    }
+
+    fn enter_variant(&mut self, _: &'de Signature) -> Result<()> {
+        self.enter()
+    }
+
+    fn leave_variant(&mut self) -> Result<()> {
+        self.leave()
+    }
+}
+
+impl fmt::Display for MatchRule<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+
+        let mut field = |key: &dyn fmt::Display, value: &str| {
+            if !std::mem::take(&mut first) {
+                f.write_str(",")?;
+            }
+
+            write!(f, "{key}='")?;
+
+            // NB: There is no escaping inside of quotes, so an apostrophe has
+            // to be escaped outside of them.
+            for (n, part) in value.split('\'').enumerate() {
+                if n > 0 {
+                    f.write_str("'\\''")?;
+                }
+
+                f.write_str(part)?;
+            }
+
+            f.write_str("'")
+        };
+
+        if let Some(message_type) = self.message_type {
+            let name = match message_type {
+                MessageType::METHOD_CALL => "method_call",
+                MessageType::METHOD_RETURN => "method_return",
+                MessageType::ERROR => "error",
+                MessageType::SIGNAL => "signal",
+                _ => "invalid",
+            };
+
+            field(&"type", name)?;
+        }
+
+        if let Some(sender) = self.sender {
+            field(&"sender", sender)?;
+        }
+
+        if let Some(path) = self.path {
+            field(&"path", path.as_str())?;
+        }
+
+        if let Some(path_namespace) = self.path_namespace {
+            field(&"path_namespace", path_namespace.as_str())?;
+        }
+
+        if let Some(interface) = self.interface {
+            field(&"interface", interface)?;
+        }
+
+        if let Some(member) = self.member {
+            field(&"member", member)?;
+        }
+
+        if let Some(destination) = self.destination {
+            field(&"destination", destination)?;
+        }
+
+        for &(index, value) in &self.args {
+            field(&format_args!("arg{index}"), value)?;
+        }
+
+        Ok(())
+    }
+}
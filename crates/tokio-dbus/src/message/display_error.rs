@@ -0,0 +1,39 @@
+use std::fmt;
+
+/// Helper for printing an error message as its name followed by the message
+/// carried in its body.
+///
+/// See [`Message::display_error`].
+///
+/// [`Message::display_error`]: crate::Message::display_error
+pub struct DisplayError<'a> {
+    error_name: &'a str,
+    message: Option<&'a str>,
+}
+
+impl<'a> DisplayError<'a> {
+    #[inline]
+    pub(crate) fn new(error_name: &'a str, message: Option<&'a str>) -> Self {
+        Self {
+            error_name,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for DisplayError<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.message {
+            Some(message) => write!(f, "{}: {message}", self.error_name),
+            None => f.write_str(self.error_name),
+        }
+    }
+}
+
+impl fmt::Debug for DisplayError<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
@@ -1,7 +1,14 @@
+use std::borrow::Cow;
+use std::fmt;
 use std::num::NonZeroU32;
+use std::time::SystemTime;
 
+use crate::error::Result;
 use crate::proto::{Flags, MessageType};
-use crate::{AsBody, Body, BodyBuf, MessageBuf, MessageKind, ObjectPath, Signature};
+use crate::{
+    limits, Arguments, AsBody, Body, BodyBuf, DisplayError, MessageBuf, MessageId, MessageKind,
+    ObjectPath, Signature,
+};
 
 /// A borrowed D-Bus message.
 ///
@@ -11,6 +18,9 @@ use crate::{AsBody, Body, BodyBuf, MessageBuf, MessageKind, ObjectPath, Signatur
 pub struct Message<'a> {
     /// The type of the message.
     pub(crate) kind: MessageKind<'a>,
+    /// The object path of a signal. The object path of a method call is part
+    /// of its kind.
+    pub(crate) path: Option<&'a ObjectPath>,
     /// Serial of the emssage.
     pub(crate) serial: NonZeroU32,
     /// Flags in the message.
@@ -21,8 +31,12 @@ pub struct Message<'a> {
     pub(crate) destination: Option<&'a str>,
     /// The sender of the message.
     pub(crate) sender: Option<&'a str>,
+    /// The correlation id of the message.
+    pub(crate) correlation_id: Option<&'a str>,
     /// The body associated with the message.
     pub(crate) body: Body<'a>,
+    /// The time at which the kernel received the message.
+    pub(crate) kernel_timestamp: Option<SystemTime>,
 }
 
 impl<'a> Message<'a> {
@@ -44,12 +58,15 @@ impl<'a> Message<'a> {
     pub fn method_call(path: &'a ObjectPath, member: &'a str, serial: NonZeroU32) -> Self {
         Self {
             kind: MessageKind::MethodCall { path, member },
+            path: None,
             serial,
             flags: Flags::EMPTY,
             interface: None,
             destination: None,
             sender: None,
+            correlation_id: None,
             body: Body::empty(),
+            kernel_timestamp: None,
         }
     }
 
@@ -83,12 +100,15 @@ impl<'a> Message<'a> {
             kind: MessageKind::MethodReturn {
                 reply_serial: self.serial,
             },
+            path: None,
             serial,
             flags: Flags::EMPTY,
             interface: None,
             destination: self.sender,
             sender: self.destination,
+            correlation_id: self.correlation_id,
             body: Body::empty(),
+            kernel_timestamp: None,
         }
     }
 
@@ -111,12 +131,15 @@ impl<'a> Message<'a> {
     pub fn signal(member: &'a str, serial: NonZeroU32) -> Self {
         Self {
             kind: MessageKind::Signal { member },
+            path: None,
             serial,
             flags: Flags::EMPTY,
             interface: None,
             destination: None,
             sender: None,
+            correlation_id: None,
             body: Body::empty(),
+            kernel_timestamp: None,
         }
     }
 
@@ -150,12 +173,15 @@ impl<'a> Message<'a> {
                 error_name,
                 reply_serial: self.serial,
             },
+            path: None,
             serial,
             flags: Flags::EMPTY,
             interface: None,
             destination: self.sender,
             sender: self.destination,
+            correlation_id: self.correlation_id,
             body: Body::empty(),
+            kernel_timestamp: None,
         }
     }
 
@@ -178,11 +204,13 @@ impl<'a> Message<'a> {
     pub fn to_owned(&self) -> MessageBuf {
         MessageBuf {
             kind: self.kind.to_owned(),
+            path: self.path.map(|path| Cow::Owned(path.to_owned())),
             serial: self.serial,
             flags: self.flags,
-            interface: self.interface.map(Box::from),
-            destination: self.destination.map(Box::from),
-            sender: self.sender.map(Box::from),
+            interface: self.interface.map(|s| Cow::Owned(s.to_owned())),
+            destination: self.destination.map(|s| Cow::Owned(s.to_owned())),
+            sender: self.sender.map(|s| Cow::Owned(s.to_owned())),
+            correlation_id: self.correlation_id.map(|s| Cow::Owned(s.to_owned())),
             body: BodyBuf::from(self.body.clone()),
         }
     }
@@ -238,6 +266,54 @@ impl<'a> Message<'a> {
         }
     }
 
+    /// Clear the provided body buffer and store the given [`Arguments`] in it,
+    /// before using it as the body of the message.
+    ///
+    /// This is a convenient way to use a tuple of arguments as the body of a
+    /// message, where the body buffer is only used as scratch space. See
+    /// [`MessageBuf::with_body_from`] for an owned alternative.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the signature of the arguments is too long.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, MessageKind, ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    /// let mut body = BodyBuf::new();
+    ///
+    /// let m = send.method_call(PATH, "Hello")
+    ///     .with_arguments(&mut body, (42u32, "Hello World!"))?;
+    ///
+    /// assert!(matches!(m.kind(), MessageKind::MethodCall { .. }));
+    /// assert_eq!(m.signature(), "us");
+    ///
+    /// let mut r = m.body();
+    /// assert_eq!(r.load::<u32>()?, 42);
+    /// assert_eq!(r.read::<str>()?, "Hello World!");
+    ///
+    /// // The buffer is reused, and its previous contents are cleared.
+    /// let m = send.method_call(PATH, "Hello")
+    ///     .with_arguments(&mut body, (7u64,))?;
+    ///
+    /// assert_eq!(m.signature(), "t");
+    /// assert_eq!(m.body().load::<u64>()?, 7);
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn with_arguments<T>(self, body: &'a mut BodyBuf, arguments: T) -> Result<Self>
+    where
+        T: Arguments,
+    {
+        body.clear();
+        body.arguments(arguments)?;
+        Ok(self.with_body(&*body))
+    }
+
     /// Get a buffer to the body of the message.
     ///
     /// # Examples
@@ -293,6 +369,60 @@ impl<'a> Message<'a> {
         self.serial
     }
 
+    /// Get the identifier of the message, consisting of its serial and its
+    /// sender.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.method_call(PATH, "Hello").with_sender(":1.42");
+    /// let id = m.id();
+    /// assert_eq!(id.serial(), m.serial());
+    /// assert_eq!(id.sender(), Some(":1.42"));
+    /// ```
+    #[must_use]
+    pub fn id(&self) -> MessageId {
+        MessageId::new(self.serial, self.sender)
+    }
+
+    /// Get the identifier of the message this is a reply to, if this is a
+    /// method return or an error.
+    ///
+    /// The identifier consists of the reply serial and the destination of this
+    /// message, which is the sender of the original call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.method_call(PATH, "Hello").with_sender(":1.42");
+    /// assert_eq!(m.reply_id(), None);
+    ///
+    /// let m2 = m.error("org.freedesktop.DBus.UnknownMethod", send.next_serial());
+    /// assert_eq!(m2.reply_id(), Some(m.id()));
+    /// ```
+    #[must_use]
+    pub fn reply_id(&self) -> Option<MessageId> {
+        match self.kind {
+            MessageKind::MethodReturn { reply_serial }
+            | MessageKind::Error { reply_serial, .. } => {
+                Some(MessageId::new(reply_serial, self.destination))
+            }
+            _ => None,
+        }
+    }
+
     /// Modify the serial of the message.
     ///
     /// # Examples
@@ -383,6 +513,174 @@ impl<'a> Message<'a> {
         self.interface
     }
 
+    /// Get the object path of the message, if it is a method call or a
+    /// signal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.method_call(PATH, "Hello");
+    /// assert_eq!(m.path(), Some(PATH));
+    /// assert_eq!(m.member(), Some("Hello"));
+    /// assert_eq!(m.reply_serial(), None);
+    ///
+    /// let m2 = m.error("org.freedesktop.DBus.Error.Failed", send.next_serial());
+    /// assert_eq!(m2.path(), None);
+    /// assert_eq!(m2.member(), None);
+    /// assert_eq!(m2.reply_serial(), Some(m.serial()));
+    /// assert_eq!(m2.error_name(), Some("org.freedesktop.DBus.Error.Failed"));
+    /// ```
+    #[must_use]
+    pub fn path(&self) -> Option<&'a ObjectPath> {
+        match self.kind {
+            MessageKind::Signal { .. } => self.path,
+            kind => kind.path(),
+        }
+    }
+
+    /// Get the member of the message, if it is a method call or a signal.
+    ///
+    /// See [`Message::path`] for an example.
+    #[must_use]
+    pub fn member(&self) -> Option<&'a str> {
+        self.kind.member()
+    }
+
+    /// Get the serial of the message being replied to, if this is a method
+    /// return or an error.
+    ///
+    /// See [`Message::path`] for an example.
+    #[must_use]
+    pub fn reply_serial(&self) -> Option<NonZeroU32> {
+        self.kind.reply_serial()
+    }
+
+    /// Get the name of the error, if this is an error.
+    ///
+    /// See [`Message::path`] for an example.
+    #[must_use]
+    pub fn error_name(&self) -> Option<&'a str> {
+        self.kind.error_name()
+    }
+
+    /// Get the human-readable message of an error.
+    ///
+    /// By convention this is the first value in the body of an error, if it's
+    /// a string. This returns `None` if this isn't an error or if the body
+    /// doesn't start with a string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, ObjectPath, SendBuf};
+    ///
+    /// let mut send = SendBuf::new();
+    /// let mut body = BodyBuf::new();
+    /// body.store("No such file")?;
+    /// body.store(2u32)?;
+    ///
+    /// let m = send.method_call(ObjectPath::ROOT, "Open");
+    /// assert_eq!(m.error_message(), None);
+    ///
+    /// let m2 = m.error("org.freedesktop.DBus.Error.FileNotFound", send.next_serial());
+    /// assert_eq!(m2.error_message(), None);
+    ///
+    /// let m2 = m2.with_body(&body);
+    /// assert_eq!(m2.error_message(), Some("No such file"));
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    #[must_use]
+    pub fn error_message(&self) -> Option<&'a str> {
+        self.kind.error_name()?;
+
+        if !self.body.signature().as_bytes().starts_with(b"s") {
+            return None;
+        }
+
+        self.body.clone().read::<str>().ok()
+    }
+
+    /// Display the name of an error followed by its message, if it has one.
+    ///
+    /// This returns `None` if this isn't an error. See
+    /// [`Message::error_message`] for how the message is determined.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, ObjectPath, SendBuf};
+    ///
+    /// let mut send = SendBuf::new();
+    /// let mut body = BodyBuf::new();
+    /// body.store("No such file")?;
+    ///
+    /// let m = send.method_call(ObjectPath::ROOT, "Open");
+    /// assert!(m.display_error().is_none());
+    ///
+    /// let m2 = m.error("org.freedesktop.DBus.Error.FileNotFound", send.next_serial());
+    ///
+    /// assert_eq!(
+    ///     m2.display_error().map(|e| e.to_string()).as_deref(),
+    ///     Some("org.freedesktop.DBus.Error.FileNotFound")
+    /// );
+    ///
+    /// let m2 = m2.with_body(&body);
+    ///
+    /// assert_eq!(
+    ///     m2.display_error().map(|e| e.to_string()).as_deref(),
+    ///     Some("org.freedesktop.DBus.Error.FileNotFound: No such file")
+    /// );
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    #[must_use]
+    pub fn display_error(&self) -> Option<DisplayError<'a>> {
+        let error_name = self.kind.error_name()?;
+        Some(DisplayError::new(error_name, self.error_message()))
+    }
+
+    /// Modify the object path of the message.
+    ///
+    /// This sets the path being called for method calls and the path of the
+    /// object emitting the signal for signals. It has no effect on other kinds
+    /// of messages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{MessageKind, ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.signal("NameAcquired");
+    /// assert_eq!(m.path(), None);
+    ///
+    /// let m2 = m.with_path(PATH);
+    /// assert_eq!(m2.member(), Some("NameAcquired"));
+    /// assert_eq!(m2.path(), Some(PATH));
+    /// ```
+    #[must_use]
+    pub fn with_path(self, path: &'a ObjectPath) -> Self {
+        match self.kind {
+            MessageKind::MethodCall { member, .. } => Self {
+                kind: MessageKind::MethodCall { path, member },
+                ..self
+            },
+            MessageKind::Signal { .. } => Self {
+                path: Some(path),
+                ..self
+            },
+            _ => self,
+        }
+    }
+
     /// Modify the interface of the message.
     ///
     /// # Examples
@@ -408,6 +706,45 @@ impl<'a> Message<'a> {
         }
     }
 
+    /// Modify the interface of the message, checking that it's not longer than
+    /// [`MAX_NAME_LENGTH`].
+    ///
+    /// Messages built through [`with_interface()`] are only checked once they
+    /// are written to a [`SendBuf`].
+    ///
+    /// [`MAX_NAME_LENGTH`]: crate::limits::MAX_NAME_LENGTH
+    /// [`with_interface()`]: Self::with_interface
+    /// [`SendBuf`]: crate::SendBuf
+    ///
+    /// # Errors
+    ///
+    /// Errors with an error for which [`Error::is_limit_exceeded`] returns
+    /// `true` if the interface is too long.
+    ///
+    /// [`Error::is_limit_exceeded`]: crate::Error::is_limit_exceeded
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.method_call(PATH, "Hello").try_with_interface("org.freedesktop.DBus")?;
+    /// assert_eq!(m.interface(), Some("org.freedesktop.DBus"));
+    ///
+    /// let name = "a".repeat(256);
+    /// let error = send.method_call(PATH, "Hello").try_with_interface(&name).unwrap_err();
+    /// assert!(error.is_limit_exceeded());
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn try_with_interface(self, interface: &'a str) -> Result<Self> {
+        limits::check_name_length(interface)?;
+        Ok(self.with_interface(interface))
+    }
+
     /// Get the destination of the message.
     ///
     /// # Examples
@@ -455,6 +792,45 @@ impl<'a> Message<'a> {
         }
     }
 
+    /// Modify the destination of the message, checking that it's not longer than
+    /// [`MAX_NAME_LENGTH`].
+    ///
+    /// Messages built through [`with_destination()`] are only checked once they
+    /// are written to a [`SendBuf`].
+    ///
+    /// [`MAX_NAME_LENGTH`]: crate::limits::MAX_NAME_LENGTH
+    /// [`with_destination()`]: Self::with_destination
+    /// [`SendBuf`]: crate::SendBuf
+    ///
+    /// # Errors
+    ///
+    /// Errors with an error for which [`Error::is_limit_exceeded`] returns
+    /// `true` if the destination is too long.
+    ///
+    /// [`Error::is_limit_exceeded`]: crate::Error::is_limit_exceeded
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.method_call(PATH, "Hello").try_with_destination(":1.131")?;
+    /// assert_eq!(m.destination(), Some(":1.131"));
+    ///
+    /// let name = "a".repeat(256);
+    /// let error = send.method_call(PATH, "Hello").try_with_destination(&name).unwrap_err();
+    /// assert!(error.is_limit_exceeded());
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn try_with_destination(self, destination: &'a str) -> Result<Self> {
+        limits::check_name_length(destination)?;
+        Ok(self.with_destination(destination))
+    }
+
     /// Get the sender of the message.
     ///
     /// # Examples
@@ -502,6 +878,134 @@ impl<'a> Message<'a> {
         }
     }
 
+    /// Modify the sender of the message, checking that it's not longer than
+    /// [`MAX_NAME_LENGTH`].
+    ///
+    /// Messages built through [`with_sender()`] are only checked once they
+    /// are written to a [`SendBuf`].
+    ///
+    /// [`MAX_NAME_LENGTH`]: crate::limits::MAX_NAME_LENGTH
+    /// [`with_sender()`]: Self::with_sender
+    /// [`SendBuf`]: crate::SendBuf
+    ///
+    /// # Errors
+    ///
+    /// Errors with an error for which [`Error::is_limit_exceeded`] returns
+    /// `true` if the sender is too long.
+    ///
+    /// [`Error::is_limit_exceeded`]: crate::Error::is_limit_exceeded
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.method_call(PATH, "Hello").try_with_sender(":1.131")?;
+    /// assert_eq!(m.sender(), Some(":1.131"));
+    ///
+    /// let name = "a".repeat(256);
+    /// let error = send.method_call(PATH, "Hello").try_with_sender(&name).unwrap_err();
+    /// assert!(error.is_limit_exceeded());
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn try_with_sender(self, sender: &'a str) -> Result<Self> {
+        limits::check_name_length(sender)?;
+        Ok(self.with_sender(sender))
+    }
+
+    /// Get the correlation id of the message.
+    ///
+    /// This is carried in the [`proto::Variant::CORRELATION_ID`] header field,
+    /// and is copied into replies constructed through
+    /// [`Message::method_return`] and [`Message::error`].
+    ///
+    /// [`proto::Variant::CORRELATION_ID`]: crate::proto::Variant::CORRELATION_ID
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.method_call(PATH, "Hello");
+    /// assert_eq!(m.correlation_id(), None);
+    ///
+    /// let m2 = m.with_correlation_id("4bf92f3577b34da6");
+    /// assert_eq!(m2.correlation_id(), Some("4bf92f3577b34da6"));
+    ///
+    /// let reply = m2.method_return(send.next_serial());
+    /// assert_eq!(reply.correlation_id(), Some("4bf92f3577b34da6"));
+    /// ```
+    #[must_use]
+    pub fn correlation_id(&self) -> Option<&'a str> {
+        self.correlation_id
+    }
+
+    /// Get the time at which the kernel received the message, if it was
+    /// received by a [`Connection`] with kernel timestamps enabled through
+    /// [`ConnectionBuilder::kernel_timestamps`].
+    ///
+    /// This is not kept by messages which have been deferred or converted
+    /// into a [`MessageBuf`].
+    ///
+    /// [`Connection`]: crate::Connection
+    /// [`ConnectionBuilder::kernel_timestamps`]: crate::ConnectionBuilder::kernel_timestamps
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::SystemTime;
+    ///
+    /// use tokio_dbus::ConnectionBuilder;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = ConnectionBuilder::new()
+    ///     .kernel_timestamps(true)
+    ///     .connect()
+    ///     .await?;
+    ///
+    /// c.wait_no_deferred().await?;
+    ///
+    /// if let Some(timestamp) = c.last_message()?.kernel_timestamp() {
+    ///     let delay = SystemTime::now().duration_since(timestamp).unwrap_or_default();
+    ///     println!("Message was delivered {delay:?} ago");
+    /// }
+    /// # Ok(()) }
+    /// ```
+    #[must_use]
+    pub fn kernel_timestamp(&self) -> Option<SystemTime> {
+        self.kernel_timestamp
+    }
+
+    /// Modify the correlation id of the message.
+    ///
+    /// See [`Message::correlation_id`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::SendBuf;
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.signal("Changed").with_correlation_id("4bf92f3577b34da6");
+    /// assert_eq!(m.correlation_id(), Some("4bf92f3577b34da6"));
+    /// ```
+    #[must_use]
+    pub fn with_correlation_id(self, correlation_id: &'a str) -> Self {
+        Self {
+            correlation_id: Some(correlation_id),
+            ..self
+        }
+    }
+
     /// Get the signature of the message.
     ///
     /// # Examples
@@ -527,7 +1031,20 @@ impl<'a> Message<'a> {
         self.body.signature()
     }
 
-    pub(crate) fn message_type(&self) -> crate::proto::MessageType {
+    /// Get the type of the message as it is encoded on the wire.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::SendBuf;
+    /// use tokio_dbus::proto::MessageType;
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.signal("Hello");
+    /// assert_eq!(m.message_type(), MessageType::SIGNAL);
+    /// ```
+    pub fn message_type(&self) -> MessageType {
         match self.kind {
             MessageKind::MethodCall { .. } => MessageType::METHOD_CALL,
             MessageKind::MethodReturn { .. } => MessageType::METHOD_RETURN,
@@ -537,15 +1054,163 @@ impl<'a> Message<'a> {
     }
 }
 
+/// Display a summary of the headers of a message, suitable for log lines.
+///
+/// The alternate format (`{:#}`) displays each header on its own line.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{BodyBuf, ObjectPath, SendBuf};
+///
+/// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+///
+/// let mut send = SendBuf::new();
+/// let mut body = BodyBuf::new();
+/// body.store("se.tedro.Example")?;
+///
+/// let m = send
+///     .method_call(PATH, "RequestName")
+///     .with_interface("org.freedesktop.DBus")
+///     .with_body(&body);
+///
+/// assert_eq!(
+///     m.to_string(),
+///     "method_call serial=1 path=/org/freedesktop/DBus interface=org.freedesktop.DBus member=RequestName signature=s body=21"
+/// );
+///
+/// assert_eq!(
+///     format!("{m:#}"),
+///     "method_call\n  serial: 1\n  path: /org/freedesktop/DBus\n  interface: org.freedesktop.DBus\n  member: RequestName\n  signature: s\n  body: 21 bytes"
+/// );
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+impl Message<'static> {
+    /// Convert into an owned [`MessageBuf`], without copying header fields.
+    ///
+    /// Since the header fields of the message are `'static`, such as when
+    /// they are constants, they can be referenced by the owned message
+    /// instead of being copied. Only the body is copied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{Message, MessageBuf, ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send
+    ///     .method_call(PATH, "Hello")
+    ///     .with_destination("org.freedesktop.DBus")
+    ///     .to_static();
+    ///
+    /// let m2 = MessageBuf::method_call(PATH.into(), "Hello".into(), m.serial())
+    ///     .with_destination("org.freedesktop.DBus".into());
+    ///
+    /// assert_eq!(m, m2);
+    /// ```
+    #[inline]
+    pub fn to_static(&self) -> MessageBuf {
+        MessageBuf {
+            kind: self.kind.to_static(),
+            path: self.path.map(Cow::Borrowed),
+            serial: self.serial,
+            flags: self.flags,
+            interface: self.interface.map(Cow::Borrowed),
+            destination: self.destination.map(Cow::Borrowed),
+            sender: self.sender.map(Cow::Borrowed),
+            correlation_id: self.correlation_id.map(Cow::Borrowed),
+            body: BodyBuf::from(self.body.clone()),
+        }
+    }
+}
+
+impl fmt::Display for Message<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, path, member, error_name, reply_serial) = match self.kind {
+            MessageKind::MethodCall { path, member } => {
+                ("method_call", Some(path), Some(member), None, None)
+            }
+            MessageKind::MethodReturn { reply_serial } => {
+                ("method_return", None, None, None, Some(reply_serial))
+            }
+            MessageKind::Error {
+                error_name,
+                reply_serial,
+            } => ("error", None, None, Some(error_name), Some(reply_serial)),
+            MessageKind::Signal { member } => ("signal", self.path, Some(member), None, None),
+        };
+
+        let alternate = f.alternate();
+        f.write_str(name)?;
+
+        let mut field = |key: &str, value: &dyn fmt::Display| {
+            if alternate {
+                write!(f, "\n  {key}: {value}")
+            } else {
+                write!(f, " {key}={value}")
+            }
+        };
+
+        field("serial", &self.serial)?;
+
+        if let Some(reply_serial) = reply_serial {
+            field("reply_serial", &reply_serial)?;
+        }
+
+        if let Some(path) = path {
+            field("path", &path)?;
+        }
+
+        if let Some(interface) = self.interface {
+            field("interface", &interface)?;
+        }
+
+        if let Some(member) = member {
+            field("member", &member)?;
+        }
+
+        if let Some(error_name) = error_name {
+            field("error_name", &error_name)?;
+        }
+
+        if let Some(destination) = self.destination {
+            field("destination", &destination)?;
+        }
+
+        if let Some(sender) = self.sender {
+            field("sender", &sender)?;
+        }
+
+        if let Some(correlation_id) = self.correlation_id {
+            field("correlation_id", &correlation_id)?;
+        }
+
+        if !self.signature().is_empty() {
+            field("signature", &self.signature().as_str())?;
+        }
+
+        if alternate {
+            field("body", &format_args!("{} bytes", self.body.len()))
+        } else {
+            field("body", &self.body.len())
+        }
+    }
+}
+
 impl PartialEq<MessageBuf> for Message<'_> {
     #[inline]
     fn eq(&self, other: &MessageBuf) -> bool {
         self.kind == other.kind
+            && self.path == other.path.as_deref()
             && self.serial == other.serial
             && self.flags == other.flags
             && self.interface == other.interface.as_deref()
             && self.destination == other.destination.as_deref()
             && self.sender == other.sender.as_deref()
+            && self.correlation_id == other.correlation_id.as_deref()
             && self.body == other.body
     }
 }
@@ -1,7 +1,14 @@
+use std::borrow::Cow;
+use std::fmt;
 use std::num::NonZeroU32;
 
+use crate::error::Result;
 use crate::message::OwnedMessageKind;
-use crate::{Body, BodyBuf, Flags, Message, MessageKind, ObjectPath, Signature};
+use crate::proto::MessageType;
+use crate::{
+    Arguments, Body, BodyBuf, DisplayError, Flags, Message, MessageHeader, MessageId, MessageKind,
+    ObjectPath, Signature,
+};
 
 /// An owned D-Bus message.
 ///
@@ -11,16 +18,21 @@ use crate::{Body, BodyBuf, Flags, Message, MessageKind, ObjectPath, Signature};
 pub struct MessageBuf {
     /// The type of the message.
     pub(super) kind: OwnedMessageKind,
+    /// The object path of a signal. The object path of a method call is part
+    /// of its kind.
+    pub(super) path: Option<Cow<'static, ObjectPath>>,
     /// Serial of the emssage.
     pub(super) serial: NonZeroU32,
     /// Flags in the message.
     pub(super) flags: Flags,
     /// The interface of the message.
-    pub(super) interface: Option<Box<str>>,
+    pub(super) interface: Option<Cow<'static, str>>,
     /// The destination of the message.
-    pub(super) destination: Option<Box<str>>,
+    pub(super) destination: Option<Cow<'static, str>>,
     /// The sender of the message.
-    pub(super) sender: Option<Box<str>>,
+    pub(super) sender: Option<Cow<'static, str>>,
+    /// The correlation id of the message.
+    pub(super) correlation_id: Option<Cow<'static, str>>,
     /// The body associated with the message.
     pub(super) body: BodyBuf,
 }
@@ -42,14 +54,20 @@ impl MessageBuf {
     /// assert_eq!(m, m2);
     /// ```
     #[must_use]
-    pub fn method_call(path: Box<ObjectPath>, member: Box<str>, serial: NonZeroU32) -> Self {
+    pub fn method_call(
+        path: Cow<'static, ObjectPath>,
+        member: Cow<'static, str>,
+        serial: NonZeroU32,
+    ) -> Self {
         Self {
             kind: OwnedMessageKind::MethodCall { path, member },
+            path: None,
             serial,
             flags: Flags::EMPTY,
             interface: None,
             destination: None,
             sender: None,
+            correlation_id: None,
             body: BodyBuf::new(),
         }
     }
@@ -86,11 +104,13 @@ impl MessageBuf {
             kind: OwnedMessageKind::MethodReturn {
                 reply_serial: self.serial,
             },
+            path: None,
             serial,
             flags: Flags::EMPTY,
             interface: None,
             destination: self.sender,
             sender: self.destination,
+            correlation_id: self.correlation_id,
             body: BodyBuf::new(),
         }
     }
@@ -109,14 +129,16 @@ impl MessageBuf {
     /// assert_eq!(m, m2);
     /// ```
     #[must_use]
-    pub fn signal(member: Box<str>, serial: NonZeroU32) -> Self {
+    pub fn signal(member: Cow<'static, str>, serial: NonZeroU32) -> Self {
         Self {
             kind: OwnedMessageKind::Signal { member },
+            path: None,
             serial,
             flags: Flags::EMPTY,
             interface: None,
             destination: None,
             sender: None,
+            correlation_id: None,
             body: BodyBuf::new(),
         }
     }
@@ -145,17 +167,19 @@ impl MessageBuf {
     /// assert_eq!(m.destination(), m2.sender());
     /// ```
     #[must_use]
-    pub fn error(self, error_name: Box<str>, serial: NonZeroU32) -> Self {
+    pub fn error(self, error_name: Cow<'static, str>, serial: NonZeroU32) -> Self {
         Self {
             kind: OwnedMessageKind::Error {
                 error_name,
                 reply_serial: self.serial,
             },
+            path: None,
             serial,
             flags: Flags::EMPTY,
             interface: None,
             destination: self.sender,
             sender: self.destination,
+            correlation_id: self.correlation_id,
             body: BodyBuf::new(),
         }
     }
@@ -165,12 +189,15 @@ impl MessageBuf {
     pub fn borrow(&self) -> Message<'_> {
         Message {
             kind: self.kind.borrow(),
+            path: self.path.as_deref(),
             serial: self.serial,
             flags: self.flags,
             interface: self.interface.as_deref(),
             destination: self.destination.as_deref(),
             sender: self.sender.as_deref(),
+            correlation_id: self.correlation_id.as_deref(),
             body: self.body.as_body(),
+            kernel_timestamp: None,
         }
     }
 
@@ -223,6 +250,46 @@ impl MessageBuf {
         Self { body, ..self }
     }
 
+    /// Replace the body of the message with one constructed from the given
+    /// [`Arguments`].
+    ///
+    /// The new body uses the same endianness as the current body of the
+    /// message.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the signature of the arguments is too long.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{MessageKind, ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.method_call(PATH, "Hello")
+    ///     .to_owned()
+    ///     .with_body_from((42u32, "Hello World!"))?;
+    ///
+    /// assert!(matches!(m.kind(), MessageKind::MethodCall { .. }));
+    /// assert_eq!(m.signature(), "us");
+    ///
+    /// let mut r = m.body();
+    /// assert_eq!(r.load::<u32>()?, 42);
+    /// assert_eq!(r.read::<str>()?, "Hello World!");
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn with_body_from<T>(self, arguments: T) -> Result<Self>
+    where
+        T: Arguments,
+    {
+        let mut body = BodyBuf::with_endianness(self.body.endianness());
+        body.arguments(arguments)?;
+        Ok(Self { body, ..self })
+    }
+
     /// Get a buffer to the body of the message.
     ///
     /// # Examples
@@ -255,6 +322,100 @@ impl MessageBuf {
         self.body.as_body()
     }
 
+    /// Get mutable access to the body buffer of the message.
+    ///
+    /// This allows the body to be built incrementally in place. The signature
+    /// of the message always reflects what has been stored in the body.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let mut m = send.method_call(PATH, "Hello").to_owned();
+    ///
+    /// for n in 0..2u32 {
+    ///     m.body_mut().store(n)?;
+    /// }
+    ///
+    /// m.body_mut().store("Hello World!")?;
+    /// assert_eq!(m.signature(), "uus");
+    ///
+    /// let mut r = m.body();
+    /// assert_eq!(r.load::<u32>()?, 0);
+    /// assert_eq!(r.load::<u32>()?, 1);
+    /// assert_eq!(r.read::<str>()?, "Hello World!");
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn body_mut(&mut self) -> &mut BodyBuf {
+        &mut self.body
+    }
+
+    /// Split the message into its header and body.
+    ///
+    /// This allows the body to be taken out of the message and modified
+    /// without copying it, after which the message can be put back together
+    /// with [`MessageBuf::from_parts`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{MessageBuf, ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.method_call(PATH, "Hello")
+    ///     .to_owned()
+    ///     .with_body_from((42u32,))?;
+    ///
+    /// let (header, mut body) = m.into_parts();
+    /// body.store("Hello World!")?;
+    ///
+    /// let m = MessageBuf::from_parts(header, body);
+    /// assert_eq!(m.member(), Some("Hello"));
+    /// assert_eq!(m.signature(), "us");
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    #[must_use]
+    pub fn into_parts(self) -> (MessageHeader, BodyBuf) {
+        let header = MessageHeader {
+            kind: self.kind,
+            path: self.path,
+            serial: self.serial,
+            flags: self.flags,
+            interface: self.interface,
+            destination: self.destination,
+            sender: self.sender,
+            correlation_id: self.correlation_id,
+        };
+
+        (header, self.body)
+    }
+
+    /// Construct a message from a header and a body.
+    ///
+    /// See [`MessageBuf::into_parts`].
+    #[must_use]
+    pub fn from_parts(header: MessageHeader, body: BodyBuf) -> Self {
+        Self {
+            kind: header.kind,
+            path: header.path,
+            serial: header.serial,
+            flags: header.flags,
+            interface: header.interface,
+            destination: header.destination,
+            sender: header.sender,
+            correlation_id: header.correlation_id,
+            body,
+        }
+    }
+
     /// Get the serial of the message.
     ///
     /// # Examples
@@ -279,6 +440,42 @@ impl MessageBuf {
         self.serial
     }
 
+    /// Get the identifier of the message, consisting of its serial and its
+    /// sender.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.method_call(PATH, "Hello").with_sender(":1.42").to_owned();
+    /// assert_eq!(m.id(), m.borrow().id());
+    /// assert_eq!(m.id().sender(), Some(":1.42"));
+    /// ```
+    #[must_use]
+    pub fn id(&self) -> MessageId {
+        MessageId::new(self.serial, self.sender.as_deref())
+    }
+
+    /// Get the identifier of the message this is a reply to, if this is a
+    /// method return or an error.
+    ///
+    /// See [`Message::reply_id`].
+    #[must_use]
+    pub fn reply_id(&self) -> Option<MessageId> {
+        match self.kind {
+            OwnedMessageKind::MethodReturn { reply_serial }
+            | OwnedMessageKind::Error { reply_serial, .. } => {
+                Some(MessageId::new(reply_serial, self.destination.as_deref()))
+            }
+            _ => None,
+        }
+    }
+
     /// Modify the serial of the message.
     ///
     /// # Examples
@@ -325,6 +522,23 @@ impl MessageBuf {
         self.flags
     }
 
+    /// Get the type of the message as it is encoded on the wire.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::SendBuf;
+    /// use tokio_dbus::proto::MessageType;
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.signal("Hello").to_owned();
+    /// assert_eq!(m.message_type(), MessageType::SIGNAL);
+    /// ```
+    pub fn message_type(&self) -> MessageType {
+        self.borrow().message_type()
+    }
+
     /// Modify the flags of the message.
     ///
     /// # Examples
@@ -369,6 +583,114 @@ impl MessageBuf {
         self.interface.as_deref()
     }
 
+    /// Get the object path of the message, if it is a method call or a
+    /// signal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.method_call(PATH, "Hello").to_owned();
+    /// assert_eq!(m.path(), Some(PATH));
+    /// assert_eq!(m.member(), Some("Hello"));
+    /// assert_eq!(m.reply_serial(), None);
+    ///
+    /// let m2 = m.clone().error("org.freedesktop.DBus.Error.Failed".into(), send.next_serial());
+    /// assert_eq!(m2.path(), None);
+    /// assert_eq!(m2.member(), None);
+    /// assert_eq!(m2.reply_serial(), Some(m.serial()));
+    /// assert_eq!(m2.error_name(), Some("org.freedesktop.DBus.Error.Failed"));
+    /// ```
+    #[must_use]
+    pub fn path(&self) -> Option<&ObjectPath> {
+        self.borrow().path()
+    }
+
+    /// Get the member of the message, if it is a method call or a signal.
+    ///
+    /// See [`MessageBuf::path`] for an example.
+    #[must_use]
+    pub fn member(&self) -> Option<&str> {
+        self.kind.borrow().member()
+    }
+
+    /// Get the serial of the message being replied to, if this is a method
+    /// return or an error.
+    ///
+    /// See [`MessageBuf::path`] for an example.
+    #[must_use]
+    pub fn reply_serial(&self) -> Option<NonZeroU32> {
+        self.kind.borrow().reply_serial()
+    }
+
+    /// Get the name of the error, if this is an error.
+    ///
+    /// See [`MessageBuf::path`] for an example.
+    #[must_use]
+    pub fn error_name(&self) -> Option<&str> {
+        self.kind.borrow().error_name()
+    }
+
+    /// Get the human-readable message of an error.
+    ///
+    /// See [`Message::error_message`] for details.
+    ///
+    /// [`Message::error_message`]: crate::Message::error_message
+    #[must_use]
+    pub fn error_message(&self) -> Option<&str> {
+        self.borrow().error_message()
+    }
+
+    /// Display the name of an error followed by its message, if it has one.
+    ///
+    /// See [`Message::display_error`] for details.
+    ///
+    /// [`Message::display_error`]: crate::Message::display_error
+    #[must_use]
+    pub fn display_error(&self) -> Option<DisplayError<'_>> {
+        self.borrow().display_error()
+    }
+
+    /// Modify the object path of the message.
+    ///
+    /// This sets the path being called for method calls and the path of the
+    /// object emitting the signal for signals. It has no effect on other kinds
+    /// of messages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.signal("NameAcquired").to_owned();
+    /// let m2 = m.with_path(PATH.into());
+    /// assert_eq!(m2.member(), Some("NameAcquired"));
+    /// assert_eq!(m2.path(), Some(PATH));
+    /// ```
+    #[must_use]
+    pub fn with_path(self, path: Cow<'static, ObjectPath>) -> Self {
+        match self.kind {
+            OwnedMessageKind::MethodCall { member, .. } => Self {
+                kind: OwnedMessageKind::MethodCall { path, member },
+                ..self
+            },
+            OwnedMessageKind::Signal { .. } => Self {
+                path: Some(path),
+                ..self
+            },
+            _ => self,
+        }
+    }
+
     /// Modify the interface of the message.
     ///
     /// # Examples
@@ -387,7 +709,7 @@ impl MessageBuf {
     /// assert_eq!(m2.interface(), Some("org.freedesktop.DBus"));
     /// ```
     #[must_use]
-    pub fn with_interface(self, interface: Box<str>) -> Self {
+    pub fn with_interface(self, interface: Cow<'static, str>) -> Self {
         Self {
             interface: Some(interface),
             ..self
@@ -434,7 +756,7 @@ impl MessageBuf {
     /// assert_eq!(m2.destination(), Some(":1.131"));
     /// ```
     #[must_use]
-    pub fn with_destination(self, destination: Box<str>) -> Self {
+    pub fn with_destination(self, destination: Cow<'static, str>) -> Self {
         Self {
             destination: Some(destination),
             ..self
@@ -481,13 +803,62 @@ impl MessageBuf {
     /// assert_eq!(m2.sender(), Some(":1.131"));
     /// ```
     #[must_use]
-    pub fn with_sender(self, sender: Box<str>) -> Self {
+    pub fn with_sender(self, sender: Cow<'static, str>) -> Self {
         Self {
             sender: Some(sender),
             ..self
         }
     }
 
+    /// Get the correlation id of the message.
+    ///
+    /// See [`Message::correlation_id`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.method_call(PATH, "Hello").to_owned();
+    /// assert_eq!(m.correlation_id(), None);
+    ///
+    /// let m2 = m.with_correlation_id("4bf92f3577b34da6".into());
+    /// assert_eq!(m2.correlation_id(), Some("4bf92f3577b34da6"));
+    /// ```
+    #[must_use]
+    pub fn correlation_id(&self) -> Option<&str> {
+        self.correlation_id.as_deref()
+    }
+
+    /// Modify the correlation id of the message.
+    ///
+    /// See [`Message::correlation_id`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.method_call(PATH, "Hello").to_owned();
+    /// let m2 = m.with_correlation_id("4bf92f3577b34da6".into());
+    /// assert_eq!(m2.borrow().correlation_id(), Some("4bf92f3577b34da6"));
+    /// ```
+    #[must_use]
+    pub fn with_correlation_id(self, correlation_id: Cow<'static, str>) -> Self {
+        Self {
+            correlation_id: Some(correlation_id),
+            ..self
+        }
+    }
+
     /// Get the signature of the message.
     ///
     /// # Examples
@@ -514,6 +885,18 @@ impl MessageBuf {
     }
 }
 
+/// Display a summary of the headers of a message, suitable for log lines.
+///
+/// See the [`Display`] implementation of [`Message`] for details.
+///
+/// [`Display`]: fmt::Display
+impl fmt::Display for MessageBuf {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.borrow().fmt(f)
+    }
+}
+
 impl PartialEq<Message<'_>> for MessageBuf {
     #[inline]
     fn eq(&self, other: &Message<'_>) -> bool {
@@ -0,0 +1,97 @@
+use std::borrow::Cow;
+use std::num::NonZeroU32;
+
+use crate::message::OwnedMessageKind;
+use crate::{Flags, MessageKind, ObjectPath};
+
+/// The header of an owned message, which is everything except its body.
+///
+/// This is constructed through [`MessageBuf::into_parts`], and combined with a
+/// body into a message again through [`MessageBuf::from_parts`].
+///
+/// [`MessageBuf::into_parts`]: crate::MessageBuf::into_parts
+/// [`MessageBuf::from_parts`]: crate::MessageBuf::from_parts
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{MessageBuf, MessageKind, ObjectPath, SendBuf};
+///
+/// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+///
+/// let mut send = SendBuf::new();
+///
+/// let m = send.method_call(PATH, "Hello")
+///     .with_interface("org.freedesktop.DBus")
+///     .to_owned();
+///
+/// let (header, _) = m.into_parts();
+/// assert!(matches!(header.kind(), MessageKind::MethodCall { member: "Hello", .. }));
+/// assert_eq!(header.interface(), Some("org.freedesktop.DBus"));
+/// assert_eq!(header.serial().get(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageHeader {
+    pub(super) kind: OwnedMessageKind,
+    pub(super) path: Option<Cow<'static, ObjectPath>>,
+    pub(super) serial: NonZeroU32,
+    pub(super) flags: Flags,
+    pub(super) interface: Option<Cow<'static, str>>,
+    pub(super) destination: Option<Cow<'static, str>>,
+    pub(super) sender: Option<Cow<'static, str>>,
+    pub(super) correlation_id: Option<Cow<'static, str>>,
+}
+
+impl MessageHeader {
+    /// Get the kind of the message.
+    #[must_use]
+    pub fn kind(&self) -> MessageKind<'_> {
+        self.kind.borrow()
+    }
+
+    /// Get the object path of the message, if it is a method call or a
+    /// signal.
+    #[must_use]
+    pub fn path(&self) -> Option<&ObjectPath> {
+        match self.kind {
+            OwnedMessageKind::Signal { .. } => self.path.as_deref(),
+            ref kind => kind.borrow().path(),
+        }
+    }
+
+    /// Get the serial of the message.
+    #[must_use]
+    pub fn serial(&self) -> NonZeroU32 {
+        self.serial
+    }
+
+    /// Get the flags of the message.
+    #[must_use]
+    pub fn flags(&self) -> Flags {
+        self.flags
+    }
+
+    /// Get the interface of the message.
+    #[must_use]
+    pub fn interface(&self) -> Option<&str> {
+        self.interface.as_deref()
+    }
+
+    /// Get the destination of the message.
+    #[must_use]
+    pub fn destination(&self) -> Option<&str> {
+        self.destination.as_deref()
+    }
+
+    /// Get the sender of the message.
+    #[must_use]
+    pub fn sender(&self) -> Option<&str> {
+        self.sender.as_deref()
+    }
+
+    /// Get the correlation id of the message.
+    #[must_use]
+    pub fn correlation_id(&self) -> Option<&str> {
+        self.correlation_id.as_deref()
+    }
+}
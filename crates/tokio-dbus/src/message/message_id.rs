@@ -0,0 +1,83 @@
+use std::fmt;
+use std::num::NonZeroU32;
+
+/// A key identifying a message, consisting of its serial and its sender.
+///
+/// Serials are only unique per sender, so both are needed to identify a
+/// message on a bus. This can be used directly as a key in a [`HashMap`] or
+/// [`BTreeMap`], such as when correlating replies with the calls that caused
+/// them.
+///
+/// [`HashMap`]: std::collections::HashMap
+/// [`BTreeMap`]: std::collections::BTreeMap
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use tokio_dbus::{ObjectPath, SendBuf};
+///
+/// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+///
+/// let mut send = SendBuf::new();
+///
+/// let m = send.method_call(PATH, "Hello")
+///     .with_sender(":1.42")
+///     .with_destination("org.freedesktop.DBus");
+///
+/// let mut calls = HashMap::new();
+/// calls.insert(m.id(), "Hello");
+///
+/// let reply = m.method_return(send.next_serial());
+/// assert_eq!(reply.reply_id().and_then(|id| calls.get(&id)), Some(&"Hello"));
+/// ```
+#[derive(Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MessageId {
+    serial: NonZeroU32,
+    sender: Option<Box<str>>,
+}
+
+impl MessageId {
+    /// Construct a new message identifier from a serial and an optional
+    /// sender.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::num::NonZeroU32;
+    ///
+    /// use tokio_dbus::MessageId;
+    ///
+    /// let serial = NonZeroU32::new(1).unwrap();
+    /// let id = MessageId::new(serial, Some(":1.42"));
+    /// assert_eq!(id.serial(), serial);
+    /// assert_eq!(id.sender(), Some(":1.42"));
+    /// ```
+    pub fn new(serial: NonZeroU32, sender: Option<&str>) -> Self {
+        Self {
+            serial,
+            sender: sender.map(Box::from),
+        }
+    }
+
+    /// Get the serial of the identified message.
+    pub fn serial(&self) -> NonZeroU32 {
+        self.serial
+    }
+
+    /// Get the sender of the identified message.
+    pub fn sender(&self) -> Option<&str> {
+        self.sender.as_deref()
+    }
+}
+
+impl fmt::Debug for MessageId {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MessageId")
+            .field("serial", &self.serial)
+            .field("sender", &self.sender())
+            .finish()
+    }
+}
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::num::NonZeroU32;
 
 use crate::{message::OwnedMessageKind, ObjectPath};
@@ -33,13 +34,81 @@ pub enum MessageKind<'a> {
     },
 }
 
-impl MessageKind<'_> {
+impl<'a> MessageKind<'a> {
+    /// Get the object path of a method call.
+    ///
+    /// The object path of a signal is available through [`Message::path`].
+    ///
+    /// [`Message::path`]: crate::Message::path
+    #[inline]
+    pub fn path(&self) -> Option<&'a ObjectPath> {
+        match *self {
+            MessageKind::MethodCall { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Get the member of a method call or signal.
+    #[inline]
+    pub fn member(&self) -> Option<&'a str> {
+        match *self {
+            MessageKind::MethodCall { member, .. } | MessageKind::Signal { member, .. } => {
+                Some(member)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the serial being replied to by a method return or error.
+    #[inline]
+    pub fn reply_serial(&self) -> Option<NonZeroU32> {
+        match *self {
+            MessageKind::MethodReturn { reply_serial }
+            | MessageKind::Error { reply_serial, .. } => Some(reply_serial),
+            _ => None,
+        }
+    }
+
+    /// Get the name of an error.
+    #[inline]
+    pub fn error_name(&self) -> Option<&'a str> {
+        match *self {
+            MessageKind::Error { error_name, .. } => Some(error_name),
+            _ => None,
+        }
+    }
+
     #[inline]
     pub(crate) fn to_owned(self) -> OwnedMessageKind {
         match self {
             MessageKind::MethodCall { path, member } => OwnedMessageKind::MethodCall {
-                path: path.into(),
-                member: member.into(),
+                path: Cow::Owned(path.to_owned()),
+                member: Cow::Owned(member.to_owned()),
+            },
+            MessageKind::MethodReturn { reply_serial } => {
+                OwnedMessageKind::MethodReturn { reply_serial }
+            }
+            MessageKind::Error {
+                error_name,
+                reply_serial,
+            } => OwnedMessageKind::Error {
+                error_name: Cow::Owned(error_name.to_owned()),
+                reply_serial,
+            },
+            MessageKind::Signal { member } => OwnedMessageKind::Signal {
+                member: Cow::Owned(member.to_owned()),
+            },
+        }
+    }
+}
+
+impl MessageKind<'static> {
+    #[inline]
+    pub(crate) fn to_static(self) -> OwnedMessageKind {
+        match self {
+            MessageKind::MethodCall { path, member } => OwnedMessageKind::MethodCall {
+                path: Cow::Borrowed(path),
+                member: Cow::Borrowed(member),
             },
             MessageKind::MethodReturn { reply_serial } => {
                 OwnedMessageKind::MethodReturn { reply_serial }
@@ -48,11 +117,11 @@ impl MessageKind<'_> {
                 error_name,
                 reply_serial,
             } => OwnedMessageKind::Error {
-                error_name: error_name.into(),
+                error_name: Cow::Borrowed(error_name),
                 reply_serial,
             },
             MessageKind::Signal { member } => OwnedMessageKind::Signal {
-                member: member.into(),
+                member: Cow::Borrowed(member),
             },
         }
     }
@@ -1,11 +1,20 @@
 pub use self::message_buf::MessageBuf;
 mod message_buf;
 
+pub use self::message_header::MessageHeader;
+mod message_header;
+
 pub(crate) use self::owned_message_kind::OwnedMessageKind;
 mod owned_message_kind;
 
 pub use self::message_kind::MessageKind;
 mod message_kind;
 
+pub use self::message_id::MessageId;
+mod message_id;
+
 pub use self::message::Message;
 mod message;
+
+pub use self::display_error::DisplayError;
+mod display_error;
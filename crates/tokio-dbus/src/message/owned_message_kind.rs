@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::num::NonZeroU32;
 
 use crate::{MessageKind, ObjectPath};
@@ -9,9 +10,9 @@ pub(crate) enum OwnedMessageKind {
     /// Method call. This message type may prompt a reply.
     MethodCall {
         /// The path being called.
-        path: Box<ObjectPath>,
+        path: Cow<'static, ObjectPath>,
         /// The member being called.
-        member: Box<str>,
+        member: Cow<'static, str>,
     },
     /// Method reply with returned data.
     MethodReturn {
@@ -22,14 +23,14 @@ pub(crate) enum OwnedMessageKind {
     /// error message.
     Error {
         /// The name of the error.
-        error_name: Box<str>,
+        error_name: Cow<'static, str>,
         /// The serial this is a reply to.
         reply_serial: NonZeroU32,
     },
     /// Signal emission.
     Signal {
         /// The member being signalled.
-        member: Box<str>,
+        member: Cow<'static, str>,
     },
 }
 
@@ -22,7 +22,7 @@ use super::{validate, Iter, ObjectPathError};
 /// * Multiple '/' characters cannot occur in sequence.
 /// * A trailing '/' character is not allowed unless the path is the root path
 ///   (a single '/' character).
-#[derive(PartialEq, Eq)]
+#[derive(Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(transparent)]
 pub struct ObjectPath([u8]);
 
@@ -117,6 +117,56 @@ impl ObjectPath {
         self.0.starts_with(&other.0)
     }
 
+    /// If this path is a descendant of `parent`, get the name of the direct
+    /// child node of `parent` which leads to it.
+    ///
+    /// Paths are compared by element, so `/foo/bar` is not a descendant of
+    /// `/fo`, and a path is not a descendant of itself.
+    ///
+    /// This can be used to list the child nodes of an intermediate object
+    /// path, which has no interfaces of its own but which peers expect to be
+    /// able to introspect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::BTreeSet;
+    ///
+    /// use tokio_dbus::ObjectPath;
+    ///
+    /// const A: &ObjectPath = ObjectPath::new_const(b"/a");
+    ///
+    /// let registered = [
+    ///     ObjectPath::new_const(b"/a/b"),
+    ///     ObjectPath::new_const(b"/a/c/d"),
+    ///     ObjectPath::new_const(b"/a/c/e"),
+    ///     ObjectPath::new_const(b"/ab"),
+    ///     A,
+    /// ];
+    ///
+    /// let children = registered
+    ///     .iter()
+    ///     .filter_map(|path| path.child_of(A))
+    ///     .collect::<BTreeSet<_>>();
+    ///
+    /// assert_eq!(children.into_iter().collect::<Vec<_>>(), ["b", "c"]);
+    ///
+    /// assert_eq!(A.child_of(ObjectPath::ROOT), Some("a"));
+    /// assert_eq!(A.child_of(A), None);
+    /// ```
+    #[must_use]
+    pub fn child_of(&self, parent: &ObjectPath) -> Option<&str> {
+        let mut it = self.iter();
+
+        for segment in parent.iter() {
+            if it.next()? != segment {
+                return None;
+            }
+        }
+
+        it.next()
+    }
+
     /// Construct a new unchecked object path.
     ///
     /// # Safety
@@ -170,7 +220,7 @@ impl ToOwned for ObjectPath {
     fn to_owned(&self) -> Self::Owned {
         // SAFETY: Type ensures that it contains a valid object path during
         // construction.
-        unsafe { ObjectPathBuf::from_raw_vec(self.0.to_vec()) }
+        unsafe { ObjectPathBuf::from_raw_bytes(&self.0) }
     }
 }
 
@@ -1,4 +1,8 @@
-use std::{borrow::Borrow, ops::Deref};
+use std::borrow::{Borrow, Cow};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
 
 use super::ObjectPath;
 
@@ -17,26 +21,91 @@ use super::ObjectPath;
 /// * Multiple '/' characters cannot occur in sequence.
 /// * A trailing '/' character is not allowed unless the path is the root path
 ///   (a single '/' character).
-#[derive(Clone, PartialEq, Eq)]
-#[repr(transparent)]
-pub struct ObjectPathBuf(Vec<u8>);
+///
+/// Object paths which are at most 22 bytes long, which covers most of the ones
+/// used in practice, are stored inline without allocating.
+#[derive(Clone)]
+pub struct ObjectPathBuf(Repr);
+
+/// The number of bytes which can be stored inline, chosen so that the inline
+/// representation is no larger than the heap allocated one.
+const INLINE: usize = 22;
+
+#[derive(Clone)]
+enum Repr {
+    Inline(u8, [u8; INLINE]),
+    Heap(Box<[u8]>),
+}
 
 impl ObjectPathBuf {
-    /// Construct an owned object path from its raw underlying vector.
+    /// Construct an owned object path from its raw bytes.
     ///
     /// # Safety
     ///
-    /// The caller must ensure that the vector contains a valid object path.
+    /// The caller must ensure that the bytes are a valid object path.
+    #[inline]
+    pub(super) unsafe fn from_raw_bytes(data: &[u8]) -> Self {
+        if data.len() <= INLINE {
+            let mut inline = [0; INLINE];
+            inline[..data.len()].copy_from_slice(data);
+            Self(Repr::Inline(data.len() as u8, inline))
+        } else {
+            Self(Repr::Heap(data.into()))
+        }
+    }
+
     #[inline]
-    pub(super) unsafe fn from_raw_vec(data: Vec<u8>) -> Self {
-        Self(data)
+    fn as_bytes(&self) -> &[u8] {
+        match &self.0 {
+            Repr::Inline(len, data) => &data[..usize::from(*len)],
+            Repr::Heap(data) => data,
+        }
     }
 
     #[inline]
     fn to_object_path(&self) -> &ObjectPath {
         // SAFETY: This type ensures during construction that the object path it
         // contains is valid.
-        unsafe { ObjectPath::new_unchecked(&self.0) }
+        unsafe { ObjectPath::new_unchecked(self.as_bytes()) }
+    }
+
+    /// Test if the object path is stored inline.
+    #[cfg(test)]
+    pub(super) fn is_inline(&self) -> bool {
+        matches!(self.0, Repr::Inline(..))
+    }
+}
+
+impl Hash for ObjectPathBuf {
+    #[inline]
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        self.to_object_path().hash(state);
+    }
+}
+
+impl PartialEq for ObjectPathBuf {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for ObjectPathBuf {}
+
+impl PartialOrd for ObjectPathBuf {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ObjectPathBuf {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.to_object_path().cmp(other.to_object_path())
     }
 }
 
@@ -62,3 +131,24 @@ impl AsRef<ObjectPath> for ObjectPathBuf {
         self
     }
 }
+
+impl fmt::Debug for ObjectPathBuf {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_object_path().fmt(f)
+    }
+}
+
+impl<'a> From<&'a ObjectPath> for Cow<'a, ObjectPath> {
+    #[inline]
+    fn from(object_path: &'a ObjectPath) -> Self {
+        Cow::Borrowed(object_path)
+    }
+}
+
+impl From<ObjectPathBuf> for Cow<'_, ObjectPath> {
+    #[inline]
+    fn from(object_path: ObjectPathBuf) -> Self {
+        Cow::Owned(object_path)
+    }
+}
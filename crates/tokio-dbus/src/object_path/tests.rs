@@ -9,4 +9,68 @@ fn legal_paths() {
     assert!(ObjectPath::new(b"//").is_err());
     assert!(ObjectPath::new(b"/se/tedro").is_ok());
     assert!(ObjectPath::new(b"/se/tedro/").is_err());
+    assert!(ObjectPath::new(b"/se/tedro_dbus/_1").is_ok());
+    assert!(ObjectPath::new(b"/se/tedro-dbus").is_err());
+}
+
+#[test]
+fn hash_and_ord() {
+    use std::collections::{BTreeSet, HashSet};
+
+    let a = ObjectPath::new(b"/se/tedro").unwrap();
+    let b = ObjectPath::new(b"/se/tedro/Example").unwrap();
+
+    let hash = HashSet::from([a.to_owned(), b.to_owned()]);
+    assert!(hash.contains(a));
+    assert!(hash.contains(b));
+    assert!(!hash.contains(ObjectPath::ROOT));
+
+    let ordered = BTreeSet::from([b.to_owned(), a.to_owned()]);
+    assert!(ordered.contains(a));
+    assert_eq!(ordered.iter().map(|p| &**p).collect::<Vec<_>>(), [a, b]);
+}
+
+#[test]
+fn child_of() {
+    let root = ObjectPath::ROOT;
+    let a = ObjectPath::new(b"/a").unwrap();
+    let ab = ObjectPath::new(b"/a/b").unwrap();
+    let abc = ObjectPath::new(b"/a/b/c").unwrap();
+    let other = ObjectPath::new(b"/ab").unwrap();
+
+    assert_eq!(root.child_of(root), None);
+    assert_eq!(a.child_of(root), Some("a"));
+    assert_eq!(abc.child_of(root), Some("a"));
+    assert_eq!(ab.child_of(a), Some("b"));
+    assert_eq!(abc.child_of(a), Some("b"));
+    assert_eq!(abc.child_of(ab), Some("c"));
+    assert_eq!(other.child_of(a), None);
+    assert_eq!(a.child_of(ab), None);
+}
+
+#[test]
+fn inline_storage() {
+    use std::mem::size_of;
+
+    use crate::ObjectPathBuf;
+
+    // The inline buffer is sized to match the heap representation on 64-bit
+    // targets.
+    #[cfg(target_pointer_width = "64")]
+    assert_eq!(size_of::<ObjectPathBuf>(), size_of::<Vec<u8>>());
+
+    let short = ObjectPath::new(b"/org/freedesktop/DBus")
+        .unwrap()
+        .to_owned();
+    assert!(short.is_inline());
+    assert_eq!(&*short, ObjectPath::new(b"/org/freedesktop/DBus").unwrap());
+
+    let long = ObjectPath::new(b"/org/freedesktop/NetworkManager").unwrap();
+    let long = long.to_owned();
+    assert!(!long.is_inline());
+    assert_eq!(long.as_str(), "/org/freedesktop/NetworkManager");
+
+    assert!(short < long);
+    assert!(long.clone() == long);
+    assert!(short != long);
 }
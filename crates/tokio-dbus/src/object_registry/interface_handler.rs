@@ -0,0 +1,75 @@
+use crate::error::Result;
+use crate::org_freedesktop_dbus_properties::PropertyChanges;
+use crate::{Body, BodyBuf, Message, SendBuf};
+
+/// Handler for method calls to an interface registered in an
+/// [`ObjectRegistry`].
+///
+/// The handler is responsible for writing a reply to the method call, unless
+/// the caller doesn't expect one. The arguments of the call are read from
+/// `args`, which lets the registry detect arguments which were left unread if
+/// it's constructed with [`ObjectRegistry::with_strict_arguments`].
+///
+/// Handlers whose properties change while handling a call report them through
+/// [`InterfaceHandler::changed_properties`], and the registry emits them as
+/// `PropertiesChanged` signals once the call has been handled.
+///
+/// [`ObjectRegistry`]: crate::ObjectRegistry
+/// [`ObjectRegistry::with_strict_arguments`]: crate::ObjectRegistry::with_strict_arguments
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{Body, BodyBuf, InterfaceHandler, Message, Result, SendBuf};
+/// use tokio_dbus::org_freedesktop_dbus::UNKNOWN_METHOD;
+///
+/// struct Counter(u32);
+///
+/// impl InterfaceHandler for Counter {
+///     fn call(
+///         &mut self,
+///         message: &Message<'_>,
+///         args: &mut Body<'_>,
+///         send: &mut SendBuf,
+///         body: &mut BodyBuf,
+///     ) -> Result<()> {
+///         let m = match message.member() {
+///             Some("Increment") => {
+///                 self.0 += args.load::<u32>()?;
+///                 body.clear();
+///                 body.store(self.0)?;
+///                 message.method_return(send.next_serial()).with_body(&*body)
+///             }
+///             _ => message.error(UNKNOWN_METHOD, send.next_serial()),
+///         };
+///
+///         send.write_message(m)
+///     }
+/// }
+/// ```
+pub trait InterfaceHandler {
+    /// Handle a method call to the interface, whose arguments are read from
+    /// `args`.
+    fn call(
+        &mut self,
+        message: &Message<'_>,
+        args: &mut Body<'_>,
+        send: &mut SendBuf,
+        body: &mut BodyBuf,
+    ) -> Result<()>;
+
+    /// Record the properties of the interface which have changed since this
+    /// was last called into `changes`.
+    ///
+    /// This is called for every interface of an object after
+    /// [`ObjectRegistry::dispatch`] has handled a method call to it, and the
+    /// recorded changes are written as one `PropertiesChanged` signal per
+    /// interface. By default no properties change.
+    ///
+    /// [`ObjectRegistry::dispatch`]: crate::ObjectRegistry::dispatch
+    #[inline]
+    fn changed_properties<'a>(&'a mut self, interface: &'a str, changes: &mut PropertyChanges<'a>) {
+        _ = interface;
+        _ = changes;
+    }
+}
@@ -0,0 +1,99 @@
+use crate::{InterfaceHandler, ObjectPath};
+
+/// A statically registered interface, which is added to registries through
+/// [`ObjectRegistry::add_registered_interfaces`].
+///
+/// Registrations are submitted through the [`register_interface!`] macro from
+/// any crate linked into the final binary. This lets crates contribute
+/// interfaces to a service without wiring them up centrally, such as in a
+/// plugin architecture.
+///
+/// This requires the `inventory` feature.
+///
+/// [`ObjectRegistry::add_registered_interfaces`]: crate::ObjectRegistry::add_registered_interfaces
+/// [`register_interface!`]: crate::register_interface
+pub struct InterfaceRegistration {
+    path: &'static ObjectPath,
+    interface: &'static str,
+    handler: fn() -> Box<dyn InterfaceHandler + Send>,
+}
+
+impl InterfaceRegistration {
+    #[doc(hidden)]
+    pub const fn new(
+        path: &'static ObjectPath,
+        interface: &'static str,
+        handler: fn() -> Box<dyn InterfaceHandler + Send>,
+    ) -> Self {
+        Self {
+            path,
+            interface,
+            handler,
+        }
+    }
+
+    /// The path of the object the interface is registered on.
+    pub fn path(&self) -> &'static ObjectPath {
+        self.path
+    }
+
+    /// The name of the registered interface.
+    pub fn interface(&self) -> &'static str {
+        self.interface
+    }
+
+    /// Construct a new handler for the interface.
+    pub(crate) fn handler(&self) -> Box<dyn InterfaceHandler + Send> {
+        (self.handler)()
+    }
+}
+
+inventory::collect!(InterfaceRegistration);
+
+/// Register an interface handler which is added to registries through
+/// [`ObjectRegistry::add_registered_interfaces`].
+///
+/// The handler expression is evaluated once for every registry the interface
+/// is added to. This requires the `inventory` feature.
+///
+/// [`ObjectRegistry::add_registered_interfaces`]: crate::ObjectRegistry::add_registered_interfaces
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{Body, BodyBuf, InterfaceHandler, Message, ObjectPath, ObjectRegistry, Result, SendBuf};
+///
+/// const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Plugin");
+///
+/// struct Plugin;
+///
+/// impl InterfaceHandler for Plugin {
+///     fn call(
+///         &mut self,
+///         message: &Message<'_>,
+///         _: &mut Body<'_>,
+///         send: &mut SendBuf,
+///         _: &mut BodyBuf,
+///     ) -> Result<()> {
+///         let serial = send.next_serial();
+///         send.write_message(message.method_return(serial))
+///     }
+/// }
+///
+/// tokio_dbus::register_interface!(PATH, "se.tedro.Plugin", Plugin);
+///
+/// let mut registry = ObjectRegistry::new();
+/// registry.add_registered_interfaces()?;
+/// assert!(registry.contains_object(PATH));
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+#[macro_export]
+macro_rules! register_interface {
+    ($path:expr, $interface:expr, $handler:expr $(,)?) => {
+        $crate::__private::inventory::submit! {
+            $crate::InterfaceRegistration::new($path, $interface, || {
+                ::std::boxed::Box::new($handler)
+            })
+        }
+    };
+}
@@ -0,0 +1,15 @@
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "tokio")]
+pub(crate) use self::object_registry::call_strict;
+pub use self::object_registry::ObjectRegistry;
+mod object_registry;
+
+pub use self::interface_handler::InterfaceHandler;
+mod interface_handler;
+
+#[cfg(feature = "inventory")]
+pub use self::interface_registration::InterfaceRegistration;
+#[cfg(feature = "inventory")]
+mod interface_registration;
@@ -0,0 +1,618 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::error::Result;
+use crate::org_freedesktop_dbus::{UNKNOWN_INTERFACE, UNKNOWN_METHOD};
+use crate::org_freedesktop_dbus_object_manager::{
+    self as object_manager, GET_MANAGED_OBJECTS, INTERFACES_ADDED, INTERFACES_REMOVED,
+};
+use crate::org_freedesktop_dbus_properties::PropertyChanges;
+use crate::{
+    ty, Body, BodyBuf, Flags, Message, MessageKind, ObjectPath, ObjectPathBuf, SendBuf, Signature,
+};
+use crate::{InterfaceHandler, Storable, ToDbusError};
+
+/// The interface used to introspect objects.
+const INTROSPECTABLE: &str = "org.freedesktop.DBus.Introspectable";
+
+/// The method used to introspect objects.
+const INTROSPECT: &str = "Introspect";
+
+/// The header of introspection data.
+const INTROSPECT_DOCTYPE: &str = r#"<!DOCTYPE node PUBLIC "-//freedesktop//DTD D-BUS Object Introspection 1.0//EN"
+ "http://www.freedesktop.org/standards/dbus/1.0/introspect.dtd">
+"#;
+
+/// The signature of the interfaces of an object, with their properties.
+const INTERFACES: &Signature = Signature::new_const(b"a{sa{sv}}");
+
+/// The signature of the reply to `GetManagedObjects`.
+const MANAGED_OBJECTS: &Signature = Signature::new_const(b"a{oa{sa{sv}}}");
+
+type Interfaces = BTreeMap<Box<str>, Box<dyn InterfaceHandler + Send>>;
+
+/// A change to the objects of a registry which should be signalled.
+enum Change {
+    Added(ObjectPathBuf, Vec<Box<str>>),
+    Removed(ObjectPathBuf, Vec<Box<str>>),
+}
+
+/// A registry of objects and the interfaces they implement, which dispatches
+/// method calls to the [`InterfaceHandler`] of each interface.
+///
+/// The registry doesn't own a connection, so objects and interfaces can be
+/// added, replaced and removed at any point between calls to
+/// [`ObjectRegistry::dispatch`], such as from inside of the handler passed to
+/// [`Connection::serve_until`].
+///
+/// If the registry is constructed with an object manager through
+/// [`ObjectRegistry::with_object_manager`], it also answers `GetManagedObjects`
+/// calls to the manager and records `InterfacesAdded` and `InterfacesRemoved`
+/// signals for changes to the objects below it, which are written through
+/// [`ObjectRegistry::write_signals`]. Since the registry doesn't know about the
+/// properties of interfaces, these report empty property maps.
+///
+/// The registry also answers `org.freedesktop.DBus.Introspectable` calls to
+/// registered objects which don't handle that interface themselves, and to the
+/// intermediate paths leading up to them. The introspection data lists the
+/// names of the interfaces of the object and its direct child nodes, so that
+/// peers can walk the object tree.
+///
+/// [`Connection::serve_until`]: crate::Connection::serve_until
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{Body, BodyBuf, InterfaceHandler, Message, ObjectPath, ObjectRegistry, Result, SendBuf};
+///
+/// const MANAGER: &ObjectPath = ObjectPath::new_const(b"/se/tedro");
+/// const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Counter");
+///
+/// struct Counter(u32);
+///
+/// impl InterfaceHandler for Counter {
+///     fn call(
+///         &mut self,
+///         message: &Message<'_>,
+///         _: &mut Body<'_>,
+///         send: &mut SendBuf,
+///         body: &mut BodyBuf,
+///     ) -> Result<()> {
+///         self.0 += 1;
+///         body.clear();
+///         body.store(self.0)?;
+///         let serial = send.next_serial();
+///         send.write_message(message.method_return(serial).with_body(&*body))
+///     }
+/// }
+///
+/// let mut registry = ObjectRegistry::new().with_object_manager(MANAGER);
+/// registry.replace_interface(PATH, "se.tedro.Counter", Counter(0));
+///
+/// let mut send = SendBuf::new();
+/// let mut body = BodyBuf::new();
+///
+/// // Emits `InterfacesAdded` for the new object.
+/// assert_eq!(registry.write_signals(&mut send, &mut body)?, 1);
+///
+/// let m = send.method_call(PATH, "Increment").with_interface("se.tedro.Counter");
+/// assert!(registry.dispatch(&m, &mut send, &mut body)?);
+///
+/// let m = send.method_call(ObjectPath::new_const(b"/se/tedro/Other"), "Increment");
+/// assert!(!registry.dispatch(&m, &mut send, &mut body)?);
+///
+/// assert!(registry.remove_object(PATH));
+///
+/// // Emits `InterfacesRemoved` for the removed object.
+/// assert_eq!(registry.write_signals(&mut send, &mut body)?, 1);
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+#[derive(Default)]
+pub struct ObjectRegistry {
+    objects: BTreeMap<ObjectPathBuf, Interfaces>,
+    object_manager: Option<ObjectPathBuf>,
+    changes: Vec<Change>,
+    strict_arguments: bool,
+}
+
+impl ObjectRegistry {
+    /// Construct a new empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Implement `org.freedesktop.DBus.ObjectManager` at `path`, which manages
+    /// all registered objects below it.
+    #[must_use]
+    pub fn with_object_manager(self, path: &ObjectPath) -> Self {
+        Self {
+            object_manager: Some(path.to_owned()),
+            ..self
+        }
+    }
+
+    /// Reply to method calls whose handler leaves arguments unread with an
+    /// `org.freedesktop.DBus.Error.InvalidArgs` error.
+    ///
+    /// Anything the handler wrote to the send buffer is discarded in favor of
+    /// the error, so handlers don't have to call [`Body::ensure_consumed`]
+    /// themselves. Defaults to `false`.
+    #[must_use]
+    pub fn with_strict_arguments(self, strict: bool) -> Self {
+        Self {
+            strict_arguments: strict,
+            ..self
+        }
+    }
+
+    /// Test if an object is registered at `path`.
+    #[must_use]
+    pub fn contains_object(&self, path: &ObjectPath) -> bool {
+        self.objects.contains_key(path)
+    }
+
+    /// Register an object at `path` without any interfaces.
+    ///
+    /// Returns `false` if an object is already registered at `path`.
+    pub fn add_object(&mut self, path: &ObjectPath) -> bool {
+        if self.objects.contains_key(path) {
+            return false;
+        }
+
+        self.objects.insert(path.to_owned(), Interfaces::new());
+        true
+    }
+
+    /// Remove the object at `path` together with all of its interfaces.
+    ///
+    /// Returns `false` if no object is registered at `path`.
+    pub fn remove_object(&mut self, path: &ObjectPath) -> bool {
+        let Some(interfaces) = self.objects.remove(path) else {
+            return false;
+        };
+
+        if !interfaces.is_empty() && self.is_managed(path) {
+            let names = interfaces.into_keys().collect();
+            self.changes.push(Change::Removed(path.to_owned(), names));
+        }
+
+        true
+    }
+
+    /// Add or replace the handler of `interface` on the object at `path`,
+    /// registering the object if needed.
+    ///
+    /// Returns the handler which was replaced, if any.
+    pub fn replace_interface<H>(
+        &mut self,
+        path: &ObjectPath,
+        interface: &str,
+        handler: H,
+    ) -> Option<Box<dyn InterfaceHandler + Send>>
+    where
+        H: 'static + InterfaceHandler + Send,
+    {
+        self.insert_interface(path, interface, Box::new(handler))
+    }
+
+    /// Add the interfaces registered through [`register_interface!`],
+    /// returning the number of interfaces added. This requires the `inventory`
+    /// feature.
+    ///
+    /// [`register_interface!`]: crate::register_interface
+    ///
+    /// # Errors
+    ///
+    /// Errors without adding any interfaces if an interface is registered more
+    /// than once on the same path, or if a handler has already been added for
+    /// a registered interface.
+    #[cfg(feature = "inventory")]
+    pub fn add_registered_interfaces(&mut self) -> Result<usize> {
+        use crate::error::{Error, ErrorKind};
+
+        let registrations = inventory::iter::<crate::InterfaceRegistration>
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        for (index, registration) in registrations.iter().enumerate() {
+            let path = registration.path();
+            let interface = registration.interface();
+
+            let duplicate = registrations[..index]
+                .iter()
+                .any(|other| other.path() == path && other.interface() == interface)
+                || self
+                    .objects
+                    .get(path)
+                    .is_some_and(|interfaces| interfaces.contains_key(interface));
+
+            if duplicate {
+                let kind = ErrorKind::DuplicateInterface(path.to_string().into(), interface.into());
+                return Err(Error::new(kind));
+            }
+        }
+
+        for registration in &registrations {
+            self.insert_interface(
+                registration.path(),
+                registration.interface(),
+                registration.handler(),
+            );
+        }
+
+        Ok(registrations.len())
+    }
+
+    fn insert_interface(
+        &mut self,
+        path: &ObjectPath,
+        interface: &str,
+        handler: Box<dyn InterfaceHandler + Send>,
+    ) -> Option<Box<dyn InterfaceHandler + Send>> {
+        let managed = self.is_managed(path);
+        let interfaces = self.objects.entry(path.to_owned()).or_default();
+        let old = interfaces.insert(interface.into(), handler);
+
+        if old.is_none() && managed {
+            self.changes
+                .push(Change::Added(path.to_owned(), vec![interface.into()]));
+        }
+
+        old
+    }
+
+    /// Remove `interface` from the object at `path`, returning its handler.
+    ///
+    /// The object stays registered even if this was its last interface.
+    pub fn remove_interface(
+        &mut self,
+        path: &ObjectPath,
+        interface: &str,
+    ) -> Option<Box<dyn InterfaceHandler + Send>> {
+        let old = self.objects.get_mut(path)?.remove(interface)?;
+
+        if self.is_managed(path) {
+            self.changes
+                .push(Change::Removed(path.to_owned(), vec![interface.into()]));
+        }
+
+        Some(old)
+    }
+
+    /// Dispatch a method call to the handler of the interface it's addressed
+    /// to.
+    ///
+    /// Calls without an interface are dispatched to the only interface of the
+    /// object if it has exactly one. Calls to interfaces which the object
+    /// doesn't implement are replied to with an error, unless the call has the
+    /// [`Flags::NO_REPLY_EXPECTED`] flag set. Calls whose handler leaves
+    /// arguments unread are replied to with an error as well if the registry
+    /// is constructed with [`ObjectRegistry::with_strict_arguments`].
+    ///
+    /// `Introspect` calls are answered with introspection data listing the
+    /// interfaces and child nodes of the path, unless the object at the path
+    /// handles `org.freedesktop.DBus.Introspectable` itself. This includes
+    /// paths which only have registered objects below them.
+    ///
+    /// Returns `false` if the message isn't a method call to a registered
+    /// object, the object manager, or a path with registered objects below
+    /// it, in which case it is left for the caller to handle.
+    ///
+    /// # Errors
+    ///
+    /// Errors if writing a reply fails, or if the handler errors.
+    pub fn dispatch(
+        &mut self,
+        message: &Message<'_>,
+        send: &mut SendBuf,
+        body: &mut BodyBuf,
+    ) -> Result<bool> {
+        let MessageKind::MethodCall { path, member } = message.kind() else {
+            return Ok(false);
+        };
+
+        if self.object_manager.as_deref() == Some(path)
+            && message.interface() == Some(object_manager::INTERFACE)
+            && member == GET_MANAGED_OBJECTS
+        {
+            if no_reply_expected(message) {
+                return Ok(true);
+            }
+
+            body.clear();
+            self.store_managed_objects(path, body)?;
+            let serial = send.next_serial();
+            send.write_message(message.method_return(serial).with_body(&*body))?;
+            return Ok(true);
+        }
+
+        if member == INTROSPECT {
+            let interfaces = self.objects.get(path);
+
+            let introspect = match message.interface() {
+                Some(interface) => {
+                    interface == INTROSPECTABLE
+                        && !interfaces.is_some_and(|interfaces| interfaces.contains_key(interface))
+                }
+                None => interfaces.is_none(),
+            };
+
+            if introspect {
+                if let Some(xml) = self.introspect(path) {
+                    if no_reply_expected(message) {
+                        return Ok(true);
+                    }
+
+                    body.clear();
+                    body.store(xml.as_str())?;
+                    let serial = send.next_serial();
+                    send.write_message(message.method_return(serial).with_body(&*body))?;
+                    return Ok(true);
+                }
+            }
+        }
+
+        let Some(interfaces) = self.objects.get_mut(path) else {
+            return Ok(false);
+        };
+
+        let handler = match message.interface() {
+            Some(interface) => interfaces.get_mut(interface),
+            None if interfaces.len() == 1 => interfaces.values_mut().next(),
+            None => None,
+        };
+
+        if let Some(handler) = handler {
+            if !send.accept_call(message, body)? {
+                return Ok(true);
+            }
+
+            let result = if self.strict_arguments {
+                call_strict(message, send, body, |args, send, body| {
+                    handler.call(message, args, send, body)
+                })
+            } else {
+                handler.call(message, &mut message.body(), send, body)
+            };
+
+            if let Err(error) = result {
+                send.cancel_call(message);
+                return Err(error);
+            }
+
+            let mut changes = PropertyChanges::new();
+
+            for (interface, handler) in interfaces.iter_mut() {
+                handler.changed_properties(interface, &mut changes);
+            }
+
+            changes.write_to(send, body, path)?;
+            return Ok(true);
+        }
+
+        if no_reply_expected(message) {
+            return Ok(true);
+        }
+
+        body.clear();
+
+        let error_name = match message.interface() {
+            Some(interface) => {
+                body.store(format!("Object {path} doesn't implement {interface}").as_str())?;
+                UNKNOWN_INTERFACE
+            }
+            None => {
+                body.store(format!("No interface specified for method {member}").as_str())?;
+                UNKNOWN_METHOD
+            }
+        };
+
+        let serial = send.next_serial();
+        send.write_message(message.error(error_name, serial).with_body(&*body))?;
+        Ok(true)
+    }
+
+    /// Write `InterfacesAdded` and `InterfacesRemoved` signals for the changes
+    /// made to the managed objects since this was last called, returning the
+    /// number of signals written.
+    ///
+    /// Nothing is written unless the registry has an object manager.
+    ///
+    /// # Errors
+    ///
+    /// Errors if writing a signal fails, in which case the changes which
+    /// haven't been signalled are kept and written by the next call.
+    pub fn write_signals(&mut self, send: &mut SendBuf, body: &mut BodyBuf) -> Result<usize> {
+        let Some(manager) = &self.object_manager else {
+            return Ok(0);
+        };
+
+        let mut written = 0;
+
+        let result = self.changes.iter().try_for_each(|change| -> Result<()> {
+            write_change(manager, change, send, body)?;
+            written += 1;
+            Ok(())
+        });
+
+        self.changes.drain(..written);
+        result?;
+        Ok(written)
+    }
+
+    /// Test if changes to the object at `path` should be signalled.
+    fn is_managed(&self, path: &ObjectPath) -> bool {
+        match &self.object_manager {
+            Some(manager) => path.child_of(manager).is_some(),
+            None => false,
+        }
+    }
+
+    /// Build the introspection data of `path`, listing its interfaces and
+    /// direct child nodes.
+    ///
+    /// Returns `None` if there is neither an object at `path` nor any below
+    /// it.
+    fn introspect(&self, path: &ObjectPath) -> Option<String> {
+        let interfaces = self.objects.get(path);
+        let is_manager = self.object_manager.as_deref() == Some(path);
+
+        let children = self
+            .objects
+            .keys()
+            .map(|path| &**path)
+            .chain(self.object_manager.as_deref())
+            .filter_map(|child| child.child_of(path))
+            .collect::<BTreeSet<_>>();
+
+        if interfaces.is_none() && !is_manager && children.is_empty() {
+            return None;
+        }
+
+        let mut xml = String::from(INTROSPECT_DOCTYPE);
+        xml.push_str("<node>\n");
+        xml.push_str("  <interface name=\"org.freedesktop.DBus.Introspectable\">\n");
+        xml.push_str("    <method name=\"Introspect\">\n");
+        xml.push_str("      <arg name=\"xml_data\" type=\"s\" direction=\"out\"/>\n");
+        xml.push_str("    </method>\n");
+        xml.push_str("  </interface>\n");
+
+        if is_manager {
+            _ = writeln!(xml, "  <interface name=\"{}\"/>", object_manager::INTERFACE);
+        }
+
+        for name in interfaces
+            .into_iter()
+            .flat_map(|interfaces| interfaces.keys())
+        {
+            _ = writeln!(xml, "  <interface name=\"{name}\"/>");
+        }
+
+        for name in children {
+            _ = writeln!(xml, "  <node name=\"{name}\"/>");
+        }
+
+        xml.push_str("</node>\n");
+        Some(xml)
+    }
+
+    /// Store the objects managed by `manager` as `a{oa{sa{sv}}}`.
+    fn store_managed_objects(&self, manager: &ObjectPath, body: &mut BodyBuf) -> Result<()> {
+        body.extend_signature(MANAGED_OBJECTS)?;
+
+        let objects = body.reserve_length::<u64>();
+
+        for (path, interfaces) in &self.objects {
+            if path.child_of(manager).is_none() {
+                continue;
+            }
+
+            body.align_mut::<u64>();
+            (&**path).store_to(body);
+            store_interfaces(body, interfaces.keys().map(|name| &**name))?;
+        }
+
+        body.patch_length(objects)?;
+        Ok(())
+    }
+}
+
+/// Call `f` with the arguments of `message`, and if it returns successfully
+/// without reading all of them, discard what it wrote and reply with an
+/// `org.freedesktop.DBus.Error.InvalidArgs` error instead.
+pub(crate) fn call_strict<F>(
+    message: &Message<'_>,
+    send: &mut SendBuf,
+    body: &mut BodyBuf,
+    f: F,
+) -> Result<()>
+where
+    F: FnOnce(&mut Body<'_>, &mut SendBuf, &mut BodyBuf) -> Result<()>,
+{
+    let mut args = message.body();
+    let checkpoint = send.checkpoint();
+
+    let result = f(&mut args, send, body);
+
+    let error = match (result, args.ensure_consumed()) {
+        (Ok(()), Err(error)) => error,
+        (result, _) => {
+            send.commit(checkpoint);
+            return result;
+        }
+    };
+
+    send.rollback(checkpoint);
+
+    if no_reply_expected(message) {
+        return Ok(());
+    }
+
+    body.clear();
+    error.write_body(body)?;
+    let serial = send.next_serial();
+    send.write_message(message.error(error.error_name(), serial).with_body(&*body))
+}
+
+/// Test if the caller of `message` doesn't expect a reply.
+fn no_reply_expected(message: &Message<'_>) -> bool {
+    message.flags() & Flags::NO_REPLY_EXPECTED
+}
+
+/// Write the `InterfacesAdded` or `InterfacesRemoved` signal for `change`.
+fn write_change(
+    manager: &ObjectPath,
+    change: &Change,
+    send: &mut SendBuf,
+    body: &mut BodyBuf,
+) -> Result<()> {
+    body.clear();
+
+    let member = match change {
+        Change::Added(path, interfaces) => {
+            body.store(&**path)?;
+            body.extend_signature(INTERFACES)?;
+            store_interfaces(body, interfaces.iter().map(|name| &**name))?;
+            INTERFACES_ADDED
+        }
+        Change::Removed(path, interfaces) => {
+            body.store(&**path)?;
+            let mut array = body.store_array::<ty::Str>()?;
+
+            for name in interfaces {
+                array.store(name);
+            }
+
+            array.finish()?;
+            INTERFACES_REMOVED
+        }
+    };
+
+    let m = send
+        .signal(member)
+        .with_path(manager)
+        .with_interface(object_manager::INTERFACE)
+        .with_body(&*body);
+
+    send.write_message(m)
+}
+
+/// Store interfaces with empty property maps as `a{sa{sv}}`, without extending
+/// the signature of the body.
+fn store_interfaces<'a>(
+    body: &mut BodyBuf,
+    interfaces: impl Iterator<Item = &'a str>,
+) -> Result<()> {
+    let entries = body.reserve_length::<u64>();
+
+    for name in interfaces {
+        body.align_mut::<u64>();
+        name.store_to(body);
+        let properties = body.reserve_length::<u64>();
+        body.patch_length(properties)?;
+    }
+
+    body.patch_length(entries)?;
+    Ok(())
+}
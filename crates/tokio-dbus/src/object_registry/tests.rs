@@ -0,0 +1,704 @@
+use crate::error::{Error, ErrorKind, Result};
+use crate::org_freedesktop_dbus::{
+    INVALID_ARGS, LIMITS_EXCEEDED, UNKNOWN_INTERFACE, UNKNOWN_METHOD,
+};
+use crate::org_freedesktop_dbus_object_manager::{
+    self as object_manager, InterfaceEntries, ManagedObjects, GET_MANAGED_OBJECTS,
+    INTERFACES_ADDED, INTERFACES_REMOVED,
+};
+use crate::org_freedesktop_dbus_properties::{
+    self as properties, EmitsChangedSignal, PropertyChanges, PropertyEntries, PROPERTIES_CHANGED,
+};
+use crate::{
+    ty, Body, BodyBuf, Flags, InterfaceHandler, Message, MessageBuf, MessageKind, Middleware,
+    ObjectPath, ObjectRegistry, RecvBuf, SendBuf, Value, Variant,
+};
+
+const MANAGER: &ObjectPath = ObjectPath::new_const(b"/se/tedro");
+const A: &ObjectPath = ObjectPath::new_const(b"/se/tedro/A");
+const B: &ObjectPath = ObjectPath::new_const(b"/se/tedro/B");
+const OUTSIDE: &ObjectPath = ObjectPath::new_const(b"/other");
+
+struct Echo(u32);
+
+impl InterfaceHandler for Echo {
+    fn call(
+        &mut self,
+        message: &Message<'_>,
+        _: &mut Body<'_>,
+        send: &mut SendBuf,
+        body: &mut BodyBuf,
+    ) -> Result<()> {
+        body.clear();
+        body.store(self.0)?;
+        let serial = send.next_serial();
+        send.write_message(message.method_return(serial).with_body(&*body))
+    }
+}
+
+/// Handler which reads a single `u32` argument, and calls a method on another
+/// object before replying with it.
+struct Forward;
+
+impl InterfaceHandler for Forward {
+    fn call(
+        &mut self,
+        message: &Message<'_>,
+        args: &mut Body<'_>,
+        send: &mut SendBuf,
+        body: &mut BodyBuf,
+    ) -> Result<()> {
+        let value = args.load::<u32>()?;
+
+        let m = send.method_call(B, "Forwarded");
+        send.write_message(m)?;
+
+        body.clear();
+        body.store(value)?;
+        let serial = send.next_serial();
+        send.write_message(message.method_return(serial).with_body(&*body))
+    }
+}
+
+/// Read all messages written to `send` and pass them to `f`.
+fn read_all(send: &mut SendBuf, mut f: impl FnMut(Message<'_>) -> Result<()>) -> Result<()> {
+    let bytes = send.buf().get().to_vec();
+    let mut bytes = &bytes[..];
+    let mut recv = RecvBuf::new();
+
+    while !bytes.is_empty() {
+        let message_ref = recv.read_from(&mut bytes)?;
+        f(recv.read_message(&message_ref)?)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn dispatch() -> Result<()> {
+    let mut registry = ObjectRegistry::new();
+    assert!(registry.add_object(A));
+    assert!(!registry.add_object(A));
+    assert!(registry
+        .replace_interface(A, "se.tedro.One", Echo(1))
+        .is_none());
+
+    let mut send = SendBuf::new();
+    let mut body = BodyBuf::new();
+
+    let m = send.method_call(A, "Get").with_interface("se.tedro.One");
+    assert!(registry.dispatch(&m, &mut send, &mut body)?);
+
+    // A single interface receives calls without an interface.
+    let m = send.method_call(A, "Get");
+    assert!(registry.dispatch(&m, &mut send, &mut body)?);
+
+    assert!(registry
+        .replace_interface(A, "se.tedro.One", Echo(2))
+        .is_some());
+    assert!(registry
+        .replace_interface(A, "se.tedro.Two", Echo(3))
+        .is_none());
+
+    let m = send.method_call(A, "Get").with_interface("se.tedro.One");
+    assert!(registry.dispatch(&m, &mut send, &mut body)?);
+    let m = send.method_call(A, "Get").with_interface("se.tedro.Two");
+    assert!(registry.dispatch(&m, &mut send, &mut body)?);
+    let m = send
+        .method_call(A, "Get")
+        .with_interface("se.tedro.Missing");
+    assert!(registry.dispatch(&m, &mut send, &mut body)?);
+    let m = send.method_call(A, "Get");
+    assert!(registry.dispatch(&m, &mut send, &mut body)?);
+
+    // Unknown objects and other kinds of messages are left to the caller.
+    let m = send.method_call(B, "Get");
+    assert!(!registry.dispatch(&m, &mut send, &mut body)?);
+    let m = send.signal("Get").with_path(A);
+    assert!(!registry.dispatch(&m, &mut send, &mut body)?);
+
+    assert!(registry.remove_interface(A, "se.tedro.Two").is_some());
+    assert!(registry.remove_interface(A, "se.tedro.Two").is_none());
+    assert!(registry.contains_object(A));
+    assert!(registry.remove_object(A));
+    assert!(!registry.remove_object(A));
+
+    let m = send.method_call(A, "Get");
+    assert!(!registry.dispatch(&m, &mut send, &mut body)?);
+
+    // No signals are recorded without an object manager.
+    assert_eq!(registry.write_signals(&mut send, &mut body)?, 0);
+
+    let mut replies = Vec::new();
+
+    read_all(&mut send, |message| {
+        match message.kind() {
+            MessageKind::MethodReturn { .. } => {
+                replies.push(Ok(message.body().load::<u32>()?));
+            }
+            MessageKind::Error { error_name, .. } => {
+                replies.push(Err(error_name.to_owned()));
+            }
+            kind => panic!("unexpected message {kind:?}"),
+        }
+
+        Ok(())
+    })?;
+
+    assert_eq!(
+        replies,
+        [
+            Ok(1),
+            Ok(1),
+            Ok(2),
+            Ok(3),
+            Err(UNKNOWN_INTERFACE.to_owned()),
+            Err(UNKNOWN_METHOD.to_owned()),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn managed_objects() -> Result<()> {
+    let mut registry = ObjectRegistry::new().with_object_manager(MANAGER);
+    registry.replace_interface(A, "se.tedro.One", Echo(1));
+    registry.replace_interface(A, "se.tedro.Two", Echo(2));
+    registry.add_object(B);
+    registry.replace_interface(OUTSIDE, "se.tedro.One", Echo(3));
+
+    let mut send = SendBuf::new();
+    let mut body = BodyBuf::new();
+
+    let m = send
+        .method_call(MANAGER, GET_MANAGED_OBJECTS)
+        .with_interface(object_manager::INTERFACE);
+    assert!(registry.dispatch(&m, &mut send, &mut body)?);
+
+    let mut objects = Vec::new();
+
+    read_all(&mut send, |message| {
+        for entry in ManagedObjects::from_message(&message)? {
+            let (path, interfaces) = entry?;
+            let mut names = Vec::new();
+
+            for interface in interfaces {
+                let (name, mut properties) = interface?;
+                assert!(properties.load_entry()?.is_none());
+                names.push(name.to_owned());
+            }
+
+            objects.push((path.to_owned(), names));
+        }
+
+        Ok(())
+    })?;
+
+    assert_eq!(
+        objects,
+        [
+            (
+                A.to_owned(),
+                vec!["se.tedro.One".to_owned(), "se.tedro.Two".to_owned()]
+            ),
+            (B.to_owned(), vec![]),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn interfaces_signals() -> Result<()> {
+    let mut registry = ObjectRegistry::new().with_object_manager(MANAGER);
+    registry.replace_interface(A, "se.tedro.One", Echo(1));
+    registry.replace_interface(A, "se.tedro.One", Echo(1));
+    registry.replace_interface(A, "se.tedro.Two", Echo(2));
+    registry.replace_interface(OUTSIDE, "se.tedro.One", Echo(3));
+    registry.remove_interface(A, "se.tedro.One");
+    registry.remove_object(A);
+    registry.remove_object(OUTSIDE);
+
+    let mut send = SendBuf::new();
+    let mut body = BodyBuf::new();
+
+    assert_eq!(registry.write_signals(&mut send, &mut body)?, 4);
+    assert_eq!(registry.write_signals(&mut send, &mut body)?, 0);
+
+    let mut signals = Vec::new();
+
+    read_all(&mut send, |message| {
+        let MessageKind::Signal { member } = message.kind() else {
+            panic!("expected signal");
+        };
+
+        assert_eq!(message.path(), Some(MANAGER));
+        assert_eq!(message.interface(), Some(object_manager::INTERFACE));
+
+        let mut body = message.body();
+        let object = body.read::<ObjectPath>()?.to_owned();
+        let mut names = Vec::new();
+
+        match member {
+            INTERFACES_ADDED => {
+                for interface in InterfaceEntries::load(&mut body)? {
+                    names.push(interface?.0.to_owned());
+                }
+            }
+            INTERFACES_REMOVED => {
+                for name in body.load_array::<ty::Str>()? {
+                    names.push(name?.to_owned());
+                }
+            }
+            member => panic!("unexpected signal {member}"),
+        }
+
+        body.ensure_consumed()?;
+        signals.push((member.to_owned(), object, names));
+        Ok(())
+    })?;
+
+    assert_eq!(
+        signals,
+        [
+            (
+                INTERFACES_ADDED.to_owned(),
+                A.to_owned(),
+                vec!["se.tedro.One".to_owned()]
+            ),
+            (
+                INTERFACES_ADDED.to_owned(),
+                A.to_owned(),
+                vec!["se.tedro.Two".to_owned()]
+            ),
+            (
+                INTERFACES_REMOVED.to_owned(),
+                A.to_owned(),
+                vec!["se.tedro.One".to_owned()]
+            ),
+            (
+                INTERFACES_REMOVED.to_owned(),
+                A.to_owned(),
+                vec!["se.tedro.Two".to_owned()]
+            ),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn dispatch_no_reply_expected() -> Result<()> {
+    let mut registry = ObjectRegistry::new().with_object_manager(MANAGER);
+    registry.replace_interface(A, "se.tedro.One", Echo(1));
+    registry.replace_interface(A, "se.tedro.Two", Echo(2));
+
+    let mut send = SendBuf::new();
+    let mut body = BodyBuf::new();
+
+    let m = send
+        .method_call(A, "Get")
+        .with_interface("se.tedro.Missing")
+        .with_flags(Flags::NO_REPLY_EXPECTED);
+    assert!(registry.dispatch(&m, &mut send, &mut body)?);
+
+    let m = send
+        .method_call(A, "Get")
+        .with_flags(Flags::NO_REPLY_EXPECTED);
+    assert!(registry.dispatch(&m, &mut send, &mut body)?);
+
+    let m = send
+        .method_call(MANAGER, GET_MANAGED_OBJECTS)
+        .with_interface(object_manager::INTERFACE)
+        .with_flags(Flags::NO_REPLY_EXPECTED);
+    assert!(registry.dispatch(&m, &mut send, &mut body)?);
+
+    assert!(send.buf().is_empty());
+    Ok(())
+}
+
+#[test]
+fn properties_changed() -> Result<()> {
+    /// Handler whose `Volume` property is changed by calls to `SetVolume`.
+    struct Volume {
+        volume: u32,
+        changed: bool,
+    }
+
+    impl InterfaceHandler for Volume {
+        fn call(
+            &mut self,
+            message: &Message<'_>,
+            args: &mut Body<'_>,
+            send: &mut SendBuf,
+            _: &mut BodyBuf,
+        ) -> Result<()> {
+            for _ in 0..2 {
+                self.volume = args.load::<u32>()?;
+                self.changed = true;
+            }
+
+            let serial = send.next_serial();
+            send.write_message(message.method_return(serial))
+        }
+
+        fn changed_properties<'a>(
+            &'a mut self,
+            interface: &'a str,
+            changes: &mut PropertyChanges<'a>,
+        ) {
+            if std::mem::take(&mut self.changed) {
+                changes.changed(
+                    interface,
+                    "Volume",
+                    Variant::U32(self.volume),
+                    EmitsChangedSignal::True,
+                );
+            }
+        }
+    }
+
+    let mut registry = ObjectRegistry::new();
+    registry.replace_interface(
+        A,
+        "se.tedro.Volume",
+        Volume {
+            volume: 0,
+            changed: false,
+        },
+    );
+    registry.replace_interface(A, "se.tedro.One", Echo(1));
+
+    let mut send = SendBuf::new();
+    let mut body = BodyBuf::new();
+
+    let mut args = BodyBuf::new();
+    args.store(10u32)?;
+    args.store(20u32)?;
+
+    let m = send
+        .method_call(A, "SetVolume")
+        .with_interface("se.tedro.Volume")
+        .with_body(&args);
+    assert!(registry.dispatch(&m, &mut send, &mut body)?);
+
+    // Calls which don't change any properties don't emit signals.
+    let m = send.method_call(A, "Get").with_interface("se.tedro.One");
+    assert!(registry.dispatch(&m, &mut send, &mut body)?);
+
+    let mut signals = 0;
+    let mut messages = 0;
+
+    read_all(&mut send, |message| {
+        messages += 1;
+
+        if let MessageKind::Signal { member } = message.kind() {
+            signals += 1;
+            assert_eq!(member, PROPERTIES_CHANGED);
+            assert_eq!(message.path(), Some(A));
+            assert_eq!(message.interface(), Some(properties::INTERFACE));
+
+            let mut body = message.body();
+            assert_eq!(body.read::<str>()?, "se.tedro.Volume");
+
+            let entries = PropertyEntries::load(&mut body)?.collect::<Result<Vec<_>>>()?;
+            assert_eq!(entries, [("Volume", Value::U32(20))]);
+            assert_eq!(body.load_array::<ty::Str>()?.read()?, None);
+        }
+
+        Ok(())
+    })?;
+
+    assert_eq!((messages, signals), (3, 1));
+    Ok(())
+}
+
+#[test]
+fn max_in_flight_calls() -> Result<()> {
+    /// Handler which defers replies to `Slow`, and fails `Fail` without
+    /// replying.
+    struct Deferred(Vec<MessageBuf>);
+
+    impl InterfaceHandler for Deferred {
+        fn call(
+            &mut self,
+            message: &Message<'_>,
+            _: &mut Body<'_>,
+            _: &mut SendBuf,
+            _: &mut BodyBuf,
+        ) -> Result<()> {
+            match message.member() {
+                Some("Slow") => {
+                    self.0.push(message.to_owned());
+                    Ok(())
+                }
+                _ => Err(Error::new(ErrorKind::MissingMember)),
+            }
+        }
+    }
+
+    let mut registry = ObjectRegistry::new();
+    registry.replace_interface(A, "se.tedro.Deferred", Deferred(Vec::new()));
+
+    let mut send = SendBuf::new();
+    let mut body = BodyBuf::new();
+    send.set_max_in_flight_calls(Some(1));
+
+    let first = send.method_call(A, "Slow").to_owned();
+    assert!(registry.dispatch(&first.borrow(), &mut send, &mut body)?);
+    assert!(send.buf().is_empty());
+    assert_eq!(send.in_flight_calls(), 1);
+
+    let second = send.method_call(A, "Slow").to_owned();
+    assert!(registry.dispatch(&second.borrow(), &mut send, &mut body)?);
+    assert_eq!(send.in_flight_calls(), 1);
+
+    let mut errors = Vec::new();
+
+    read_all(&mut send, |message| {
+        errors.push((
+            message.reply_serial(),
+            message.error_name().map(str::to_owned),
+        ));
+        Ok(())
+    })?;
+
+    assert_eq!(
+        errors,
+        [(Some(second.serial()), Some(LIMITS_EXCEEDED.to_owned()))]
+    );
+
+    // Replying to the deferred call makes room for another one.
+    let serial = send.next_serial();
+    send.write_message(first.borrow().method_return(serial))?;
+    assert_eq!(send.in_flight_calls(), 0);
+
+    // Calls whose handler fails are no longer in flight.
+    let m = send.method_call(A, "Fail");
+    assert!(registry.dispatch(&m, &mut send, &mut body).is_err());
+    assert_eq!(send.in_flight_calls(), 0);
+    Ok(())
+}
+
+#[test]
+fn write_signals_error() -> Result<()> {
+    /// Middleware which rejects the second message it sees.
+    struct FailSecond(usize);
+
+    impl Middleware for FailSecond {
+        fn outgoing<'a>(&'a mut self, message: Message<'a>) -> Result<Message<'a>> {
+            self.0 += 1;
+
+            if self.0 == 2 {
+                return Err(std::io::Error::from(std::io::ErrorKind::BrokenPipe).into());
+            }
+
+            Ok(message)
+        }
+    }
+
+    let mut registry = ObjectRegistry::new().with_object_manager(MANAGER);
+    registry.replace_interface(A, "se.tedro.One", Echo(1));
+    registry.replace_interface(B, "se.tedro.One", Echo(2));
+    registry.remove_object(A);
+
+    let mut send = SendBuf::new();
+    send.push_middleware(FailSecond(0));
+    let mut body = BodyBuf::new();
+
+    assert!(registry.write_signals(&mut send, &mut body).is_err());
+    assert_eq!(registry.write_signals(&mut send, &mut body)?, 2);
+    assert_eq!(registry.write_signals(&mut send, &mut body)?, 0);
+
+    let mut signals = Vec::new();
+
+    read_all(&mut send, |message| {
+        let MessageKind::Signal { member, .. } = message.kind() else {
+            panic!("expected signal");
+        };
+
+        let object = message.body().read::<ObjectPath>()?.to_owned();
+        signals.push((member.to_owned(), object));
+        Ok(())
+    })?;
+
+    assert_eq!(
+        signals,
+        [
+            (INTERFACES_ADDED.to_owned(), A.to_owned()),
+            (INTERFACES_ADDED.to_owned(), B.to_owned()),
+            (INTERFACES_REMOVED.to_owned(), A.to_owned()),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn strict_arguments() -> Result<()> {
+    let mut args = BodyBuf::new();
+
+    for strict in [false, true] {
+        let mut send = SendBuf::new();
+        let mut body = BodyBuf::new();
+        let mut registry = ObjectRegistry::new().with_strict_arguments(strict);
+        registry.replace_interface(A, "se.tedro.Forward", Forward);
+
+        for arguments in [(1u32, None), (2u32, Some("trailing"))] {
+            args.clear();
+
+            match arguments {
+                (value, Some(trailing)) => args.arguments((value, trailing))?,
+                (value, None) => args.store(value)?,
+            }
+
+            let m = send.method_call(A, "Get").with_body(&args);
+            assert!(registry.dispatch(&m, &mut send, &mut body)?);
+        }
+
+        // Calls which don't expect a reply are discarded entirely.
+        args.clear();
+        args.arguments((3u32, "trailing"))?;
+        let m = send
+            .method_call(A, "Get")
+            .with_body(&args)
+            .with_flags(Flags::NO_REPLY_EXPECTED);
+        assert!(registry.dispatch(&m, &mut send, &mut body)?);
+
+        // Only the method calls written by calls which were kept are pending.
+        assert_eq!(send.pending_replies(), if strict { 1 } else { 3 });
+
+        let mut replies = Vec::new();
+
+        read_all(&mut send, |message| {
+            match message.kind() {
+                MessageKind::MethodCall { member, .. } => {
+                    replies.push(Ok(member.to_owned()));
+                }
+                MessageKind::MethodReturn { .. } => {
+                    replies.push(Ok(message.body().load::<u32>()?.to_string()));
+                }
+                MessageKind::Error { error_name, .. } => {
+                    replies.push(Err(error_name.to_owned()));
+                }
+                kind => panic!("unexpected message {kind:?}"),
+            }
+
+            Ok(())
+        })?;
+
+        let forwarded = || Ok("Forwarded".to_owned());
+
+        if strict {
+            assert_eq!(
+                replies,
+                [
+                    forwarded(),
+                    Ok("1".to_owned()),
+                    Err(INVALID_ARGS.to_owned())
+                ]
+            );
+        } else {
+            assert_eq!(
+                replies,
+                [
+                    forwarded(),
+                    Ok("1".to_owned()),
+                    forwarded(),
+                    Ok("2".to_owned()),
+                    forwarded(),
+                    Ok("3".to_owned()),
+                ]
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn introspect_intermediate_nodes() -> Result<()> {
+    const INTERMEDIATE: &ObjectPath = ObjectPath::new_const(b"/a");
+    const AB: &ObjectPath = ObjectPath::new_const(b"/a/b");
+    const ACD: &ObjectPath = ObjectPath::new_const(b"/a/c/d");
+
+    let mut registry = ObjectRegistry::new();
+    registry.replace_interface(AB, "se.tedro.One", Echo(1));
+    assert!(registry.add_object(ACD));
+
+    let mut send = SendBuf::new();
+    let mut body = BodyBuf::new();
+
+    for path in [ObjectPath::ROOT, INTERMEDIATE, AB] {
+        let m = send
+            .method_call(path, "Introspect")
+            .with_interface("org.freedesktop.DBus.Introspectable");
+        assert!(registry.dispatch(&m, &mut send, &mut body)?);
+    }
+
+    // Paths without objects at or below them are left to the caller.
+    let m = send
+        .method_call(OUTSIDE, "Introspect")
+        .with_interface("org.freedesktop.DBus.Introspectable");
+    assert!(!registry.dispatch(&m, &mut send, &mut body)?);
+
+    let mut replies = Vec::new();
+
+    read_all(&mut send, |message| {
+        assert!(matches!(message.kind(), MessageKind::MethodReturn { .. }));
+        replies.push(message.body().read::<str>()?.to_owned());
+        Ok(())
+    })?;
+
+    assert_eq!(replies.len(), 3);
+
+    let root = &replies[0];
+    assert!(root.contains("<node name=\"a\"/>"));
+    assert!(!root.contains("<node name=\"b\"/>"));
+
+    let intermediate = &replies[1];
+    assert!(intermediate.contains("<node name=\"b\"/>"));
+    assert!(intermediate.contains("<node name=\"c\"/>"));
+    assert!(!intermediate.contains("<node name=\"d\"/>"));
+    assert!(!intermediate.contains("se.tedro.One"));
+
+    let object = &replies[2];
+    assert!(object.contains("<interface name=\"se.tedro.One\"/>"));
+    assert!(!object.contains("<node name="));
+    Ok(())
+}
+
+#[cfg(feature = "inventory")]
+const REGISTERED: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Registered");
+
+#[cfg(feature = "inventory")]
+crate::register_interface!(REGISTERED, "se.tedro.Registered", Echo(7));
+
+#[cfg(feature = "inventory")]
+#[test]
+fn registered_interfaces() -> Result<()> {
+    let mut registry = ObjectRegistry::new();
+    assert!(registry.add_registered_interfaces()? >= 1);
+    assert!(registry.contains_object(REGISTERED));
+
+    // Registered interfaces are never silently replaced.
+    let error = registry.add_registered_interfaces().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Interface se.tedro.Registered on /se/tedro/Registered is already registered"
+    );
+
+    let mut send = SendBuf::new();
+    let mut body = BodyBuf::new();
+
+    let m = send
+        .method_call(REGISTERED, "Get")
+        .with_interface("se.tedro.Registered");
+    assert!(registry.dispatch(&m, &mut send, &mut body)?);
+
+    read_all(&mut send, |m| {
+        assert_eq!(m.body().load::<u32>()?, 7);
+        Ok(())
+    })
+}
@@ -1,6 +1,7 @@
 //! Types associated with the `org.freedesktop.DBus` interface.
 
-use crate::ObjectPath;
+use crate::error::Result;
+use crate::{Message, MessageKind, ObjectPath};
 
 /// Well known destination name.
 pub const DESTINATION: &str = "org.freedesktop.DBus";
@@ -11,6 +12,53 @@ pub const INTERFACE: &str = "org.freedesktop.DBus";
 /// Well known D-Bus path.
 pub const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
 
+/// Interface used by debugging tools to monitor all messages on the bus.
+pub const MONITORING_INTERFACE: &str = "org.freedesktop.DBus.Monitoring";
+
+/// Interface of messages which are synthesized locally by the connection and
+/// never sent over the wire.
+pub const LOCAL_INTERFACE: &str = "org.freedesktop.DBus.Local";
+
+/// Path of messages which are synthesized locally by the connection and never
+/// sent over the wire.
+pub const LOCAL_PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus/Local");
+
+/// Member of the signal on [`LOCAL_INTERFACE`] which is received once the
+/// connection has been lost.
+pub const DISCONNECTED: &str = "Disconnected";
+
+/// Member of the signal which is sent to a connection when it has lost
+/// ownership of a name, such as when it has been replaced by another
+/// connection.
+pub const NAME_LOST: &str = "NameLost";
+
+/// Member of the signal which is sent to a connection when it has gained
+/// ownership of a name.
+pub const NAME_ACQUIRED: &str = "NameAcquired";
+
+/// Member of the signal which is broadcast by the bus when the owner of a name
+/// changes.
+pub const NAME_OWNER_CHANGED: &str = "NameOwnerChanged";
+
+/// The generic error name, used when no more specific error name applies.
+pub const FAILED: &str = "org.freedesktop.DBus.Error.Failed";
+
+/// Error returned when the arguments of a method call are invalid.
+pub const INVALID_ARGS: &str = "org.freedesktop.DBus.Error.InvalidArgs";
+
+/// Error returned when calling a method on an interface which the object
+/// doesn't implement.
+pub const UNKNOWN_INTERFACE: &str = "org.freedesktop.DBus.Error.UnknownInterface";
+
+/// Error returned when calling a method which doesn't exist.
+pub const UNKNOWN_METHOD: &str = "org.freedesktop.DBus.Error.UnknownMethod";
+
+/// Error returned when a service is handling too many method calls, see
+/// [`SendBuf::set_max_in_flight_calls`].
+///
+/// [`SendBuf::set_max_in_flight_calls`]: crate::SendBuf::set_max_in_flight_calls
+pub const LIMITS_EXCEEDED: &str = "org.freedesktop.DBus.Error.LimitsExceeded";
+
 raw_set! {
     /// The flags to a `RequestName` call.
     #[repr(u32)]
@@ -66,3 +114,105 @@ raw_enum! {
         ALREADY_OWNER = 4,
     }
 }
+
+/// A signal emitted by the message bus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BusSignal<'a> {
+    /// The owner of a name has changed.
+    NameOwnerChanged {
+        /// The name whose owner changed.
+        name: &'a str,
+        /// The unique name of the previous owner, which is empty if the name
+        /// had no owner.
+        old_owner: &'a str,
+        /// The unique name of the new owner, which is empty if the name no
+        /// longer has an owner.
+        new_owner: &'a str,
+    },
+    /// This connection has gained ownership of a name.
+    NameAcquired {
+        /// The name which was acquired.
+        name: &'a str,
+    },
+    /// This connection has lost ownership of a name.
+    NameLost {
+        /// The name which was lost.
+        name: &'a str,
+    },
+}
+
+impl<'a> BusSignal<'a> {
+    /// Decode a bus signal from the given message.
+    ///
+    /// Returns `None` if the message is not a signal sent by the bus on the bus
+    /// interface, so signals which are spoofed by other peers are ignored.
+    /// Decoding borrows from the message and doesn't allocate.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the body of a bus signal doesn't match its expected
+    /// signature. Since this is out of the control of the receiver, callers
+    /// should typically ignore such signals rather than propagate the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, SendBuf};
+    /// use tokio_dbus::org_freedesktop_dbus::{BusSignal, DESTINATION, INTERFACE, NAME_OWNER_CHANGED};
+    ///
+    /// let mut send = SendBuf::new();
+    /// let mut body = BodyBuf::new();
+    /// body.arguments(("se.tedro.Example", "", ":1.42"))?;
+    ///
+    /// let m = send.signal(NAME_OWNER_CHANGED)
+    ///     .with_interface(INTERFACE)
+    ///     .with_sender(DESTINATION)
+    ///     .with_body(&body);
+    ///
+    /// assert_eq!(
+    ///     BusSignal::from_message(&m)?,
+    ///     Some(BusSignal::NameOwnerChanged {
+    ///         name: "se.tedro.Example",
+    ///         old_owner: "",
+    ///         new_owner: ":1.42",
+    ///     })
+    /// );
+    ///
+    /// let m = m.with_sender(":1.7");
+    /// assert_eq!(BusSignal::from_message(&m)?, None);
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn from_message(message: &Message<'a>) -> Result<Option<Self>> {
+        let MessageKind::Signal { member, .. } = message.kind() else {
+            return Ok(None);
+        };
+
+        if message.sender() != Some(DESTINATION) || message.interface() != Some(INTERFACE) {
+            return Ok(None);
+        }
+
+        let body = message.body();
+
+        let signal = match member {
+            NAME_OWNER_CHANGED => {
+                let (name, old_owner, new_owner) = body.decode()?;
+
+                BusSignal::NameOwnerChanged {
+                    name,
+                    old_owner,
+                    new_owner,
+                }
+            }
+            NAME_ACQUIRED => BusSignal::NameAcquired {
+                name: body.decode()?,
+            },
+            NAME_LOST => BusSignal::NameLost {
+                name: body.decode()?,
+            },
+            _ => return Ok(None),
+        };
+
+        Ok(Some(signal))
+    }
+}
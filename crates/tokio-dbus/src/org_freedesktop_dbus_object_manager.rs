@@ -0,0 +1,184 @@
+//! Types associated with the `org.freedesktop.DBus.ObjectManager` interface.
+//!
+//! Replies to [`GET_MANAGED_OBJECTS`] contain the properties of every interface
+//! of every object managed by a service, which can be decoded one entry at a
+//! time through [`ManagedObjects`].
+
+use crate::body::read_dict;
+use crate::error::{Error, ErrorKind, Result};
+use crate::org_freedesktop_dbus_properties::PropertyEntries;
+use crate::{Body, Message, ObjectPath, Signature};
+
+/// Well known interface name.
+pub const INTERFACE: &str = "org.freedesktop.DBus.ObjectManager";
+
+/// The name of the method which gets all managed objects.
+pub const GET_MANAGED_OBJECTS: &str = "GetManagedObjects";
+
+/// The name of the signal emitted when interfaces are added to an object.
+pub const INTERFACES_ADDED: &str = "InterfacesAdded";
+
+/// The name of the signal emitted when interfaces are removed from an object.
+pub const INTERFACES_REMOVED: &str = "InterfacesRemoved";
+
+/// The signature of the reply to [`GET_MANAGED_OBJECTS`].
+const MANAGED_OBJECTS: &Signature = Signature::new_const(b"a{oa{sa{sv}}}");
+
+/// Streaming decoder for the reply to [`GET_MANAGED_OBJECTS`] with the
+/// signature `a{oa{sa{sv}}}`.
+///
+/// Each entry is an object path and the [`InterfaceEntries`] of that object,
+/// which can be decoded further or skipped by dropping them. This is also an
+/// iterator over the entries, which stops after the first error.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{BodyBuf, ObjectPath, Signature, Value};
+/// use tokio_dbus::org_freedesktop_dbus_object_manager::ManagedObjects;
+///
+/// let mut buf = BodyBuf::new();
+///
+/// buf.store_parsed(
+///     Signature::new("a{oa{sa{sv}}}")?,
+///     &["{/a: {se.tedro.A: {Name: s:a}}, /b: {se.tedro.A: {Name: s:b}, se.tedro.B: {}}}"],
+/// )?;
+///
+/// let mut body = buf.as_body();
+/// let mut objects = ManagedObjects::load(&mut body)?;
+///
+/// let Some((path, _)) = objects.load_entry()? else {
+///     panic!("expected object");
+/// };
+///
+/// assert_eq!(path, ObjectPath::new("/a")?);
+///
+/// let Some((path, mut interfaces)) = objects.load_entry()? else {
+///     panic!("expected object");
+/// };
+///
+/// assert_eq!(path, ObjectPath::new("/b")?);
+///
+/// let (interface, mut properties) = interfaces.load_entry()?.expect("interface");
+/// assert_eq!(interface, "se.tedro.A");
+/// assert_eq!(properties.load_entry()?, Some(("Name", Value::String(String::from("b")))));
+/// assert_eq!(properties.load_entry()?, None);
+///
+/// let (interface, mut properties) = interfaces.load_entry()?.expect("interface");
+/// assert_eq!(interface, "se.tedro.B");
+/// assert_eq!(properties.load_entry()?, None);
+///
+/// assert!(interfaces.load_entry()?.is_none());
+/// assert!(objects.load_entry()?.is_none());
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+pub struct ManagedObjects<'de> {
+    buf: Body<'de>,
+}
+
+impl<'de> ManagedObjects<'de> {
+    /// Start decoding the managed objects in the body of a message, such as a
+    /// reply to [`GET_MANAGED_OBJECTS`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if the signature of the message is not `a{oa{sa{sv}}}`.
+    pub fn from_message(message: &Message<'de>) -> Result<Self> {
+        if message.signature() != MANAGED_OBJECTS {
+            return Err(Error::new(ErrorKind::SignatureMismatch(
+                MANAGED_OBJECTS.into(),
+                message.signature().into(),
+            )));
+        }
+
+        Self::load(&mut message.body())
+    }
+
+    /// Start decoding managed objects `a{oa{sa{sv}}}` at the current position
+    /// of the body, advancing the body past all of them.
+    pub fn load(body: &mut Body<'de>) -> Result<Self> {
+        Ok(Self {
+            buf: read_dict(body)?,
+        })
+    }
+
+    /// Load the next object.
+    pub fn load_entry(&mut self) -> Result<Option<(&'de ObjectPath, InterfaceEntries<'de>)>> {
+        if self.buf.is_empty() {
+            return Ok(None);
+        }
+
+        self.buf.align::<u64>()?;
+        let path = self.buf.read::<ObjectPath>()?;
+        let interfaces = InterfaceEntries::load(&mut self.buf)?;
+        Ok(Some((path, interfaces)))
+    }
+}
+
+impl<'de> Iterator for ManagedObjects<'de> {
+    type Item = Result<(&'de ObjectPath, InterfaceEntries<'de>)>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.load_entry() {
+            Ok(entry) => Ok(entry).transpose(),
+            Err(error) => {
+                // NB: Make sure that the iterator is fused after an error.
+                self.buf = Body::empty();
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// Streaming decoder for the interfaces of an object `a{sa{sv}}`.
+///
+/// Each entry is the name of an interface and the [`PropertyEntries`] of that
+/// interface. This is also an iterator over the entries, which stops after the
+/// first error.
+///
+/// See [`ManagedObjects`].
+pub struct InterfaceEntries<'de> {
+    buf: Body<'de>,
+}
+
+impl<'de> InterfaceEntries<'de> {
+    /// Start decoding interfaces `a{sa{sv}}` at the current position of the
+    /// body, advancing the body past all of them.
+    ///
+    /// This is also the layout of the second argument of the
+    /// [`INTERFACES_ADDED`] signal.
+    pub fn load(body: &mut Body<'de>) -> Result<Self> {
+        Ok(Self {
+            buf: read_dict(body)?,
+        })
+    }
+
+    /// Load the next interface.
+    pub fn load_entry(&mut self) -> Result<Option<(&'de str, PropertyEntries<'de>)>> {
+        if self.buf.is_empty() {
+            return Ok(None);
+        }
+
+        self.buf.align::<u64>()?;
+        let interface = self.buf.read::<str>()?;
+        let properties = PropertyEntries::load(&mut self.buf)?;
+        Ok(Some((interface, properties)))
+    }
+}
+
+impl<'de> Iterator for InterfaceEntries<'de> {
+    type Item = Result<(&'de str, PropertyEntries<'de>)>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.load_entry() {
+            Ok(entry) => Ok(entry).transpose(),
+            Err(error) => {
+                // NB: Make sure that the iterator is fused after an error.
+                self.buf = Body::empty();
+                Some(Err(error))
+            }
+        }
+    }
+}
@@ -0,0 +1,471 @@
+//! Types associated with the `org.freedesktop.DBus.Properties` interface.
+//!
+//! Services are responsible for tracking which of their properties have
+//! changed. The [`PropertyChanges`] helper collects such changes and coalesces
+//! them into as few `PropertiesChanged` signals as possible, according to the
+//! emission policy of each property. Interfaces served through an
+//! [`ObjectRegistry`] report their changes through
+//! [`InterfaceHandler::changed_properties`], and the registry emits the signals
+//! once a method call has been handled.
+//!
+//! [`ObjectRegistry`]: crate::ObjectRegistry
+//! [`InterfaceHandler::changed_properties`]: crate::InterfaceHandler::changed_properties
+//!
+//! Services coordinating the rollout of features can declare the version of
+//! their interfaces through the conventional [`VERSION`] property with
+//! [`InterfaceVersions`], which clients read as an [`InterfaceVersion`].
+//!
+//! Large replies to [`GET_ALL`] can be decoded one property at a time through
+//! [`PropertyEntries`].
+
+use std::fmt;
+
+use crate::body::read_dict;
+use crate::error::{Error, ErrorKind, Result};
+use crate::{ty, Body, BodyBuf, FromBody, Message, MessageKind, ObjectPath, SendBuf, Signature};
+use crate::{Value, Variant};
+
+/// Well known interface name.
+pub const INTERFACE: &str = "org.freedesktop.DBus.Properties";
+
+/// The name of the signal emitted when properties change.
+pub const PROPERTIES_CHANGED: &str = "PropertiesChanged";
+
+/// The name of the method which gets all properties of an interface.
+pub const GET_ALL: &str = "GetAll";
+
+/// The signature of a map of properties.
+const PROPERTIES: &Signature = Signature::new_const(b"a{sv}");
+
+/// The conventional name of the property holding the version of an interface.
+pub const VERSION: &str = "Version";
+
+/// Error returned when reading a property which doesn't exist.
+pub const UNKNOWN_PROPERTY: &str = "org.freedesktop.DBus.Error.UnknownProperty";
+
+/// The emission policy of a property, as specified through the
+/// `org.freedesktop.DBus.Property.EmitsChangedSignal` annotation.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EmitsChangedSignal {
+    /// The signal is emitted with the new value of the property. This is the
+    /// default.
+    #[default]
+    True,
+    /// The signal is emitted, but only lists the property as invalidated
+    /// without including its value.
+    Invalidates,
+    /// The property never changes, so no signal is emitted.
+    Const,
+    /// No signal is emitted when the property changes.
+    False,
+}
+
+/// Changes to the properties of a single interface.
+#[derive(Debug)]
+struct InterfaceChanges<'a> {
+    interface: &'a str,
+    changed: Vec<(&'a str, Variant<'a>)>,
+    invalidated: Vec<&'a str>,
+}
+
+/// Tracker for property changes of an object which coalesces them into
+/// `PropertiesChanged` signals.
+///
+/// Changes are recorded with [`PropertyChanges::changed`] as they happen, and
+/// written as one signal per interface with [`PropertyChanges::write_to`],
+/// typically once a method call has been fully handled. Changing the same
+/// property multiple times only emits its latest value.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{BodyBuf, ObjectPath, SendBuf, Variant};
+/// use tokio_dbus::org_freedesktop_dbus_properties::{EmitsChangedSignal, PropertyChanges};
+///
+/// const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Example");
+///
+/// let mut send = SendBuf::new();
+/// let mut body = BodyBuf::new();
+///
+/// let mut changes = PropertyChanges::new();
+/// changes.changed("se.tedro.Example", "Volume", Variant::U32(10), EmitsChangedSignal::True);
+/// changes.changed("se.tedro.Example", "Volume", Variant::U32(20), EmitsChangedSignal::True);
+/// changes.changed("se.tedro.Example", "Tracks", Variant::U32(0), EmitsChangedSignal::Invalidates);
+/// changes.changed("se.tedro.Example", "Name", Variant::String("x"), EmitsChangedSignal::Const);
+///
+/// assert_eq!(changes.write_to(&mut send, &mut body, PATH)?, 1);
+/// assert!(changes.is_empty());
+///
+/// // The body of the last signal written is left in the scratch buffer.
+/// assert_eq!(body.signature(), "sa{sv}as");
+/// assert_eq!(body.as_body().read::<str>()?, "se.tedro.Example");
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct PropertyChanges<'a> {
+    interfaces: Vec<InterfaceChanges<'a>>,
+}
+
+impl<'a> PropertyChanges<'a> {
+    /// Construct a new empty change tracker.
+    pub fn new() -> Self {
+        Self {
+            interfaces: Vec::new(),
+        }
+    }
+
+    /// Test if there are no changes which would cause a signal to be emitted.
+    pub fn is_empty(&self) -> bool {
+        self.interfaces.is_empty()
+    }
+
+    /// Record that the property `name` of `interface` has changed to `value`.
+    ///
+    /// Depending on `emits`, the value replaces any previously recorded value
+    /// of the same property, the property is marked as invalidated, or the
+    /// change is ignored.
+    pub fn changed(
+        &mut self,
+        interface: &'a str,
+        name: &'a str,
+        value: Variant<'a>,
+        emits: EmitsChangedSignal,
+    ) {
+        if matches!(emits, EmitsChangedSignal::Const | EmitsChangedSignal::False) {
+            return;
+        }
+
+        let changes = match self
+            .interfaces
+            .iter()
+            .position(|c| c.interface == interface)
+        {
+            Some(index) => &mut self.interfaces[index],
+            None => {
+                self.interfaces.push(InterfaceChanges {
+                    interface,
+                    changed: Vec::new(),
+                    invalidated: Vec::new(),
+                });
+
+                let last = self.interfaces.len() - 1;
+                &mut self.interfaces[last]
+            }
+        };
+
+        changes.changed.retain(|&(n, _)| n != name);
+        changes.invalidated.retain(|&n| n != name);
+
+        if emits == EmitsChangedSignal::Invalidates {
+            changes.invalidated.push(name);
+        } else {
+            changes.changed.push((name, value));
+        }
+    }
+
+    /// Write one `PropertiesChanged` signal for each interface with changed
+    /// properties to `send`, using `body` as a scratch buffer.
+    ///
+    /// Returns the number of signals written. The tracker is empty afterwards.
+    pub fn write_to(
+        &mut self,
+        send: &mut SendBuf,
+        body: &mut BodyBuf,
+        path: &ObjectPath,
+    ) -> Result<usize> {
+        let mut count = 0;
+
+        for changes in self.interfaces.drain(..) {
+            body.clear();
+            body.store(changes.interface)?;
+
+            let mut changed = body.store_dict::<ty::Str, ty::Variant>()?;
+
+            for (name, value) in changes.changed {
+                changed.store(name, value);
+            }
+
+            changed.finish()?;
+
+            let mut invalidated = body.store_array::<ty::Str>()?;
+
+            for name in changes.invalidated {
+                invalidated.store(name);
+            }
+
+            invalidated.finish()?;
+
+            let m = send
+                .signal(PROPERTIES_CHANGED)
+                .with_path(path)
+                .with_interface(INTERFACE)
+                .with_body(&*body);
+
+            send.write_message(m)?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+}
+
+/// The version of an interface.
+///
+/// Versions are assumed to be backwards compatible, so an interface supports
+/// every feature introduced in its version or any earlier one.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::Variant;
+/// use tokio_dbus::org_freedesktop_dbus_properties::InterfaceVersion;
+///
+/// let version = InterfaceVersion::from_variant(&Variant::U32(3))?;
+/// assert!(version.supports(2));
+/// assert!(!version.supports(4));
+///
+/// // Versions provided through annotations are parsed from strings.
+/// assert_eq!(InterfaceVersion::parse("3")?, version);
+/// assert!(InterfaceVersion::parse("three").is_err());
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InterfaceVersion(u32);
+
+impl InterfaceVersion {
+    /// Construct a new interface version.
+    #[inline]
+    pub const fn new(version: u32) -> Self {
+        Self(version)
+    }
+
+    /// Get the version number.
+    #[inline]
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+
+    /// Test if the interface supports features introduced in `required`.
+    #[inline]
+    pub const fn supports(self, required: u32) -> bool {
+        self.0 >= required
+    }
+
+    /// Parse a version from a string, such as the value of an annotation.
+    pub fn parse(string: &str) -> Result<Self> {
+        match string.trim().parse() {
+            Ok(version) => Ok(Self(version)),
+            Err(..) => Err(Error::new(ErrorKind::InvalidInterfaceVersion(
+                string.into(),
+            ))),
+        }
+    }
+
+    /// Convert the value of a version property into a version.
+    ///
+    /// Unsigned integers and strings containing one are supported.
+    pub fn from_variant(value: &Variant<'_>) -> Result<Self> {
+        match *value {
+            Variant::U8(version) => Ok(Self(version.into())),
+            Variant::U16(version) => Ok(Self(version.into())),
+            Variant::String(string) => Self::parse(string),
+            value => Ok(Self(u32::try_from(value)?)),
+        }
+    }
+}
+
+impl fmt::Display for InterfaceVersion {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// The declared versions of the interfaces of an object.
+///
+/// This answers calls to `org.freedesktop.DBus.Properties.Get` for the
+/// [`VERSION`] property of declared interfaces through
+/// [`InterfaceVersions::reply`].
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{BodyBuf, Message, ObjectPath, RecvBuf, SendBuf, Variant};
+/// use tokio_dbus::org_freedesktop_dbus_properties::{self as properties, InterfaceVersion, InterfaceVersions};
+///
+/// const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Example");
+///
+/// let mut versions = InterfaceVersions::new();
+/// versions.declare("se.tedro.Example", InterfaceVersion::new(2));
+///
+/// let mut send = SendBuf::new();
+/// let mut body = BodyBuf::new();
+///
+/// body.arguments(("se.tedro.Example", properties::VERSION))?;
+///
+/// let call = send
+///     .method_call(PATH, "Get")
+///     .with_interface(properties::INTERFACE)
+///     .with_body(&body);
+///
+/// let mut reply_body = BodyBuf::new();
+/// let reply = versions.reply(&call, &mut send, &mut reply_body)?.expect("version call");
+///
+/// assert_eq!(reply.body().decode::<Variant<'_>>()?, Variant::U32(2));
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct InterfaceVersions {
+    versions: Vec<(Box<str>, InterfaceVersion)>,
+}
+
+impl InterfaceVersions {
+    /// Construct a new empty set of versions.
+    pub fn new() -> Self {
+        Self {
+            versions: Vec::new(),
+        }
+    }
+
+    /// Declare the version of `interface`, replacing any previously declared
+    /// version.
+    pub fn declare(&mut self, interface: &str, version: InterfaceVersion) {
+        match self.versions.iter_mut().find(|(i, _)| **i == *interface) {
+            Some((_, existing)) => *existing = version,
+            None => self.versions.push((interface.into(), version)),
+        }
+    }
+
+    /// Get the declared version of `interface`.
+    pub fn get(&self, interface: &str) -> Option<InterfaceVersion> {
+        self.versions
+            .iter()
+            .find(|(i, _)| **i == *interface)
+            .map(|&(_, version)| version)
+    }
+
+    /// Construct the reply to `message` if it reads the [`VERSION`] property
+    /// of a declared interface, using `body` to store the reply.
+    ///
+    /// Returns `None` if `message` is not such a call, in which case it should
+    /// be handled like any other message.
+    pub fn reply<'a>(
+        &self,
+        message: &Message<'a>,
+        send: &mut SendBuf,
+        body: &'a mut BodyBuf,
+    ) -> Result<Option<Message<'a>>> {
+        let MessageKind::MethodCall { member: "Get", .. } = message.kind() else {
+            return Ok(None);
+        };
+
+        if message.interface() != Some(INTERFACE) {
+            return Ok(None);
+        }
+
+        let mut args = message.body();
+
+        if args.signature() != "ss" {
+            return Ok(None);
+        }
+
+        let interface = args.read::<str>()?;
+        let property = args.read::<str>()?;
+
+        let Some(version) = self.get(interface).filter(|_| property == VERSION) else {
+            return Ok(None);
+        };
+
+        body.clear();
+        body.store(Variant::U32(version.get()))?;
+        Ok(Some(
+            message.method_return(send.next_serial()).with_body(body),
+        ))
+    }
+}
+
+/// Streaming decoder for a map of properties `a{sv}`, such as the reply to
+/// [`GET_ALL`].
+///
+/// Entries are decoded one at a time as they are requested, so only the value
+/// of the current property is materialized instead of the whole map. Names are
+/// borrowed from the underlying body.
+///
+/// This is also an iterator over the entries, which stops after the first
+/// error.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{BodyBuf, Signature, Value};
+/// use tokio_dbus::org_freedesktop_dbus_properties::PropertyEntries;
+///
+/// let mut buf = BodyBuf::new();
+/// buf.store_parsed(Signature::new("a{sv}")?, &["{Name: s:Home, Routes: au:[1, 2]}"])?;
+///
+/// let mut body = buf.as_body();
+/// let mut entries = PropertyEntries::load(&mut body)?;
+///
+/// assert_eq!(entries.load_entry()?, Some(("Name", Value::String(String::from("Home")))));
+/// assert_eq!(entries.load_entry()?, Some(("Routes", Value::Array(vec![Value::U32(1), Value::U32(2)]))));
+/// assert_eq!(entries.load_entry()?, None);
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+pub struct PropertyEntries<'de> {
+    buf: Body<'de>,
+}
+
+impl<'de> PropertyEntries<'de> {
+    /// Start decoding the properties in the body of a message, such as a reply
+    /// to [`GET_ALL`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if the signature of the message is not `a{sv}`.
+    pub fn from_message(message: &Message<'de>) -> Result<Self> {
+        if message.signature() != PROPERTIES {
+            return Err(Error::new(ErrorKind::SignatureMismatch(
+                PROPERTIES.into(),
+                message.signature().into(),
+            )));
+        }
+
+        Self::load(&mut message.body())
+    }
+
+    /// Start decoding a map of properties `a{sv}` at the current position of
+    /// the body, advancing the body past the whole map.
+    pub fn load(body: &mut Body<'de>) -> Result<Self> {
+        Ok(Self {
+            buf: read_dict(body)?,
+        })
+    }
+
+    /// Load the next property.
+    pub fn load_entry(&mut self) -> Result<Option<(&'de str, Value)>> {
+        if self.buf.is_empty() {
+            return Ok(None);
+        }
+
+        self.buf.align::<u64>()?;
+        let name = self.buf.read::<str>()?;
+        let value = Value::from_body(&mut self.buf)?;
+        Ok(Some((name, value)))
+    }
+}
+
+impl<'de> Iterator for PropertyEntries<'de> {
+    type Item = Result<(&'de str, Value)>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.load_entry() {
+            Ok(entry) => Ok(entry).transpose(),
+            Err(error) => {
+                // NB: Make sure that the iterator is fused after an error.
+                self.buf = Body::empty();
+                Some(Err(error))
+            }
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! Types associated with the `org.freedesktop.login1.Manager` interface.
+//!
+//! This is the interface of `systemd-logind`, which manages user sessions and
+//! system power states. Services which need to prepare for the system
+//! suspending or shutting down subscribe to the signals decoded by
+//! [`LoginSignal`], by adding [`MATCH_RULE`] through [`Connection::add_match`].
+//!
+//! Inhibitor locks which delay or block such transitions are taken through
+//! [`inhibit`], which returns the file descriptor holding the lock. Receiving
+//! it requires the `libc` feature.
+//!
+//! This requires the `systemd` feature.
+//!
+//! [`Connection::add_match`]: crate::Connection::add_match
+
+#[cfg(feature = "tokio")]
+use std::os::fd::OwnedFd;
+
+use crate::error::Result;
+#[cfg(feature = "tokio")]
+use crate::error::{Error, ErrorKind};
+#[cfg(feature = "tokio")]
+use crate::{Connection, UnixFd};
+use crate::{Message, MessageKind, ObjectPath};
+
+/// Well known destination name.
+pub const DESTINATION: &str = "org.freedesktop.login1";
+
+/// Well known interface name of the manager.
+pub const MANAGER_INTERFACE: &str = "org.freedesktop.login1.Manager";
+
+/// Well known D-Bus path of the manager.
+pub const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/login1");
+
+/// Match rule which subscribes to all signals emitted by the manager.
+pub const MATCH_RULE: &str = "type='signal',interface='org.freedesktop.login1.Manager'";
+
+/// How an inhibitor lock taken through [`inhibit`] affects the operations it
+/// inhibits.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InhibitMode {
+    /// The operations are blocked for as long as the lock is held.
+    Block,
+    /// The operations are delayed until the lock is released, or until a
+    /// timeout configured by the system elapses.
+    #[default]
+    Delay,
+}
+
+impl InhibitMode {
+    /// Get the name of the mode as passed to the manager.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::org_freedesktop_login1::InhibitMode;
+    ///
+    /// assert_eq!(InhibitMode::Block.as_str(), "block");
+    /// assert_eq!(InhibitMode::Delay.as_str(), "delay");
+    /// ```
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InhibitMode::Block => "block",
+            InhibitMode::Delay => "delay",
+        }
+    }
+}
+
+/// Take an inhibitor lock for the colon-separated list of operations in
+/// `what`, such as `"sleep"` or `"shutdown:sleep"`, returning the file
+/// descriptor which holds the lock.
+///
+/// The lock is released once the file descriptor is closed. `who` is a human
+/// readable name of the application taking the lock, and `why` describes why
+/// the lock is taken.
+///
+/// # Errors
+///
+/// Errors if the manager responds with an error, or if the reply doesn't carry
+/// a file descriptor. The latter happens without the `libc` feature, since the
+/// connection can't receive file descriptors without it.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_dbus::Connection;
+/// use tokio_dbus::org_freedesktop_login1::{self as login1, InhibitMode, LoginSignal};
+///
+/// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+/// let mut c = Connection::system_bus().await?;
+/// c.add_match(login1::MATCH_RULE).await?;
+///
+/// let mut lock = Some(login1::inhibit(&mut c, "sleep", "Example", "Saving state", InhibitMode::Delay).await?);
+///
+/// loop {
+///     c.wait().await?;
+///
+///     if let Some(LoginSignal::PrepareForSleep { start: true }) = LoginSignal::from_message(&c.last_message()?)? {
+///         // Save state, then let the system go to sleep.
+///         lock.take();
+///     }
+/// }
+/// # }
+/// ```
+#[cfg(feature = "tokio")]
+pub async fn inhibit(
+    c: &mut Connection,
+    what: &str,
+    who: &str,
+    why: &str,
+    mode: InhibitMode,
+) -> Result<OwnedFd> {
+    let reply = c
+        .call_method(DESTINATION, PATH, MANAGER_INTERFACE, "Inhibit", |body| {
+            body.arguments((what, who, why, mode.as_str()))
+        })
+        .await?;
+
+    let fd = reply.body().decode::<UnixFd>()?;
+    let message_ref = c.last_message_ref()?;
+    let unix_fds = c.take_unix_fds(&message_ref)?;
+
+    let Some(fd) = usize::try_from(fd.index())
+        .ok()
+        .and_then(|index| unix_fds.into_iter().nth(index))
+    else {
+        return Err(Error::new(ErrorKind::MissingUnixFd(fd.index())));
+    };
+
+    Ok(fd)
+}
+
+/// A signal emitted by the login manager.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LoginSignal<'a> {
+    /// The system is about to suspend or hibernate if `start` is `true`, or
+    /// has resumed if it is `false`.
+    PrepareForSleep {
+        /// If the system is about to suspend rather than having resumed.
+        start: bool,
+    },
+    /// The system is about to shut down if `start` is `true`, or a shutdown
+    /// has been cancelled if it is `false`.
+    PrepareForShutdown {
+        /// If the system is about to shut down rather than the shutdown having
+        /// been cancelled.
+        start: bool,
+    },
+    /// A new session has been created.
+    SessionNew {
+        /// The identifier of the session.
+        id: &'a str,
+        /// The object path of the session.
+        path: &'a ObjectPath,
+    },
+    /// A session has been removed.
+    SessionRemoved {
+        /// The identifier of the session.
+        id: &'a str,
+        /// The object path of the session.
+        path: &'a ObjectPath,
+    },
+}
+
+impl<'a> LoginSignal<'a> {
+    /// Decode a login manager signal from the given message.
+    ///
+    /// Returns `None` if the message is not a signal originating from the
+    /// login manager interface.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, ObjectPath, SendBuf};
+    /// use tokio_dbus::org_freedesktop_login1::{LoginSignal, MANAGER_INTERFACE};
+    ///
+    /// const SESSION: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/login1/session/c2");
+    ///
+    /// let mut send = SendBuf::new();
+    /// let mut body = BodyBuf::new();
+    /// body.arguments(("c2", SESSION))?;
+    ///
+    /// let m = send.signal("SessionNew")
+    ///     .with_interface(MANAGER_INTERFACE)
+    ///     .with_body(&body);
+    ///
+    /// assert_eq!(
+    ///     LoginSignal::from_message(&m)?,
+    ///     Some(LoginSignal::SessionNew { id: "c2", path: SESSION })
+    /// );
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn from_message(message: &Message<'a>) -> Result<Option<Self>> {
+        let MessageKind::Signal { member, .. } = message.kind() else {
+            return Ok(None);
+        };
+
+        if message.interface() != Some(MANAGER_INTERFACE) {
+            return Ok(None);
+        }
+
+        let body = message.body();
+
+        let signal = match member {
+            "PrepareForSleep" => LoginSignal::PrepareForSleep {
+                start: body.decode()?,
+            },
+            "PrepareForShutdown" => LoginSignal::PrepareForShutdown {
+                start: body.decode()?,
+            },
+            "SessionNew" => {
+                let (id, path) = body.decode()?;
+                LoginSignal::SessionNew { id, path }
+            }
+            "SessionRemoved" => {
+                let (id, path) = body.decode()?;
+                LoginSignal::SessionRemoved { id, path }
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(signal))
+    }
+}
@@ -0,0 +1,304 @@
+//! Types associated with the `org.freedesktop.Notifications` interface.
+//!
+//! This is the interface implemented by desktop notification daemons. See the
+//! [Desktop Notifications Specification] for details.
+//!
+//! [Desktop Notifications Specification]:
+//!     https://specifications.freedesktop.org/notification-spec/latest/
+
+use crate::error::Result;
+#[cfg(any(test, feature = "tokio"))]
+use crate::BodyBuf;
+#[cfg(feature = "tokio")]
+use crate::Connection;
+use crate::{ty, Message, MessageKind, ObjectPath, Variant};
+
+/// Well known destination name.
+pub const DESTINATION: &str = "org.freedesktop.Notifications";
+
+/// Well known interface name.
+pub const INTERFACE: &str = "org.freedesktop.Notifications";
+
+/// Well known D-Bus path.
+pub const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/Notifications");
+
+/// Match rule which subscribes to all signals emitted by the notification
+/// daemon.
+pub const MATCH_RULE: &str = "type='signal',interface='org.freedesktop.Notifications'";
+
+raw_enum! {
+    /// The reason a notification was closed, as reported by the
+    /// `NotificationClosed` signal.
+    #[repr(u32)]
+    pub enum CloseReason {
+        /// The notification expired.
+        EXPIRED = 1,
+        /// The notification was dismissed by the user.
+        DISMISSED = 2,
+        /// The notification was closed by a call to `CloseNotification`.
+        CLOSED = 3,
+        /// Undefined or reserved reasons.
+        UNDEFINED = 4,
+    }
+}
+
+/// A notification to send through [`notify`].
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::Variant;
+/// use tokio_dbus::org_freedesktop_notifications::Notification;
+///
+/// let n = Notification::new("Hello", "World")
+///     .with_app_name("example")
+///     .with_actions(&[("default", "Open")])
+///     .with_hints(&[("urgency", Variant::U8(2))])
+///     .with_expire_timeout(5000);
+///
+/// assert_eq!(n.summary(), "Hello");
+/// assert_eq!(n.body(), "World");
+/// ```
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+pub struct Notification<'a> {
+    app_name: &'a str,
+    replaces_id: u32,
+    app_icon: &'a str,
+    summary: &'a str,
+    body: &'a str,
+    actions: &'a [(&'a str, &'a str)],
+    hints: &'a [(&'a str, Variant<'a>)],
+    expire_timeout: i32,
+}
+
+impl<'a> Notification<'a> {
+    /// Construct a new notification with the given `summary` and `body`.
+    ///
+    /// The notification does not replace any existing notification and uses
+    /// the default expiration timeout of the server.
+    pub fn new(summary: &'a str, body: &'a str) -> Self {
+        Self {
+            app_name: "",
+            replaces_id: 0,
+            app_icon: "",
+            summary,
+            body,
+            actions: &[],
+            hints: &[],
+            expire_timeout: -1,
+        }
+    }
+
+    /// Get the summary of the notification.
+    pub fn summary(&self) -> &'a str {
+        self.summary
+    }
+
+    /// Get the body of the notification.
+    pub fn body(&self) -> &'a str {
+        self.body
+    }
+
+    /// Set the name of the application sending the notification.
+    #[must_use]
+    pub fn with_app_name(self, app_name: &'a str) -> Self {
+        Self { app_name, ..self }
+    }
+
+    /// Replace the existing notification with the given identifier.
+    #[must_use]
+    pub fn with_replaces_id(self, replaces_id: u32) -> Self {
+        Self {
+            replaces_id,
+            ..self
+        }
+    }
+
+    /// Set the icon of the notification.
+    #[must_use]
+    pub fn with_app_icon(self, app_icon: &'a str) -> Self {
+        Self { app_icon, ..self }
+    }
+
+    /// Set the actions of the notification as pairs of action keys and their
+    /// human-readable labels.
+    ///
+    /// Invoked actions are reported through
+    /// [`NotificationSignal::ActionInvoked`].
+    #[must_use]
+    pub fn with_actions(self, actions: &'a [(&'a str, &'a str)]) -> Self {
+        Self { actions, ..self }
+    }
+
+    /// Set hints for the notification, such as `urgency` or `category`.
+    #[must_use]
+    pub fn with_hints(self, hints: &'a [(&'a str, Variant<'a>)]) -> Self {
+        Self { hints, ..self }
+    }
+
+    /// Set the timeout in milliseconds after which the notification expires.
+    ///
+    /// `-1` uses the default of the server and `0` means that the
+    /// notification never expires.
+    #[must_use]
+    pub fn with_expire_timeout(self, expire_timeout: i32) -> Self {
+        Self {
+            expire_timeout,
+            ..self
+        }
+    }
+
+    /// Store the arguments of a `Notify` call in `buf`.
+    #[cfg(any(test, feature = "tokio"))]
+    pub(crate) fn store_to(&self, buf: &mut BodyBuf) -> Result<()> {
+        buf.store(self.app_name)?;
+        buf.store(self.replaces_id)?;
+        buf.store(self.app_icon)?;
+        buf.store(self.summary)?;
+        buf.store(self.body)?;
+
+        let mut actions = buf.store_array::<ty::Str>()?;
+
+        for &(key, label) in self.actions {
+            actions.store(key);
+            actions.store(label);
+        }
+
+        actions.finish()?;
+
+        let mut hints = buf.store_dict::<ty::Str, ty::Variant>()?;
+
+        for &(key, value) in self.hints {
+            hints.store(key, value);
+        }
+
+        hints.finish()?;
+
+        buf.store(self.expire_timeout)?;
+        Ok(())
+    }
+}
+
+/// Send a desktop notification and return its identifier.
+///
+/// To receive signals when actions are invoked or notifications are closed,
+/// add [`MATCH_RULE`] through [`Connection::add_match`] and decode received
+/// messages using [`NotificationSignal::from_message`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_dbus::{Connection, Variant};
+/// use tokio_dbus::org_freedesktop_notifications::{self as notifications, Notification};
+///
+/// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+/// let mut c = Connection::session_bus().await?;
+///
+/// let n = Notification::new("Hello", "Sent from tokio-dbus")
+///     .with_hints(&[("urgency", Variant::U8(1))]);
+///
+/// let id = notifications::notify(&mut c, &n).await?;
+/// notifications::close_notification(&mut c, id).await?;
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "tokio")]
+pub async fn notify(c: &mut Connection, notification: &Notification<'_>) -> Result<u32> {
+    let reply = call(c, "Notify", |body| notification.store_to(body)).await?;
+    reply.body().load::<u32>()
+}
+
+/// Close the desktop notification with the given identifier, as returned by
+/// [`notify`].
+#[cfg(feature = "tokio")]
+pub async fn close_notification(c: &mut Connection, id: u32) -> Result<()> {
+    call(c, "CloseNotification", |body| body.store(id)).await?;
+    Ok(())
+}
+
+/// Call `member` on the notification daemon.
+#[cfg(feature = "tokio")]
+async fn call<'c, F>(c: &'c mut Connection, member: &str, arguments: F) -> Result<Message<'c>>
+where
+    F: FnOnce(&mut BodyBuf) -> Result<()>,
+{
+    c.call_method(DESTINATION, PATH, INTERFACE, member, arguments)
+        .await
+}
+
+/// A signal emitted by the notification daemon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NotificationSignal<'a> {
+    /// An action of a notification was invoked by the user.
+    ActionInvoked {
+        /// The identifier of the notification.
+        id: u32,
+        /// The key of the invoked action.
+        action_key: &'a str,
+    },
+    /// A notification was closed.
+    NotificationClosed {
+        /// The identifier of the notification.
+        id: u32,
+        /// The reason the notification was closed.
+        reason: CloseReason,
+    },
+}
+
+impl<'a> NotificationSignal<'a> {
+    /// Decode a notification signal from the given message.
+    ///
+    /// Returns `None` if the message is not a signal originating from the
+    /// notifications interface.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, SendBuf};
+    /// use tokio_dbus::org_freedesktop_notifications::{NotificationSignal, INTERFACE};
+    ///
+    /// let mut send = SendBuf::new();
+    /// let mut body = BodyBuf::new();
+    /// body.store(42u32)?;
+    /// body.store("default")?;
+    ///
+    /// let m = send.signal("ActionInvoked")
+    ///     .with_interface(INTERFACE)
+    ///     .with_body(&body);
+    ///
+    /// assert_eq!(
+    ///     NotificationSignal::from_message(&m)?,
+    ///     Some(NotificationSignal::ActionInvoked { id: 42, action_key: "default" })
+    /// );
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn from_message(message: &Message<'a>) -> Result<Option<Self>> {
+        let MessageKind::Signal { member, .. } = message.kind() else {
+            return Ok(None);
+        };
+
+        if message.interface() != Some(INTERFACE) {
+            return Ok(None);
+        }
+
+        let mut body = message.body();
+
+        let signal = match member {
+            "ActionInvoked" => {
+                let (id, action_key) = body.load_struct::<(u32, ty::Str)>()?;
+                NotificationSignal::ActionInvoked { id, action_key }
+            }
+            "NotificationClosed" => {
+                let (id, reason) = body.load_struct::<(u32, u32)>()?;
+                NotificationSignal::NotificationClosed {
+                    id,
+                    reason: CloseReason::new(reason),
+                }
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(signal))
+    }
+}
@@ -0,0 +1,287 @@
+//! Types associated with the XDG desktop portal interfaces.
+//!
+//! Most portal methods don't return their results directly. Instead they
+//! return the object path of a request object, and the results are delivered
+//! through a `Response` signal emitted on that path once the user has
+//! interacted with the portal. [`request`] takes care of this by subscribing
+//! to the response before the call is made.
+//!
+//! See the [XDG Desktop Portal documentation] for details.
+//!
+//! [XDG Desktop Portal documentation]:
+//!     https://flatpak.github.io/xdg-desktop-portal/docs/requests.html
+
+use std::collections::HashMap;
+#[cfg(feature = "tokio")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::error::Result;
+#[cfg(feature = "tokio")]
+use crate::error::{Error, ErrorKind};
+#[cfg(feature = "tokio")]
+use crate::proto::MessageType;
+#[cfg(feature = "tokio")]
+use crate::{BodyBuf, Connection, MatchRule};
+use crate::{Message, MessageKind, ObjectPath, ObjectPathBuf, Value};
+
+/// Well known destination name.
+pub const DESTINATION: &str = "org.freedesktop.portal.Desktop";
+
+/// Well known D-Bus path of the portal frontend.
+pub const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/portal/desktop");
+
+/// Well known interface name of request objects.
+pub const REQUEST_INTERFACE: &str = "org.freedesktop.portal.Request";
+
+/// The name of the signal emitted on request objects when they complete.
+pub const RESPONSE: &str = "Response";
+
+/// The name of the option through which callers pick the path of the request
+/// object.
+pub const HANDLE_TOKEN: &str = "handle_token";
+
+raw_enum! {
+    /// The response code of a completed portal request.
+    #[repr(u32)]
+    pub enum ResponseCode {
+        /// The request succeeded, and its results are available.
+        SUCCESS = 0,
+        /// The request was cancelled by the user.
+        CANCELLED = 1,
+        /// The request ended in some other way.
+        OTHER = 2,
+    }
+}
+
+/// The response to a portal request.
+///
+/// See [`request`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    /// How the request completed.
+    pub code: ResponseCode,
+    /// The results of the request, whose contents depend on the method called.
+    pub results: HashMap<String, Value>,
+}
+
+impl Response {
+    /// Decode a response from the given message.
+    ///
+    /// Returns `None` if the message is not a `Response` signal emitted by a
+    /// request object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, SendBuf, Signature, Value};
+    /// use tokio_dbus::org_freedesktop_portal::{Response, ResponseCode, REQUEST_INTERFACE, RESPONSE};
+    ///
+    /// let mut send = SendBuf::new();
+    /// let mut body = BodyBuf::new();
+    /// body.store_parsed(Signature::new("ua{sv}")?, &["0", r#"{uri: s:"file:///tmp/a.png"}"#])?;
+    ///
+    /// let m = send.signal(RESPONSE)
+    ///     .with_interface(REQUEST_INTERFACE)
+    ///     .with_body(&body);
+    ///
+    /// let response = Response::from_message(&m)?.expect("response");
+    /// assert_eq!(response.code, ResponseCode::SUCCESS);
+    /// assert_eq!(response.results["uri"].as_str(), Some("file:///tmp/a.png"));
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn from_message(message: &Message<'_>) -> Result<Option<Self>> {
+        let MessageKind::Signal {
+            member: RESPONSE, ..
+        } = message.kind()
+        else {
+            return Ok(None);
+        };
+
+        if message.interface() != Some(REQUEST_INTERFACE) {
+            return Ok(None);
+        }
+
+        let (code, results) = message.body().decode::<(u32, HashMap<String, Value>)>()?;
+
+        Ok(Some(Self {
+            code: ResponseCode::new(code),
+            results,
+        }))
+    }
+}
+
+/// Get the path of the request object the portal creates for a call made by
+/// the connection with the given unique name, using the given handle token.
+///
+/// # Errors
+///
+/// Errors if the token contains characters which are not valid in an object
+/// path element, which are anything except `[A-Za-z0-9_]`.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::org_freedesktop_portal::request_path;
+///
+/// let path = request_path(":1.42", "tokio_dbus_1")?;
+/// assert_eq!(path.to_string(), "/org/freedesktop/portal/desktop/request/1_42/tokio_dbus_1");
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+pub fn request_path(unique_name: &str, token: &str) -> Result<ObjectPathBuf> {
+    let sender = unique_name.trim_start_matches(':').replace('.', "_");
+    let path = format!("/org/freedesktop/portal/desktop/request/{sender}/{token}");
+    Ok(ObjectPath::new(&path)?.to_owned())
+}
+
+/// Call a method on an XDG desktop portal and wait for its response.
+///
+/// Portal methods return the path of a request object, and deliver their
+/// results later through a `Response` signal on that path. This generates a
+/// handle token, which `arguments` is called with to store the arguments of
+/// the method in the provided body. The token must be included as the
+/// [`HANDLE_TOKEN`] entry of the options of the method, which lets the
+/// response be subscribed to before the method is called so that it can't be
+/// missed.
+///
+/// Other messages received while waiting are deferred, and the subscription
+/// is removed once the response has been received.
+///
+/// # Errors
+///
+/// Errors if the method call fails, or if the response can't be decoded. A
+/// response indicating that the user cancelled the request is not an error,
+/// but is reported through [`Response::code`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_dbus::{ty, Connection, Variant};
+/// use tokio_dbus::org_freedesktop_portal::{self as portal, ResponseCode, HANDLE_TOKEN};
+///
+/// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+/// let mut c = Connection::session_bus().await?;
+///
+/// let response = portal::request(&mut c, "org.freedesktop.portal.Screenshot", "Screenshot", |body, token| {
+///     body.store("")?;
+///
+///     let mut options = body.store_dict::<ty::Str, ty::Variant>()?;
+///     options.store(HANDLE_TOKEN, Variant::String(token));
+///     options.store("interactive", Variant::Bool(true));
+///     options.finish()?;
+///     Ok(())
+/// }).await?;
+///
+/// if response.code == ResponseCode::SUCCESS {
+///     println!("{:?}", response.results.get("uri"));
+/// }
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "tokio")]
+pub async fn request<F>(
+    c: &mut Connection,
+    interface: &str,
+    member: &str,
+    arguments: F,
+) -> Result<Response>
+where
+    F: FnOnce(&mut BodyBuf, &str) -> Result<()>,
+{
+    let Some(name) = c.name() else {
+        return Err(Error::new(ErrorKind::MissingUniqueName));
+    };
+
+    let token = next_token();
+    let expected = request_path(name, &token)?;
+
+    let mut rule = response_rule(&expected);
+    c.add_match(&rule).await?;
+
+    let result = response(
+        c, interface, member, arguments, &token, &expected, &mut rule,
+    )
+    .await;
+
+    // NB: The subscription is removed on all paths, but failing to do so
+    // doesn't hide the original error.
+    let removed = c.remove_match(&rule).await;
+    let response = result?;
+    removed?;
+    Ok(response)
+}
+
+/// Call a portal method and wait for its response once subscribed to it
+/// through `rule`, which is updated if the subscription changes.
+#[cfg(feature = "tokio")]
+async fn response<F>(
+    c: &mut Connection,
+    interface: &str,
+    member: &str,
+    arguments: F,
+    token: &str,
+    expected: &ObjectPath,
+    rule: &mut String,
+) -> Result<Response>
+where
+    F: FnOnce(&mut BodyBuf, &str) -> Result<()>,
+{
+    let handle = c
+        .call_method(DESTINATION, PATH, interface, member, |body| {
+            arguments(body, token)
+        })
+        .await?
+        .body()
+        .read::<ObjectPath>()?
+        .to_owned();
+
+    // NB: Older portals don't support handle tokens, in which case the request
+    // object has a different path than the one we subscribed to.
+    if *handle != *expected {
+        c.remove_match(rule).await?;
+        *rule = response_rule(&handle);
+        c.add_match(rule).await?;
+    }
+
+    let is_response = |message: &Message<'_>| {
+        message.member() == Some(RESPONSE)
+            && message.interface() == Some(REQUEST_INTERFACE)
+            && message.path() == Some(&*handle)
+    };
+
+    let response = match c.recv.remove_deferred(|m| is_response(&m.borrow())) {
+        Some(message) => Response::from_message(&message.borrow())?,
+        None => loop {
+            c.wait_no_deferred().await?;
+            let message = c.recv.last_message_no_deferred()?;
+
+            if is_response(&message) {
+                break Response::from_message(&message)?;
+            }
+
+            c.recv.defer_last()?;
+        },
+    };
+
+    let Some(response) = response else {
+        return Err(Error::new(ErrorKind::MissingMessage));
+    };
+
+    Ok(response)
+}
+
+/// Construct the match rule for the response of a portal request.
+#[cfg(feature = "tokio")]
+fn response_rule(path: &ObjectPath) -> String {
+    MatchRule::new()
+        .with_message_type(MessageType::SIGNAL)
+        .with_path(path)
+        .with_interface(REQUEST_INTERFACE)
+        .with_member(RESPONSE)
+        .to_string()
+}
+
+/// Generate a handle token which is unique within this process.
+#[cfg(feature = "tokio")]
+fn next_token() -> String {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    format!("tokio_dbus_{}", NEXT.fetch_add(1, Ordering::Relaxed))
+}
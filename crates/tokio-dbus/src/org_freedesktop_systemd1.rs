@@ -0,0 +1,266 @@
+//! Types associated with the `org.freedesktop.systemd1.Manager` interface.
+//!
+//! This is the interface through which the systemd service manager is
+//! controlled. Calls which change the state of units return a job, whose
+//! completion is reported through the [`ManagerSignal::JobRemoved`] signal.
+//! systemd only emits these signals to clients which have called
+//! [`subscribe`].
+//!
+//! This requires the `systemd` feature.
+
+use crate::error::Result;
+use crate::{ty, Message, MessageKind, ObjectPath};
+#[cfg(feature = "tokio")]
+use crate::{BodyBuf, Connection, ObjectPathBuf};
+
+/// Well known destination name.
+pub const DESTINATION: &str = "org.freedesktop.systemd1";
+
+/// Well known interface name of the manager.
+pub const MANAGER_INTERFACE: &str = "org.freedesktop.systemd1.Manager";
+
+/// Well known D-Bus path of the manager.
+pub const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/systemd1");
+
+/// Match rule which subscribes to all signals emitted by the manager.
+pub const MATCH_RULE: &str = "type='signal',interface='org.freedesktop.systemd1.Manager'";
+
+/// How a job which changes the state of a unit interacts with already queued
+/// jobs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum JobMode {
+    /// Replace already queued jobs which conflict with the new job.
+    #[default]
+    Replace,
+    /// Fail if the new job conflicts with already queued jobs.
+    Fail,
+    /// Stop all other units, which is only valid for starting units.
+    Isolate,
+    /// Ignore all unit dependencies of the new job.
+    IgnoreDependencies,
+    /// Ignore only the requirement dependencies of the new job.
+    IgnoreRequirements,
+}
+
+impl JobMode {
+    /// Get the name of the mode as passed to the manager.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::org_freedesktop_systemd1::JobMode;
+    ///
+    /// assert_eq!(JobMode::Replace.as_str(), "replace");
+    /// assert_eq!(JobMode::IgnoreDependencies.as_str(), "ignore-dependencies");
+    /// ```
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobMode::Replace => "replace",
+            JobMode::Fail => "fail",
+            JobMode::Isolate => "isolate",
+            JobMode::IgnoreDependencies => "ignore-dependencies",
+            JobMode::IgnoreRequirements => "ignore-requirements",
+        }
+    }
+}
+
+/// Start the given unit, returning the object path of the queued job.
+///
+/// The job completes asynchronously, which is reported through the
+/// [`ManagerSignal::JobRemoved`] signal once [`subscribe`] has been called.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_dbus::Connection;
+/// use tokio_dbus::org_freedesktop_systemd1::{self as systemd1, JobMode, ManagerSignal};
+///
+/// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+/// let mut c = Connection::system_bus().await?;
+/// c.add_match(systemd1::MATCH_RULE).await?;
+/// systemd1::subscribe(&mut c).await?;
+///
+/// let job = systemd1::start_unit(&mut c, "example.service", JobMode::Replace).await?;
+///
+/// loop {
+///     c.wait().await?;
+///     let message = c.last_message()?;
+///
+///     if let Some(ManagerSignal::JobRemoved { job: removed, result, .. }) =
+///         ManagerSignal::from_message(&message)?
+///     {
+///         if removed == &*job {
+///             println!("Job finished: {result}");
+///             break;
+///         }
+///     }
+/// }
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "tokio")]
+pub async fn start_unit(c: &mut Connection, name: &str, mode: JobMode) -> Result<ObjectPathBuf> {
+    unit_job(c, "StartUnit", name, mode).await
+}
+
+/// Stop the given unit, returning the object path of the queued job.
+///
+/// See [`start_unit`].
+#[cfg(feature = "tokio")]
+pub async fn stop_unit(c: &mut Connection, name: &str, mode: JobMode) -> Result<ObjectPathBuf> {
+    unit_job(c, "StopUnit", name, mode).await
+}
+
+/// Restart the given unit, returning the object path of the queued job.
+///
+/// See [`start_unit`].
+#[cfg(feature = "tokio")]
+pub async fn restart_unit(c: &mut Connection, name: &str, mode: JobMode) -> Result<ObjectPathBuf> {
+    unit_job(c, "RestartUnit", name, mode).await
+}
+
+/// Get the object path of a loaded unit.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_dbus::Connection;
+/// use tokio_dbus::org_freedesktop_systemd1 as systemd1;
+///
+/// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+/// let mut c = Connection::system_bus().await?;
+/// let unit = systemd1::get_unit(&mut c, "dbus.service").await?;
+/// println!("{}", &*unit);
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "tokio")]
+pub async fn get_unit(c: &mut Connection, name: &str) -> Result<ObjectPathBuf> {
+    let reply = call(c, "GetUnit", |body| body.store(name)).await?;
+    Ok(reply.body().read::<ObjectPath>()?.to_owned())
+}
+
+/// Ask systemd to emit signals about jobs and units to this connection.
+///
+/// Signals still have to be matched through [`Connection::add_match`], such as
+/// with [`MATCH_RULE`].
+#[cfg(feature = "tokio")]
+pub async fn subscribe(c: &mut Connection) -> Result<()> {
+    call(c, "Subscribe", |_| Ok(())).await?;
+    Ok(())
+}
+
+/// Call a method which queues a job for the unit `name`.
+#[cfg(feature = "tokio")]
+async fn unit_job(
+    c: &mut Connection,
+    member: &str,
+    name: &str,
+    mode: JobMode,
+) -> Result<ObjectPathBuf> {
+    let reply = call(c, member, |body| body.arguments((name, mode.as_str()))).await?;
+    Ok(reply.body().read::<ObjectPath>()?.to_owned())
+}
+
+/// Call `member` on the manager.
+#[cfg(feature = "tokio")]
+async fn call<'c, F>(c: &'c mut Connection, member: &str, arguments: F) -> Result<Message<'c>>
+where
+    F: FnOnce(&mut BodyBuf) -> Result<()>,
+{
+    c.call_method(DESTINATION, PATH, MANAGER_INTERFACE, member, arguments)
+        .await
+}
+
+/// A signal emitted by the manager.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ManagerSignal<'a> {
+    /// A job has been queued.
+    JobNew {
+        /// The numeric identifier of the job.
+        id: u32,
+        /// The object path of the job.
+        job: &'a ObjectPath,
+        /// The name of the unit the job belongs to.
+        unit: &'a str,
+    },
+    /// A job has completed, or has been removed for another reason.
+    JobRemoved {
+        /// The numeric identifier of the job.
+        id: u32,
+        /// The object path of the job.
+        job: &'a ObjectPath,
+        /// The name of the unit the job belongs to.
+        unit: &'a str,
+        /// The result of the job, such as `done`, `canceled`, `timeout`,
+        /// `failed`, `dependency` or `skipped`.
+        result: &'a str,
+    },
+}
+
+impl<'a> ManagerSignal<'a> {
+    /// Decode a manager signal from the given message.
+    ///
+    /// Returns `None` if the message is not a signal originating from the
+    /// manager interface.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, ObjectPath, SendBuf};
+    /// use tokio_dbus::org_freedesktop_systemd1::{ManagerSignal, MANAGER_INTERFACE};
+    ///
+    /// const JOB: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/systemd1/job/42");
+    ///
+    /// let mut send = SendBuf::new();
+    /// let mut body = BodyBuf::new();
+    /// body.arguments((42u32, JOB, "example.service", "done"))?;
+    ///
+    /// let m = send.signal("JobRemoved")
+    ///     .with_interface(MANAGER_INTERFACE)
+    ///     .with_body(&body);
+    ///
+    /// assert_eq!(
+    ///     ManagerSignal::from_message(&m)?,
+    ///     Some(ManagerSignal::JobRemoved {
+    ///         id: 42,
+    ///         job: JOB,
+    ///         unit: "example.service",
+    ///         result: "done",
+    ///     })
+    /// );
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn from_message(message: &Message<'a>) -> Result<Option<Self>> {
+        let MessageKind::Signal { member, .. } = message.kind() else {
+            return Ok(None);
+        };
+
+        if message.interface() != Some(MANAGER_INTERFACE) {
+            return Ok(None);
+        }
+
+        let mut body = message.body();
+
+        let signal = match member {
+            "JobNew" => {
+                let (id, job, unit) = body.load_struct::<(u32, ty::ObjectPath, ty::Str)>()?;
+                ManagerSignal::JobNew { id, job, unit }
+            }
+            "JobRemoved" => {
+                let (id, job, unit, result) =
+                    body.load_struct::<(u32, ty::ObjectPath, ty::Str, ty::Str)>()?;
+
+                ManagerSignal::JobRemoved {
+                    id,
+                    job,
+                    unit,
+                    result,
+                }
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(signal))
+    }
+}
@@ -1,4 +1,26 @@
 //! Low level details for the D-Bus protocol implementation.
+//!
+//! These are the raw values used on the wire, which can be useful when
+//! implementing tooling such as monitors or fuzzers. Each type can be converted
+//! to and from its underlying representation, where conversion from a raw value
+//! only succeeds if it is known.
+//!
+//! # Examples
+//!
+//! ```
+//! use tokio_dbus::proto::{Flags, MessageType, Type, Variant};
+//!
+//! assert_eq!(MessageType::from_repr(4), Some(MessageType::SIGNAL));
+//! assert_eq!(MessageType::from_repr(5), None);
+//! assert_eq!(u8::from(MessageType::ERROR), 3);
+//!
+//! assert_eq!(Variant::from_repr(8), Some(Variant::SIGNATURE));
+//! assert_eq!(Type::from_repr(b's'), Some(Type::STRING));
+//! assert_eq!(Type::STRING.get(), b's');
+//!
+//! assert_eq!(Flags::from_repr(3), Some(Flags::NO_REPLY_EXPECTED | Flags::NO_AUTO_START));
+//! assert_eq!(Flags::from_repr(8), None);
+//! ```
 
 #[doc(inline)]
 pub use tokio_dbus_core::proto::{Endianness, Flags, MessageType, Type, Variant};
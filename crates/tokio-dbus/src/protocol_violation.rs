@@ -0,0 +1,115 @@
+use std::error;
+use std::fmt;
+
+use crate::proto::{self, Endianness, Flags, MessageType};
+use crate::Error;
+
+/// Details about a message received from a peer which violates the D-Bus
+/// protocol.
+///
+/// This is accessed through [`Error::protocol_violation`], and contains a
+/// snapshot of the fixed header of the offending message alongside the offset
+/// into the message at which the violation was detected.
+///
+/// The underlying cause is available through [`std::error::Error::source`].
+#[derive(Debug)]
+pub struct ProtocolViolation {
+    header: proto::Header,
+    offset: usize,
+    recoverable: bool,
+    error: Error,
+}
+
+impl ProtocolViolation {
+    #[inline]
+    pub(crate) fn new(
+        header: proto::Header,
+        offset: usize,
+        recoverable: bool,
+        error: Error,
+    ) -> Self {
+        Self {
+            header,
+            offset,
+            recoverable,
+            error,
+        }
+    }
+
+    /// The error which caused the violation.
+    #[inline]
+    pub(crate) fn error(&self) -> &Error {
+        &self.error
+    }
+
+    /// The endianness of the offending message.
+    #[inline]
+    pub fn endianness(&self) -> Endianness {
+        self.header.endianness
+    }
+
+    /// The type of the offending message.
+    #[inline]
+    pub fn message_type(&self) -> MessageType {
+        self.header.message_type
+    }
+
+    /// The flags of the offending message.
+    #[inline]
+    pub fn flags(&self) -> Flags {
+        self.header.flags
+    }
+
+    /// The serial of the offending message. This might be zero.
+    #[inline]
+    pub fn serial(&self) -> u32 {
+        self.header.serial
+    }
+
+    /// The length of the body of the offending message as declared in its
+    /// header.
+    #[inline]
+    pub fn body_length(&self) -> u32 {
+        self.header.body_length
+    }
+
+    /// The offset in bytes from the start of the offending message at which
+    /// the violation was detected.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Test if the offending message has been consumed in its entirety, so
+    /// that the connection is still in sync with its peer and can continue
+    /// to receive messages.
+    ///
+    /// See [`ConnectionBuilder::skip_invalid_messages`].
+    ///
+    /// [`ConnectionBuilder::skip_invalid_messages`]: crate::ConnectionBuilder::skip_invalid_messages
+    #[inline]
+    pub fn is_recoverable(&self) -> bool {
+        self.recoverable
+    }
+}
+
+impl fmt::Display for ProtocolViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Protocol violation at offset {} of {:?} message with serial {} and body length {}: {}",
+            self.offset,
+            self.header.message_type,
+            self.header.serial,
+            self.header.body_length,
+            self.error
+        )
+    }
+}
+
+impl error::Error for ProtocolViolation {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
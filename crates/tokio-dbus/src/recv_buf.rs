@@ -1,26 +1,131 @@
 use std::collections::VecDeque;
+use std::io;
 use std::mem::size_of;
 use std::num::NonZeroU32;
+#[cfg(unix)]
+use std::os::fd::OwnedFd;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
 
-use crate::buf::AlignedBuf;
+use crate::buf::{padding_to, AlignedBuf, MAX_ARRAY_LENGTH, MAX_BODY_LENGTH};
 use crate::error::{Error, ErrorKind, Result};
 use crate::proto;
-use crate::{Body, Endianness, Message, MessageBuf, MessageKind, ObjectPath, Signature};
+use crate::sender_quota::SenderUsage;
+use crate::utils::{read_some, Instant};
+use crate::{
+    Body, Endianness, Frame, HeaderFields, Message, MessageBuf, MessageKind, ObjectPath,
+    SenderQuota, Signature,
+};
+
+/// Offset at which header fields start, after the fixed header and the length
+/// of the header fields.
+const HEADER_FIELDS_START: usize = size_of::<proto::Header>() + size_of::<u32>();
+
+/// Counter used to give each [`RecvBuf`] a unique identifier.
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
 
 /// An owned reference to a message in a [`RecvBuf`].
 ///
 /// To convert into a [`Message`], use [`Connection::read_message`] or
 /// [`RecvBuf::read_message`].
 ///
+/// A message reference is only valid until the next message is received into
+/// the buffer it was taken from. Reading a reference which is no longer valid
+/// results in an error for which [`Error::is_stale_message`] returns `true`.
+///
 /// [`Message`]: crate::Message
 /// [`Connection::read_message`]: crate::Connection::read_message
 /// [`RecvBuf::read_message`]: crate::RecvBuf::read_message
 /// [`RecvBuf`]: crate::RecvBuf
-pub(crate) struct MessageRef {
+#[derive(Debug, Clone, Copy)]
+pub struct MessageRef {
+    pub(crate) id: u64,
+    pub(crate) epoch: u64,
     pub(crate) serial: NonZeroU32,
     pub(crate) message_type: proto::MessageType,
     pub(crate) flags: proto::Flags,
     pub(crate) headers: usize,
+    pub(crate) received: Instant,
+    pub(crate) kernel_timestamp: Option<SystemTime>,
+}
+
+impl MessageRef {
+    /// Get the serial of the referenced message.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::Connection;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    /// c.wait_no_deferred().await?;
+    ///
+    /// let message_ref = c.last_message_ref()?;
+    /// let message = c.read_message(&message_ref)?;
+    /// assert_eq!(message_ref.serial(), message.serial());
+    /// # Ok(()) }
+    /// ```
+    pub fn serial(&self) -> NonZeroU32 {
+        self.serial
+    }
+
+    /// Get the monotonic time at which the referenced message was fully
+    /// received from the underlying socket.
+    ///
+    /// This can be used to measure how long a message has been waiting to be
+    /// processed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::Connection;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    /// c.wait_no_deferred().await?;
+    ///
+    /// let message_ref = c.last_message_ref()?;
+    /// println!("Message was received {:?} ago", message_ref.received().elapsed());
+    /// # Ok(()) }
+    /// ```
+    pub fn received(&self) -> Instant {
+        self.received
+    }
+
+    /// Get the time at which the kernel received the last part of the
+    /// referenced message on the socket.
+    ///
+    /// This is only available if kernel timestamps have been enabled through
+    /// [`ConnectionBuilder::kernel_timestamps`], and unlike [`received`] it
+    /// doesn't include the time spent before the connection was polled.
+    ///
+    /// [`ConnectionBuilder::kernel_timestamps`]: crate::ConnectionBuilder::kernel_timestamps
+    /// [`received`]: Self::received
+    pub fn kernel_timestamp(&self) -> Option<SystemTime> {
+        self.kernel_timestamp
+    }
+}
+
+/// The state of the message being received.
+#[derive(Debug, Clone, Copy)]
+enum RecvState {
+    /// No message is being received.
+    Idle,
+    /// The fixed part of the header is being received.
+    Header,
+    /// The rest of the message is being received, up until the given total
+    /// length of the message.
+    Body(usize),
+}
+
+/// The header of a message which is being received.
+#[derive(Debug, Clone, Copy)]
+struct PendingHeader {
+    serial: NonZeroU32,
+    message_type: proto::MessageType,
+    flags: proto::Flags,
+    headers: usize,
 }
 
 /// Buffer used for receiving messages through D-Bus.
@@ -30,13 +135,35 @@ pub struct RecvBuf {
     /// The currently configured endianness of the receive buffer. This changes
     /// in response to the endianness of the messages being received.
     endianness: Endianness,
-    /// The last serial observed. This is used to determine whether a
-    /// [`MessageRef`] is valid or not.
+    /// Unique identifier of this buffer, used to make sure that a
+    /// [`MessageRef`] is read from the buffer it was taken from.
+    id: u64,
+    /// Incremented every time the buffer is cleared. This is used to determine
+    /// whether a [`MessageRef`] is valid or not.
+    epoch: u64,
+    /// The last message received.
     last_message: Option<MessageRef>,
     /// If a message has been taken from the receive buffer.
     deferred_taken: bool,
     /// Stored messages.
     deferred: VecDeque<MessageBuf>,
+    /// Usage of deferred messages by sender, if a quota is set.
+    sender_usage: Option<SenderUsage>,
+    /// The state of the message being received.
+    state: RecvState,
+    /// The header of the message being received, which is only made
+    /// available once it has been received in its entirety.
+    pending: Option<PendingHeader>,
+    /// A protocol violation detected in the message being received, which is
+    /// reported once it has been received in its entirety.
+    violation: Option<Error>,
+    /// File descriptors received alongside the last message.
+    #[cfg(unix)]
+    unix_fds: Vec<OwnedFd>,
+    /// Whether file descriptors received alongside the last message were
+    /// truncated.
+    #[cfg(all(feature = "tokio", feature = "libc"))]
+    unix_fds_truncated: bool,
 }
 
 impl RecvBuf {
@@ -45,62 +172,353 @@ impl RecvBuf {
         Self {
             buf: AlignedBuf::new(),
             endianness: Endianness::NATIVE,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            epoch: 0,
             last_message: None,
             deferred_taken: false,
             deferred: VecDeque::new(),
+            sender_usage: None,
+            state: RecvState::Idle,
+            pending: None,
+            violation: None,
+            #[cfg(unix)]
+            unix_fds: Vec::new(),
+            #[cfg(all(feature = "tokio", feature = "libc"))]
+            unix_fds_truncated: false,
+        }
+    }
+
+    /// Receive the next message from `reader`, returning a reference to it.
+    ///
+    /// This is what [`Connection`] uses to receive messages, and can be used to
+    /// receive messages over a custom transport. Everything needed to frame
+    /// messages is kept in the buffer, so if `reader` would block, the next
+    /// call continues where this one left off. A message only becomes
+    /// available once it has been received in its entirety.
+    ///
+    /// Receiving a message invalidates any outstanding [`MessageRef`].
+    ///
+    /// [`Connection`]: crate::Connection
+    ///
+    /// # Errors
+    ///
+    /// Errors if reading fails, where an error for which
+    /// [`Error::is_would_block`] returns `true` indicates that `reader` would
+    /// block. Errors if the received message violates the protocol, see
+    /// [`Error::protocol_violation`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{ObjectPath, RecvBuf, SendBuf};
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// for member in ["First", "Second"] {
+    ///     let m = send
+    ///         .signal(member)
+    ///         .with_path(ObjectPath::ROOT)
+    ///         .with_interface("se.tedro.Example");
+    ///     send.write_message(m)?;
+    /// }
+    ///
+    /// let mut bytes = Vec::new();
+    /// send.write_to(&mut bytes)?;
+    ///
+    /// let mut reader = &bytes[..];
+    /// let mut recv = RecvBuf::new();
+    ///
+    /// let first = recv.read_from(&mut reader)?;
+    /// assert_eq!(recv.read_message(&first)?.member(), Some("First"));
+    ///
+    /// let second = recv.read_from(&mut reader)?;
+    /// assert_eq!(recv.read_message(&second)?.member(), Some("Second"));
+    /// assert!(recv.read_message(&first).unwrap_err().is_stale_message());
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn read_from<R>(&mut self, reader: &mut R) -> Result<MessageRef>
+    where
+        R: ?Sized + io::Read,
+    {
+        loop {
+            match self.state {
+                RecvState::Idle => {
+                    self.clear();
+                    self.state = RecvState::Header;
+                }
+                RecvState::Header => {
+                    self.fill(reader, HEADER_FIELDS_START)?;
+
+                    let mut read_buf = self.buf.as_aligned();
+
+                    let mut header = read_buf.load::<proto::Header>()?;
+                    let mut headers = read_buf.load::<u32>()?;
+
+                    header.adjust(header.endianness);
+                    headers.adjust(header.endianness);
+
+                    // NB: Lengths which are out of bounds means that we can't
+                    // trust the framing of the message, so the connection
+                    // can't recover from them.
+                    let violation = |offset, kind| {
+                        Error::new_protocol_violation(header, offset, false, Error::new(kind))
+                    };
+
+                    if header.body_length > MAX_BODY_LENGTH {
+                        return Err(violation(4, ErrorKind::BodyTooLong(header.body_length)));
+                    }
+
+                    if headers > MAX_ARRAY_LENGTH {
+                        return Err(violation(12, ErrorKind::ArrayTooLong(headers)));
+                    }
+
+                    let Some(body_length) = usize::try_from(header.body_length).ok() else {
+                        return Err(violation(4, ErrorKind::BodyTooLong(header.body_length)));
+                    };
+
+                    let Some(headers) = usize::try_from(headers).ok() else {
+                        return Err(violation(12, ErrorKind::ArrayTooLong(headers)));
+                    };
+
+                    // Padding used in the header.
+                    let total = headers + padding_to::<u64>(headers) + body_length;
+                    self.state = RecvState::Body(HEADER_FIELDS_START + total);
+                    self.endianness = header.endianness;
+
+                    match NonZeroU32::new(header.serial) {
+                        Some(serial) => {
+                            self.pending = Some(PendingHeader {
+                                serial,
+                                message_type: header.message_type,
+                                flags: header.flags,
+                                headers,
+                            });
+                        }
+                        None => {
+                            // The message is still received in its entirety
+                            // to stay in sync with the peer.
+                            self.violation = Some(Error::new_protocol_violation(
+                                header,
+                                8,
+                                true,
+                                Error::new(ErrorKind::ZeroSerial),
+                            ));
+                        }
+                    }
+                }
+                RecvState::Body(end) => {
+                    self.fill(reader, end)?;
+                    self.state = RecvState::Idle;
+
+                    if let Some(error) = self.violation.take() {
+                        return Err(error);
+                    }
+
+                    let Some(pending) = self.pending.take() else {
+                        return Err(Error::new(ErrorKind::MissingMessage));
+                    };
+
+                    let message_ref = MessageRef {
+                        id: self.id,
+                        epoch: self.epoch,
+                        serial: pending.serial,
+                        message_type: pending.message_type,
+                        flags: pending.flags,
+                        headers: pending.headers,
+                        received: Instant::now(),
+                        kernel_timestamp: None,
+                    };
+
+                    self.last_message = Some(message_ref);
+                    return Ok(message_ref);
+                }
+            }
+        }
+    }
+
+    /// Receive into the buffer until it is `end` bytes long.
+    ///
+    /// If this would block, whatever has been received so far is kept in the
+    /// buffer so that the next call picks up where this one left off.
+    fn fill<R>(&mut self, reader: &mut R, end: usize) -> io::Result<()>
+    where
+        R: ?Sized + io::Read,
+    {
+        let remaining = end.saturating_sub(self.buf.len());
+        self.buf.reserve_bytes(remaining);
+
+        while self.buf.len() < end {
+            let remaining = end - self.buf.len();
+            let n = read_some(reader, &mut self.buf.get_mut()[..remaining])?;
+            self.buf.advance(n);
         }
+
+        Ok(())
+    }
+
+    /// Set the quota of deferred messages which can be held for each sender.
+    ///
+    /// Messages received from the transport which are deferred while waiting
+    /// for something else are checked against the quota of their sender.
+    /// Messages deferred through [`RecvBuf::defer`] count towards the quota,
+    /// but are never rejected.
+    ///
+    /// Defaults to `None`, which means that deferred messages are unbounded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{RecvBuf, SenderQuota};
+    ///
+    /// let mut recv = RecvBuf::new();
+    /// recv.set_sender_quota(Some(SenderQuota::new().with_max_messages(16)));
+    /// ```
+    pub fn set_sender_quota(&mut self, quota: Option<SenderQuota>) {
+        self.sender_usage = quota.map(|quota| SenderUsage::new(quota, &self.deferred));
     }
 
     /// Defer the given message, causing it to be received again at the next
     /// wait.
     pub fn defer(&mut self, message: MessageBuf) {
+        if let Some(usage) = &mut self.sender_usage {
+            usage.add(&message);
+        }
+
         self.deferred.push_back(message);
     }
 
     /// Defer the last message.
+    ///
+    /// If a sender quota is set and the message exceeds it, the message is
+    /// either dropped or an error is returned.
+    #[cfg(feature = "tokio")]
     pub(crate) fn defer_last(&mut self) -> Result<()> {
         let message = last_message(&self.last_message, &self.buf, self.endianness)?;
-        self.deferred.push_back(message.to_owned());
+        let message = message.to_owned();
+
+        if let Some(usage) = &mut self.sender_usage {
+            if !usage.fits(&message) {
+                if usage.quota().is_drop() {
+                    return Ok(());
+                }
+
+                let sender = message.sender().unwrap_or_default();
+                return Err(Error::new(ErrorKind::SenderQuotaExceeded(sender.into())));
+            }
+
+            usage.add(&message);
+        }
+
+        self.deferred.push_back(message);
         Ok(())
     }
 
     /// Try to take a single deferred message.
+    #[cfg(feature = "tokio")]
     pub(crate) fn take_deferred(&mut self) -> bool {
         if self.deferred_taken {
-            self.deferred.pop_front();
+            if let Some(message) = self.deferred.pop_front() {
+                if let Some(usage) = &mut self.sender_usage {
+                    usage.remove(&message);
+                }
+            }
         }
 
         self.deferred_taken = !self.deferred.is_empty();
         self.deferred_taken
     }
 
-    /// Access the underlying buffer.
-    #[inline]
-    pub(crate) fn buf(&self) -> &AlignedBuf {
-        &self.buf
+    /// Remove the first deferred message matching `predicate`, skipping the
+    /// deferred message which is currently being read if any.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn remove_deferred<F>(&mut self, predicate: F) -> Option<MessageBuf>
+    where
+        F: FnMut(&MessageBuf) -> bool,
+    {
+        let skip = usize::from(self.deferred_taken);
+        let index = self.deferred.iter().skip(skip).position(predicate)?;
+        let message = self.deferred.remove(index + skip)?;
+
+        if let Some(usage) = &mut self.sender_usage {
+            usage.remove(&message);
+        }
+
+        Some(message)
     }
 
-    /// Access the underlying buffer mutably.
-    #[inline]
-    pub(crate) fn buf_mut(&mut self) -> &mut AlignedBuf {
-        &mut self.buf
+    /// Clear the receive buffer.
+    ///
+    /// This invalidates any outstanding [`MessageRef`].
+    pub(crate) fn clear(&mut self) {
+        self.buf.clear();
+        self.epoch = self.epoch.wrapping_add(1);
+        self.last_message = None;
+        #[cfg(unix)]
+        self.unix_fds.clear();
+        #[cfg(all(feature = "tokio", feature = "libc"))]
+        {
+            self.unix_fds_truncated = false;
+        }
     }
 
-    /// Set last serial.
-    #[inline]
-    pub(crate) fn set_last_message(&mut self, message_ref: MessageRef) {
-        self.last_message = Some(message_ref);
+    /// Attach the time at which the kernel received the last message.
+    #[cfg(all(feature = "tokio", feature = "libc"))]
+    pub(crate) fn set_kernel_timestamp(&mut self, kernel_timestamp: Option<SystemTime>) {
+        if let Some(last) = &mut self.last_message {
+            last.kernel_timestamp = kernel_timestamp;
+        }
     }
 
-    /// Set endianness of buffer content.
-    pub(crate) fn set_endianness(&mut self, endianness: Endianness) {
-        self.endianness = endianness;
+    /// Attach file descriptors received by the transport to the last message,
+    /// and whether they were truncated.
+    #[cfg(all(feature = "tokio", feature = "libc"))]
+    pub(crate) fn set_unix_fds(&mut self, unix_fds: Vec<OwnedFd>, truncated: bool) {
+        self.unix_fds = unix_fds;
+        self.unix_fds_truncated = truncated;
     }
 
-    /// Clear the receive buffer.
-    pub(crate) fn clear(&mut self) {
-        self.buf.clear();
-        self.last_message = None;
+    /// Take the file descriptors which were received alongside the message
+    /// referenced by `message_ref`.
+    ///
+    /// A [`UnixFd`] loaded from the body of the message is an index into the
+    /// returned list. File descriptors which aren't taken are closed once the
+    /// next message is received, and file descriptors are never attached to
+    /// deferred messages.
+    ///
+    /// Only [`Connection`] receives file descriptors, and only with the `libc`
+    /// feature enabled, in which case it negotiates passing them with the bus
+    /// during authentication.
+    ///
+    /// [`UnixFd`]: crate::UnixFd
+    /// [`Connection`]: crate::Connection
+    ///
+    /// # Errors
+    ///
+    /// If the reference was not taken from this buffer, or another message has
+    /// been received since the reference was taken, this errors with an error
+    /// for which [`Error::is_stale_message`] returns `true`.
+    ///
+    /// If the file descriptors passed alongside the message didn't all fit
+    /// when they were received, this errors with a recoverable
+    /// [`Error::protocol_violation`].
+    #[cfg(unix)]
+    pub fn take_unix_fds(&mut self, message_ref: &MessageRef) -> Result<Vec<OwnedFd>> {
+        match &self.last_message {
+            Some(last) if last.id == message_ref.id && last.epoch == message_ref.epoch => {
+                #[cfg(all(feature = "tokio", feature = "libc"))]
+                if self.unix_fds_truncated {
+                    let mut header = self.buf.as_aligned().load::<proto::Header>()?;
+                    header.adjust(self.endianness);
+                    // NB: The message has been received in its entirety, so
+                    // the connection is still in sync.
+                    let error = Error::new(ErrorKind::UnixFdsTruncated);
+                    return Err(Error::new_protocol_violation(header, 0, true, error));
+                }
+
+                Ok(std::mem::take(&mut self.unix_fds))
+            }
+            _ => Err(Error::new(ErrorKind::StaleMessage)),
+        }
     }
 
     /// Read the last message buffered.
@@ -148,6 +566,90 @@ impl RecvBuf {
     pub fn last_message_no_deferred(&self) -> Result<Message<'_>> {
         last_message(&self.last_message, &self.buf, self.endianness)
     }
+
+    /// Get a reference to the last message received into the buffer.
+    ///
+    /// This does not include deferred messages. The reference can be converted
+    /// into a [`Message`] using [`read_message()`] for as long as no other
+    /// message has been received.
+    ///
+    /// [`read_message()`]: Self::read_message
+    ///
+    /// # Errors
+    ///
+    /// In case there is no message buffered.
+    pub fn last_message_ref(&self) -> Result<MessageRef> {
+        match self.last_message {
+            Some(message_ref) => Ok(message_ref),
+            None => Err(Error::new(ErrorKind::MissingMessage)),
+        }
+    }
+
+    /// Read the message referenced by `message_ref`.
+    ///
+    /// # Errors
+    ///
+    /// If the reference was not taken from this buffer, or another message has
+    /// been received since the reference was taken, this errors with an error
+    /// for which [`Error::is_stale_message`] returns `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::RecvBuf;
+    ///
+    /// let recv = RecvBuf::new();
+    /// assert!(recv.last_message_ref().is_err());
+    /// ```
+    pub fn read_message(&self, message_ref: &MessageRef) -> Result<Message<'_>> {
+        match &self.last_message {
+            Some(last) if last.id == message_ref.id && last.epoch == message_ref.epoch => {
+                last_message(&self.last_message, &self.buf, self.endianness)
+            }
+            _ => Err(Error::new(ErrorKind::StaleMessage)),
+        }
+    }
+
+    /// Iterate over the raw header fields of the message referenced by
+    /// `message_ref`.
+    ///
+    /// This includes fields which are not interpreted by this crate, such as
+    /// those added by extensions to the specification.
+    ///
+    /// # Errors
+    ///
+    /// If the reference was not taken from this buffer, or another message has
+    /// been received since the reference was taken, this errors with an error
+    /// for which [`Error::is_stale_message`] returns `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_dbus::Connection;
+    ///
+    /// # #[tokio::main] async fn main() -> tokio_dbus::Result<()> {
+    /// let mut c = Connection::session_bus().await?;
+    /// c.wait_no_deferred().await?;
+    ///
+    /// let message_ref = c.last_message_ref()?;
+    ///
+    /// for field in c.header_fields(&message_ref)? {
+    ///     let field = field?;
+    ///     println!("{:?}: {}", field.code(), field.value().display());
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn header_fields(&self, message_ref: &MessageRef) -> Result<HeaderFields<'_>> {
+        match &self.last_message {
+            Some(last) if last.id == message_ref.id && last.epoch == message_ref.epoch => {
+                let mut buf = self.buf.as_aligned();
+                buf.advance(HEADER_FIELDS_START)?;
+                let mut buf = Body::from_raw_parts(buf, self.endianness, Signature::empty());
+                Ok(HeaderFields::new(buf.read_until(last.headers)))
+            }
+            _ => Err(Error::new(ErrorKind::StaleMessage)),
+        }
+    }
 }
 
 fn last_message<'a>(
@@ -159,15 +661,41 @@ fn last_message<'a>(
         return Err(Error::new(ErrorKind::MissingMessage));
     };
 
+    let mut offset = 0;
+
+    match parse_message(message_ref, buf, endianness, &mut offset) {
+        Ok(message) => Ok(message),
+        Err(error) => {
+            let mut header = buf.as_aligned().load::<proto::Header>()?;
+            header.adjust(endianness);
+            // NB: The message has been received in its entirety, so the
+            // connection is still in sync.
+            Err(Error::new_protocol_violation(header, offset, true, error))
+        }
+    }
+}
+
+/// Parse the message referenced by `message_ref`, keeping track of the offset
+/// being parsed in `offset` for diagnostics.
+fn parse_message<'a>(
+    message_ref: &MessageRef,
+    buf: &'a AlignedBuf,
+    endianness: Endianness,
+    offset: &mut usize,
+) -> Result<Message<'a>> {
     let MessageRef {
+        id: _,
+        epoch: _,
         serial,
         message_type,
         flags,
         headers,
+        received: _,
+        kernel_timestamp,
     } = *message_ref;
 
     let mut buf = buf.as_aligned();
-    buf.advance(size_of::<proto::Header>() + size_of::<u32>())?;
+    buf.advance(HEADER_FIELDS_START)?;
 
     let mut path = None;
     let mut interface = None;
@@ -177,6 +705,9 @@ fn last_message<'a>(
     let mut destination = None;
     let mut signature = Signature::empty();
     let mut sender = None;
+    let mut correlation_id = None;
+    let mut unix_fds = 0;
+    let mut seen = [false; 256];
 
     // Use a `Body` abstraction here, since we need to adjust the headers by
     // the received endianness.
@@ -185,9 +716,23 @@ fn last_message<'a>(
     let mut st = buf.read_until(headers);
 
     while !st.is_empty() {
+        *offset = HEADER_FIELDS_START + headers - st.len();
         // NB: Structs are aligned to 8 bytes.
         st.align::<u64>()?;
         let variant = st.load::<proto::Variant>()?;
+
+        // NB: Unknown header fields are ignored, so only duplicates of known
+        // fields are rejected.
+        if proto::Variant::from_repr(variant.get()).is_some() {
+            let seen = &mut seen[usize::from(variant.get())];
+
+            if *seen {
+                return Err(Error::new(ErrorKind::DuplicateHeaderField(variant)));
+            }
+
+            *seen = true;
+        }
+
         let sig = st.read::<Signature>()?;
 
         match (variant, sig.as_bytes()) {
@@ -217,14 +762,28 @@ fn last_message<'a>(
             (proto::Variant::SENDER, b"s") => {
                 sender = Some(st.read::<str>()?);
             }
+            (proto::Variant::CORRELATION_ID, b"s") => {
+                correlation_id = Some(st.read::<str>()?);
+            }
+            (proto::Variant::UNIX_FDS, b"u") => {
+                unix_fds = st.load::<u32>()?;
+            }
             (_, _) => {
                 crate::signature::skip(sig, &mut st)?;
             }
         }
     }
 
+    *offset = HEADER_FIELDS_START + headers;
     buf.align::<u64>()?;
 
+    if signature.is_empty() != buf.is_empty() {
+        return Err(Error::new(ErrorKind::BodySignatureMismatch(
+            signature.into(),
+            buf.len(),
+        )));
+    }
+
     let kind = match message_type {
         proto::MessageType::METHOD_CALL => {
             let Some(path) = path else {
@@ -259,6 +818,14 @@ fn last_message<'a>(
             }
         }
         proto::MessageType::SIGNAL => {
+            if path.is_none() {
+                return Err(Error::new(ErrorKind::MissingPath));
+            }
+
+            if interface.is_none() {
+                return Err(Error::new(ErrorKind::MissingInterface));
+            }
+
             let Some(member) = member else {
                 return Err(Error::new(ErrorKind::MissingMember));
             };
@@ -270,12 +837,19 @@ fn last_message<'a>(
 
     Ok(Message {
         kind,
+        // NB: The object path of a method call is part of its kind.
+        path: match kind {
+            MessageKind::Signal { .. } => path,
+            _ => None,
+        },
         serial,
         flags,
         interface,
         destination,
         sender,
-        body: buf.with_signature(signature),
+        correlation_id,
+        kernel_timestamp,
+        body: buf.with_signature(signature).with_unix_fds(unix_fds),
     })
 }
 
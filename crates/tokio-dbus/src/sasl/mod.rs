@@ -1,4 +1,20 @@
-//! Types related to SASL authentication which D-Bus performs.
+//! Types and utilities related to the SASL authentication which D-Bus
+//! performs.
+//!
+//! Payloads of SASL messages are encoded as ASCII hex. For the `EXTERNAL`
+//! mechanism on unix, the payload is the ASCII hex encoding of the user id
+//! formatted as a decimal string. So the user id `1000` is encoded as the
+//! string `"1000"`, which is sent as `31303030`.
+//!
+//! # Examples
+//!
+//! ```
+//! use tokio_dbus::sasl;
+//!
+//! let mut buf = [0; 20];
+//! assert_eq!(sasl::encode_uid(1000, &mut buf), b"31303030");
+//! assert_eq!(sasl::decode_uid(b"31303030"), Some(1000));
+//! ```
 
 #[cfg(test)]
 mod tests;
@@ -7,11 +23,157 @@ use core::fmt;
 
 use crate::lossy_str::LossyStr;
 
+/// The lowercase hex alphabet.
+const HEX: [u8; 16] = *b"0123456789abcdef";
+
+/// The maximum length of a hex-encoded user id, which is two hex digits for
+/// each of the at most ten decimal digits of a `u32`.
+pub const MAX_UID_LENGTH: usize = 20;
+
+/// Hex-encode `bytes` into `out` using lowercase digits.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::sasl;
+///
+/// let mut out = Vec::new();
+/// sasl::encode_hex(b"S-1-5", &mut out);
+/// assert_eq!(out, b"532d312d35");
+/// ```
+pub fn encode_hex(bytes: &[u8], out: &mut Vec<u8>) {
+    out.reserve(bytes.len() * 2);
+
+    for &b in bytes {
+        out.push(HEX[(b >> 4) as usize]);
+        out.push(HEX[(b & 0xf) as usize]);
+    }
+}
+
+/// Decode hex-encoded `hex` into `out`, accepting both lowercase and
+/// uppercase digits.
+///
+/// Returns `false` if `hex` has an odd length or contains anything other than
+/// hex digits, in which case the contents of `out` is unspecified.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::sasl;
+///
+/// let mut out = Vec::new();
+/// assert!(sasl::decode_hex(b"532D312d35", &mut out));
+/// assert_eq!(out, b"S-1-5");
+///
+/// assert!(!sasl::decode_hex(b"532", &mut out));
+/// assert!(!sasl::decode_hex(b"5x", &mut out));
+/// ```
+pub fn decode_hex(hex: &[u8], out: &mut Vec<u8>) -> bool {
+    if hex.len() % 2 != 0 {
+        return false;
+    }
+
+    out.reserve(hex.len() / 2);
+
+    for pair in hex.chunks_exact(2) {
+        let (Some(hi), Some(lo)) = (hex_value(pair[0]), hex_value(pair[1])) else {
+            return false;
+        };
+
+        out.push(hi << 4 | lo);
+    }
+
+    true
+}
+
+/// Encode a user id as used by the `EXTERNAL` mechanism, which is the ASCII
+/// hex encoding of its decimal representation.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::sasl;
+///
+/// let mut buf = [0; sasl::MAX_UID_LENGTH];
+/// assert_eq!(sasl::encode_uid(0, &mut buf), b"30");
+/// assert_eq!(sasl::encode_uid(1000, &mut buf), b"31303030");
+/// assert_eq!(sasl::encode_uid(u32::MAX, &mut buf), b"34323934393637323935");
+/// ```
+pub fn encode_uid(uid: u32, buf: &mut [u8; MAX_UID_LENGTH]) -> &[u8] {
+    let mut digits = [0; MAX_UID_LENGTH / 2];
+    let mut start = digits.len();
+    let mut uid = uid;
+
+    loop {
+        start -= 1;
+        digits[start] = (uid % 10) as u8 + b'0';
+        uid /= 10;
+
+        if uid == 0 {
+            break;
+        }
+    }
+
+    let digits = &digits[start..];
+
+    for (out, &digit) in buf.chunks_exact_mut(2).zip(digits) {
+        out[0] = HEX[(digit >> 4) as usize];
+        out[1] = HEX[(digit & 0xf) as usize];
+    }
+
+    &buf[..digits.len() * 2]
+}
+
+/// Decode a user id encoded as by [`encode_uid`].
+///
+/// Returns `None` if `hex` is not the hex encoding of a decimal number which
+/// fits in a `u32`.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::sasl;
+///
+/// assert_eq!(sasl::decode_uid(b"31303030"), Some(1000));
+/// assert_eq!(sasl::decode_uid(b"34323934393637323936"), None);
+/// assert_eq!(sasl::decode_uid(b""), None);
+/// ```
+pub fn decode_uid(hex: &[u8]) -> Option<u32> {
+    if hex.is_empty() || hex.len() > MAX_UID_LENGTH || hex.len() % 2 != 0 {
+        return None;
+    }
+
+    let mut uid = 0u32;
+
+    for pair in hex.chunks_exact(2) {
+        let digit = hex_value(pair[0])? << 4 | hex_value(pair[1])?;
+
+        if !digit.is_ascii_digit() {
+            return None;
+        }
+
+        uid = uid.checked_mul(10)?.checked_add(u32::from(digit - b'0'))?;
+    }
+
+    Some(uid)
+}
+
+/// Get the value of a single hex digit.
+fn hex_value(b: u8) -> Option<u8> {
+    match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        b'A'..=b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
+
 /// A GUID sent over SASL.
 #[repr(transparent)]
 pub struct Guid([u8]);
 
 impl Guid {
+    #[cfg(feature = "tokio")]
     #[inline]
     pub(crate) fn new(guid: &[u8]) -> &Guid {
         // SAFETY: The byte slice is repr transparent over this type.
@@ -29,15 +191,25 @@ impl fmt::Debug for Guid {
 }
 
 /// A SASL message.
-pub enum SaslRequest<'a> {
+#[cfg(feature = "tokio")]
+pub(crate) enum SaslRequest<'a> {
     /// The AUTH message.
     Auth(Auth<'a>),
+    /// The NEGOTIATE_UNIX_FD message.
+    #[cfg(feature = "libc")]
+    NegotiateUnixFd,
 }
 
 /// A SASL message.
-pub enum SaslResponse<'a> {
+#[cfg(feature = "tokio")]
+pub(crate) enum SaslResponse<'a> {
     /// The OK message.
     Ok(#[allow(unused)] &'a Guid),
+    /// The ERROR message.
+    Error,
+    /// The AGREE_UNIX_FD message.
+    #[cfg(feature = "libc")]
+    AgreeUnixFd,
 }
 
 /// The SASL authentication method.
@@ -48,35 +220,34 @@ pub enum Auth<'a> {
 }
 
 impl<'a> Auth<'a> {
-    /// Construct external authentication from u32 ascii hex.
+    /// Construct an external authentication from the real user id of the
+    /// current process, using `buf` as storage for the encoded payload.
     #[cfg(all(unix, feature = "libc"))]
     pub fn external_from_uid(buf: &'a mut [u8; 32]) -> Auth<'a> {
         let id = unsafe { libc::getuid() };
         Self::external_from_u32_ascii_hex(buf, id)
     }
 
-    /// Construct an external authentication from a u32.
-    pub fn external_from_u32_ascii_hex(buf: &'a mut [u8; 32], mut id: u32) -> Auth<'a> {
-        const HEX: [u8; 16] = *b"0123456789abcdef";
-
-        let mut n = 0;
-
-        if id == 0 {
-            buf[0] = b'0';
-            buf[1] = b'0';
-            n = 2;
-        } else {
-            while id > 0 {
-                let byte = (id % 10) as u8 + b'0';
-                buf[n] = HEX[(byte & 0xf) as usize];
-                n += 1;
-                buf[n] = HEX[(byte >> 4) as usize];
-                n += 1;
-                id /= 10;
-            }
-        }
-
-        buf[..n].reverse();
-        Auth::External(&buf[..n])
+    /// Construct an external authentication from a user id, using `buf` as
+    /// storage for the encoded payload.
+    ///
+    /// See [`encode_uid`] for how the user id is encoded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::Auth;
+    ///
+    /// assert_eq!(
+    ///     Auth::external_from_u32_ascii_hex(&mut [0; 32], 1000),
+    ///     Auth::External(b"31303030")
+    /// );
+    /// ```
+    pub fn external_from_u32_ascii_hex(buf: &'a mut [u8; 32], id: u32) -> Auth<'a> {
+        let mut encoded = [0; MAX_UID_LENGTH];
+        let encoded = encode_uid(id, &mut encoded);
+        let buf = &mut buf[..encoded.len()];
+        buf.copy_from_slice(encoded);
+        Auth::External(buf)
     }
 }
@@ -1,4 +1,4 @@
-use super::Auth;
+use super::{decode_hex, decode_uid, encode_hex, encode_uid, Auth, MAX_UID_LENGTH};
 
 #[test]
 fn test_external_from_uid() {
@@ -12,6 +12,88 @@ fn test_external_from_uid() {
     );
     assert_eq!(
         Auth::external_from_u32_ascii_hex(&mut [0; 32], 0),
-        Auth::External(b"00")
+        Auth::External(b"30")
     );
 }
+
+#[test]
+fn test_encode_hex() {
+    let mut out = Vec::new();
+    encode_hex(b"S-1-5", &mut out);
+    assert_eq!(out, b"532d312d35");
+}
+
+#[test]
+fn test_hex_roundtrip() {
+    let bytes = (0..=u8::MAX).collect::<Vec<_>>();
+
+    let mut hex = Vec::new();
+    encode_hex(&bytes, &mut hex);
+    assert_eq!(hex.len(), bytes.len() * 2);
+
+    let mut out = Vec::new();
+    assert!(decode_hex(&hex, &mut out));
+    assert_eq!(out, bytes);
+
+    let mut out = Vec::new();
+    assert!(decode_hex(&hex.to_ascii_uppercase(), &mut out));
+    assert_eq!(out, bytes);
+}
+
+#[test]
+fn test_decode_hex_invalid() {
+    for hex in [&b"3"[..], b"3g", b"g3", b" 30", b"30 ", b"-1"] {
+        assert!(!decode_hex(hex, &mut Vec::new()), "{hex:?}");
+    }
+}
+
+#[test]
+fn test_uid_roundtrip() {
+    let mut buf = [0; MAX_UID_LENGTH];
+
+    let uids = (0..=100_000)
+        .chain((0..32).map(|n| 1 << n))
+        .chain((1..=10).map(|n| 10u32.saturating_pow(n) - 1))
+        .chain([u32::MAX - 1, u32::MAX]);
+
+    for uid in uids {
+        let encoded = encode_uid(uid, &mut buf);
+
+        let expected = uid
+            .to_string()
+            .bytes()
+            .flat_map(|b| format!("{b:02x}").into_bytes())
+            .collect::<Vec<_>>();
+
+        assert_eq!(encoded, expected, "{uid}");
+        assert_eq!(decode_uid(encoded), Some(uid), "{uid}");
+    }
+}
+
+#[test]
+fn test_decode_uid_invalid() {
+    let cases: &[&[u8]] = &[
+        // Empty.
+        b"",
+        // Odd length.
+        b"313",
+        // Not hex.
+        b"3x",
+        // Not a decimal digit ("a").
+        b"61",
+        // Sign ("+1").
+        b"2b31",
+        // Overflow (4294967296).
+        b"34323934393637323936",
+        // Too long (00000000001).
+        b"3030303030303030303031",
+    ];
+
+    for &hex in cases {
+        assert_eq!(decode_uid(hex), None, "{:?}", String::from_utf8_lossy(hex));
+    }
+
+    // Leading zeros are accepted.
+    assert_eq!(decode_uid(b"3030"), Some(0));
+    assert_eq!(decode_uid(b"3031"), Some(1));
+}
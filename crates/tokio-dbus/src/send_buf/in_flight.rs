@@ -0,0 +1,99 @@
+use std::collections::VecDeque;
+use std::num::NonZeroU32;
+
+use crate::utils::Instant;
+
+use super::pending::DEFAULT_REPLY_TIMEOUT;
+
+/// Table of received method calls which haven't been replied to yet.
+///
+/// Calls are only tracked once a maximum has been set.
+pub(super) struct InFlightCalls {
+    /// The maximum number of calls in flight, if any.
+    pub(super) max: Option<usize>,
+    /// The sender and serial of each call in flight, in the order they were
+    /// accepted, and when they were accepted.
+    calls: VecDeque<(Option<Box<str>>, NonZeroU32, Instant)>,
+}
+
+impl InFlightCalls {
+    pub(super) fn new() -> Self {
+        Self {
+            max: None,
+            calls: VecDeque::new(),
+        }
+    }
+
+    /// Get the number of calls in flight.
+    #[inline]
+    pub(super) fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    /// Set the maximum number of calls in flight.
+    pub(super) fn set_max(&mut self, max: Option<usize>) {
+        self.max = max;
+
+        if max.is_none() {
+            self.calls.clear();
+        }
+    }
+
+    /// Accept a call, returns `false` if the maximum number of calls in flight
+    /// has been reached.
+    ///
+    /// Calls which are already in flight are accepted again without counting
+    /// them twice.
+    pub(super) fn insert(
+        &mut self,
+        sender: Option<&str>,
+        serial: NonZeroU32,
+        now: Instant,
+    ) -> bool {
+        let Some(max) = self.max else {
+            return true;
+        };
+
+        if self.position(sender, serial).is_some() {
+            return true;
+        }
+
+        self.expire(now);
+
+        if self.calls.len() >= max {
+            return false;
+        }
+
+        self.calls.push_back((sender.map(Box::from), serial, now));
+        true
+    }
+
+    /// Remove the call being replied to, returns `true` if it was in flight.
+    pub(super) fn remove(&mut self, destination: Option<&str>, reply_serial: NonZeroU32) -> bool {
+        let Some(index) = self.position(destination, reply_serial) else {
+            return false;
+        };
+
+        self.calls.remove(index);
+        true
+    }
+
+    fn position(&self, sender: Option<&str>, serial: NonZeroU32) -> Option<usize> {
+        self.calls
+            .iter()
+            .position(|(s, n, _)| *n == serial && s.as_deref() == sender)
+    }
+
+    /// Expire calls which have been in flight for longer than callers wait for
+    /// a reply by default, since a handler which never replies would
+    /// otherwise hold on to its slot indefinitely.
+    fn expire(&mut self, now: Instant) {
+        while let Some(&(_, _, accepted)) = self.calls.front() {
+            if now.saturating_duration_since(accepted) < DEFAULT_REPLY_TIMEOUT {
+                break;
+            }
+
+            self.calls.pop_front();
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use crate::error::Result;
+use crate::Message;
+
+/// A transformer which inspects and modifies outgoing messages before they are
+/// written to a [`SendBuf`].
+///
+/// Middleware is registered with [`SendBuf::push_middleware`], and is applied
+/// to every message written through [`SendBuf::write_message`] in the order in
+/// which it was registered. Returning an error rejects the message, in which
+/// case nothing is written.
+///
+/// The returned message may borrow from the middleware, which allows it to
+/// stamp messages with values it owns.
+///
+/// [`SendBuf`]: crate::SendBuf
+/// [`SendBuf::push_middleware`]: crate::SendBuf::push_middleware
+/// [`SendBuf::write_message`]: crate::SendBuf::write_message
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{Message, Middleware, ObjectPath, Result, SendBuf};
+///
+/// struct Stamp {
+///     sender: String,
+///     written: usize,
+/// }
+///
+/// impl Middleware for Stamp {
+///     fn outgoing<'a>(&'a mut self, message: Message<'a>) -> Result<Message<'a>> {
+///         self.written += 1;
+///         Ok(message.with_sender(&self.sender))
+///     }
+/// }
+///
+/// let mut send = SendBuf::new();
+///
+/// send.push_middleware(Stamp {
+///     sender: String::from(":1.42"),
+///     written: 0,
+/// });
+///
+/// let m = send.method_call(ObjectPath::ROOT, "Hello");
+/// send.write_message(m)?;
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+pub trait Middleware {
+    /// Process an outgoing message, returning the message to write in its
+    /// place.
+    fn outgoing<'a>(&'a mut self, message: Message<'a>) -> Result<Message<'a>>;
+}
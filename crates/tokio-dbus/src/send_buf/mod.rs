@@ -0,0 +1,1025 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::VecDeque;
+use std::io;
+use std::mem;
+use std::num::NonZeroU32;
+use std::time::Duration;
+
+use crate::buf::UnalignedBuf;
+use crate::error::{Error, ErrorKind, Result};
+use crate::signature::{SignatureBuilder, SignatureError};
+use crate::utils::{write_some, Instant};
+use crate::{limits, proto, Endianness, Flags, Write};
+
+use self::pending::PendingReplies;
+mod pending;
+
+use self::in_flight::InFlightCalls;
+mod in_flight;
+
+pub use self::transaction::Transaction;
+mod transaction;
+
+pub use self::middleware::Middleware;
+mod middleware;
+use crate::org_freedesktop_dbus::LIMITS_EXCEEDED;
+use crate::{BodyBuf, Message, MessageKind, ObjectPath, Signature};
+
+/// Buffer used for sending messages through D-Bus.
+pub struct SendBuf {
+    buf: UnalignedBuf,
+    /// The lengths of the messages in the buffer, which haven't been
+    /// completely sent yet.
+    frames: VecDeque<usize>,
+    /// The number of bytes of the first message in the buffer that have been
+    /// sent.
+    sent: usize,
+    serial: u32,
+    pending: PendingReplies,
+    in_flight: InFlightCalls,
+    /// How long queued messages can go without being sent before writing
+    /// further messages fails.
+    watchdog: Option<Duration>,
+    /// When messages were queued in an empty buffer, or when sending last made
+    /// progress. Only tracked if the watchdog is enabled.
+    stalled_since: Option<Instant>,
+    /// The number of bytes of a streamed byte array which remain to be
+    /// written, see [`SendBuf::begin_byte_array`].
+    streaming: usize,
+    /// Middleware applied to outgoing messages, in order.
+    middleware: Vec<Box<dyn Middleware + Send + Sync>>,
+    /// Serials of method calls which started waiting for a reply while a
+    /// checkpoint was open, see [`SendBuf::checkpoint`].
+    journal: Vec<NonZeroU32>,
+    /// The number of open checkpoints.
+    checkpoints: usize,
+}
+
+impl SendBuf {
+    /// Construct a new send buffer.
+    pub fn new() -> Self {
+        Self {
+            buf: UnalignedBuf::new(),
+            frames: VecDeque::new(),
+            sent: 0,
+            serial: 0,
+            pending: PendingReplies::new(),
+            in_flight: InFlightCalls::new(),
+            watchdog: None,
+            stalled_since: None,
+            streaming: 0,
+            middleware: Vec::new(),
+            journal: Vec::new(),
+            checkpoints: 0,
+        }
+    }
+
+    /// Register middleware which is applied to outgoing messages.
+    ///
+    /// Middleware is applied in the order in which it's registered, before
+    /// the message is validated and serialized. Messages written through
+    /// [`Connection::write_broadcast`] are pre-encoded and bypass middleware.
+    ///
+    /// See [`Middleware`] for more details.
+    ///
+    /// [`Connection::write_broadcast`]: crate::Connection::write_broadcast
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{Flags, Message, Middleware, ObjectPath, Result, SendBuf};
+    ///
+    /// struct NoReply;
+    ///
+    /// impl Middleware for NoReply {
+    ///     fn outgoing<'a>(&'a mut self, message: Message<'a>) -> Result<Message<'a>> {
+    ///         let flags = message.flags() | Flags::NO_REPLY_EXPECTED;
+    ///         Ok(message.with_flags(flags))
+    ///     }
+    /// }
+    ///
+    /// let mut send = SendBuf::new();
+    /// send.set_max_pending_replies(Some(0));
+    /// send.push_middleware(NoReply);
+    ///
+    /// let m = send.method_call(ObjectPath::ROOT, "Notify");
+    /// send.write_message(m)?;
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn push_middleware<M>(&mut self, middleware: M)
+    where
+        M: 'static + Middleware + Send + Sync,
+    {
+        self.middleware.push(Box::new(middleware));
+    }
+
+    /// Set the maximum number of method calls which can be awaiting a reply
+    /// at the same time.
+    ///
+    /// Once the limit is reached, writing another method call which expects a
+    /// reply fails with an error for which
+    /// [`Error::is_too_many_pending_replies`] returns `true`. Pending replies
+    /// are released when a reply is received through a [`Connection`] or
+    /// [`SendBuf::complete_reply`], or when they expire as configured by
+    /// [`SendBuf::set_reply_timeout`].
+    ///
+    /// Defaults to `None`, which means that the number of pending replies is
+    /// only bound by their expiry.
+    ///
+    /// [`Connection`]: crate::Connection
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    /// send.set_max_pending_replies(Some(1));
+    ///
+    /// let m = send.method_call(PATH, "Hello");
+    /// let serial = m.serial();
+    /// send.write_message(m)?;
+    ///
+    /// let m = send.method_call(PATH, "Hello");
+    /// let error = send.write_message(m.clone()).unwrap_err();
+    /// assert!(error.is_too_many_pending_replies());
+    ///
+    /// assert!(send.complete_reply(serial));
+    /// send.write_message(m)?;
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn set_max_pending_replies(&mut self, max: Option<usize>) {
+        self.pending.max = max;
+    }
+
+    /// Set the time after which a method call which hasn't received a reply
+    /// is no longer considered to be pending.
+    ///
+    /// Defaults to 25 seconds.
+    pub fn set_reply_timeout(&mut self, timeout: Duration) {
+        self.pending.timeout = timeout;
+    }
+
+    /// Set how long queued messages can go without being sent before writing
+    /// further messages fails.
+    ///
+    /// This catches the mistake of writing messages without driving the
+    /// connection, such as through [`Connection::wait`] or
+    /// [`Connection::flush`], in which case nothing is ever sent. Once queued
+    /// messages have made no progress for longer than `period`,
+    /// [`SendBuf::write_message`] fails with an error for which
+    /// [`Error::is_not_driven`] returns `true`. With the `tracing` feature
+    /// enabled a warning is also emitted.
+    ///
+    /// Defaults to `None`, which disables the watchdog.
+    ///
+    /// [`Connection::wait`]: crate::Connection::wait
+    /// [`Connection::flush`]: crate::Connection::flush
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use tokio_dbus::SendBuf;
+    ///
+    /// let mut send = SendBuf::new();
+    /// send.set_watchdog(Some(Duration::ZERO));
+    ///
+    /// let m = send.signal("Tick").with_interface("se.tedro.Example");
+    /// send.write_message(m.clone())?;
+    ///
+    /// let error = send.write_message(m).unwrap_err();
+    /// assert!(error.is_not_driven());
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn set_watchdog(&mut self, period: Option<Duration>) {
+        self.watchdog = period;
+
+        if period.is_none() {
+            self.stalled_since = None;
+        }
+    }
+
+    /// Get the number of method calls which are awaiting a reply.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{Flags, ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.method_call(PATH, "Hello");
+    /// send.write_message(m)?;
+    ///
+    /// let m = send.method_call(PATH, "Hello").with_flags(Flags::NO_REPLY_EXPECTED);
+    /// send.write_message(m)?;
+    ///
+    /// let m = send.signal("Tick");
+    /// send.write_message(m)?;
+    ///
+    /// assert_eq!(send.pending_replies(), 1);
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn pending_replies(&self) -> usize {
+        self.pending.serials.len()
+    }
+
+    /// Mark the method call with the given serial as replied to, returns
+    /// `true` if it was awaiting a reply.
+    ///
+    /// This is done automatically for replies received through a
+    /// [`Connection`].
+    ///
+    /// [`Connection`]: crate::Connection
+    pub fn complete_reply(&mut self, serial: NonZeroU32) -> bool {
+        self.pending.remove(serial)
+    }
+
+    /// Set the maximum number of received method calls which can be handled
+    /// at the same time.
+    ///
+    /// A call is in flight from when it's accepted through
+    /// [`SendBuf::accept_call`] until a reply to it is written, which lets
+    /// handlers defer their replies without an unbounded number of calls
+    /// piling up. Calls which haven't been replied to within the default reply
+    /// timeout of 25 seconds are no longer considered to be in flight, since
+    /// the caller has given up on them.
+    ///
+    /// [`ObjectRegistry::dispatch`] and [`Connection::serve_until`] accept
+    /// calls before handling them, so once the limit is reached further calls
+    /// are answered with a [`LIMITS_EXCEEDED`] error.
+    ///
+    /// Defaults to `None`, which means that calls in flight are not tracked.
+    ///
+    /// [`ObjectRegistry::dispatch`]: crate::ObjectRegistry::dispatch
+    /// [`Connection::serve_until`]: crate::Connection::serve_until
+    /// [`LIMITS_EXCEEDED`]: crate::org_freedesktop_dbus::LIMITS_EXCEEDED
+    pub fn set_max_in_flight_calls(&mut self, max: Option<usize>) {
+        self.in_flight.set_max(max);
+    }
+
+    /// Get the number of received method calls which are in flight, see
+    /// [`SendBuf::set_max_in_flight_calls`].
+    pub fn in_flight_calls(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Accept a received method call for handling, returns `false` if the
+    /// maximum number of calls in flight has been reached.
+    ///
+    /// If the call isn't accepted, a [`LIMITS_EXCEEDED`] error is written in
+    /// reply to it using `body` as a scratch buffer, so the caller should skip
+    /// handling it. Messages which aren't method calls expecting a reply are
+    /// always accepted without being tracked.
+    ///
+    /// [`LIMITS_EXCEEDED`]: crate::org_freedesktop_dbus::LIMITS_EXCEEDED
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, ObjectPath, SendBuf};
+    ///
+    /// let mut send = SendBuf::new();
+    /// let mut body = BodyBuf::new();
+    /// send.set_max_in_flight_calls(Some(1));
+    ///
+    /// let first = send.method_call(ObjectPath::ROOT, "Slow").to_owned();
+    /// let second = send.method_call(ObjectPath::ROOT, "Slow").to_owned();
+    ///
+    /// assert!(send.accept_call(&first.borrow(), &mut body)?);
+    /// assert!(!send.accept_call(&second.borrow(), &mut body)?);
+    ///
+    /// // Replying to the first call makes room for another one.
+    /// let serial = send.next_serial();
+    /// send.write_message(first.borrow().method_return(serial))?;
+    /// assert_eq!(send.in_flight_calls(), 0);
+    /// assert!(send.accept_call(&second.borrow(), &mut body)?);
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn accept_call(&mut self, message: &Message<'_>, body: &mut BodyBuf) -> Result<bool> {
+        let MessageKind::MethodCall { .. } = message.kind else {
+            return Ok(true);
+        };
+
+        if message.flags & Flags::NO_REPLY_EXPECTED
+            || self
+                .in_flight
+                .insert(message.sender, message.serial, Instant::now())
+        {
+            return Ok(true);
+        }
+
+        body.clear();
+        body.store(format!("Too many method calls in flight ({})", self.in_flight.len()).as_str())?;
+        let serial = self.next_serial();
+        self.write_message(message.error(LIMITS_EXCEEDED, serial).with_body(&*body))?;
+        Ok(false)
+    }
+
+    /// Stop tracking a call which was accepted through
+    /// [`SendBuf::accept_call`], such as when its handler failed without
+    /// replying.
+    pub(crate) fn cancel_call(&mut self, message: &Message<'_>) {
+        self.in_flight.remove(message.sender, message.serial);
+    }
+
+    /// Access the underlying buffer.
+    pub(crate) fn buf(&mut self) -> &UnalignedBuf {
+        &self.buf
+    }
+
+    /// Access the underlying buffer mutably.
+    pub(crate) fn buf_mut(&mut self) -> &mut UnalignedBuf {
+        &mut self.buf
+    }
+
+    /// Reserve room for `additional` bytes of messages in the buffer.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        self.buf.reserve_bytes(additional);
+    }
+
+    /// Write the messages which are queued in the buffer to `writer`.
+    ///
+    /// This is what [`Connection`] uses to send messages, and can be used to
+    /// send messages over a custom transport. Bytes are only removed from the
+    /// buffer once they have been written, so if `writer` would block the
+    /// remainder of any partially written message is written by the next
+    /// call.
+    ///
+    /// [`Connection`]: crate::Connection
+    ///
+    /// # Errors
+    ///
+    /// Errors if writing fails, where an error for which
+    /// [`Error::is_would_block`] returns `true` indicates that `writer` would
+    /// block.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{ObjectPath, RecvBuf, SendBuf};
+    ///
+    /// let mut send = SendBuf::new();
+    /// let m = send
+    ///     .signal("Tick")
+    ///     .with_path(ObjectPath::ROOT)
+    ///     .with_interface("se.tedro.Example");
+    /// send.write_message(m)?;
+    ///
+    /// let mut bytes = Vec::new();
+    /// send.write_to(&mut bytes)?;
+    ///
+    /// let mut recv = RecvBuf::new();
+    /// let message_ref = recv.read_from(&mut &bytes[..])?;
+    /// assert_eq!(recv.read_message(&message_ref)?.member(), Some("Tick"));
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn write_to<W>(&mut self, writer: &mut W) -> Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        while !self.buf.is_empty() {
+            let n = write_some(writer, self.buf.get())?;
+            self.advance(n);
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Record that everything written to the buffer after `start` is a single
+    /// message.
+    pub(crate) fn end_message(&mut self, start: usize) {
+        self.frames.push_back(self.buf.len() - start);
+
+        if self.watchdog.is_some() && self.stalled_since.is_none() {
+            self.stalled_since = Some(Instant::now());
+        }
+    }
+
+    /// Mark `n` bytes at the front of the buffer as sent.
+    pub(crate) fn advance(&mut self, n: usize) {
+        self.buf.advance(n);
+        self.sent += n;
+
+        while let Some(&len) = self.frames.front() {
+            if self.sent < len {
+                break;
+            }
+
+            self.sent -= len;
+            self.frames.pop_front();
+        }
+
+        if self.watchdog.is_some() {
+            self.stalled_since = (!self.buf.is_empty()).then(Instant::now);
+        }
+    }
+
+    /// Check that queued messages have been sent within the watchdog period.
+    fn check_watchdog(&self) -> Result<()> {
+        let (Some(period), Some(since)) = (self.watchdog, self.stalled_since) else {
+            return Ok(());
+        };
+
+        if since.elapsed() < period {
+            return Ok(());
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            ?period,
+            queued = self.frames.len(),
+            "Queued messages are not being sent, is the connection being driven?"
+        );
+
+        Err(Error::new(ErrorKind::NotDriven(period)))
+    }
+
+    /// Test if a message has only been partially sent, in which case the
+    /// remainder of it has to be sent before anything else.
+    #[cfg(any(test, feature = "tokio"))]
+    pub(crate) fn is_partially_sent(&self) -> bool {
+        self.sent > 0
+    }
+
+    /// Get the next serial for this send buffer.
+    ///
+    /// Serials wrap around once exhausted, skipping zero and the serials of
+    /// any method calls which are still awaiting a reply.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::SendBuf;
+    ///
+    /// let mut send = SendBuf::new();
+    /// assert_eq!(send.next_serial().get(), 1);
+    /// assert_eq!(send.next_serial().get(), 2);
+    /// ```
+    pub fn next_serial(&mut self) -> NonZeroU32 {
+        loop {
+            self.serial = self.serial.wrapping_add(1);
+
+            let Some(serial) = NonZeroU32::new(self.serial) else {
+                continue;
+            };
+
+            if self.pending.contains(serial) {
+                continue;
+            }
+
+            break serial;
+        }
+    }
+
+    /// Construct a method call [`Message`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{Message, MessageBuf, ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.method_call(PATH, "Hello").to_owned();
+    /// let m2 = MessageBuf::method_call(PATH.into(), "Hello".into(), m.serial());
+    /// assert_eq!(m, m2);
+    /// ```
+    pub fn method_call<'a>(&mut self, path: &'a ObjectPath, member: &'a str) -> Message<'a> {
+        Message::method_call(path, member, self.next_serial())
+    }
+
+    /// Construct a signal [`Message`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{Message, MessageBuf, SendBuf};
+    ///
+    /// let mut send = SendBuf::new();
+    ///
+    /// let m = send.signal("Hello").to_owned();
+    /// let m2 = MessageBuf::signal("Hello".into(), m.serial());
+    /// assert_eq!(m, m2);
+    /// ```
+    pub fn signal<'a>(&mut self, member: &'a str) -> Message<'a> {
+        Message::signal(member, self.next_serial())
+    }
+
+    /// Write several messages to the buffer, where either all of them are
+    /// queued for sending or none of them are.
+    ///
+    /// If `f` returns an error, every message it wrote is removed from the
+    /// buffer and any method calls it wrote stop being tracked as pending.
+    /// Serials allocated by the transaction are not reused.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{ObjectPath, SendBuf};
+    ///
+    /// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+    ///
+    /// let mut send = SendBuf::new();
+    /// send.set_max_pending_replies(Some(1));
+    ///
+    /// let error = send
+    ///     .transaction(|tx| {
+    ///         let m = tx.signal("Changed").with_interface("se.tedro.Example");
+    ///         tx.write_message(m)?;
+    ///
+    ///         for _ in 0..2 {
+    ///             let m = tx.method_call(PATH, "Hello");
+    ///             tx.write_message(m)?;
+    ///         }
+    ///
+    ///         Ok(())
+    ///     })
+    ///     .unwrap_err();
+    ///
+    /// assert!(error.is_too_many_pending_replies());
+    /// assert_eq!(send.pending_replies(), 0);
+    ///
+    /// let mut bytes = Vec::new();
+    /// send.write_to(&mut bytes)?;
+    /// assert!(bytes.is_empty());
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn transaction<F, T>(&mut self, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Transaction<'_>) -> Result<T>,
+    {
+        let checkpoint = self.checkpoint();
+
+        match f(&mut Transaction::new(self)) {
+            Ok(value) => {
+                self.commit(checkpoint);
+                Ok(value)
+            }
+            Err(error) => {
+                self.rollback(checkpoint);
+                Err(error)
+            }
+        }
+    }
+
+    /// Mark the current end of the buffer, so that the messages written after
+    /// it can be discarded with [`SendBuf::rollback`].
+    ///
+    /// Every checkpoint must be passed to either [`SendBuf::commit`] or
+    /// [`SendBuf::rollback`], innermost first.
+    pub(crate) fn checkpoint(&mut self) -> Checkpoint {
+        self.checkpoints += 1;
+
+        Checkpoint {
+            len: self.buf.len(),
+            frames: self.frames.len(),
+            stalled_since: self.stalled_since,
+            journal: self.journal.len(),
+        }
+    }
+
+    /// Keep the messages written since the given checkpoint.
+    pub(crate) fn commit(&mut self, checkpoint: Checkpoint) {
+        self.close_checkpoint(checkpoint);
+    }
+
+    /// Discard the messages written since the given checkpoint, and stop
+    /// tracking the method calls among them as pending.
+    pub(crate) fn rollback(&mut self, checkpoint: Checkpoint) {
+        for serial in self.journal.drain(checkpoint.journal..) {
+            self.pending.remove(serial);
+        }
+
+        self.buf.truncate(checkpoint.len);
+        self.frames.truncate(checkpoint.frames);
+        self.stalled_since = checkpoint.stalled_since;
+        self.close_checkpoint(checkpoint);
+    }
+
+    fn close_checkpoint(&mut self, checkpoint: Checkpoint) {
+        debug_assert!(self.journal.len() >= checkpoint.journal);
+        self.checkpoints -= 1;
+
+        if self.checkpoints == 0 {
+            self.journal.clear();
+        }
+    }
+
+    /// Write a message to the buffer.
+    ///
+    /// Method calls which expect a reply are tracked as pending until a reply
+    /// is received, see [`SendBuf::set_max_pending_replies`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if the body of the message is too long, or if the message is a
+    /// method call which expects a reply and the maximum number of pending
+    /// replies has been reached.
+    ///
+    /// Errors if the member, interface, error name, destination or sender of
+    /// the message is longer than [`MAX_NAME_LENGTH`], since the bus would
+    /// otherwise disconnect the connection. For such errors
+    /// [`Error::is_limit_exceeded`] returns `true`.
+    ///
+    /// [`MAX_NAME_LENGTH`]: crate::limits::MAX_NAME_LENGTH
+    ///
+    /// A body which doesn't use native endianness is re-encoded to do so, which
+    /// errors if it doesn't match its signature.
+    ///
+    /// Errors if the watchdog configured through [`SendBuf::set_watchdog`] has
+    /// detected that queued messages aren't being sent.
+    pub fn write_message(&mut self, message: Message<'_>) -> Result<()> {
+        self.write_message_with_trailer(message, None)?;
+        Ok(())
+    }
+
+    /// Write a message whose body ends with a byte array `ay` of `len` bytes,
+    /// without having the contents of the array available up front.
+    ///
+    /// The body of `message` holds the arguments which precede the array.
+    /// After this, the contents of the array have to be written through
+    /// [`extend_byte_array()`], which can be done in chunks as the buffer is
+    /// being sent. This bounds memory use when sending large binary payloads.
+    ///
+    /// Until all `len` bytes have been written, writing any other message
+    /// fails, and [`Broadcast`] messages must not be written.
+    ///
+    /// [`extend_byte_array()`]: Self::extend_byte_array
+    /// [`Broadcast`]: crate::Broadcast
+    ///
+    /// # Errors
+    ///
+    /// Errors under the same conditions as [`write_message()`], or if `len`
+    /// exceeds [`MAX_ARRAY_LENGTH`].
+    ///
+    /// [`write_message()`]: Self::write_message
+    /// [`MAX_ARRAY_LENGTH`]: crate::limits::MAX_ARRAY_LENGTH
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{BodyBuf, ObjectPath, RecvBuf, SendBuf};
+    ///
+    /// let mut send = SendBuf::new();
+    /// let mut body = BodyBuf::new();
+    /// body.store("image.png")?;
+    ///
+    /// let m = send.method_call(ObjectPath::ROOT, "Upload").with_body(&body);
+    /// send.begin_byte_array(m, 8)?;
+    ///
+    /// let mut bytes = Vec::new();
+    ///
+    /// for chunk in [b"\x89PNG", b"\r\n\x1a\n"] {
+    ///     send.extend_byte_array(chunk)?;
+    ///     send.write_to(&mut bytes)?;
+    /// }
+    ///
+    /// let mut recv = RecvBuf::new();
+    /// let message_ref = recv.read_from(&mut &bytes[..])?;
+    /// let message = recv.read_message(&message_ref)?;
+    ///
+    /// let mut body = message.body();
+    /// assert_eq!(body.signature(), "say");
+    /// assert_eq!(body.read::<str>()?, "image.png");
+    /// assert_eq!(body.read_byte_array()?, b"\x89PNG\r\n\x1a\n");
+    /// # Ok::<_, tokio_dbus::Error>(())
+    /// ```
+    pub fn begin_byte_array(&mut self, message: Message<'_>, len: usize) -> Result<()> {
+        limits::check_array_length(len)?;
+        self.write_message_with_trailer(message, Some(len))?;
+        self.streaming = len;
+        Ok(())
+    }
+
+    /// Write the next chunk of the byte array of a message started with
+    /// [`begin_byte_array()`].
+    ///
+    /// [`begin_byte_array()`]: Self::begin_byte_array
+    ///
+    /// # Errors
+    ///
+    /// Errors if `bytes` is longer than what remains of the array.
+    pub fn extend_byte_array(&mut self, bytes: &[u8]) -> Result<()> {
+        if bytes.len() > self.streaming {
+            return Err(Error::new(ErrorKind::ByteArrayOverflow(
+                bytes.len(),
+                self.streaming,
+            )));
+        }
+
+        self.buf.extend_from_slice(bytes);
+        self.streaming -= bytes.len();
+        Ok(())
+    }
+
+    /// Get the number of bytes which remain to be written of the byte array
+    /// of a message started with [`begin_byte_array()`].
+    ///
+    /// [`begin_byte_array()`]: Self::begin_byte_array
+    pub fn remaining_byte_array(&self) -> usize {
+        self.streaming
+    }
+
+    /// Write a message with an optional trailing byte array of the given
+    /// length.
+    fn write_message_with_trailer(
+        &mut self,
+        message: Message<'_>,
+        trailer: Option<usize>,
+    ) -> Result<()> {
+        if self.streaming > 0 {
+            return Err(Error::new(ErrorKind::ByteArrayIncomplete(self.streaming)));
+        }
+
+        if self.middleware.is_empty() {
+            return self.write_processed_message(message, trailer);
+        }
+
+        // NB: Middleware is temporarily moved out, since the messages it
+        // returns may borrow from it.
+        let mut middleware = mem::take(&mut self.middleware);
+
+        let result = apply_middleware(&mut middleware, message)
+            .and_then(|message| self.write_processed_message(message, trailer));
+
+        self.middleware = middleware;
+        result
+    }
+
+    /// Write a message once middleware has been applied.
+    fn write_processed_message(
+        &mut self,
+        message: Message<'_>,
+        trailer: Option<usize>,
+    ) -> Result<()> {
+        self.check_watchdog()?;
+        check_names(&message)?;
+
+        let body = message.body();
+        let unix_fds = body.unix_fds();
+
+        // NB: The header is always written in native endianness, so a body
+        // using a different endianness has to be re-encoded to match it. A
+        // partially read body is re-encoded as well, so that only its
+        // remaining values are sent and they are correctly aligned.
+        let normalized;
+
+        let body = if body.endianness() != Endianness::NATIVE || body.is_partially_read() {
+            normalized = body.to_endianness(Endianness::NATIVE)?;
+            normalized.as_body()
+        } else {
+            body
+        };
+
+        // NB: The trailing byte array is aligned to its length prefix.
+        let total = match trailer {
+            Some(len) => ((body.len() + 3) & !3) + 4 + len,
+            None => body.len(),
+        };
+
+        limits::check_body_length(total)?;
+        let body_length = total as u32;
+
+        let signature = match trailer {
+            Some(..) => {
+                let mut signature =
+                    SignatureBuilder::from_owned_signature(body.signature().to_owned());
+
+                if !signature.extend_from_signature(<[u8] as Write>::SIGNATURE) {
+                    return Err(SignatureError::too_long().into());
+                }
+
+                Some(signature)
+            }
+            None => None,
+        };
+
+        let signature = match &signature {
+            Some(signature) => signature.to_signature(),
+            None => body.signature(),
+        };
+
+        if body.signature().is_empty() != body.is_empty() {
+            return Err(Error::new(ErrorKind::BodySignatureMismatch(
+                body.signature().into(),
+                body.len(),
+            )));
+        }
+
+        if let MessageKind::MethodCall { .. } = message.kind {
+            if !(message.flags & Flags::NO_REPLY_EXPECTED) {
+                let now = Instant::now();
+
+                if !self.pending.has_capacity(now) {
+                    return Err(Error::new(ErrorKind::TooManyPendingReplies(
+                        self.pending.serials.len(),
+                    )));
+                }
+
+                // NB: Whether the message is pending is determined by the
+                // message written once middleware has been applied.
+                if self.checkpoints > 0 && !self.pending.contains(message.serial) {
+                    self.journal.push(message.serial);
+                }
+
+                self.pending.insert(message.serial, now);
+            }
+        }
+
+        if let Some(reply_serial) = message.kind.reply_serial() {
+            self.in_flight.remove(message.destination, reply_serial);
+        }
+
+        self.buf.update_base_align();
+        let message_start = self.buf.len();
+
+        // The following is a section which performs manual header mangling.
+        // It's simply easier to do it like this than make sure that all
+        // message-writing abstractions are compatible with an unaligned buffer.
+
+        self.buf.store(proto::Header {
+            endianness: Endianness::NATIVE,
+            message_type: message.message_type(),
+            flags: message.flags,
+            version: 1,
+            body_length,
+            serial: message.serial.get(),
+        });
+
+        let length = self.buf.alloc::<u32>();
+        let start = self.buf.len();
+
+        match message.kind {
+            MessageKind::MethodCall { path, member } => {
+                self.buf.align_mut::<u64>();
+                self.buf.store(proto::Variant::PATH);
+                self.buf.write(Signature::OBJECT_PATH);
+                self.buf.write(path);
+
+                self.buf.align_mut::<u64>();
+                self.buf.store(proto::Variant::MEMBER);
+                self.buf.write(Signature::STRING);
+                self.buf.write(member);
+            }
+            MessageKind::MethodReturn { reply_serial } => {
+                self.buf.align_mut::<u64>();
+                self.buf.store(proto::Variant::REPLY_SERIAL);
+                self.buf.write(Signature::UINT32);
+                self.buf.store(reply_serial.get());
+            }
+            MessageKind::Error {
+                error_name,
+                reply_serial,
+            } => {
+                self.buf.align_mut::<u64>();
+                self.buf.store(proto::Variant::ERROR_NAME);
+                self.buf.write(Signature::STRING);
+                self.buf.write(error_name);
+
+                self.buf.align_mut::<u64>();
+                self.buf.store(proto::Variant::REPLY_SERIAL);
+                self.buf.write(Signature::UINT32);
+                self.buf.store(reply_serial.get());
+            }
+            MessageKind::Signal { member } => {
+                if let Some(path) = message.path {
+                    self.buf.align_mut::<u64>();
+                    self.buf.store(proto::Variant::PATH);
+                    self.buf.write(Signature::OBJECT_PATH);
+                    self.buf.write(path);
+                }
+
+                self.buf.align_mut::<u64>();
+                self.buf.store(proto::Variant::MEMBER);
+                self.buf.write(Signature::STRING);
+                self.buf.write(member);
+            }
+        }
+
+        if let Some(interface) = message.interface {
+            self.buf.align_mut::<u64>();
+            self.buf.store(proto::Variant::INTERFACE);
+            self.buf.write(Signature::STRING);
+            self.buf.write(interface);
+        }
+
+        if let Some(destination) = message.destination {
+            self.buf.align_mut::<u64>();
+            self.buf.store(proto::Variant::DESTINATION);
+            self.buf.write(Signature::STRING);
+            self.buf.write(destination);
+        }
+
+        if let Some(sender) = message.sender {
+            self.buf.align_mut::<u64>();
+            self.buf.store(proto::Variant::SENDER);
+            self.buf.write(Signature::STRING);
+            self.buf.write(sender);
+        }
+
+        if let Some(correlation_id) = message.correlation_id {
+            self.buf.align_mut::<u64>();
+            self.buf.store(proto::Variant::CORRELATION_ID);
+            self.buf.write(Signature::STRING);
+            self.buf.write(correlation_id);
+        }
+
+        if !signature.is_empty() {
+            self.buf.align_mut::<u64>();
+            self.buf.store(proto::Variant::SIGNATURE);
+            self.buf.write(Signature::SIGNATURE);
+            self.buf.write(signature);
+        }
+
+        if unix_fds > 0 {
+            self.buf.align_mut::<u64>();
+            self.buf.store(proto::Variant::UNIX_FDS);
+            self.buf.write(Signature::UINT32);
+            self.buf.store(unix_fds);
+        }
+
+        self.buf.store_at(length, (self.buf.len() - start) as u32);
+
+        self.buf.align_mut::<u64>();
+        self.buf.extend_from_slice(body.get());
+
+        if let Some(len) = trailer {
+            self.buf.align_mut::<u32>();
+            self.buf.store(len as u32);
+        }
+
+        self.end_message(message_start);
+
+        // NB: The message isn't complete until the contents of the byte array
+        // have been written, but it's tracked as one frame.
+        if let (Some(len), Some(frame)) = (trailer, self.frames.back_mut()) {
+            *frame += len;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for SendBuf {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A position in a [`SendBuf`] which can be rolled back to, see
+/// [`SendBuf::checkpoint`].
+pub(crate) struct Checkpoint {
+    len: usize,
+    frames: usize,
+    stalled_since: Option<Instant>,
+    journal: usize,
+}
+
+/// Apply middleware to a message in order.
+fn apply_middleware<'a>(
+    middleware: &'a mut [Box<dyn Middleware + Send + Sync>],
+    mut message: Message<'a>,
+) -> Result<Message<'a>> {
+    for middleware in middleware {
+        message = middleware.outgoing(message)?;
+    }
+
+    Ok(message)
+}
+
+/// Check that the names in the header of a message don't exceed the maximum
+/// name length.
+fn check_names(message: &Message<'_>) -> Result<()> {
+    match message.kind {
+        MessageKind::MethodCall { member, .. } | MessageKind::Signal { member, .. } => {
+            limits::check_name_length(member)?;
+        }
+        MessageKind::Error { error_name, .. } => {
+            limits::check_name_length(error_name)?;
+        }
+        MessageKind::MethodReturn { .. } => {}
+    }
+
+    for name in [message.interface, message.destination, message.sender]
+        .into_iter()
+        .flatten()
+    {
+        limits::check_name_length(name)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use std::collections::{HashMap, VecDeque};
+use std::num::NonZeroU32;
+use std::time::Duration;
+
+use crate::utils::Instant;
+
+/// The default time after which a pending reply is forgotten.
+///
+/// This matches the default reply timeout used by the reference
+/// implementation.
+pub(super) const DEFAULT_REPLY_TIMEOUT: Duration = Duration::from_secs(25);
+
+/// Table of method calls which are awaiting a reply.
+pub(super) struct PendingReplies {
+    /// The maximum number of pending replies, if any.
+    pub(super) max: Option<usize>,
+    /// Time after which a pending reply expires.
+    pub(super) timeout: Duration,
+    /// Serials which are awaiting a reply, and when they were sent.
+    pub(super) serials: HashMap<NonZeroU32, Instant>,
+    /// Serials in the order they were sent, used for expiry. This might
+    /// contain serials which have since been replied to.
+    order: VecDeque<(NonZeroU32, Instant)>,
+}
+
+impl PendingReplies {
+    pub(super) fn new() -> Self {
+        Self {
+            max: None,
+            timeout: DEFAULT_REPLY_TIMEOUT,
+            serials: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Test if the given serial is awaiting a reply.
+    #[inline]
+    pub(super) fn contains(&self, serial: NonZeroU32) -> bool {
+        self.serials.contains_key(&serial)
+    }
+
+    /// Test if another pending reply can be inserted, expiring old entries if
+    /// necessary.
+    pub(super) fn has_capacity(&mut self, now: Instant) -> bool {
+        self.expire(now);
+
+        match self.max {
+            Some(max) => self.serials.len() < max,
+            None => true,
+        }
+    }
+
+    /// Insert a pending reply.
+    pub(super) fn insert(&mut self, serial: NonZeroU32, now: Instant) {
+        self.serials.insert(serial, now);
+        self.order.push_back((serial, now));
+    }
+
+    /// Remove a pending reply, returns `true` if it was pending.
+    pub(super) fn remove(&mut self, serial: NonZeroU32) -> bool {
+        let removed = self.serials.remove(&serial).is_some();
+
+        if self.serials.is_empty() {
+            self.order.clear();
+        }
+
+        removed
+    }
+
+    /// Expire pending replies which have been waiting for longer than the
+    /// configured timeout.
+    fn expire(&mut self, now: Instant) {
+        while let Some(&(serial, sent)) = self.order.front() {
+            if now.saturating_duration_since(sent) < self.timeout {
+                break;
+            }
+
+            self.order.pop_front();
+
+            // NB: Only remove the entry if it wasn't replaced by a newer
+            // call which reused the same serial.
+            if self.serials.get(&serial) == Some(&sent) {
+                self.serials.remove(&serial);
+            }
+        }
+    }
+}
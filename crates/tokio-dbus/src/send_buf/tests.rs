@@ -0,0 +1,418 @@
+use std::time::Duration;
+
+use crate::error::{Error, ErrorKind, Result};
+use crate::{BodyBuf, Broadcast, Endianness, ObjectPath, RecvBuf, SendBuf, UnixFd};
+
+const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+
+#[test]
+fn broadcast() -> Result<()> {
+    let mut expected = SendBuf::new();
+    let mut actual = SendBuf::new();
+
+    let broadcast = Broadcast::new(
+        SendBuf::new()
+            .signal("Tick")
+            .with_path(PATH)
+            .with_interface("org.freedesktop.DBus"),
+    )?;
+
+    for _ in 0..2 {
+        let m = expected
+            .signal("Tick")
+            .with_path(PATH)
+            .with_interface("org.freedesktop.DBus");
+        expected.write_message(m)?;
+
+        broadcast.write_to(&mut actual)?;
+    }
+
+    assert_eq!(actual.buf().get(), expected.buf().get());
+
+    // A broadcast can't be written in the middle of a byte array.
+    let m = actual.signal("Stream");
+    actual.begin_byte_array(m, 4)?;
+    let len = actual.buf().len();
+    assert!(broadcast.write_to(&mut actual).is_err());
+    assert_eq!(actual.buf().len(), len);
+    Ok(())
+}
+
+#[test]
+fn serial_skips_pending() -> Result<()> {
+    let mut send = SendBuf::new();
+    send.serial = u32::MAX - 1;
+
+    let m = send.method_call(PATH, "Hello");
+    assert_eq!(m.serial().get(), u32::MAX);
+    send.write_message(m)?;
+
+    // Wrapping skips zero.
+    let m = send.method_call(PATH, "Hello");
+    assert_eq!(m.serial().get(), 1);
+    send.write_message(m)?;
+
+    send.serial = u32::MAX - 1;
+
+    // Serials which are awaiting a reply are skipped.
+    assert_eq!(send.next_serial().get(), 2);
+    Ok(())
+}
+
+#[test]
+fn pending_replies_expire() -> Result<()> {
+    let mut send = SendBuf::new();
+    send.set_max_pending_replies(Some(1));
+    send.set_reply_timeout(Duration::ZERO);
+
+    for _ in 0..2 {
+        let m = send.method_call(PATH, "Hello");
+        send.write_message(m)?;
+    }
+
+    assert_eq!(send.pending_replies(), 1);
+    Ok(())
+}
+
+#[test]
+fn owned_message_keeps_signature() -> Result<()> {
+    let mut send = SendBuf::new();
+    let mut body = BodyBuf::new();
+    body.store(42u32)?;
+
+    let m = send.signal("Owned").with_body(&body).to_owned();
+    assert_eq!(m.signature(), "u");
+    assert_eq!(m.borrow().signature(), "u");
+    send.write_message(m.borrow())?;
+    Ok(())
+}
+
+#[test]
+fn foreign_body_is_normalized() -> Result<()> {
+    let foreign = if Endianness::NATIVE == Endianness::LITTLE {
+        Endianness::BIG
+    } else {
+        Endianness::LITTLE
+    };
+
+    let mut expected = Vec::new();
+
+    for endianness in [Endianness::NATIVE, foreign] {
+        let mut send = SendBuf::new();
+        let mut body = BodyBuf::with_endianness(endianness);
+        body.arguments((42u32, "foo", 7u64))?;
+
+        let m = send.signal("Normalized").with_body(&body);
+        send.write_message(m)?;
+
+        if endianness == Endianness::NATIVE {
+            expected = send.buf().get().to_vec();
+        } else {
+            assert_eq!(send.buf().get(), &expected[..]);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn partially_read_body() -> Result<()> {
+    let mut expected = SendBuf::new();
+    let mut body = BodyBuf::new();
+    body.store("foo")?;
+
+    let m = expected.signal("Partial").with_body(&body);
+    expected.write_message(m)?;
+
+    let mut send = SendBuf::new();
+    let mut body = BodyBuf::new();
+    body.arguments((42u32, "foo"))?;
+
+    let mut partial = body.as_body();
+    assert_eq!(partial.load::<u32>()?, 42);
+
+    let m = send.signal("Partial").with_body(partial.clone());
+    send.write_message(m)?;
+    assert_eq!(send.buf().get(), expected.buf().get());
+
+    let mut buf = partial.to_endianness(Endianness::NATIVE)?;
+    assert_eq!(buf.signature(), "s");
+    assert_eq!(buf.as_body().read::<str>()?, "foo");
+    buf.store(1u8)?;
+    assert_eq!(buf.signature(), "sy");
+    Ok(())
+}
+
+#[test]
+fn partially_sent() -> Result<()> {
+    let mut send = SendBuf::new();
+
+    let m = send.signal("First");
+    send.write_message(m)?;
+    let first = send.buf().len();
+
+    let m = send.signal("Second");
+    send.write_message(m)?;
+    assert!(!send.is_partially_sent());
+
+    send.advance(first - 1);
+    assert!(send.is_partially_sent());
+
+    send.advance(1);
+    assert!(!send.is_partially_sent());
+
+    send.advance(4);
+    assert!(send.is_partially_sent());
+
+    let rest = send.buf().len();
+    send.advance(rest);
+    assert!(!send.is_partially_sent());
+    assert!(send.buf().is_empty());
+    Ok(())
+}
+
+#[test]
+fn watchdog() -> Result<()> {
+    let mut send = SendBuf::new();
+    send.set_watchdog(Some(Duration::ZERO));
+
+    let m = send.signal("First");
+    send.write_message(m)?;
+
+    let m = send.signal("Second");
+    assert!(send.write_message(m).unwrap_err().is_not_driven());
+
+    // Any progress resets the watchdog.
+    send.advance(4);
+    send.set_watchdog(Some(Duration::from_secs(60)));
+    let m = send.signal("Second");
+    send.write_message(m)?;
+
+    send.set_watchdog(Some(Duration::ZERO));
+    send.write_to(&mut Vec::new())?;
+    let m = send.signal("Third");
+    send.write_message(m)?;
+
+    send.set_watchdog(None);
+    let m = send.signal("Fourth");
+    send.write_message(m)?;
+    Ok(())
+}
+
+#[test]
+fn transaction_rollback() -> Result<()> {
+    let mut send = SendBuf::new();
+
+    let m = send.signal("First");
+    send.write_message(m)?;
+    send.advance(4);
+    let expected = send.buf().get().to_vec();
+
+    let result = send.transaction(|tx| {
+        let m = tx.method_call(PATH, "Hello");
+        tx.write_message(m)?;
+        Err::<(), _>(Error::new(ErrorKind::MissingMessage))
+    });
+
+    assert!(result.is_err());
+    assert_eq!(send.buf().get(), &expected[..]);
+    assert_eq!(send.pending_replies(), 0);
+
+    let serial = send.transaction(|tx| {
+        let m = tx.method_call(PATH, "Hello");
+        let serial = m.serial();
+        tx.write_message(m)?;
+        Ok(serial)
+    })?;
+
+    assert!(send.buf().len() > expected.len());
+    assert_eq!(send.pending_replies(), 1);
+    assert!(send.complete_reply(serial));
+
+    // The first message is still partially sent, and the one written by the
+    // transaction follows it.
+    let first = expected.len();
+    send.advance(first - 1);
+    assert!(send.is_partially_sent());
+    send.advance(1);
+    assert!(!send.is_partially_sent());
+    Ok(())
+}
+
+#[test]
+fn transaction_rollback_middleware() -> Result<()> {
+    use crate::{Flags, Message, Middleware};
+
+    /// Middleware which makes every message expect a reply.
+    struct ExpectReply;
+
+    impl Middleware for ExpectReply {
+        fn outgoing<'a>(&'a mut self, message: Message<'a>) -> Result<Message<'a>> {
+            Ok(message.with_flags(Flags::EMPTY))
+        }
+    }
+
+    let mut send = SendBuf::new();
+    send.push_middleware(ExpectReply);
+
+    let result = send.transaction(|tx| {
+        let m = tx
+            .method_call(PATH, "Hello")
+            .with_flags(Flags::NO_REPLY_EXPECTED);
+        tx.write_message(m)?;
+        Err::<(), _>(Error::new(ErrorKind::MissingMessage))
+    });
+
+    assert!(result.is_err());
+    assert!(send.buf().is_empty());
+    assert_eq!(send.pending_replies(), 0);
+    Ok(())
+}
+
+#[test]
+fn names_too_long() -> Result<()> {
+    let mut send = SendBuf::new();
+    let name = "a".repeat(256);
+
+    let m = send.method_call(ObjectPath::ROOT, &name);
+    assert!(send.write_message(m).unwrap_err().is_limit_exceeded());
+
+    let m = send
+        .method_call(ObjectPath::ROOT, "Ping")
+        .with_destination(&name);
+    assert!(send.write_message(m).unwrap_err().is_limit_exceeded());
+
+    let m = send.signal("Changed").with_interface(&name);
+    assert!(send.write_message(m).unwrap_err().is_limit_exceeded());
+
+    assert!(send.buf().is_empty());
+
+    let m = send
+        .method_call(ObjectPath::ROOT, "Ping")
+        .with_destination(&name[..255]);
+    send.write_message(m)?;
+
+    // The checked builders report the error where the name is set.
+    let m = send.method_call(ObjectPath::ROOT, "Ping");
+    assert!(m
+        .clone()
+        .try_with_interface(&name)
+        .unwrap_err()
+        .is_limit_exceeded());
+    assert!(m
+        .clone()
+        .try_with_destination(&name)
+        .unwrap_err()
+        .is_limit_exceeded());
+    assert!(m
+        .clone()
+        .try_with_sender(&name)
+        .unwrap_err()
+        .is_limit_exceeded());
+
+    let m = m.try_with_destination(&name[..255])?;
+    send.write_message(m)?;
+    Ok(())
+}
+
+#[test]
+fn unix_fds_header() -> Result<()> {
+    let mut send = SendBuf::new();
+    let mut body = BodyBuf::new();
+    body.store(UnixFd::new(2))?;
+    body.store(UnixFd::new(0))?;
+    assert_eq!(body.unix_fds(), 3);
+
+    let m = send.method_call(PATH, "Open").with_body(&body);
+    send.write_message(m)?;
+    let m = send.method_call(PATH, "Close");
+    send.write_message(m)?;
+
+    let bytes = send.buf().get().to_vec();
+    let mut bytes = &bytes[..];
+    let mut recv = RecvBuf::new();
+
+    let message_ref = recv.read_from(&mut bytes)?;
+    let message = recv.read_message(&message_ref)?;
+    assert_eq!(message.body().unix_fds(), 3);
+    assert_eq!(message.to_owned().body().unix_fds(), 3);
+
+    let (first, second) = message.body().decode::<(UnixFd, UnixFd)>()?;
+    assert_eq!(first, UnixFd::new(2));
+    assert_eq!(second, UnixFd::new(0));
+
+    let message_ref = recv.read_from(&mut bytes)?;
+    let message = recv.read_message(&message_ref)?;
+    assert_eq!(message.body().unix_fds(), 0);
+    Ok(())
+}
+
+#[test]
+fn middleware() -> Result<()> {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{Message, Middleware};
+
+    struct Stamp(String);
+
+    impl Middleware for Stamp {
+        fn outgoing<'a>(&'a mut self, message: Message<'a>) -> Result<Message<'a>> {
+            Ok(message.with_sender(&self.0))
+        }
+    }
+
+    struct Record(Arc<Mutex<Vec<String>>>);
+
+    impl Middleware for Record {
+        fn outgoing<'a>(&'a mut self, message: Message<'a>) -> Result<Message<'a>> {
+            let sender = message.sender().unwrap_or_default().to_owned();
+            self.0.lock().unwrap().push(sender);
+            Ok(message)
+        }
+    }
+
+    struct Deny;
+
+    impl Middleware for Deny {
+        fn outgoing<'a>(&'a mut self, message: Message<'a>) -> Result<Message<'a>> {
+            if message.destination() == Some("se.tedro.Denied") {
+                return Err(Error::from(std::io::Error::from(
+                    std::io::ErrorKind::PermissionDenied,
+                )));
+            }
+
+            Ok(message)
+        }
+    }
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+
+    let mut send = SendBuf::new();
+    send.push_middleware(Stamp(String::from(":1.42")));
+    send.push_middleware(Record(seen.clone()));
+    send.push_middleware(Deny);
+
+    let m = send.method_call(PATH, "Hello");
+    send.write_message(m)?;
+    let len = send.buf().len();
+
+    let m = send
+        .method_call(PATH, "Hello")
+        .with_destination("se.tedro.Denied");
+    assert!(send.write_message(m).is_err());
+    assert_eq!(send.buf().len(), len);
+
+    assert_eq!(*seen.lock().unwrap(), [":1.42", ":1.42"]);
+
+    let mut bytes = send.buf().get();
+    let mut recv = RecvBuf::new();
+    let message_ref = recv.read_from(&mut bytes)?;
+    assert_eq!(recv.read_message(&message_ref)?.sender(), Some(":1.42"));
+    Ok(())
+}
+
+#[test]
+fn send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SendBuf>();
+}
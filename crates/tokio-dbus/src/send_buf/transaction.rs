@@ -0,0 +1,46 @@
+use std::num::NonZeroU32;
+
+use crate::error::Result;
+use crate::{Message, ObjectPath, SendBuf};
+
+/// A transaction which writes several messages to a [`SendBuf`], where either
+/// all of them are queued for sending or none of them are.
+///
+/// See [`SendBuf::transaction`].
+pub struct Transaction<'a> {
+    send: &'a mut SendBuf,
+}
+
+impl<'a> Transaction<'a> {
+    pub(super) fn new(send: &'a mut SendBuf) -> Self {
+        Self { send }
+    }
+
+    /// Get the next serial for the underlying send buffer.
+    ///
+    /// See [`SendBuf::next_serial`].
+    pub fn next_serial(&mut self) -> NonZeroU32 {
+        self.send.next_serial()
+    }
+
+    /// Construct a method call [`Message`].
+    ///
+    /// See [`SendBuf::method_call`].
+    pub fn method_call<'b>(&mut self, path: &'b ObjectPath, member: &'b str) -> Message<'b> {
+        self.send.method_call(path, member)
+    }
+
+    /// Construct a signal [`Message`].
+    ///
+    /// See [`SendBuf::signal`].
+    pub fn signal<'b>(&mut self, member: &'b str) -> Message<'b> {
+        self.send.signal(member)
+    }
+
+    /// Write a message as part of the transaction.
+    ///
+    /// See [`SendBuf::write_message`].
+    pub fn write_message(&mut self, message: Message<'_>) -> Result<()> {
+        self.send.write_message_with_trailer(message, None)
+    }
+}
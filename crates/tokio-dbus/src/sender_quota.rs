@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use crate::MessageBuf;
+
+/// Quotas on the deferred messages which are held on behalf of each sender.
+///
+/// Messages which are received while waiting for something else, such as the
+/// reply to a method call, are deferred in the [`RecvBuf`] until they are
+/// read. A peer which floods a connection can make this queue grow without
+/// bound, so public-facing services can limit the number of messages and the
+/// number of body bytes deferred for any single sender.
+///
+/// Messages without a sender, which can only happen on peer-to-peer
+/// connections, share a quota.
+///
+/// Set through [`RecvBuf::set_sender_quota`] or
+/// [`ConnectionBuilder::sender_quota`].
+///
+/// [`RecvBuf`]: crate::RecvBuf
+/// [`RecvBuf::set_sender_quota`]: crate::RecvBuf::set_sender_quota
+/// [`ConnectionBuilder::sender_quota`]: crate::ConnectionBuilder::sender_quota
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{RecvBuf, SenderQuota};
+///
+/// let quota = SenderQuota::new()
+///     .with_max_messages(128)
+///     .with_max_bytes(1 << 20)
+///     .with_drop(true);
+///
+/// let mut recv = RecvBuf::new();
+/// recv.set_sender_quota(Some(quota));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SenderQuota {
+    max_messages: Option<usize>,
+    max_bytes: Option<usize>,
+    drop: bool,
+}
+
+impl SenderQuota {
+    /// Construct a new quota which doesn't limit anything.
+    pub const fn new() -> Self {
+        Self {
+            max_messages: None,
+            max_bytes: None,
+            drop: false,
+        }
+    }
+
+    /// Set the maximum number of messages which can be deferred for a single
+    /// sender.
+    #[must_use]
+    pub const fn with_max_messages(self, max_messages: usize) -> Self {
+        Self {
+            max_messages: Some(max_messages),
+            ..self
+        }
+    }
+
+    /// Set the maximum number of body bytes which can be deferred for a single
+    /// sender.
+    #[must_use]
+    pub const fn with_max_bytes(self, max_bytes: usize) -> Self {
+        Self {
+            max_bytes: Some(max_bytes),
+            ..self
+        }
+    }
+
+    /// Set whether messages exceeding the quota should be dropped.
+    ///
+    /// By default deferring a message which exceeds the quota of its sender
+    /// errors with an error for which [`Error::is_sender_quota_exceeded`]
+    /// returns `true`. If this is set, the message is dropped instead.
+    ///
+    /// [`Error::is_sender_quota_exceeded`]: crate::Error::is_sender_quota_exceeded
+    #[must_use]
+    pub const fn with_drop(self, drop: bool) -> Self {
+        Self { drop, ..self }
+    }
+
+    /// Test if messages exceeding the quota should be dropped.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn is_drop(&self) -> bool {
+        self.drop
+    }
+}
+
+impl Default for SenderQuota {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The resources used by the deferred messages of one sender.
+#[derive(Default)]
+struct Usage {
+    messages: usize,
+    bytes: usize,
+}
+
+/// Tracks deferred messages against a [`SenderQuota`].
+pub(crate) struct SenderUsage {
+    #[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+    quota: SenderQuota,
+    usage: HashMap<Box<str>, Usage>,
+}
+
+impl SenderUsage {
+    /// Construct tracking for `quota`, accounting for already deferred
+    /// `messages`.
+    pub(crate) fn new<'a>(
+        quota: SenderQuota,
+        messages: impl IntoIterator<Item = &'a MessageBuf>,
+    ) -> Self {
+        let mut this = Self {
+            quota,
+            usage: HashMap::new(),
+        };
+
+        for message in messages {
+            this.add(message);
+        }
+
+        this
+    }
+
+    /// Get the quota being tracked.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn quota(&self) -> &SenderQuota {
+        &self.quota
+    }
+
+    /// Test if `message` fits within the quota of its sender.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn fits(&self, message: &MessageBuf) -> bool {
+        let (messages, bytes) = match self.usage.get(message.sender().unwrap_or_default()) {
+            Some(usage) => (usage.messages, usage.bytes),
+            None => (0, 0),
+        };
+
+        let bytes = bytes.saturating_add(message.body().len());
+
+        self.quota.max_messages.map_or(true, |max| messages < max)
+            && self.quota.max_bytes.map_or(true, |max| bytes <= max)
+    }
+
+    /// Account for a message being deferred.
+    pub(crate) fn add(&mut self, message: &MessageBuf) {
+        let sender = message.sender().unwrap_or_default();
+
+        let usage = match self.usage.get_mut(sender) {
+            Some(usage) => usage,
+            None => self.usage.entry(sender.into()).or_default(),
+        };
+
+        usage.messages += 1;
+        usage.bytes += message.body().len();
+    }
+
+    /// Account for a deferred message being removed.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn remove(&mut self, message: &MessageBuf) {
+        let sender = message.sender().unwrap_or_default();
+
+        let Some(usage) = self.usage.get_mut(sender) else {
+            return;
+        };
+
+        usage.messages = usage.messages.saturating_sub(1);
+        usage.bytes = usage.bytes.saturating_sub(message.body().len());
+
+        if usage.messages == 0 {
+            self.usage.remove(sender);
+        }
+    }
+}
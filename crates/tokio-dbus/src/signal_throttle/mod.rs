@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::utils::Instant;
+use crate::{Message, MessageBuf, MessageKind, ObjectPath, ObjectPathBuf, SendBuf};
+
+/// Identifies a signal by its path, interface and member.
+type SignalKey = (Option<ObjectPathBuf>, Option<Box<str>>, Box<str>);
+
+struct Entry {
+    /// When the signal was last written.
+    last: Instant,
+    /// The latest emission held back during the window.
+    pending: Option<MessageBuf>,
+}
+
+/// Wrapper which limits how often the same signal is emitted.
+///
+/// Signals are considered the same if they have the same path, interface and
+/// member. Once a signal has been written, further emissions of it within the
+/// configured window are held back. By default only the latest held back
+/// emission is kept and is written by [`SignalThrottle::flush`] once the
+/// window has passed, which coalesces rapid updates such as progress reports
+/// into one signal per window. With [`SignalThrottle::with_coalesce`] set to
+/// `false`, held back emissions are dropped instead.
+///
+/// Messages which are not signals are written immediately.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use tokio_dbus::{BodyBuf, ObjectPath, SendBuf, SignalThrottle};
+///
+/// const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Example");
+///
+/// let mut send = SendBuf::new();
+/// let mut body = BodyBuf::new();
+/// let mut throttle = SignalThrottle::new(Duration::from_secs(60));
+///
+/// for progress in 0..10u32 {
+///     body.clear();
+///     body.store(progress)?;
+///
+///     let m = send.signal("Progress")
+///         .with_path(PATH)
+///         .with_interface("se.tedro.Example")
+///         .with_body(&body);
+///
+///     let written = throttle.write_message(&mut send, m)?;
+///     assert_eq!(written, progress == 0);
+/// }
+///
+/// // The latest progress is written once the window has passed.
+/// assert!(throttle.next_deadline().is_some());
+/// assert_eq!(throttle.flush(&mut send)?, 0);
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+pub struct SignalThrottle {
+    window: Duration,
+    coalesce: bool,
+    entries: HashMap<SignalKey, Entry>,
+}
+
+impl SignalThrottle {
+    /// Construct a new throttle which writes each signal at most once per
+    /// `window`.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            coalesce: true,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Set whether held back emissions should be coalesced and written once
+    /// the window has passed, or dropped.
+    ///
+    /// Defaults to `true`.
+    #[must_use]
+    pub fn with_coalesce(self, coalesce: bool) -> Self {
+        Self { coalesce, ..self }
+    }
+
+    /// Write `message` to `send` unless it is a signal which has been written
+    /// within the current window.
+    ///
+    /// Returns `true` if the message was written.
+    pub fn write_message(&mut self, send: &mut SendBuf, message: Message<'_>) -> Result<bool> {
+        self.write_message_at(send, message, Instant::now())
+    }
+
+    /// Write held back signals whose window has passed, returning the number of
+    /// signals written.
+    ///
+    /// This should be called periodically, such as when
+    /// [`SignalThrottle::next_deadline`] has been reached.
+    pub fn flush(&mut self, send: &mut SendBuf) -> Result<usize> {
+        self.flush_at(send, Instant::now())
+    }
+
+    /// Get the earliest time at which a held back signal is due to be written.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.entries
+            .values()
+            .filter(|e| e.pending.is_some())
+            .map(|e| e.last + self.window)
+            .min()
+    }
+
+    fn write_message_at(
+        &mut self,
+        send: &mut SendBuf,
+        message: Message<'_>,
+        now: Instant,
+    ) -> Result<bool> {
+        let MessageKind::Signal { member } = message.kind() else {
+            send.write_message(message)?;
+            return Ok(true);
+        };
+
+        let key = (
+            message.path().map(ObjectPath::to_owned),
+            message.interface().map(Box::from),
+            Box::from(member),
+        );
+
+        if let Some(entry) = self.entries.get_mut(&key) {
+            if now < entry.last + self.window {
+                if self.coalesce {
+                    entry.pending = Some(message.to_owned());
+                }
+
+                return Ok(false);
+            }
+        }
+
+        send.write_message(message)?;
+
+        self.entries.insert(
+            key,
+            Entry {
+                last: now,
+                pending: None,
+            },
+        );
+
+        Ok(true)
+    }
+
+    fn flush_at(&mut self, send: &mut SendBuf, now: Instant) -> Result<usize> {
+        let mut count = 0;
+
+        for entry in self.entries.values_mut() {
+            if now < entry.last + self.window {
+                continue;
+            }
+
+            if let Some(message) = entry.pending.take() {
+                let serial = send.next_serial();
+                send.write_message(message.borrow().with_serial(serial))?;
+                entry.last = now;
+                count += 1;
+            }
+        }
+
+        let window = self.window;
+        self.entries
+            .retain(|_, e| e.pending.is_some() || now < e.last + window);
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests;
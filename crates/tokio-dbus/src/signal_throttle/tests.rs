@@ -0,0 +1,74 @@
+use std::time::{Duration, Instant};
+
+use crate::error::Result;
+use crate::{BodyBuf, Message, ObjectPath, SendBuf};
+
+use super::SignalThrottle;
+
+const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/Example");
+const WINDOW: Duration = Duration::from_secs(1);
+
+fn progress<'a>(send: &mut SendBuf, body: &'a BodyBuf) -> Message<'a> {
+    send.signal("Progress")
+        .with_path(PATH)
+        .with_interface("se.tedro.Example")
+        .with_body(body)
+}
+
+#[test]
+fn coalesce() -> Result<()> {
+    let start = Instant::now();
+    let mut send = SendBuf::new();
+    let mut body = BodyBuf::new();
+    let mut throttle = SignalThrottle::new(WINDOW);
+
+    for n in 0..3u32 {
+        body.clear();
+        body.store(n)?;
+        let m = progress(&mut send, &body);
+        let now = start + Duration::from_millis(u64::from(n) * 100);
+        assert_eq!(throttle.write_message_at(&mut send, m, now)?, n == 0);
+    }
+
+    // Other signals and messages are not affected.
+    let m = send.signal("Other").with_path(PATH);
+    assert!(throttle.write_message_at(&mut send, m, start)?);
+    let m = send.method_call(PATH, "Call");
+    assert!(throttle.write_message_at(&mut send, m, start)?);
+
+    let len = send.buf().len();
+    assert_eq!(throttle.next_deadline(), Some(start + WINDOW));
+    assert_eq!(throttle.flush_at(&mut send, start + WINDOW / 2)?, 0);
+    assert_eq!(send.buf().len(), len);
+
+    assert_eq!(throttle.flush_at(&mut send, start + WINDOW)?, 1);
+    assert!(send.buf().len() > len);
+    assert_eq!(throttle.next_deadline(), None);
+
+    // The coalesced signal starts a new window.
+    body.clear();
+    body.store(3u32)?;
+    let m = progress(&mut send, &body);
+    assert!(!throttle.write_message_at(&mut send, m, start + WINDOW)?);
+
+    let m = progress(&mut send, &body);
+    assert!(throttle.write_message_at(&mut send, m, start + WINDOW * 3)?);
+    Ok(())
+}
+
+#[test]
+fn drop_excess() -> Result<()> {
+    let start = Instant::now();
+    let mut send = SendBuf::new();
+    let body = BodyBuf::new();
+    let mut throttle = SignalThrottle::new(WINDOW).with_coalesce(false);
+
+    let m = progress(&mut send, &body);
+    assert!(throttle.write_message_at(&mut send, m, start)?);
+    let m = progress(&mut send, &body);
+    assert!(!throttle.write_message_at(&mut send, m, start)?);
+
+    assert_eq!(throttle.next_deadline(), None);
+    assert_eq!(throttle.flush_at(&mut send, start + WINDOW)?, 0);
+    Ok(())
+}
@@ -1,18 +1,45 @@
+//! Types for working with D-Bus signatures.
+//!
+//! This provides the primitives used to parse signatures, such as the
+//! [`Iter`] iterator which walks over the complete types in a signature. It is
+//! useful for tools which generate code from signatures.
+//!
+//! # Examples
+//!
+//! ```
+//! use tokio_dbus::Signature;
+//! use tokio_dbus::signature::Type;
+//!
+//! let sig = Signature::new("a{sv}(ii)u")?;
+//! let mut it = sig.iter();
+//!
+//! let Some(Type::Array(element)) = it.next() else {
+//!     panic!("expected array");
+//! };
+//!
+//! assert_eq!(
+//!     element.iter().collect::<Vec<_>>(),
+//!     [Type::Dict(Signature::STRING, Signature::VARIANT)]
+//! );
+//!
+//! assert_eq!(it.next(), Some(Type::Struct(Signature::new("ii")?)));
+//! assert_eq!(it.next(), Some(Type::Signature(Signature::UINT32)));
+//! assert_eq!(it.next(), None);
+//! # Ok::<_, tokio_dbus::Error>(())
+//! ```
+
 #[cfg(test)]
 mod tests;
 
-#[macro_use]
-mod stack;
-
-#[doc(inline)]
-pub use tokio_dbus_core::signature::{Signature, SignatureBuf, SignatureError};
 #[doc(inline)]
-pub(crate) use tokio_dbus_core::signature::{SignatureBuilder, MAX_DEPTH};
+pub use tokio_dbus_core::signature::{Iter, Signature, SignatureBuf, SignatureError, Type};
+
+pub(crate) use tokio_dbus_core::signature::SignatureBuilder;
 
 use crate::buf::UnalignedBuf;
 use crate::error::Result;
 
-use crate::{Body, BodyBuf, Read, Write};
+use crate::{Body, BodyBuf, Read, Visitor, Write};
 
 impl crate::write::sealed::Sealed for Signature {}
 
@@ -45,99 +72,33 @@ impl Read for Signature {
     }
 }
 
-/// Return the stride needed to skip over read buffer.
-pub(crate) fn skip(this: &Signature, read: &mut Body<'_>) -> Result<()> {
+/// Get the alignment of the first complete type in the signature.
+pub(crate) fn alignment_of(this: &Signature) -> usize {
     use crate::proto::Type;
 
-    #[derive(Debug, Clone, Copy)]
-    enum Step {
-        Fixed(usize),
-        StringNul,
-        Variant,
-        ByteNul,
+    let Some(&b) = this.as_bytes().first() else {
+        return 1;
+    };
+
+    match Type::new(b) {
+        Type::INT16 | Type::UINT16 => 2,
+        Type::BOOLEAN
+        | Type::INT32
+        | Type::UINT32
+        | Type::UNIX_FD
+        | Type::STRING
+        | Type::OBJECT_PATH
+        | Type::ARRAY => 4,
+        Type::INT64 | Type::UINT64 | Type::DOUBLE | Type::OPEN_PAREN | Type::OPEN_BRACE => 8,
+        _ => 1,
     }
+}
 
-    let mut stack = self::stack::Stack::<bool, MAX_DEPTH>::new();
-    let mut arrays = 0;
-
-    for &b in this.as_bytes() {
-        let t = Type::new(b);
-
-        let step = match t {
-            Type::BYTE => Step::Fixed(1),
-            Type::BOOLEAN => Step::Fixed(1),
-            Type::INT16 => Step::Fixed(2),
-            Type::UINT16 => Step::Fixed(2),
-            Type::INT32 => Step::Fixed(4),
-            Type::UINT32 => Step::Fixed(4),
-            Type::INT64 => Step::Fixed(8),
-            Type::UINT64 => Step::Fixed(8),
-            Type::DOUBLE => Step::Fixed(8),
-            Type::STRING => Step::StringNul,
-            Type::OBJECT_PATH => Step::StringNul,
-            Type::SIGNATURE => Step::ByteNul,
-            Type::VARIANT => Step::Variant,
-            Type::UNIX_FD => Step::Fixed(4),
-            Type::ARRAY => {
-                if arrays == 0 {
-                    let n = read.load::<u32>()? as usize;
-                    read.advance(n)?;
-                }
-
-                arrays += 1;
-                stack.try_push(true);
-                continue;
-            }
-            Type::OPEN_PAREN => {
-                stack.try_push(false);
-                continue;
-            }
-            Type::CLOSE_PAREN => {
-                stack.pop();
-                Step::Fixed(0)
-            }
-            Type::OPEN_BRACE => {
-                stack.try_push(false);
-                continue;
-            }
-            Type::CLOSE_BRACE => {
-                stack.pop();
-                Step::Fixed(0)
-            }
-            _ => unreachable!(),
-        };
-
-        let in_array = arrays > 0;
-
-        // Unwind arrays.
-        while let Some(true) = stack.peek() {
-            arrays -= 1;
-            stack.pop();
-        }
-
-        if in_array {
-            continue;
-        }
-
-        match step {
-            Step::Fixed(n) => {
-                read.advance(n)?;
-            }
-            Step::StringNul => {
-                let n = read.load::<u32>()? as usize;
-                read.advance(n.saturating_add(1))?;
-            }
-            Step::ByteNul => {
-                let n = read.load::<u8>()? as usize;
-                read.advance(n.saturating_add(1))?;
-            }
-            Step::Variant => {
-                let _ = read.load::<u8>()?;
-                let sig = read.read::<Signature>()?;
-                skip(sig, read)?;
-            }
-        }
-    }
+/// Skip over the values described by the signature.
+pub(crate) fn skip(this: &Signature, read: &mut Body<'_>) -> Result<()> {
+    struct Skip;
+
+    impl Visitor<'_> for Skip {}
 
-    Ok(())
+    read.walk(this, &mut Skip)
 }
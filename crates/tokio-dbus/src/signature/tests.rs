@@ -32,14 +32,14 @@ fn signature_skip_array() -> Result<()> {
     first.store("A");
     first.store("B");
     first.store("C");
-    first.finish();
+    first.finish()?;
 
     let mut second = array.store_array();
     second.store("D");
     second.store("E");
-    second.finish();
+    second.finish()?;
 
-    array.finish();
+    array.finish()?;
 
     let sig = Signature::new_const(b"aas");
     assert_eq!(sig, buf.signature());
@@ -3,6 +3,7 @@ use crate::{BodyBuf, Signature};
 
 pub(crate) mod sealed {
     pub trait Sealed {}
+    pub trait DynSealed {}
 }
 
 /// Trait used for types which can be stored with a `store()` call.
@@ -30,6 +31,59 @@ pub trait Storable: self::sealed::Sealed {
     fn write_signature(builder: &mut SignatureBuilder) -> bool;
 }
 
+/// An object-safe variant of [`Storable`].
+///
+/// This is implemented for every [`Storable`] type which is [`Clone`], and
+/// makes it possible to build heterogeneous lists of values at runtime such as
+/// `Vec<Box<dyn StorableDyn>>`. Values are stored with
+/// [`BodyBuf::store_dyn`].
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{BodyBuf, StorableDyn, Variant};
+///
+/// let mut args: Vec<Box<dyn StorableDyn>> = Vec::new();
+/// args.push(Box::new(10u16));
+/// args.push(Box::new("Hello World"));
+/// args.push(Box::new(Variant::U32(42)));
+///
+/// let mut body = BodyBuf::new();
+///
+/// for arg in &args {
+///     body.store_dyn(&**arg)?;
+/// }
+///
+/// assert_eq!(body.signature(), "qsv");
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+pub trait StorableDyn: self::sealed::DynSealed {
+    /// Store a frame into a buffer body.
+    #[doc(hidden)]
+    fn store_to_dyn(&self, buf: &mut BodyBuf);
+
+    /// Write a signature.
+    #[doc(hidden)]
+    fn write_signature_dyn(&self, builder: &mut SignatureBuilder) -> bool;
+}
+
+impl<T> self::sealed::DynSealed for T where T: Storable + Clone {}
+
+impl<T> StorableDyn for T
+where
+    T: Storable + Clone,
+{
+    #[inline]
+    fn store_to_dyn(&self, buf: &mut BodyBuf) {
+        self.clone().store_to(buf);
+    }
+
+    #[inline]
+    fn write_signature_dyn(&self, builder: &mut SignatureBuilder) -> bool {
+        T::write_signature(builder)
+    }
+}
+
 impl self::sealed::Sealed for String {}
 
 /// [`Storable`] implementation for [`String`].
@@ -58,3 +112,34 @@ impl Storable for String {
         builder.extend_from_signature(Signature::STRING)
     }
 }
+
+impl self::sealed::Sealed for bool {}
+
+/// [`Storable`] implementation for [`bool`], which is stored as a 32-bit
+/// integer that is either `0` or `1`.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::BodyBuf;
+///
+/// let mut body = BodyBuf::new();
+///
+/// body.store(true)?;
+/// body.store(10u16)?;
+///
+/// assert_eq!(body.signature(), "bq");
+/// assert_eq!(body.as_body().decode::<(bool, u16)>()?, (true, 10));
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+impl Storable for bool {
+    #[inline]
+    fn store_to(self, buf: &mut BodyBuf) {
+        buf.store_frame(u32::from(self));
+    }
+
+    #[inline]
+    fn write_signature(builder: &mut SignatureBuilder) -> bool {
+        builder.extend_from_signature(Signature::BOOLEAN)
+    }
+}
@@ -0,0 +1,447 @@
+//! Helpers for asserting on bodies and messages in tests.
+//!
+//! This is only available with the `test-support` feature, which is intended
+//! to be enabled for dev-dependencies:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! tokio-dbus = { version = "*", features = ["test-support"] }
+//! ```
+//!
+//! The [`assert_body_eq!`] macro compares a body against a list of expected
+//! values, and [`MessageMatcher`] checks a message against a [`MatchRule`] and
+//! its signature.
+//!
+//! [`MatchRule`]: crate::MatchRule Both produce panic messages which describe the first
+//! difference found.
+//!
+//! [`assert_body_eq!`]: crate::assert_body_eq
+//!
+//! # Examples
+//!
+//! ```
+//! use tokio_dbus::{assert_body_eq, BodyBuf, ObjectPath, SendBuf, Signature};
+//! use tokio_dbus::test_support::MessageMatcher;
+//!
+//! const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+//!
+//! let mut send = SendBuf::new();
+//! let mut body = BodyBuf::new();
+//! body.store("se.tedro.Example")?;
+//! body.store(4u32)?;
+//!
+//! let m = send.method_call(PATH, "RequestName")
+//!     .with_interface("org.freedesktop.DBus")
+//!     .with_body(&body);
+//!
+//! MessageMatcher::method_call(PATH, "RequestName")
+//!     .with_interface("org.freedesktop.DBus")
+//!     .with_signature(Signature::new("su")?)
+//!     .assert(&m);
+//!
+//! assert_body_eq!(m.body(), "se.tedro.Example", 4u32);
+//! # Ok::<_, tokio_dbus::Error>(())
+//! ```
+
+use std::error;
+use std::fmt;
+use std::num::NonZeroU32;
+
+use crate::proto::MessageType;
+use crate::{AsBody, MatchRule, Message, ObjectPath, Signature, Value};
+
+/// Assert that a body contains exactly the given values.
+///
+/// The first argument is anything which implements [`AsBody`], like a
+/// [`Body`] or a reference to a [`BodyBuf`]. The remaining arguments are stored
+/// in order into a new [`BodyBuf`] through [`BodyBuf::store`], which is then
+/// compared to the body through [`compare_bodies`].
+///
+/// [`AsBody`]: crate::AsBody
+/// [`Body`]: crate::Body
+/// [`BodyBuf`]: crate::BodyBuf
+/// [`BodyBuf::store`]: crate::BodyBuf::store
+/// [`compare_bodies`]: crate::test_support::compare_bodies
+///
+/// # Panics
+///
+/// Panics if the signature or any of the values in the body differ from the
+/// expected ones.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{assert_body_eq, BodyBuf};
+///
+/// let mut buf = BodyBuf::new();
+/// buf.store(42u32)?;
+/// buf.store("Hello World")?;
+///
+/// assert_body_eq!(&buf, 42u32, "Hello World");
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+///
+/// ```should_panic
+/// use tokio_dbus::{assert_body_eq, BodyBuf};
+///
+/// let mut buf = BodyBuf::new();
+/// buf.store(42u32)?;
+///
+/// assert_body_eq!(&buf, 43u32);
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+#[macro_export]
+macro_rules! assert_body_eq {
+    ($body:expr $(, $value:expr)* $(,)?) => {{
+        let mut expected = $crate::BodyBuf::new();
+
+        $(
+            if let Err(error) = expected.store($value) {
+                panic!("Failed to store expected value `{}`: {}", stringify!($value), error);
+            }
+        )*
+
+        if let Err(mismatch) = $crate::test_support::compare_bodies($body, &expected) {
+            panic!("{}", mismatch);
+        }
+    }};
+}
+
+/// Compare the remaining contents of two bodies.
+///
+/// Bodies are considered equal if they have the same [remaining signature] and
+/// decode to the same values. Since values are compared after decoding, bodies with
+/// different endianness can be equal.
+///
+/// # Errors
+///
+/// Errors with a [`Mismatch`] describing the first difference found, including
+/// the position of the differing value in the signature.
+///
+/// [remaining signature]: crate::Body::remaining_signature
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::BodyBuf;
+/// use tokio_dbus::test_support::compare_bodies;
+///
+/// let mut a = BodyBuf::new();
+/// a.store(42u32)?;
+/// a.store("Hello")?;
+///
+/// let mut b = BodyBuf::new();
+/// b.store(42u32)?;
+/// b.store("World")?;
+///
+/// let mismatch = compare_bodies(&a, &b).unwrap_err();
+///
+/// assert_eq!(
+///     mismatch.to_string(),
+///     "Body differs at argument 1 with signature `s` (offset 1 in \"us\")\n  actual: String(\"Hello\")\nexpected: String(\"World\")"
+/// );
+///
+/// // Only the values which haven't been read are compared.
+/// let mut body = a.as_body();
+/// assert_eq!(body.load::<u32>()?, 42);
+///
+/// let mut c = BodyBuf::new();
+/// c.store("Hello")?;
+/// assert!(compare_bodies(body, &c).is_ok());
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+pub fn compare_bodies<'a, 'b>(
+    actual: impl AsBody<'a>,
+    expected: impl AsBody<'b>,
+) -> Result<(), Mismatch> {
+    let mut actual = actual.as_body();
+    let mut expected = expected.as_body();
+
+    let signature = actual.remaining_signature();
+
+    if signature != expected.remaining_signature() {
+        return Err(Mismatch::new(format!(
+            "Body signature differs\n  actual: {:?}\nexpected: {:?}",
+            signature,
+            expected.remaining_signature()
+        )));
+    }
+
+    let actual_values = match Value::load_body(&mut actual) {
+        Ok(values) => values,
+        Err(error) => {
+            return Err(Mismatch::new(format!(
+                "Failed to decode actual body with signature {signature:?}: {error}"
+            )))
+        }
+    };
+
+    let expected_values = match Value::load_body(&mut expected) {
+        Ok(values) => values,
+        Err(error) => {
+            return Err(Mismatch::new(format!(
+                "Failed to decode expected body with signature {signature:?}: {error}"
+            )))
+        }
+    };
+
+    let mut rest = signature;
+
+    for (index, (a, b)) in actual_values.iter().zip(&expected_values).enumerate() {
+        let Some((ty, tail)) = rest.split_first_complete_type() else {
+            break;
+        };
+
+        if a != b {
+            let offset = signature.len() - rest.len();
+
+            return Err(Mismatch::new(format!(
+                "Body differs at argument {index} with signature `{}` (offset {offset} in {signature:?})\n  actual: {a:?}\nexpected: {b:?}",
+                ty.as_str()
+            )));
+        }
+
+        rest = tail;
+    }
+
+    Ok(())
+}
+
+/// Matches messages against a [`MatchRule`], and by their error name, reply
+/// serial and signature.
+///
+/// Only the parts of the message which have been specified are checked. The
+/// rule is checked through [`MatchRule::matches`].
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{ObjectPath, SendBuf};
+/// use tokio_dbus::test_support::MessageMatcher;
+///
+/// const PATH: &ObjectPath = ObjectPath::new_const(b"/org/freedesktop/DBus");
+///
+/// let mut send = SendBuf::new();
+///
+/// let m = send.signal("NameAcquired")
+///     .with_path(PATH)
+///     .with_interface("org.freedesktop.DBus");
+///
+/// assert!(MessageMatcher::signal("NameAcquired").with_path(PATH).matches(&m));
+/// assert!(!MessageMatcher::signal("NameLost").matches(&m));
+///
+/// let mismatch = MessageMatcher::method_call(PATH, "NameAcquired").check(&m).unwrap_err();
+/// assert_eq!(
+///     mismatch.to_string(),
+///     "Message doesn't match `type='method_call',path='/org/freedesktop/DBus',member='NameAcquired'`\n  actual: signal serial=1 path=/org/freedesktop/DBus interface=org.freedesktop.DBus member=NameAcquired body=0"
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MessageMatcher<'a> {
+    rule: MatchRule<'a>,
+    error_name: Option<&'a str>,
+    reply_serial: Option<NonZeroU32>,
+    signature: Option<&'a Signature>,
+}
+
+impl<'a> MessageMatcher<'a> {
+    /// Construct a matcher which matches any message.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Construct a matcher for a method call to the given path and member.
+    #[must_use]
+    pub fn method_call(path: &'a ObjectPath, member: &'a str) -> Self {
+        Self::new()
+            .with_message_type(MessageType::METHOD_CALL)
+            .with_path(path)
+            .with_member(member)
+    }
+
+    /// Construct a matcher for a method return to the given serial.
+    #[must_use]
+    pub fn method_return(reply_serial: NonZeroU32) -> Self {
+        Self::new()
+            .with_message_type(MessageType::METHOD_RETURN)
+            .with_reply_serial(reply_serial)
+    }
+
+    /// Construct a matcher for an error with the given name.
+    #[must_use]
+    pub fn error(error_name: &'a str) -> Self {
+        Self::new()
+            .with_message_type(MessageType::ERROR)
+            .with_error_name(error_name)
+    }
+
+    /// Construct a matcher for a signal with the given member.
+    #[must_use]
+    pub fn signal(member: &'a str) -> Self {
+        Self::new()
+            .with_message_type(MessageType::SIGNAL)
+            .with_member(member)
+    }
+
+    /// Construct a matcher which matches messages matching the given rule.
+    #[must_use]
+    pub fn from_rule(rule: MatchRule<'a>) -> Self {
+        Self {
+            rule,
+            ..Self::default()
+        }
+    }
+
+    /// Match the type of the message.
+    #[must_use]
+    pub fn with_message_type(self, message_type: MessageType) -> Self {
+        Self {
+            rule: self.rule.with_message_type(message_type),
+            ..self
+        }
+    }
+
+    /// Match the path of the message.
+    #[must_use]
+    pub fn with_path(self, path: &'a ObjectPath) -> Self {
+        Self {
+            rule: self.rule.with_path(path),
+            ..self
+        }
+    }
+
+    /// Match the interface of the message.
+    #[must_use]
+    pub fn with_interface(self, interface: &'a str) -> Self {
+        Self {
+            rule: self.rule.with_interface(interface),
+            ..self
+        }
+    }
+
+    /// Match the member of the message.
+    #[must_use]
+    pub fn with_member(self, member: &'a str) -> Self {
+        Self {
+            rule: self.rule.with_member(member),
+            ..self
+        }
+    }
+
+    /// Match the error name of the message.
+    #[must_use]
+    pub fn with_error_name(self, error_name: &'a str) -> Self {
+        Self {
+            error_name: Some(error_name),
+            ..self
+        }
+    }
+
+    /// Match the serial the message is a reply to.
+    #[must_use]
+    pub fn with_reply_serial(self, reply_serial: NonZeroU32) -> Self {
+        Self {
+            reply_serial: Some(reply_serial),
+            ..self
+        }
+    }
+
+    /// Match the signature of the body of the message.
+    #[must_use]
+    pub fn with_signature(self, signature: &'a Signature) -> Self {
+        Self {
+            signature: Some(signature),
+            ..self
+        }
+    }
+
+    /// Test if the message matches.
+    #[must_use]
+    pub fn matches(&self, message: &Message<'_>) -> bool {
+        self.check(message).is_ok()
+    }
+
+    /// Check the message against the matcher.
+    ///
+    /// # Errors
+    ///
+    /// Errors with a [`Mismatch`] describing the first part of the message
+    /// which differs.
+    pub fn check(&self, message: &Message<'_>) -> Result<(), Mismatch> {
+        if !self.rule.matches(message) {
+            return Err(Mismatch::new(format!(
+                "Message doesn't match `{}`\n  actual: {message}",
+                self.rule
+            )));
+        }
+
+        check_option("Error name", self.error_name, message.error_name())?;
+        check_option("Reply serial", self.reply_serial, message.reply_serial())?;
+        check("Signature", self.signature, message.signature())?;
+        Ok(())
+    }
+
+    /// Assert that the message matches.
+    ///
+    /// # Panics
+    ///
+    /// Panics with a description of the first part of the message which
+    /// differs.
+    #[track_caller]
+    pub fn assert(&self, message: &Message<'_>) {
+        if let Err(mismatch) = self.check(message) {
+            panic!("{mismatch}");
+        }
+    }
+}
+
+/// A difference found while comparing bodies or matching messages.
+///
+/// See [`compare_bodies`] and [`MessageMatcher::check`].
+#[derive(Debug)]
+pub struct Mismatch {
+    message: Box<str>,
+}
+
+impl Mismatch {
+    fn new(message: String) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Mismatch {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.message.fmt(f)
+    }
+}
+
+impl error::Error for Mismatch {}
+
+fn check<T>(what: &str, expected: Option<T>, actual: T) -> Result<(), Mismatch>
+where
+    T: fmt::Debug + PartialEq,
+{
+    match expected {
+        Some(expected) if expected != actual => Err(Mismatch::new(format!(
+            "{what} differs\n  actual: {actual:?}\nexpected: {expected:?}"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn check_option<T>(what: &str, expected: Option<T>, actual: Option<T>) -> Result<(), Mismatch>
+where
+    T: fmt::Debug + PartialEq,
+{
+    match expected {
+        Some(expected) if Some(&expected) != actual.as_ref() => Err(Mismatch::new(format!(
+            "{what} differs\n  actual: {actual:?}\nexpected: {:?}",
+            Some(expected)
+        ))),
+        _ => Ok(()),
+    }
+}
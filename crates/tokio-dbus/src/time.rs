@@ -0,0 +1,157 @@
+//! Conversions for [`Duration`] and [`SystemTime`].
+//!
+//! There is no dedicated type for time in D-Bus. These follow the convention
+//! used by systemd and logind, where both durations and points in time are
+//! encoded as a `u64` number of microseconds, with points in time being
+//! relative to the Unix epoch.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::Result;
+use crate::signature::SignatureBuilder;
+use crate::{Arguments, Body, BodyBuf, FromBody, Signature, SignatureError, Storable};
+
+/// Convert a duration into microseconds, saturating on overflow.
+#[inline]
+fn to_usec(duration: Duration) -> u64 {
+    u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
+}
+
+/// Convert a point in time into microseconds since the Unix epoch, where
+/// points in time before the epoch are clamped to it.
+#[inline]
+fn system_time_to_usec(time: SystemTime) -> u64 {
+    to_usec(time.duration_since(UNIX_EPOCH).unwrap_or_default())
+}
+
+impl crate::storable::sealed::Sealed for Duration {}
+
+/// [`Storable`] implementation for [`Duration`], which is stored as a `u64`
+/// number of microseconds.
+///
+/// Any sub-microsecond precision is truncated, and durations which don't fit
+/// are saturated to `u64::MAX`. This requires the `usec` feature.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use tokio_dbus::BodyBuf;
+///
+/// let mut body = BodyBuf::new();
+/// body.store(Duration::from_millis(1500))?;
+///
+/// assert_eq!(body.signature(), "t");
+/// assert_eq!(body.as_body().load::<u64>()?, 1_500_000);
+/// assert_eq!(body.as_body().decode::<Duration>()?, Duration::from_millis(1500));
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+impl Storable for Duration {
+    #[inline]
+    fn store_to(self, buf: &mut BodyBuf) {
+        buf.store_frame(to_usec(self));
+    }
+
+    #[inline]
+    fn write_signature(builder: &mut SignatureBuilder) -> bool {
+        builder.extend_from_signature(Signature::UINT64)
+    }
+}
+
+impl crate::arguments::sealed::Sealed for Duration {}
+
+impl Arguments for Duration {
+    #[inline]
+    fn extend_to(&self, buf: &mut BodyBuf) -> Result<()> {
+        buf.store(*self)
+    }
+
+    #[inline]
+    fn buf_to(&self, buf: &mut BodyBuf) {
+        buf.store_frame(to_usec(*self));
+    }
+}
+
+/// Decode a [`Duration`] from a `u64` number of microseconds.
+///
+/// This requires the `usec` feature.
+impl<'de> FromBody<'de> for Duration {
+    #[inline]
+    fn write_signature(signature: &mut SignatureBuilder) -> Result<(), SignatureError> {
+        <u64 as FromBody<'de>>::write_signature(signature)
+    }
+
+    #[inline]
+    fn from_body(body: &mut Body<'de>) -> Result<Self> {
+        Ok(Duration::from_micros(body.load()?))
+    }
+}
+
+impl crate::storable::sealed::Sealed for SystemTime {}
+
+/// [`Storable`] implementation for [`SystemTime`], which is stored as a `u64`
+/// number of microseconds since the Unix epoch.
+///
+/// This is the representation used by timestamp properties in systemd and
+/// logind, such as `TimeUSec` fields. Points in time before the epoch are
+/// stored as `0`, which these services use to indicate that a timestamp is
+/// unset. This requires the `usec` feature.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, SystemTime, UNIX_EPOCH};
+///
+/// use tokio_dbus::BodyBuf;
+///
+/// let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+///
+/// let mut body = BodyBuf::new();
+/// body.arguments((time, Duration::from_secs(5)))?;
+///
+/// assert_eq!(body.signature(), "tt");
+/// assert_eq!(body.as_body().decode::<(SystemTime, Duration)>()?, (time, Duration::from_secs(5)));
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+impl Storable for SystemTime {
+    #[inline]
+    fn store_to(self, buf: &mut BodyBuf) {
+        buf.store_frame(system_time_to_usec(self));
+    }
+
+    #[inline]
+    fn write_signature(builder: &mut SignatureBuilder) -> bool {
+        builder.extend_from_signature(Signature::UINT64)
+    }
+}
+
+impl crate::arguments::sealed::Sealed for SystemTime {}
+
+impl Arguments for SystemTime {
+    #[inline]
+    fn extend_to(&self, buf: &mut BodyBuf) -> Result<()> {
+        buf.store(*self)
+    }
+
+    #[inline]
+    fn buf_to(&self, buf: &mut BodyBuf) {
+        buf.store_frame(system_time_to_usec(*self));
+    }
+}
+
+/// Decode a [`SystemTime`] from a `u64` number of microseconds since the Unix
+/// epoch.
+///
+/// This requires the `usec` feature.
+impl<'de> FromBody<'de> for SystemTime {
+    #[inline]
+    fn write_signature(signature: &mut SignatureBuilder) -> Result<(), SignatureError> {
+        <u64 as FromBody<'de>>::write_signature(signature)
+    }
+
+    #[inline]
+    fn from_body(body: &mut Body<'de>) -> Result<Self> {
+        Ok(UNIX_EPOCH + Duration::from_micros(body.load()?))
+    }
+}
@@ -0,0 +1,99 @@
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
+
+use crate::error::Result;
+use crate::org_freedesktop_dbus::{FAILED, INVALID_ARGS};
+use crate::{BodyBuf, Error};
+
+/// Conversion of an error into a D-Bus error reply.
+///
+/// An error reply consists of an error name, and by convention a body whose
+/// first argument is a string describing the error. The description defaults
+/// to the [`Display`] implementation of the error, and the error name to
+/// `org.freedesktop.DBus.Error.Failed`.
+///
+/// This is implemented for [`Error`], [`std::io::Error`] and boxed standard
+/// errors, which are all mapped to `org.freedesktop.DBus.Error.Failed`. The
+/// exception is errors raised by [`Body::ensure_consumed`] when a message has
+/// trailing arguments, which are mapped to
+/// `org.freedesktop.DBus.Error.InvalidArgs`. Such replies can also be written
+/// automatically through [`Connection::serve_strict_until`] and
+/// [`ObjectRegistry::with_strict_arguments`]. Other error types, like
+/// `anyhow::Error`, can be converted into a boxed error first.
+///
+/// [`Body::ensure_consumed`]: crate::Body::ensure_consumed
+/// [`Connection::serve_strict_until`]: crate::Connection::serve_strict_until
+/// [`ObjectRegistry::with_strict_arguments`]: crate::ObjectRegistry::with_strict_arguments
+///
+/// See [`ReplyBuilder::write_result`].
+///
+/// [`Display`]: fmt::Display
+/// [`ReplyBuilder::write_result`]: crate::ReplyBuilder::write_result
+///
+/// # Examples
+///
+/// ```
+/// use std::fmt;
+///
+/// use tokio_dbus::{BodyBuf, Result, ToDbusError};
+///
+/// #[derive(Debug)]
+/// struct NoSuchUnit(u32);
+///
+/// impl fmt::Display for NoSuchUnit {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         write!(f, "No unit with id {}", self.0)
+///     }
+/// }
+///
+/// impl ToDbusError for NoSuchUnit {
+///     fn error_name(&self) -> &str {
+///         "se.tedro.Example.Error.NoSuchUnit"
+///     }
+///
+///     fn write_body(&self, body: &mut BodyBuf) -> Result<()> {
+///         body.arguments((self.to_string().as_str(), self.0))
+///     }
+/// }
+///
+/// let error = NoSuchUnit(42);
+///
+/// let mut body = BodyBuf::new();
+/// error.write_body(&mut body)?;
+///
+/// assert_eq!(error.error_name(), "se.tedro.Example.Error.NoSuchUnit");
+/// assert_eq!(body.signature(), "su");
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+pub trait ToDbusError: fmt::Display {
+    /// The name of the error, which defaults to
+    /// `org.freedesktop.DBus.Error.Failed`.
+    fn error_name(&self) -> &str {
+        FAILED
+    }
+
+    /// Write the body of the error reply, which defaults to a single string
+    /// with the [`Display`] representation of the error.
+    ///
+    /// [`Display`]: fmt::Display
+    fn write_body(&self, body: &mut BodyBuf) -> Result<()> {
+        body.store(self.to_string().as_str())
+    }
+}
+
+impl ToDbusError for Error {
+    fn error_name(&self) -> &str {
+        if self.is_trailing_body() {
+            INVALID_ARGS
+        } else {
+            FAILED
+        }
+    }
+}
+
+impl ToDbusError for io::Error {}
+
+impl ToDbusError for Box<dyn StdError> {}
+
+impl ToDbusError for Box<dyn StdError + Send + Sync> {}
@@ -21,6 +21,10 @@ pub trait Fields: self::sealed::Sealed + Marker {
     /// The next struct fields to write.
     #[doc(hidden)]
     type Remaining;
+
+    /// The number of fields.
+    #[doc(hidden)]
+    const LEN: usize;
 }
 
 impl self::sealed::Sealed for () {}
@@ -50,6 +54,7 @@ impl Marker for () {
 impl Fields for () {
     type First = Empty;
     type Remaining = ();
+    const LEN: usize = 0;
 }
 
 macro_rules! struct_fields {
@@ -87,6 +92,7 @@ macro_rules! struct_fields {
 
             #[inline]
             fn load_struct<'de>(buf: &mut Body<'de>) -> Result<Self::Return<'de>> {
+                buf.align::<u64>()?;
                 Ok((<$first>::load_struct(buf)?, $(<$rest>::load_struct(buf)? ,)*))
             }
 
@@ -107,6 +113,7 @@ macro_rules! struct_fields {
         {
             type First = A;
             type Remaining = ($($rest,)*);
+            const LEN: usize = 1 + <Self::Remaining as Fields>::LEN;
         }
     }
 }
@@ -12,6 +12,11 @@ pub trait Marker: self::sealed::Sealed + Aligned {
     #[doc(hidden)]
     type Return<'de>;
 
+    /// The fixed size of the marker when it is stored in an array, if it has
+    /// one.
+    #[doc(hidden)]
+    const FIXED_SIZE: Option<usize> = None;
+
     /// Read the value from a structure.
     #[doc(hidden)]
     fn load_struct<'de>(buf: &mut Body<'de>) -> Result<Self::Return<'de>>;
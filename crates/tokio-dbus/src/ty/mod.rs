@@ -16,9 +16,10 @@
 //!         w.store(1u8);
 //!         w.store(2u8);
 //!         w.store(3u8);
-//!     })
+//!         Ok(())
+//!     })?
 //!     .store("Hello World")
-//!     .finish();
+//!     .finish()?;
 //!
 //! assert_eq!(buf.signature(), b"y(quays)");
 //! assert_eq!(buf.get(), &[10, 0, 0, 0, 0, 0, 0, 0, 10, 0, 0, 0, 10, 0, 0, 0, 3, 0, 0, 0, 1, 2, 3, 0, 11, 0, 0, 0, 72, 101, 108, 108, 111, 32, 87, 111, 114, 108, 100, 0]);
@@ -41,7 +42,41 @@ use std::marker::PhantomData;
 
 use crate::error::ErrorKind;
 use crate::signature::SignatureBuilder;
-use crate::{Body, Error, LoadArray, Result, SignatureError};
+use crate::{Body, Error, LoadArray, LoadDict, Result, SignatureBuf, SignatureError};
+
+/// Construct the signature of the type described by the [`Marker`] `T`.
+///
+/// This can be used to compare signatures received over the wire with those
+/// of types written through markers.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{ty, BodyBuf, Variant};
+///
+/// let mut body = BodyBuf::new();
+/// let mut dict = body.store_dict::<ty::Str, ty::Variant>()?;
+/// dict.store("urgency", Variant::U8(2));
+/// dict.finish()?;
+/// body.store(42u32)?;
+///
+/// let options = ty::signature_of::<ty::Dict<ty::Str, ty::Variant>>()?;
+/// assert_eq!(options.as_str(), "a{sv}");
+/// assert!(body.signature().starts_with(&options));
+///
+/// let pair = ty::signature_of::<(u8, ty::Str)>()?;
+/// assert_eq!(pair.as_str(), "(ys)");
+/// assert!(!body.signature().starts_with(&pair));
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+pub fn signature_of<T>() -> Result<SignatureBuf, SignatureError>
+where
+    T: ?Sized + Marker,
+{
+    let mut signature = SignatureBuilder::new();
+    T::write_signature(&mut signature)?;
+    Ok(signature.to_signature().to_owned())
+}
 
 /// The [`Marker`] for the [`str`] type.
 ///
@@ -58,7 +93,7 @@ use crate::{Body, Error, LoadArray, Result, SignatureError};
 /// buf.store_struct::<(u8, ty::Str)>()?
 ///     .store(42u8)
 ///     .store("Hello World!")
-///     .finish();
+///     .finish()?;
 ///
 /// assert_eq!(buf.signature(), b"(ys)");
 ///
@@ -90,7 +125,7 @@ impl_trait_unsized_marker!(Str, u32, str, STRING);
 /// buf.store_struct::<(u8, ty::Signature)>()?
 ///     .store(42u8)
 ///     .store(Signature::new("ay")?)
-///     .finish();
+///     .finish()?;
 ///
 /// assert_eq!(buf.signature(), b"(yg)");
 ///
@@ -122,7 +157,7 @@ impl_trait_unsized_marker!(Signature, u8, crate::Signature, SIGNATURE);
 /// buf.store_struct::<(u8, ty::ObjectPath)>()?
 ///     .store(42u8)
 ///     .store(ObjectPath::new("/se/tedro/DBusExample")?)
-///     .finish();
+///     .finish()?;
 ///
 /// assert_eq!(buf.signature(), b"(yo)");
 ///
@@ -137,7 +172,7 @@ impl_trait_unsized_marker!(Signature, u8, crate::Signature, SIGNATURE);
 #[non_exhaustive]
 pub struct ObjectPath;
 
-impl_trait_unsized_marker!(ObjectPath, u8, crate::ObjectPath, OBJECT_PATH);
+impl_trait_unsized_marker!(ObjectPath, u32, crate::ObjectPath, OBJECT_PATH);
 
 /// The [`Marker`] for an array type, like `[u8]`.
 ///
@@ -154,8 +189,9 @@ impl_trait_unsized_marker!(ObjectPath, u8, crate::ObjectPath, OBJECT_PATH);
 ///     .store_array(|w| {
 ///         w.store("Hello");
 ///         w.store("World");
-///     })
-///     .finish();
+///         Ok(())
+///     })?
+///     .finish()?;
 ///
 /// assert_eq!(buf.signature(), b"(yas)");
 ///
@@ -177,7 +213,7 @@ impl<T> Aligned for Array<T>
 where
     T: Aligned,
 {
-    type Alignment = T;
+    type Alignment = u32;
 }
 
 impl<T> self::marker::sealed::Sealed for Array<T> where T: Marker {}
@@ -202,6 +238,134 @@ where
     }
 }
 
+/// The [`Marker`] for a dictionary, which is an array of dict entries with keys
+/// of type `K` and values of type `V`.
+///
+/// The key must be a basic type, such as a number or a string.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{BodyBuf, Variant};
+/// use tokio_dbus::ty;
+///
+/// let mut buf = BodyBuf::new();
+///
+/// buf.store_struct::<(u8, ty::Dict<ty::Str, ty::Variant>)>()?
+///     .store(42u8)
+///     .store_dict(|w| {
+///         w.store("urgency", Variant::U8(2));
+///         w.store("category", Variant::String("im"));
+///         Ok(())
+///     })?
+///     .finish()?;
+///
+/// assert_eq!(buf.signature(), b"(ya{sv})");
+///
+/// let mut b = buf.as_body();
+///
+/// let (n, mut dict) = b.load_struct::<(u8, ty::Dict<ty::Str, ty::Variant>)>()?;
+///
+/// assert_eq!(n, 42u8);
+/// assert_eq!(dict.load_entry()?, Some(("urgency", Variant::U8(2))));
+/// assert_eq!(dict.load_entry()?, Some(("category", Variant::String("im"))));
+/// assert_eq!(dict.load_entry()?, None);
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+pub struct Dict<K, V>(PhantomData<(K, V)>);
+
+impl<K, V> self::aligned::sealed::Sealed for Dict<K, V> {}
+
+impl<K, V> Aligned for Dict<K, V> {
+    type Alignment = u32;
+}
+
+impl<K, V> self::marker::sealed::Sealed for Dict<K, V>
+where
+    K: Marker,
+    V: Marker,
+{
+}
+
+impl<K, V> Marker for Dict<K, V>
+where
+    K: Marker,
+    V: Marker,
+{
+    type Return<'de> = LoadDict<'de, K, V>;
+
+    #[inline]
+    fn load_struct<'de>(buf: &mut Body<'de>) -> Result<Self::Return<'de>> {
+        buf.load_dict::<K, V>()
+    }
+
+    #[inline]
+    fn write_signature(signature: &mut SignatureBuilder) -> Result<(), SignatureError> {
+        signature.open_array()?;
+        signature.open_dict()?;
+        K::write_signature(signature)?;
+        V::write_signature(signature)?;
+        signature.close_dict()?;
+        signature.close_array();
+        Ok(())
+    }
+}
+
+/// The [`Marker`] for booleans, which are stored as [`bool`].
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{ty, BodyBuf};
+///
+/// let mut buf = BodyBuf::new();
+///
+/// buf.store_struct::<(ty::Bool, u8)>()?
+///     .store(true)
+///     .store(42u8)
+///     .finish()?;
+///
+/// assert_eq!(buf.signature(), b"(by)");
+///
+/// let mut b = buf.as_body();
+/// assert_eq!(b.load_struct::<(ty::Bool, u8)>()?, (true, 42u8));
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+#[non_exhaustive]
+pub struct Bool;
+
+impl self::aligned::sealed::Sealed for Bool {}
+
+impl Aligned for Bool {
+    type Alignment = u32;
+}
+
+impl self::marker::sealed::Sealed for Bool {}
+
+impl Marker for Bool {
+    type Return<'de> = bool;
+
+    const FIXED_SIZE: Option<usize> = Some(4);
+
+    #[inline]
+    fn load_struct<'de>(buf: &mut Body<'de>) -> Result<Self::Return<'de>> {
+        match buf.load::<u32>()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            n => Err(Error::new(ErrorKind::InvalidBoolean(n))),
+        }
+    }
+
+    #[inline]
+    fn write_signature(signature: &mut SignatureBuilder) -> Result<(), SignatureError> {
+        if !signature.extend_from_signature(crate::Signature::BOOLEAN) {
+            return Err(SignatureError::too_long());
+        }
+
+        Ok(())
+    }
+}
+
 /// The [`Marker`] for the [`Variant`] type.
 ///
 /// [`Variant`]: crate::Variant
@@ -211,7 +375,7 @@ pub struct Variant;
 impl self::aligned::sealed::Sealed for Variant {}
 
 impl Aligned for Variant {
-    type Alignment = u32;
+    type Alignment = u8;
 }
 
 impl self::marker::sealed::Sealed for Variant {}
@@ -224,8 +388,22 @@ impl Marker for Variant {
         let signature: &crate::Signature = buf.read()?;
 
         let variant = match signature.as_bytes() {
-            b"s" => crate::Variant::String(buf.read()?),
+            b"y" => crate::Variant::U8(buf.load()?),
+            b"b" => match buf.load::<u32>()? {
+                0 => crate::Variant::Bool(false),
+                1 => crate::Variant::Bool(true),
+                n => return Err(Error::new(ErrorKind::InvalidBoolean(n))),
+            },
+            b"n" => crate::Variant::I16(buf.load()?),
+            b"q" => crate::Variant::U16(buf.load()?),
+            b"i" => crate::Variant::I32(buf.load()?),
             b"u" => crate::Variant::U32(buf.load()?),
+            b"x" => crate::Variant::I64(buf.load()?),
+            b"t" => crate::Variant::U64(buf.load()?),
+            b"d" => crate::Variant::F64(buf.load()?),
+            b"s" => crate::Variant::String(buf.read()?),
+            b"o" => crate::Variant::ObjectPath(buf.read()?),
+            b"g" => crate::Variant::Signature(buf.read()?),
             _ => {
                 return Err(Error::new(ErrorKind::UnsupportedVariant(signature.into())));
             }
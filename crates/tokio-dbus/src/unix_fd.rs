@@ -0,0 +1,152 @@
+use std::fmt;
+
+use crate::proto::Endianness;
+use crate::signature::{SignatureBuilder, SignatureError};
+use crate::{Body, BodyBuf, Frame, Result, Signature};
+
+/// A unix file descriptor stored in a body.
+///
+/// On the wire a file descriptor is represented by an index into the list of
+/// file descriptors which are attached to the message out of band. The number
+/// of file descriptors a body refers to is tracked by [`BodyBuf::unix_fds`],
+/// and is used to populate the [`UNIX_FDS`] header field when the message is
+/// written.
+///
+/// This type acts as its own [`Marker`], so it can be used symmetrically when
+/// storing and loading values.
+///
+/// [`UNIX_FDS`]: crate::proto::Variant::UNIX_FDS
+/// [`Marker`]: crate::ty::Marker
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::{BodyBuf, UnixFd};
+///
+/// let mut body = BodyBuf::new();
+/// body.store(UnixFd::new(0))?;
+/// body.store_struct::<(u8, UnixFd)>()?
+///     .store(1u8)
+///     .store(UnixFd::new(1))
+///     .finish()?;
+///
+/// assert_eq!(body.signature(), "h(yh)");
+/// assert_eq!(body.unix_fds(), 2);
+///
+/// let mut b = body.as_body();
+/// assert_eq!(b.unix_fds(), 2);
+/// assert_eq!(b.load::<UnixFd>()?, UnixFd::new(0));
+/// assert_eq!(b.load_struct::<(u8, UnixFd)>()?, (1, UnixFd::new(1)));
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct UnixFd(u32);
+
+impl UnixFd {
+    /// Construct a file descriptor referring to the given index in the list of
+    /// file descriptors attached to a message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::UnixFd;
+    ///
+    /// let fd = UnixFd::new(3);
+    /// assert_eq!(fd.index(), 3);
+    /// ```
+    #[inline]
+    pub const fn new(index: u32) -> Self {
+        Self(index)
+    }
+
+    /// Get the index of the file descriptor in the list of file descriptors
+    /// attached to a message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::UnixFd;
+    ///
+    /// let fd = UnixFd::new(3);
+    /// assert_eq!(fd.index(), 3);
+    /// ```
+    #[inline]
+    pub const fn index(self) -> u32 {
+        self.0
+    }
+}
+
+impl fmt::Debug for UnixFd {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("UnixFd").field(&self.0).finish()
+    }
+}
+
+impl crate::frame::sealed::Sealed for UnixFd {}
+
+unsafe impl Frame for UnixFd {
+    const SIGNATURE: &'static Signature = Signature::UNIX_FD;
+
+    #[inline]
+    fn adjust(&mut self, endianness: Endianness) {
+        self.0.adjust(endianness);
+    }
+}
+
+impl crate::ty::aligned::sealed::Sealed for UnixFd {}
+
+impl crate::ty::Aligned for UnixFd {
+    type Alignment = u32;
+}
+
+impl crate::ty::marker::sealed::Sealed for UnixFd {}
+
+impl crate::ty::Marker for UnixFd {
+    type Return<'de> = UnixFd;
+
+    const FIXED_SIZE: Option<usize> = Some(4);
+
+    #[inline]
+    fn load_struct<'de>(buf: &mut Body<'de>) -> Result<Self::Return<'de>> {
+        buf.load()
+    }
+
+    #[inline]
+    fn write_signature(signature: &mut SignatureBuilder) -> Result<(), SignatureError> {
+        if !signature.extend_from_signature(Signature::UNIX_FD) {
+            return Err(SignatureError::too_long());
+        }
+
+        Ok(())
+    }
+}
+
+impl crate::storable::sealed::Sealed for UnixFd {}
+
+impl crate::storable::Storable for UnixFd {
+    #[inline]
+    fn store_to(self, buf: &mut BodyBuf) {
+        buf.store_unix_fd(self);
+    }
+
+    #[inline]
+    fn write_signature(signature: &mut SignatureBuilder) -> bool {
+        signature.extend_from_signature(Signature::UNIX_FD)
+    }
+}
+
+impl crate::arguments::sealed::Sealed for UnixFd {}
+
+impl crate::arguments::Arguments for UnixFd {
+    #[inline]
+    fn extend_to(&self, buf: &mut BodyBuf) -> Result<()> {
+        buf.store(*self)
+    }
+
+    #[inline]
+    fn buf_to(&self, buf: &mut BodyBuf) {
+        buf.store_unix_fd(*self);
+    }
+}
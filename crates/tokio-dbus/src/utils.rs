@@ -1,4 +1,14 @@
+use std::io;
+
+/// The clock used to timestamp messages. It's provided by the browser on
+/// `wasm32-unknown-unknown`, where the standard library has no clock.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+pub(crate) use std::time::Instant;
+#[cfg(all(target_family = "wasm", target_os = "unknown"))]
+pub(crate) use web_time::Instant;
+
 /// Trim whitespace from end of bytes.
+#[cfg(feature = "tokio")]
 pub(crate) fn trim_end(mut bytes: &[u8]) -> &[u8] {
     while let [prefix @ .., c] = bytes {
         if !c.is_ascii_whitespace() {
@@ -12,8 +22,41 @@ pub(crate) fn trim_end(mut bytes: &[u8]) -> &[u8] {
 }
 
 /// Split once at the given byte.
+#[cfg(feature = "tokio")]
 pub(crate) fn split_once(bytes: &[u8], byte: u8) -> Option<(&[u8], &[u8])> {
     let n = bytes.iter().position(|&c| c == byte)?;
     let (head, tail) = bytes.split_at(n);
     Some((head, &tail[1..]))
 }
+
+/// Write some of the given bytes, retrying if interrupted.
+pub(crate) fn write_some<W>(writer: &mut W, bytes: &[u8]) -> io::Result<usize>
+where
+    W: ?Sized + io::Write,
+{
+    loop {
+        match writer.write(bytes) {
+            Ok(0) => return Err(io::Error::from(io::ErrorKind::WriteZero)),
+            Ok(n) => return Ok(n),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Read some bytes into the given buffer, retrying if interrupted.
+///
+/// Reaching the end of the input is reported as an error.
+pub(crate) fn read_some<R>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize>
+where
+    R: ?Sized + io::Read,
+{
+    loop {
+        match reader.read(buf) {
+            Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+            Ok(n) => return Ok(n),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
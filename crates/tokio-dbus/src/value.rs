@@ -0,0 +1,305 @@
+use tokio_dbus_core::signature::Type as SignatureType;
+
+use crate::buf::MAX_ARRAY_LENGTH;
+use crate::error::{ErrorKind, Result};
+use crate::limits::enter;
+use crate::proto::Type;
+use crate::signature::SignatureBuilder;
+use crate::{Body, Error, FromBody, ObjectPath, Signature, SignatureError};
+
+/// An owned value of any D-Bus type.
+///
+/// Unlike [`Variant`], which only holds basic types, this can represent
+/// containers like arrays, structs and dictionaries. This makes it suitable for
+/// decoding properties whose type varies, such as the settings and device
+/// properties exposed by NetworkManager and BlueZ.
+///
+/// Variants are flattened into the value they contain.
+///
+/// When decoded through [`FromBody`], a value corresponds to a variant `v`,
+/// which is the only D-Bus type able to hold a value of any type. Combined with
+/// the [`FromBody`] implementations for [`Vec`] and [`HashMap`] this allows
+/// shapes like `a{sa{sv}}` and `aa{sv}` to be decoded into nested collections.
+///
+/// [`Variant`]: crate::Variant
+/// [`HashMap`]: std::collections::HashMap
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use tokio_dbus::{BodyBuf, Signature, Value};
+///
+/// let mut buf = BodyBuf::new();
+///
+/// buf.store_parsed(
+///     Signature::new("a{sa{sv}}")?,
+///     &["{connection: {id: s:Home, autoconnect: b:true}, ipv4: {dns: au:[16843009]}}"],
+/// )?;
+///
+/// let settings = buf.as_body().decode::<HashMap<String, HashMap<String, Value>>>()?;
+/// let connection = &settings["connection"];
+/// assert_eq!(connection["id"].as_str(), Some("Home"));
+/// assert_eq!(connection["autoconnect"], Value::Bool(true));
+///
+/// let dns = settings["ipv4"]["dns"].as_array().unwrap_or_default();
+/// assert_eq!(dns, [Value::U32(16843009)]);
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Value {
+    /// A byte.
+    U8(u8),
+    /// A boolean.
+    Bool(bool),
+    /// An i16.
+    I16(i16),
+    /// A u16.
+    U16(u16),
+    /// An i32.
+    I32(i32),
+    /// A u32.
+    U32(u32),
+    /// An i64.
+    I64(i64),
+    /// A u64.
+    U64(u64),
+    /// A double.
+    F64(f64),
+    /// A string.
+    String(String),
+    /// An object path.
+    ObjectPath(Box<ObjectPath>),
+    /// A signature.
+    Signature(Box<Signature>),
+    /// The index of a unix file descriptor.
+    UnixFd(u32),
+    /// An array of values.
+    Array(Vec<Value>),
+    /// A struct, with one value for each field.
+    Struct(Vec<Value>),
+    /// An array of dict entries, in the order they were decoded.
+    Dict(Vec<(Value, Value)>),
+}
+
+impl Value {
+    /// Get the value as a string, if it is one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::Value;
+    ///
+    /// assert_eq!(Value::String(String::from("Home")).as_str(), Some("Home"));
+    /// assert_eq!(Value::U32(42).as_str(), None);
+    /// ```
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// Get the elements of the value, if it is an array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::Value;
+    ///
+    /// let value = Value::Array(vec![Value::U8(1), Value::U8(2)]);
+    /// assert_eq!(value.as_array(), Some(&[Value::U8(1), Value::U8(2)][..]));
+    /// assert_eq!(Value::U8(1).as_array(), None);
+    /// ```
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Get the entries of the value, if it is a dictionary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::Value;
+    ///
+    /// let value = Value::Dict(vec![(Value::String(String::from("id")), Value::U32(1))]);
+    ///
+    /// let entries = value.as_dict().unwrap_or_default();
+    /// assert_eq!(entries.len(), 1);
+    /// assert_eq!(entries[0].0.as_str(), Some("id"));
+    /// ```
+    pub fn as_dict(&self) -> Option<&[(Value, Value)]> {
+        match self {
+            Value::Dict(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// Get the value for the given string key, if the value is a dictionary
+    /// with string keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::Value;
+    ///
+    /// let value = Value::Dict(vec![(Value::String(String::from("id")), Value::U32(1))]);
+    ///
+    /// assert_eq!(value.get("id"), Some(&Value::U32(1)));
+    /// assert_eq!(value.get("uuid"), None);
+    /// ```
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        let entries = self.as_dict()?;
+        let (_, value) = entries.iter().find(|(k, _)| k.as_str() == Some(key))?;
+        Some(value)
+    }
+
+    /// Load one value for each complete type in the signature of the body.
+    #[cfg(any(
+        all(feature = "tokio", feature = "introspect"),
+        feature = "test-support"
+    ))]
+    pub(crate) fn load_body(body: &mut Body<'_>) -> Result<Vec<Self>> {
+        let mut values = Vec::new();
+
+        for ty in body.remaining_signature().iter() {
+            values.push(Self::load(body, ty, 0)?);
+        }
+
+        Ok(values)
+    }
+
+    /// Load a value of the given type.
+    fn load(body: &mut Body<'_>, ty: SignatureType<'_>, depth: usize) -> Result<Self> {
+        let value = match ty {
+            SignatureType::Signature(signature) => {
+                let b = signature.as_bytes().first().copied().unwrap_or_default();
+                Self::load_basic(body, Type::new(b), depth)?
+            }
+            SignatureType::Array(element) => {
+                let depth = enter(depth)?;
+                let bytes = body.load::<u32>()?;
+
+                if bytes > MAX_ARRAY_LENGTH {
+                    return Err(Error::new(ErrorKind::ArrayTooLong(bytes)));
+                }
+
+                // NB: Padding up until the first element is not included in
+                // the length of the array.
+                body.align_for(element)?;
+
+                if bytes as usize > body.len() {
+                    return Err(Error::new(ErrorKind::BufferUnderflow));
+                }
+
+                let mut array = body.read_until(bytes as usize);
+
+                if let Some(SignatureType::Dict(key, value)) = element.iter().next() {
+                    let mut entries = Vec::new();
+
+                    while !array.is_empty() {
+                        array.align::<u64>()?;
+                        let key = Self::load_all(&mut array, key, depth)?;
+                        let value = Self::load_all(&mut array, value, depth)?;
+                        entries.push((key, value));
+                    }
+
+                    Value::Dict(entries)
+                } else {
+                    let mut values = Vec::new();
+
+                    while !array.is_empty() {
+                        values.push(Self::load_all(&mut array, element, depth)?);
+                    }
+
+                    Value::Array(values)
+                }
+            }
+            SignatureType::Struct(fields) => {
+                let depth = enter(depth)?;
+                body.align::<u64>()?;
+
+                let mut values = Vec::new();
+
+                for ty in fields.iter() {
+                    values.push(Self::load(body, ty, depth)?);
+                }
+
+                Value::Struct(values)
+            }
+            SignatureType::Dict(..) => {
+                return Err(Error::new(ErrorKind::UnsupportedType(Type::OPEN_BRACE)));
+            }
+        };
+
+        Ok(value)
+    }
+
+    /// Load a value from a signature, which must consist of a single complete
+    /// type.
+    fn load_all(body: &mut Body<'_>, signature: &Signature, depth: usize) -> Result<Self> {
+        let mut it = signature.iter();
+
+        let (Some(ty), None) = (it.next(), it.next()) else {
+            return Err(Error::new(ErrorKind::Signature(
+                SignatureError::not_single_complete_type(),
+            )));
+        };
+
+        Self::load(body, ty, depth)
+    }
+
+    fn load_basic(body: &mut Body<'_>, ty: Type, depth: usize) -> Result<Self> {
+        let value = match ty {
+            Type::BYTE => Value::U8(body.load()?),
+            Type::BOOLEAN => match body.load::<u32>()? {
+                0 => Value::Bool(false),
+                1 => Value::Bool(true),
+                n => return Err(Error::new(ErrorKind::InvalidBoolean(n))),
+            },
+            Type::INT16 => Value::I16(body.load()?),
+            Type::UINT16 => Value::U16(body.load()?),
+            Type::INT32 => Value::I32(body.load()?),
+            Type::UINT32 => Value::U32(body.load()?),
+            Type::INT64 => Value::I64(body.load()?),
+            Type::UINT64 => Value::U64(body.load()?),
+            Type::DOUBLE => Value::F64(body.load()?),
+            Type::STRING => Value::String(body.read::<str>()?.to_owned()),
+            Type::OBJECT_PATH => Value::ObjectPath(body.read::<ObjectPath>()?.into()),
+            Type::SIGNATURE => Value::Signature(body.read::<Signature>()?.into()),
+            Type::UNIX_FD => Value::UnixFd(body.load()?),
+            Type::VARIANT => Self::load_variant(body, depth)?,
+            _ => return Err(Error::new(ErrorKind::UnsupportedType(ty))),
+        };
+
+        Ok(value)
+    }
+
+    fn load_variant(body: &mut Body<'_>, depth: usize) -> Result<Self> {
+        let depth = enter(depth)?;
+        let signature = body.read::<Signature>()?;
+        Self::load_all(body, signature, depth)
+    }
+}
+
+/// Decode a [`Value`] from a variant `v`.
+impl<'de> FromBody<'de> for Value {
+    #[inline]
+    fn write_signature(signature: &mut SignatureBuilder) -> Result<(), SignatureError> {
+        if !signature.extend_from_signature(Signature::VARIANT) {
+            return Err(SignatureError::too_long());
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn from_body(body: &mut Body<'de>) -> Result<Self> {
+        Self::load_variant(body, 0)
+    }
+}
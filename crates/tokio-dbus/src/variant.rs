@@ -1,33 +1,348 @@
-use crate::{signature::SignatureBuilder, BodyBuf, Signature, Storable};
+use crate::error::{Error, ErrorKind, Result};
+use crate::{signature::SignatureBuilder, BodyBuf, ObjectPath, Signature, Storable};
 
 /// A variant.
+///
+/// Values can be extracted through accessors like [`Variant::as_u32`], or
+/// through [`TryFrom`] conversions which apply the same rules. Integers are
+/// widened when it can be done losslessly, so that clients don't have to
+/// handle every numeric type a service might use for a value.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_dbus::Variant;
+///
+/// let properties = [("Volume", Variant::U8(42)), ("Name", Variant::String("Speaker"))];
+///
+/// for (name, value) in properties {
+///     match name {
+///         "Volume" => {
+///             let volume: u32 = value.try_into()?;
+///             assert_eq!(volume, 42);
+///         }
+///         "Name" => {
+///             assert_eq!(value.as_str(), Some("Speaker"));
+///         }
+///         _ => {}
+///     }
+/// }
+///
+/// assert!(u8::try_from(Variant::String("Speaker")).is_err());
+/// # Ok::<_, tokio_dbus::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Variant<'de> {
-    /// A string variant.
-    String(&'de str),
+    /// A byte variant.
+    U8(u8),
+    /// A boolean variant.
+    Bool(bool),
+    /// An i16 variant.
+    I16(i16),
+    /// A u16 variant.
+    U16(u16),
+    /// An i32 variant.
+    I32(i32),
     /// A u32 variant.
     U32(u32),
+    /// An i64 variant.
+    I64(i64),
+    /// A u64 variant.
+    U64(u64),
+    /// A double variant.
+    F64(f64),
+    /// A string variant.
+    String(&'de str),
+    /// An object path variant.
+    ObjectPath(&'de ObjectPath),
     /// A stored signature.
     Signature(&'de Signature),
 }
 
+impl<'de> Variant<'de> {
+    /// Get the signature of the value in the variant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::{Signature, Variant};
+    ///
+    /// assert_eq!(Variant::U32(42).signature(), Signature::UINT32);
+    /// assert_eq!(Variant::String("foo").signature(), Signature::STRING);
+    /// ```
+    pub fn signature(&self) -> &'static Signature {
+        match self {
+            Variant::U8(..) => Signature::BYTE,
+            Variant::Bool(..) => Signature::BOOLEAN,
+            Variant::I16(..) => Signature::INT16,
+            Variant::U16(..) => Signature::UINT16,
+            Variant::I32(..) => Signature::INT32,
+            Variant::U32(..) => Signature::UINT32,
+            Variant::I64(..) => Signature::INT64,
+            Variant::U64(..) => Signature::UINT64,
+            Variant::F64(..) => Signature::DOUBLE,
+            Variant::String(..) => Signature::STRING,
+            Variant::ObjectPath(..) => Signature::OBJECT_PATH,
+            Variant::Signature(..) => Signature::SIGNATURE,
+        }
+    }
+
+    /// Get the value of a boolean variant.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Variant::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the value of a byte variant.
+    pub fn as_u8(&self) -> Option<u8> {
+        match *self {
+            Variant::U8(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the value of the variant as an `i16`, widening smaller integers.
+    pub fn as_i16(&self) -> Option<i16> {
+        match *self {
+            Variant::U8(value) => Some(value.into()),
+            Variant::I16(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the value of the variant as a `u16`, widening smaller unsigned
+    /// integers.
+    pub fn as_u16(&self) -> Option<u16> {
+        match *self {
+            Variant::U8(value) => Some(value.into()),
+            Variant::U16(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the value of the variant as an `i32`, widening smaller integers.
+    pub fn as_i32(&self) -> Option<i32> {
+        match *self {
+            Variant::U8(value) => Some(value.into()),
+            Variant::I16(value) => Some(value.into()),
+            Variant::U16(value) => Some(value.into()),
+            Variant::I32(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the value of the variant as a `u32`, widening smaller unsigned
+    /// integers.
+    ///
+    /// Signed integers are never converted, even if they are positive, since
+    /// that's not a lossless conversion for every value of their type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::Variant;
+    ///
+    /// assert_eq!(Variant::U8(42).as_u32(), Some(42));
+    /// assert_eq!(Variant::U32(42).as_u32(), Some(42));
+    /// assert_eq!(Variant::I32(42).as_u32(), None);
+    /// assert_eq!(Variant::U64(42).as_u32(), None);
+    /// ```
+    pub fn as_u32(&self) -> Option<u32> {
+        match *self {
+            Variant::U8(value) => Some(value.into()),
+            Variant::U16(value) => Some(value.into()),
+            Variant::U32(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the value of the variant as an `i64`, widening smaller integers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::Variant;
+    ///
+    /// assert_eq!(Variant::I16(-42).as_i64(), Some(-42));
+    /// assert_eq!(Variant::U32(u32::MAX).as_i64(), Some(u32::MAX.into()));
+    /// assert_eq!(Variant::U64(42).as_i64(), None);
+    /// ```
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Variant::U8(value) => Some(value.into()),
+            Variant::I16(value) => Some(value.into()),
+            Variant::U16(value) => Some(value.into()),
+            Variant::I32(value) => Some(value.into()),
+            Variant::U32(value) => Some(value.into()),
+            Variant::I64(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the value of the variant as a `u64`, widening smaller unsigned
+    /// integers.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Variant::U8(value) => Some(value.into()),
+            Variant::U16(value) => Some(value.into()),
+            Variant::U32(value) => Some(value.into()),
+            Variant::U64(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the value of the variant as an `f64`, converting integers of up to
+    /// 32 bits which can all be represented exactly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::Variant;
+    ///
+    /// assert_eq!(Variant::F64(0.5).as_f64(), Some(0.5));
+    /// assert_eq!(Variant::I32(-1).as_f64(), Some(-1.0));
+    /// assert_eq!(Variant::U64(1).as_f64(), None);
+    /// ```
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Variant::U8(value) => Some(value.into()),
+            Variant::I16(value) => Some(value.into()),
+            Variant::U16(value) => Some(value.into()),
+            Variant::I32(value) => Some(value.into()),
+            Variant::U32(value) => Some(value.into()),
+            Variant::F64(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the value of a string variant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_dbus::Variant;
+    ///
+    /// assert_eq!(Variant::String("foo").as_str(), Some("foo"));
+    /// assert_eq!(Variant::U32(42).as_str(), None);
+    /// ```
+    pub fn as_str(&self) -> Option<&'de str> {
+        match *self {
+            Variant::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the value of an object path variant.
+    pub fn as_object_path(&self) -> Option<&'de ObjectPath> {
+        match *self {
+            Variant::ObjectPath(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the value of a signature variant.
+    pub fn as_signature(&self) -> Option<&'de Signature> {
+        match *self {
+            Variant::Signature(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! impl_try_from {
+    ($($ty:ty, $method:ident, $signature:ident),* $(,)?) => {
+        $(
+            /// Convert a variant using the same lossless rules as
+            #[doc = concat!("[`Variant::", stringify!($method), "`].")]
+            impl<'de> TryFrom<Variant<'de>> for $ty {
+                type Error = Error;
+
+                #[inline]
+                fn try_from(variant: Variant<'de>) -> Result<Self> {
+                    match variant.$method() {
+                        Some(value) => Ok(value),
+                        None => Err(Error::new(ErrorKind::VariantMismatch(
+                            Signature::$signature,
+                            variant.signature(),
+                        ))),
+                    }
+                }
+            }
+        )*
+    }
+}
+
+impl_try_from! {
+    bool, as_bool, BOOLEAN,
+    u8, as_u8, BYTE,
+    i16, as_i16, INT16,
+    u16, as_u16, UINT16,
+    i32, as_i32, INT32,
+    u32, as_u32, UINT32,
+    i64, as_i64, INT64,
+    u64, as_u64, UINT64,
+    f64, as_f64, DOUBLE,
+    &'de str, as_str, STRING,
+    &'de ObjectPath, as_object_path, OBJECT_PATH,
+    &'de Signature, as_signature, SIGNATURE,
+}
+
 impl crate::storable::sealed::Sealed for Variant<'_> {}
 
 impl Storable for Variant<'_> {
     #[inline]
     fn store_to(self, buf: &mut BodyBuf) {
         match self {
+            Variant::U8(number) => {
+                buf.write_only(Signature::BYTE);
+                buf.store_frame(number);
+            }
+            Variant::Bool(value) => {
+                buf.write_only(Signature::BOOLEAN);
+                buf.store_frame(u32::from(value));
+            }
+            Variant::I16(number) => {
+                buf.write_only(Signature::INT16);
+                buf.store_frame(number);
+            }
+            Variant::U16(number) => {
+                buf.write_only(Signature::UINT16);
+                buf.store_frame(number);
+            }
+            Variant::I32(number) => {
+                buf.write_only(Signature::INT32);
+                buf.store_frame(number);
+            }
+            Variant::U32(number) => {
+                buf.write_only(Signature::UINT32);
+                buf.store_frame(number);
+            }
+            Variant::I64(number) => {
+                buf.write_only(Signature::INT64);
+                buf.store_frame(number);
+            }
+            Variant::U64(number) => {
+                buf.write_only(Signature::UINT64);
+                buf.store_frame(number);
+            }
+            Variant::F64(number) => {
+                buf.write_only(Signature::DOUBLE);
+                buf.store_frame(number);
+            }
             Variant::String(string) => {
                 buf.write_only(Signature::STRING);
                 buf.write_only(string);
             }
+            Variant::ObjectPath(path) => {
+                buf.write_only(Signature::OBJECT_PATH);
+                buf.write_only(path);
+            }
             Variant::Signature(signature) => {
                 buf.write_only(Signature::SIGNATURE);
                 buf.write_only(signature);
             }
-            Variant::U32(number) => {
-                buf.write_only(Signature::UINT32);
-                buf.store_frame(number);
-            }
         }
     }
 
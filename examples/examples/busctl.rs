@@ -0,0 +1,160 @@
+use anyhow::{bail, Context, Result};
+use tokio_dbus::{Connection, ObjectPath, Signature, Visitor};
+
+const USAGE: &str = "\
+Usage:
+    busctl list
+    busctl introspect <service> <object>
+    busctl call <service> <object> <interface> <method> [<signature> [<argument>...]]";
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let mut c = Connection::session_bus().await?;
+
+    match &args[..] {
+        ["list"] => {
+            let mut names = c.list_names().await?;
+            names.sort();
+
+            for name in names {
+                println!("{name}");
+            }
+        }
+        ["introspect", service, object] => {
+            let path = ObjectPath::new(object)?;
+            println!("{}", c.introspect(service, path).await?);
+        }
+        ["call", service, object, interface, method, rest @ ..] => {
+            let path = ObjectPath::new(object)?;
+
+            let (signature, arguments) = match rest {
+                [] => (Signature::EMPTY, &[][..]),
+                [signature, arguments @ ..] => (Signature::new(signature)?, arguments),
+            };
+
+            let reply = c
+                .call_parsed(service, path, interface, method, signature, arguments)
+                .await
+                .with_context(|| format!("calling {interface}.{method}"))?;
+
+            let mut printer = Printer::default();
+            reply.body().walk(reply.signature(), &mut printer)?;
+            println!("{} {}", reply.signature().as_str(), printer.out);
+        }
+        _ => bail!("{USAGE}"),
+    }
+
+    Ok(())
+}
+
+/// Prints values roughly the way `busctl` does.
+#[derive(Default)]
+struct Printer {
+    out: String,
+}
+
+impl Printer {
+    fn push(&mut self, value: impl std::fmt::Display) {
+        if !self.out.is_empty() && !self.out.ends_with(['{', '(']) {
+            self.out.push(' ');
+        }
+
+        self.out.push_str(&value.to_string());
+    }
+}
+
+impl Visitor<'_> for Printer {
+    fn visit_u8(&mut self, value: u8) -> tokio_dbus::Result<()> {
+        self.push(value);
+        Ok(())
+    }
+
+    fn visit_bool(&mut self, value: bool) -> tokio_dbus::Result<()> {
+        self.push(value);
+        Ok(())
+    }
+
+    fn visit_i16(&mut self, value: i16) -> tokio_dbus::Result<()> {
+        self.push(value);
+        Ok(())
+    }
+
+    fn visit_u16(&mut self, value: u16) -> tokio_dbus::Result<()> {
+        self.push(value);
+        Ok(())
+    }
+
+    fn visit_i32(&mut self, value: i32) -> tokio_dbus::Result<()> {
+        self.push(value);
+        Ok(())
+    }
+
+    fn visit_u32(&mut self, value: u32) -> tokio_dbus::Result<()> {
+        self.push(value);
+        Ok(())
+    }
+
+    fn visit_i64(&mut self, value: i64) -> tokio_dbus::Result<()> {
+        self.push(value);
+        Ok(())
+    }
+
+    fn visit_u64(&mut self, value: u64) -> tokio_dbus::Result<()> {
+        self.push(value);
+        Ok(())
+    }
+
+    fn visit_f64(&mut self, value: f64) -> tokio_dbus::Result<()> {
+        self.push(value);
+        Ok(())
+    }
+
+    fn visit_str(&mut self, value: &str) -> tokio_dbus::Result<()> {
+        self.push(format_args!("{value:?}"));
+        Ok(())
+    }
+
+    fn visit_object_path(&mut self, value: &ObjectPath) -> tokio_dbus::Result<()> {
+        self.push(format_args!("\"{value}\""));
+        Ok(())
+    }
+
+    fn visit_signature(&mut self, value: &Signature) -> tokio_dbus::Result<()> {
+        self.push(format_args!("\"{}\"", value.as_str()));
+        Ok(())
+    }
+
+    fn visit_unix_fd(&mut self, index: u32) -> tokio_dbus::Result<()> {
+        self.push(index);
+        Ok(())
+    }
+
+    fn enter_array(&mut self, element: &Signature) -> tokio_dbus::Result<()> {
+        self.push(format_args!("a{}", element.as_str()));
+        self.push("{");
+        Ok(())
+    }
+
+    fn leave_array(&mut self) -> tokio_dbus::Result<()> {
+        self.push("}");
+        Ok(())
+    }
+
+    fn enter_struct(&mut self, _: &Signature) -> tokio_dbus::Result<()> {
+        self.push("(");
+        Ok(())
+    }
+
+    fn leave_struct(&mut self) -> tokio_dbus::Result<()> {
+        self.push(")");
+        Ok(())
+    }
+
+    fn enter_variant(&mut self, signature: &Signature) -> tokio_dbus::Result<()> {
+        self.push(format_args!("{}", signature.as_str()));
+        Ok(())
+    }
+}
@@ -32,11 +32,8 @@ async fn main() -> Result<()> {
             MessageKind::MethodReturn { reply_serial } if reply_serial == serial => {
                 break message.body().load::<u32>()?;
             }
-            MessageKind::Error {
-                error_name,
-                reply_serial,
-            } if reply_serial == serial => {
-                bail!("{error_name}: {}", message.body().read::<str>()?)
+            MessageKind::Error { reply_serial, .. } if reply_serial == serial => {
+                bail!("{}", message.display_error().expect("error message"))
             }
             _ => {}
         }
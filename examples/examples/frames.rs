@@ -0,0 +1,134 @@
+//! An adapter which exchanges D-Bus messages as binary frames.
+//!
+//! This only uses the message and serialization types, which don't depend on a
+//! socket and build for `wasm32-unknown-unknown` with default features
+//! disabled. Frames are sent over channels, with a task playing the part of a
+//! bridge which would forward them to the bus.
+
+use std::io::Cursor;
+
+use anyhow::{bail, Result};
+use tokio::sync::mpsc;
+use tokio_dbus::{BodyBuf, Message, MessageKind, MessageRef, ObjectPath, RecvBuf, SendBuf};
+
+const INTERFACE: &str = "se.tedro.DBusExample.Pingable";
+const PATH: &ObjectPath = ObjectPath::new_const(b"/se/tedro/DBusExample");
+
+/// Sends and receives messages as binary frames.
+///
+/// Every frame contains one or more complete messages.
+struct FrameTransport {
+    send: SendBuf,
+    recv: RecvBuf,
+    outgoing: mpsc::UnboundedSender<Vec<u8>>,
+    incoming: mpsc::UnboundedReceiver<Vec<u8>>,
+    frame: Cursor<Vec<u8>>,
+}
+
+impl FrameTransport {
+    fn new(
+        outgoing: mpsc::UnboundedSender<Vec<u8>>,
+        incoming: mpsc::UnboundedReceiver<Vec<u8>>,
+    ) -> Self {
+        Self {
+            send: SendBuf::new(),
+            recv: RecvBuf::new(),
+            outgoing,
+            incoming,
+            frame: Cursor::new(Vec::new()),
+        }
+    }
+
+    /// Send every buffered message as a single frame.
+    fn flush(&mut self) -> Result<()> {
+        let mut frame = Vec::new();
+        self.send.write_to(&mut frame)?;
+
+        if !frame.is_empty() && self.outgoing.send(frame).is_err() {
+            bail!("Channel closed");
+        }
+
+        Ok(())
+    }
+
+    /// Receive the next message, waiting for a frame if necessary.
+    async fn next(&mut self) -> Result<MessageRef> {
+        while self.frame.position() == self.frame.get_ref().len() as u64 {
+            let Some(frame) = self.incoming.recv().await else {
+                bail!("Channel closed");
+            };
+
+            self.frame = Cursor::new(frame);
+        }
+
+        Ok(self.recv.read_from(&mut self.frame)?)
+    }
+}
+
+/// The bridge, which replies to pings.
+async fn bridge(mut transport: FrameTransport) -> Result<()> {
+    let mut body = BodyBuf::new();
+
+    loop {
+        let message_ref = transport.next().await?;
+        let message = transport.recv.read_message(&message_ref)?;
+
+        let MessageKind::MethodCall { path, member } = message.kind() else {
+            continue;
+        };
+
+        if path != PATH || message.interface() != Some(INTERFACE) || member != "Ping" {
+            continue;
+        }
+
+        let value = message.body().load::<u32>()?;
+
+        body.clear();
+        body.store(value)?;
+
+        let m = message
+            .method_return(transport.send.next_serial())
+            .with_body(&body);
+
+        transport.send.write_message(m)?;
+        transport.flush()?;
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let (to_bridge, from_client) = mpsc::unbounded_channel();
+    let (to_client, from_bridge) = mpsc::unbounded_channel();
+
+    tokio::spawn(bridge(FrameTransport::new(to_client, from_client)));
+
+    let mut client = FrameTransport::new(to_bridge, from_bridge);
+    let mut body = BodyBuf::new();
+
+    for n in 0..3u32 {
+        body.clear();
+        body.store(n)?;
+
+        let m = client
+            .send
+            .method_call(PATH, "Ping")
+            .with_interface(INTERFACE)
+            .with_body(&body);
+
+        let serial = m.serial();
+        client.send.write_message(m)?;
+        client.flush()?;
+
+        let message_ref = client.next().await?;
+        let reply: Message<'_> = client.recv.read_message(&message_ref)?;
+
+        match reply.kind() {
+            MessageKind::MethodReturn { reply_serial } if reply_serial == serial => {
+                println!("Pong: {}", reply.body().load::<u32>()?);
+            }
+            kind => bail!("Unexpected reply: {kind:?}"),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use anyhow::{bail, Result};
+use tokio_dbus::org_freedesktop_dbus::{DISCONNECTED, LOCAL_INTERFACE};
+use tokio_dbus::proto::Variant;
+use tokio_dbus::{Connection, MatchRule, MessageKind};
+
+const USAGE: &str = "\
+Usage:
+    monitor [--system] [<service>...]";
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let mut system = false;
+    let mut services = Vec::new();
+
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--system" => system = true,
+            _ if arg.starts_with('-') => bail!("{USAGE}"),
+            _ => services.push(arg),
+        }
+    }
+
+    let mut c = if system {
+        Connection::system_bus().await?
+    } else {
+        Connection::session_bus().await?
+    };
+
+    // Like `busctl monitor`, show messages both from and to each service.
+    let mut rules = Vec::new();
+
+    for service in &services {
+        rules.push(MatchRule::new().with_sender(service));
+        rules.push(MatchRule::new().with_destination(service));
+    }
+
+    c.become_monitor(&rules).await?;
+
+    loop {
+        c.wait().await?;
+        let message = c.last_message()?;
+
+        if let (
+            Some(LOCAL_INTERFACE),
+            MessageKind::Signal {
+                member: DISCONNECTED,
+                ..
+            },
+        ) = (message.interface(), message.kind())
+        {
+            break;
+        }
+
+        println!("‣ {message:#}");
+        println!("  flags: {:?}", message.flags());
+        println!("  endianness: {:?}", message.body().endianness());
+
+        // Print header fields which aren't covered by the summary above.
+        let message_ref = c.last_message_ref()?;
+
+        for field in c.header_fields(&message_ref)? {
+            let field = field?;
+
+            match field.code() {
+                Variant::PATH
+                | Variant::INTERFACE
+                | Variant::MEMBER
+                | Variant::ERROR_NAME
+                | Variant::REPLY_SERIAL
+                | Variant::DESTINATION
+                | Variant::SENDER
+                | Variant::SIGNATURE => {}
+                code => println!("  {code:?}: {}", field.value().display()),
+            }
+        }
+
+        if !message.body().is_empty() {
+            println!("  body:");
+
+            for line in message.body().display().to_string().lines() {
+                println!("    {line}");
+            }
+        }
+
+        println!();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use anyhow::Result;
+use tokio_dbus::org_freedesktop_notifications::{self, Notification, NotificationSignal};
+use tokio_dbus::{Connection, Variant};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let mut c = Connection::session_bus().await?;
+    c.add_match(org_freedesktop_notifications::MATCH_RULE)
+        .await?;
+
+    let hints = [
+        ("urgency", Variant::U8(1)),
+        ("category", Variant::String("im.received")),
+    ];
+
+    let n = Notification::new("Hello", "Sent from tokio-dbus")
+        .with_app_name("tokio-dbus")
+        .with_actions(&[("default", "Open")])
+        .with_hints(&hints);
+
+    let id = org_freedesktop_notifications::notify(&mut c, &n).await?;
+    println!("Sent notification {id}");
+
+    loop {
+        c.wait().await?;
+        let message = c.last_message()?;
+
+        match NotificationSignal::from_message(&message)? {
+            Some(NotificationSignal::ActionInvoked { id: n, action_key }) if n == id => {
+                println!("Action invoked: {action_key}");
+            }
+            Some(NotificationSignal::NotificationClosed { id: n, reason }) if n == id => {
+                println!("Closed: {reason:?}");
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}